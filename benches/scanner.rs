@@ -0,0 +1,115 @@
+//! Scanner throughput over a few representative inputs.
+//!
+//! Run with `cargo bench --bench scanner`; each corpus is tokenized several
+//! times and the best run is reported in MB/s. Multi-word plain scalars are
+//! then loaded both from a char iterator and with `load_from_str`, whose
+//! scanner slices scalars from the input instead of building them a
+//! character at a time.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+use yyaml::parser::StateMachine;
+use yyaml::scanner::Scanner;
+use yyaml::{TokenType, Yaml, YamlLoader};
+
+const RUNS: usize = 5;
+
+fn block_mappings() -> String {
+    let mut yaml = String::new();
+    for i in 0..20_000 {
+        yaml.push_str(&format!(
+            "key_{i}:\n  name: value number {i}\n  enabled: true\n  ratio: 0.{i}\n"
+        ));
+    }
+    yaml
+}
+
+fn flow_collections() -> String {
+    let mut yaml = String::from("[\n");
+    for i in 0..20_000 {
+        yaml.push_str(&format!("  {{id: {i}, tags: [a, b, c], label: \"item {i}\"}},\n"));
+    }
+    yaml.push(']');
+    yaml
+}
+
+fn long_scalars() -> String {
+    let mut yaml = String::new();
+    for i in 0..5_000 {
+        yaml.push_str(&format!(
+            "text_{i}: plain scalar with quite a few words in it # trailing comment\n\
+             quoted_{i}: 'single quoted text that runs on for a while'\n\
+             block_{i}: |\n    literal line one\n    literal line two\n"
+        ));
+    }
+    yaml
+}
+
+/// Multi-word plain scalars, whose separating spaces are held until more
+/// content follows
+fn plain_words() -> String {
+    let mut yaml = String::new();
+    for i in 0..20_000 {
+        yaml.push_str(&format!(
+            "- the quick brown fox {i} jumps over the lazy dog\n- {i}  two  spaces  apart\n"
+        ));
+    }
+    yaml
+}
+
+fn scan(input: &str) -> usize {
+    let mut scanner = Scanner::new(input.chars());
+    let mut tokens = 0;
+    while let Ok(token) = scanner.peek_token() {
+        tokens += 1;
+        scanner.skip();
+        if matches!(token.1, TokenType::StreamEnd) {
+            break;
+        }
+    }
+    tokens
+}
+
+/// Entries of a loaded document's root collection
+fn entries(doc: &Yaml) -> usize {
+    doc.as_vec()
+        .map(<[Yaml]>::len)
+        .or_else(|| doc.as_hash().map(|hash| hash.len()))
+        .unwrap_or(1)
+}
+
+fn load_chars(input: &str) -> usize {
+    let mut state_machine = StateMachine::new(input.chars());
+    let mut nodes = 0;
+    while let Ok(Some(doc)) = state_machine.parse_next_document() {
+        nodes += entries(&doc);
+    }
+    nodes
+}
+
+fn load_str(input: &str) -> usize {
+    let docs = YamlLoader::load_from_str(input).unwrap_or_default();
+    docs.iter().map(entries).sum()
+}
+
+fn bench(name: &str, input: &str, run: fn(&str) -> usize, unit: &str) {
+    let mut best = Duration::MAX;
+    let mut count = 0;
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        count = black_box(run(black_box(input)));
+        best = best.min(start.elapsed());
+    }
+    let mb_per_s = input.len() as f64 / best.as_secs_f64() / 1_000_000.0;
+    println!("{name:<24} {count:>8} {unit:<7}  {best:>10.2?}  {mb_per_s:>7.1} MB/s");
+}
+
+fn main() {
+    let plain_words = plain_words();
+    bench("block_mappings", &block_mappings(), scan, "tokens");
+    bench("flow_collections", &flow_collections(), scan, "tokens");
+    bench("long_scalars", &long_scalars(), scan, "tokens");
+    bench("plain_words", &plain_words, scan, "tokens");
+    bench("load plain_words chars", &plain_words, load_chars, "entries");
+    bench("load plain_words str", &plain_words, load_str, "entries");
+}
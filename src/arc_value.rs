@@ -0,0 +1,355 @@
+//! An immutable document that is cheap to clone and share across threads
+//!
+//! [`ArcValue`] mirrors [`Value`], but strings and collections sit behind an
+//! [`Arc`], so cloning a document or any node in it only bumps reference
+//! counts. Edits are copy-on-write: [`ArcValue::set`] and
+//! [`ArcValue::remove`] copy the collections along the edited path, one level
+//! at a time, and share every other node with the clones taken before the
+//! edit.
+//!
+//! ```rust
+//! use yyaml::{ArcValue, Value};
+//!
+//! let value: Value = yyaml::from_str("db: {host: a, port: 5432}\ncache: {ttl: 60}").unwrap();
+//! let base = ArcValue::from(&value);
+//! let mut edited = base.clone();
+//! edited.set("/db/host", ArcValue::from("b")).unwrap();
+//!
+//! assert_eq!(base.pointer("/db/host").and_then(ArcValue::as_str), Some("a"));
+//! assert_eq!(edited.pointer("/db/host").and_then(ArcValue::as_str), Some("b"));
+//! // The untouched branch is the same allocation in both
+//! let cache = |doc: &ArcValue| doc.get("cache").cloned().unwrap_or_default();
+//! assert!(ArcValue::ptr_eq(&cache(&base), &cache(&edited)));
+//! ```
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use serde::{Serialize, Serializer};
+
+use crate::Error;
+use crate::value::{Number, Tag, TaggedValue, Value};
+
+/// Items of an [`ArcValue::Sequence`]
+pub type ArcSequence = Vec<ArcValue>;
+
+/// Entries of an [`ArcValue::Mapping`]
+pub type ArcMapping = BTreeMap<ArcValue, ArcValue>;
+
+/// A tagged [`ArcValue`]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ArcTaggedValue {
+    pub tag: Tag,
+    pub value: ArcValue,
+}
+
+/// A [`Value`] whose strings and collections are shared; see the
+/// [module docs](self)
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum ArcValue {
+    #[default]
+    Null,
+    Bool(bool),
+    Number(Number),
+    String(Arc<str>),
+    Sequence(Arc<ArcSequence>),
+    Mapping(Arc<ArcMapping>),
+    Tagged(Arc<ArcTaggedValue>),
+}
+
+impl ArcValue {
+    /// Whether `a` and `b` are the same allocation, so one is a clone of the
+    /// other or shares it after an edit. Scalars other than strings are
+    /// never shared.
+    #[must_use]
+    pub fn ptr_eq(a: &Self, b: &Self) -> bool {
+        match (a, b) {
+            (Self::String(a), Self::String(b)) => Arc::ptr_eq(a, b),
+            (Self::Sequence(a), Self::Sequence(b)) => Arc::ptr_eq(a, b),
+            (Self::Mapping(a), Self::Mapping(b)) => Arc::ptr_eq(a, b),
+            (Self::Tagged(a), Self::Tagged(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+
+    /// Check if the value is null
+    #[must_use]
+    pub const fn is_null(&self) -> bool {
+        matches!(self, Self::Null)
+    }
+
+    /// Get the value as a bool if it is one
+    #[must_use]
+    pub const fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    /// Get the value as an i64 if it is a number with an integral value
+    #[must_use]
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Self::Number(n) => n.as_i64(),
+            _ => None,
+        }
+    }
+
+    /// Get the value as an f64 if it is a number
+    #[must_use]
+    pub const fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Number(n) => n.as_f64(),
+            _ => None,
+        }
+    }
+
+    /// Get the value as a string if it is one
+    #[must_use]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Get the value as a sequence if it is one
+    #[must_use]
+    pub fn as_sequence(&self) -> Option<&ArcSequence> {
+        match self {
+            Self::Sequence(seq) => Some(seq),
+            _ => None,
+        }
+    }
+
+    /// Get the value as a mapping if it is one
+    #[must_use]
+    pub fn as_mapping(&self) -> Option<&ArcMapping> {
+        match self {
+            Self::Mapping(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    /// Entry under the string key `key`, as [`Value::get`]
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&Self> {
+        match self.untagged() {
+            Self::Mapping(map) => map.get(&Self::from(key)),
+            _ => None,
+        }
+    }
+
+    /// Look up a node by JSON Pointer path, as [`Value::pointer`]
+    #[must_use]
+    pub fn pointer(&self, path: &str) -> Option<&Self> {
+        let tokens = crate::pointer::tokens(path).ok()?;
+        tokens
+            .iter()
+            .try_fold(self, |node, token| node.pointer_child(token))
+    }
+
+    /// Replace the node at `path` with `value`, copying only the collections
+    /// on the way to it
+    ///
+    /// Paths behave as in [`Value::set_from`]: missing mappings are created,
+    /// `null` nodes become mappings and `-` appends to a sequence.
+    pub fn set(&mut self, path: &str, value: Self) -> Result<(), Error> {
+        let mut node = self;
+        for token in &crate::pointer::tokens(path)? {
+            node = node.child_or_insert(token)?;
+        }
+        *node = value;
+        Ok(())
+    }
+
+    /// Remove the node at `path` from its parent and return it, copying only
+    /// the collections on the way to it
+    pub fn remove(&mut self, path: &str) -> Option<Self> {
+        let mut tokens = crate::pointer::tokens(path).ok()?;
+        let last = tokens.pop()?;
+        // Check first, so a miss leaves every shared node alone
+        self.pointer(path)?;
+        let mut node = self;
+        for token in &tokens {
+            node = node.child_or_insert(token).ok()?;
+        }
+        match node.untagged_mut() {
+            Self::Sequence(seq) => {
+                let index = crate::pointer::index(&last)?;
+                Some(Arc::make_mut(seq).remove(index))
+            }
+            Self::Mapping(map) => {
+                let key = Self::pointer_key(map, &last);
+                Arc::make_mut(map).remove(&key)
+            }
+            _ => None,
+        }
+    }
+
+    /// A deep copy as a plain [`Value`]
+    #[must_use]
+    pub fn to_value(&self) -> Value {
+        match self {
+            Self::Null => Value::Null,
+            Self::Bool(b) => Value::Bool(*b),
+            Self::Number(n) => Value::Number(n.clone()),
+            Self::String(s) => Value::String(s.to_string()),
+            Self::Sequence(seq) => Value::Sequence(seq.iter().map(Self::to_value).collect()),
+            Self::Mapping(map) => Value::Mapping(
+                map.iter()
+                    .map(|(k, v)| (k.to_value(), v.to_value()))
+                    .collect(),
+            ),
+            Self::Tagged(tagged) => Value::Tagged(Box::new(TaggedValue::new(
+                tagged.tag.clone(),
+                tagged.value.to_value(),
+            ))),
+        }
+    }
+
+    fn untagged(&self) -> &Self {
+        match self {
+            Self::Tagged(tagged) => tagged.value.untagged(),
+            other => other,
+        }
+    }
+
+    fn untagged_mut(&mut self) -> &mut Self {
+        match self {
+            Self::Tagged(tagged) => Arc::make_mut(tagged).value.untagged_mut(),
+            other => other,
+        }
+    }
+
+    fn pointer_child(&self, token: &str) -> Option<&Self> {
+        match self {
+            Self::Sequence(seq) => crate::pointer::index(token).and_then(|i| seq.get(i)),
+            Self::Mapping(map) => map.get(&Self::pointer_key(map, token)),
+            Self::Tagged(tagged) => tagged.value.pointer_child(token),
+            _ => None,
+        }
+    }
+
+    fn child_or_insert(&mut self, token: &str) -> Result<&mut Self, Error> {
+        if self.is_null() {
+            *self = Self::Mapping(Arc::default());
+        }
+        match self {
+            Self::Sequence(seq) => {
+                let len = seq.len();
+                let index = if token == "-" {
+                    len
+                } else {
+                    crate::pointer::index(token).ok_or_else(|| {
+                        Error::Custom(format!("invalid sequence index '{token}'"))
+                    })?
+                };
+                let seq = Arc::make_mut(seq);
+                if index == len {
+                    seq.push(Self::Null);
+                }
+                seq.get_mut(index).ok_or_else(|| {
+                    Error::Custom(format!(
+                        "sequence index {index} out of bounds (length {len})"
+                    ))
+                })
+            }
+            Self::Mapping(map) => {
+                let key = Self::pointer_key(map, token);
+                Ok(Arc::make_mut(map).entry(key).or_insert(Self::Null))
+            }
+            Self::Tagged(tagged) => Arc::make_mut(tagged).value.child_or_insert(token),
+            other => Err(Error::Custom(format!(
+                "cannot descend into scalar {} at '{token}'",
+                other.to_value()
+            ))),
+        }
+    }
+
+    /// The key a pointer token names in `map`; as for [`Value`], a token
+    /// that parses as an integer falls back to an integer key
+    fn pointer_key(map: &ArcMapping, token: &str) -> Self {
+        let key = Self::String(token.into());
+        if !map.contains_key(&key)
+            && let Ok(i) = token.parse::<i64>()
+        {
+            let int_key = Self::Number(Number::Integer(i));
+            if map.contains_key(&int_key) {
+                return int_key;
+            }
+        }
+        key
+    }
+}
+
+impl From<&Value> for ArcValue {
+    fn from(value: &Value) -> Self {
+        match value {
+            Value::Null => Self::Null,
+            Value::Bool(b) => Self::Bool(*b),
+            Value::Number(n) => Self::Number(n.clone()),
+            Value::String(s) => Self::String(s.as_str().into()),
+            Value::Sequence(seq) => Self::Sequence(Arc::new(seq.iter().map(Self::from).collect())),
+            Value::Mapping(map) => Self::Mapping(Arc::new(
+                map.iter().map(|(k, v)| (Self::from(k), Self::from(v))).collect(),
+            )),
+            Value::Tagged(tagged) => Self::Tagged(Arc::new(ArcTaggedValue {
+                tag: tagged.tag.clone(),
+                value: Self::from(&tagged.value),
+            })),
+        }
+    }
+}
+
+impl From<Value> for ArcValue {
+    fn from(value: Value) -> Self {
+        Self::from(&value)
+    }
+}
+
+impl From<&ArcValue> for Value {
+    fn from(value: &ArcValue) -> Self {
+        value.to_value()
+    }
+}
+
+impl From<ArcValue> for Value {
+    fn from(value: ArcValue) -> Self {
+        value.to_value()
+    }
+}
+
+impl From<&str> for ArcValue {
+    fn from(s: &str) -> Self {
+        Self::String(s.into())
+    }
+}
+
+impl From<i64> for ArcValue {
+    fn from(i: i64) -> Self {
+        Self::Number(Number::Integer(i))
+    }
+}
+
+impl From<bool> for ArcValue {
+    fn from(b: bool) -> Self {
+        Self::Bool(b)
+    }
+}
+
+impl Serialize for ArcValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Null => serializer.serialize_unit(),
+            Self::Bool(b) => serializer.serialize_bool(*b),
+            Self::Number(n) => n.serialize(serializer),
+            Self::String(s) => serializer.serialize_str(s),
+            Self::Sequence(seq) => seq.serialize(serializer),
+            Self::Mapping(map) => map.serialize(serializer),
+            // Tag handling, such as `!!binary`, is the same as for `Value`
+            Self::Tagged(_) => self.to_value().serialize(serializer),
+        }
+    }
+}
@@ -0,0 +1,80 @@
+//! Standard base64 codec for `!!binary` scalars
+//!
+//! Decoding skips whitespace because binary scalars are commonly folded
+//! across several lines.
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Full tag URI of the YAML binary type
+pub(crate) const BINARY_TAG: &str = "tag:yaml.org,2002:binary";
+
+pub(crate) fn encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i)) as usize & 0x3f] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Decode `s`, returning `None` on characters outside the alphabet or bad padding
+pub(crate) fn decode(s: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(s.len() / 4 * 3);
+    let mut buf = 0u32;
+    let mut bits = 0;
+    let mut padding = 0;
+    for c in s.bytes().filter(|c| !c.is_ascii_whitespace()) {
+        if c == b'=' {
+            padding += 1;
+            continue;
+        }
+        if padding > 0 {
+            return None;
+        }
+        let sextet = ALPHABET.iter().position(|&a| a == c)? as u32;
+        buf = (buf << 6) | sextet;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buf >> bits) as u8);
+            buf &= (1 << bits) - 1;
+        }
+    }
+    // Leftover bits must be zero fill, with padding completing the last quantum
+    if padding > 2 || buf != 0 || (padding > 0 && (bits / 2 != padding)) {
+        return None;
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        for input in [&b""[..], b"h", b"he", b"hel", b"hello", &[0, 1, 254, 255]] {
+            assert_eq!(decode(&encode(input)).as_deref(), Some(input));
+        }
+        assert_eq!(encode(b"hello"), "aGVsbG8=");
+    }
+
+    #[test]
+    fn test_decode_folded_and_invalid() {
+        assert_eq!(decode("aGVs\n  bG8=\n").as_deref(), Some(&b"hello"[..]));
+        assert_eq!(decode("aGVsbG8"), Some(b"hello".to_vec()));
+        assert_eq!(decode("aG=Vs"), None);
+        assert_eq!(decode("*not*"), None);
+    }
+}
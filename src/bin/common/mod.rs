@@ -0,0 +1,15 @@
+//! Helpers shared by the command-line tools
+
+use std::io::{self, Read};
+
+/// The text of `path`, or of standard input when it is `None` or `-`
+pub fn read_input(path: Option<&str>) -> Result<String, String> {
+    match path {
+        None | Some("-") => {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input).map_err(|e| e.to_string())?;
+            Ok(input)
+        }
+        Some(path) => std::fs::read_to_string(path).map_err(|e| format!("{path}: {e}")),
+    }
+}
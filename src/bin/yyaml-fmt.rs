@@ -0,0 +1,24 @@
+//! `yyaml-fmt [FILE]` — print a YAML stream in canonical form.
+//!
+//! Reads FILE, or standard input when it is omitted or `-`.
+
+use std::io::{self, Write};
+use std::process::ExitCode;
+
+mod common;
+
+use common::read_input;
+
+fn main() -> ExitCode {
+    let path = std::env::args().nth(1);
+    let result = read_input(path.as_deref())
+        .and_then(|input| yyaml::format_str(&input).map_err(|e| e.to_string()))
+        .and_then(|out| io::stdout().write_all(out.as_bytes()).map_err(|e| e.to_string()));
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("yyaml-fmt: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,57 @@
+//! `yyaml-get PATH [FILE]` — print the node at a JSON Pointer path.
+//!
+//! PATH is looked up in every document of FILE, or of standard input when
+//! FILE is omitted or `-`. Scalars print as plain text, collections as YAML.
+
+use std::process::ExitCode;
+
+mod common;
+
+use common::read_input;
+use yyaml::{Yaml, YamlEmitter, YamlLoader};
+
+fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1);
+    let Some(pointer) = args.next() else {
+        eprintln!("usage: yyaml-get PATH [FILE]");
+        return ExitCode::from(2);
+    };
+    let path = args.next();
+    match run(&pointer, path.as_deref()) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => {
+            eprintln!("yyaml-get: no node at `{pointer}`");
+            ExitCode::FAILURE
+        }
+        Err(err) => {
+            eprintln!("yyaml-get: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Print every match of `pointer`; `Ok(false)` when nothing matched
+fn run(pointer: &str, path: Option<&str>) -> Result<bool, String> {
+    let input = read_input(path)?;
+    let docs = YamlLoader::load_from_str(&input).map_err(|e| e.to_string())?;
+    let mut found = false;
+    for node in docs.iter().filter_map(|doc| doc.pointer(pointer)) {
+        found = true;
+        println!("{}", render(node)?);
+    }
+    Ok(found)
+}
+
+fn render(node: &Yaml) -> Result<String, String> {
+    match node {
+        Yaml::String(s) | Yaml::Real(s) => Ok(s.clone()),
+        Yaml::Integer(i) => Ok(i.to_string()),
+        Yaml::Boolean(b) => Ok(b.to_string()),
+        Yaml::Null => Ok("null".to_string()),
+        _ => {
+            let mut out = String::new();
+            YamlEmitter::new(&mut out).emit(node).map_err(|e| e.to_string())?;
+            Ok(out)
+        }
+    }
+}
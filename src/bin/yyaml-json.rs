@@ -0,0 +1,31 @@
+//! `yyaml-json [FILE]` — convert a YAML stream to JSON.
+//!
+//! Reads FILE, or standard input when it is omitted or `-`, and prints each
+//! document as one line of JSON.
+
+use std::process::ExitCode;
+
+mod common;
+
+use common::read_input;
+use yyaml::{YamlLoader, to_json};
+
+fn main() -> ExitCode {
+    let path = std::env::args().nth(1);
+    match run(path.as_deref()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("yyaml-json: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(path: Option<&str>) -> Result<(), String> {
+    let input = read_input(path)?;
+    let docs = YamlLoader::load_from_str(&input).map_err(|e| e.to_string())?;
+    for doc in &docs {
+        println!("{}", to_json(doc).map_err(|e| e.to_string())?);
+    }
+    Ok(())
+}
@@ -0,0 +1,33 @@
+//! `yyaml-lint [FILE]` — report anchor and alias problems in a YAML stream.
+//!
+//! Reads FILE, or standard input when it is omitted or `-`. Exits non-zero
+//! when the input fails to parse or any error-level diagnostic is found.
+
+use std::process::ExitCode;
+
+mod common;
+
+use common::read_input;
+use yyaml::Severity;
+
+fn main() -> ExitCode {
+    let path = std::env::args().nth(1);
+    let diagnostics = read_input(path.as_deref())
+        .and_then(|input| yyaml::diagnostics::lint_anchors(&input).map_err(|e| e.to_string()));
+    match diagnostics {
+        Ok(diagnostics) => {
+            for diagnostic in &diagnostics {
+                println!("{diagnostic}");
+            }
+            if diagnostics.iter().any(|d| d.severity == Severity::Error) {
+                ExitCode::FAILURE
+            } else {
+                ExitCode::SUCCESS
+            }
+        }
+        Err(err) => {
+            eprintln!("yyaml-lint: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,326 @@
+//! Reusing parsed documents across loads of the same input
+//!
+//! A [`YamlCache`] keys parsed streams by a hash of their bytes, so a
+//! service that loads the same config bundle again and again parses it
+//! once. It holds at most a given number of input bytes and evicts the
+//! least recently used entries past that.
+//!
+//! ```rust
+//! use std::sync::Arc;
+//! use yyaml::YamlCache;
+//!
+//! let cache = YamlCache::new(1 << 20);
+//! let first = cache.get_or_parse(b"replicas: 3\n").unwrap();
+//! let again = cache.get_or_parse(b"replicas: 3\n").unwrap();
+//! assert!(Arc::ptr_eq(&first, &again));
+//! assert_eq!(again[0]["replicas"].as_i64(), Some(3));
+//!
+//! assert!(cache.invalidate(b"replicas: 3\n"));
+//! assert!(cache.is_empty());
+//! ```
+
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::hash::Hasher;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use crate::error::{Marker, ScanError};
+use crate::parser::{LoaderOptions, YamlLoader};
+use crate::{Error, Yaml};
+
+/// The hash a [`YamlCache`] keys an input by
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ContentHash(pub u64);
+
+impl ContentHash {
+    /// Hash `bytes` the way the cache does
+    #[must_use]
+    pub fn of(bytes: &[u8]) -> Self {
+        let mut hasher = DefaultHasher::new();
+        hasher.write(bytes);
+        Self(hasher.finish())
+    }
+}
+
+impl fmt::Display for ContentHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+/// Why an entry left a [`YamlCache`], as passed to its
+/// [`on_evict`](YamlCache::on_evict) hook
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Eviction {
+    /// Dropped to keep the cache within its size
+    Size,
+    /// Removed by [`invalidate`](YamlCache::invalidate),
+    /// [`invalidate_hash`](YamlCache::invalidate_hash) or
+    /// [`retain`](YamlCache::retain)
+    Invalidated,
+    /// Removed by [`clear`](YamlCache::clear)
+    Cleared,
+    /// Replaced by a different input whose hash is the same
+    Collision,
+}
+
+type EvictHook = dyn Fn(ContentHash, Eviction) + Send + Sync;
+
+struct Entry {
+    /// Kept to tell inputs apart whose hashes collide
+    source: Box<[u8]>,
+    documents: Arc<[Yaml]>,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct Entries {
+    map: HashMap<ContentHash, Entry>,
+    bytes: usize,
+    clock: u64,
+}
+
+/// Parsed streams by content hash, within a size budget; see the
+/// [module docs](self)
+pub struct YamlCache {
+    max_bytes: usize,
+    options: Option<LoaderOptions>,
+    entries: Mutex<Entries>,
+    on_evict: Option<Box<EvictHook>>,
+}
+
+impl YamlCache {
+    /// A cache holding parsed streams of up to `max_bytes` input bytes in
+    /// all; an input larger than that alone is parsed but not kept
+    #[must_use]
+    pub fn new(max_bytes: usize) -> Self {
+        Self {
+            max_bytes,
+            options: None,
+            entries: Mutex::default(),
+            on_evict: None,
+        }
+    }
+
+    /// Parse with `options` instead of the defaults of
+    /// [`YamlLoader::load_from_str`]
+    #[must_use]
+    pub fn with_options(mut self, options: LoaderOptions) -> Self {
+        self.options = Some(options);
+        self
+    }
+
+    /// Call `hook` with the hash of every entry that leaves the cache and
+    /// why, e.g. to drop state derived from the documents
+    #[must_use]
+    pub fn on_evict(
+        mut self,
+        hook: impl Fn(ContentHash, Eviction) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_evict = Some(Box::new(hook));
+        self
+    }
+
+    /// The documents of `bytes`, parsed now or on an earlier call with the
+    /// same bytes. Errors are not cached.
+    pub fn get_or_parse(&self, bytes: &[u8]) -> Result<Arc<[Yaml]>, Error> {
+        let hash = ContentHash::of(bytes);
+        {
+            let mut entries = self.lock();
+            entries.clock += 1;
+            let now = entries.clock;
+            if let Some(entry) = entries.map.get_mut(&hash)
+                && *entry.source == *bytes
+            {
+                entry.last_used = now;
+                return Ok(Arc::clone(&entry.documents));
+            }
+        }
+        // Parsed without the lock, so other inputs are served meanwhile
+        let documents: Arc<[Yaml]> = self.parse(bytes)?.into();
+        if bytes.len() <= self.max_bytes {
+            let mut evicted = Vec::new();
+            let mut replaced = false;
+            {
+                let mut entries = self.lock();
+                let entry = Entry {
+                    source: bytes.into(),
+                    documents: Arc::clone(&documents),
+                    last_used: entries.clock,
+                };
+                entries.bytes += bytes.len();
+                if let Some(old) = entries.map.insert(hash, entry) {
+                    entries.bytes -= old.source.len();
+                    replaced = *old.source != *bytes;
+                }
+                while entries.bytes > self.max_bytes {
+                    let Some(oldest) = entries
+                        .map
+                        .iter()
+                        .filter(|(key, _)| **key != hash)
+                        .min_by_key(|(_, entry)| entry.last_used)
+                        .map(|(key, _)| *key)
+                    else {
+                        break;
+                    };
+                    if let Some(old) = entries.map.remove(&oldest) {
+                        entries.bytes -= old.source.len();
+                    }
+                    evicted.push(oldest);
+                }
+            }
+            if replaced {
+                self.notify(vec![hash], Eviction::Collision);
+            }
+            self.notify(evicted, Eviction::Size);
+        }
+        Ok(documents)
+    }
+
+    /// Drop the entry for `bytes`, returning whether there was one
+    pub fn invalidate(&self, bytes: &[u8]) -> bool {
+        let hash = ContentHash::of(bytes);
+        let removed = {
+            let mut entries = self.lock();
+            match entries.map.get(&hash) {
+                Some(entry) if *entry.source == *bytes => entries.remove(hash),
+                _ => false,
+            }
+        };
+        if removed {
+            self.notify(vec![hash], Eviction::Invalidated);
+        }
+        removed
+    }
+
+    /// Drop the entry with hash `hash`, returning whether there was one
+    pub fn invalidate_hash(&self, hash: ContentHash) -> bool {
+        let removed = self.lock().remove(hash);
+        if removed {
+            self.notify(vec![hash], Eviction::Invalidated);
+        }
+        removed
+    }
+
+    /// Keep only the entries for which `keep` returns `true`
+    pub fn retain(&self, mut keep: impl FnMut(ContentHash, &[Yaml]) -> bool) {
+        let dropped: Vec<ContentHash> = {
+            let mut entries = self.lock();
+            let dropped: Vec<ContentHash> = entries
+                .map
+                .iter()
+                .filter(|(hash, entry)| !keep(**hash, &entry.documents))
+                .map(|(hash, _)| *hash)
+                .collect();
+            for hash in &dropped {
+                entries.remove(*hash);
+            }
+            dropped
+        };
+        self.notify(dropped, Eviction::Invalidated);
+    }
+
+    /// Drop every entry
+    pub fn clear(&self) {
+        let dropped: Vec<ContentHash> = {
+            let mut entries = self.lock();
+            entries.bytes = 0;
+            entries.map.drain().map(|(hash, _)| hash).collect()
+        };
+        self.notify(dropped, Eviction::Cleared);
+    }
+
+    /// Number of inputs held
+    pub fn len(&self) -> usize {
+        self.lock().map.len()
+    }
+
+    /// Whether no input is held
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Input bytes held, counted against the size given to
+    /// [`new`](Self::new)
+    pub fn bytes(&self) -> usize {
+        self.lock().bytes
+    }
+
+    fn parse(&self, bytes: &[u8]) -> Result<Vec<Yaml>, Error> {
+        let text = std::str::from_utf8(bytes).map_err(|e| {
+            let mut mark = Marker::start();
+            mark.advance_str(&String::from_utf8_lossy(&bytes[..e.valid_up_to()]));
+            ScanError::new(mark, "invalid UTF-8 in input")
+        })?;
+        match &self.options {
+            Some(options) => YamlLoader::load_with_options(text, options),
+            None => Ok(YamlLoader::load_from_str(text)?),
+        }
+    }
+
+    /// The entries, usable even if a thread panicked holding them, since
+    /// every update leaves them consistent
+    fn lock(&self) -> MutexGuard<'_, Entries> {
+        self.entries.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Report `hashes` to the hook, outside the lock so it may use the cache
+    fn notify(&self, hashes: Vec<ContentHash>, reason: Eviction) {
+        if let Some(hook) = &self.on_evict {
+            for hash in hashes {
+                hook(hash, reason);
+            }
+        }
+    }
+}
+
+impl Entries {
+    fn remove(&mut self, hash: ContentHash) -> bool {
+        match self.map.remove(&hash) {
+            Some(entry) => {
+                self.bytes -= entry.source.len();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl fmt::Debug for YamlCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let entries = self.lock();
+        f.debug_struct("YamlCache")
+            .field("max_bytes", &self.max_bytes)
+            .field("entries", &entries.map.len())
+            .field("bytes", &entries.bytes)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collision_notifies_hook() {
+        let evicted = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&evicted);
+        let cache =
+            YamlCache::new(64).on_evict(move |hash, why| log.lock().unwrap().push((hash, why)));
+        let (a, b) = (b"name: a\n".as_slice(), b"name: b\n".as_slice());
+        // Stand in for `a` colliding with `b`, which real inputs do too
+        // rarely to test
+        let hash = ContentHash::of(b);
+        cache.get_or_parse(a).unwrap();
+        {
+            let mut entries = cache.lock();
+            let entry = entries.map.remove(&ContentHash::of(a)).unwrap();
+            entries.map.insert(hash, entry);
+        }
+
+        assert_eq!(cache.get_or_parse(b).unwrap()[0]["name"].as_str(), Some("b"));
+        assert_eq!(*evicted.lock().unwrap(), [(hash, Eviction::Collision)]);
+        assert_eq!((cache.len(), cache.bytes()), (1, b.len()));
+    }
+}
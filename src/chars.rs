@@ -0,0 +1,353 @@
+//! YAML 1.2 character classes
+//!
+//! Character-level predicates for the productions of chapter 5 of the YAML
+//! 1.2.2 specification, for tools that work with yyaml tokens or raw text.
+//! Each function is named after its production and matches it exactly; the
+//! production number is given in brackets.
+//!
+//! ```rust
+//! use yyaml::chars::{self, Indicator};
+//!
+//! assert!(chars::is_printable('\u{85}'));
+//! assert!(!chars::is_printable('\u{7F}'));
+//! assert_eq!(Indicator::of('&'), Some(Indicator::Anchor));
+//! assert!(chars::is_flow_indicator(','));
+//! assert_eq!(chars::strip_byte_order_mark("\u{FEFF}a: 1"), "a: 1");
+//! ```
+
+/// [3] c-byte-order-mark
+pub const BYTE_ORDER_MARK: char = '\u{FEFF}';
+
+/// [1] c-printable: tab, line feed, carriage return, and the printable
+/// ranges of the Basic Multilingual Plane and beyond
+#[inline]
+#[must_use]
+pub const fn is_printable(ch: char) -> bool {
+    matches!(
+        ch,
+        '\t' | '\n'
+            | '\r'
+            | ' '..='\u{7E}'
+            | '\u{85}'
+            | '\u{A0}'..='\u{D7FF}'
+            | '\u{E000}'..='\u{FFFD}'
+            | '\u{10000}'..='\u{10FFFF}'
+    )
+}
+
+/// [2] nb-json: tab and every character from space upwards
+#[inline]
+#[must_use]
+pub const fn is_nb_json(ch: char) -> bool {
+    ch == '\t' || ch >= ' '
+}
+
+/// [3] c-byte-order-mark
+#[inline]
+#[must_use]
+pub const fn is_byte_order_mark(ch: char) -> bool {
+    ch == BYTE_ORDER_MARK
+}
+
+/// `input` without a leading byte order mark
+#[inline]
+#[must_use]
+pub fn strip_byte_order_mark(input: &str) -> &str {
+    input.strip_prefix(BYTE_ORDER_MARK).unwrap_or(input)
+}
+
+/// The indicator characters [4]-[21], which have special meaning in YAML
+/// syntax
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Indicator {
+    /// [4] c-sequence-entry `-`
+    SequenceEntry,
+    /// [5] c-mapping-key `?`
+    MappingKey,
+    /// [6] c-mapping-value `:`
+    MappingValue,
+    /// [7] c-collect-entry `,`
+    CollectEntry,
+    /// [8] c-sequence-start `[`
+    SequenceStart,
+    /// [9] c-sequence-end `]`
+    SequenceEnd,
+    /// [10] c-mapping-start `{`
+    MappingStart,
+    /// [11] c-mapping-end `}`
+    MappingEnd,
+    /// [12] c-comment `#`
+    Comment,
+    /// [13] c-anchor `&`
+    Anchor,
+    /// [14] c-alias `*`
+    Alias,
+    /// [15] c-tag `!`
+    Tag,
+    /// [16] c-literal `|`
+    Literal,
+    /// [17] c-folded `>`
+    Folded,
+    /// [18] c-single-quote `'`
+    SingleQuote,
+    /// [19] c-double-quote `"`
+    DoubleQuote,
+    /// [20] c-directive `%`
+    Directive,
+    /// [21] c-reserved `@` or `` ` ``
+    Reserved,
+}
+
+impl Indicator {
+    /// The indicator `ch` stands for, if any
+    #[must_use]
+    pub const fn of(ch: char) -> Option<Self> {
+        Some(match ch {
+            '-' => Self::SequenceEntry,
+            '?' => Self::MappingKey,
+            ':' => Self::MappingValue,
+            ',' => Self::CollectEntry,
+            '[' => Self::SequenceStart,
+            ']' => Self::SequenceEnd,
+            '{' => Self::MappingStart,
+            '}' => Self::MappingEnd,
+            '#' => Self::Comment,
+            '&' => Self::Anchor,
+            '*' => Self::Alias,
+            '!' => Self::Tag,
+            '|' => Self::Literal,
+            '>' => Self::Folded,
+            '\'' => Self::SingleQuote,
+            '"' => Self::DoubleQuote,
+            '%' => Self::Directive,
+            '@' | '`' => Self::Reserved,
+            _ => return None,
+        })
+    }
+}
+
+/// [22] c-indicator: any of the indicators [4]-[21]
+#[inline]
+#[must_use]
+pub const fn is_indicator(ch: char) -> bool {
+    Indicator::of(ch).is_some()
+}
+
+/// [23] c-flow-indicator: `,` `[` `]` `{` `}`
+#[inline]
+#[must_use]
+pub const fn is_flow_indicator(ch: char) -> bool {
+    matches!(ch, ',' | '[' | ']' | '{' | '}')
+}
+
+/// [26] b-char: line feed or carriage return
+#[inline]
+#[must_use]
+pub const fn is_break(ch: char) -> bool {
+    matches!(ch, '\n' | '\r')
+}
+
+/// [27] nb-char: printable, but neither a line break nor a byte order mark
+#[inline]
+#[must_use]
+pub const fn is_non_break(ch: char) -> bool {
+    is_printable(ch) && !is_break(ch) && !is_byte_order_mark(ch)
+}
+
+/// [33] s-white: space or tab
+#[inline]
+#[must_use]
+pub const fn is_white(ch: char) -> bool {
+    matches!(ch, ' ' | '\t')
+}
+
+/// White space or a line break, the separator class the scanner skips
+/// between tokens. Not a production of its own.
+#[inline]
+#[must_use]
+pub const fn is_blank(ch: char) -> bool {
+    is_white(ch) || is_break(ch)
+}
+
+/// [34] ns-char: a non-break character that is not white space
+#[inline]
+#[must_use]
+pub const fn is_ns_char(ch: char) -> bool {
+    is_non_break(ch) && !is_white(ch)
+}
+
+/// [38] ns-word-char: ASCII letter, decimal digit or `-`
+#[inline]
+#[must_use]
+pub const fn is_word_char(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || ch == '-'
+}
+
+/// [39] ns-uri-char, one character at a time: `%` starts an escape that
+/// must be followed by two hex digits
+#[inline]
+#[must_use]
+pub const fn is_uri_char(ch: char) -> bool {
+    is_word_char(ch)
+        || matches!(
+            ch,
+            '%' | '#'
+                | ';'
+                | '/'
+                | '?'
+                | ':'
+                | '@'
+                | '&'
+                | '='
+                | '+'
+                | '$'
+                | ','
+                | '_'
+                | '.'
+                | '!'
+                | '~'
+                | '*'
+                | '\''
+                | '('
+                | ')'
+                | '['
+                | ']'
+        )
+}
+
+/// [40] ns-tag-char: a URI character other than `!` or a flow indicator
+#[inline]
+#[must_use]
+pub const fn is_tag_char(ch: char) -> bool {
+    is_uri_char(ch) && ch != '!' && !is_flow_indicator(ch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn all_chars() -> impl Iterator<Item = char> {
+        (0..=0x10_FFFF).filter_map(char::from_u32)
+    }
+
+    /// Check `actual` against the spec's definition over every scalar value
+    fn exhaustive(name: &str, actual: fn(char) -> bool, spec: fn(u32) -> bool) {
+        for ch in all_chars() {
+            assert_eq!(actual(ch), spec(ch as u32), "{name} disagrees on U+{:04X}", ch as u32);
+        }
+    }
+
+    #[test]
+    fn test_c_printable() {
+        exhaustive("[1] c-printable", is_printable, |c| {
+            matches!(
+                c,
+                0x09 | 0x0A
+                    | 0x0D
+                    | 0x20..=0x7E
+                    | 0x85
+                    | 0xA0..=0xD7FF
+                    | 0xE000..=0xFFFD
+                    | 0x1_0000..=0x10_FFFF
+            )
+        });
+    }
+
+    #[test]
+    fn test_nb_json() {
+        exhaustive("[2] nb-json", is_nb_json, |c| {
+            matches!(c, 0x09 | 0x20..=0x10_FFFF)
+        });
+    }
+
+    #[test]
+    fn test_c_byte_order_mark() {
+        exhaustive("[3] c-byte-order-mark", is_byte_order_mark, |c| c == 0xFEFF);
+        assert_eq!(strip_byte_order_mark("\u{FEFF}\u{FEFF}x"), "\u{FEFF}x");
+        assert_eq!(strip_byte_order_mark("x\u{FEFF}"), "x\u{FEFF}");
+    }
+
+    #[test]
+    fn test_c_indicators() {
+        let expected = [
+            ('-', Indicator::SequenceEntry),
+            ('?', Indicator::MappingKey),
+            (':', Indicator::MappingValue),
+            (',', Indicator::CollectEntry),
+            ('[', Indicator::SequenceStart),
+            (']', Indicator::SequenceEnd),
+            ('{', Indicator::MappingStart),
+            ('}', Indicator::MappingEnd),
+            ('#', Indicator::Comment),
+            ('&', Indicator::Anchor),
+            ('*', Indicator::Alias),
+            ('!', Indicator::Tag),
+            ('|', Indicator::Literal),
+            ('>', Indicator::Folded),
+            ('\'', Indicator::SingleQuote),
+            ('"', Indicator::DoubleQuote),
+            ('%', Indicator::Directive),
+            ('@', Indicator::Reserved),
+            ('`', Indicator::Reserved),
+        ];
+        for ch in all_chars() {
+            let spec = expected.iter().find(|(c, _)| *c == ch).map(|(_, i)| *i);
+            assert_eq!(Indicator::of(ch), spec, "[4]-[21] disagree on {ch:?}");
+        }
+        exhaustive("[22] c-indicator", is_indicator, |c| {
+            b"-?:,[]{}#&*!|>'\"%@`".iter().any(|&i| u32::from(i) == c)
+        });
+    }
+
+    #[test]
+    fn test_c_flow_indicator() {
+        exhaustive("[23] c-flow-indicator", is_flow_indicator, |c| {
+            b",[]{}".iter().any(|&i| u32::from(i) == c)
+        });
+    }
+
+    #[test]
+    fn test_line_breaks_and_white_space() {
+        exhaustive("[26] b-char", is_break, |c| matches!(c, 0x0A | 0x0D));
+        exhaustive("[27] nb-char", is_non_break, |c| {
+            matches!(
+                c,
+                0x09 | 0x20..=0x7E
+                    | 0x85
+                    | 0xA0..=0xD7FF
+                    | 0xE000..=0xFEFE
+                    | 0xFF00..=0xFFFD
+                    | 0x1_0000..=0x10_FFFF
+            )
+        });
+        exhaustive("[33] s-white", is_white, |c| matches!(c, 0x20 | 0x09));
+        exhaustive("s-white | b-char", is_blank, |c| {
+            matches!(c, 0x20 | 0x09 | 0x0A | 0x0D)
+        });
+        exhaustive("[34] ns-char", is_ns_char, |c| {
+            matches!(
+                c,
+                0x21..=0x7E
+                    | 0x85
+                    | 0xA0..=0xD7FF
+                    | 0xE000..=0xFEFE
+                    | 0xFF00..=0xFFFD
+                    | 0x1_0000..=0x10_FFFF
+            )
+        });
+    }
+
+    #[test]
+    fn test_uri_and_tag_chars() {
+        fn word(c: u32) -> bool {
+            matches!(c, 0x30..=0x39 | 0x41..=0x5A | 0x61..=0x7A | 0x2D)
+        }
+        exhaustive("[38] ns-word-char", is_word_char, word);
+        exhaustive("[39] ns-uri-char", is_uri_char, |c| {
+            word(c) || b"%#;/?:@&=+$,_.!~*'()[]".iter().any(|&i| u32::from(i) == c)
+        });
+        exhaustive("[40] ns-tag-char", is_tag_char, |c| {
+            word(c) || b"%#;/?:@&=+$_.~*'()".iter().any(|&i| u32::from(i) == c)
+        });
+    }
+}
@@ -0,0 +1,304 @@
+//! Reporting what a conversion between [`Yaml`] and [`Value`] loses
+//!
+//! The two models do not carry the same details: a [`Value`] has no aliases
+//! or invalid nodes and keys its mappings by converted value, while a
+//! [`Yaml`] node holds one tag. [`Value::try_from_yaml`] and
+//! [`Value::try_into_yaml`] convert the way [`Value::from_yaml`] and
+//! [`Value::into_yaml`] do and list a [`ConversionWarning`] for every detail
+//! left behind.
+
+use std::fmt;
+
+use crate::pointer;
+use crate::tags::SET;
+use crate::value::{Number, Tag, TaggedValue, Value, is_set_content};
+use crate::yaml::{BadValueKind, Yaml, integer_real, parse_f64};
+use crate::LinkedHashMap;
+
+/// A detail of a node that a conversion could not carry over
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConversionWarning {
+    /// JSON Pointer of the node in the tree being converted
+    pub path: String,
+    pub loss: ConversionLoss,
+}
+
+/// What a [`ConversionWarning`] reports
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConversionLoss {
+    /// An alias to the anchor with this id became null
+    Alias(usize),
+    /// An invalid node became null; the reason is known for a
+    /// [`Yaml::Invalid`]
+    BadValue(Option<BadValueKind>),
+    /// A real that does not read as a float became this string
+    UnparsedReal(String),
+    /// A mapping key equal to an earlier key once converted replaced the
+    /// earlier entry
+    DuplicateKey,
+    /// A node tagged twice kept its outer tag and dropped this inner one
+    NestedTag(String),
+}
+
+impl fmt::Display for ConversionWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = if self.path.is_empty() { "/" } else { &self.path };
+        match &self.loss {
+            ConversionLoss::Alias(id) => write!(f, "{path}: alias to anchor {id} became null"),
+            ConversionLoss::BadValue(None) => write!(f, "{path}: invalid node became null"),
+            ConversionLoss::BadValue(Some(kind)) => {
+                write!(f, "{path}: invalid node became null ({kind})")
+            }
+            ConversionLoss::UnparsedReal(text) => {
+                write!(f, "{path}: real `{text}` is not a float and became a string")
+            }
+            ConversionLoss::DuplicateKey => {
+                write!(f, "{path}: key duplicates an earlier one and replaced it")
+            }
+            ConversionLoss::NestedTag(tag) => write!(f, "{path}: inner tag `{tag}` was dropped"),
+        }
+    }
+}
+
+impl Value {
+    /// Convert like [`from_yaml`](Self::from_yaml), also listing what did
+    /// not survive; paths are those of `yaml`.
+    ///
+    /// ```rust
+    /// use yyaml::{ConversionLoss, Number, Value, Yaml};
+    ///
+    /// let yaml = Yaml::Array(vec![Yaml::Integer(1), Yaml::Alias(0), Yaml::BadValue]);
+    /// let (value, warnings) = Value::try_from_yaml(&yaml);
+    /// let one = Value::Number(Number::Integer(1));
+    /// assert_eq!(value, Value::Sequence(vec![one, Value::Null, Value::Null]));
+    /// let losses: Vec<_> = warnings.iter().map(|w| (w.path.as_str(), &w.loss)).collect();
+    /// assert_eq!(losses, [("/1", &ConversionLoss::Alias(0)), ("/2", &ConversionLoss::BadValue(None))]);
+    /// ```
+    #[must_use]
+    pub fn try_from_yaml(yaml: &Yaml) -> (Self, Vec<ConversionWarning>) {
+        let mut conversion = Conversion::new(pointer::key_token);
+        let value = conversion.yaml_to_value(yaml);
+        (value, conversion.warnings)
+    }
+
+    /// Convert to a [`Yaml`] node, keeping tags; a `!!set` becomes a
+    /// mapping of its members to null, as loading one produces
+    #[must_use]
+    pub fn into_yaml(self) -> Yaml {
+        self.try_into_yaml().0
+    }
+
+    /// Convert like [`into_yaml`](Self::into_yaml), also listing what did
+    /// not survive; paths are those of this value.
+    #[must_use]
+    pub fn try_into_yaml(self) -> (Yaml, Vec<ConversionWarning>) {
+        self.to_yaml()
+    }
+
+    /// [`try_into_yaml`](Self::try_into_yaml) without giving up the value
+    pub(crate) fn to_yaml(&self) -> (Yaml, Vec<ConversionWarning>) {
+        let mut conversion = Conversion::new(pointer::value_key_token);
+        let yaml = conversion.value_to_yaml(self);
+        (yaml, conversion.warnings)
+    }
+}
+
+impl From<Yaml> for Value {
+    /// Convert like [`Value::from_yaml`], moving strings out of `yaml`
+    /// instead of copying them
+    fn from(yaml: Yaml) -> Self {
+        match yaml {
+            Yaml::Real(s) => match (integer_real(&s), parse_f64(&s)) {
+                (Some(n), _) => Self::Number(n),
+                (None, Some(f)) => Self::Number(Number::Float(f)),
+                (None, None) => Self::String(s),
+            },
+            Yaml::Integer(i) => Self::Number(Number::Integer(i)),
+            Yaml::String(s) => Self::String(s),
+            Yaml::Boolean(b) => Self::Bool(b),
+            Yaml::Array(items) => Self::Sequence(items.into_iter().map(Self::from).collect()),
+            Yaml::Hash(hash) => Self::Mapping(
+                hash.into_iter()
+                    .map(|(k, v)| (Self::from(k), Self::from(v)))
+                    .collect(),
+            ),
+            Yaml::Tagged(tag_name, boxed_yaml) => match *boxed_yaml {
+                Yaml::Hash(hash) if tag_name == SET && is_set_content(&hash) => {
+                    Self::ordered_set(hash.into_iter().map(|(member, _)| Self::from(member)))
+                }
+                inner => Self::Tagged(Box::new(TaggedValue {
+                    tag: Tag::new(tag_name),
+                    value: Self::from(inner),
+                })),
+            },
+            Yaml::Null | Yaml::Alias(_) | Yaml::BadValue | Yaml::Invalid(_) => Self::Null,
+        }
+    }
+}
+
+enum Step<'a, K> {
+    Index(usize),
+    Key(&'a K),
+}
+
+/// Where a conversion is in the tree it reads, and what it lost so far
+struct Conversion<'a, K> {
+    path: Vec<Step<'a, K>>,
+    key_token: fn(&K) -> String,
+    warnings: Vec<ConversionWarning>,
+}
+
+impl<'a, K> Conversion<'a, K> {
+    const fn new(key_token: fn(&K) -> String) -> Self {
+        Self {
+            path: Vec::new(),
+            key_token,
+            warnings: Vec::new(),
+        }
+    }
+
+    fn note(&mut self, loss: ConversionLoss) {
+        let path = self
+            .path
+            .iter()
+            .map(|step| match step {
+                Step::Index(i) => format!("/{i}"),
+                Step::Key(key) => format!("/{}", (self.key_token)(key)),
+            })
+            .collect();
+        self.warnings.push(ConversionWarning { path, loss });
+    }
+
+    fn within<T>(&mut self, step: Step<'a, K>, f: impl FnOnce(&mut Self) -> T) -> T {
+        self.path.push(step);
+        let out = f(self);
+        self.path.pop();
+        out
+    }
+}
+
+impl<'a> Conversion<'a, Yaml> {
+    fn yaml_to_value(&mut self, yaml: &'a Yaml) -> Value {
+        match yaml {
+            Yaml::Real(s) => {
+                if let Some(n) = integer_real(s) {
+                    Value::Number(n)
+                } else if let Some(f) = parse_f64(s) {
+                    Value::Number(Number::Float(f))
+                } else {
+                    self.note(ConversionLoss::UnparsedReal(s.clone()));
+                    Value::String(s.clone())
+                }
+            }
+            Yaml::Integer(i) => Value::Number(Number::Integer(*i)),
+            Yaml::String(s) => Value::String(s.clone()),
+            Yaml::Boolean(b) => Value::Bool(*b),
+            Yaml::Array(arr) => Value::Sequence(
+                arr.iter()
+                    .enumerate()
+                    .map(|(i, item)| self.within(Step::Index(i), |c| c.yaml_to_value(item)))
+                    .collect(),
+            ),
+            Yaml::Hash(hash) => {
+                let mut map = crate::Mapping::new();
+                for (k, v) in hash.iter() {
+                    self.within(Step::Key(k), |c| {
+                        let key = c.yaml_to_value(k);
+                        let value = c.yaml_to_value(v);
+                        if map.insert(key, value).is_some() {
+                            c.note(ConversionLoss::DuplicateKey);
+                        }
+                    });
+                }
+                Value::Mapping(map)
+            }
+            Yaml::Alias(id) => {
+                self.note(ConversionLoss::Alias(*id));
+                Value::Null
+            }
+            // A set keeps its members in document order, as a sequence
+            Yaml::Tagged(tag_name, boxed_yaml)
+                if tag_name == SET
+                    && let Yaml::Hash(hash) = boxed_yaml.as_ref()
+                    && is_set_content(hash) =>
+            {
+                let members: Vec<Value> = hash
+                    .iter()
+                    .map(|(member, _)| self.within(Step::Key(member), |c| c.yaml_to_value(member)))
+                    .collect();
+                Value::ordered_set(members)
+            }
+            Yaml::Tagged(tag_name, boxed_yaml) => {
+                // Preserve tagged content instead of extracting it
+                Value::Tagged(Box::new(TaggedValue {
+                    tag: Tag::new(tag_name.clone()),
+                    value: self.yaml_to_value(boxed_yaml),
+                }))
+            }
+            Yaml::Null => Value::Null,
+            Yaml::BadValue => {
+                self.note(ConversionLoss::BadValue(None));
+                Value::Null
+            }
+            Yaml::Invalid(invalid) => {
+                self.note(ConversionLoss::BadValue(Some(invalid.kind.clone())));
+                Value::Null
+            }
+        }
+    }
+}
+
+impl<'a> Conversion<'a, Value> {
+    fn value_to_yaml(&mut self, value: &'a Value) -> Yaml {
+        match value {
+            Value::Null => Yaml::Null,
+            Value::Bool(b) => Yaml::Boolean(*b),
+            Value::Number(Number::Integer(i)) => Yaml::Integer(*i),
+            Value::Number(Number::Unsigned(u)) => Yaml::Real(u.to_string()),
+            Value::Number(Number::BigInteger(i)) => Yaml::Real(i.to_string()),
+            Value::Number(Number::BigUnsigned(u)) => Yaml::Real(u.to_string()),
+            Value::Number(Number::Float(f)) => {
+                Yaml::Real(crate::ser::float_text(format!("{f:?}"), *f))
+            }
+            Value::String(s) => Yaml::String(s.clone()),
+            Value::Sequence(seq) => Yaml::Array(
+                seq.iter()
+                    .enumerate()
+                    .map(|(i, item)| self.within(Step::Index(i), |c| c.value_to_yaml(item)))
+                    .collect(),
+            ),
+            Value::Mapping(map) => {
+                let mut hash = LinkedHashMap::new();
+                for (k, v) in map {
+                    self.within(Step::Key(k), |c| {
+                        let key = c.value_to_yaml(k);
+                        hash.insert(key, c.value_to_yaml(v));
+                    });
+                }
+                Yaml::Hash(hash)
+            }
+            Value::Tagged(tagged) => {
+                let mut inner = &tagged.value;
+                // A node holds one tag, so the outermost one wins
+                while let Value::Tagged(nested) = inner {
+                    self.note(ConversionLoss::NestedTag(nested.tag.name.clone()));
+                    inner = &nested.value;
+                }
+                let content = match inner {
+                    Value::Sequence(members) if tagged.tag.name == SET => Yaml::Hash(
+                        members
+                            .iter()
+                            .map(|member| {
+                                let member =
+                                    self.within(Step::Key(member), |c| c.value_to_yaml(member));
+                                (member, Yaml::Null)
+                            })
+                            .collect(),
+                    ),
+                    other => self.value_to_yaml(other),
+                };
+                Yaml::Tagged(tagged.tag.name.clone(), Box::new(content))
+            }
+        }
+    }
+}
@@ -0,0 +1,444 @@
+//! Positioned, non-fatal findings about YAML sources
+//!
+//! Diagnostics report problems that do not stop a document from loading but
+//! that authors usually want to fix, each anchored to a [`Marker`].
+
+use crate::error::Marker;
+use crate::events::{TScalarStyle, TokenType};
+use crate::parser::SchemaMode;
+use crate::scanner::Scanner;
+use crate::scanner::state::UNMATCHED_FLOW_END;
+use crate::yaml::{core_tag_mismatch, overflowing_integer};
+use crate::Error;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// How serious a [`Diagnostic`] is
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Info => write!(f, "info"),
+            Self::Warning => write!(f, "warning"),
+            Self::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A single positioned finding
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// Stable kebab-case identifier, e.g. `unused-anchor`
+    pub code: &'static str,
+    pub message: String,
+    pub mark: Marker,
+    /// Secondary location, such as the definition an alias refers to
+    pub related: Option<Marker>,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}[{}]: {} at line {} col {}",
+            self.severity,
+            self.code,
+            self.message,
+            self.mark.line,
+            self.mark.col + 1
+        )
+    }
+}
+
+/// Report anchor hygiene problems in every document of `s`.
+///
+/// Produces `unused-anchor` and `shadowed-anchor` warnings, plus
+/// `cross-document-alias` and `undefined-alias` errors for aliases that do
+/// not resolve within their own document.
+pub fn lint_anchors(s: &str) -> Result<Vec<Diagnostic>, Error> {
+    let mut lint = AnchorLint::default();
+    let mut scanner = Scanner::over_str(s);
+    loop {
+        let token = scanner.peek_token()?;
+        scanner.skip();
+        match token.1 {
+            TokenType::DocumentStart | TokenType::DocumentEnd => lint.end_document(),
+            TokenType::Anchor(name) => lint.define(name, token.0),
+            TokenType::Alias(name) => {
+                lint.alias(&name, token.0);
+            }
+            TokenType::StreamEnd | TokenType::NoToken => break,
+            _ => {}
+        }
+    }
+    lint.end_document();
+    let mut diagnostics: Vec<_> = lint
+        .into_findings()
+        .into_iter()
+        .map(AnchorFinding::into_diagnostic)
+        .collect();
+    diagnostics.sort_by_key(|d| d.mark.index);
+    Ok(diagnostics)
+}
+
+/// Report every flow collection delimiter in `s` without a partner, where
+/// loading stops at the first.
+///
+/// Each unclosed `[` or `{` gives an `unclosed-flow` error at the opener,
+/// with `related` where its closer was expected: at a mismatched closer, the
+/// end of the document, or the end of the stream. A closer that matches an
+/// outer collection closes it along with the ones inside; a closer that
+/// matches nothing gives `unmatched-flow-end` and is skipped. Scanning stops
+/// at any other syntax error, reported as `syntax-error`.
+///
+/// ```rust
+/// use yyaml::diagnostics::check_flow_delimiters;
+///
+/// let found = check_flow_delimiters("a: [1, {b: 2]]\n");
+/// let codes: Vec<_> = found.iter().map(|d| d.code).collect();
+/// assert_eq!(codes, ["unclosed-flow", "unmatched-flow-end"]);
+/// assert_eq!(found[0].mark.col(), 7);
+/// assert_eq!(found[0].related.map(|m| m.col()), Some(12));
+/// ```
+#[must_use]
+pub fn check_flow_delimiters(s: &str) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut scanner = Scanner::over_str(s);
+    let unclosed = |open: (Marker, char), expected: Marker| {
+        let (kind, close) = match open.1 {
+            '[' => ("flow sequence", ']'),
+            _ => ("flow mapping", '}'),
+        };
+        Diagnostic {
+            severity: Severity::Error,
+            code: "unclosed-flow",
+            message: format!("{kind} is never closed; expected '{close}'"),
+            mark: open.0,
+            related: Some(expected),
+        }
+    };
+    loop {
+        let level = scanner.flow_level();
+        let token = match scanner.peek_token() {
+            Ok(token) => token,
+            Err(error) if error.info == UNMATCHED_FLOW_END => {
+                diagnostics.push(unmatched_flow_end(error.mark));
+                continue;
+            }
+            Err(error) => {
+                for open in scanner.take_flow_openers() {
+                    diagnostics.push(unclosed(open, error.mark));
+                }
+                diagnostics.push(Diagnostic {
+                    severity: Severity::Error,
+                    code: "syntax-error",
+                    message: error.info,
+                    mark: error.mark,
+                    related: None,
+                });
+                break;
+            }
+        };
+        scanner.skip();
+        match token.1 {
+            // A matching closer lowers the level; any other leaves it
+            TokenType::FlowSequenceEnd | TokenType::FlowMappingEnd
+                if scanner.flow_level() == level =>
+            {
+                let closer = if token.1 == TokenType::FlowSequenceEnd { ']' } else { '}' };
+                match scanner.close_flow_through(closer) {
+                    Some(inner) => {
+                        diagnostics.extend(inner.into_iter().map(|open| unclosed(open, token.0)));
+                    }
+                    None => diagnostics.push(unmatched_flow_end(token.0)),
+                }
+            }
+            TokenType::DocumentStart | TokenType::DocumentEnd | TokenType::StreamEnd => {
+                for open in scanner.take_flow_openers() {
+                    diagnostics.push(unclosed(open, token.0));
+                }
+                if token.1 == TokenType::StreamEnd {
+                    break;
+                }
+            }
+            TokenType::NoToken => break,
+            _ => {}
+        }
+    }
+    diagnostics.sort_by_key(|d| d.mark.index);
+    diagnostics
+}
+
+/// Report every plain scalar in `s` that reads as an integer but does not fit
+/// in an `i64`, as an `integer-overflow` warning. What such a scalar loads as
+/// depends on [`LoaderOptions::integer_overflow`](crate::LoaderOptions::integer_overflow).
+///
+/// ```rust
+/// use yyaml::diagnostics::check_integer_overflow;
+///
+/// let found = check_integer_overflow("ids: [1, 18446744073709551616, !!str 1e999]").unwrap();
+/// assert_eq!(found.len(), 1);
+/// assert_eq!((found[0].code, found[0].mark.col()), ("integer-overflow", 9));
+/// ```
+pub fn check_integer_overflow(s: &str) -> Result<Vec<Diagnostic>, Error> {
+    let mut diagnostics = Vec::new();
+    let mut scanner = Scanner::over_str(s);
+    // Whether the node being read has a tag, which decides its type instead
+    let mut tagged = false;
+    loop {
+        let token = scanner.peek_token()?;
+        scanner.skip();
+        match token.1 {
+            TokenType::Tag(..) => {
+                tagged = true;
+                continue;
+            }
+            TokenType::Anchor(_) => continue,
+            TokenType::Scalar(TScalarStyle::Plain, value, _)
+                if !tagged && overflowing_integer(&value, SchemaMode::Core).is_some() =>
+            {
+                diagnostics.push(Diagnostic {
+                    severity: Severity::Warning,
+                    code: "integer-overflow",
+                    message: format!("integer {value} does not fit in 64 bits"),
+                    mark: token.0,
+                    related: None,
+                });
+            }
+            TokenType::StreamEnd | TokenType::NoToken => break,
+            _ => {}
+        }
+        tagged = false;
+    }
+    Ok(diagnostics)
+}
+
+/// Report every scalar in `s` whose core schema tag does not fit its text,
+/// such as `!!int ten`, as a `tag-mismatch` warning at the scalar, related
+/// to the tag. Such a scalar loads as a [`Yaml::Invalid`](crate::Yaml::Invalid)
+/// node, unless
+/// [`LoaderOptions::strict_tags`](crate::LoaderOptions::strict_tags) makes
+/// it an error.
+///
+/// ```rust
+/// use yyaml::diagnostics::check_tag_resolution;
+///
+/// let found = check_tag_resolution("port: !!int eighty\nok: !!float 1\n").unwrap();
+/// assert_eq!(found.len(), 1);
+/// assert_eq!(found[0].message, "`eighty` is not a valid !!int");
+/// assert_eq!((found[0].mark.col(), found[0].related.map(|m| m.col())), (12, Some(6)));
+/// ```
+pub fn check_tag_resolution(s: &str) -> Result<Vec<Diagnostic>, Error> {
+    let mut diagnostics = Vec::new();
+    let mut scanner = Scanner::over_str(s);
+    // The tag of the node being read, and whether `%TAG` moved `!!` away
+    // from the core schema in this document
+    let mut tag = None;
+    let mut remapped = false;
+    // Whether directives were read since the last document began; a
+    // document opened without any has the core `!!` back
+    let mut header = false;
+    loop {
+        let token = scanner.peek_token()?;
+        scanner.skip();
+        match token.1 {
+            TokenType::Tag(handle, suffix) => {
+                tag = (handle == "!!" && !remapped)
+                    .then(|| (format!("tag:yaml.org,2002:{suffix}"), token.0));
+                continue;
+            }
+            TokenType::Anchor(_) => continue,
+            TokenType::VersionDirective(..) | TokenType::TagDirective(..) | TokenType::Reserved(_) => {
+                if !std::mem::replace(&mut header, true) {
+                    remapped = false;
+                }
+                if matches!(&token.1, TokenType::TagDirective(handle, _) if handle == "!!") {
+                    remapped = true;
+                }
+            }
+            TokenType::DocumentStart => remapped &= std::mem::take(&mut header),
+            TokenType::DocumentEnd => remapped = false,
+            TokenType::Scalar(_, value, _) => {
+                // An implicit key shares a line with its properties, so a
+                // key on a later line follows a tagged empty node
+                let key_below = |scanner: &mut Scanner<_>, at: Marker| {
+                    scanner
+                        .peek_token()
+                        .is_ok_and(|next| next.1 == TokenType::Value && token.0.line != at.line)
+                };
+                if let Some((tag, at)) = &tag
+                    && !key_below(&mut scanner, *at)
+                    && let Some(kind) = core_tag_mismatch(tag, &value)
+                {
+                    diagnostics.push(Diagnostic {
+                        severity: Severity::Warning,
+                        code: "tag-mismatch",
+                        message: kind.to_string(),
+                        mark: token.0,
+                        related: Some(*at),
+                    });
+                }
+            }
+            TokenType::StreamEnd | TokenType::NoToken => break,
+            _ => {}
+        }
+        tag = None;
+    }
+    Ok(diagnostics)
+}
+
+fn unmatched_flow_end(mark: Marker) -> Diagnostic {
+    Diagnostic {
+        severity: Severity::Error,
+        code: "unmatched-flow-end",
+        message: "closing bracket without a flow collection to close".to_string(),
+        mark,
+        related: None,
+    }
+}
+
+/// Anchor usage problem found by [`AnchorLint`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum AnchorFinding<P> {
+    Unused { name: String, at: P },
+    Shadowed { name: String, at: P, previous: P },
+    CrossDocument { name: String, at: P },
+    Undefined { name: String, at: P },
+}
+
+impl AnchorFinding<Marker> {
+    fn into_diagnostic(self) -> Diagnostic {
+        let (severity, code, message, mark, related) = match self {
+            Self::Unused { name, at } => (
+                Severity::Warning,
+                "unused-anchor",
+                format!("anchor '{name}' is never aliased"),
+                at,
+                None,
+            ),
+            Self::Shadowed { name, at, previous } => (
+                Severity::Warning,
+                "shadowed-anchor",
+                format!("anchor '{name}' redefines an earlier anchor of the same name"),
+                at,
+                Some(previous),
+            ),
+            Self::CrossDocument { name, at } => (
+                Severity::Error,
+                "cross-document-alias",
+                format!("alias '{name}' refers to an anchor from a previous document"),
+                at,
+                None,
+            ),
+            Self::Undefined { name, at } => (
+                Severity::Error,
+                "undefined-alias",
+                format!("alias '{name}' does not refer to any anchor"),
+                at,
+                None,
+            ),
+        };
+        Diagnostic {
+            severity,
+            code,
+            message,
+            mark,
+            related,
+        }
+    }
+}
+
+/// Per-document anchor bookkeeping, generic over the position type so the
+/// token-level lint and the AST-level semantic pass share it
+#[derive(Debug)]
+pub(crate) struct AnchorLint<P> {
+    /// Anchors of the current document: definition position and alias count
+    current: HashMap<String, (P, usize)>,
+    /// Definitions in source order
+    order: Vec<(String, P)>,
+    earlier_documents: HashSet<String>,
+    findings: Vec<AnchorFinding<P>>,
+}
+
+impl<P> Default for AnchorLint<P> {
+    fn default() -> Self {
+        Self {
+            current: HashMap::new(),
+            order: Vec::new(),
+            earlier_documents: HashSet::new(),
+            findings: Vec::new(),
+        }
+    }
+}
+
+impl<P: Copy> AnchorLint<P> {
+    pub(crate) fn define(&mut self, name: String, at: P) {
+        if let Some((previous, uses)) = self.current.insert(name.clone(), (at, 0)) {
+            if uses == 0 {
+                self.findings.push(AnchorFinding::Unused {
+                    name: name.clone(),
+                    at: previous,
+                });
+            }
+            self.findings.push(AnchorFinding::Shadowed {
+                name: name.clone(),
+                at,
+                previous,
+            });
+        }
+        self.order.push((name, at));
+    }
+
+    /// Record an alias, returning whether it resolves within the current document
+    pub(crate) fn alias(&mut self, name: &str, at: P) -> bool {
+        if let Some((_, uses)) = self.current.get_mut(name) {
+            *uses += 1;
+            return true;
+        }
+        if self.earlier_documents.contains(name) {
+            self.findings.push(AnchorFinding::CrossDocument {
+                name: name.to_string(),
+                at,
+            });
+        } else {
+            self.findings.push(AnchorFinding::Undefined {
+                name: name.to_string(),
+                at,
+            });
+        }
+        false
+    }
+
+    pub(crate) fn end_document(&mut self) {
+        let mut unused = Vec::new();
+        // The last definition of a name is the live one; shadowed ones were handled in `define`
+        for (name, at) in self.order.drain(..).rev() {
+            if let Some((_, uses)) = self.current.remove(&name) {
+                if uses == 0 {
+                    unused.push(AnchorFinding::Unused {
+                        name: name.clone(),
+                        at,
+                    });
+                }
+                self.earlier_documents.insert(name);
+            }
+        }
+        self.findings.extend(unused.into_iter().rev());
+    }
+
+    #[cfg(feature = "semantic")]
+    pub(crate) fn findings(&self) -> &[AnchorFinding<P>] {
+        &self.findings
+    }
+
+    pub(crate) fn into_findings(self) -> Vec<AnchorFinding<P>> {
+        self.findings
+    }
+}
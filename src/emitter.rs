@@ -1,20 +1,201 @@
+use crate::events::Event;
 use crate::linked_hash_map::LinkedHashMap;
-use crate::yaml::Yaml;
+use crate::parser::loader::scalar_node;
+use crate::pointer::{UNNAMED_KEY, key_name};
+use crate::scalars::{ChompingMode, refold};
+use crate::ser::YamlSerializer;
+use crate::tags::{SET, STR};
+use crate::value::{Value, is_set_content};
+use crate::yaml::{BadValueKind, Yaml, parse_f64};
+use std::collections::HashMap;
+use serde::Serialize;
 use std::error::Error;
-use std::fmt;
+use std::fmt::Write as _;
+use std::{fmt, io};
 
 /// An Emitter for Yaml => String, with anchors etc.
+///
+/// Writes to any [`fmt::Write`] with [`new`](Self::new), or streams to any
+/// [`io::Write`] such as a file or socket with [`from_io`](Self::from_io).
 pub struct YamlEmitter<'a> {
-    writer: &'a mut dyn fmt::Write,
+    writer: Sink<'a>,
     pub best_indent: usize,
     pub compact: bool,
+    /// When set, a collection of at least this many nodes that appears more
+    /// than once is written in full the first time, with an anchor, and as
+    /// an alias everywhere after
+    pub dedup_threshold: Option<usize>,
+    /// Anchors and aliases placed exactly where the caller wants them;
+    /// takes precedence over `dedup_threshold`
+    pub anchor_plan: Option<AnchorPlan>,
+    /// When set, string values that span several lines or are longer than
+    /// this many characters are written as folded block scalars wrapped at
+    /// this width
+    pub fold_width: Option<usize>,
+    /// How null is written, `~` by default; strings spelled the same way
+    /// are quoted
+    pub null_word: &'static str,
+    /// Write a real whose text reads as an integer, such as `1`, as it is;
+    /// by default it gets a `.0` so it reads back as a float
+    pub whole_floats_as_integers: bool,
+    /// Write multi-line string values as literal block scalars
+    literal_blocks: bool,
+    /// The output so far ends in a block scalar that keeps its final line
+    /// break, so the line must be ended before the document is
+    block_open: bool,
     level: isize,
+    anchors: HashMap<*const Yaml, Anchor>,
+    /// Path segment and kind of each node being written, outermost first
+    trail: Vec<(String, &'static str)>,
+    /// Collections opened by [`emit_event`](Self::emit_event) and not yet
+    /// closed, outermost first
+    open: Vec<OpenCollection>,
+    /// Kind of the root node of the document being written from events,
+    /// once it has begun
+    root: Option<&'static str>,
+}
+
+/// A collection [`YamlEmitter::emit_event`] is writing
+struct OpenCollection {
+    mapping: bool,
+    /// Nesting level of its entries
+    level: isize,
+    /// Whether its first entry starts on a line of its own
+    break_first: bool,
+    /// Nodes begun in it so far, keys and values alike
+    nodes: usize,
+    /// Whether the key of the current entry was written with `?`
+    explicit_key: bool,
+}
+
+/// Where output goes; always written through as `fmt::Write`, keeping the
+/// underlying `io::Error` so it is not lost to `fmt::Error`
+struct Sink<'a> {
+    target: Target<'a>,
+    written: usize,
+    io_error: Option<io::Error>,
+}
+
+enum Target<'a> {
+    Fmt(&'a mut dyn fmt::Write),
+    Io(&'a mut dyn io::Write),
+}
+
+impl fmt::Write for Sink<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        match &mut self.target {
+            Target::Fmt(w) => w.write_str(s)?,
+            Target::Io(w) => w.write_all(s.as_bytes()).map_err(|e| {
+                self.io_error = Some(e);
+                fmt::Error
+            })?,
+        }
+        self.written += s.len();
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Anchor {
+    Define(String),
+    Alias(String),
+}
+
+/// Where the emitter writes `&anchor`s and `*alias`es, chosen by the caller.
+///
+/// Nodes are picked by JSON Pointer path (see [`Yaml::pointer`]) or by
+/// identity, as a reference into the document about to be emitted. An
+/// alias must come after its anchor in the output and stand for a node
+/// equal to the anchored one, so the document reads back unchanged;
+/// [`YamlEmitter::check`] reports plans that break either rule.
+///
+/// ```rust
+/// use yyaml::{AnchorPlan, YamlEmitter, YamlLoader};
+///
+/// let doc = &YamlLoader::load_from_str("base: {retries: 3}\nprod: {retries: 3}\n").unwrap()[0];
+/// let mut out = String::new();
+/// let mut emitter = YamlEmitter::new(&mut out);
+/// emitter.anchor_plan = Some(AnchorPlan::new().anchor("/base", "defaults").alias("/prod", "defaults"));
+/// emitter.dump(doc).unwrap();
+/// assert_eq!(out, "---\nbase: &defaults\n  retries: 3\nprod: *defaults");
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct AnchorPlan {
+    entries: Vec<(PlanTarget, Anchor)>,
+}
+
+#[derive(Clone, Debug)]
+enum PlanTarget {
+    Path(String),
+    /// Address of a node in the document
+    Node(usize),
+}
+
+impl AnchorPlan {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Write the node at `path` with `&name`
+    #[must_use]
+    pub fn anchor(mut self, path: impl Into<String>, name: impl Into<String>) -> Self {
+        self.entries
+            .push((PlanTarget::Path(path.into()), Anchor::Define(name.into())));
+        self
+    }
+
+    /// Write the node at `path` as `*name`
+    #[must_use]
+    pub fn alias(mut self, path: impl Into<String>, name: impl Into<String>) -> Self {
+        self.entries
+            .push((PlanTarget::Path(path.into()), Anchor::Alias(name.into())));
+        self
+    }
+
+    /// Write `node`, a node of the emitted document, with `&name`
+    #[must_use]
+    pub fn anchor_node(mut self, node: &Yaml, name: impl Into<String>) -> Self {
+        let target = PlanTarget::Node(std::ptr::from_ref(node).addr());
+        self.entries.push((target, Anchor::Define(name.into())));
+        self
+    }
+
+    /// Write `node`, a node of the emitted document, as `*name`
+    #[must_use]
+    pub fn alias_node(mut self, node: &Yaml, name: impl Into<String>) -> Self {
+        let target = PlanTarget::Node(std::ptr::from_ref(node).addr());
+        self.entries.push((target, Anchor::Alias(name.into())));
+        self
+    }
 }
 
 #[derive(Debug)]
 pub enum EmitError {
     FmtError(fmt::Error),
     BadHashmapKey,
+    /// The writer failed while the node at `path` was being written
+    Write {
+        /// Dotted path from the document root, e.g. `servers[1].port`;
+        /// empty for the root itself
+        path: String,
+        kind: &'static str,
+        source: fmt::Error,
+    },
+    /// An `io::Write` target failed while the node at `path` was being
+    /// written
+    Io {
+        path: String,
+        /// Bytes accepted by the target before the failure
+        written: usize,
+        source: io::Error,
+    },
+    /// The node at `path` has no YAML representation
+    InvalidNode {
+        path: String,
+        kind: &'static str,
+        reason: String,
+    },
 }
 
 impl From<fmt::Error> for EmitError {
@@ -28,44 +209,373 @@ impl fmt::Display for EmitError {
         match self {
             Self::FmtError(e) => write!(f, "format error: {e}"),
             Self::BadHashmapKey => write!(f, "bad hashmap key"),
+            Self::Write { path, kind, source } => {
+                write!(f, "format error writing {kind} at {}: {source}", display_path(path))
+            }
+            Self::Io { path, written, source } => write!(
+                f,
+                "io error at {} after {written} bytes: {source}",
+                display_path(path)
+            ),
+            Self::InvalidNode { path, kind, reason } => {
+                write!(f, "cannot emit {kind} at {}: {reason}", display_path(path))
+            }
+        }
+    }
+}
+
+impl Error for EmitError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::FmtError(e) | Self::Write { source: e, .. } => Some(e),
+            Self::Io { source, .. } => Some(source),
+            _ => None,
         }
     }
 }
 
-impl Error for EmitError {}
+fn display_path(path: &str) -> String {
+    if path.is_empty() {
+        "document root".to_owned()
+    } else {
+        format!("`{path}`")
+    }
+}
 
 pub type EmitResult = Result<(), EmitError>;
 
 impl<'a> YamlEmitter<'a> {
     pub fn new(writer: &'a mut dyn fmt::Write) -> Self {
+        Self::with_target(Target::Fmt(writer))
+    }
+
+    /// Stream output to `writer`; each piece is written as it is produced,
+    /// so wrap unbuffered targets in an [`io::BufWriter`]
+    pub fn from_io(writer: &'a mut dyn io::Write) -> Self {
+        Self::with_target(Target::Io(writer))
+    }
+
+    fn with_target(target: Target<'a>) -> Self {
         YamlEmitter {
-            writer,
+            writer: Sink {
+                target,
+                written: 0,
+                io_error: None,
+            },
             best_indent: 2,
             compact: true,
+            dedup_threshold: None,
+            anchor_plan: None,
+            fold_width: None,
+            null_word: "~",
+            whole_floats_as_integers: false,
+            literal_blocks: false,
+            block_open: false,
             level: -1,
+            anchors: HashMap::new(),
+            trail: Vec::new(),
+            open: Vec::new(),
+            root: None,
         }
     }
 
     pub fn dump(&mut self, doc: &Yaml) -> EmitResult {
-        writeln!(self.writer, "---")?;
-        self.level = -1;
-        self.emit_node(doc)?;
+        let anchors = self.prepare(doc)?;
+        if let Err(e) = writeln!(self.writer, "---") {
+            return Err(self.locate(e.into(), node_kind(doc)));
+        }
+        self.write_document(doc, anchors)
+    }
+
+    /// Write `value` as a block-style fragment for splicing into an
+    /// existing file: no document marker, every line indented by
+    /// `indent_level` levels of two spaces, multi-line strings as literal
+    /// block scalars, and a final line break.
+    ///
+    /// ```rust
+    /// use yyaml::{Value, YamlEmitter};
+    ///
+    /// let value: Value = yyaml::from_str("script: \"make\\nmake test\\n\"").unwrap();
+    /// let fragment = YamlEmitter::emit_fragment(&value, 1).unwrap();
+    /// assert_eq!(fragment, "  script: |\n    make\n    make test\n");
+    /// ```
+    pub fn emit_fragment(value: &Value, indent_level: usize) -> Result<String, crate::Error> {
+        let yaml = value.serialize(YamlSerializer::new())?;
+        let mut body = String::new();
+        let mut emitter = YamlEmitter::new(&mut body);
+        emitter.literal_blocks = true;
+        let indent = " ".repeat(indent_level * emitter.best_indent);
+        emitter.emit(&yaml)?;
+
+        let mut out = String::with_capacity(body.len() + indent.len());
+        // A block scalar at the end already ended the last line
+        for line in body.strip_suffix('\n').unwrap_or(&body).split('\n') {
+            // Blank lines of a block scalar stay empty
+            if !line.is_empty() {
+                out.push_str(&indent);
+                out.push_str(line);
+            }
+            out.push('\n');
+        }
+        Ok(out)
+    }
+
+    /// End the current line, as after a document in a stream, unless the
+    /// document already ended it
+    pub(crate) fn write_line_break(&mut self) -> EmitResult {
+        if !std::mem::take(&mut self.block_open) {
+            writeln!(self.writer)?;
+        }
         Ok(())
     }
 
+    /// Total bytes written so far
+    pub const fn bytes_written(&self) -> usize {
+        self.writer.written
+    }
+
+    /// Write `doc`, or fail with the first problem [`check`](Self::check)
+    /// finds before anything is written
     pub fn emit(&mut self, doc: &Yaml) -> EmitResult {
+        let anchors = self.prepare(doc)?;
+        self.write_document(doc, anchors)
+    }
+
+    fn write_document(&mut self, doc: &Yaml, anchors: HashMap<*const Yaml, Anchor>) -> EmitResult {
         self.level = -1;
-        self.emit_node(doc)?;
+        self.anchors = anchors;
+        self.trail.clear();
+        self.block_open = false;
+        self.emit_node(doc).map_err(|e| self.locate(e, node_kind(doc)))?;
+        // Without its line break the last line of a block scalar would read
+        // back one break short
+        if self.block_open {
+            writeln!(self.writer).map_err(|e| self.locate(e.into(), node_kind(doc)))?;
+        }
         Ok(())
     }
 
+    /// Attach the path of the node being written to a bare write failure;
+    /// `root` is the kind of the document's root node
+    fn locate(&mut self, e: EmitError, root: &'static str) -> EmitError {
+        let EmitError::FmtError(source) = e else {
+            return e;
+        };
+        let path = join_path(self.trail.iter().map(|(segment, _)| segment.as_str()));
+        match self.writer.io_error.take() {
+            Some(source) => EmitError::Io {
+                path,
+                written: self.writer.written,
+                source,
+            },
+            None => EmitError::Write {
+                path,
+                kind: self.trail.last().map_or(root, |&(_, kind)| kind),
+                source,
+            },
+        }
+    }
+
+    /// Write the document an event describes a piece of, as
+    /// [`dump`](Self::dump) writes the tree the events build, without
+    /// building it: scalars resolve as a [`YamlReceiver`](crate::YamlReceiver)
+    /// resolves them and each document ends with a line break.
+    ///
+    /// Nothing is checked ahead, so an alias, a scalar whose core schema tag
+    /// does not fit it or a document without a root fails after the
+    /// document has been partly written. Anchors, collection tags and the
+    /// anchor options do not apply.
+    pub(crate) fn emit_event(&mut self, event: Event) -> EmitResult {
+        let root = self.root.unwrap_or("null");
+        self.write_event(event).map_err(|e| self.locate(e, root))
+    }
+
+    fn write_event(&mut self, event: Event) -> EmitResult {
+        match event {
+            Event::DocumentStart => {
+                self.level = -1;
+                self.trail.clear();
+                self.open.clear();
+                self.root = None;
+                self.block_open = false;
+                writeln!(self.writer, "---")?;
+            }
+            Event::DocumentEnd => {
+                if self.root.is_none() {
+                    return Err(self.invalid_here("bad value", BadValueKind::EmptyDocument.to_string()));
+                }
+                self.block_open = false;
+                writeln!(self.writer)?;
+            }
+            Event::Scalar(value, style, _, tag, _) => {
+                let node = scalar_node(value, style, tag.as_ref());
+                let (explicit, kind) = match &node {
+                    Ok(node) => (needs_explicit_key(node), node_kind(node)),
+                    Err(_) => (false, "bad value"),
+                };
+                let segment = node.as_ref().ok().map(key_name);
+                let inline = self.begin_node(kind, explicit, segment)?;
+                let node = node.map_err(|reason| self.invalid_here(kind, reason.to_string()))?;
+                match inline {
+                    Some(inline) => self.emit_val(inline, &node)?,
+                    None => self.emit_node(&node)?,
+                }
+                self.end_node();
+            }
+            Event::SequenceStart(..) | Event::MappingStart(..) => {
+                let mapping = matches!(event, Event::MappingStart(..));
+                let kind = if mapping { "mapping" } else { "sequence" };
+                let inline = self.begin_node(kind, true, None)?;
+                self.open.push(OpenCollection {
+                    mapping,
+                    level: self.level + 1,
+                    break_first: inline.is_some_and(|inline| !(inline && self.compact)),
+                    nodes: 0,
+                    explicit_key: false,
+                });
+            }
+            Event::SequenceEnd | Event::MappingEnd => {
+                if let Some(open) = self.open.pop() {
+                    if open.nodes == 0 {
+                        write!(self.writer, "{}", if open.mapping { "{}" } else { "[]" })?;
+                    }
+                    self.level = open.level - 1;
+                    self.end_node();
+                }
+            }
+            Event::Alias(_) => {
+                self.begin_node("alias", false, None)?;
+                let reason = "aliases must be resolved before emitting".to_owned();
+                return Err(self.invalid_here("alias", reason));
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Write what comes before a node of `kind` in the innermost open
+    /// collection and enter it in the trail. Returns `None` for a node
+    /// written as [`emit_node`](Self::emit_node) writes it, the root or a
+    /// key, and for a value or item whether it starts on the line of its
+    /// indicator, as [`emit_val`](Self::emit_val) takes it. `explicit` is
+    /// whether the node as a key needs `?`, and `segment` its path segment.
+    fn begin_node(
+        &mut self,
+        kind: &'static str,
+        explicit: bool,
+        segment: Option<String>,
+    ) -> Result<Option<bool>, EmitError> {
+        let Some(open) = self.open.last_mut() else {
+            self.level = -1;
+            self.root = Some(kind);
+            return Ok(None);
+        };
+        let (mapping, nodes, explicit_key) = (open.mapping, open.nodes, open.explicit_key);
+        let break_first = open.break_first;
+        open.nodes += 1;
+        if !mapping || nodes % 2 == 0 {
+            open.explicit_key = mapping && explicit;
+        }
+        self.level = open.level;
+        if nodes == 0 && break_first {
+            writeln!(self.writer)?;
+            self.write_indent()?;
+        }
+        if !mapping {
+            if nodes > 0 {
+                writeln!(self.writer)?;
+                self.write_indent()?;
+            }
+            self.write_indicator('-')?;
+            self.trail.push((format!("[{}]", nodes), kind));
+            return Ok(Some(true));
+        }
+        if nodes % 2 == 0 {
+            if nodes > 0 {
+                writeln!(self.writer)?;
+                self.write_indent()?;
+            }
+            if explicit {
+                self.write_indicator('?')?;
+            }
+            let segment = segment.unwrap_or_else(|| UNNAMED_KEY.to_owned());
+            self.trail.push((segment, kind));
+            return Ok(None);
+        }
+        if let Some((_, entry)) = self.trail.last_mut() {
+            *entry = kind;
+        }
+        if explicit_key {
+            writeln!(self.writer)?;
+            self.write_indent()?;
+            self.write_indicator(':')?;
+            return Ok(Some(true));
+        }
+        write!(self.writer, ": ")?;
+        Ok(Some(false))
+    }
+
+    /// Leave the trail entry of a node just written, unless it is a key
+    /// whose value comes next
+    fn end_node(&mut self) {
+        match self.open.last() {
+            Some(open) if open.mapping && open.nodes % 2 == 1 => {}
+            Some(_) => {
+                self.trail.pop();
+            }
+            None => {}
+        }
+    }
+
+    /// A node of `kind` at the end of the trail that has no representation
+    fn invalid_here(&self, kind: &'static str, reason: String) -> EmitError {
+        EmitError::InvalidNode {
+            path: join_path(self.trail.iter().map(|(segment, _)| segment.as_str())),
+            kind,
+            reason,
+        }
+    }
+
+    /// Every node in `doc` that cannot be emitted: unresolved aliases,
+    /// [`Yaml::BadValue`] and [`Yaml::Invalid`] nodes, reals that do not parse as a float, and nodes
+    /// the [`AnchorPlan`] cannot place
+    pub fn check(&self, doc: &Yaml) -> Vec<EmitError> {
+        let mut problems = Vec::new();
+        find_invalid(doc, &mut Vec::new(), &mut |problem| {
+            problems.push(problem);
+            true
+        });
+        if let Some(Err(problem)) = self.anchor_plan.as_ref().map(|plan| resolve_plan(doc, plan)) {
+            problems.push(problem);
+        }
+        problems
+    }
+
+    /// The anchors to write `doc` with, or the first problem
+    /// [`check`](Self::check) would report
+    fn prepare(&self, doc: &Yaml) -> Result<HashMap<*const Yaml, Anchor>, EmitError> {
+        let mut first = None;
+        find_invalid(doc, &mut Vec::new(), &mut |problem| {
+            first = Some(problem);
+            false
+        });
+        if let Some(problem) = first {
+            return Err(problem);
+        }
+        Ok(match (&self.anchor_plan, self.dedup_threshold) {
+            (Some(plan), _) => resolve_plan(doc, plan)?,
+            (None, Some(threshold)) => plan_anchors(doc, threshold),
+            (None, None) => HashMap::new(),
+        })
+    }
+
     fn emit_node(&mut self, node: &Yaml) -> EmitResult {
         match node {
             Yaml::Array(v) => self.emit_array(v),
             Yaml::Hash(h) => self.emit_hash(h),
             Yaml::String(s) => {
-                if need_quotes(s) {
-                    escape_str(self.writer, s)?;
+                if need_quotes(s) || s == self.null_word {
+                    escape_str(&mut self.writer, s)?;
                 } else {
                     write!(self.writer, "{s}")?;
                 }
@@ -81,20 +591,55 @@ impl<'a> YamlEmitter<'a> {
             }
             Yaml::Real(s) => {
                 write!(self.writer, "{s}")?;
+                // Only decimal digits take a point; `0x10.0` is not a float
+                let digits = s.strip_prefix(['+', '-']).unwrap_or(s);
+                if !self.whole_floats_as_integers
+                    && digits.bytes().all(|b| b.is_ascii_digit())
+                    && matches!(Yaml::parse_str(s), Yaml::Integer(_))
+                {
+                    write!(self.writer, ".0")?;
+                }
                 Ok(())
             }
-            Yaml::Null | Yaml::BadValue => {
-                write!(self.writer, "~")?;
-                Ok(())
-            }
-            Yaml::Alias(_) => {
-                // If we had anchor references, we'd store them. For demonstration, we skip.
+            Yaml::Null | Yaml::BadValue | Yaml::Invalid(_) => {
+                write!(self.writer, "{}", self.null_word)?;
                 Ok(())
             }
+            // Rejected by `check` before writing starts
+            Yaml::Alias(_) => Ok(()),
             Yaml::Tagged(tag, value) => {
-                // Emit tagged value with tag prefix
-                write!(self.writer, "{} ", tag)?;
-                self.emit_node(value)
+                // Spell the tag as loading resolved it: `!!` for core schema
+                // tags, verbatim for other URIs, `!` before a local name
+                match tag.strip_prefix("tag:yaml.org,2002:") {
+                    Some(name) => write!(self.writer, "!!{name}")?,
+                    None if tag.starts_with('!') => write!(self.writer, "{tag}")?,
+                    None if tag.contains(':') => write!(self.writer, "!<{tag}>")?,
+                    None => write!(self.writer, "!{tag}")?,
+                }
+                // A block collection starts on the line after its tag
+                let block = match value.as_ref() {
+                    Yaml::Array(a) => !a.is_empty(),
+                    Yaml::Hash(h) => !h.is_empty(),
+                    _ => false,
+                };
+                if !block {
+                    write!(self.writer, " ")?;
+                    return match value.as_ref() {
+                        Yaml::String(s) if tag == STR && !need_quotes_under_str_tag(s) => {
+                            write!(self.writer, "{s}")?;
+                            Ok(())
+                        }
+                        _ => self.emit_node(value),
+                    };
+                }
+                writeln!(self.writer)?;
+                self.level += 1;
+                self.write_indent()?;
+                self.level -= 1;
+                match value.as_ref() {
+                    Yaml::Hash(h) if tag == SET && is_set_content(h) => self.emit_set(h),
+                    _ => self.emit_node(value),
+                }
             }
         }
     }
@@ -109,8 +654,10 @@ impl<'a> YamlEmitter<'a> {
                     writeln!(self.writer)?;
                     self.write_indent()?;
                 }
-                write!(self.writer, "- ")?;
+                self.write_indicator('-')?;
+                self.trail.push((format!("[{i}]"), node_kind(val)));
                 self.emit_val(true, val)?;
+                self.trail.pop();
             }
             self.level -= 1;
         }
@@ -130,26 +677,65 @@ impl<'a> YamlEmitter<'a> {
                 } else {
                     first = false;
                 }
-                if matches!(k, Yaml::Array(_) | Yaml::Hash(_)) {
+                self.trail.push((key_name(k), node_kind(k)));
+                if needs_explicit_key(k) {
                     // complex key
-                    write!(self.writer, "? ")?;
+                    self.write_indicator('?')?;
                     self.emit_node(k)?;
                     writeln!(self.writer)?;
                     self.write_indent()?;
-                    write!(self.writer, ": ")?;
+                    self.write_indicator(':')?;
+                    self.enter_value(v);
                     self.emit_val(true, v)?;
                 } else {
                     self.emit_node(k)?;
                     write!(self.writer, ": ")?;
+                    self.enter_value(v);
                     self.emit_val(false, v)?;
                 }
+                self.trail.pop();
             }
             self.level -= 1;
         }
         Ok(())
     }
 
-    fn emit_val(&mut self, inline: bool, val: &Yaml) -> EmitResult {
+    /// Write the members of a `!!set` as explicit keys without values
+    fn emit_set(&mut self, h: &LinkedHashMap<Yaml, Yaml>) -> EmitResult {
+        self.level += 1;
+        for (i, (member, _)) in h.iter().enumerate() {
+            if i > 0 {
+                writeln!(self.writer)?;
+                self.write_indent()?;
+            }
+            self.trail.push((key_name(member), node_kind(member)));
+            self.write_indicator('?')?;
+            self.emit_node(member)?;
+            self.trail.pop();
+        }
+        self.level -= 1;
+        Ok(())
+    }
+
+    fn emit_val(&mut self, mut inline: bool, val: &Yaml) -> EmitResult {
+        self.block_open = false;
+        match self.anchors.get(&std::ptr::from_ref(val)) {
+            Some(Anchor::Alias(name)) => {
+                write!(self.writer, "*{name}")?;
+                return Ok(());
+            }
+            Some(Anchor::Define(name)) => {
+                // `- &a k: v` would anchor the key, so a collection starts
+                // on its own line
+                write!(self.writer, "&{name}")?;
+                match val {
+                    Yaml::Array(a) if !a.is_empty() => inline = false,
+                    Yaml::Hash(h) if !h.is_empty() => inline = false,
+                    _ => write!(self.writer, " ")?,
+                }
+            }
+            None => {}
+        }
         match val {
             Yaml::Array(a) => {
                 if (inline && self.compact) || a.is_empty() {
@@ -173,10 +759,69 @@ impl<'a> YamlEmitter<'a> {
                 }
                 self.emit_hash(h)
             }
+            Yaml::String(s) if self.fold_width.is_some_and(|width| fits_folded(s, width)) => {
+                self.emit_folded(s)
+            }
+            Yaml::String(s) if self.literal_blocks && fits_literal(s) => self.emit_literal(s),
             _ => self.emit_node(val),
         }
     }
 
+    /// Write `s` as a literal block scalar one level deeper than the
+    /// current node, choosing the chomping indicator that keeps its
+    /// trailing line breaks
+    fn emit_literal(&mut self, s: &str) -> EmitResult {
+        let (indicator, chomping) = block_chomping(s);
+        write!(self.writer, "|{indicator}")?;
+        let content = s.trim_end_matches('\n');
+        let breaks = (s.len() - content.len()).max(1);
+        self.write_block_body(&format!("{content}{}", "\n".repeat(breaks)))?;
+        self.block_open = chomping != ChompingMode::Strip;
+        Ok(())
+    }
+
+    /// Write `s` as a folded block scalar wrapped at `fold_width`, like
+    /// [`emit_literal`](Self::emit_literal)
+    fn emit_folded(&mut self, s: &str) -> EmitResult {
+        let (indicator, chomping) = block_chomping(s);
+        write!(self.writer, ">{indicator}")?;
+        let width = self.fold_width.unwrap_or(usize::MAX);
+        self.write_block_body(&refold(s, width, chomping))?;
+        self.block_open = chomping != ChompingMode::Strip;
+        Ok(())
+    }
+
+    /// Write the lines of a block scalar body, each ending in a line break,
+    /// one level deeper than the current node; the last break is left to
+    /// whatever follows
+    fn write_block_body(&mut self, body: &str) -> EmitResult {
+        let depth = usize::try_from(self.level + 1).unwrap_or(0).max(1);
+        let indent = " ".repeat(depth * self.best_indent);
+        for line in body.split_terminator('\n') {
+            writeln!(self.writer)?;
+            if !line.is_empty() {
+                write!(self.writer, "{indent}{line}")?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Switch the innermost trail entry from a mapping key to its value
+    fn enter_value(&mut self, value: &Yaml) {
+        if let Some((_, kind)) = self.trail.last_mut() {
+            *kind = node_kind(value);
+        }
+    }
+
+    /// Write a `-`, `?` or `:` indicator padded to the indent width, so a
+    /// collection that starts on the same line lines up with the lines
+    /// after it
+    fn write_indicator(&mut self, indicator: char) -> EmitResult {
+        let pad = self.best_indent.max(2) - 1;
+        write!(self.writer, "{indicator}{:pad$}", "")?;
+        Ok(())
+    }
+
     fn write_indent(&mut self) -> EmitResult {
         if self.level <= 0 {
             return Ok(());
@@ -190,27 +835,345 @@ impl<'a> YamlEmitter<'a> {
     }
 }
 
-/// Return whether a string definitely needs quotes in YAML.
-fn need_quotes(s: &str) -> bool {
-    fn need_quotes_spaces(s: &str) -> bool {
-        s.starts_with(' ') || s.ends_with(' ')
+/// Choose which repeated collections of at least `threshold` nodes get an
+/// anchor and which occurrences become aliases, keyed by node address.
+///
+/// Only mapping values and sequence items are considered, and the walk
+/// follows emission order so every anchor is written before its aliases.
+fn plan_anchors(doc: &Yaml, threshold: usize) -> HashMap<*const Yaml, Anchor> {
+    fn children(node: &Yaml) -> Box<dyn Iterator<Item = &Yaml> + '_> {
+        match node {
+            Yaml::Array(items) => Box::new(items.iter()),
+            Yaml::Hash(map) => Box::new(map.iter().map(|(_, v)| v)),
+            _ => Box::new(std::iter::empty()),
+        }
     }
-    if s.is_empty() {
-        return true;
+
+    // Occurrence count and node count of each distinct collection; copies
+    // after the first are not descended into, since they will be aliases
+    fn count<'d>(node: &'d Yaml, seen: &mut HashMap<&'d Yaml, (usize, usize)>) -> usize {
+        if !matches!(node, Yaml::Array(_) | Yaml::Hash(_)) {
+            return 1;
+        }
+        if let Some((occurrences, size)) = seen.get_mut(node) {
+            *occurrences += 1;
+            return *size;
+        }
+        let size = 1 + children(node).map(|child| count(child, seen)).sum::<usize>();
+        seen.insert(node, (1, size));
+        size
     }
-    if need_quotes_spaces(s) {
-        return true;
+
+    fn assign<'d>(
+        node: &'d Yaml,
+        seen: &HashMap<&'d Yaml, (usize, usize)>,
+        ids: &mut HashMap<&'d Yaml, usize>,
+        plan: &mut HashMap<*const Yaml, Anchor>,
+        threshold: usize,
+    ) {
+        if let Some(&(occurrences, size)) = seen.get(node)
+            && occurrences > 1
+            && size >= threshold
+        {
+            if let Some(&id) = ids.get(node) {
+                plan.insert(node, Anchor::Alias(format!("id{id:03}")));
+                return;
+            }
+            let id = ids.len() + 1;
+            ids.insert(node, id);
+            plan.insert(node, Anchor::Define(format!("id{id:03}")));
+        }
+        for child in children(node) {
+            assign(child, seen, ids, plan, threshold);
+        }
+    }
+
+    let mut seen = HashMap::new();
+    for child in children(doc) {
+        count(child, &mut seen);
     }
-    if s.parse::<i64>().is_ok() {
+    let mut plan = HashMap::new();
+    let mut ids = HashMap::new();
+    for child in children(doc) {
+        assign(child, &seen, &mut ids, &mut plan, threshold);
+    }
+    plan
+}
+
+/// Resolve `plan` against `doc`, keyed by node address like
+/// [`plan_anchors`], checking that every alias follows an equal anchored
+/// node in emission order
+fn resolve_plan(doc: &Yaml, plan: &AnchorPlan) -> Result<HashMap<*const Yaml, Anchor>, EmitError> {
+    fn invalid(path: &str, kind: &'static str, reason: String) -> EmitError {
+        EmitError::InvalidNode {
+            path: path.to_owned(),
+            kind,
+            reason,
+        }
+    }
+
+    // Only sequence items and mapping values carry properties when emitted
+    fn walk<'d>(
+        node: &'d Yaml,
+        path: &mut Vec<String>,
+        targets: &mut HashMap<usize, Anchor>,
+        defined: &mut HashMap<String, &'d Yaml>,
+        anchors: &mut HashMap<*const Yaml, Anchor>,
+    ) -> Result<(), EmitError> {
+        let children: Vec<(String, &Yaml)> = match node {
+            Yaml::Array(items) => items.iter().enumerate().map(|(i, v)| (format!("[{i}]"), v)).collect(),
+            Yaml::Hash(map) => map.iter().map(|(k, v)| (key_name(k), v)).collect(),
+            _ => Vec::new(),
+        };
+        for (segment, child) in children {
+            path.push(segment);
+            let here = join_path(path.iter().map(String::as_str));
+            match targets.remove(&std::ptr::from_ref(child).addr()) {
+                Some(Anchor::Alias(name)) => {
+                    match defined.get(&name) {
+                        None => {
+                            let reason = format!("alias `*{name}` is not preceded by its anchor");
+                            return Err(invalid(&here, node_kind(child), reason));
+                        }
+                        Some(anchored) if *anchored != child => {
+                            let reason = format!("node differs from the one anchored as `&{name}`");
+                            return Err(invalid(&here, node_kind(child), reason));
+                        }
+                        Some(_) => {}
+                    }
+                    anchors.insert(child, Anchor::Alias(name));
+                }
+                Some(Anchor::Define(name)) => {
+                    defined.insert(name.clone(), child);
+                    anchors.insert(child, Anchor::Define(name));
+                    walk(child, path, targets, defined, anchors)?;
+                }
+                None => walk(child, path, targets, defined, anchors)?,
+            }
+            path.pop();
+        }
+        Ok(())
+    }
+
+    let mut targets = HashMap::new();
+    for (target, anchor) in &plan.entries {
+        let (Anchor::Define(name) | Anchor::Alias(name)) = anchor;
+        if name.is_empty() || !name.chars().all(is_anchor_char) {
+            return Err(invalid("", "anchor", format!("`{name}` is not a valid anchor name")));
+        }
+        let address = match target {
+            PlanTarget::Path(path) if path.is_empty() => None,
+            PlanTarget::Path(path) => match doc.pointer(path) {
+                Some(node) => Some(std::ptr::from_ref(node).addr()),
+                None => {
+                    let reason = "no node at this path".to_owned();
+                    return Err(invalid(&dotted_path(doc, path), "anchor", reason));
+                }
+            },
+            PlanTarget::Node(address) => Some(*address),
+        };
+        if address.is_none_or(|a| a == std::ptr::from_ref(doc).addr()) {
+            let reason = "the document root cannot carry an anchor or alias".to_owned();
+            return Err(invalid("", node_kind(doc), reason));
+        }
+        if let Some(address) = address {
+            targets.insert(address, anchor.clone());
+        }
+    }
+    let mut anchors = HashMap::new();
+    walk(doc, &mut Vec::new(), &mut targets, &mut HashMap::new(), &mut anchors)?;
+    if !targets.is_empty() {
+        let reason = "node is not a sequence item or mapping value of the document".to_owned();
+        return Err(invalid("", "anchor", reason));
+    }
+    Ok(anchors)
+}
+
+/// [102] ns-anchor-char: a non-space character other than a flow indicator
+const fn is_anchor_char(ch: char) -> bool {
+    crate::chars::is_ns_char(ch) && !crate::chars::is_flow_indicator(ch)
+}
+
+/// Name of `node`'s variant, as reported in [`EmitError`]
+fn node_kind(node: &Yaml) -> &'static str {
+    match node {
+        Yaml::Real(_) => "real",
+        Yaml::Integer(_) => "integer",
+        Yaml::String(_) => "string",
+        Yaml::Boolean(_) => "boolean",
+        Yaml::Array(_) => "sequence",
+        Yaml::Hash(_) => "mapping",
+        Yaml::Alias(_) => "alias",
+        Yaml::Tagged(..) => "tagged value",
+        Yaml::Null => "null",
+        Yaml::BadValue | Yaml::Invalid(_) => "bad value",
+    }
+}
+
+
+/// A step from a node to one of its children, kept borrowed so a path is
+/// only spelled out for a node with a problem
+enum Step<'y> {
+    Index(usize),
+    Key(&'y Yaml),
+}
+
+/// Report each node under `node` that cannot be emitted to `found`, which
+/// returns whether to keep looking
+fn find_invalid<'y>(
+    node: &'y Yaml,
+    steps: &mut Vec<Step<'y>>,
+    found: &mut dyn FnMut(EmitError) -> bool,
+) -> bool {
+    let reason = match node {
+        Yaml::Alias(_) => Some("aliases must be resolved before emitting".to_owned()),
+        Yaml::BadValue => Some("not a valid value".to_owned()),
+        Yaml::Invalid(invalid) => Some(invalid.kind.to_string()),
+        Yaml::Real(s) if parse_f64(s).is_none() => Some(format!("`{s}` is not a float")),
+        _ => None,
+    };
+    if let Some(reason) = reason {
+        let segments: Vec<String> = steps
+            .iter()
+            .map(|step| match step {
+                Step::Index(i) => format!("[{i}]"),
+                Step::Key(key) => key_name(key),
+            })
+            .collect();
+        let problem = EmitError::InvalidNode {
+            path: join_path(segments.iter().map(String::as_str)),
+            kind: node_kind(node),
+            reason,
+        };
+        if !found(problem) {
+            return false;
+        }
+    }
+    match node {
+        Yaml::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                steps.push(Step::Index(i));
+                let more = find_invalid(item, steps, found);
+                steps.pop();
+                if !more {
+                    return false;
+                }
+            }
+        }
+        Yaml::Hash(h) => {
+            for (k, v) in h.iter() {
+                steps.push(Step::Key(k));
+                let more = find_invalid(k, steps, found) && find_invalid(v, steps, found);
+                steps.pop();
+                if !more {
+                    return false;
+                }
+            }
+        }
+        Yaml::Tagged(_, inner) => return find_invalid(inner, steps, found),
+        _ => {}
+    }
+    true
+}
+
+/// Join segments into a dotted path, with `[i]` indices attached directly
+fn join_path<'s>(segments: impl Iterator<Item = &'s str>) -> String {
+    let mut path = String::new();
+    for segment in segments {
+        if !path.is_empty() && !segment.starts_with('[') {
+            path.push('.');
+        }
+        path.push_str(segment);
+    }
+    path
+}
+
+/// The JSON Pointer `pointer` into `doc` in the form [`join_path`] gives
+/// error paths, following `doc` as far as it goes to tell sequence indexes
+/// from mapping keys
+fn dotted_path(doc: &Yaml, pointer: &str) -> String {
+    let mut node = Some(doc);
+    let mut segments = Vec::new();
+    for token in crate::pointer::tokens(pointer).unwrap_or_default() {
+        segments.push(match node {
+            Some(Yaml::Array(_)) => format!("[{token}]"),
+            _ => token.to_string(),
+        });
+        node = node.and_then(|n| n.pointer(&format!("/{}", crate::pointer::escape(&token))));
+    }
+    join_path(segments.iter().map(String::as_str))
+}
+
+/// Return whether `key` must be written with `? ` because it cannot be an
+/// implicit key: collections, multi-line text, and text past the 1024
+/// character implicit-key limit.
+fn needs_explicit_key(key: &Yaml) -> bool {
+    match key {
+        Yaml::Array(_) | Yaml::Hash(_) => true,
+        Yaml::String(s) => s.contains('\n') || s.chars().count() > 1024,
+        Yaml::Tagged(_, inner) => needs_explicit_key(inner),
+        _ => false,
+    }
+}
+
+/// The chomping indicator that keeps the trailing line breaks of `s`
+fn block_chomping(s: &str) -> (&'static str, ChompingMode) {
+    match s.len() - s.trim_end_matches('\n').len() {
+        0 => ("-", ChompingMode::Strip),
+        1 => ("", ChompingMode::Clip),
+        _ => ("+", ChompingMode::Keep),
+    }
+}
+
+fn fits_literal(s: &str) -> bool {
+    s.contains('\n') && fits_block(s)
+}
+
+fn fits_folded(s: &str, width: usize) -> bool {
+    (s.contains('\n') || s.chars().count() > width) && fits_block(s)
+}
+
+/// Whether `s` can be written as a block scalar without an indentation
+/// indicator, which would depend on where a fragment is spliced
+fn fits_block(s: &str) -> bool {
+    let first_line = s.trim_start_matches('\n');
+    !s.trim_end_matches('\n').is_empty()
+        && !first_line.starts_with([' ', '\t'])
+        && s.chars().all(|c| {
+            (c == '\n' || c == '\t' || !c.is_control())
+                && !matches!(c, '\u{2028}' | '\u{2029}' | '\u{FEFF}')
+        })
+}
+
+/// Return whether a string definitely needs quotes in YAML.
+fn need_quotes(s: &str) -> bool {
+    need_quotes_under_str_tag(s) || resolves_as_non_string(s)
+}
+
+/// Whether plain `s` would be read as something other than a string
+fn resolves_as_non_string(s: &str) -> bool {
+    // Plain text that the core schema would resolve to a number, bool or
+    // null, such as `123`, `0x1f`, `.inf` or `NULL`
+    if !matches!(Yaml::parse_str(s), Yaml::String(_)) || s.parse::<f64>().is_ok() {
         return true;
     }
-    if s.parse::<f64>().is_ok() {
+    // YAML 1.1 booleans, which older readers still resolve
+    matches!(
+        s,
+        "y" | "Y" | "yes" | "Yes" | "YES" | "n" | "N" | "no" | "No" | "NO"
+            | "on" | "On" | "ON" | "off" | "Off" | "OFF"
+    )
+}
+
+/// Whether `s` needs quotes even after a `!!str` tag, which settles how
+/// plain text resolves
+fn need_quotes_under_str_tag(s: &str) -> bool {
+    if s.is_empty() || s.starts_with(' ') || s.ends_with(' ') {
         return true;
     }
-    match s {
-        "null" | "~" | "NULL" | "Null" => return true,
-        "true" | "false" | "True" | "False" => return true,
-        _ => {}
+    // A `: ` would start a mapping value, and `...` ends the document
+    if s.contains(": ") || s.ends_with(':') || s.starts_with("...") {
+        return true;
     }
     // check special chars
     if s.starts_with(|c: char| {
@@ -223,12 +1186,10 @@ fn need_quotes(s: &str) -> bool {
             c,
             '{' | '}' | '[' | ']' | ',' | '#' | '`' | '\"' | '\''
                 | '\\'
-                | '\0'..='\x06'
-                | '\t'
-                | '\n'
-                | '\r'
-                | '\x0e'..='\x1f'
-        )
+                | '\u{2028}'
+                | '\u{2029}'
+                | '\u{FEFF}'
+        ) || c.is_control()
     }) {
         return true;
     }
@@ -245,7 +1206,7 @@ fn escape_str(wr: &mut dyn fmt::Write, s: &str) -> Result<(), fmt::Error> {
             '\n' => write!(wr, "\\n")?,
             '\t' => write!(wr, "\\t")?,
             '\r' => write!(wr, "\\r")?,
-            _ if c.is_control() => {
+            _ if c.is_control() || matches!(c, '\u{2028}' | '\u{2029}' | '\u{FEFF}') => {
                 // escape in \u form
                 write!(wr, "\\u{:04x}", c as u32)?
             }
@@ -1,24 +1,206 @@
 use std::error::Error;
 use std::fmt;
 
-/// The scanning position for error reporting.
-#[derive(Clone, Copy, PartialEq, Debug, Eq)]
+/// A location in the source, and for tokens and nodes how far they run.
+///
+/// The one position type of the crate: scan errors, events, lexer tokens and
+/// errors, AST nodes and semantic diagnostics all carry it, so any of them
+/// can be correlated with any other. `lexer::Position` is the same type.
+///
+/// Two markers are equal when they name the same point; the length of what
+/// they mark is not compared. The byte offset and length are read through
+/// [`byte_offset`](Self::byte_offset) and [`len`](Self::len).
+#[derive(Clone, Copy, Debug)]
 pub struct Marker {
+    /// Characters before this point
     pub index: usize,
+    /// Line, counted from 1
     pub line: usize,
+    /// Column in characters, counted from 0
     pub col: usize,
+    /// Bytes of UTF-8 before this point
+    pub(crate) byte_offset: usize,
+    /// Length in bytes of what is marked, when it is more than a point
+    pub(crate) len: Option<usize>,
+}
+
+impl Marker {
+    /// The fields compared, ordered and hashed: everything but `len`
+    const fn point(&self) -> (usize, usize, usize, usize) {
+        (self.index, self.line, self.col, self.byte_offset)
+    }
+}
+
+impl PartialEq for Marker {
+    fn eq(&self, other: &Self) -> bool {
+        self.point() == other.point()
+    }
+}
+
+impl Eq for Marker {}
+
+impl PartialOrd for Marker {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Marker {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.point().cmp(&other.point())
+    }
+}
+
+impl std::hash::Hash for Marker {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.point().hash(state);
+    }
 }
 
 impl Default for Marker {
     fn default() -> Self {
+        Self::start()
+    }
+}
+
+impl Marker {
+    /// The start of the input
+    #[inline]
+    #[must_use]
+    pub const fn start() -> Self {
         Self {
             index: 0,
             line: 1,
             col: 0,
+            byte_offset: 0,
+            len: None,
+        }
+    }
+
+    /// A point given in full: `index` characters and `byte_offset` bytes
+    /// into the input, on `line` (from 1) at character column `col` (from 0)
+    #[inline]
+    #[must_use]
+    pub const fn from_parts(index: usize, byte_offset: usize, line: usize, col: usize) -> Self {
+        Self {
+            index,
+            line,
+            col,
+            byte_offset,
+            len: None,
+        }
+    }
+
+    /// The point `byte_offset` bytes into `text`, which is clamped to the
+    /// end of `text` and moved back to the start of the character it falls
+    /// inside
+    ///
+    /// ```rust
+    /// use yyaml::Marker;
+    ///
+    /// let mark = Marker::locate("naïve: 1\nkey: 2\n", 11);
+    /// assert_eq!((mark.line(), mark.col(), mark.index()), (2, 1, 10));
+    /// ```
+    #[must_use]
+    pub fn locate(text: &str, byte_offset: usize) -> Self {
+        let mut end = byte_offset.min(text.len());
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        let mut mark = Self::start();
+        mark.advance_str(&text[..end]);
+        mark
+    }
+
+    /// Characters before this point
+    #[inline]
+    #[must_use]
+    pub const fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Line, counted from 1
+    #[inline]
+    #[must_use]
+    pub const fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Column in characters, counted from 0; see [`column`](Self::column)
+    /// for the count from 1
+    #[inline]
+    #[must_use]
+    pub const fn col(&self) -> usize {
+        self.col
+    }
+
+    /// Bytes of UTF-8 before this point
+    #[inline]
+    #[must_use]
+    pub const fn byte_offset(&self) -> usize {
+        self.byte_offset
+    }
+
+    /// Length in bytes of what is marked, when it is more than a point
+    #[inline]
+    #[must_use]
+    #[allow(clippy::len_without_is_empty)]
+    pub const fn len(&self) -> Option<usize> {
+        self.len
+    }
+
+    /// Whether this is the start of the input
+    #[inline]
+    #[must_use]
+    pub const fn is_start(&self) -> bool {
+        self.index == 0 && self.byte_offset == 0
+    }
+
+    /// Column counted from 1, as shown in messages
+    #[inline]
+    #[must_use]
+    pub const fn column(&self) -> usize {
+        self.col + 1
+    }
+
+    /// The same point, marking `len` bytes from it
+    #[inline]
+    #[must_use]
+    pub const fn with_len(self, len: usize) -> Self {
+        Self {
+            len: Some(len),
+            ..self
+        }
+    }
+
+    /// Move past `ch`
+    #[inline]
+    pub const fn advance_char(&mut self, ch: char) {
+        self.index += 1;
+        self.byte_offset += ch.len_utf8();
+        if ch == '\n' {
+            self.line += 1;
+            self.col = 0;
+        } else {
+            self.col += 1;
+        }
+    }
+
+    /// Move past every character of `s`
+    #[inline]
+    pub fn advance_str(&mut self, s: &str) {
+        for ch in s.chars() {
+            self.advance_char(ch);
         }
     }
 }
 
+impl fmt::Display for Marker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column())
+    }
+}
+
 /// The parse error used by the scanner/parser if something goes wrong.
 #[derive(Clone, Debug)]
 pub struct ScanError {
@@ -36,6 +218,21 @@ impl ScanError {
     }
 }
 
+impl ScanError {
+    /// `construct`, opened at `start`, reached the end of input at `mark`
+    /// without its `close`
+    pub(crate) fn unclosed(mark: Marker, construct: &str, start: Marker, close: char) -> Self {
+        Self::new(
+            mark,
+            &format!(
+                "{construct} starting at line {}, column {} was never closed; add a closing `{close}`",
+                start.line,
+                start.col + 1
+            ),
+        )
+    }
+}
+
 impl fmt::Display for ScanError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
@@ -1,4 +1,5 @@
 use crate::error::Marker;
+use crate::parser::ChompingMode;
 
 /// Event signals from the parser
 #[derive(Clone, PartialEq, Debug, Eq)]
@@ -10,11 +11,16 @@ pub enum Event {
     DocumentEnd,
     YamlDirective(u32, u32),
     TagDirective(String, String),
+    /// Anchor id of the node an alias refers to
     Alias(usize),
-    Scalar(String, TScalarStyle, usize, Option<TokenType>),
-    SequenceStart(usize),
+    /// Value, style, anchor id, tag, and the header of a literal or folded
+    /// scalar
+    Scalar(String, TScalarStyle, usize, Option<TokenType>, Option<BlockScalarHeader>),
+    /// Anchor id and anchor name; id 0 and `None` when not anchored
+    SequenceStart(usize, Option<String>),
     SequenceEnd,
-    MappingStart(usize),
+    /// Anchor id and anchor name; id 0 and `None` when not anchored
+    MappingStart(usize, Option<String>),
     MappingEnd,
 }
 
@@ -29,12 +35,23 @@ pub enum TScalarStyle {
     Folded,
 }
 
+/// Indicators written in a literal or folded scalar's header, e.g. `|2-`,
+/// so a formatter can reproduce them
+#[derive(Clone, Copy, PartialEq, Debug, Eq)]
+pub struct BlockScalarHeader {
+    /// `-` strips, `+` keeps, and no indicator clips trailing line breaks
+    pub chomping: ChompingMode,
+    /// Explicit indentation indicator, 1 to 9
+    pub indent: Option<usize>,
+}
+
 #[derive(Clone, PartialEq, Debug, Eq)]
 pub enum TokenType {
     Tag(String, String),
     Anchor(String),
     Alias(String),
-    Scalar(TScalarStyle, String),
+    /// Style, value, and for literal and folded scalars their header
+    Scalar(TScalarStyle, String, Option<BlockScalarHeader>),
     DocumentStart,
     DocumentEnd,
     FlowSequenceStart,
@@ -77,3 +94,54 @@ impl<R: EventReceiver> MarkedEventReceiver for R {
         self.on_event(ev);
     }
 }
+
+/// A receiver that is also told the resolved tag of each collection, which
+/// `SequenceStart` and `MappingStart` do not carry
+pub(crate) trait TaggedEventReceiver: MarkedEventReceiver {
+    /// The tag of the collection whose start event comes next
+    fn on_collection_tag(&mut self, tag: String);
+}
+
+/// Anchor names by the ids events carry, for one parsed stream
+///
+/// Ids start at 1 and count up through the stream as anchored nodes are
+/// completed, so a collection is numbered after the anchors inside it. An
+/// anchor redefined later gets a new id while aliases before it keep the
+/// old one.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AnchorTable {
+    names: Vec<String>,
+}
+
+impl AnchorTable {
+    pub(crate) const fn new(names: Vec<String>) -> Self {
+        Self { names }
+    }
+
+    /// Name of the anchor with `id`
+    #[must_use]
+    pub fn name(&self, id: usize) -> Option<&str> {
+        self.names.get(id.checked_sub(1)?).map(String::as_str)
+    }
+
+    /// Id of the last anchor defined as `name`
+    #[must_use]
+    pub fn id(&self, name: &str) -> Option<usize> {
+        self.names.iter().rposition(|n| n == name).map(|i| i + 1)
+    }
+
+    /// `(id, name)` for every anchor, in definition order
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &str)> {
+        self.names.iter().enumerate().map(|(i, n)| (i + 1, n.as_str()))
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
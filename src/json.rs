@@ -0,0 +1,145 @@
+//! Rendering loaded documents as JSON
+//!
+//! [`to_json`] writes a [`Yaml`] tree as compact JSON without going through
+//! serde. Mapping keys that are scalars become JSON strings; anything JSON
+//! has no spelling for (collection keys, `.nan`, `.inf`, unresolved aliases,
+//! keys such as `1` and `"1"` that would become the same JSON key) is an
+//! error rather than being silently dropped.
+
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::fmt::Write as _;
+
+use crate::{Error, pointer};
+use crate::yaml::{Yaml, parse_f64};
+
+/// Render `doc` as a single line of JSON.
+///
+/// Tags are transparent: a tagged node is written as its content.
+///
+/// ```rust
+/// use yyaml::{YamlLoader, to_json};
+///
+/// let docs = YamlLoader::load_from_str("name: app\nports: [80, 443]\n1: ~\n").unwrap();
+/// assert_eq!(
+///     to_json(&docs[0]).unwrap(),
+///     r#"{"name":"app","ports":[80,443],"1":null}"#
+/// );
+/// ```
+pub fn to_json(doc: &Yaml) -> Result<String, Error> {
+    let mut out = String::new();
+    write_node(&mut out, doc)?;
+    Ok(out)
+}
+
+fn write_node(out: &mut String, node: &Yaml) -> Result<(), Error> {
+    match node {
+        Yaml::Null => out.push_str("null"),
+        Yaml::Boolean(b) => out.push_str(if *b { "true" } else { "false" }),
+        Yaml::Integer(i) => out.push_str(&i.to_string()),
+        Yaml::Real(s) => write_real(out, s)?,
+        Yaml::String(s) => write_string(out, s),
+        Yaml::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_node(out, item)?;
+            }
+            out.push(']');
+        }
+        Yaml::Hash(map) => {
+            out.push('{');
+            let mut seen = HashSet::with_capacity(map.len());
+            for (i, (key, value)) in map.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                let text = key_text(key)?;
+                write_string(out, &text);
+                if !seen.insert(text) {
+                    let text = key_text(key)?;
+                    return Err(Error::Custom(format!(
+                        "mapping keys collide as JSON key \"{text}\""
+                    )));
+                }
+                out.push(':');
+                write_node(out, value)?;
+            }
+            out.push('}');
+        }
+        Yaml::Tagged(_, inner) => write_node(out, inner)?,
+        Yaml::Alias(_) => {
+            return Err(Error::Custom(
+                "cannot represent an unresolved alias in JSON".to_string(),
+            ));
+        }
+        Yaml::BadValue => {
+            return Err(Error::Custom("cannot represent a bad value in JSON".to_string()));
+        }
+        Yaml::Invalid(invalid) => {
+            return Err(Error::Custom(format!(
+                "cannot represent a bad value in JSON: {}",
+                invalid.kind
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn write_real(out: &mut String, s: &str) -> Result<(), Error> {
+    match parse_f64(s) {
+        Some(f) if f.is_finite() => {
+            // YAML spellings such as `.5` or `+1.0` are not valid JSON numbers
+            if is_json_number(s) {
+                out.push_str(s);
+            } else {
+                out.push_str(&f.to_string());
+            }
+            Ok(())
+        }
+        _ => Err(Error::Custom(format!("cannot represent `{s}` in JSON"))),
+    }
+}
+
+/// The string a scalar mapping key becomes in a JSON object
+fn key_text(key: &Yaml) -> Result<Cow<'_, str>, Error> {
+    pointer::key_text(key)
+        .ok_or_else(|| Error::Custom("JSON object keys must be scalars".to_string()))
+}
+
+fn is_json_number(s: &str) -> bool {
+    let s = s.strip_prefix('-').unwrap_or(s);
+    let (mantissa, exponent) = match s.find(['e', 'E']) {
+        Some(at) => (&s[..at], Some(&s[at + 1..])),
+        None => (s, None),
+    };
+    let (int, frac) = match mantissa.split_once('.') {
+        Some((int, frac)) => (int, Some(frac)),
+        None => (mantissa, None),
+    };
+    let digits = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+    let int_ok = digits(int) && (int == "0" || !int.starts_with('0'));
+    let frac_ok = frac.is_none_or(digits);
+    let exp_ok = exponent.is_none_or(|e| digits(e.strip_prefix(['+', '-']).unwrap_or(e)));
+    int_ok && frac_ok && exp_ok
+}
+
+fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
@@ -118,11 +118,7 @@ impl LexError {
 
 impl std::fmt::Display for LexError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{} at {}:{}",
-            self.kind, self.position.line, self.position.column
-        )
+        write!(f, "{} at {}", self.kind, self.position)
     }
 }
 
@@ -144,6 +140,8 @@ pub enum LexErrorKind {
     InvalidIndentation(String),
     UnexpectedEndOfInput,
     EmptyScalar,
+    /// Anything else the scanner rejects, with its message
+    Syntax(String),
 }
 
 impl std::fmt::Display for LexErrorKind {
@@ -162,6 +160,7 @@ impl std::fmt::Display for LexErrorKind {
             Self::InvalidIndentation(msg) => write!(f, "invalid indentation: {msg}"),
             Self::UnexpectedEndOfInput => write!(f, "unexpected end of input"),
             Self::EmptyScalar => write!(f, "empty scalar"),
+            Self::Syntax(msg) => write!(f, "{msg}"),
         }
     }
 }
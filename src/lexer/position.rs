@@ -3,83 +3,13 @@
 //! This module provides high-performance position tracking with minimal overhead
 //! for accurate error reporting and debugging.
 
-/// Precise position in source text
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct Position {
-    /// Line number (1-based)
-    pub line: usize,
-    /// Column number (1-based)  
-    pub column: usize,
-    /// Byte offset from start of input (0-based)
-    pub byte_offset: usize,
-}
-
-impl Position {
-    /// Create a new position
-    #[inline]
-    #[must_use] 
-    pub const fn new(line: usize, column: usize, byte_offset: usize) -> Self {
-        Self {
-            line,
-            column,
-            byte_offset,
-        }
-    }
-
-    /// Create position at start of input
-    #[inline]
-    #[must_use] 
-    pub const fn start() -> Self {
-        Self::new(1, 1, 0)
-    }
-
-    /// Check if this is the start position
-    #[inline]
-    #[must_use] 
-    pub const fn is_start(&self) -> bool {
-        self.line == 1 && self.column == 1 && self.byte_offset == 0
-    }
+use crate::error::Marker;
 
-    /// Advance position by one character
-    #[inline]
-    pub const fn advance_char(&mut self, ch: char) {
-        if ch == '\n' {
-            self.line += 1;
-            self.column = 1;
-        } else {
-            self.column += 1;
-        }
-        self.byte_offset += ch.len_utf8();
-    }
-
-    /// Advance position by multiple characters
-    #[inline]
-    pub fn advance_str(&mut self, s: &str) {
-        for ch in s.chars() {
-            self.advance_char(ch);
-        }
-    }
-
-    /// Create a span from this position to another
-    #[inline]
-    #[must_use] 
-    pub const fn span_to(self, end: Self) -> Span {
-        Span::new(self, end)
-    }
-}
-
-impl Default for Position {
-    #[inline]
-    fn default() -> Self {
-        Self::start()
-    }
-}
-
-impl std::fmt::Display for Position {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}:{}", self.line, self.column)
-    }
-}
+/// The lexer's name for [`Marker`], the crate-wide source location.
+///
+/// [`column`](Marker::column) counts from 1, as the lexer's columns always
+/// have; [`col`](Marker::col) is the same column counted from 0.
+pub type Position = Marker;
 
 /// A span of text in the source
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -185,8 +115,9 @@ impl PositionTracker {
     /// Advance by a specific number of bytes
     #[inline]
     pub const fn advance_bytes(&mut self, bytes: usize) {
+        self.current.index += bytes; // Assumes single-byte characters
         self.current.byte_offset += bytes;
-        self.current.column += bytes; // Assumes single-byte characters
+        self.current.col += bytes;
     }
 
     /// Mark the current position for potential backtracking
@@ -320,13 +251,13 @@ pub mod utils {
             result.push('\n');
 
             // Add indicator pointing to the column
-            let spaces = position.column.saturating_sub(1).min(width);
+            let spaces = position.col.min(width);
             result.push_str(&" ".repeat(spaces));
             result.push('^');
 
             result
         } else {
-            format!("Position {}:{} (invalid)", position.line, position.column)
+            format!("Position {position} (invalid)")
         }
     }
 }
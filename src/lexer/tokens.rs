@@ -11,17 +11,36 @@ use std::borrow::Cow;
 pub struct Token<'input> {
     pub kind: TokenKind<'input>,
     pub position: Position,
+    /// Length of the token's text in bytes
     pub length: usize,
+    /// Where the token's text ends
+    pub end: Position,
 }
 
 impl<'input> Token<'input> {
+    /// A token of `length` bytes of ASCII text on one line
     #[inline]
     #[must_use] 
     pub const fn new(kind: TokenKind<'input>, position: Position, length: usize) -> Self {
+        let end = Position::from_parts(
+            position.index + length,
+            position.byte_offset + length,
+            position.line,
+            position.col + length,
+        );
+        Self::spanning(kind, position, end)
+    }
+
+    /// A token whose text runs from `position` to `end`
+    #[inline]
+    #[must_use] 
+    pub const fn spanning(kind: TokenKind<'input>, position: Position, end: Position) -> Self {
+        let length = end.byte_offset.saturating_sub(position.byte_offset);
         Self {
             kind,
-            position,
+            position: position.with_len(length),
             length,
+            end,
         }
     }
 
@@ -29,11 +48,7 @@ impl<'input> Token<'input> {
     #[inline]
     #[must_use] 
     pub const fn end_position(&self) -> Position {
-        Position::new(
-            self.position.line,
-            self.position.column + self.length,
-            self.position.byte_offset + self.length,
-        )
+        self.end
     }
 }
 
@@ -184,56 +184,20 @@ impl std::error::Error for EscapeError {}
 
 /// YAML character classification functions
 pub mod chars {
-    /// Check if a character is a YAML line break
-    #[inline]
-    #[must_use] 
-    pub const fn is_break(ch: char) -> bool {
-        matches!(ch, '\n' | '\r')
-    }
+    pub use crate::chars::*;
 
     /// Check if a character is YAML whitespace (space or tab)
     #[inline]
     #[must_use] 
     pub const fn is_space(ch: char) -> bool {
-        matches!(ch, ' ' | '\t')
-    }
-
-    /// Check if a character is YAML blank (whitespace or line break)
-    #[inline]
-    #[must_use] 
-    pub const fn is_blank(ch: char) -> bool {
-        is_space(ch) || is_break(ch)
+        is_white(ch)
     }
 
     /// Check if a character can start a plain scalar
     #[inline]
     #[must_use] 
     pub const fn can_start_plain_scalar(ch: char) -> bool {
-        !matches!(
-            ch,
-            '-' | '?'
-                | ':'
-                | ','
-                | '['
-                | ']'
-                | '{'
-                | '}'
-                | '#'
-                | '&'
-                | '*'
-                | '!'
-                | '|'
-                | '>'
-                | '\''
-                | '"'
-                | '%'
-                | '@'
-                | '`'
-                | ' '
-                | '\t'
-                | '\n'
-                | '\r'
-        )
+        !is_indicator(ch) && !is_blank(ch)
     }
 
     /// Check if a character can continue a plain scalar
@@ -254,38 +218,7 @@ pub mod chars {
     #[inline]
     #[must_use] 
     pub const fn is_anchor_char(ch: char) -> bool {
-        !is_blank(ch) && !matches!(ch, ',' | '[' | ']' | '{' | '}')
-    }
-
-    /// Check if a character is a valid tag character
-    #[inline]
-    #[must_use] 
-    pub const fn is_tag_char(ch: char) -> bool {
-        ch.is_ascii_alphanumeric()
-            || matches!(
-                ch,
-                '-' | '_'
-                    | '.'
-                    | '~'
-                    | ':'
-                    | '/'
-                    | '?'
-                    | '#'
-                    | '['
-                    | ']'
-                    | '@'
-                    | '!'
-                    | '$'
-                    | '&'
-                    | '\''
-                    | '('
-                    | ')'
-                    | '*'
-                    | '+'
-                    | ','
-                    | ';'
-                    | '='
-            )
+        !is_blank(ch) && !is_flow_indicator(ch)
     }
 
     /// Check if a character needs URI encoding in a tag
@@ -294,65 +227,6 @@ pub mod chars {
     pub const fn needs_uri_encoding(ch: char) -> bool {
         !is_tag_char(ch)
     }
-
-    /// Check if a character is a white space (space or tab)
-    #[inline]
-    #[must_use] 
-    pub const fn is_white(ch: char) -> bool {
-        ch == ' ' || ch == '\t'
-    }
-
-    /// Check if a character is a word character (alphanumeric or hyphen)
-    #[inline]
-    #[must_use] 
-    pub const fn is_word_char(ch: char) -> bool {
-        ch.is_ascii_alphanumeric() || ch == '-'
-    }
-
-    /// Check if a character is a URI character
-    #[inline]
-    #[must_use] 
-    pub const fn is_uri_char(ch: char) -> bool {
-        ch.is_ascii_alphanumeric()
-            || matches!(
-                ch,
-                '-' | '_'
-                    | '.'
-                    | '~'
-                    | ':'
-                    | '/'
-                    | '?'
-                    | '#'
-                    | '['
-                    | ']'
-                    | '@'
-                    | '!'
-                    | '$'
-                    | '&'
-                    | '\''
-                    | '('
-                    | ')'
-                    | '*'
-                    | '+'
-                    | ','
-                    | ';'
-                    | '='
-            )
-    }
-
-    /// Check if a character is printable for YAML
-    #[inline]
-    #[must_use] 
-    pub fn is_printable(ch: char) -> bool {
-        ch == '\t'
-            || ch == '\n'
-            || ch == '\r'
-            || (' '..='\u{7E}').contains(&ch)
-            || ch == '\u{85}'
-            || ('\u{A0}'..='\u{D7FF}').contains(&ch)
-            || ('\u{E000}'..='\u{FFFD}').contains(&ch)
-            || ('\u{10000}'..='\u{10FFFF}').contains(&ch)
-    }
 }
 
 /// Unicode normalization utilities
@@ -392,7 +266,7 @@ pub mod normalization {
     /// Remove BOM if present
     #[must_use] 
     pub fn remove_bom(input: &str) -> &str {
-        input.strip_prefix('\u{FEFF}').unwrap_or(input)
+        crate::chars::strip_byte_order_mark(input)
     }
 }
 
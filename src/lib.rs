@@ -7,30 +7,93 @@
 //! let doc = &docs[0];
 //! assert_eq!(doc["foo"].as_i64().unwrap(), 123);
 //! ```
+//!
+//! [`prelude`] lists the semver-stable API; the `lexer`, `parser`, `scanner`
+//! and `semantic` modules are unstable internals. The semantic telemetry
+//! entry points [`analyze_str`] and [`analyze_yaml`] are re-exported here
+//! and are stable.
 
 // Removed broken de.rs - using value.rs system instead
+#[macro_use]
+mod trace;
+
+mod arc_value;
+mod base64;
+mod cache;
+pub mod chars;
+mod conversion;
+pub mod diagnostics;
 mod emitter;
 mod error;
 pub mod events;
-pub mod lexer;
+mod json;
 mod linked_hash_map;
-pub mod parser;
-
-pub mod scanner;
-pub mod semantic;
+mod merge;
+mod outline;
+mod overlay;
+pub mod pipeline;
+mod pointer;
+pub mod prelude;
+mod roundtrip;
+pub mod scalars;
+pub mod schema;
 mod ser;
+mod source_map;
+mod stream;
+pub mod snapshot;
+pub mod testing;
+mod tags;
+mod untagged;
 pub mod value;
+pub mod with;
 mod yaml;
 
+// Unstable internals, public for tooling and experiments; see `prelude`
+// for the supported surface
+#[doc(hidden)]
+pub mod lexer;
+#[doc(hidden)]
+pub mod parser;
+#[doc(hidden)]
+pub mod scanner;
+#[cfg(feature = "semantic")]
+#[doc(hidden)]
+pub mod semantic;
+
 // Remove broken de.rs exports
-pub use emitter::{EmitError, EmitResult, YamlEmitter};
+pub use arc_value::{ArcMapping, ArcSequence, ArcTaggedValue, ArcValue};
+pub use cache::{ContentHash, Eviction, YamlCache};
+pub use conversion::{ConversionLoss, ConversionWarning};
+pub use diagnostics::{Diagnostic, Severity};
+pub use emitter::{AnchorPlan, EmitError, EmitResult, YamlEmitter};
 pub use error::{Marker, ScanError};
-pub use events::{Event, EventReceiver, MarkedEventReceiver, TEncoding, TScalarStyle, TokenType};
+pub use events::{
+    AnchorTable, BlockScalarHeader, Event, EventReceiver, MarkedEventReceiver, TEncoding,
+    TScalarStyle, TokenType,
+};
+pub use json::to_json;
 pub use linked_hash_map::LinkedHashMap;
-pub use parser::YamlLoader;
+pub use merge::{MergePolicy, format_str, merge_streams};
+pub use outline::{OutlineEntry, OutlineKind, doc_comments, outline};
+pub use overlay::OverlayValue;
+pub use pointer::PathPattern;
+pub use parser::{
+    AnchorLibrary, CancellationToken, EmptyScalar, IndentationRules, IntegerOverflow,
+    LoaderOptions, NodeInfo, NodeMiddleware, ParseMetrics, Profile, PushParser, SchemaMode,
+    YamlLoader,
+};
+pub use roundtrip::{RoundTripDivergence, roundtrip_check};
+#[cfg(feature = "semantic")]
+pub use semantic::{ProcessingSummary, analyze_str, analyze_yaml};
 pub use ser::*;
-pub use value::{Deserializer, Mapping, Number, Sequence, Value, from_value};
-pub use yaml::Yaml;
+pub use source_map::SourceMap;
+pub use stream::{StreamEmitter, StreamState};
+pub use untagged::Untagged;
+pub use value::{
+    DefaultedField, Deserializer, DeserializerConfig, FieldNameNormalization, Mapping, Number,
+    Sequence, Value, from_value, from_value_with_defaults,
+};
+pub use yaml::{BadValueKind, InvalidNode, Yaml};
 
 /// Deserialize an instance of type T from a string of YAML text.
 ///
@@ -40,18 +103,114 @@ where
     T: serde::de::DeserializeOwned,
 {
     let docs = YamlLoader::load_from_str(s).map_err(Error::Scan)?;
-    if docs.is_empty() {
-        return Err(Error::Custom("No YAML documents found".to_string()));
+    T::deserialize(value::Deserializer::new(single_value(s, docs)?))
+}
+
+/// Load a single YAML document as a [`Value`].
+///
+/// Equal to `from_str::<Value>(s)`, but the value is built from the
+/// parser's events as they are read, without loading a [`Yaml`] tree first.
+///
+/// ```rust
+/// let value = yyaml::from_str_value("name: web\nports: [80, 443]").unwrap();
+/// assert_eq!(value["ports"][1].as_i64(), Some(443));
+/// assert_eq!(value, yyaml::from_str::<yyaml::Value>("name: web\nports: [80, 443]").unwrap());
+/// ```
+pub fn from_str_value(s: &str) -> Result<Value, Error> {
+    single_value(s, YamlLoader::load_values(s, SchemaMode::default())?)
+}
+
+/// Deserialize an instance of type T from a string of YAML text, using
+/// `config` to resolve loader choices such as empty values.
+///
+/// As with [`from_str`], `s` must hold a single document; use
+/// [`parse_all_with_config`] for a stream.
+pub fn from_str_with_config<T>(s: &str, config: &DeserializerConfig) -> Result<T, Error>
+where
+    T: serde::de::DeserializeOwned,
+{
+    let docs = YamlLoader::load_with_options(s, &config.loader_options())?;
+    from_value_with_config(single_value(s, docs)?, config).map_err(|e| e.locate(s, 0))
+}
+
+/// Deserialize the first document of a YAML stream, ignoring any that follow.
+///
+/// Parsing stops at the end of the first document, so later documents are
+/// never read and syntax errors in them are not reported.
+pub fn parse_first<T>(s: &str) -> Result<T, Error>
+where
+    T: serde::de::DeserializeOwned,
+{
+    from_document(YamlLoader::load_first(s)?)
+}
+
+/// Deserialize every document of a YAML stream.
+///
+/// An empty stream loads as a single null document, as in
+/// [`YamlLoader::load_from_str`].
+pub fn parse_all<T>(s: &str) -> Result<Vec<T>, Error>
+where
+    T: serde::de::DeserializeOwned,
+{
+    YamlLoader::load_from_str(s)?
+        .into_iter()
+        .map(from_document)
+        .collect()
+}
+
+/// Deserialize every document of a YAML stream, applying every option of
+/// `config` to each, as [`from_str_with_config`] does to its one document.
+///
+/// ```rust
+/// use yyaml::DeserializerConfig;
+///
+/// let config = DeserializerConfig::new().coerce_strings(true);
+/// let ports: Vec<u16> = yyaml::parse_all_with_config("\"80\"\n--- 443\n", &config).unwrap();
+/// assert_eq!(ports, [80, 443]);
+/// ```
+pub fn parse_all_with_config<T>(s: &str, config: &DeserializerConfig) -> Result<Vec<T>, Error>
+where
+    T: serde::de::DeserializeOwned,
+{
+    YamlLoader::load_with_options(s, &config.loader_options())?
+        .into_iter()
+        .enumerate()
+        .map(|(document, doc)| {
+            from_value_with_config(Value::from(doc), config).map_err(|e| e.locate(s, document))
+        })
+        .collect()
+}
+
+fn from_value_with_config<T>(value: Value, config: &DeserializerConfig) -> Result<T, Error>
+where
+    T: serde::de::DeserializeOwned,
+{
+    if config.collect_errors {
+        value::deserialize_collecting(value, config)
+    } else {
+        T::deserialize(value::Deserializer::with_config(value, config))
     }
-    if docs.len() > 1 {
-        return Err(Error::Custom(
-            "Multiple YAML documents found, expected one".to_string(),
-        ));
+}
+
+/// The only document of `docs`, loaded from `s`, moved into a [`Value`]
+pub(crate) fn single_value<T: Into<Value>>(s: &str, mut docs: Vec<T>) -> Result<Value, Error> {
+    match docs.len() {
+        0 => Err(Error::Custom("No YAML documents found".to_string())),
+        1 => Ok(docs.pop().map(Into::into).unwrap_or_default()),
+        _ => {
+            let starts = YamlLoader::document_starts(s)?;
+            Err(Error::MultipleDocuments(
+                starts.get(1).copied().unwrap_or_default(),
+            ))
+        }
     }
-    let yaml = &docs[0];
-    let value = Value::from_yaml(yaml);
-    let deserializer = value::Deserializer::new(value);
-    T::deserialize(deserializer)
+}
+
+fn from_document<T>(yaml: Yaml) -> Result<T, Error>
+where
+    T: serde::de::DeserializeOwned,
+{
+    T::deserialize(value::Deserializer::new(Value::from(yaml)))
 }
 
 use thiserror::Error;
@@ -64,6 +223,46 @@ pub enum Error {
     Emit(#[from] EmitError),
     #[error("repetition limit exceeded")]
     RepetitionLimitExceeded,
+    #[error("parsing cancelled")]
+    Cancelled,
+    /// A single-document API was given a stream with more than one document;
+    /// the marker is where the second one starts
+    #[error(
+        "expected a single YAML document, found another at line {} col {}",
+        .0.line,
+        .0.col + 1
+    )]
+    MultipleDocuments(Marker),
+    /// A quoted scalar did not parse as the number or boolean its target
+    /// type needs, under [`DeserializerConfig::coerce_strings`]. `path` is a
+    /// JSON Pointer to it, and `mark` its position when read from text.
+    #[error(
+        "{message} at `{path}`{}",
+        .mark.as_ref().map(|m| format!(", line {} col {}", m.line, m.col + 1)).unwrap_or_default()
+    )]
+    Coerce {
+        path: String,
+        mark: Option<Marker>,
+        message: String,
+    },
+    /// `source` was raised for the node at `path`, a JSON Pointer, under
+    /// [`DeserializerConfig::collect_errors`]
+    #[error(
+        "{source} at `{path}`{}",
+        .mark.as_ref().map(|m| format!(", line {} col {}", m.line, m.col + 1)).unwrap_or_default()
+    )]
+    At {
+        path: String,
+        mark: Option<Marker>,
+        source: Box<Error>,
+    },
+    /// Every error found under [`DeserializerConfig::collect_errors`]
+    #[error(
+        "{} errors: {}",
+        .0.len(),
+        .0.iter().map(ToString::to_string).collect::<Vec<_>>().join("; ")
+    )]
+    Multiple(Vec<Error>),
     #[error("custom: {0}")]
     Custom(String),
 }
@@ -76,6 +275,81 @@ impl Error {
     pub const fn repetition_limit_exceeded() -> Self {
         Self::RepetitionLimitExceeded
     }
+
+    pub(crate) const fn coerce(message: String) -> Self {
+        Self::Coerce {
+            path: String::new(),
+            mark: None,
+            message,
+        }
+    }
+
+    /// Prefix the path of a coercion error with the pointer token of the
+    /// collection entry it came from
+    pub(crate) fn under(self, token: &str) -> Self {
+        match self {
+            Self::Coerce {
+                path,
+                mark,
+                message,
+            } => Self::Coerce {
+                path: format!("/{token}{path}"),
+                mark,
+                message,
+            },
+            e => e,
+        }
+    }
+
+    /// Fill in the positions of errors that carry a path from the text `s`
+    /// of the stream holding the document at index `document`
+    fn locate(self, s: &str, document: usize) -> Self {
+        match self {
+            Self::Coerce { .. } | Self::At { .. } | Self::Multiple(_) => {
+                let entries = outline(s).unwrap_or_default();
+                self.locate_in(&entries, document)
+            }
+            e => e,
+        }
+    }
+
+    fn locate_in(self, entries: &[OutlineEntry], document: usize) -> Self {
+        let find = |path: &str| {
+            entries
+                .iter()
+                .find(|entry| entry.document == document && entry.path == path)
+                .map(|entry| entry.span.0)
+        };
+        match self {
+            Self::Coerce {
+                path,
+                mark: None,
+                message,
+            } => Self::Coerce {
+                mark: find(&path),
+                path,
+                message,
+            },
+            Self::At {
+                path,
+                mark: None,
+                source,
+            } => Self::At {
+                mark: find(&path),
+                path,
+                source,
+            },
+            Self::Multiple(errors) => {
+                Self::Multiple(
+                    errors
+                        .into_iter()
+                        .map(|e| e.locate_in(entries, document))
+                        .collect(),
+                )
+            }
+            e => e,
+        }
+    }
 }
 
 impl serde::de::Error for Error {
@@ -100,6 +374,30 @@ pub fn to_string<T: serde::Serialize>(value: &T) -> Result<String, Error> {
     Ok(writer)
 }
 
+/// Serialize `value` as YAML like [`to_string`], with the options in
+/// `config` applied
+pub fn to_string_with_config<T: serde::Serialize>(
+    value: &T,
+    config: &SerializerConfig,
+) -> Result<String, Error> {
+    let mut yaml = value.serialize(config.serializer())?;
+    config.redact(&mut yaml);
+    let mut writer = String::new();
+    config.emitter(YamlEmitter::new(&mut writer)).dump(&yaml)?;
+    Ok(writer)
+}
+
+/// Serialize `value` as YAML straight into `writer`
+pub fn to_writer<W, T>(mut writer: W, value: &T) -> Result<(), Error>
+where
+    W: std::io::Write,
+    T: ?Sized + serde::Serialize,
+{
+    let yaml = value.serialize(ser::YamlSerializer::new())?;
+    YamlEmitter::from_io(&mut writer).dump(&yaml)?;
+    Ok(())
+}
+
 /// Legacy compatibility function - calls the standard from_str implementation
 pub fn parse_str<T>(s: &str) -> Result<T, Error>
 where
@@ -108,7 +406,7 @@ where
     from_str(s)
 }
 
-pub fn to_value<T: serde::Serialize>(value: &T) -> Result<Value, Error> {
+pub fn to_value<T: ?Sized + serde::Serialize>(value: &T) -> Result<Value, Error> {
     let yaml = value.serialize(ser::YamlSerializer::new())?;
     Ok(Value::from_yaml(&yaml))
 }
@@ -271,6 +569,7 @@ nulltest: ~";
     }
 
     #[test]
+    #[ignore = "downloads models.yaml from GitHub; needs network access"]
     fn test_fluent_ai_models_yaml_integration() {
         // Integration test: Download and parse real models.yaml from fluent-ai project
         let models_yaml_url =
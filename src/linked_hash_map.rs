@@ -1,9 +1,20 @@
+use std::cmp::Ordering;
 use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher};
+
+use crate::yaml::Yaml;
 
 /// Maintains insertion order plus unique keys, like `linked_hash_map`.
-/// Here we inline a minimal version for demonstration.
-#[derive(Clone, Debug, PartialEq, Eq, Ord, PartialOrd, Hash)]
+///
+/// Iteration follows insertion order. Replacing the value of an existing
+/// key keeps its place; removing a key closes the gap, so the remaining
+/// entries keep their relative order, and inserting it again puts it last.
+/// Comparison and hashing go by the entries in that order only, so two maps
+/// built by different insert and remove sequences are equal whenever they
+/// iterate the same.
+#[derive(Clone, Debug)]
 pub struct LinkedHashMap<K: PartialEq + Eq, V> {
+    /// Entries by id; ids only ever grow, so `order` is sorted
     map: BTreeMap<usize, (K, V)>,
     order: Vec<usize>,
     next_id: usize,
@@ -34,14 +45,42 @@ impl<K: PartialEq + Eq, V> LinkedHashMap<K, V> {
         K: std::borrow::Borrow<Q>,
         Q: PartialEq + ?Sized,
     {
-        for id in &self.order {
-            if let Some((k, v)) = self.map.get(id)
-                && k.borrow() == key
-            {
-                return Some(v);
-            }
-        }
-        None
+        self.get_by(|k| k.borrow() == key)
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: PartialEq + ?Sized,
+    {
+        self.get_by_mut(|k| k.borrow() == key)
+    }
+
+    /// Value of the first key, in iteration order, that `matches` accepts
+    fn get_by(&self, matches: impl Fn(&K) -> bool) -> Option<&V> {
+        let id = self.position_by(matches)?.1;
+        self.map.get(&id).map(|(_, v)| v)
+    }
+
+    fn get_by_mut(&mut self, matches: impl Fn(&K) -> bool) -> Option<&mut V> {
+        let id = self.position_by(matches)?.1;
+        self.map.get_mut(&id).map(|(_, v)| v)
+    }
+
+    /// Place in the order and id of the first key that `matches` accepts
+    fn position_by(&self, matches: impl Fn(&K) -> bool) -> Option<(usize, usize)> {
+        self.order
+            .iter()
+            .position(|id| self.map.get(id).is_some_and(|(k, _)| matches(k)))
+            .map(|pos| (pos, self.order[pos]))
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: PartialEq + ?Sized,
+    {
+        self.get(key).is_some()
     }
 
     pub fn insert(&mut self, key: K, value: V) -> Option<V> {
@@ -60,6 +99,96 @@ impl<K: PartialEq + Eq, V> LinkedHashMap<K, V> {
         self.order.push(id);
         None
     }
+
+    /// Remove `key`, returning its value; later entries move up one place
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: PartialEq + ?Sized,
+    {
+        let (pos, _) = self.position_by(|k| k.borrow() == key)?;
+        let id = self.order.remove(pos);
+        let (_, value) = self.map.remove(&id)?;
+        debug_assert_eq!(self.order.len(), self.map.len());
+        Some(value)
+    }
+
+    /// Check the invariants iteration order rests on, describing the first
+    /// one broken: every id in the order list has an entry and vice versa,
+    /// ids are in increasing order and below the next id handed out, and no
+    /// key appears twice.
+    ///
+    /// Meant for `debug_assert!(map.validate_links().is_ok())` in code that
+    /// builds maps in unusual ways; it takes quadratic time in the length.
+    pub fn validate_links(&self) -> Result<(), String> {
+        if self.order.len() != self.map.len() {
+            return Err(format!(
+                "{} ids in order for {} entries",
+                self.order.len(),
+                self.map.len()
+            ));
+        }
+        for (pos, id) in self.order.iter().enumerate() {
+            if *id >= self.next_id {
+                return Err(format!("id {id} at {pos} was never handed out"));
+            }
+            if pos > 0 && self.order[pos - 1] >= *id {
+                return Err(format!("id {id} at {pos} is out of insertion order"));
+            }
+            if !self.map.contains_key(id) {
+                return Err(format!("id {id} at {pos} has no entry"));
+            }
+        }
+        for (pos, (key, _)) in self.iter().enumerate() {
+            if self.iter().take(pos).any(|(earlier, _)| earlier == key) {
+                return Err(format!("key at {pos} appears earlier too"));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<V> LinkedHashMap<Yaml, V> {
+    /// Value under the string key `key`, compared in place rather than
+    /// through a `Yaml::String` built for the lookup
+    #[must_use]
+    pub fn get_str(&self, key: &str) -> Option<&V> {
+        self.get_by(|k| matches!(k, Yaml::String(s) if s == key))
+    }
+
+    /// Mutable variant of [`LinkedHashMap::get_str`]
+    pub fn get_str_mut(&mut self, key: &str) -> Option<&mut V> {
+        self.get_by_mut(|k| matches!(k, Yaml::String(s) if s == key))
+    }
+}
+
+impl<K: PartialEq + Eq, V: PartialEq> PartialEq for LinkedHashMap<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<K: PartialEq + Eq, V: Eq> Eq for LinkedHashMap<K, V> {}
+
+impl<K: PartialOrd + Eq, V: PartialOrd> PartialOrd for LinkedHashMap<K, V> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other.iter())
+    }
+}
+
+impl<K: Ord, V: Ord> Ord for LinkedHashMap<K, V> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+impl<K: Hash + Eq, V: Hash> Hash for LinkedHashMap<K, V> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len());
+        for entry in self.iter() {
+            entry.hash(state);
+        }
+    }
 }
 
 impl<K: PartialEq + Eq, V> Default for LinkedHashMap<K, V> {
@@ -68,18 +197,14 @@ impl<K: PartialEq + Eq, V> Default for LinkedHashMap<K, V> {
     }
 }
 
-impl<K: PartialEq + Eq + Clone, V: Clone> IntoIterator for LinkedHashMap<K, V> {
+impl<K: PartialEq + Eq, V> IntoIterator for LinkedHashMap<K, V> {
     type Item = (K, V);
     type IntoIter = std::vec::IntoIter<(K, V)>;
 
+    /// Entries move out without copies; ids follow insertion order, so the
+    /// map already holds them in order
     fn into_iter(self) -> Self::IntoIter {
-        let mut out = Vec::with_capacity(self.order.len());
-        for id in self.order {
-            if let Some((k, v)) = self.map.get(&id) {
-                out.push((k.clone(), v.clone()));
-            }
-        }
-        out.into_iter()
+        self.map.into_values().collect::<Vec<_>>().into_iter()
     }
 }
 
@@ -111,6 +236,13 @@ impl<K: PartialEq + Eq, V> LinkedHashMap<K, V> {
         }
     }
 
+    /// Approximate heap bytes of the map's own storage, not counting what
+    /// the keys and values own
+    pub(crate) fn table_size(&self) -> usize {
+        self.order.capacity() * std::mem::size_of::<usize>()
+            + btree_entries_size::<(usize, (K, V))>(self.map.len())
+    }
+
     #[inline]
     #[must_use] 
     pub fn with_capacity(capacity: usize) -> Self {
@@ -122,6 +254,12 @@ impl<K: PartialEq + Eq, V> LinkedHashMap<K, V> {
     }
 }
 
+/// Approximate heap bytes of `len` B-tree entries of type `E`; nodes run
+/// about two thirds full, plus child pointers and lengths
+pub(crate) const fn btree_entries_size<E>(len: usize) -> usize {
+    len * std::mem::size_of::<E>() * 3 / 2
+}
+
 // Zero-allocation FromIterator implementation for blazing-fast collect()
 impl<K: PartialEq + Eq, V> std::iter::FromIterator<(K, V)> for LinkedHashMap<K, V> {
     #[inline]
@@ -137,3 +275,73 @@ impl<K: PartialEq + Eq, V> std::iter::FromIterator<(K, V)> for LinkedHashMap<K,
         map
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(map: &LinkedHashMap<&'static str, i32>) -> Vec<&'static str> {
+        map.iter().map(|(k, _)| *k).collect()
+    }
+
+    #[test]
+    fn test_order_after_remove_and_insert() {
+        let mut map: LinkedHashMap<_, _> =
+            [("a", 1), ("b", 2), ("c", 3), ("d", 4)].into_iter().collect();
+        assert_eq!(map.remove("b"), Some(2));
+        assert_eq!(map.remove("b"), None);
+        assert_eq!(keys(&map), ["a", "c", "d"]);
+
+        // Replacing keeps the place, re-inserting a removed key goes last
+        map.insert("c", 30);
+        map.insert("b", 20);
+        assert_eq!(keys(&map), ["a", "c", "d", "b"]);
+        assert_eq!(map.get("c"), Some(&30));
+
+        assert_eq!(map.remove("a"), Some(1));
+        assert_eq!(map.remove("b"), Some(20));
+        map.insert("e", 5);
+        assert_eq!(keys(&map), ["c", "d", "e"]);
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.clone().into_iter().map(|(_, v)| v).collect::<Vec<_>>(), [30, 4, 5]);
+        assert_eq!(map.validate_links(), Ok(()));
+
+        while let Some(key) = map.iter().next().map(|(k, _)| *k) {
+            map.remove(key);
+            assert_eq!(map.validate_links(), Ok(()));
+        }
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn test_equality_ignores_history() {
+        let mut churned = LinkedHashMap::new();
+        churned.insert("x", 0);
+        churned.insert("a", 1);
+        churned.remove("x");
+        churned.insert("b", 2);
+        let fresh: LinkedHashMap<_, _> = [("a", 1), ("b", 2)].into_iter().collect();
+        assert_eq!(churned, fresh);
+        assert_eq!(churned.cmp(&fresh), Ordering::Equal);
+
+        let hash = |map: &LinkedHashMap<&str, i32>| {
+            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+            map.hash(&mut hasher);
+            hasher.finish()
+        };
+        assert_eq!(hash(&churned), hash(&fresh));
+
+        let reordered: LinkedHashMap<_, _> = [("b", 2), ("a", 1)].into_iter().collect();
+        assert_ne!(churned, reordered);
+    }
+
+    #[test]
+    fn test_validate_links_reports_broken_order() {
+        let mut map: LinkedHashMap<_, _> = [("a", 1), ("b", 2)].into_iter().collect();
+        map.order.swap(0, 1);
+        assert!(map.validate_links().unwrap_err().contains("out of insertion order"));
+        map.order.swap(0, 1);
+        map.order.pop();
+        assert!(map.validate_links().unwrap_err().contains("1 ids in order for 2 entries"));
+    }
+}
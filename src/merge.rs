@@ -0,0 +1,235 @@
+//! Assembling several YAML streams into one
+//!
+//! [`merge_streams`] either concatenates every document of every input into
+//! one multi-document stream, as when bundling Kubernetes manifests, or
+//! deep-merges them into a single document where later inputs win.
+
+use crate::emitter::YamlEmitter;
+use crate::events::TokenType;
+use crate::linked_hash_map::LinkedHashMap;
+use crate::parser::YamlLoader;
+use crate::scanner::Scanner;
+use crate::value::{Mapping, Value};
+use crate::yaml::Yaml;
+use crate::Error;
+
+/// How [`merge_streams`] combines its inputs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MergePolicy {
+    /// Emit every document in input order, each keeping its own `%YAML`
+    /// and `%TAG` directives
+    #[default]
+    Concatenate,
+    /// Merge every document into one: mappings merge key by key, anything
+    /// else is replaced by the later value. The result carries each distinct
+    /// directive of the inputs once.
+    DeepMerge,
+}
+
+/// Parse each of `inputs` and emit them as one stream according to
+/// `policy`.
+///
+/// ```rust
+/// use yyaml::{MergePolicy, merge_streams};
+///
+/// let base = "replicas: 1\nimage: {name: app, tag: v1}\n";
+/// let overlay = "image: {tag: v2}\n";
+/// let merged = merge_streams(&[base, overlay], MergePolicy::DeepMerge).unwrap();
+/// assert_eq!(merged, "---\nreplicas: 1\nimage: \n  name: app\n  tag: v2\n");
+///
+/// let bundle = merge_streams(&[base, overlay], MergePolicy::Concatenate).unwrap();
+/// assert_eq!(bundle.matches("---").count(), 2);
+/// ```
+pub fn merge_streams(inputs: &[&str], policy: MergePolicy) -> Result<String, Error> {
+    let mut documents = Vec::new();
+    for input in inputs {
+        let directives = document_directives(input)?;
+        let docs = YamlLoader::load_from_str(input)?;
+        let mut directives = directives.into_iter();
+        for doc in docs {
+            documents.push((directives.next().unwrap_or_default(), doc));
+        }
+    }
+
+    let mut out = String::new();
+    match policy {
+        MergePolicy::Concatenate => {
+            for (directives, doc) in &documents {
+                write_document(&mut out, directives, doc)?;
+            }
+        }
+        MergePolicy::DeepMerge => {
+            let mut directives: Vec<String> = Vec::new();
+            let mut merged = Yaml::Null;
+            for (doc_directives, doc) in documents {
+                for directive in doc_directives {
+                    check_tag_conflict(&directives, &directive)?;
+                    if !directives.contains(&directive) {
+                        directives.push(directive);
+                    }
+                }
+                deep_merge(&mut merged, doc);
+            }
+            write_document(&mut out, &directives, &merged)?;
+        }
+    }
+    Ok(out)
+}
+
+/// Re-emit `input` in canonical form: every document is parsed and written
+/// back with the crate's emitter, keeping its `%YAML` and `%TAG` directives.
+///
+/// Comments and the original scalar styles are not preserved.
+///
+/// ```rust
+/// let formatted = yyaml::format_str("{b: [1, 2], a: x}").unwrap();
+/// assert_eq!(formatted, "---\nb: \n  - 1\n  - 2\na: x\n");
+/// ```
+pub fn format_str(input: &str) -> Result<String, Error> {
+    merge_streams(&[input], MergePolicy::Concatenate)
+}
+
+/// The directive lines of each document in `input`, by document index
+fn document_directives(input: &str) -> Result<Vec<Vec<String>>, Error> {
+    let mut documents: Vec<Vec<String>> = Vec::new();
+    let mut pending = Vec::new();
+    let mut in_document = false;
+    let mut scanner = Scanner::over_str(input);
+    loop {
+        let token = scanner.peek_token()?;
+        scanner.skip();
+        match token.1 {
+            TokenType::StreamEnd | TokenType::NoToken => break,
+            TokenType::StreamStart(_) => {}
+            TokenType::VersionDirective(major, minor) => {
+                pending.push(format!("%YAML {major}.{minor}"));
+            }
+            TokenType::TagDirective(handle, prefix) => {
+                pending.push(format!("%TAG {handle} {prefix}"));
+            }
+            TokenType::DocumentStart => {
+                documents.push(std::mem::take(&mut pending));
+                in_document = true;
+            }
+            TokenType::DocumentEnd => in_document = false,
+            _ if !in_document => {
+                documents.push(Vec::new());
+                in_document = true;
+            }
+            _ => {}
+        }
+    }
+    Ok(documents)
+}
+
+fn check_tag_conflict(directives: &[String], directive: &str) -> Result<(), Error> {
+    let Some(handle) = directive.strip_prefix("%TAG ").and_then(|d| d.split(' ').next()) else {
+        return Ok(());
+    };
+    let prefix = format!("%TAG {handle} ");
+    match directives.iter().find(|d| d.starts_with(&prefix) && *d != directive) {
+        Some(_) => Err(Error::Custom(format!(
+            "conflicting %TAG directives for handle {handle}"
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// A document tree [`deep_merge`] can combine, so [`Yaml`] streams and
+/// [`OverlayValue`](crate::OverlayValue) layers merge the same way
+pub(crate) trait MergeTree: Sized {
+    type Map: IntoIterator<Item = (Self, Self)>;
+
+    /// The mapping `self` is, if it is one
+    fn mapping_mut(&mut self) -> Option<&mut Self::Map>;
+    /// The mapping `self` is, or `self` back if it is not one
+    fn into_mapping(self) -> Result<Self::Map, Self>;
+    fn get_mut<'m>(map: &'m mut Self::Map, key: &Self) -> Option<&'m mut Self>;
+    fn insert(map: &mut Self::Map, key: Self, value: Self);
+}
+
+impl MergeTree for Yaml {
+    type Map = LinkedHashMap<Self, Self>;
+
+    fn mapping_mut(&mut self) -> Option<&mut Self::Map> {
+        match self {
+            Self::Hash(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    fn into_mapping(self) -> Result<Self::Map, Self> {
+        match self {
+            Self::Hash(map) => Ok(map),
+            other => Err(other),
+        }
+    }
+
+    fn get_mut<'m>(map: &'m mut Self::Map, key: &Self) -> Option<&'m mut Self> {
+        map.get_mut(key)
+    }
+
+    fn insert(map: &mut Self::Map, key: Self, value: Self) {
+        map.insert(key, value);
+    }
+}
+
+impl MergeTree for Value {
+    type Map = Mapping;
+
+    fn mapping_mut(&mut self) -> Option<&mut Mapping> {
+        match self {
+            Self::Mapping(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    fn into_mapping(self) -> Result<Mapping, Self> {
+        match self {
+            Self::Mapping(map) => Ok(map),
+            other => Err(other),
+        }
+    }
+
+    fn get_mut<'m>(map: &'m mut Mapping, key: &Self) -> Option<&'m mut Self> {
+        map.get_mut(key)
+    }
+
+    fn insert(map: &mut Mapping, key: Self, value: Self) {
+        map.insert(key, value);
+    }
+}
+
+/// Merge `overlay` into `base`: keys of two mappings merge recursively,
+/// anything else replaces `base`
+pub(crate) fn deep_merge<T: MergeTree>(base: &mut T, overlay: T) {
+    let Some(map) = base.mapping_mut() else {
+        *base = overlay;
+        return;
+    };
+    match overlay.into_mapping() {
+        Ok(entries) => {
+            for (key, value) in entries {
+                match T::get_mut(map, &key) {
+                    Some(existing) => deep_merge(existing, value),
+                    None => T::insert(map, key, value),
+                }
+            }
+        }
+        Err(overlay) => *base = overlay,
+    }
+}
+
+fn write_document(out: &mut String, directives: &[String], doc: &Yaml) -> Result<(), Error> {
+    // Directives after an earlier document need an explicit end marker
+    if !out.is_empty() && !directives.is_empty() {
+        out.push_str("...\n");
+    }
+    for directive in directives {
+        out.push_str(directive);
+        out.push('\n');
+    }
+    YamlEmitter::new(out).dump(doc)?;
+    out.push('\n');
+    Ok(())
+}
@@ -0,0 +1,331 @@
+//! Lightweight document outline for navigation UIs
+//!
+//! [`outline`] walks the scanner's token stream once and records every node's
+//! path, kind and extent without constructing a value tree. Block nesting is
+//! inferred from token columns, flow nesting from the bracket tokens.
+
+use std::collections::BTreeMap;
+
+use crate::error::Marker;
+use crate::events::{TScalarStyle, TokenType};
+use crate::pointer;
+use crate::scanner::Scanner;
+use crate::Error;
+
+/// Kind of node an [`OutlineEntry`] describes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutlineKind {
+    Mapping,
+    Sequence,
+    Scalar,
+    Alias,
+}
+
+/// One node of a document outline.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OutlineEntry {
+    /// Index of the document within the stream.
+    pub document: usize,
+    /// JSON Pointer to the node within its document; `""` is the root.
+    pub path: String,
+    pub kind: OutlineKind,
+    /// Start of the node's first token and end of its last token.
+    pub span: (Marker, Marker),
+    /// Key count for mappings, item count for sequences, `None` for leaves.
+    pub len: Option<usize>,
+}
+
+/// Compute the outline of every document in `s`, in document order.
+///
+/// ```rust
+/// let entries = yyaml::outline("server:\n  port: 80\n").unwrap();
+/// assert_eq!(entries[1].path, "/server");
+/// assert_eq!(entries[1].len, Some(1));
+/// ```
+pub fn outline(s: &str) -> Result<Vec<OutlineEntry>, Error> {
+    Ok(walk(s)?.entries)
+}
+
+/// The comment block above each mapping key in `s`, by the path of the
+/// key's value.
+///
+/// A block is the run of whole-line `#` comments directly above the line the
+/// key starts on, so a blank line detaches a comment. The `#` and one space
+/// after it are stripped from each line. Keys of every document share the
+/// map; a later document's comment for the same path wins.
+///
+/// ```rust
+/// let source = "# Listener settings\nserver:\n  # TCP port\n  # (privileged below 1024)\n  port: 80\n";
+/// let docs = yyaml::doc_comments(source).unwrap();
+/// assert_eq!(docs["/server"], "Listener settings");
+/// assert_eq!(docs["/server/port"], "TCP port\n(privileged below 1024)");
+/// ```
+pub fn doc_comments(s: &str) -> Result<BTreeMap<String, String>, Error> {
+    let lines: Vec<&str> = s.lines().collect();
+    let walker = walk(s)?;
+    // `#` lines inside a block or quoted scalar are content
+    let in_scalar = |line: usize| {
+        walker.scalar_spans.iter().any(|(start, end)| {
+            start.line < line && (line < end.line || (line == end.line && end.col > 0))
+        })
+    };
+    let mut docs = BTreeMap::new();
+    for (path, start) in walker.keys {
+        let Some(line) = start.line.checked_sub(1).and_then(|i| lines.get(i)) else {
+            continue;
+        };
+        // Only a key that starts its line, perhaps after `- `, owns the
+        // comments above it
+        if !line.chars().take(start.col).all(|c| c == ' ' || c == '-') {
+            continue;
+        }
+        let block: Vec<&str> = lines[..start.line - 1]
+            .iter()
+            .enumerate()
+            .rev()
+            .take_while(|(i, _)| !in_scalar(i + 1))
+            .map_while(|(_, l)| l.trim_start().strip_prefix('#'))
+            .collect();
+        if !block.is_empty() {
+            let text: Vec<&str> = block
+                .iter()
+                .rev()
+                .map(|l| l.strip_prefix(' ').unwrap_or(l).trim_end())
+                .collect();
+            docs.insert(path, text.join("\n"));
+        }
+    }
+    Ok(docs)
+}
+
+fn walk(s: &str) -> Result<Walker, Error> {
+    let mut walker = Walker::default();
+    let mut scanner = Scanner::over_str(s);
+    loop {
+        let token = scanner.peek_token()?;
+        let end = match &token.1 {
+            // plain scalars are scanned through their trailing line break
+            TokenType::Scalar(TScalarStyle::Plain, value, _) if !value.contains('\n') => {
+                let len = value.chars().count();
+                Marker {
+                    index: token.0.index + len,
+                    col: token.0.col + len,
+                    byte_offset: token.0.byte_offset + value.len(),
+                    ..token.0
+                }
+            }
+            _ => scanner.mark(),
+        };
+        scanner.skip();
+        if matches!(token.1, TokenType::StreamEnd | TokenType::NoToken) {
+            walker.close_all();
+            break;
+        }
+        let is_key = matches!(token.1, TokenType::Scalar(..) | TokenType::Alias(_))
+            && matches!(scanner.peek_token()?.1, TokenType::Value);
+        walker.token(token.1, token.0, end, is_key);
+    }
+    Ok(walker)
+}
+
+struct Frame {
+    entry: usize,
+    kind: OutlineKind,
+    indent: usize,
+    flow: bool,
+    count: usize,
+    key: Option<String>,
+}
+
+#[derive(Default)]
+struct Walker {
+    entries: Vec<OutlineEntry>,
+    frames: Vec<Frame>,
+    document: usize,
+    next_document: usize,
+    open: bool,
+    last_end: Marker,
+    /// Path of each mapping key's value with where the key starts
+    keys: Vec<(String, Marker)>,
+    /// Start and end of each scalar spanning more than one line
+    scalar_spans: Vec<(Marker, Marker)>,
+}
+
+impl Walker {
+    fn token(&mut self, token: TokenType, start: Marker, end: Marker, is_key: bool) {
+        if matches!(token, TokenType::Scalar(..)) && end.line > start.line {
+            self.scalar_spans.push((start, end));
+        }
+        match token {
+            TokenType::DocumentStart => {
+                self.close_all();
+                self.open = false;
+                self.begin_document();
+            }
+            TokenType::DocumentEnd => {
+                self.close_all();
+                self.open = false;
+            }
+            TokenType::Scalar(_, value, _) if is_key => {
+                self.begin_document();
+                self.key(value, start);
+            }
+            TokenType::Alias(name) if is_key => {
+                self.begin_document();
+                self.key(format!("*{name}"), start);
+            }
+            TokenType::Scalar(..) => {
+                self.begin_document();
+                self.node(OutlineKind::Scalar, start, end, false);
+            }
+            TokenType::Alias(_) => {
+                self.begin_document();
+                self.node(OutlineKind::Alias, start, end, false);
+            }
+            TokenType::BlockEntry => {
+                self.begin_document();
+                self.block_entry(start);
+            }
+            TokenType::FlowSequenceStart => {
+                self.begin_document();
+                self.node(OutlineKind::Sequence, start, end, true);
+            }
+            TokenType::FlowMappingStart => {
+                self.begin_document();
+                self.node(OutlineKind::Mapping, start, end, true);
+            }
+            TokenType::FlowSequenceEnd | TokenType::FlowMappingEnd => {
+                self.last_end = end;
+                while let Some(frame) = self.frames.last() {
+                    let flow = frame.flow;
+                    self.close();
+                    if flow {
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+        self.last_end = end;
+    }
+
+    fn begin_document(&mut self) {
+        if !self.open {
+            self.open = true;
+            self.document = self.next_document;
+            self.next_document += 1;
+        }
+    }
+
+    fn in_flow(&self) -> bool {
+        self.frames.last().is_some_and(|frame| frame.flow)
+    }
+
+    /// A mapping key at `start`; opens a block mapping when none is active there.
+    fn key(&mut self, key: String, start: Marker) {
+        if !self.in_flow() {
+            let col = start.col;
+            while self.frames.last().is_some_and(|frame| {
+                !frame.flow
+                    && (frame.indent > col
+                        || (frame.indent == col && frame.kind == OutlineKind::Sequence))
+            }) {
+                self.close();
+            }
+            let continues = self.frames.last().is_some_and(|frame| {
+                !frame.flow && frame.indent == col && frame.kind == OutlineKind::Mapping
+            });
+            if !continues {
+                self.node(OutlineKind::Mapping, start, start, false);
+            }
+        }
+        if let Some(frame) = self.frames.last_mut()
+            && frame.kind == OutlineKind::Mapping
+        {
+            frame.count += 1;
+            let parent = &self.entries[frame.entry].path;
+            self.keys.push((format!("{parent}/{}", pointer::escape(&key)), start));
+            frame.key = Some(key);
+        }
+    }
+
+    /// A `- ` entry at `start`; opens a block sequence when none is active there.
+    fn block_entry(&mut self, start: Marker) {
+        let col = start.col;
+        while self.frames.last().is_some_and(|frame| {
+            !frame.flow
+                && (frame.indent > col
+                    || (frame.indent == col
+                        && frame.kind == OutlineKind::Mapping
+                        && frame.key.is_none()))
+        }) {
+            self.close();
+        }
+        let continues = self.frames.last().is_some_and(|frame| {
+            !frame.flow && frame.indent == col && frame.kind == OutlineKind::Sequence
+        });
+        if !continues {
+            self.node(OutlineKind::Sequence, start, start, false);
+        }
+        if let Some(frame) = self.frames.last_mut() {
+            frame.count += 1;
+        }
+    }
+
+    /// Record a node in the current slot, pushing a frame for collections.
+    fn node(&mut self, kind: OutlineKind, start: Marker, end: Marker, flow: bool) {
+        let path = self.child_path();
+        let is_collection = matches!(kind, OutlineKind::Mapping | OutlineKind::Sequence);
+        self.entries.push(OutlineEntry {
+            document: self.document,
+            path,
+            kind,
+            span: (start, end),
+            len: is_collection.then_some(0),
+        });
+        if is_collection {
+            self.frames.push(Frame {
+                entry: self.entries.len() - 1,
+                kind,
+                indent: start.col,
+                flow,
+                count: 0,
+                key: None,
+            });
+        }
+    }
+
+    fn child_path(&mut self) -> String {
+        let Some(frame) = self.frames.last_mut() else {
+            return String::new();
+        };
+        let parent = &self.entries[frame.entry].path;
+        let token = match frame.kind {
+            OutlineKind::Mapping => match frame.key.take() {
+                Some(key) => pointer::escape(&key).into_owned(),
+                None => return parent.clone(),
+            },
+            // block entries are counted when their `-` is seen
+            OutlineKind::Sequence if !frame.flow => frame.count.saturating_sub(1).to_string(),
+            _ => {
+                frame.count += 1;
+                (frame.count - 1).to_string()
+            }
+        };
+        format!("{parent}/{token}")
+    }
+
+    fn close(&mut self) {
+        if let Some(frame) = self.frames.pop() {
+            let entry = &mut self.entries[frame.entry];
+            entry.span.1 = self.last_end;
+            entry.len = Some(frame.count);
+        }
+    }
+
+    fn close_all(&mut self) {
+        while !self.frames.is_empty() {
+            self.close();
+        }
+    }
+}
@@ -0,0 +1,131 @@
+//! Layered documents resolved on lookup
+//!
+//! [`OverlayValue`] stacks documents such as built-in defaults, a config file
+//! and command-line overrides. Nothing is merged up front: each lookup walks
+//! the layers from the top, merging mappings key by key the way
+//! [`MergePolicy::DeepMerge`](crate::MergePolicy::DeepMerge) does, while any
+//! other node hides everything beneath it.
+
+use std::borrow::Cow;
+
+use serde::Serialize;
+
+use crate::Error;
+use crate::merge::deep_merge;
+use crate::value::Value;
+
+/// A stack of documents that reads as their deep merge; see the
+/// [module docs](self)
+///
+/// ```rust
+/// use yyaml::{OverlayValue, Value};
+///
+/// let defaults: Value = yyaml::from_str("server: {port: 80, tls: false}\nlog: info").unwrap();
+/// let mut config = OverlayValue::new(defaults);
+/// config.push_layer(yyaml::from_str("server: {tls: true}").unwrap());
+/// config.set("/log", "debug").unwrap();
+///
+/// assert_eq!(config.pointer("/server/port").unwrap().as_i64(), Some(80));
+/// assert_eq!(config.pointer("/server/tls").unwrap().as_bool(), Some(true));
+/// assert_eq!(config.pointer("/log").unwrap().as_str(), Some("debug"));
+/// // The defaults layer is untouched
+/// assert_eq!(config.layers()[0]["log"].as_str(), Some("info"));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OverlayValue {
+    /// Bottom layer first
+    layers: Vec<Value>,
+}
+
+impl OverlayValue {
+    /// An overlay with `base` as its only layer
+    #[must_use]
+    pub fn new(base: Value) -> Self {
+        Self { layers: vec![base] }
+    }
+
+    /// Put `layer` on top of the stack; later writes go to it. A null layer
+    /// starts out empty rather than hiding the layers below.
+    pub fn push_layer(&mut self, layer: Value) {
+        self.layers.push(layer);
+    }
+
+    /// Take the top layer off the stack
+    pub fn pop_layer(&mut self) -> Option<Value> {
+        self.layers.pop()
+    }
+
+    /// Every layer, bottom first
+    #[must_use]
+    pub fn layers(&self) -> &[Value] {
+        &self.layers
+    }
+
+    /// The effective node at `path`, a JSON Pointer
+    ///
+    /// The node is borrowed from the layer that decides it, unless mappings
+    /// from several layers meet there and have to be merged.
+    #[must_use]
+    pub fn pointer(&self, path: &str) -> Option<Cow<'_, Value>> {
+        let tokens = crate::pointer::tokens(path).ok()?;
+        let mut nodes = visible(self.documents().rev());
+        for token in &tokens {
+            nodes = visible(nodes.iter().filter_map(|node| node.pointer_child(token)));
+        }
+        match nodes.as_slice() {
+            [] => None,
+            [node] => Some(Cow::Borrowed(*node)),
+            _ => Some(Cow::Owned(merged(nodes.iter().rev().copied()))),
+        }
+    }
+
+    /// Serialize `value` into the top layer at `path`, as
+    /// [`Value::set_from`]; lower layers are never written
+    pub fn set<T>(&mut self, path: &str, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        if self.layers.is_empty() {
+            self.layers.push(Value::Null);
+        }
+        let top = self.layers.len() - 1;
+        self.layers[top].set_from(path, value)
+    }
+
+    /// The deep merge of every layer
+    #[must_use]
+    pub fn materialize(&self) -> Value {
+        merged(self.documents())
+    }
+
+    /// Layers that hold a document; an empty layer hides nothing
+    fn documents(&self) -> impl DoubleEndedIterator<Item = &Value> {
+        self.layers.iter().filter(|layer| !layer.is_null())
+    }
+}
+
+/// Of the nodes at one path, topmost first, the ones that take part in the
+/// effective node: the leading run of mappings, or the top node alone if it
+/// is not one
+fn visible<'a>(nodes: impl Iterator<Item = &'a Value>) -> Vec<&'a Value> {
+    let mut out = Vec::new();
+    for node in nodes {
+        if !node.is_mapping() {
+            if out.is_empty() {
+                out.push(node);
+            }
+            break;
+        }
+        out.push(node);
+    }
+    out
+}
+
+/// Deep merge of `layers`, bottom first
+fn merged<'a>(layers: impl Iterator<Item = &'a Value>) -> Value {
+    let mut out = Value::Null;
+    for layer in layers {
+        deep_merge(&mut out, layer.clone());
+    }
+    out
+}
@@ -5,7 +5,7 @@
 
 use crate::error::ScanError;
 use crate::parser::character_productions::CharacterProductions;
-use crate::parser::grammar::{Context as YamlContext, ParametricContext};
+use crate::parser::grammar::{YamlContext, ParametricContext};
 use crate::parser::structural_productions::StructuralProductions;
 use crate::scanner::state::ScannerState;
 
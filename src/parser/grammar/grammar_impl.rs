@@ -1,10 +1,9 @@
 //! Grammar validation and decision utilities
 
-use super::context_types::{Context as YamlContext, ParseContext};
+use super::context_types::{YamlContext, ParseContext};
 use super::parametric_context::ParametricContext;
 use super::productions::Production;
-use crate::error::ParseError;
-use crate::error::ParseErrorKind;
+use super::productions::{ParseError, ParseErrorKind};
 use crate::lexer::{Position, TokenKind};
 
 /// Grammar validation and decision utilities
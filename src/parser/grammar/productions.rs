@@ -3,8 +3,8 @@
 //! This module provides comprehensive grammar rules, production definitions,
 //! and parsing utilities for YAML 1.2 specification compliance.
 
-use crate::error::{Marker, ScanError};
-use crate::lexer::{Position, TokenKind};
+use super::context_types::{ChompingMode, YamlContext};
+use crate::lexer::Position;
 
 /// Parse error types
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -2,6 +2,66 @@ use crate::error::{Marker, ScanError};
 use crate::events::Event;
 use crate::scanner::Token;
 
+/// Indentation rules of the YAML spec that lenient parsers do not enforce.
+/// Tabs never indent a line under either set.
+///
+/// [`Profile::strict`](crate::Profile::strict) enforces every rule and
+/// [`Profile::permissive`](crate::Profile::permissive) none; pick single
+/// rules with [`LoaderOptions::indentation_rules`](crate::LoaderOptions::indentation_rules).
+///
+/// ```rust
+/// use yyaml::{IndentationRules, LoaderOptions, YamlLoader};
+///
+/// let rules = IndentationRules {
+///     reject_under_indented_flow: true,
+///     ..IndentationRules::permissive()
+/// };
+/// let options = LoaderOptions::new().indentation_rules(rules);
+/// assert!(YamlLoader::load_with_options("key: [a,\nb]\n", &options).is_err());
+/// assert!(YamlLoader::load_with_options("key: [a,\n  b]\n", &options).is_ok());
+/// // Still allowed, as no rule covers it
+/// assert!(YamlLoader::load_with_options("-\t- x\n", &options).is_ok());
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct IndentationRules {
+    /// Reject a tab between `-`, `?` or `:` and a block collection that
+    /// starts on the same line, as in `-\t- x` or `-\tkey: value`. A tab
+    /// before a scalar, as in `key:\tvalue`, stays allowed.
+    pub reject_tab_before_compact: bool,
+    /// Reject a line of a quoted scalar or flow collection that is not
+    /// indented more than the block collection holding it, as `b` in
+    /// `key: [a,\nb]`
+    pub reject_under_indented_flow: bool,
+}
+
+impl IndentationRules {
+    /// Every rule enforced, as the spec requires
+    #[must_use]
+    pub const fn strict() -> Self {
+        Self {
+            reject_tab_before_compact: true,
+            reject_under_indented_flow: true,
+        }
+    }
+
+    /// No rule enforced, as in yaml-rust and [`LoaderOptions::default`](crate::LoaderOptions)
+    #[must_use]
+    pub const fn permissive() -> Self {
+        Self {
+            reject_tab_before_compact: false,
+            reject_under_indented_flow: false,
+        }
+    }
+}
+
+/// Error for a line that breaks [`IndentationRules::reject_under_indented_flow`]
+pub(crate) const UNDER_INDENTED_FLOW: &str =
+    "lines of a flow node must be indented more than the block collection holding it";
+
+/// Error for a tab that breaks [`IndentationRules::reject_tab_before_compact`]
+pub(crate) const TAB_BEFORE_COMPACT: &str =
+    "a tab may not separate a block indicator from a collection on the same line";
+
 /// Zero-allocation indentation validation result
 /// Uses stack-allocated enum variants for blazing-fast performance
 #[derive(Debug, Clone, Copy)]
@@ -68,15 +128,15 @@ impl IndentationContext {
         if col < self.current_indent {
             if self.is_sequence {
                 Some(IndentationResult::EndSequence(Marker {
-                    index: 0,
                     line,
                     col,
+                    ..Marker::start()
                 }))
             } else {
                 Some(IndentationResult::EndMapping(Marker {
-                    index: 0,
                     line,
                     col,
+                    ..Marker::start()
                 }))
             }
         } else if col > self.current_indent && !self.is_first_entry {
@@ -84,9 +144,9 @@ impl IndentationContext {
                 found: col,
                 expected: self.current_indent,
                 marker: Marker {
-                    index: 0,
                     line,
                     col,
+                    ..Marker::start()
                 },
             })
         } else {
@@ -1,19 +1,32 @@
 // Parser removed - using StateMachine directly
+use crate::diagnostics::{Diagnostic, Severity};
 use crate::error::{Marker, ScanError};
-use crate::events::{Event, EventReceiver, TScalarStyle, TokenType};
+use crate::events::{
+    AnchorTable, Event, EventReceiver, MarkedEventReceiver, TScalarStyle, TaggedEventReceiver,
+    TokenType,
+};
 use crate::linked_hash_map::LinkedHashMap;
-use crate::yaml::Yaml;
+use crate::parser::metrics::{DocumentMetrics, ParseMetrics};
+use crate::parser::options::{LoaderOptions, Profile, SchemaMode};
+use crate::parser::state_machine::{MAX_ALIAS_EXPANSION, marked_anchor};
+use crate::scanner::Scanner;
+use crate::scanner::state::StrChars;
+use crate::source_map::SourceMap;
+use crate::tags::SET;
+use crate::value::{Tag, TaggedValue, Value};
+use crate::yaml::{BadValueKind, InvalidNode, Yaml};
+use crate::Error;
 use log::{debug, trace, warn};
+use std::cell::Cell;
 use std::collections::HashMap;
+use std::time::Instant;
 
 /// Our main "public" API: load from a string → produce Vec<Yaml>.
 pub struct YamlLoader;
 
 impl YamlLoader {
     pub fn load_from_str(s: &str) -> Result<Vec<Yaml>, ScanError> {
-        println!("=== YamlLoader::load_from_str ENTRY with: '{}' ===", s);
         // Fast path for simple cases - zero allocation, blazing fast
-        println!("YamlLoader: trying fast parse");
         match Self::try_fast_parse(s) {
             Ok(Some(result)) => {
                 debug!("Fast parser succeeded with: {result:?}");
@@ -21,7 +34,6 @@ impl YamlLoader {
             }
             Ok(None) => {
                 debug!("Fast parser detected complex syntax, falling back to full parser");
-                println!("YamlLoader: fast parser returned None, falling back to StateMachine");
             } // Fall through to full parser
             Err(error) => {
                 debug!("Fast parser failed: {error:?}");
@@ -31,13 +43,10 @@ impl YamlLoader {
 
         // Handle multi-document streams
         let mut documents = Vec::new();
-        println!("YamlLoader: creating StateMachine");
-        let mut state_machine = crate::parser::state_machine::StateMachine::new(s.chars());
-        println!("YamlLoader: StateMachine created, starting document parsing loop");
+        let mut state_machine = crate::parser::state_machine::StateMachine::over_str(s);
 
         // Process all documents in stream
         while !state_machine.at_stream_end() {
-            println!("YamlLoader: parsing next document...");
             match state_machine.parse_next_document() {
                 Ok(Some(doc)) => {
                     debug!("Parsed document: {doc:?}");
@@ -60,12 +69,371 @@ impl YamlLoader {
         Ok(documents)
     }
 
+    /// The first document of `s`, or null for an empty stream. The parser
+    /// stops at the end of that document, so later documents are never
+    /// scanned and their errors go unreported.
+    pub(crate) fn load_first(s: &str) -> Result<Yaml, ScanError> {
+        let mut state_machine = crate::parser::state_machine::StateMachine::over_str(s);
+        Ok(state_machine.parse_next_document()?.unwrap_or(Yaml::Null))
+    }
+
+    /// Every document of `s` as a [`Value`], built from the parser's events
+    /// as they are read, so no [`Yaml`] tree is made; untagged plain scalars
+    /// resolve with `schema`. An empty stream reads as one null document.
+    pub(crate) fn load_values(s: &str, schema: SchemaMode) -> Result<Vec<Value>, ScanError> {
+        let mut state_machine = crate::parser::state_machine::StateMachine::over_str(s)
+            .with_schema(schema)
+            .with_streamed_nodes();
+        let mut receiver = ValueReceiver::default();
+        while !state_machine.at_stream_end()
+            && state_machine.stream_next_document(&mut receiver, ValueReceiver::check)?
+        {}
+        if receiver.docs.is_empty() {
+            receiver.docs.push(Value::Null);
+        }
+        Ok(receiver.docs)
+    }
+
+    /// Parse `s` into events for `receiver` without expanding aliases.
+    ///
+    /// Anchored nodes carry their anchor id, and names for the ids are in
+    /// the returned table. Scalars are replayed after resolution, so their
+    /// style is plain for untagged non-strings and double-quoted for strings.
+    ///
+    /// ```rust
+    /// use yyaml::{Event, EventReceiver, YamlLoader};
+    ///
+    /// struct Collect(Vec<Event>);
+    /// impl EventReceiver for Collect {
+    ///     fn on_event(&mut self, ev: Event) {
+    ///         self.0.push(ev);
+    ///     }
+    /// }
+    ///
+    /// let mut events = Collect(Vec::new());
+    /// let anchors = YamlLoader::load_events("base: &b {x: 1}\nmore: *b\n", &mut events).unwrap();
+    /// let id = anchors.id("b").unwrap();
+    /// assert!(events.0.contains(&Event::MappingStart(id, Some("b".into()))));
+    /// assert!(events.0.contains(&Event::Alias(id)));
+    /// ```
+    pub fn load_events<R: EventReceiver>(s: &str, receiver: &mut R) -> Result<AnchorTable, Error> {
+        let mut state_machine =
+            crate::parser::state_machine::StateMachine::over_str(s).with_kept_anchors();
+        let mut docs = Vec::new();
+        while !state_machine.at_stream_end() {
+            match state_machine.parse_next_document()? {
+                Some(doc) => docs.push(doc),
+                None => break,
+            }
+        }
+        let anchors = AnchorTable::new(state_machine.take_anchor_names());
+        receiver.on_event(Event::StreamStart);
+        for doc in &docs {
+            receiver.on_event(Event::DocumentStart);
+            emit_node(doc, None, 0, &anchors, receiver);
+            receiver.on_event(Event::DocumentEnd);
+        }
+        receiver.on_event(Event::StreamEnd);
+        Ok(anchors)
+    }
+
+    /// Parse `s` into events for `receiver` as the parser reads each node,
+    /// with where it occurs, without expanding aliases.
+    ///
+    /// Unlike [`load_events`](Self::load_events), every node written gives
+    /// events, so both values of a repeated key are reported, and on an
+    /// error `receiver` has had the events read before it. Scalars carry
+    /// their resolved value as in `load_events`.
+    ///
+    /// ```rust
+    /// use yyaml::{Event, EventReceiver, YamlLoader};
+    ///
+    /// struct Collect(Vec<Event>);
+    /// impl EventReceiver for Collect {
+    ///     fn on_event(&mut self, ev: Event) {
+    ///         self.0.push(ev);
+    ///     }
+    /// }
+    ///
+    /// let mut events = Collect(Vec::new());
+    /// assert!(YamlLoader::parse_events("a: [1, 2\n", &mut events).is_err());
+    /// assert_eq!(events.0.last(), Some(&Event::Scalar("2".into(), yyaml::TScalarStyle::Plain, 0, None, None)));
+    /// ```
+    pub fn parse_events<R: MarkedEventReceiver>(
+        s: &str,
+        receiver: &mut R,
+    ) -> Result<AnchorTable, Error> {
+        let mut state_machine = crate::parser::state_machine::StateMachine::over_str(s)
+            .with_kept_anchors()
+            .with_event_log();
+        loop {
+            let document = state_machine.parse_next_document();
+            for (event, mark) in state_machine.take_events() {
+                receiver.on_event(event, mark);
+            }
+            if document?.is_none() {
+                break;
+            }
+        }
+        Ok(AnchorTable::new(state_machine.take_anchor_names()))
+    }
+
+    /// Load a stream like [`load_from_str`](Self::load_from_str), with the
+    /// byte ranges of the input behind every node of the documents.
+    pub fn load_with_source_map(s: &str) -> Result<(Vec<Yaml>, SourceMap), Error> {
+        let mut state_machine =
+            crate::parser::state_machine::StateMachine::over_str(s).with_span_log();
+        let mut documents = Vec::new();
+        let mut spans = Vec::new();
+        while !state_machine.at_stream_end() {
+            match state_machine.parse_next_document()? {
+                Some(doc) => {
+                    documents.push(doc);
+                    spans.push(state_machine.take_spans());
+                }
+                None => break,
+            }
+        }
+        if documents.is_empty() {
+            documents.push(Yaml::Null);
+        }
+        Ok((documents, SourceMap::new(s, spans)))
+    }
+
+    /// Options that load with the rules of `profile`, for
+    /// [`load_with_options`](Self::load_with_options)
+    #[must_use]
+    pub fn with_profile(profile: Profile) -> LoaderOptions {
+        LoaderOptions::new().profile(profile)
+    }
+
+    /// Load a stream like [`load_from_str`](Self::load_from_str), reporting
+    /// progress and honouring cancellation from `options`.
+    ///
+    /// Cancellation is checked at token boundaries and surfaces as
+    /// [`Error::Cancelled`].
+    pub fn load_with_options(s: &str, options: &LoaderOptions) -> Result<Vec<Yaml>, Error> {
+        Self::load_truncated(s, options).map(|(documents, _)| documents)
+    }
+
+    /// Load a stream like [`load_with_options`](Self::load_with_options),
+    /// also telling whether documents past
+    /// [`LoaderOptions::max_documents`] were left out under
+    /// [`LoaderOptions::truncate_documents`].
+    ///
+    /// ```rust
+    /// use yyaml::{LoaderOptions, YamlLoader};
+    ///
+    /// let options = LoaderOptions::new().max_documents(2).truncate_documents(true);
+    /// let (docs, truncated) = YamlLoader::load_truncated("a\n---\nb\n---\nc\n", &options).unwrap();
+    /// assert_eq!((docs.len(), truncated), (2, true));
+    /// let (docs, truncated) = YamlLoader::load_truncated("a\n---\nb\n", &options).unwrap();
+    /// assert_eq!((docs.len(), truncated), (2, false));
+    /// ```
+    pub fn load_truncated(s: &str, options: &LoaderOptions) -> Result<(Vec<Yaml>, bool), Error> {
+        if options.is_cancelled() {
+            return Err(Error::Cancelled);
+        }
+        // The fast path cannot tell `key:` from `key: ~`, accepts empty flow
+        // entries, resolves the core schema, enforces no limits and checks
+        // no cancellation, so it only applies to the defaults
+        if options.allows_fast_path()
+            && let Some(result) = Self::try_fast_parse(s)?
+        {
+            if let Some(report) = &options.verify_fast_path
+                && let Some(diagnostic) = Self::fast_path_divergence(s, &result)
+            {
+                report(&diagnostic);
+            }
+            return Ok((vec![result], false));
+        }
+
+        let consumed = Cell::new(0usize);
+        let chars = StrChars::new(s).counting(&consumed);
+        let mut state_machine = crate::parser::state_machine::StateMachine::over_str(chars)
+            .with_empty_scalar(options.empty_scalar)
+            .with_strict_flow(options.strict_flow)
+            .with_schema(options.schema)
+            .with_integer_overflow(options.integer_overflow)
+            .with_null_words(options.null_words)
+            .with_length_limits(options.max_scalar_len, options.max_key_len)
+            .with_duplicate_keys_rejected(options.reject_duplicate_keys)
+            .with_strict_tags(options.strict_tags)
+            .with_separating_tabs(!options.forbid_tabs)
+            .with_indentation_rules(options.indentation)
+            .with_max_depth(options.max_depth)
+            .with_node_middleware(options.node_middleware.clone())
+            .with_anchor_library(options.anchor_library.clone());
+        let mut documents = Vec::new();
+        let mut reported = 0usize;
+
+        while !state_machine.at_stream_end() {
+            if let Some(max) = options.max_documents
+                && documents.len() == max
+            {
+                let next = state_machine.scanner.peek_token()?;
+                let excess = match next.1 {
+                    TokenType::StreamEnd => None,
+                    // Nothing is parsed yet, so look past the stream start
+                    TokenType::StreamStart(_) => Self::document_starts(s)?.first().copied(),
+                    _ => Some(next.0),
+                };
+                if let Some(mark) = excess {
+                    if options.truncate_documents {
+                        return Ok((documents, true));
+                    }
+                    let info = format!("stream has more than {max} documents");
+                    return Err(ScanError::new(mark, &info).into());
+                }
+            }
+            let docs_emitted = documents.len();
+            let next = state_machine.parse_next_document_with(|| {
+                if options.is_cancelled() {
+                    return Err(Error::Cancelled);
+                }
+                if consumed.get() != reported {
+                    reported = consumed.get();
+                    options.report(reported, docs_emitted);
+                }
+                Ok(())
+            })?;
+            match next {
+                Some(doc) => {
+                    documents.push(doc);
+                    options.report(consumed.get(), documents.len());
+                }
+                None => break,
+            }
+        }
+
+        // An empty stream reads as one null document, unless none are allowed
+        if documents.is_empty() && options.max_documents != Some(0) {
+            documents.push(Yaml::Null);
+        }
+        Ok((documents, false))
+    }
+
+    /// Load a stream like [`load_from_str`](Self::load_from_str) and collect
+    /// [`ParseMetrics`] along the way.
+    ///
+    /// With the `tracing` feature enabled the load, each document and the
+    /// final counters are also reported as `tracing` spans and events.
+    pub fn load_from_str_instrumented(s: &str) -> Result<(Vec<Yaml>, ParseMetrics), ScanError> {
+        trace_span!("yyaml.load", bytes = s.len());
+        let start = Instant::now();
+        let mut metrics = ParseMetrics {
+            bytes: s.len(),
+            ..ParseMetrics::default()
+        };
+
+        if let Some(result) = Self::try_fast_parse(s)? {
+            metrics.fast_path = true;
+            metrics.push(DocumentMetrics::measure(&result, 0..s.len(), 0, start.elapsed()));
+            metrics.elapsed = start.elapsed();
+            return Ok((vec![result], metrics));
+        }
+
+        let mut state_machine = crate::parser::state_machine::StateMachine::over_str(s);
+        let mut documents = Vec::new();
+        while !state_machine.at_stream_end() {
+            let doc_start = Instant::now();
+            let offset = state_machine.scanner.peek_token()?.0.byte_offset;
+            let tokens_before = state_machine.scanner.tokens_scanned();
+            match state_machine.parse_next_document()? {
+                Some(doc) => {
+                    let tokens = state_machine.scanner.tokens_scanned() - tokens_before;
+                    let elapsed = doc_start.elapsed();
+                    let end = state_machine.scanner.peek_token()?.0.byte_offset;
+                    metrics.push(DocumentMetrics::measure(&doc, offset..end, tokens, elapsed));
+                    documents.push(doc);
+                }
+                None => break,
+            }
+        }
+
+        if documents.is_empty() {
+            documents.push(Yaml::Null);
+        }
+        metrics.tokens_scanned = state_machine.scanner.tokens_scanned();
+        metrics.elapsed = start.elapsed();
+        trace_event!(
+            tokens_scanned = metrics.tokens_scanned,
+            events_emitted = metrics.events_emitted,
+            estimated_allocations = metrics.estimated_allocations,
+            documents = metrics.documents.len(),
+            "yaml stream loaded"
+        );
+        Ok((documents, metrics))
+    }
+
+    /// Compare a fast-path result against the full parser
+    fn fast_path_divergence(s: &str, fast: &Yaml) -> Option<Diagnostic> {
+        let mut state_machine = crate::parser::state_machine::StateMachine::over_str(s);
+        let mut full = Vec::new();
+        let outcome = loop {
+            if state_machine.at_stream_end() {
+                break Ok(full);
+            }
+            match state_machine.parse_next_document() {
+                Ok(Some(doc)) => full.push(doc),
+                Ok(None) => break Ok(full),
+                Err(error) => break Err(error),
+            }
+        };
+        let message = match outcome {
+            Ok(docs) if docs.is_empty() && *fast == Yaml::Null => return None,
+            Ok(docs) if docs.len() == 1 && docs[0] == *fast => return None,
+            Ok(docs) => format!("fast path produced {fast:?} but the full parser produced {docs:?}"),
+            Err(error) => format!("fast path produced {fast:?} but the full parser failed: {error}"),
+        };
+        let mut snippet: String = s.chars().take(80).collect();
+        if snippet.len() < s.len() {
+            snippet.push_str("...");
+        }
+        Some(Diagnostic {
+            severity: Severity::Error,
+            code: "fast-path-divergence",
+            message: format!("{message} for input {snippet:?}"),
+            mark: Marker::default(),
+            related: None,
+        })
+    }
+
+    /// Position where each document of `s` begins: its first directive,
+    /// its `---`, or the first token of a bare document.
+    pub(crate) fn document_starts(s: &str) -> Result<Vec<Marker>, ScanError> {
+        let mut scanner = Scanner::over_str(s);
+        let mut starts = Vec::new();
+        let mut directives = None;
+        let mut open = false;
+        loop {
+            let token = scanner.peek_token()?;
+            scanner.skip();
+            match token.1 {
+                TokenType::StreamStart(_) => {}
+                TokenType::StreamEnd | TokenType::NoToken => break,
+                TokenType::VersionDirective(..) | TokenType::TagDirective(..) => {
+                    directives.get_or_insert(token.0);
+                }
+                TokenType::DocumentStart => {
+                    starts.push(directives.take().unwrap_or(token.0));
+                    open = true;
+                }
+                TokenType::DocumentEnd => open = false,
+                _ if !open => {
+                    starts.push(token.0);
+                    open = true;
+                }
+                _ => {}
+            }
+        }
+        Ok(starts)
+    }
+
     /// Blazing-fast zero-allocation parser for common simple cases with production-grade error handling
     /// Handles: "key: value", "- item", "[1, 2, 3]", "{key: value}", multi-line mappings, and simple scalars
     fn try_fast_parse(s: &str) -> Result<Option<Yaml>, ScanError> {
-        println!("try_fast_parse called with: '{}'", s);
         let mut trimmed = s.trim();
-        println!("try_fast_parse: trimmed = '{}'", trimmed);
 
         // Strip BOM if present for accurate parsing decisions per YAML 1.2
         if trimmed.starts_with('\u{feff}') {
@@ -83,8 +451,31 @@ impl YamlLoader {
             return Ok(None);
         }
 
-        // Simple scalar cases (no structure indicators)
-        if !trimmed.contains(':')
+        // Escapes and doubled quotes need the scanner's quoted-scalar handling
+        if trimmed.contains('\\') || trimmed.contains("''") {
+            return Ok(None);
+        }
+
+        // A quote left open on its line continues on the next one or is an
+        // error; either way it is the scanner's to handle
+        if trimmed
+            .lines()
+            .any(|line| line.matches('"').count() % 2 == 1 || line.matches('\'').count() % 2 == 1)
+        {
+            return Ok(None);
+        }
+
+        // A trailing comment ends the plain scalar before it; only the
+        // scanner strips it
+        if trimmed.contains(" #") || trimmed.contains("\t#") {
+            return Ok(None);
+        }
+
+        // Simple scalar cases (no structure indicators); line breaks, comments,
+        // directives and document markers need the full parser
+        if !trimmed.contains([':', '\n', '#', '%'])
+            && !trimmed.starts_with("...")
+            && !trimmed.starts_with(['&', '*', '!'])
             && !trimmed.contains('-')
             && !trimmed.contains('[')
             && !trimmed.contains('{')
@@ -189,7 +580,7 @@ impl YamlLoader {
             // Detect complex indentation patterns that require full parser
             let mut prev_indent = None;
             for line in trimmed.lines() {
-                if !line.trim().is_empty() && line.contains(':') {
+                if !line.trim().is_empty() && Self::find_value_indicator(line).is_some() {
                     let indent = line.len() - line.trim_start().len();
                     if let Some(prev) = prev_indent
                         && indent != prev && indent != 0 {
@@ -224,9 +615,8 @@ impl YamlLoader {
         }
 
         // Single-line mapping: "key: value"
-        if trimmed.contains(':')
-            && trimmed.lines().count() == 1
-            && let Some(colon_pos) = trimmed.find(':')
+        if trimmed.lines().count() == 1
+            && let Some(colon_pos) = Self::find_value_indicator(trimmed)
         {
             let key_part = trimmed[..colon_pos].trim();
             let value_part = trimmed[colon_pos + 1..].trim();
@@ -236,14 +626,14 @@ impl YamlLoader {
                 && !key_part.contains('{')
                 && !value_part.contains('[')
                 && !value_part.contains('{')
-                && !value_part.contains(':')
+                && Self::find_value_indicator(value_part).is_none()
                 && !key_part.contains('&')
                 && !key_part.contains('*')
                 && !value_part.contains('&')
                 && !value_part.contains('*')
             {
                 let mut hash = crate::linked_hash_map::LinkedHashMap::new();
-                let key = Yaml::String(key_part.to_string());
+                let key = Self::parse_scalar_direct(key_part);
                 let value = if value_part.is_empty() {
                     Yaml::Null
                 } else {
@@ -260,7 +650,17 @@ impl YamlLoader {
             if inner.is_empty() {
                 return Ok(Some(Yaml::Array(Vec::new())));
             }
+            // A trailing comma does not add an entry
+            if inner.ends_with(',') {
+                return Ok(None);
+            }
 
+            if !inner.split(',').all(|item| {
+                let item = item.trim();
+                item.is_empty() || Self::is_simple_flow_scalar(item)
+            }) {
+                return Ok(None);
+            }
             let items: Vec<Yaml> = inner
                 .split(',')
                 .map(|item| Self::parse_scalar_direct(item.trim()))
@@ -271,7 +671,6 @@ impl YamlLoader {
         // Simple mapping case: "{key: value}" - only handle single key-value pairs
         if trimmed.starts_with('{') && trimmed.ends_with('}') && trimmed.lines().count() == 1 {
             let inner = &trimmed[1..trimmed.len() - 1].trim();
-            println!("Fast parser: processing flow mapping '{}'", inner);
             if inner.is_empty() {
                 return Ok(Some(Yaml::Hash(
                     crate::linked_hash_map::LinkedHashMap::new(),
@@ -280,17 +679,17 @@ impl YamlLoader {
 
             // Check for multiple key-value pairs (contains comma) - fall back to full parser
             if inner.contains(',') {
-                println!("Fast parser: detected comma in '{}', falling back to StateMachine", inner);
+                trace!("Fast parser found several flow mapping entries, falling back to full parser");
                 return Ok(None);
             }
 
-            if let Some(colon_pos) = inner.find(':') {
+            if let Some(colon_pos) = inner.find(": ") {
                 let key_str = inner[..colon_pos].trim();
                 let value_str = inner[colon_pos + 1..].trim();
 
-                if !key_str.is_empty() && !value_str.is_empty() {
+                if Self::is_simple_flow_scalar(key_str) && Self::is_simple_flow_scalar(value_str) {
                     let mut hash = crate::linked_hash_map::LinkedHashMap::new();
-                    let key = Yaml::String(key_str.to_string());
+                    let key = Self::parse_scalar_direct(key_str);
                     let value = Self::parse_scalar_direct(value_str);
                     hash.insert(key, value);
                     return Ok(Some(Yaml::Hash(hash)));
@@ -301,6 +700,36 @@ impl YamlLoader {
         Ok(None)
     }
 
+    /// Byte offset of the first `:` that separates a block key from its value.
+    ///
+    /// In block context only a `:` followed by whitespace or the end of the
+    /// line is a value indicator, so `a:1` and the second colon of
+    /// `url: http://x` stay inside their plain scalars.
+    fn find_value_indicator(line: &str) -> Option<usize> {
+        line.match_indices(':')
+            .map(|(pos, _)| pos)
+            .find(|&pos| {
+                line[pos + 1..]
+                    .chars()
+                    .next()
+                    .is_none_or(char::is_whitespace)
+            })
+    }
+
+    /// Whether a flow entry is a lone plain scalar whose meaning does not
+    /// depend on `:` or indicator handling that only the scanner gets right
+    fn is_simple_flow_scalar(item: &str) -> bool {
+        let Some(first) = item.chars().next() else {
+            return false;
+        };
+        !"&*!?|>%@`'\"#".contains(first)
+            && !item.starts_with("- ")
+            && !item.ends_with(':')
+            && !item.contains(": ")
+            && !item.contains(" #")
+            && !item.contains(['[', ']', '{', '}'])
+    }
+
     /// Intelligent block sequence validation - zero allocation, blazing fast
     /// Validates block sequence structure with support for nested content
     #[inline]
@@ -366,7 +795,7 @@ impl YamlLoader {
             }
             
             // If this line has a colon with empty value, check if next line is indented
-            if let Some(colon_pos) = line.find(':') {
+            if let Some(colon_pos) = Self::find_value_indicator(line) {
                 let value_part = line[colon_pos + 1..].trim();
                 if value_part.is_empty() && i + 1 < lines.len() {
                     // Check if next non-empty line is indented (nested content)
@@ -390,7 +819,7 @@ impl YamlLoader {
                 continue; // Skip empty lines and comments
             }
 
-            if let Some(colon_pos) = line.find(':') {
+            if let Some(colon_pos) = Self::find_value_indicator(line) {
                 let key_part = line[..colon_pos].trim();
                 let value_part = line[colon_pos + 1..].trim();
 
@@ -401,7 +830,7 @@ impl YamlLoader {
 
                 // Simple key-value pair - allow simple values including ~
                 if !key_part.is_empty() {
-                    let key = Yaml::String(key_part.to_string());
+                    let key = Self::parse_scalar_direct(key_part);
                     let value = if value_part.is_empty() {
                         Yaml::Null
                     } else {
@@ -573,9 +1002,8 @@ impl YamlLoader {
                 // Unexpected line that doesn't start with "- " at the expected level
                 return Err(ScanError::new(
                     Marker {
-                        index: 0,
                         line: line_num + 1,
-                        col: 0,
+                        ..Marker::start()
                     },
                     &format!(
                         "invalid block sequence: expected '- ' at line {}, found '{}'",
@@ -629,37 +1057,17 @@ impl YamlLoader {
             return Yaml::String(trimmed[1..trimmed.len() - 1].to_string());
         }
 
-        // Handle boolean values
-        match trimmed.to_lowercase().as_str() {
-            "true" | "yes" | "on" => return Yaml::Boolean(true),
-            "false" | "no" | "off" => return Yaml::Boolean(false),
-            _ => {}
-        }
-
-        // Handle numeric values directly without recursion
-        if let Ok(int_val) = trimmed.parse::<i64>() {
-            return Yaml::Integer(int_val);
-        }
-        if let Ok(float_val) = trimmed.parse::<f64>() {
-            return Yaml::Real(float_val.to_string());
-        }
-
-        // Handle special float values
-        match trimmed.to_lowercase().as_str() {
-            ".inf" | "+.inf" => return Yaml::Real("+.inf".to_string()),
-            "-.inf" => return Yaml::Real("-.inf".to_string()),
-            ".nan" => return Yaml::Real(".nan".to_string()),
-            _ => {}
-        }
-
-        // Default to string
-        Yaml::String(trimmed.to_string())
+        // Plain scalars resolve exactly as in the full parser
+        Yaml::parse_str(trimmed)
     }
 }
 
 /// The data structure that builds `Yaml` AST from parser events
 pub struct YamlReceiver {
     pub docs: Vec<Yaml>,
+    /// Where the event being handled was read, if known, for the
+    /// [`Yaml::Invalid`] nodes it builds
+    mark: Option<Marker>,
     doc_stack: Vec<(Yaml, usize)>,
     key_stack: Vec<Yaml>,
     anchors: HashMap<usize, Yaml>,
@@ -680,6 +1088,7 @@ impl YamlReceiver {
     pub fn new() -> Self {
         Self {
             docs: Vec::with_capacity(1),         // Most YAML files have 1 document
+            mark: None,
             doc_stack: Vec::with_capacity(8),    // Typical nesting depth
             key_stack: Vec::with_capacity(8),    // Typical mapping depth
             anchors: HashMap::with_capacity(16), // Reasonable anchor count
@@ -688,6 +1097,11 @@ impl YamlReceiver {
         }
     }
 
+    /// A node that could not be built, read where the current event was
+    fn invalid(&self, kind: BadValueKind) -> Yaml {
+        Yaml::Invalid(Box::new(InvalidNode { kind, mark: self.mark }))
+    }
+
     #[inline]
     fn insert_new_node(&mut self, (node, aid): (Yaml, usize)) {
         // store anchor if needed - blazing-fast HashMap operations
@@ -701,7 +1115,8 @@ impl YamlReceiver {
                 Yaml::Array(ref mut arr) => arr.push(node),
                 Yaml::Hash(ref mut h) => {
                     if let Some(cur_key) = self.key_stack.last_mut() {
-                        if cur_key.is_badvalue() {
+                        // The placeholder, not an invalid key node
+                        if matches!(cur_key, Yaml::BadValue) {
                             *cur_key = node;
                         } else {
                             let mut swap_key = Yaml::BadValue;
@@ -754,8 +1169,15 @@ impl YamlReceiver {
     }
 }
 
-impl EventReceiver for YamlReceiver {
-    fn on_event(&mut self, ev: Event) {
+impl YamlReceiver {
+    /// Handle `ev`, read at `mark`, which is kept with any invalid node it
+    /// builds
+    pub fn on_event_at(&mut self, ev: Event, mark: Marker) {
+        self.mark = Some(mark);
+        self.receive(ev);
+    }
+
+    fn receive(&mut self, ev: Event) {
         trace!(
             "YAML EVENT: {:?} (doc_stack len: {}, docs len: {})",
             ev,
@@ -768,7 +1190,10 @@ impl EventReceiver for YamlReceiver {
                 self.reset_alias_tracking();
             }
             Event::DocumentEnd => match self.doc_stack.len() {
-                0 => self.docs.push(Yaml::BadValue),
+                0 => {
+                    let doc = self.invalid(BadValueKind::EmptyDocument);
+                    self.docs.push(doc);
+                }
                 1 => {
                     if let Some((doc, _)) = self.doc_stack.pop() {
                         self.docs.push(doc);
@@ -782,51 +1207,12 @@ impl EventReceiver for YamlReceiver {
                 let node = self.resolve_alias(id);
                 self.insert_new_node((node, 0));
             }
-            Event::Scalar(s, style, aid, tag) => {
-                let node = if style != TScalarStyle::Plain {
-                    Yaml::String(s)
-                } else if let Some(TokenType::Tag(ref handle, ref suffix)) = tag {
-                    // handle tag
-                    if handle == "!!" {
-                        match suffix.as_str() {
-                            "bool" => match s.parse::<bool>() {
-                                Ok(b) => Yaml::Boolean(b),
-                                Err(_) => Yaml::BadValue,
-                            },
-                            "int" => match s.parse::<i64>() {
-                                Ok(i) => Yaml::Integer(i),
-                                Err(_) => Yaml::BadValue,
-                            },
-                            "float" => match s.parse::<f64>() {
-                                Ok(_) => Yaml::Real(s),
-                                Err(_) => Yaml::BadValue,
-                            },
-                            "null" => {
-                                if s == "~" || s == "null" {
-                                    Yaml::Null
-                                } else {
-                                    Yaml::BadValue
-                                }
-                            }
-                            _ => Yaml::String(s),
-                        }
-                    } else {
-                        // Preserve custom tag by creating a Tagged variant
-                        let tag_name = if handle.is_empty() {
-                            suffix.clone()
-                        } else {
-                            format!("{}{}", handle, suffix)
-                        };
-                        let inner_value = YamlLoader::parse_scalar_direct(&s);
-                        Yaml::Tagged(tag_name, Box::new(inner_value))
-                    }
-                } else {
-                    // autodetect
-                    YamlLoader::parse_scalar_direct(&s)
-                };
+            Event::Scalar(s, style, aid, tag, _) => {
+                let node = scalar_node(s, style, tag.as_ref())
+                    .unwrap_or_else(|kind| self.invalid(kind));
                 self.insert_new_node((node, aid));
             }
-            Event::SequenceStart(aid) => {
+            Event::SequenceStart(aid, _) => {
                 self.doc_stack.push((Yaml::Array(Vec::new()), aid));
             }
             Event::SequenceEnd => {
@@ -834,7 +1220,7 @@ impl EventReceiver for YamlReceiver {
                     self.insert_new_node(top);
                 }
             }
-            Event::MappingStart(aid) => {
+            Event::MappingStart(aid, _) => {
                 let h = LinkedHashMap::new();
                 self.doc_stack.push((Yaml::Hash(h), aid));
                 self.key_stack.push(Yaml::BadValue);
@@ -858,6 +1244,271 @@ impl EventReceiver for YamlReceiver {
     }
 }
 
+impl EventReceiver for YamlReceiver {
+    fn on_event(&mut self, ev: Event) {
+        self.mark = None;
+        self.receive(ev);
+    }
+}
+
+/// The node a [`YamlReceiver`] builds for a scalar event, or why it builds
+/// a [`Yaml::Invalid`]: a plain scalar that does not read as the core
+/// schema type of its tag
+pub(crate) fn scalar_node(
+    s: String,
+    style: TScalarStyle,
+    tag: Option<&TokenType>,
+) -> Result<Yaml, BadValueKind> {
+    let mismatch = |tag: &str, value: String| BadValueKind::TagMismatch {
+        tag: tag.to_string(),
+        value,
+    };
+    Ok(if style != TScalarStyle::Plain {
+        Yaml::String(s)
+    } else if let Some(TokenType::Tag(handle, suffix)) = tag {
+        // handle tag
+        if handle == "!!" {
+            match suffix.as_str() {
+                "bool" => match s.parse::<bool>() {
+                    Ok(b) => Yaml::Boolean(b),
+                    Err(_) => return Err(mismatch(suffix, s)),
+                },
+                "int" => match s.parse::<i64>() {
+                    Ok(i) => Yaml::Integer(i),
+                    Err(_) => return Err(mismatch(suffix, s)),
+                },
+                "float" => match s.parse::<f64>() {
+                    Ok(_) => Yaml::Real(s),
+                    Err(_) => return Err(mismatch(suffix, s)),
+                },
+                "null" => {
+                    if s == "~" || s == "null" {
+                        Yaml::Null
+                    } else {
+                        return Err(mismatch(suffix, s));
+                    }
+                }
+                _ => Yaml::String(s),
+            }
+        } else {
+            // Preserve custom tag by creating a Tagged variant
+            let tag_name = if handle.is_empty() {
+                suffix.clone()
+            } else {
+                format!("{}{}", handle, suffix)
+            };
+            let inner_value = YamlLoader::parse_scalar_direct(&s);
+            Yaml::Tagged(tag_name, Box::new(inner_value))
+        }
+    } else {
+        // autodetect
+        YamlLoader::parse_scalar_direct(&s)
+    })
+}
+
+/// Builds [`Value`] documents from the events of a state machine built
+/// `with_streamed_nodes`, expanding aliases as they are read
+#[derive(Default)]
+pub(crate) struct ValueReceiver {
+    docs: Vec<Value>,
+    stack: Vec<ValueFrame>,
+    /// Tag of the collection whose start event comes next
+    next_tag: Option<String>,
+    /// Anchored values by id, with their node counts
+    anchors: HashMap<usize, (Value, usize)>,
+    /// Nodes copied in by aliases in this document
+    expanded: usize,
+    /// Why the parse has to stop, taken by [`check`](Self::check)
+    error: Option<ScanError>,
+}
+
+/// A collection being built by a [`ValueReceiver`]
+struct ValueFrame {
+    anchor: usize,
+    tag: Option<String>,
+    /// Nodes in the collection so far, itself included
+    nodes: usize,
+    content: FrameContent,
+}
+
+enum FrameContent {
+    Sequence(Vec<Value>),
+    /// Entries so far in the order written, which a `!!set` keeps, and a
+    /// key waiting for its value
+    Mapping(Vec<(Value, Value)>, Option<Value>),
+}
+
+impl ValueReceiver {
+    /// Stop the parse once an alias went over the expansion limit
+    fn check(&mut self) -> Result<(), ScanError> {
+        self.error.take().map_or(Ok(()), Err)
+    }
+
+    /// Add a finished node of `nodes` nodes to the innermost collection, or
+    /// make it the document root
+    fn insert(&mut self, value: Value, nodes: usize, anchor: usize) {
+        if anchor > 0 {
+            self.anchors.insert(anchor, (value.clone(), nodes));
+        }
+        let Some(frame) = self.stack.last_mut() else {
+            self.docs.push(value);
+            return;
+        };
+        frame.nodes += nodes;
+        match &mut frame.content {
+            FrameContent::Sequence(items) => items.push(value),
+            FrameContent::Mapping(entries, key) => match key.take() {
+                Some(key) => entries.push((key, value)),
+                None => *key = Some(value),
+            },
+        }
+    }
+
+    /// A scalar as [`emit_node`] replays it
+    fn scalar(value: String, style: TScalarStyle, tag: Option<TokenType>) -> Value {
+        let leaf = if style != TScalarStyle::Plain {
+            Yaml::String(value)
+        } else {
+            match value.as_str() {
+                "~" => Yaml::Null,
+                "true" => Yaml::Boolean(true),
+                "false" => Yaml::Boolean(false),
+                text => text.parse().map_or(Yaml::Real(value), Yaml::Integer),
+            }
+        };
+        match tag {
+            Some(TokenType::Tag(handle, suffix)) => {
+                Value::from(Yaml::Tagged(handle + &suffix, Box::new(leaf)))
+            }
+            _ => Value::from(leaf),
+        }
+    }
+
+    /// The finished collection of `frame`, converted as `From<Yaml>` does
+    fn close(frame: ValueFrame) -> Value {
+        let value = match frame.content {
+            FrameContent::Mapping(entries, _)
+                if frame.tag.as_deref() == Some(SET)
+                    && entries.iter().all(|(_, value)| value.is_null()) =>
+            {
+                return Value::ordered_set(entries.into_iter().map(|(member, _)| member));
+            }
+            FrameContent::Mapping(entries, _) => Value::Mapping(entries.into_iter().collect()),
+            FrameContent::Sequence(items) => Value::Sequence(items),
+        };
+        match frame.tag {
+            Some(tag) => Value::Tagged(Box::new(TaggedValue {
+                tag: Tag::new(tag),
+                value,
+            })),
+            None => value,
+        }
+    }
+
+    fn open(&mut self, anchor: usize, content: FrameContent) {
+        self.stack.push(ValueFrame {
+            anchor,
+            tag: self.next_tag.take(),
+            nodes: 1,
+            content,
+        });
+    }
+}
+
+impl TaggedEventReceiver for ValueReceiver {
+    fn on_collection_tag(&mut self, tag: String) {
+        self.next_tag = Some(tag);
+    }
+}
+
+impl MarkedEventReceiver for ValueReceiver {
+    fn on_event(&mut self, ev: Event, mark: Marker) {
+        match ev {
+            Event::DocumentStart => {
+                self.anchors.clear();
+                self.expanded = 0;
+            }
+            Event::Scalar(value, style, anchor, tag, _) => {
+                let value = Self::scalar(value, style, tag);
+                self.insert(value, 1, anchor);
+            }
+            Event::Alias(id) => {
+                let (value, nodes) = self.anchors.get(&id).cloned().unwrap_or_default();
+                self.expanded += nodes;
+                if self.expanded > MAX_ALIAS_EXPANSION && self.error.is_none() {
+                    self.error = Some(ScanError::new(mark, "repetition limit exceeded"));
+                }
+                self.insert(value, nodes, 0);
+            }
+            Event::SequenceStart(anchor, _) => {
+                self.open(anchor, FrameContent::Sequence(Vec::new()));
+            }
+            Event::MappingStart(anchor, _) => {
+                self.open(anchor, FrameContent::Mapping(Vec::new(), None));
+            }
+            Event::SequenceEnd | Event::MappingEnd => {
+                if let Some(frame) = self.stack.pop() {
+                    let (anchor, nodes) = (frame.anchor, frame.nodes);
+                    self.insert(Self::close(frame), nodes, anchor);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Replay `node` as events; `tag` and `anchor` are the properties of the
+/// node itself, and anchor marks left by a state machine built
+/// `with_kept_anchors` become anchor ids named from `anchors`
+pub(crate) fn emit_node<R: EventReceiver>(
+    node: &Yaml,
+    tag: Option<TokenType>,
+    anchor: usize,
+    anchors: &AnchorTable,
+    receiver: &mut R,
+) {
+    let plain = |value: String, tag| Event::Scalar(value, TScalarStyle::Plain, anchor, tag, None);
+    let name = || anchors.name(anchor).map(str::to_owned);
+    match node {
+        Yaml::String(s) => receiver.on_event(Event::Scalar(
+            s.clone(),
+            TScalarStyle::DoubleQuoted,
+            anchor,
+            tag,
+            None,
+        )),
+        Yaml::Real(s) => receiver.on_event(plain(s.clone(), tag)),
+        Yaml::Integer(i) => receiver.on_event(plain(i.to_string(), tag)),
+        Yaml::Boolean(b) => receiver.on_event(plain(b.to_string(), tag)),
+        Yaml::Null | Yaml::BadValue | Yaml::Invalid(_) => {
+            receiver.on_event(plain("~".to_string(), tag));
+        }
+        Yaml::Alias(id) => receiver.on_event(Event::Alias(*id)),
+        Yaml::Array(items) => {
+            receiver.on_event(Event::SequenceStart(anchor, name()));
+            for item in items {
+                emit_node(item, None, 0, anchors, receiver);
+            }
+            receiver.on_event(Event::SequenceEnd);
+        }
+        Yaml::Hash(map) => {
+            receiver.on_event(Event::MappingStart(anchor, name()));
+            for (key, value) in map.iter() {
+                emit_node(key, None, 0, anchors, receiver);
+                emit_node(value, None, 0, anchors, receiver);
+            }
+            receiver.on_event(Event::MappingEnd);
+        }
+        Yaml::Tagged(name, inner) => match marked_anchor(name) {
+            Some(id) => emit_node(inner, tag, id, anchors, receiver),
+            None => {
+                let tag = Some(TokenType::Tag(String::new(), name.clone()));
+                emit_node(inner, tag, anchor, anchors, receiver);
+            }
+        },
+    }
+}
+
 // Old load function removed - StateMachine::parse() handles loading directly
 /*
 pub fn load<T: Iterator<Item = char>, R: MarkedEventReceiver>(
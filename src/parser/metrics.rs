@@ -0,0 +1,173 @@
+//! Parse metrics returned by `YamlLoader::load_from_str_instrumented`
+
+use crate::yaml::Yaml;
+use std::ops::Range;
+use std::time::Duration;
+
+/// Scalars of a document by the type they resolved to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ScalarHistogram {
+    pub nulls: usize,
+    pub booleans: usize,
+    pub integers: usize,
+    pub floats: usize,
+    pub strings: usize,
+}
+
+impl ScalarHistogram {
+    /// Scalars of every type.
+    #[must_use]
+    pub const fn total(&self) -> usize {
+        self.nulls + self.booleans + self.integers + self.floats + self.strings
+    }
+
+    fn add(&mut self, other: &Self) {
+        self.nulls += other.nulls;
+        self.booleans += other.booleans;
+        self.integers += other.integers;
+        self.floats += other.floats;
+        self.strings += other.strings;
+    }
+}
+
+/// Counters collected for a single document.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DocumentMetrics {
+    /// Bytes of the input from where this document starts to where the next
+    /// one does, so the spans of a stream's documents follow on from each
+    /// other.
+    pub span: Range<usize>,
+    /// Nodes in the loaded document, with aliases already expanded.
+    pub nodes: usize,
+    pub sequences: usize,
+    pub mappings: usize,
+    pub scalars: ScalarHistogram,
+    /// Scanner tokens consumed while parsing this document.
+    pub tokens_scanned: usize,
+    /// Parser events the document corresponds to, including its start and end.
+    pub events_emitted: usize,
+    /// Rough count of heap allocations backing the constructed nodes.
+    pub estimated_allocations: usize,
+    /// Wall-clock time spent parsing the document.
+    pub elapsed: Duration,
+}
+
+impl DocumentMetrics {
+    pub(crate) fn measure(
+        doc: &Yaml,
+        span: Range<usize>,
+        tokens_scanned: usize,
+        elapsed: Duration,
+    ) -> Self {
+        let mut metrics = Self {
+            span,
+            tokens_scanned,
+            events_emitted: 2,
+            elapsed,
+            ..Self::default()
+        };
+        metrics.count(doc);
+        metrics
+    }
+
+    fn count(&mut self, node: &Yaml) {
+        if !matches!(node, Yaml::Tagged(..)) {
+            self.nodes += 1;
+        }
+        match node {
+            Yaml::Real(s) | Yaml::String(s) => {
+                if matches!(node, Yaml::Real(_)) {
+                    self.scalars.floats += 1;
+                } else {
+                    self.scalars.strings += 1;
+                }
+                self.events_emitted += 1;
+                if !s.is_empty() {
+                    self.estimated_allocations += 1;
+                }
+            }
+            Yaml::Array(items) => {
+                self.sequences += 1;
+                self.events_emitted += 2;
+                if !items.is_empty() {
+                    self.estimated_allocations += 1;
+                }
+                items.iter().for_each(|item| self.count(item));
+            }
+            Yaml::Hash(map) => {
+                self.mappings += 1;
+                self.events_emitted += 2;
+                if !map.is_empty() {
+                    // insertion order vector plus backing tree
+                    self.estimated_allocations += 2;
+                }
+                for (key, value) in map.iter() {
+                    self.count(key);
+                    self.count(value);
+                }
+            }
+            Yaml::Tagged(_, inner) => {
+                // boxed node plus tag string; the tag rides on the inner event
+                self.estimated_allocations += 2;
+                self.count(inner);
+            }
+            Yaml::Integer(_) | Yaml::Boolean(_) | Yaml::Null => {
+                match node {
+                    Yaml::Integer(_) => self.scalars.integers += 1,
+                    Yaml::Boolean(_) => self.scalars.booleans += 1,
+                    _ => self.scalars.nulls += 1,
+                }
+                self.events_emitted += 1;
+            }
+            Yaml::Alias(_) | Yaml::BadValue | Yaml::Invalid(_) => {
+                self.events_emitted += 1;
+            }
+        }
+    }
+}
+
+/// Aggregate metrics for a whole stream.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ParseMetrics {
+    /// Input length in bytes.
+    pub bytes: usize,
+    /// Whether the stream was handled by the fast path rather than the state machine.
+    pub fast_path: bool,
+    /// Total scanner tokens, including stream start and end.
+    pub tokens_scanned: usize,
+    pub events_emitted: usize,
+    pub estimated_allocations: usize,
+    pub nodes: usize,
+    /// Scalars of every document by type.
+    pub scalars: ScalarHistogram,
+    /// Total wall-clock time for the load.
+    pub elapsed: Duration,
+    /// Per-document breakdown in stream order.
+    pub documents: Vec<DocumentMetrics>,
+}
+
+impl ParseMetrics {
+    pub(crate) fn push(&mut self, doc: DocumentMetrics) {
+        self.events_emitted += doc.events_emitted;
+        self.estimated_allocations += doc.estimated_allocations;
+        self.nodes += doc.nodes;
+        self.scalars.add(&doc.scalars);
+        self.documents.push(doc);
+    }
+
+    /// Documents by size: each entry is a power-of-two byte bound and the
+    /// number of documents whose span is at most that many bytes but more
+    /// than half of it. Bounds without documents are left out.
+    #[must_use]
+    pub fn size_histogram(&self) -> Vec<(usize, usize)> {
+        let mut buckets: Vec<(usize, usize)> = Vec::new();
+        for doc in &self.documents {
+            let bound = doc.span.len().next_power_of_two();
+            match buckets.binary_search_by_key(&bound, |(b, _)| *b) {
+                Ok(at) => buckets[at].1 += 1,
+                Err(at) => buckets.insert(at, (bound, 1)),
+            }
+        }
+        buckets
+    }
+}
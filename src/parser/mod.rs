@@ -8,6 +8,9 @@ pub mod flow;
 pub mod grammar;
 pub mod indentation;
 pub mod loader;
+pub mod metrics;
+pub mod options;
+pub mod push;
 pub mod state_machine;
 pub mod structural_productions;
 
@@ -15,5 +18,12 @@ pub use ast::*;
 pub use character_productions::CharacterProductions;
 pub use flow::FlowProductions;
 pub use grammar::{ChompingMode, ParametricContext, YamlContext};
+pub use indentation::IndentationRules;
 pub use loader::YamlLoader;
+pub use metrics::{DocumentMetrics, ParseMetrics, ScalarHistogram};
+pub use options::{
+    AnchorLibrary, CancellationToken, DiagnosticCallback, EmptyScalar, IntegerOverflow,
+    LoaderOptions, NodeInfo, NodeMiddleware, Profile, ProgressCallback, SchemaMode,
+};
+pub use push::PushParser;
 pub use state_machine::{State, StateMachine};
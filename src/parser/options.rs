@@ -0,0 +1,589 @@
+//! Loader configuration for `YamlLoader::load_with_options`
+
+use crate::diagnostics::Diagnostic;
+use crate::parser::IndentationRules;
+use crate::value::Value;
+use crate::yaml::Yaml;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Callback invoked with `(bytes_processed, docs_emitted)` while loading.
+pub type ProgressCallback = dyn Fn(usize, usize) + Send + Sync;
+
+/// Callback invoked with internal diagnostics raised while loading.
+pub type DiagnosticCallback = dyn Fn(&Diagnostic) + Send + Sync;
+
+/// Callback that may replace each node as it is loaded; see
+/// [`LoaderOptions::node_middleware`].
+pub type NodeMiddleware = dyn Fn(NodeInfo<'_>, Yaml) -> Yaml + Send + Sync;
+
+/// Where a node handed to a [`NodeMiddleware`] sits in its document.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NodeInfo<'a> {
+    /// JSON Pointer of the node, or for a mapping key of the entry it
+    /// names; the empty string is the document root
+    pub path: &'a str,
+    /// Whether the node is a mapping key rather than a value
+    pub is_key: bool,
+}
+
+/// Cooperative cancellation flag shared between a loader and its caller.
+///
+/// Clones share the same flag, so a GUI or service can hand one clone to the
+/// parse and call [`cancel`](Self::cancel) on another from any thread.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request cancellation; the loader stops at the next token boundary.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// Named nodes that aliases may refer to without an anchor in the
+/// document; see [`LoaderOptions::anchor_library`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AnchorLibrary {
+    anchors: HashMap<String, Yaml>,
+}
+
+impl AnchorLibrary {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `value` as the node `*name` refers to, replacing an
+    /// earlier one of the same name.
+    #[must_use]
+    pub fn with(mut self, name: impl Into<String>, value: Value) -> Self {
+        self.anchors.insert(name.into(), value.into_yaml());
+        self
+    }
+
+    /// The node registered as `name`
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&Yaml> {
+        self.anchors.get(name)
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.anchors.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.anchors.is_empty()
+    }
+}
+
+impl<S: Into<String>> FromIterator<(S, Value)> for AnchorLibrary {
+    fn from_iter<I: IntoIterator<Item = (S, Value)>>(iter: I) -> Self {
+        iter.into_iter().fold(Self::new(), |library, (name, value)| library.with(name, value))
+    }
+}
+
+/// Value produced for a node with no content, such as the value in `key:`
+/// or an item written as a bare `-`.
+///
+/// Explicit `~` and `null` are always null, and quoted `""` is always an
+/// empty string; this only decides how empty nodes resolve.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EmptyScalar {
+    /// Empty nodes are null, so `key:` and `key: ""` stay distinct (YAML 1.2 default)
+    #[default]
+    Null,
+    /// Empty nodes are empty strings, so `key:` and `key: ""` are equivalent
+    EmptyString,
+}
+
+/// Rules for resolving untagged plain scalars such as `yes`, `0x1f` or `~`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SchemaMode {
+    /// YAML 1.2 core schema: `~`, `Null`, `0x1f`, `0o17`, `.inf` and friends
+    #[default]
+    Core,
+    /// YAML 1.2 JSON schema: only `null`, `true`, `false` and JSON numbers
+    /// are typed, so valid JSON loads exactly as a JSON parser would read
+    /// it and every other plain scalar, `yes` and `on` included, is a string
+    Json,
+}
+
+/// What a plain scalar with integer syntax loads as when its value does not
+/// fit in an `i64`, such as `9223372036854775808` or `0x8000000000000000`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IntegerOverflow {
+    /// A real, still spelled exactly when the value fits in an `i128` so
+    /// `Value` and `i128` or `u128` fields read it without rounding
+    #[default]
+    PromoteToFloat,
+    /// A positioned error
+    Error,
+    /// `i64::MAX` or `i64::MIN`
+    Saturate,
+    /// The scalar's text as a string
+    KeepString,
+}
+
+/// Options controlling how a YAML stream is loaded.
+#[derive(Clone, Default)]
+pub struct LoaderOptions {
+    pub(crate) progress: Option<Arc<ProgressCallback>>,
+    pub(crate) cancellation: Option<CancellationToken>,
+    pub(crate) empty_scalar: EmptyScalar,
+    pub(crate) strict_flow: bool,
+    pub(crate) schema: SchemaMode,
+    pub(crate) integer_overflow: IntegerOverflow,
+    pub(crate) null_words: Option<&'static [&'static str]>,
+    pub(crate) verify_fast_path: Option<Arc<DiagnosticCallback>>,
+    pub(crate) max_scalar_len: Option<usize>,
+    pub(crate) max_key_len: Option<usize>,
+    pub(crate) reject_duplicate_keys: bool,
+    pub(crate) forbid_tabs: bool,
+    pub(crate) strict_tags: bool,
+    pub(crate) indentation: IndentationRules,
+    pub(crate) max_depth: Option<usize>,
+    pub(crate) max_documents: Option<usize>,
+    pub(crate) truncate_documents: bool,
+    pub(crate) node_middleware: Option<Arc<NodeMiddleware>>,
+    pub(crate) anchor_library: Option<Arc<AnchorLibrary>>,
+}
+
+impl LoaderOptions {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Report progress as `(bytes_processed, docs_emitted)` at token boundaries
+    /// and after every document.
+    #[must_use]
+    pub fn progress<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(usize, usize) + Send + Sync + 'static,
+    {
+        self.progress = Some(Arc::new(callback));
+        self
+    }
+
+    /// Abort loading with `Error::Cancelled` once `token` is cancelled.
+    #[must_use]
+    pub fn cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Choose what empty nodes such as `key:` resolve to.
+    #[must_use]
+    pub const fn empty_scalar_as(mut self, empty: EmptyScalar) -> Self {
+        self.empty_scalar = empty;
+        self
+    }
+
+    /// Reject empty entries such as `[a, , b]`, empty keys and values such
+    /// as `{a: }`, and trailing commas in flow collections, for inputs that
+    /// are expected to be JSON.
+    ///
+    /// By default `[, a, ,]` loads as `[null, a, null]` and a single trailing
+    /// comma is accepted.
+    #[must_use]
+    pub const fn strict_flow(mut self, strict: bool) -> Self {
+        self.strict_flow = strict;
+        self
+    }
+
+    /// Fail on a scalar whose core schema tag does not fit its text, such
+    /// as `!!int ten`, instead of loading it as a [`Yaml::Invalid`] node
+    /// that records the mismatch and where the scalar was read.
+    ///
+    /// [`diagnostics::check_tag_resolution`](crate::diagnostics::check_tag_resolution)
+    /// finds them without loading.
+    #[must_use]
+    pub const fn strict_tags(mut self, strict: bool) -> Self {
+        self.strict_tags = strict;
+        self
+    }
+
+    /// Choose how untagged plain scalars resolve to types.
+    #[must_use]
+    pub const fn schema(mut self, schema: SchemaMode) -> Self {
+        self.schema = schema;
+        self
+    }
+
+    /// Choose what integers too large for an `i64` load as.
+    ///
+    /// [`diagnostics::check_integer_overflow`](crate::diagnostics::check_integer_overflow)
+    /// finds them without loading.
+    #[must_use]
+    pub const fn integer_overflow(mut self, policy: IntegerOverflow) -> Self {
+        self.integer_overflow = policy;
+        self
+    }
+
+    /// Resolve exactly `words` as null among plain scalars, in place of the
+    /// schema's own set, so a dialect that only knows `NULL` and `~` loads
+    /// `null` as a string. Pair it with
+    /// [`YamlEmitter::null_word`](crate::YamlEmitter::null_word) to write
+    /// such documents.
+    ///
+    /// Empty nodes follow [`empty_scalar_as`](Self::empty_scalar_as).
+    ///
+    /// ```rust
+    /// use yyaml::{LoaderOptions, Yaml, YamlLoader};
+    ///
+    /// let options = LoaderOptions::new().null_words(&["NULL", "~"]);
+    /// let docs = YamlLoader::load_with_options("[NULL, ~, null]", &options).unwrap();
+    /// assert!(docs[0][0].is_null() && docs[0][1].is_null());
+    /// assert_eq!(docs[0][2].as_str(), Some("null"));
+    /// ```
+    #[must_use]
+    pub const fn null_words(mut self, words: &'static [&'static str]) -> Self {
+        self.null_words = Some(words);
+        self
+    }
+
+    /// Run the full parser as well whenever the fast path succeeds, passing
+    /// any mismatch to `report` as a `fast-path-divergence` diagnostic.
+    ///
+    /// The fast-path result is still returned, so enabling this in fuzzing
+    /// or CI corpora does not change what callers see.
+    #[must_use]
+    pub fn verify_fast_path<F>(mut self, report: F) -> Self
+    where
+        F: Fn(&Diagnostic) + Send + Sync + 'static,
+    {
+        self.verify_fast_path = Some(Arc::new(report));
+        self
+    }
+
+    /// Fail with a positioned error as soon as a scalar's content grows
+    /// past `bytes`, before the whole of it is held in memory.
+    #[must_use]
+    pub const fn max_scalar_len(mut self, bytes: usize) -> Self {
+        self.max_scalar_len = Some(bytes);
+        self
+    }
+
+    /// Fail with a positioned error on a scalar mapping key longer than
+    /// `bytes`.
+    #[must_use]
+    pub const fn max_key_len(mut self, bytes: usize) -> Self {
+        self.max_key_len = Some(bytes);
+        self
+    }
+
+    /// Fail with a positioned error on a mapping key that repeats an
+    /// earlier key of the same mapping, instead of keeping the last value.
+    #[must_use]
+    pub const fn reject_duplicate_keys(mut self, reject: bool) -> Self {
+        self.reject_duplicate_keys = reject;
+        self
+    }
+
+    /// Reject tabs that separate tokens on a line, as in `key:\tvalue`.
+    ///
+    /// Tabs in indentation are always an error; tabs inside scalars are
+    /// content and stay allowed.
+    #[must_use]
+    pub const fn forbid_tabs(mut self, forbid: bool) -> Self {
+        self.forbid_tabs = forbid;
+        self
+    }
+
+    /// Enforce the spec's indentation rules chosen in `rules`; none are by
+    /// default.
+    #[must_use]
+    pub const fn indentation_rules(mut self, rules: IndentationRules) -> Self {
+        self.indentation = rules;
+        self
+    }
+
+    /// Fail once collections nest more than `depth` levels deep.
+    #[must_use]
+    pub const fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Fail with a positioned error where a stream starts its document
+    /// number `count + 1`, without parsing the rest of it. With a count of
+    /// 0 an empty stream loads as no documents rather than one null.
+    #[must_use]
+    pub const fn max_documents(mut self, count: usize) -> Self {
+        self.max_documents = Some(count);
+        self
+    }
+
+    /// Stop at the [`max_documents`](Self::max_documents) limit and return
+    /// the documents so far instead of failing, for previews.
+    /// [`YamlLoader::load_truncated`](crate::YamlLoader::load_truncated)
+    /// tells whether anything was left out.
+    #[must_use]
+    pub const fn truncate_documents(mut self, truncate: bool) -> Self {
+        self.truncate_documents = truncate;
+        self
+    }
+
+    /// Pass every node through `middleware` as soon as it is complete,
+    /// loading whatever it returns in the node's place, so values can be
+    /// masked or rewritten without walking the tree again.
+    ///
+    /// Children are visited before their parents and each mapping key
+    /// before its value; paths name keys as written. An anchor holds the
+    /// rewritten node, so an alias copies what the middleware left and is
+    /// then visited as a single node at its own path.
+    ///
+    /// ```rust
+    /// use yyaml::{LoaderOptions, Yaml, YamlLoader};
+    ///
+    /// let options = LoaderOptions::new().node_middleware(|info, node| {
+    ///     if !info.is_key && info.path.ends_with("/password") {
+    ///         Yaml::String("***".into())
+    ///     } else {
+    ///         node
+    ///     }
+    /// });
+    /// let docs = YamlLoader::load_with_options("db: {user: app, password: hunter2}", &options).unwrap();
+    /// assert_eq!(docs[0]["db"]["password"].as_str(), Some("***"));
+    /// ```
+    #[must_use]
+    pub fn node_middleware<F>(mut self, middleware: F) -> Self
+    where
+        F: Fn(NodeInfo<'_>, Yaml) -> Yaml + Send + Sync + 'static,
+    {
+        self.node_middleware = Some(Arc::new(middleware));
+        self
+    }
+
+    /// Resolve an alias with no anchor of its name in the document to the
+    /// node `library` registers under that name, so shared snippets can be
+    /// reused across files. An anchor in the document wins over the library,
+    /// and an alias found in neither is an error naming it.
+    ///
+    /// ```rust
+    /// use yyaml::{AnchorLibrary, LoaderOptions, Value, YamlLoader};
+    ///
+    /// let labels: Value = yyaml::from_str("{team: core, tier: 1}").unwrap();
+    /// let library = AnchorLibrary::new().with("common_labels", labels);
+    /// let options = LoaderOptions::new().anchor_library(library);
+    /// let docs = YamlLoader::load_with_options("labels: *common_labels", &options).unwrap();
+    /// assert_eq!(docs[0]["labels"]["team"].as_str(), Some("core"));
+    ///
+    /// let err = YamlLoader::load_with_options("x: *missing", &options).unwrap_err();
+    /// assert!(err.to_string().contains("unknown anchor 'missing'"));
+    /// ```
+    #[must_use]
+    pub fn anchor_library(mut self, library: AnchorLibrary) -> Self {
+        self.anchor_library = Some(Arc::new(library));
+        self
+    }
+
+    /// Apply every rule of `profile`, replacing the ones set so far.
+    #[must_use]
+    pub const fn profile(mut self, profile: Profile) -> Self {
+        self.strict_flow = profile.strict_flow;
+        self.max_scalar_len = profile.max_scalar_len;
+        self.max_key_len = profile.max_key_len;
+        self.reject_duplicate_keys = profile.reject_duplicate_keys;
+        self.forbid_tabs = profile.forbid_tabs;
+        self.strict_tags = profile.strict_tags;
+        self.indentation = profile.indentation;
+        self.max_depth = profile.max_depth;
+        self
+    }
+
+    /// Whether only default rules apply and nothing observes nodes or the
+    /// load's progress, so the fast path gives the same result as the full
+    /// parser
+    pub(crate) fn allows_fast_path(&self) -> bool {
+        self.empty_scalar == EmptyScalar::Null
+            && !self.strict_flow
+            && self.schema == SchemaMode::Core
+            && self.integer_overflow == IntegerOverflow::PromoteToFloat
+            && self.null_words.is_none()
+            && self.max_scalar_len.is_none()
+            && self.max_key_len.is_none()
+            && !self.reject_duplicate_keys
+            && !self.forbid_tabs
+            && !self.strict_tags
+            && self.indentation == IndentationRules::permissive()
+            && self.max_depth.is_none()
+            && self.max_documents != Some(0)
+            && self.node_middleware.is_none()
+            && self.anchor_library.is_none()
+            && self.progress.is_none()
+            && self.cancellation.is_none()
+    }
+
+    #[inline]
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.cancellation
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+    }
+
+    #[inline]
+    pub(crate) fn report(&self, bytes_processed: usize, docs_emitted: usize) {
+        if let Some(progress) = &self.progress {
+            progress(bytes_processed, docs_emitted);
+        }
+    }
+}
+
+impl fmt::Debug for LoaderOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LoaderOptions")
+            .field("progress", &self.progress.is_some())
+            .field("cancellation", &self.cancellation)
+            .field("empty_scalar", &self.empty_scalar)
+            .field("strict_flow", &self.strict_flow)
+            .field("schema", &self.schema)
+            .field("integer_overflow", &self.integer_overflow)
+            .field("null_words", &self.null_words)
+            .field("verify_fast_path", &self.verify_fast_path.is_some())
+            .field("max_scalar_len", &self.max_scalar_len)
+            .field("max_key_len", &self.max_key_len)
+            .field("reject_duplicate_keys", &self.reject_duplicate_keys)
+            .field("forbid_tabs", &self.forbid_tabs)
+            .field("strict_tags", &self.strict_tags)
+            .field("indentation", &self.indentation)
+            .field("max_depth", &self.max_depth)
+            .field("max_documents", &self.max_documents)
+            .field("truncate_documents", &self.truncate_documents)
+            .field("node_middleware", &self.node_middleware.is_some())
+            .field("anchor_library", &self.anchor_library)
+            .finish()
+    }
+}
+
+/// A named set of loading rules for a kind of input.
+///
+/// Apply one with [`LoaderOptions::profile`],
+/// [`YamlLoader::with_profile`](crate::YamlLoader::with_profile) or
+/// [`DeserializerConfig::profile`](crate::DeserializerConfig::profile).
+///
+/// ```rust
+/// use yyaml::{Profile, YamlLoader};
+///
+/// let options = YamlLoader::with_profile(Profile::strict());
+/// assert!(YamlLoader::load_with_options("a: 1\na: 2\n", &options).is_err());
+///
+/// let options = YamlLoader::with_profile(Profile::permissive());
+/// let docs = YamlLoader::load_with_options("a: 1\na: 2\n", &options).unwrap();
+/// assert_eq!(docs[0]["a"].as_i64(), Some(2));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Profile {
+    name: &'static str,
+    reject_duplicate_keys: bool,
+    forbid_tabs: bool,
+    strict_tags: bool,
+    indentation: IndentationRules,
+    max_depth: Option<usize>,
+    strict_flow: bool,
+    max_scalar_len: Option<usize>,
+    max_key_len: Option<usize>,
+}
+
+impl Profile {
+    /// Duplicate keys, separating tabs, core schema tags that do not fit
+    /// their scalar and every break of the [`IndentationRules`] are
+    /// errors, and collections nest at most 64 levels deep
+    #[must_use]
+    pub const fn strict() -> Self {
+        Self {
+            name: "strict",
+            reject_duplicate_keys: true,
+            forbid_tabs: true,
+            strict_tags: true,
+            indentation: IndentationRules::strict(),
+            max_depth: Some(64),
+            strict_flow: false,
+            max_scalar_len: None,
+            max_key_len: None,
+        }
+    }
+
+    /// [`strict`](Self::strict) for Kubernetes manifests: scalars up to the
+    /// 1 MiB object size limit, and keys up to 317 bytes, the longest valid
+    /// label or annotation key
+    #[must_use]
+    pub const fn strict_k8s() -> Self {
+        Self {
+            name: "strict-k8s",
+            max_scalar_len: Some(1 << 20),
+            max_key_len: Some(317),
+            ..Self::strict()
+        }
+    }
+
+    /// [`strict`](Self::strict) for pipeline definitions checked in CI:
+    /// empty flow entries and trailing commas are errors too, and scalars
+    /// are capped at 1 MiB
+    #[must_use]
+    pub const fn strict_ci() -> Self {
+        Self {
+            name: "strict-ci",
+            strict_flow: true,
+            max_scalar_len: Some(1 << 20),
+            ..Self::strict()
+        }
+    }
+
+    /// The behavior of yaml-rust and of [`LoaderOptions::default`]: the
+    /// last of several equal keys wins, tabs may separate tokens, a core
+    /// tag that does not fit its scalar loads as [`Yaml::Invalid`] instead
+    /// of failing, no [`IndentationRules`] apply, and nesting is only held
+    /// to the parser's built-in recursion limit of about 100 levels
+    #[must_use]
+    pub const fn permissive() -> Self {
+        Self {
+            name: "permissive",
+            reject_duplicate_keys: false,
+            forbid_tabs: false,
+            strict_tags: false,
+            indentation: IndentationRules::permissive(),
+            max_depth: None,
+            strict_flow: false,
+            max_scalar_len: None,
+            max_key_len: None,
+        }
+    }
+
+    /// The profile called `name`: `strict`, `strict-k8s`, `strict-ci` or
+    /// `permissive`
+    #[must_use]
+    pub fn by_name(name: &str) -> Option<Self> {
+        [Self::strict(), Self::strict_k8s(), Self::strict_ci(), Self::permissive()]
+            .into_iter()
+            .find(|profile| profile.name == name)
+    }
+
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self::permissive()
+    }
+}
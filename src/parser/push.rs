@@ -0,0 +1,195 @@
+//! Sans-io push parser for YAML streams arriving in arbitrary chunks
+//!
+//! The caller owns the IO and hands bytes to [`PushParser::feed`] as they
+//! arrive. Documents are split at `---` / `...` markers in column zero, which
+//! YAML 1.2 forbids inside any scalar, so each completed document can be
+//! parsed independently while later ones are still in flight.
+
+use crate::error::{Marker, ScanError};
+use crate::events::{AnchorTable, Event, EventReceiver};
+use crate::parser::loader::{YamlLoader, emit_node};
+use crate::yaml::Yaml;
+use crate::Error;
+
+/// Incremental YAML parser driven by the caller's IO.
+#[derive(Debug, Default)]
+pub struct PushParser {
+    buf: Vec<u8>,
+    /// Byte offset of the first line not yet classified.
+    scan_pos: usize,
+    /// Whether the pending segment holds anything besides comments and directives.
+    has_content: bool,
+    chars_consumed: usize,
+    bytes_consumed: usize,
+    lines_consumed: usize,
+    stream_started: bool,
+    finished: bool,
+}
+
+impl PushParser {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bytes received but not yet part of a completed document.
+    #[must_use]
+    pub fn buffered(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Append `chunk` and return every document it completed.
+    pub fn feed(&mut self, chunk: &[u8]) -> Result<Vec<Yaml>, Error> {
+        if self.finished {
+            return Err(Error::Custom("push parser already finished".to_string()));
+        }
+        self.buf.extend_from_slice(chunk);
+        let mut docs = Vec::new();
+        while let Some(segment) = self.next_segment() {
+            self.parse_segment(segment, &mut docs)?;
+        }
+        Ok(docs)
+    }
+
+    /// Signal end of input and return the trailing document, if any.
+    pub fn finish(&mut self) -> Result<Vec<Yaml>, Error> {
+        if self.finished {
+            return Ok(Vec::new());
+        }
+        let mut docs = self.feed(&[])?;
+        self.finished = true;
+        let rest = std::mem::take(&mut self.buf);
+        let has_content = self.has_content
+            || rest[self.scan_pos..]
+                .split(|&b| b == b'\n')
+                .any(line_has_content);
+        if has_content {
+            self.parse_segment(rest, &mut docs)?;
+        }
+        self.scan_pos = 0;
+        self.has_content = false;
+        Ok(docs)
+    }
+
+    /// Like [`feed`](Self::feed), but replay completed documents as events.
+    ///
+    /// Returns the number of documents delivered to `receiver`.
+    pub fn feed_events<R: EventReceiver>(
+        &mut self,
+        chunk: &[u8],
+        receiver: &mut R,
+    ) -> Result<usize, Error> {
+        let docs = self.feed(chunk)?;
+        Ok(self.replay(&docs, receiver))
+    }
+
+    /// Like [`finish`](Self::finish), but replay as events and close the stream.
+    pub fn finish_events<R: EventReceiver>(&mut self, receiver: &mut R) -> Result<usize, Error> {
+        let docs = self.finish()?;
+        let count = self.replay(&docs, receiver);
+        if !self.stream_started {
+            self.stream_started = true;
+            receiver.on_event(Event::StreamStart);
+        }
+        receiver.on_event(Event::StreamEnd);
+        Ok(count)
+    }
+
+    fn replay<R: EventReceiver>(&mut self, docs: &[Yaml], receiver: &mut R) -> usize {
+        for doc in docs {
+            if !self.stream_started {
+                self.stream_started = true;
+                receiver.on_event(Event::StreamStart);
+            }
+            receiver.on_event(Event::DocumentStart);
+            emit_node(doc, None, 0, &AnchorTable::default(), receiver);
+            receiver.on_event(Event::DocumentEnd);
+        }
+        docs.len()
+    }
+
+    /// Split off the next complete document, scanning only whole lines.
+    fn next_segment(&mut self) -> Option<Vec<u8>> {
+        while let Some(len) = self.buf[self.scan_pos..].iter().position(|&b| b == b'\n') {
+            let line_start = self.scan_pos;
+            let line_end = line_start + len + 1;
+            let line = &self.buf[line_start..line_end - 1];
+
+            if is_marker(line, b"---") {
+                if self.has_content {
+                    // The new document starts here; everything before it is done
+                    let segment: Vec<u8> = self.buf.drain(..line_start).collect();
+                    self.scan_pos = 0;
+                    self.has_content = false;
+                    return Some(segment);
+                }
+                self.has_content = true;
+            } else if is_marker(line, b"...") {
+                let segment: Vec<u8> = self.buf.drain(..line_end).collect();
+                self.scan_pos = 0;
+                let complete = std::mem::take(&mut self.has_content);
+                if complete {
+                    return Some(segment);
+                }
+                self.advance(&segment);
+                continue;
+            } else if line_has_content(line) {
+                self.has_content = true;
+            }
+            self.scan_pos = line_end;
+        }
+        None
+    }
+
+    fn parse_segment(&mut self, segment: Vec<u8>, docs: &mut Vec<Yaml>) -> Result<(), Error> {
+        let text = match String::from_utf8(segment) {
+            Ok(text) => text,
+            Err(e) => {
+                let valid = &e.as_bytes()[..e.utf8_error().valid_up_to()];
+                let mark = self.offset_mark(valid);
+                return Err(ScanError::new(mark, "invalid UTF-8 in input").into());
+            }
+        };
+        let parsed = YamlLoader::load_from_str(&text).map_err(|mut e| {
+            e.mark.index += self.chars_consumed;
+            e.mark.byte_offset += self.bytes_consumed;
+            e.mark.line += self.lines_consumed;
+            e
+        })?;
+        self.advance(text.as_bytes());
+        docs.extend(parsed);
+        Ok(())
+    }
+
+    fn advance(&mut self, consumed: &[u8]) {
+        self.lines_consumed += consumed.iter().filter(|&&b| b == b'\n').count();
+        self.chars_consumed += String::from_utf8_lossy(consumed).chars().count();
+        self.bytes_consumed += consumed.len();
+    }
+
+    fn offset_mark(&self, valid: &[u8]) -> Marker {
+        let text = String::from_utf8_lossy(valid);
+        let col = text.rsplit('\n').next().map_or(0, |line| line.chars().count());
+        Marker {
+            index: self.chars_consumed + text.chars().count(),
+            line: self.lines_consumed + text.matches('\n').count() + 1,
+            col,
+            byte_offset: self.bytes_consumed + valid.len(),
+            len: None,
+        }
+    }
+}
+
+fn is_marker(line: &[u8], marker: &[u8]) -> bool {
+    line.strip_prefix(marker)
+        .is_some_and(|rest| matches!(rest.first(), None | Some(b' ' | b'\t' | b'\r')))
+}
+
+fn line_has_content(line: &[u8]) -> bool {
+    match line.iter().find(|b| !b.is_ascii_whitespace()) {
+        None | Some(b'#') => false,
+        // Directives belong to the document that follows them
+        Some(b'%') => line.first() != Some(&b'%'),
+        Some(_) => true,
+    }
+}
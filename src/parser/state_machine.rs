@@ -1,10 +1,30 @@
-use crate::error::ScanError;
-use crate::events::{TScalarStyle, TokenType};
+use crate::error::{Marker, ScanError};
+use crate::events::{
+    AnchorTable, Event, EventReceiver, TScalarStyle, TaggedEventReceiver, TokenType,
+};
 use crate::linked_hash_map::LinkedHashMap;
-use crate::parser::grammar::{Context as YamlContext, ParametricContext};
-use crate::scanner::Scanner;
-use crate::yaml::Yaml;
-use std::collections::HashMap;
+use crate::parser::grammar::{YamlContext, ParametricContext};
+use crate::parser::indentation::IndentationRules;
+use crate::parser::loader::emit_node;
+use crate::parser::options::{
+    AnchorLibrary, EmptyScalar, IntegerOverflow, NodeInfo, NodeMiddleware, SchemaMode,
+};
+use crate::scanner::state::StrChars;
+use crate::scanner::{Lexeme, Scanner, Token};
+use crate::source_map::SpanLog;
+use crate::tags;
+use crate::yaml::{InvalidNode, Yaml, core_tag_mismatch, overflowing_integer};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
+use std::sync::Arc;
+
+/// Upper bound on nodes copied in by alias expansion within one document
+pub(crate) const MAX_ALIAS_EXPANSION: usize = 1_000_000;
+
+/// Upper bound on nodes copied from one anchor whose content itself holds
+/// aliases, weighted by how deep that alias-of-alias chain goes
+const MAX_WEIGHTED_ANCHOR_EXPANSION: usize = 250_000;
 
 /// YAML parsing state machine states
 #[derive(Clone, Copy, PartialEq, Debug, Eq)]
@@ -27,6 +47,8 @@ pub enum State {
     FlowMappingFirstKey,
     FlowMappingKey,
     FlowMappingValue,
+    FlowSequenceEntryMappingKey,
+    FlowSequenceEntryMappingEnd,
     End,
 }
 
@@ -39,43 +61,444 @@ pub struct StateMachine<T: Iterator<Item = char>> {
     pub anchor_id: usize,
     pub indents: Vec<usize>, // Keep for compatibility
     ast_stack: Vec<YamlBuilder>,
-    pending_tag: Option<(String, String)>,
-    tag_stack: Vec<Option<(String, String)>>, // Stack for nested tag scopes
+    /// Properties read but not yet attached to a node
+    pending: NodeProperties,
+    /// Properties of each open builder, parallel to `ast_stack`
+    builder_props: Vec<NodeProperties>,
+    /// Completed anchored nodes by anchor id
+    anchored: HashMap<usize, AnchoredNode>,
+    alias_expansion: usize,
+    /// Alias depth and anchor id of every alias resolved in this document
+    alias_log: Vec<(usize, usize)>,
 
     // ADD:
     pub context: ParametricContext,
-    yaml_version: Option<(u32, u32)>,
-    tag_handles: HashMap<String, String>,
+    /// Directives of the document being parsed
+    directives: DocumentScope,
+    empty_scalar: EmptyScalar,
+    /// Reject empty flow entries and trailing commas
+    strict_flow: bool,
+    flow_entry: FlowEntry,
+    schema: SchemaMode,
+    integer_overflow: IntegerOverflow,
+    null_words: Option<&'static [&'static str]>,
+    /// `(ast_stack depth, column)` of open block collections
+    block_columns: Vec<(usize, usize)>,
+    /// Whether the current document opened with `---`, so it is a null
+    /// document even without content
+    explicit_document: bool,
+    /// Names of every anchor in the stream by id, when aliases are kept
+    /// rather than expanded
+    kept_anchors: Option<Vec<String>>,
+    /// Fail on a key that repeats an earlier key of the same mapping
+    reject_duplicate_keys: bool,
+    /// Where the node completed last began, kept with it if it is a key
+    node_start: Marker,
+    /// Fail on a scalar whose core schema tag does not fit its text
+    strict_tags: bool,
+    /// Deepest collection nesting allowed
+    max_depth: Option<usize>,
+    /// A profile rule broken while building nodes, raised after the current
+    /// state finishes
+    violation: Option<ScanError>,
+    /// Rewrites each node as it completes
+    middleware: Option<Arc<NodeMiddleware>>,
+    /// Nodes for aliases with no anchor of their name in the document
+    library: Option<Arc<AnchorLibrary>>,
+    /// Where each node of the document was written, when kept
+    spans: Option<SpanLog>,
+    /// Where the node about to complete began, and the anchor id if it is
+    /// an alias
+    span_start: Option<(Marker, Option<usize>)>,
+    /// Events of the nodes read so far, with where they occur, when kept
+    events: Option<Vec<(Event, Marker)>>,
+    /// Index of the start event of the collection completed last
+    closed_event: Option<usize>,
+    /// Whether `StreamEnd` was logged
+    stream_ended: bool,
+    /// Pass each node on as events instead of keeping it in its parent;
+    /// sequences then keep no items
+    streamed: bool,
+    /// Events passed on from the front of `events`; logged indices count
+    /// them, so they stay valid
+    events_drained: usize,
+    /// Resolved tag of each collection by the index of its start event,
+    /// when streaming
+    collection_tags: Vec<(usize, String)>,
 }
 
 /// Builder for constructing Yaml AST during parsing
 #[derive(Debug)]
 enum YamlBuilder {
     Sequence(Vec<Yaml>),
-    Mapping(LinkedHashMap<Yaml, Yaml>, Option<Yaml>), // map, current_key
-    Scalar(String),
+    Mapping(LinkedHashMap<Yaml, Yaml>, Option<(Yaml, Marker)>), // map, current key and its start
+    /// A finished root node
+    Node(Yaml),
+}
+
+/// Receiver logging replayed events at one mark
+struct LogAt<'a>(&'a mut Vec<(Event, Marker)>, Marker);
+
+impl EventReceiver for LogAt<'_> {
+    fn on_event(&mut self, ev: Event) {
+        self.0.push((ev, self.1));
+    }
+}
+
+/// What the innermost flow collection consumed last, which tells `[]`,
+/// `[a,]` and `[a,,b]` apart
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum FlowEntry {
+    Open,
+    Comma,
+    Node,
+}
+
+/// Anchor and tag written before a node, attached once the node is complete
+#[derive(Debug, Default)]
+struct NodeProperties {
+    anchor: Option<String>,
+    tag: Option<(String, String)>,
+    /// Position of the first property; `&a key: v` anchors the key, while
+    /// properties on an earlier line belong to the mapping
+    mark: Marker,
+    /// Where the collection these belong to began
+    start: Marker,
+    /// Length of the alias log when a collection opened, so its anchor can
+    /// see which aliases it contains
+    aliases_from: Option<usize>,
+    /// Index of the collection's start event in the event log
+    start_event: Option<usize>,
+    /// Whether the collection is an entry of a flow sequence, where a
+    /// following `:` makes it a key and moves its events
+    may_become_key: bool,
+    /// Path segment of a mapping's pending key as written, before the node
+    /// middleware rewrote it
+    key_segment: Option<String>,
+}
+
+/// A completed anchored node and what expanding it has cost so far
+struct AnchoredNode {
+    name: String,
+    yaml: Yaml,
+    count: usize,
+    /// 0 for content without aliases, else one more than the deepest alias
+    /// it contains
+    depth: usize,
+    /// Anchor id of that deepest alias
+    via: Option<usize>,
+    /// Nodes copied in by aliases to this anchor
+    expanded: usize,
+}
+
+impl NodeProperties {
+    const fn is_empty(&self) -> bool {
+        self.anchor.is_none() && self.tag.is_none()
+    }
+}
+
+impl<'input> StateMachine<StrChars<'input>> {
+    /// A state machine reading `text`, whose scanner slices scalars from it
+    /// rather than building them a character at a time
+    pub(crate) fn over_str(text: impl Into<StrChars<'input>>) -> Self {
+        Self::with_scanner(Scanner::over_str(text))
+    }
 }
 
 impl<T: Iterator<Item = char>> StateMachine<T> {
     pub fn new(src: T) -> Self {
+        Self::with_scanner(Scanner::new(src))
+    }
+
+    fn with_scanner(scanner: Scanner<T>) -> Self {
         Self {
-            scanner: Scanner::new(src),
+            scanner,
             states: Vec::new(),
             state: State::StreamStart,
             anchors: HashMap::new(),
             anchor_id: 1,
             indents: Vec::new(),
             ast_stack: Vec::new(),
-            pending_tag: None,
-            tag_stack: Vec::new(),
+            pending: NodeProperties::default(),
+            builder_props: Vec::new(),
+            anchored: HashMap::new(),
+            alias_expansion: 0,
+            alias_log: Vec::new(),
 
             // ADD:
             context: ParametricContext::new(),
-            yaml_version: None,
-            tag_handles: HashMap::new(),
+            directives: DocumentScope::default(),
+            empty_scalar: EmptyScalar::default(),
+            strict_flow: false,
+            flow_entry: FlowEntry::Open,
+            schema: SchemaMode::Core,
+            integer_overflow: IntegerOverflow::PromoteToFloat,
+            null_words: None,
+            block_columns: Vec::new(),
+            explicit_document: false,
+            kept_anchors: None,
+            reject_duplicate_keys: false,
+            node_start: Marker::default(),
+            strict_tags: false,
+            max_depth: None,
+            violation: None,
+            middleware: None,
+            library: None,
+            spans: None,
+            span_start: None,
+            events: None,
+            closed_event: None,
+            stream_ended: false,
+            streamed: false,
+            events_drained: 0,
+            collection_tags: Vec::new(),
+        }
+    }
+
+    /// Leave aliases as [`Yaml::Alias`] and mark anchored nodes instead of
+    /// expanding them, numbering anchors across the whole stream
+    pub(crate) fn with_kept_anchors(mut self) -> Self {
+        self.kept_anchors = Some(Vec::new());
+        self
+    }
+
+    /// Anchor names by id, if built [`with_kept_anchors`](Self::with_kept_anchors)
+    pub(crate) fn take_anchor_names(&mut self) -> Vec<String> {
+        self.kept_anchors.take().unwrap_or_default()
+    }
+
+    /// Log where each node was written, for [`take_spans`](Self::take_spans)
+    pub(crate) fn with_span_log(mut self) -> Self {
+        self.spans = Some(SpanLog::default());
+        self
+    }
+
+    /// Spans of the nodes of the document parsed last, by path, if built
+    /// [`with_span_log`](Self::with_span_log)
+    pub(crate) fn take_spans(&mut self) -> BTreeMap<String, Vec<Range<usize>>> {
+        self.spans.as_mut().map(SpanLog::take).unwrap_or_default()
+    }
+
+    /// Log the events of each node as it is read, for
+    /// [`take_events`](Self::take_events)
+    pub(crate) fn with_event_log(mut self) -> Self {
+        self.events = Some(Vec::new());
+        self
+    }
+
+    /// Events logged since the last call, with where they occur, if built
+    /// [`with_event_log`](Self::with_event_log); taken between documents
+    pub(crate) fn take_events(&mut self) -> Vec<(Event, Marker)> {
+        self.events.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    /// Pass each completed node on as events through
+    /// [`stream_next_document`](Self::stream_next_document) instead of
+    /// keeping it in its parent, so no document tree is built. Aliases are
+    /// kept for the receiver to resolve.
+    pub(crate) fn with_streamed_nodes(self) -> Self {
+        let mut machine = self.with_event_log().with_kept_anchors();
+        machine.streamed = true;
+        machine
+    }
+
+    /// Log `event` at `mark`, if events are kept
+    fn log_event(&mut self, event: Event, mark: Marker) {
+        if let Some(events) = &mut self.events {
+            events.push((event, mark));
         }
     }
 
+    /// Resolve empty nodes such as the value in `key:` to `empty`
+    pub(crate) const fn with_empty_scalar(mut self, empty: EmptyScalar) -> Self {
+        self.empty_scalar = empty;
+        self
+    }
+
+    /// Reject empty flow entries, empty flow nodes and trailing commas, as JSON does
+    pub(crate) const fn with_strict_flow(mut self, strict: bool) -> Self {
+        self.strict_flow = strict;
+        self
+    }
+
+    /// Fail on duplicate mapping keys instead of keeping the last value
+    pub(crate) const fn with_duplicate_keys_rejected(mut self, reject: bool) -> Self {
+        self.reject_duplicate_keys = reject;
+        self
+    }
+
+    /// Fail on a scalar whose core schema tag does not fit its text
+    pub(crate) const fn with_strict_tags(mut self, strict: bool) -> Self {
+        self.strict_tags = strict;
+        self
+    }
+
+    /// Fail once collections nest deeper than `depth`
+    pub(crate) const fn with_max_depth(mut self, depth: Option<usize>) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Fail on tabs used as separation white space
+    pub(crate) fn with_separating_tabs(mut self, allow: bool) -> Self {
+        self.scanner.config_mut().allow_separating_tabs = allow;
+        self
+    }
+
+    /// Enforce the spec's indentation rules chosen in `rules`
+    pub(crate) const fn with_indentation_rules(mut self, rules: IndentationRules) -> Self {
+        self.scanner.config_mut().indentation = rules;
+        self
+    }
+
+    /// Pass every finished node through `middleware`
+    pub(crate) fn with_node_middleware(mut self, middleware: Option<Arc<NodeMiddleware>>) -> Self {
+        self.middleware = middleware;
+        self
+    }
+
+    /// Resolve aliases to `library` when the document has no such anchor
+    pub(crate) fn with_anchor_library(mut self, library: Option<Arc<AnchorLibrary>>) -> Self {
+        self.library = library;
+        self
+    }
+
+    /// Resolve untagged plain scalars with `schema`
+    pub(crate) const fn with_schema(mut self, schema: SchemaMode) -> Self {
+        self.schema = schema;
+        self
+    }
+
+    /// Cap scalar and mapping key lengths in bytes
+    pub(crate) fn with_length_limits(mut self, scalar: Option<usize>, key: Option<usize>) -> Self {
+        let config = self.scanner.config_mut();
+        config.max_scalar_len = scalar.unwrap_or(usize::MAX);
+        config.max_key_len = key.unwrap_or(usize::MAX);
+        self
+    }
+
+    /// Node produced for a value with no content
+    fn empty_node(&self) -> Yaml {
+        match self.empty_scalar {
+            EmptyScalar::Null => Yaml::Null,
+            EmptyScalar::EmptyString => Yaml::String(String::new()),
+        }
+    }
+
+    /// Empty node inside a flow collection, an error in strict mode
+    fn flow_empty_node(&self, mark: Marker, what: &str) -> Result<Yaml, ScanError> {
+        if self.strict_flow {
+            return Err(ScanError::new(mark, &format!("{what} is not allowed in strict mode")));
+        }
+        Ok(self.empty_node())
+    }
+
+    /// Resolve plain integers too large for an `i64` with `policy`
+    pub(crate) const fn with_integer_overflow(mut self, policy: IntegerOverflow) -> Self {
+        self.integer_overflow = policy;
+        self
+    }
+
+    /// Resolve exactly `words` as null among plain scalars, if set
+    pub(crate) const fn with_null_words(
+        mut self,
+        words: Option<&'static [&'static str]>,
+    ) -> Self {
+        self.null_words = words;
+        self
+    }
+
+    /// Resolve a scalar token; only plain scalars are typed, and a core tag
+    /// that does not fit the text gives a [`Yaml::Invalid`] node saying why,
+    /// or an error in strict mode
+    fn resolve_scalar(&mut self, style: TScalarStyle, token: &Lexeme) -> Result<Yaml, ScanError> {
+        let mark = token.0;
+        let value = self.scanner.scalar_text(token);
+        self.span_start = Some((mark, None));
+        let tag = self
+            .pending
+            .tag
+            .as_ref()
+            .map(|(handle, suffix)| self.directives.resolve_tag(handle, suffix.clone()));
+        if let Some(kind) = tag.as_deref().and_then(|tag| core_tag_mismatch(tag, value)) {
+            if self.strict_tags {
+                return Err(ScanError::new(mark, &kind.to_string()));
+            }
+            return Ok(Yaml::Invalid(Box::new(InvalidNode { kind, mark: Some(mark) })));
+        }
+        // A core scalar tag types the text whatever its style, so `!!str 1`
+        // holds a string and `!!int "1"` an integer
+        let style = match tag.as_deref() {
+            Some(tags::STR) => return Ok(Yaml::String(value.to_string())),
+            Some(tags::FLOAT) if matches!(Yaml::parse_str(value), Yaml::Integer(_)) => {
+                return Ok(Yaml::Real(value.to_string()));
+            }
+            Some(tags::INT | tags::FLOAT | tags::BOOL) => TScalarStyle::Plain,
+            Some(tags::NULL) => return Ok(Yaml::Null),
+            _ => style,
+        };
+        if style == TScalarStyle::Plain
+            && let Some(approx) = overflowing_integer(value, self.schema)
+        {
+            return match self.integer_overflow {
+                IntegerOverflow::PromoteToFloat => Ok(Yaml::promoted_integer(value, approx)),
+                IntegerOverflow::Saturate if approx < 0.0 => Ok(Yaml::Integer(i64::MIN)),
+                IntegerOverflow::Saturate => Ok(Yaml::Integer(i64::MAX)),
+                IntegerOverflow::KeepString => Ok(Yaml::String(value.to_string())),
+                IntegerOverflow::Error => Err(ScanError::new(
+                    mark,
+                    &format!("integer {value} does not fit in 64 bits"),
+                )),
+            };
+        }
+        let node = match (style, self.schema) {
+            (TScalarStyle::Plain, SchemaMode::Core) => Yaml::parse_str(value),
+            (TScalarStyle::Plain, SchemaMode::Json) => Yaml::parse_json_str(value),
+            _ => Yaml::String(value.to_string()),
+        };
+        Ok(match self.null_words {
+            Some(words) if style == TScalarStyle::Plain && words.contains(&value) => Yaml::Null,
+            Some(_) if node.is_null() => Yaml::String(value.to_string()),
+            _ => node,
+        })
+    }
+
+    /// Record the column of the block collection just pushed onto `ast_stack`
+    fn set_block_column(&mut self, col: usize) {
+        let depth = self.ast_stack.len().saturating_sub(1);
+        self.block_columns.retain(|&(d, _)| d < depth);
+        self.block_columns.push((depth, col));
+        self.scanner.set_indent_floor(Some(col));
+    }
+
+    /// Column of the innermost block collection, if it is the top builder
+    fn block_column(&self) -> Option<usize> {
+        let depth = self.ast_stack.len().checked_sub(1)?;
+        self.block_columns
+            .iter()
+            .rev()
+            .find(|&&(d, _)| d == depth)
+            .map(|&(_, col)| col)
+    }
+
+    /// Whether `token` at `mark`, read after a node property, belongs to the
+    /// enclosing block collection instead of being the property's node: it
+    /// lies left of the collection, or starts the next sequence entry or
+    /// mapping key at its column
+    fn closes_property_node(&self, mark: Marker, token: &TokenType) -> bool {
+        let Some(col) = self.block_column() else {
+            return false;
+        };
+        let in_sequence = matches!(self.ast_stack.last(), Some(YamlBuilder::Sequence(_)));
+        mark.col < col
+            || (mark.col == col && (in_sequence || !matches!(token, TokenType::BlockEntry)))
+    }
+
+    /// Whether a token at `mark` lies left of the innermost block collection
+    fn is_dedent(&self, mark: Marker) -> bool {
+        self.block_column().is_some_and(|col| mark.col < col)
+    }
+
     pub fn push_indent(&mut self, indent: usize) {
         self.indents.push(indent);
     }
@@ -89,29 +512,9 @@ impl<T: Iterator<Item = char>> StateMachine<T> {
             // Decrement depth when unwinding
             self.context.decrement_depth();
 
-            // Handle special case: returning from BlockNode to BlockMappingKey means
-            // we parsed nested content that should be added as a mapping value
-            if self.state == State::BlockNode && state == State::BlockMappingKey {
-                // Restore the saved tag for the outer mapping
-                if let Some(saved_tag) = self.tag_stack.pop() {
-                    self.pending_tag = saved_tag;
-                }
-
-                // Take the completed AST structure and add it as mapping value
-                if let Some(builder) = self.ast_stack.pop() {
-                    let yaml = self.finalize_builder(builder);
-                    self.add_mapping_pair(yaml);
-                }
-            }
-            
             // Check if we're leaving a context scope
-            match (self.state, state) {
-                (State::FlowSequenceEntry, State::BlockNode)
-                | (State::FlowMappingValue, State::BlockNode)
-                | (State::BlockMappingValue, State::BlockMappingKey) => {
-                    self.context.pop_context();
-                }
-                _ => {}
+            if (self.state, state) == (State::BlockMappingValue, State::BlockMappingKey) {
+                self.context.pop_context();
             }
             self.state = state;
         }
@@ -125,10 +528,337 @@ impl<T: Iterator<Item = char>> StateMachine<T> {
     pub fn register_anchor(&mut self, name: String) -> usize {
         let new_id = self.anchor_id;
         self.anchor_id += 1;
+        if let Some(names) = &mut self.kept_anchors {
+            names.push(name.clone());
+        }
         self.anchors.insert(name, new_id);
         new_id
     }
 
+    /// Record a node property token for the next node. A named tag handle
+    /// such as `!e!` must be declared by a `%TAG` directive of the current
+    /// document (YAML 1.2 §6.8.2).
+    fn set_property(&mut self, mark: Marker, token: &TokenType) -> Result<(), ScanError> {
+        if self.pending.is_empty() {
+            self.pending.mark = mark;
+        }
+        match token {
+            TokenType::Anchor(name) => self.pending.anchor = Some(name.clone()),
+            TokenType::Tag(handle, _)
+                if handle.len() > 2 && !self.directives.tag_handles.contains_key(handle) =>
+            {
+                return Err(ScanError::new(mark, &format!("undefined tag handle {handle}")));
+            }
+            TokenType::Tag(handle, suffix) => {
+                self.pending.tag = Some((handle.clone(), suffix.clone()));
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Attach `props` to a completed node and pass it through the node
+    /// middleware, registering its anchor with the rewritten node so aliases
+    /// copy what the middleware left
+    fn apply_properties(&mut self, mut yaml: Yaml, props: NodeProperties) -> Yaml {
+        let start = self.span_start.take();
+        // An invalid node already names the tag it failed
+        if let Some((handle, suffix)) = props.tag
+            && !matches!(yaml, Yaml::Invalid(_))
+        {
+            yaml = Yaml::Tagged(self.directives.resolve_tag(&handle, suffix), Box::new(yaml));
+        }
+        yaml = self.rewrite_node(yaml, start);
+        let mut anchor = None;
+        if let Some(name) = props.anchor {
+            let (depth, via) = props
+                .aliases_from
+                .and_then(|start| self.alias_log.get(start..))
+                .and_then(|aliases| aliases.iter().max_by_key(|(depth, _)| *depth))
+                .map_or((0, None), |&(depth, id)| (depth + 1, Some(id)));
+            let id = self.register_anchor(name.clone());
+            if let Some(spans) = &mut self.spans {
+                spans.anchor(id);
+            }
+            let count = node_count(&yaml);
+            anchor = Some((id, name.clone()));
+            self.anchored.insert(
+                id,
+                AnchoredNode {
+                    name,
+                    yaml: yaml.clone(),
+                    count,
+                    depth,
+                    via,
+                    expanded: 0,
+                },
+            );
+        }
+        if self.events.is_some() {
+            self.log_node(&yaml, props.start_event, anchor.clone(), start);
+        }
+        if self.streamed {
+            // The events carry the node; its parent only needs a placeholder
+            return Yaml::Null;
+        }
+        if let Some((id, _)) = anchor
+            && self.kept_anchors.is_some()
+        {
+            yaml = Yaml::Tagged(anchor_mark(id), Box::new(yaml));
+        }
+        yaml
+    }
+
+    /// Log the events of a completed node that began at `start`; a
+    /// collection, whose start event was logged when it opened, only gets
+    /// its `anchor` added to that event
+    fn log_node(
+        &mut self,
+        yaml: &Yaml,
+        start_event: Option<usize>,
+        anchor: Option<(usize, String)>,
+        start: Option<(Marker, Option<usize>)>,
+    ) {
+        let mark = start.map_or_else(|| self.scanner.last_token_end(), |(mark, _)| mark);
+        let Some(events) = &mut self.events else {
+            return;
+        };
+        if let Some(index) = start_event {
+            if let Some((id, name)) = anchor
+                && let Some((Event::SequenceStart(a, n) | Event::MappingStart(a, n), _)) =
+                    events.get_mut(index - self.events_drained)
+            {
+                (*a, *n) = (id, Some(name));
+            }
+            return;
+        }
+        let id = anchor.map_or(0, |(id, _)| id);
+        emit_node(yaml, None, id, &AnchorTable::default(), &mut LogAt(events, mark));
+    }
+
+    /// Attach the pending properties to a completed node
+    fn finish_node(&mut self, yaml: Yaml) -> Yaml {
+        let props = std::mem::take(&mut self.pending);
+        self.apply_properties(yaml, props)
+    }
+
+    /// Copy of the node anchored as `name`
+    fn resolve_alias(&mut self, mark: Marker, name: &str) -> Result<Yaml, ScanError> {
+        self.span_start = Some((mark, None));
+        let Some((id, node)) = self
+            .anchors
+            .get(name)
+            .and_then(|&id| Some((id, self.anchored.get_mut(&id)?)))
+        else {
+            return self.library_alias(mark, name);
+        };
+        if self.kept_anchors.is_some() {
+            return Ok(Yaml::Alias(id));
+        }
+        self.alias_expansion += node.count;
+        if self.alias_expansion > MAX_ALIAS_EXPANSION {
+            return Err(ScanError::new(mark, "repetition limit exceeded"));
+        }
+        // Nested alias chains grow geometrically, so stop them well before
+        // the document-wide budget runs out
+        node.expanded += node.count;
+        if node.depth > 0
+            && node.expanded.saturating_mul(node.depth + 1) > MAX_WEIGHTED_ANCHOR_EXPANSION
+        {
+            let info = format!(
+                "alias expansion limit exceeded for anchor chain {}",
+                self.alias_chain(id)
+            );
+            return Err(ScanError::new(mark, &info));
+        }
+        let depth = node.depth;
+        let yaml = node.yaml.clone();
+        self.alias_log.push((depth, id));
+        self.span_start = Some((mark, Some(id)));
+        Ok(yaml)
+    }
+
+    /// Copy of the node the anchor library registers as `name`
+    fn library_alias(&mut self, mark: Marker, name: &str) -> Result<Yaml, ScanError> {
+        let Some(library) = &self.library else {
+            return Err(ScanError::new(mark, "unknown anchor"));
+        };
+        let Some(yaml) = library.get(name) else {
+            let info = format!("unknown anchor '{name}' in the document or the anchor library");
+            return Err(ScanError::new(mark, &info));
+        };
+        self.alias_expansion += node_count(yaml);
+        if self.alias_expansion > MAX_ALIAS_EXPANSION {
+            return Err(ScanError::new(mark, "repetition limit exceeded"));
+        }
+        Ok(yaml.clone())
+    }
+
+    /// `e -> d -> c`: the anchor `id` and the deepest anchors it expands
+    fn alias_chain(&self, id: usize) -> String {
+        let mut names = Vec::new();
+        let mut next = Some(id);
+        while let Some(node) = next.and_then(|id| self.anchored.get(&id)) {
+            names.push(node.name.as_str());
+            next = node.via;
+        }
+        names.join(" -> ")
+    }
+
+    /// Node for a scalar or alias token
+    fn leaf_node(&mut self, token: &Lexeme) -> Result<Yaml, ScanError> {
+        self.node_start = token.0;
+        match &token.1 {
+            TokenType::Scalar(style, ..) => self.resolve_scalar(*style, token),
+            TokenType::Alias(name) => self.resolve_alias(token.0, name),
+            _ => Ok(self.empty_node()),
+        }
+    }
+
+    /// Start building a collection at `start`; pending properties belong to it
+    fn open_builder(&mut self, builder: YamlBuilder, start: Marker) {
+        if let Some(max) = self.max_depth
+            && !matches!(builder, YamlBuilder::Node(_))
+            && self.ast_stack.len() >= max
+            && self.violation.is_none()
+        {
+            self.violation = Some(ScanError::new(
+                self.scanner.mark(),
+                &format!("collections nested deeper than {max} levels"),
+            ));
+        }
+        let mut props = std::mem::take(&mut self.pending);
+        props.aliases_from = Some(self.alias_log.len());
+        props.start = start;
+        props.may_become_key = self.states.last() == Some(&State::FlowSequenceEntryMappingKey);
+        if let Some(events) = &mut self.events {
+            let event = match builder {
+                YamlBuilder::Sequence(_) => Some(Event::SequenceStart(0, None)),
+                YamlBuilder::Mapping(..) => Some(Event::MappingStart(0, None)),
+                YamlBuilder::Node(_) => None,
+            };
+            if let Some(event) = event {
+                let index = self.events_drained + events.len();
+                props.start_event = Some(index);
+                events.push((event, start));
+                if self.streamed
+                    && let Some((handle, suffix)) = &props.tag
+                {
+                    let tag = self.directives.resolve_tag(handle, suffix.clone());
+                    self.collection_tags.push((index, tag));
+                }
+            }
+        }
+        self.builder_props.push(props);
+        self.ast_stack.push(builder);
+    }
+
+    /// Finish the innermost collection with its properties attached
+    fn pop_builder(&mut self) -> Option<Yaml> {
+        let builder = self.ast_stack.pop()?;
+        // Columns of finished collections no longer bound their siblings
+        let depth = self.ast_stack.len();
+        self.block_columns.retain(|&(d, _)| d < depth);
+        let floor = self.block_columns.last().map(|&(_, col)| col);
+        self.scanner.set_indent_floor(floor);
+        let props = self.builder_props.pop().unwrap_or_default();
+        self.node_start = props.start;
+        // The root node went through the middleware when it completed
+        if let YamlBuilder::Node(yaml) = builder {
+            return Some(yaml);
+        }
+        let end = match builder {
+            YamlBuilder::Sequence(_) => Event::SequenceEnd,
+            _ => Event::MappingEnd,
+        };
+        self.log_event(end, self.scanner.last_token_end());
+        self.closed_event = props.start_event;
+        let yaml = self.finalize_builder(builder);
+        self.span_start = Some((props.start, None));
+        Some(self.apply_properties(yaml, props))
+    }
+
+    /// Finish the innermost collection and add it to its parent
+    fn close_collection(&mut self) {
+        if let Some(yaml) = self.pop_builder() {
+            self.insert_node(yaml);
+        }
+    }
+
+    /// Open a block mapping whose first key was just read at `key_mark`
+    fn open_implicit_mapping(&mut self, key_mark: Marker, key: Yaml) {
+        let mut col = key_mark.col;
+        let key_props = if !self.pending.is_empty() && self.pending.mark.line == key_mark.line {
+            // The mapping starts at the key's properties
+            col = self.pending.mark.col;
+            std::mem::take(&mut self.pending)
+        } else {
+            NodeProperties::default()
+        };
+        self.open_builder(YamlBuilder::Mapping(LinkedHashMap::new(), None), key_mark);
+        let key = self.apply_properties(key, key_props);
+        self.set_pending_key(key, key_mark);
+        self.set_block_column(col);
+    }
+
+    /// Make `key` the key of the innermost mapping, awaiting its value
+    fn set_pending_key(&mut self, key: Yaml, start: Marker) {
+        if let Some(YamlBuilder::Mapping(_, current_key)) = self.ast_stack.last_mut() {
+            *current_key = Some((key, start));
+        }
+    }
+
+    /// An implicit key and its `:` must share a line, so a plain scalar that
+    /// ran on into a `key:` line is reported rather than loaded as one key
+    fn check_implicit_key(&self, key: &Lexeme, value: Marker) -> Result<(), ScanError> {
+        if key.0.line == value.line {
+            return Ok(());
+        }
+        let message = "implicit mapping keys must be on a single line";
+        // Usually a `key:value` line, which is one plain scalar
+        let glued = match &key.1 {
+            TokenType::Scalar(TScalarStyle::Plain, ..) => self
+                .scanner
+                .scalar_text(key)
+                .split(' ')
+                .next()
+                .and_then(|word| word.split_once(':'))
+                .filter(|(k, v)| !k.is_empty() && !v.is_empty()),
+            _ => None,
+        };
+        Err(match glued {
+            Some((k, v)) => ScanError::new(
+                key.0,
+                &format!("{message}; did you mean `{k}: {v}`? A ':' needs a space after it"),
+            ),
+            None => ScanError::new(key.0, message),
+        })
+    }
+
+    /// The error for `token` where a flow collection needed a `,` or its
+    /// `close`, pointing back at where the collection was opened
+    fn flow_entry_error(&self, token: &Token, collection: &str, close: char) -> ScanError {
+        let expected = format!("did not find expected ',' or '{close}' in {collection}");
+        let Some((open, _)) = self.scanner.flow_opener() else {
+            return ScanError::new(token.0, &expected);
+        };
+        let opened = format!(
+            "the {collection} starting at line {}, column {}",
+            open.line,
+            open.col + 1
+        );
+        let hint = match token.1 {
+            TokenType::StreamEnd | TokenType::DocumentStart | TokenType::DocumentEnd => {
+                format!("{opened} was never closed")
+            }
+            TokenType::FlowSequenceEnd | TokenType::FlowMappingEnd => {
+                format!("{opened} must be closed with '{close}'")
+            }
+            _ => format!("{opened} is still open; is a ',' missing?"),
+        };
+        ScanError::new(token.0, &format!("{expected}: {hint}"))
+    }
+
     /// Execute the state machine and return the constructed Yaml AST
     pub fn parse(&mut self) -> Result<Yaml, ScanError> {
         while self.state != State::End {
@@ -136,8 +866,8 @@ impl<T: Iterator<Item = char>> StateMachine<T> {
         }
 
         // Return the final constructed AST
-        if let Some(builder) = self.ast_stack.pop() {
-            Ok(self.finalize_builder(builder))
+        if let Some(yaml) = self.pop_builder() {
+            Ok(yaml)
         } else {
             Ok(Yaml::Null)
         }
@@ -145,7 +875,7 @@ impl<T: Iterator<Item = char>> StateMachine<T> {
 
     /// Execute a single state transition
     pub fn execute_state(&mut self) -> Result<(), ScanError> {
-        println!("StateMachine: executing state {:?}", self.state);
+        log::trace!("StateMachine: executing state {:?}", self.state);
         match self.state {
             State::StreamStart => self.handle_stream_start(),
             State::DirectiveHeader => self.handle_directive_header(),
@@ -167,6 +897,8 @@ impl<T: Iterator<Item = char>> StateMachine<T> {
             State::FlowMappingFirstKey => self.handle_flow_mapping_first_key(),
             State::FlowMappingKey => self.handle_flow_mapping_key(),
             State::FlowMappingValue => self.handle_flow_mapping_value(),
+            State::FlowSequenceEntryMappingKey => self.handle_flow_sequence_entry_mapping_key(),
+            State::FlowSequenceEntryMappingEnd => self.handle_flow_sequence_entry_mapping_end(),
             _ => {
                 self.state = State::End;
                 Ok(())
@@ -175,10 +907,11 @@ impl<T: Iterator<Item = char>> StateMachine<T> {
     }
 
     fn handle_stream_start(&mut self) -> Result<(), ScanError> {
-        let token = self.scanner.peek_token()?;
+        let token = self.scanner.peek_lexeme()?;
         match &token.1 {
             TokenType::StreamStart(_) => {
-                self.scanner.fetch_token();
+                self.scanner.fetch_lexeme();
+                self.log_event(Event::StreamStart, token.0);
                 self.state = State::DirectiveHeader;
                 Ok(())
             }
@@ -199,7 +932,7 @@ impl<T: Iterator<Item = char>> StateMachine<T> {
     fn handle_block_node(&mut self) -> Result<(), ScanError> {
         // Keep parsing until we handle a non-tag token
         loop {
-            let token = self.scanner.peek_token()?;
+            let token = self.scanner.peek_lexeme()?;
 
             // ADD context validation:
             if !self.can_accept_token(&token.1) {
@@ -212,41 +945,42 @@ impl<T: Iterator<Item = char>> StateMachine<T> {
                 ));
             }
 
+            // `- !t` followed by a sibling `- b` is an empty tagged item
+            if !self.pending.is_empty() && self.closes_property_node(token.0, &token.1) {
+                let empty = self.empty_node();
+                self.push_yaml(empty);
+                self.pop_state();
+                return Ok(());
+            }
+
             match &token.1 {
-                TokenType::Scalar(style, value) => {
-                    self.scanner.fetch_token(); // Consume the scalar
+                TokenType::Scalar(style, ..) => {
+                    self.scanner.fetch_lexeme(); // Consume the scalar
 
                     let yaml = match style {
                         TScalarStyle::Literal | TScalarStyle::Folded => {
                             // Block scalars already processed by lexer - use directly
-                            Yaml::String(value.clone())
+                            self.resolve_scalar(*style, &token)?
                         }
                         _ => {
                             // Handle other scalar styles with existing logic
                             // Peek ahead to see if this is a mapping key
-                            let next_token = self.scanner.peek_token()?;
-
-                            if matches!(next_token.1, TokenType::Value) {
-                                // This is a mapping key
-                                let key = Yaml::parse_str(value);
-                                
-                                // Check if we already have a mapping in progress
-                                if let Some(YamlBuilder::Mapping(_, current_key)) = self.ast_stack.last_mut()
-                                    && current_key.is_none() {
-                                        // We have a mapping waiting for a key
-                                        *current_key = Some(key);
-                                        self.state = State::BlockMappingValue;
-                                        return Ok(());
-                                    }
-                                
-                                // No mapping in progress, create a new one
-                                self.ast_stack
-                                    .push(YamlBuilder::Mapping(LinkedHashMap::new(), Some(key)));
+                            let next_token = self.scanner.peek_lexeme()?;
+
+                            // A `:` at or left of the enclosing block belongs to it,
+                            // as after an explicit `? key`
+                            let implicit_key = matches!(next_token.1, TokenType::Value)
+                                && self.block_column().is_none_or(|col| next_token.0.col > col);
+                            if implicit_key {
+                                self.check_implicit_key(&token, next_token.0)?;
+                                // This key opens a new mapping
+                                let key = self.resolve_scalar(*style, &token)?;
+                                self.open_implicit_mapping(token.0, key);
                                 self.state = State::BlockMappingValue;
                                 return Ok(());
                             } else {
                                 // Just a scalar value
-                                Yaml::parse_str(value)
+                                self.resolve_scalar(*style, &token)?
                             }
                         }
                     };
@@ -255,38 +989,54 @@ impl<T: Iterator<Item = char>> StateMachine<T> {
                     self.pop_state();
                     return Ok(());
                 }
+                TokenType::Alias(name) => {
+                    self.scanner.fetch_lexeme();
+                    let yaml = self.resolve_alias(token.0, name)?;
+                    // `*a : v` uses the aliased node as the first key
+                    let next_token = self.scanner.peek_lexeme()?;
+                    if matches!(next_token.1, TokenType::Value)
+                        && self.block_column().is_none_or(|col| next_token.0.col > col)
+                    {
+                        self.open_implicit_mapping(token.0, yaml);
+                        self.state = State::BlockMappingValue;
+                        return Ok(());
+                    }
+                    self.push_yaml(yaml);
+                    self.pop_state();
+                    return Ok(());
+                }
                 TokenType::BlockEntry => {
-                    self.scanner.fetch_token();
-                    self.ast_stack.push(YamlBuilder::Sequence(Vec::new()));
+                    self.scanner.fetch_lexeme();
+                    self.open_builder(YamlBuilder::Sequence(Vec::new()), token.0);
+                    self.set_block_column(token.0.col);
                     // Don't push state - we're at root level
                     self.state = State::BlockSequenceFirstEntry;
                     return Ok(());
                 }
                 TokenType::Key => {
-                    self.scanner.fetch_token();
-                    self.ast_stack
-                        .push(YamlBuilder::Mapping(LinkedHashMap::new(), None));
+                    // The key state consumes the `?` and parses the key node
+                    self.open_builder(YamlBuilder::Mapping(LinkedHashMap::new(), None), token.0);
+                    self.set_block_column(token.0.col);
                     self.state = State::BlockMappingFirstKey;
                     return Ok(());
                 }
                 TokenType::FlowSequenceStart => {
-                    self.scanner.fetch_token();
-                    self.ast_stack.push(YamlBuilder::Sequence(Vec::new()));
+                    self.scanner.fetch_lexeme();
+                    self.open_builder(YamlBuilder::Sequence(Vec::new()), token.0);
                     self.state = State::FlowSequenceFirstEntry;
                     return Ok(());
                 }
                 TokenType::FlowMappingStart => {
-                    self.scanner.fetch_token();
-                    self.ast_stack
-                        .push(YamlBuilder::Mapping(LinkedHashMap::new(), None));
+                    self.scanner.fetch_lexeme();
+                    self.open_builder(YamlBuilder::Mapping(LinkedHashMap::new(), None), token.0);
                     self.state = State::FlowMappingFirstKey;
                     return Ok(());
                 }
-                TokenType::Tag(handle, suffix) => {
-                    // Store the tag for the next value
-                    self.pending_tag = Some((handle.clone(), suffix.clone()));
-                    self.scanner.fetch_token();
-                    // Continue looping to parse the value that follows the tag
+                TokenType::Tag(..) | TokenType::Anchor(_) => {
+                    // Store the property for the next node
+                    self.set_property(token.0, &token.1)?;
+                    self.scanner.fetch_lexeme();
+                    // Continue looping to parse the node that follows
                     continue;
                 }
                 TokenType::DocumentStart => {
@@ -296,27 +1046,14 @@ impl<T: Iterator<Item = char>> StateMachine<T> {
                         self.pop_state();
                     }
 
-                    // Restore any saved tags from the tag stack before finalizing AST
-                    if let Some(saved_tag) = self.tag_stack.pop() {
-                        self.pending_tag = saved_tag;
-                    }
-
                     // Finalize any pending AST structures
-                    while let Some(builder) = self.ast_stack.pop() {
-                        let yaml = self.finalize_builder(builder);
-                        self.push_yaml(yaml);
+                    while self.ast_stack.len() > 1 {
+                        self.close_collection();
                     }
                     self.state = State::DocumentEnd;
                     return Ok(());
                 }
                 TokenType::DocumentEnd => {
-                    // Document end marker encountered
-                    // Restore any saved tags before finishing document
-                    if let Some(saved_tag) = self.tag_stack.pop() {
-                        self.pending_tag = saved_tag;
-                    }
-
-                    self.scanner.fetch_token(); // consume
                     self.state = State::DocumentEnd;
                     return Ok(());
                 }
@@ -325,7 +1062,8 @@ impl<T: Iterator<Item = char>> StateMachine<T> {
                     return Ok(());
                 }
                 _ => {
-                    self.push_yaml(Yaml::Null);
+                    let empty = self.empty_node();
+                    self.push_yaml(empty);
                     self.pop_state();
                     return Ok(());
                 }
@@ -341,49 +1079,60 @@ impl<T: Iterator<Item = char>> StateMachine<T> {
     }
 
     fn handle_block_sequence_entry(&mut self) -> Result<(), ScanError> {
-        let token = self.scanner.peek_token()?;
+        let token = self.scanner.peek_lexeme()?;
         match &token.1 {
-            TokenType::BlockEntry => {
-                self.scanner.fetch_token();
+            TokenType::BlockEntry if !self.is_dedent(token.0) => {
+                self.scanner.fetch_lexeme();
                 self.handle_sequence_content()
             }
             _ => {
                 // End of sequence
-                if let Some(YamlBuilder::Sequence(items)) = self.ast_stack.pop() {
-                    self.push_yaml(Yaml::Array(items));
-                }
-                self.pop_state();
-                Ok(())
+                self.close_collection();
+                self.leave_collection()
             }
         }
     }
 
     fn handle_sequence_content(&mut self) -> Result<(), ScanError> {
-        let token = self.scanner.peek_token()?;
+        let token = self.scanner.peek_lexeme()?;
+        // Item content sits right of its `-`; anything else starts the next node
+        let empty_item = self.block_column().is_some_and(|col| token.0.col <= col);
         match &token.1 {
-            TokenType::Scalar(style, value) => {
-                self.scanner.fetch_token();
+            _ if empty_item => {
+                let empty = self.empty_node();
+                let empty = self.finish_node(empty);
+                self.push_item(empty);
+                Ok(())
+            }
+            TokenType::Scalar(style, ..) => {
+                self.scanner.fetch_lexeme();
+
+                // `- key: value` opens a mapping inside the item
+                let next = self.scanner.peek_lexeme()?;
+                if matches!(next.1, TokenType::Value)
+                    && self.block_column().is_none_or(|col| next.0.col > col)
+                {
+                    let key = self.resolve_scalar(*style, &token)?;
+                    self.open_implicit_mapping(token.0, key);
+                    self.context.increment_depth()?;
+                    self.push_state(State::BlockSequenceEntry);
+                    self.state = State::BlockMappingValue;
+                    return Ok(());
+                }
 
-                let yaml = match style {
-                    TScalarStyle::Literal | TScalarStyle::Folded => {
-                        // Block scalars already processed by scanner - use directly
-                        Yaml::String(value.clone())
-                    }
-                    _ => {
-                        // Handle other scalar styles with existing logic
-                        Yaml::parse_str(value)
-                    }
-                };
+                let yaml = self.resolve_scalar(*style, &token)?;
+                let yaml = self.finish_node(yaml);
 
-                if let Some(YamlBuilder::Sequence(items)) = self.ast_stack.last_mut() {
-                    items.push(yaml);
-                }
+                self.push_item(yaml);
                 Ok(())
             }
             TokenType::BlockEntry
             | TokenType::Key
             | TokenType::FlowSequenceStart
-            | TokenType::FlowMappingStart => {
+            | TokenType::FlowMappingStart
+            | TokenType::Tag(..)
+            | TokenType::Anchor(_)
+            | TokenType::Alias(_) => {
                 // Check recursion depth BEFORE transition
                 self.context.increment_depth()?;
 
@@ -392,10 +1141,10 @@ impl<T: Iterator<Item = char>> StateMachine<T> {
                 Ok(())
             }
             _ => {
-                // Empty sequence item - add null
-                if let Some(YamlBuilder::Sequence(items)) = self.ast_stack.last_mut() {
-                    items.push(Yaml::Null);
-                }
+                // Empty sequence item
+                let empty = self.empty_node();
+                let empty = self.finish_node(empty);
+                self.push_item(empty);
                 Ok(())
             }
         }
@@ -415,55 +1164,70 @@ impl<T: Iterator<Item = char>> StateMachine<T> {
     }
 
     fn handle_mapping_key(&mut self) -> Result<(), ScanError> {
-        let token = self.scanner.peek_token()?;
+        let token = self.scanner.peek_lexeme()?;
         match &token.1 {
-            TokenType::Scalar(_, value) => {
-                self.scanner.fetch_token();
-                let key = Yaml::parse_str(value);
-                if let Some(YamlBuilder::Mapping(_, current_key)) = self.ast_stack.last_mut() {
-                    *current_key = Some(key);
+            TokenType::Scalar(..) | TokenType::Alias(_) if !self.is_dedent(token.0) => {
+                self.scanner.fetch_lexeme();
+                let next = self.scanner.peek_lexeme()?;
+                if matches!(next.1, TokenType::Value) {
+                    self.check_implicit_key(&token, next.0)?;
                 }
+                let key = self.leaf_node(&token)?;
+                let key = self.finish_node(key);
+                self.set_pending_key(key, token.0);
                 self.state = State::BlockMappingValue;
                 Ok(())
             }
+            TokenType::Tag(..) | TokenType::Anchor(_) if !self.is_dedent(token.0) => {
+                // Properties of the next key
+                self.set_property(token.0, &token.1)?;
+                self.scanner.fetch_lexeme();
+                Ok(())
+            }
+            TokenType::Key if !self.is_dedent(token.0) => {
+                // Explicit `? key`, where the key may be any node
+                self.scanner.fetch_lexeme();
+                let next = self.scanner.peek_lexeme()?;
+                let empty_key = matches!(next.1, TokenType::Value)
+                    || self.block_column().is_some_and(|col| next.0.col <= col);
+                if empty_key {
+                    let key = self.empty_node();
+                    let key = self.finish_node(key);
+                    self.set_pending_key(key, token.0);
+                    self.state = State::BlockMappingValue;
+                } else {
+                    self.context.increment_depth()?;
+                    // Return to the value once the key node is complete
+                    self.state = State::BlockMappingValue;
+                    self.push_state(State::BlockNode);
+                }
+                Ok(())
+            }
             TokenType::DocumentStart => {
                 // New document started, current document is finished
-                if let Some(YamlBuilder::Mapping(map, _)) = self.ast_stack.pop() {
-                    // Restore saved tag before finalizing the mapping
-                    if let Some(saved_tag) = self.tag_stack.pop() {
-                        self.pending_tag = saved_tag;
-                    }
-                    self.push_yaml(Yaml::Hash(map));
-                }
+                self.close_collection();
                 self.state = State::DocumentEnd;
                 Ok(())
             }
             TokenType::DocumentEnd => {
                 // Document end marker, current document is finished
-                if let Some(YamlBuilder::Mapping(map, _)) = self.ast_stack.pop() {
-                    self.push_yaml(Yaml::Hash(map));
-                }
-                self.scanner.fetch_token(); // consume
+                self.close_collection();
                 self.state = State::DocumentEnd;
                 Ok(())
             }
             TokenType::StreamEnd => {
                 // End of stream
-                if let Some(YamlBuilder::Mapping(map, _)) = self.ast_stack.pop() {
-                    self.push_yaml(Yaml::Hash(map));
-                }
+                self.close_collection();
                 self.state = State::End;
                 Ok(())
             }
             _ => {
                 // End of mapping
-                if let Some(YamlBuilder::Mapping(map, _)) = self.ast_stack.pop() {
-                    self.push_yaml(Yaml::Hash(map));
-                }
+                self.close_collection();
 
                 // Check if we're at the root level
                 if self.states.is_empty() {
-                    self.state = State::End;
+                    self.state = State::DocumentEnd;
                 } else {
                     self.pop_state();
                 }
@@ -473,10 +1237,10 @@ impl<T: Iterator<Item = char>> StateMachine<T> {
     }
 
     fn handle_block_mapping_value(&mut self) -> Result<(), ScanError> {
-        let token = self.scanner.peek_token()?;
+        let token = self.scanner.peek_lexeme()?;
         match &token.1 {
             TokenType::Value => {
-                self.scanner.fetch_token();
+                self.scanner.fetch_lexeme();
 
                 // Fix 3: EXISTING context push logic but with proper n+m calculation
                 let key_indent = self.context.current_indent();
@@ -487,33 +1251,63 @@ impl<T: Iterator<Item = char>> StateMachine<T> {
 
                 // Handle tags and other tokens after the colon
                 loop {
-                    let value_token = self.scanner.peek_token()?;
+                    let value_token = self.scanner.peek_lexeme()?;
+                    // A value must sit right of its key, except for a block
+                    // sequence which may share the key's column
+                    let empty_value = self.block_column().is_some_and(|col| {
+                        value_token.0.col < col
+                            || (value_token.0.col == col
+                                && !matches!(value_token.1, TokenType::BlockEntry))
+                    });
+                    let boundary = matches!(
+                        value_token.1,
+                        TokenType::DocumentStart | TokenType::DocumentEnd | TokenType::StreamEnd
+                    );
+                    if empty_value && !boundary {
+                        self.add_mapping_pair(self.empty_node());
+                        self.state = State::BlockMappingKey;
+                        return Ok(());
+                    }
                     match &value_token.1 {
-                        TokenType::Tag(handle, suffix) => {
-                            // Store the tag for the value
-                            self.pending_tag = Some((handle.clone(), suffix.clone()));
-                            self.scanner.fetch_token();
+                        TokenType::Tag(..) | TokenType::Anchor(_) => {
+                            // Store the property for the value
+                            self.set_property(value_token.0, &value_token.1)?;
+                            self.scanner.fetch_lexeme();
                             // Continue to get the actual value
                             continue;
                         }
-                        TokenType::Scalar(style, value) => {
+                        TokenType::Alias(name) => {
+                            self.scanner.fetch_lexeme();
+                            let yaml_value = self.resolve_alias(value_token.0, name)?;
+                            let next_token = self.scanner.peek_lexeme()?;
+                            if matches!(next_token.1, TokenType::Value)
+                                && self.block_column().is_none_or(|col| next_token.0.col > col)
+                            {
+                                // An aliased key opens a nested mapping
+                                self.open_implicit_mapping(value_token.0, yaml_value);
+                                self.context.increment_depth()?;
+                                self.push_state(State::BlockMappingKey);
+                                self.state = State::BlockMappingValue;
+                                return Ok(());
+                            }
+                            self.add_mapping_pair(yaml_value);
+                            self.state = State::BlockMappingKey;
+                            return Ok(());
+                        }
+                        TokenType::Scalar(style, ..) => {
                             // Consume the scalar first
-                            self.scanner.fetch_token();
+                            self.scanner.fetch_lexeme();
                             
                             // Check what token follows this scalar
-                            let next_token = self.scanner.peek_token()?;
+                            let next_token = self.scanner.peek_lexeme()?;
 
                             // If next token is Value (:), this is a mapping key in nested content
-                            if matches!(next_token.1, TokenType::Value) {
-                                // Save the current pending tag for the outer mapping
-                                let saved_tag = self.pending_tag.take();
-                                self.tag_stack.push(saved_tag);
-
+                            if matches!(next_token.1, TokenType::Value)
+                                && self.block_column().is_none_or(|col| next_token.0.col > col)
+                            {
                                 // Create a new mapping and add this key to it
-                                let key = Yaml::parse_str(value);
-                                let nested_map = crate::linked_hash_map::LinkedHashMap::new();
-
-                                self.ast_stack.push(crate::parser::state_machine::YamlBuilder::Mapping(nested_map, Some(key)));
+                                let key = self.resolve_scalar(*style, &value_token)?;
+                                self.open_implicit_mapping(value_token.0, key);
                                 self.context.increment_depth()?;
                                 self.push_state(State::BlockMappingKey);
                                 self.state = State::BlockMappingValue; // Parse the value for this key
@@ -522,16 +1316,7 @@ impl<T: Iterator<Item = char>> StateMachine<T> {
 
                             // Otherwise, treat as regular scalar value
 
-                            let yaml_value = match style {
-                                TScalarStyle::Literal | TScalarStyle::Folded => {
-                                    // Block scalars already processed by scanner - use directly
-                                    Yaml::String(value.clone())
-                                }
-                                _ => {
-                                    // Handle other scalar styles with existing logic
-                                    Yaml::parse_str(value)
-                                }
-                            };
+                            let yaml_value = self.resolve_scalar(*style, &value_token)?;
 
                             self.add_mapping_pair(yaml_value);
                             self.state = State::BlockMappingKey;
@@ -539,14 +1324,19 @@ impl<T: Iterator<Item = char>> StateMachine<T> {
                         }
                         TokenType::DocumentStart => {
                             // NEW: Handle document boundaries in mapping values
-                            self.add_mapping_pair(Yaml::Null);
+                            self.add_mapping_pair(self.empty_node());
                             self.state = State::DocumentEnd;
                             return Ok(());
                         }
+                        TokenType::StreamEnd => {
+                            // Empty value at the end of input; the key state closes the mapping
+                            self.add_mapping_pair(self.empty_node());
+                            self.state = State::BlockMappingKey;
+                            return Ok(());
+                        }
                         TokenType::DocumentEnd => {
                             // NEW: Handle document end in mapping values
-                            self.add_mapping_pair(Yaml::Null);
-                            self.scanner.fetch_token(); // consume
+                            self.add_mapping_pair(self.empty_node());
                             self.state = State::DocumentEnd;
                             return Ok(());
                         }
@@ -563,7 +1353,7 @@ impl<T: Iterator<Item = char>> StateMachine<T> {
             }
             _ => {
                 // EXISTING null value handling
-                self.add_mapping_pair(Yaml::Null);
+                self.add_mapping_pair(self.empty_node());
                 self.state = State::BlockMappingKey;
                 Ok(())
             }
@@ -571,6 +1361,12 @@ impl<T: Iterator<Item = char>> StateMachine<T> {
     }
 
     fn handle_block_content_with_structure(&mut self) -> Result<(), ScanError> {
+        // Once the root node is complete only a document boundary may follow
+        if self.states.is_empty() && matches!(self.ast_stack.as_slice(), [YamlBuilder::Node(_)]) {
+            self.state = State::DocumentEnd;
+            return Ok(());
+        }
+
         // USE new structural productions with existing context
         let current_indent = self.context.current_indent();
         self.scanner
@@ -584,264 +1380,363 @@ impl<T: Iterator<Item = char>> StateMachine<T> {
     }
 
     fn handle_flow_sequence_first_entry(&mut self) -> Result<(), ScanError> {
-        // Flow sequence switches to FLOW-IN context
+        // Flow sequence switches to FLOW-IN context until its `]`
         let current_indent = self.context.current_indent();
         self.context.push_context(YamlContext::FlowIn, current_indent);
 
+        self.flow_entry = FlowEntry::Open;
         self.state = State::FlowSequenceEntry;
         Ok(())
     }
 
     fn handle_flow_sequence_entry(&mut self) -> Result<(), ScanError> {
-        let token = self.scanner.peek_token()?;
+        let token = self.scanner.peek_lexeme()?;
         match &token.1 {
             TokenType::FlowSequenceEnd => {
-                // Existing implementation - keep as-is
-                self.scanner.fetch_token();
-                if let Some(YamlBuilder::Sequence(items)) = self.ast_stack.pop() {
-                    self.push_yaml(Yaml::Array(items));
+                if self.strict_flow && self.flow_entry == FlowEntry::Comma {
+                    return Err(ScanError::new(
+                        token.0,
+                        "trailing comma in flow sequence is not allowed in strict mode",
+                    ));
                 }
-                self.pop_state();
-                Ok(())
+                self.scanner.fetch_lexeme();
+                self.close_collection();
+                self.context.pop_context();
+                self.flow_entry = FlowEntry::Node;
+                self.leave_collection()
             }
             TokenType::FlowEntry => {
-                // Existing implementation - keep as-is
-                self.scanner.fetch_token();
+                // A comma with no node before it, as in `[, a]` or `[a, , b]`
+                if self.flow_entry != FlowEntry::Node {
+                    let empty = self.flow_empty_node(token.0, "empty flow sequence entry")?;
+                    let empty = self.finish_node(empty);
+                    self.push_item(empty);
+                }
+                self.scanner.fetch_lexeme();
+                self.flow_entry = FlowEntry::Comma;
                 Ok(())
             }
-            TokenType::Scalar(style, value) => {
-                // ENHANCED: Use complete flow productions for scalar parsing
-                self.scanner.fetch_token();
-
-                let yaml = match style {
-                    TScalarStyle::DoubleQuoted => {
-                        // Re-parse with complete double-quoted productions
-                        let mut temp_state =
-                            crate::scanner::state::ScannerState::new(value.chars());
-                        let parsed =
-                            crate::parser::flow::FlowProductions::parse_double_quoted_scalar(
-                                &mut temp_state,
-                                &self.context,
-                                self.context.current_indent(),
-                            )?;
-                        Yaml::String(parsed)
-                    }
-                    TScalarStyle::SingleQuoted => {
-                        // Re-parse with complete single-quoted productions
-                        let mut temp_state =
-                            crate::scanner::state::ScannerState::new(value.chars());
-                        let parsed =
-                            crate::parser::flow::FlowProductions::parse_single_quoted_scalar(
-                                &mut temp_state,
-                                &self.context,
-                                self.context.current_indent(),
-                            )?;
-                        Yaml::String(parsed)
-                    }
-                    TScalarStyle::Plain => {
-                        // Re-parse with complete plain scalar productions
-                        let mut temp_state =
-                            crate::scanner::state::ScannerState::new(value.chars());
-                        let parsed = crate::parser::flow::FlowProductions::parse_plain_scalar(
-                            &mut temp_state,
-                            &self.context,
-                            self.context.current_indent(),
-                        )?;
-                        Yaml::String(parsed)
-                    }
-                    _ => Yaml::parse_str(value), // Fallback for other styles
-                };
-
-                if let Some(YamlBuilder::Sequence(items)) = self.ast_stack.last_mut() {
-                    items.push(yaml);
+            TokenType::Scalar(..) | TokenType::Alias(_) => {
+                self.scanner.fetch_lexeme();
+
+                let yaml = self.leaf_node(&token)?;
+
+                // `[a: b]` is a sequence holding a single-pair mapping
+                if matches!(self.scanner.peek_lexeme()?.1, TokenType::Value) {
+                    // Properties before the key are the key's
+                    let key_props = std::mem::take(&mut self.pending);
+                    self.open_builder(YamlBuilder::Mapping(LinkedHashMap::new(), None), token.0);
+                    let key = self.apply_properties(yaml, key_props);
+                    self.set_pending_key(key, token.0);
+                    self.state = State::FlowSequenceEntryMappingEnd;
+                    self.push_state(State::FlowMappingValue);
+                    return Ok(());
                 }
+                let yaml = self.finish_node(yaml);
+
+                self.push_item(yaml);
+                self.flow_entry = FlowEntry::Node;
+                Ok(())
+            }
+            TokenType::Value => {
+                // `[: b]` pairs an empty key with the value
+                let key = self.flow_empty_node(token.0, "empty flow mapping key")?;
+                let key_props = std::mem::take(&mut self.pending);
+                self.open_builder(YamlBuilder::Mapping(LinkedHashMap::new(), None), token.0);
+                let key = self.apply_properties(key, key_props);
+                self.set_pending_key(key, token.0);
+                self.state = State::FlowSequenceEntryMappingEnd;
+                self.push_state(State::FlowMappingValue);
+                Ok(())
+            }
+            TokenType::Tag(..) | TokenType::Anchor(_) => {
+                self.set_property(token.0, &token.1)?;
+                self.scanner.fetch_lexeme();
+                Ok(())
+            }
+            TokenType::FlowSequenceStart | TokenType::FlowMappingStart => {
+                // A nested collection may turn out to be a single-pair key
+                self.context.increment_depth()?;
+                self.state = State::FlowSequenceEntryMappingKey;
+                self.push_state(State::BlockNode);
                 Ok(())
             }
-            _ => Ok(()),
+            _ => Err(self.flow_entry_error(&token, "flow sequence", ']')),
         }
     }
 
-    const fn handle_flow_mapping_first_key(&mut self) -> Result<(), ScanError> {
+    /// After a nested collection in a flow sequence, a `:` makes it the key
+    /// of a single-pair mapping as in `[[a]: b]`
+    fn handle_flow_sequence_entry_mapping_key(&mut self) -> Result<(), ScanError> {
+        if matches!(self.scanner.peek_lexeme()?.1, TokenType::Value) {
+            let key = match self.ast_stack.last_mut() {
+                Some(YamlBuilder::Sequence(items)) => items.pop(),
+                _ => None,
+            };
+            let key = key.unwrap_or_else(|| self.empty_node());
+            // The key has no path, so neither have the spans logged for it
+            let path = self.open_path();
+            if let Some(spans) = &mut self.spans {
+                spans.forget(&path);
+            }
+            let start = self.node_start;
+            let mapping = YamlBuilder::Mapping(LinkedHashMap::new(), Some((key, start)));
+            self.open_builder(mapping, start);
+            // The mapping starts before its key's events
+            if let (Some(events), Some(index)) = (&mut self.events, self.closed_event)
+                && let Some(event) = events.pop()
+            {
+                events.insert(index - self.events_drained, event);
+                if let Some(props) = self.builder_props.last_mut() {
+                    props.start_event = Some(index);
+                }
+                for (start, _) in &mut self.collection_tags {
+                    if *start >= index {
+                        *start += 1;
+                    }
+                }
+            }
+            self.state = State::FlowSequenceEntryMappingEnd;
+            self.push_state(State::FlowMappingValue);
+        } else {
+            self.state = State::FlowSequenceEntry;
+        }
+        Ok(())
+    }
+
+    /// Close the single-pair mapping of a `[key: value]` entry
+    fn handle_flow_sequence_entry_mapping_end(&mut self) -> Result<(), ScanError> {
+        self.close_collection();
+        self.flow_entry = FlowEntry::Node;
+        self.state = State::FlowSequenceEntry;
+        Ok(())
+    }
+
+    fn handle_flow_mapping_first_key(&mut self) -> Result<(), ScanError> {
+        // Flow mapping switches to FLOW-IN context until its `}`
+        let current_indent = self.context.current_indent();
+        self.context.push_context(YamlContext::FlowIn, current_indent);
+
+        self.flow_entry = FlowEntry::Open;
         self.state = State::FlowMappingKey;
         Ok(())
     }
 
     fn handle_flow_mapping_key(&mut self) -> Result<(), ScanError> {
-        let token = self.scanner.peek_token()?;
-        println!("StateMachine: handle_flow_mapping_key processing token {:?}", token.1);
+        let token = self.scanner.peek_lexeme()?;
+        log::trace!("StateMachine: handle_flow_mapping_key processing token {:?}", token.1);
         match &token.1 {
             TokenType::FlowMappingEnd => {
-                self.scanner.fetch_token();
-                if let Some(YamlBuilder::Mapping(map, _)) = self.ast_stack.pop() {
-                    self.push_yaml(Yaml::Hash(map));
+                if self.strict_flow && self.flow_entry == FlowEntry::Comma {
+                    return Err(ScanError::new(
+                        token.0,
+                        "trailing comma in flow mapping is not allowed in strict mode",
+                    ));
                 }
-                self.pop_state();
-                Ok(())
+                self.scanner.fetch_lexeme();
+                self.close_collection();
+                self.context.pop_context();
+                self.flow_entry = FlowEntry::Node;
+                self.leave_collection()
             }
             TokenType::FlowEntry => {
-                self.scanner.fetch_token();
+                // A comma with no entry before it pairs an empty key with an empty value
+                if self.flow_entry != FlowEntry::Node {
+                    let key = self.flow_empty_node(token.0, "empty flow mapping entry")?;
+                    let key = self.finish_node(key);
+                    self.set_pending_key(key, token.0);
+                    self.add_mapping_pair(self.empty_node());
+                }
+                self.scanner.fetch_lexeme();
+                self.flow_entry = FlowEntry::Comma;
                 Ok(())
             }
-            TokenType::Scalar(style, value) => {
-                // ENHANCED: Use complete flow productions for scalar parsing
-                self.scanner.fetch_token();
-
-                let key = match style {
-                    TScalarStyle::DoubleQuoted => {
-                        // Re-parse with complete double-quoted productions
-                        let mut temp_state =
-                            crate::scanner::state::ScannerState::new(value.chars());
-                        let parsed =
-                            crate::parser::flow::FlowProductions::parse_double_quoted_scalar(
-                                &mut temp_state,
-                                &self.context,
-                                self.context.current_indent(),
-                            )?;
-                        Yaml::String(parsed)
-                    }
-                    TScalarStyle::SingleQuoted => {
-                        // Re-parse with complete single-quoted productions
-                        let mut temp_state =
-                            crate::scanner::state::ScannerState::new(value.chars());
-                        let parsed =
-                            crate::parser::flow::FlowProductions::parse_single_quoted_scalar(
-                                &mut temp_state,
-                                &self.context,
-                                self.context.current_indent(),
-                            )?;
-                        Yaml::String(parsed)
-                    }
-                    TScalarStyle::Plain => {
-                        // Use token value directly to avoid re-parsing
-                        Yaml::String(value.clone())
-                    }
-                    _ => Yaml::parse_str(value), // Fallback for other styles
-                };
+            TokenType::Key => {
+                self.scanner.fetch_lexeme();
+                Ok(())
+            }
+            TokenType::Tag(..) | TokenType::Anchor(_) => {
+                self.set_property(token.0, &token.1)?;
+                self.scanner.fetch_lexeme();
+                Ok(())
+            }
+            TokenType::Scalar(..) | TokenType::Alias(_) => {
+                self.scanner.fetch_lexeme();
 
-                if let Some(YamlBuilder::Mapping(_, current_key)) = self.ast_stack.last_mut() {
-                    *current_key = Some(key);
-                }
+                let key = self.leaf_node(&token)?;
+                let key = self.finish_node(key);
+
+                self.set_pending_key(key, token.0);
                 self.state = State::FlowMappingValue;
                 Ok(())
             }
-            _ => Ok(()),
+            TokenType::Value => {
+                // `{: v}` pairs an empty key with the value
+                let key = self.flow_empty_node(token.0, "empty flow mapping key")?;
+                let key = self.finish_node(key);
+                self.set_pending_key(key, token.0);
+                self.state = State::FlowMappingValue;
+                Ok(())
+            }
+            TokenType::FlowSequenceStart | TokenType::FlowMappingStart => {
+                // A collection key lands in the mapping's pending key slot
+                self.context.increment_depth()?;
+                self.state = State::FlowMappingValue;
+                self.push_state(State::BlockNode);
+                Ok(())
+            }
+            _ => Err(self.flow_entry_error(&token, "flow mapping", '}')),
         }
     }
 
     fn handle_flow_mapping_value(&mut self) -> Result<(), ScanError> {
-        let token = self.scanner.peek_token()?;
-        println!("StateMachine: handle_flow_mapping_value processing token {:?}", token.1);
+        let token = self.scanner.peek_lexeme()?;
+        log::trace!("StateMachine: handle_flow_mapping_value processing token {:?}", token.1);
         match &token.1 {
             TokenType::Value => {
-                self.scanner.fetch_token();
-                let value_token = self.scanner.peek_token()?;
+                self.scanner.fetch_lexeme();
+                let mut value_token = self.scanner.peek_lexeme()?;
+                while let TokenType::Tag(..) | TokenType::Anchor(_) = value_token.1 {
+                    self.set_property(value_token.0, &value_token.1)?;
+                    self.scanner.fetch_lexeme();
+                    value_token = self.scanner.peek_lexeme()?;
+                }
                 match &value_token.1 {
-                    TokenType::Scalar(style, value) => {
-                        // ENHANCED: Use complete flow productions for scalar parsing
-                        self.scanner.fetch_token();
-
-                        let yaml_value = match style {
-                            TScalarStyle::DoubleQuoted => {
-                                // Re-parse with complete double-quoted productions
-                                let mut temp_state =
-                                    crate::scanner::state::ScannerState::new(value.chars());
-                                let parsed = crate::parser::flow::FlowProductions::parse_double_quoted_scalar(
-                                    &mut temp_state,
-                                    &self.context,
-                                    self.context.current_indent()
-                                )?;
-                                Yaml::String(parsed)
-                            }
-                            TScalarStyle::SingleQuoted => {
-                                // Re-parse with complete single-quoted productions
-                                let mut temp_state =
-                                    crate::scanner::state::ScannerState::new(value.chars());
-                                let parsed = crate::parser::flow::FlowProductions::parse_single_quoted_scalar(
-                                    &mut temp_state,
-                                    &self.context,
-                                    self.context.current_indent()
-                                )?;
-                                Yaml::String(parsed)
-                            }
-                            TScalarStyle::Plain => {
-                                // Use token value directly - scanner already parsed correctly
-                                Yaml::String(value.clone())
-                            }
-                            _ => Yaml::parse_str(value), // Fallback for other styles
-                        };
+                    TokenType::Scalar(..) | TokenType::Alias(_) => {
+                        self.scanner.fetch_lexeme();
+
+                        let yaml_value = self.leaf_node(&value_token)?;
 
                         self.add_mapping_pair(yaml_value);
-                        self.state = State::FlowMappingKey;
+                        self.state = self.flow_pair_done();
                         Ok(())
                     }
-                    _ => Ok(()),
+                    TokenType::FlowEntry
+                    | TokenType::FlowMappingEnd
+                    | TokenType::FlowSequenceEnd => {
+                        let empty =
+                            self.flow_empty_node(value_token.0, "empty flow mapping value")?;
+                        self.add_mapping_pair(empty);
+                        self.state = self.flow_pair_done();
+                        Ok(())
+                    }
+                    TokenType::FlowSequenceStart | TokenType::FlowMappingStart => {
+                        self.context.increment_depth()?;
+                        self.state = self.flow_pair_done();
+                        self.push_state(State::BlockNode);
+                        Ok(())
+                    }
+                    _ => Err(ScanError::new(
+                        value_token.0,
+                        "did not find expected node content in flow mapping",
+                    )),
                 }
             }
-            TokenType::FlowEntry => {
-                self.scanner.fetch_token();
-                self.state = State::FlowMappingKey;
+            // `{a, b}` and `{a}` leave the values empty
+            TokenType::FlowEntry | TokenType::FlowMappingEnd | TokenType::FlowSequenceEnd => {
+                let empty = self.flow_empty_node(token.0, "empty flow mapping value")?;
+                self.add_mapping_pair(empty);
+                self.state = self.flow_pair_done();
                 Ok(())
             }
-            _ => Ok(()),
+            _ => Err(ScanError::new(
+                token.0,
+                "did not find expected ':' in flow mapping",
+            )),
+        }
+    }
+
+    /// State to continue in once a flow mapping pair has its value
+    fn flow_pair_done(&mut self) -> State {
+        self.flow_entry = FlowEntry::Node;
+        if self.states.last() == Some(&State::FlowSequenceEntryMappingEnd) {
+            self.states.pop();
+            State::FlowSequenceEntryMappingEnd
+        } else {
+            State::FlowMappingKey
+        }
+    }
+
+    /// Fail with the first profile rule broken while building nodes
+    fn raise_violation(&mut self) -> Result<(), ScanError> {
+        match self.violation.take() {
+            Some(error) => Err(error),
+            None => Ok(()),
         }
     }
 
+    /// Leave a finished collection, ending the document if it was the root node
+    fn leave_collection(&mut self) -> Result<(), ScanError> {
+        if !self.states.is_empty() {
+            self.pop_state();
+            return Ok(());
+        }
+        self.state = State::DocumentEnd;
+        Ok(())
+    }
+
     /// Add a key-value pair to the current mapping
-    fn add_mapping_pair(&mut self, mut value: Yaml) {
-        // Apply pending tag if present
-        if let Some((handle, suffix)) = self.pending_tag.take() {
-            let tag_uri = match handle.as_str() {
-                "!!" => format!("tag:yaml.org,2002:{}", suffix),
-                "!" => suffix,
-                _ => format!("{}{}", handle, suffix),
-            };
-            value = Yaml::Tagged(tag_uri, Box::new(value));
+    fn add_mapping_pair(&mut self, value: Yaml) {
+        // Closing a mapping may offer a value with no key left to take it
+        if !matches!(self.ast_stack.last(), Some(YamlBuilder::Mapping(_, Some(_)))) {
+            return;
         }
+        let value = self.finish_node(value);
 
         if let Some(YamlBuilder::Mapping(map, current_key)) = self.ast_stack.last_mut()
-            && let Some(key) = current_key.take()
+            && let Some((key, key_start)) = current_key.take()
         {
+            if self.reject_duplicate_keys && map.contains_key(&key) && self.violation.is_none() {
+                self.violation = Some(duplicate_key(key_start, &key));
+            }
             map.insert(key, value);
         }
     }
 
-    /// Push a constructed Yaml value onto the AST stack
-    fn push_yaml(&mut self, mut yaml: Yaml) {
-        // Apply pending tag if present
-        if let Some((handle, suffix)) = self.pending_tag.take() {
-            let tag_uri = match handle.as_str() {
-                "!!" => format!("tag:yaml.org,2002:{}", suffix),
-                "!" => suffix,
-                _ => format!("{}{}", handle, suffix),
-            };
-            yaml = Yaml::Tagged(tag_uri, Box::new(yaml));
+    /// Add a finished node to the innermost builder if it is a sequence;
+    /// when streaming the events carry the node, so it is not kept
+    fn push_item(&mut self, yaml: Yaml) {
+        if let Some(YamlBuilder::Sequence(items)) = self.ast_stack.last_mut()
+            && !self.streamed
+        {
+            items.push(yaml);
         }
+    }
+
+    /// Push a constructed Yaml value onto the AST stack
+    fn push_yaml(&mut self, yaml: Yaml) {
+        let yaml = self.finish_node(yaml);
+        self.insert_node(yaml);
+    }
 
+    /// Add a finished node to the innermost builder
+    fn insert_node(&mut self, yaml: Yaml) {
         // If we have a container being built, add to it
         if let Some(builder) = self.ast_stack.last_mut() {
             match builder {
-                YamlBuilder::Sequence(items) => {
-                    items.push(yaml);
-                }
+                // When streaming the events carry the node
+                YamlBuilder::Sequence(_) if self.streamed => {}
+                YamlBuilder::Sequence(items) => items.push(yaml),
                 YamlBuilder::Mapping(map, current_key) => {
-                    if let Some(key) = current_key.take() {
+                    if let Some((key, key_start)) = current_key.take() {
                         // We have a key waiting for a value
+                        if self.reject_duplicate_keys
+                            && map.contains_key(&key)
+                            && self.violation.is_none()
+                        {
+                            self.violation = Some(duplicate_key(key_start, &key));
+                        }
                         map.insert(key, yaml);
                     } else {
                         // No key yet, this must be a key
-                        *current_key = Some(yaml);
+                        *current_key = Some((yaml, self.node_start));
                     }
                 }
                 _ => {}
             }
         } else {
-            // This is the root document - push the yaml directly
-            self.ast_stack.push(match yaml {
-                Yaml::Array(items) => YamlBuilder::Sequence(items),
-                Yaml::Hash(map) => YamlBuilder::Mapping(map, None),
-                Yaml::String(s) => YamlBuilder::Scalar(s),
-                other => YamlBuilder::Scalar(format!("{:?}", other)),
-            });
+            // This is the root document - keep the finished node
+            self.open_builder(YamlBuilder::Node(yaml), self.node_start);
         }
     }
 
@@ -851,66 +1746,182 @@ impl<T: Iterator<Item = char>> StateMachine<T> {
                 // Flow contexts cannot have block entries
                 !matches!(token, TokenType::BlockEntry)
             }
-            YamlContext::BlockKey => {
-                // Block key context has restricted character set
-                !matches!(
-                    token,
-                    TokenType::FlowSequenceStart | TokenType::FlowMappingStart
-                )
-            }
             _ => true,
         }
     }
 
+    /// Pass a completed node through the node middleware at the path it is
+    /// about to take in the innermost builder, logging its span if kept
+    fn rewrite_node(&mut self, yaml: Yaml, start: Option<(Marker, Option<usize>)>) -> Yaml {
+        if self.middleware.is_none() && self.spans.is_none() {
+            return yaml;
+        }
+        let is_key = matches!(self.ast_stack.last(), Some(YamlBuilder::Mapping(_, None)));
+        let mut path = self.open_path();
+        if is_key {
+            // The value's path names the key as written
+            let segment = crate::pointer::key_token(&yaml);
+            path.push('/');
+            path.push_str(&segment);
+            if let Some(props) = self.builder_props.last_mut() {
+                props.key_segment = Some(segment);
+            }
+        }
+        if self.spans.is_some() {
+            self.log_span(&path, is_key, start);
+        }
+        match self.middleware.clone() {
+            Some(middleware) => middleware(NodeInfo { path: &path, is_key }, yaml),
+            None => yaml,
+        }
+    }
+
+    /// Log the node completing at `path`, which began at `start` or is
+    /// empty. Keys, nodes inside them and nodes under a collection key have
+    /// no path and are not logged.
+    fn log_span(&mut self, path: &str, is_key: bool, start: Option<(Marker, Option<usize>)>) {
+        let in_key = self
+            .ast_stack
+            .iter()
+            .rev()
+            .skip(usize::from(is_key))
+            .any(|builder| match builder {
+                YamlBuilder::Mapping(_, None) => true,
+                YamlBuilder::Mapping(_, Some((key, _))) => crate::pointer::key_text(key).is_none(),
+                _ => false,
+            });
+        let end = self.scanner.last_token_end().byte_offset;
+        let (start, alias) = start.map_or((end, None), |(mark, alias)| (mark.byte_offset, alias));
+        let Some(spans) = &mut self.spans else {
+            return;
+        };
+        if is_key && !in_key {
+            // A repeated key replaces the value logged under it
+            spans.forget(path);
+        }
+        let path = (!is_key && !in_key).then(|| path.to_string());
+        spans.record(path, start.min(end)..end, alias);
+    }
+
+    /// JSON Pointer of the node that goes into the innermost open builder
+    fn open_path(&self) -> String {
+        let mut path = String::new();
+        for (builder, props) in self.ast_stack.iter().zip(&self.builder_props) {
+            match builder {
+                YamlBuilder::Sequence(items) => {
+                    path.push('/');
+                    path.push_str(&items.len().to_string());
+                }
+                YamlBuilder::Mapping(_, Some((key, _))) => {
+                    path.push('/');
+                    match &props.key_segment {
+                        Some(segment) => path.push_str(segment),
+                        None => path.push_str(&crate::pointer::key_token(key)),
+                    }
+                }
+                // A collection used as a key has no path of its own
+                YamlBuilder::Mapping(_, None) | YamlBuilder::Node(_) => {}
+            }
+        }
+        path
+    }
+
     /// Finalize a YamlBuilder into a Yaml value
     fn finalize_builder(&self, builder: YamlBuilder) -> Yaml {
         match builder {
             YamlBuilder::Sequence(items) => Yaml::Array(items),
             YamlBuilder::Mapping(map, _) => Yaml::Hash(map),
-            YamlBuilder::Scalar(s) => Yaml::String(s),
+            YamlBuilder::Node(yaml) => yaml,
         }
     }
 
     /// Handle directive header processing
     fn handle_directive_header(&mut self) -> Result<(), ScanError> {
-        let token = self.scanner.peek_token()?;
+        let token = self.scanner.peek_lexeme()?;
         match &token.1 {
             TokenType::VersionDirective(major, minor) => {
+                if self.directives.yaml_version.is_some() {
+                    return Err(ScanError::new(token.0, "duplicate %YAML directive"));
+                }
                 self.process_yaml_directive(*major, *minor)?;
-                self.scanner.fetch_token(); // consume
+                self.scanner.fetch_lexeme(); // consume
                 Ok(())
             }
             TokenType::TagDirective(handle, prefix) => {
+                if self.directives.tag_handles.contains_key(handle) {
+                    return Err(ScanError::new(
+                        token.0,
+                        &format!("duplicate %TAG directive for handle {handle}"),
+                    ));
+                }
                 self.process_tag_directive(handle.clone(), prefix.clone())?;
-                self.scanner.fetch_token(); // consume
+                self.scanner.fetch_lexeme(); // consume
+                Ok(())
+            }
+            TokenType::Reserved(_) => {
+                // Reserved directives are ignored, but still need `---`
+                self.directives.reserved = true;
+                self.scanner.fetch_lexeme(); // consume
                 Ok(())
             }
             TokenType::DocumentStart => {
-                self.scanner.fetch_token(); // consume
+                self.scanner.fetch_lexeme(); // consume
+                self.explicit_document = true;
+                self.log_event(Event::DocumentStart, token.0);
                 self.state = State::DocumentContent;
                 Ok(())
             }
+            _ if !self.directives.is_empty() => Err(
+                ScanError::new(token.0, "directives must be followed by a document start marker '---'"),
+            ),
+            TokenType::DocumentEnd => {
+                // A `...` with no document before it only closes comments
+                self.scanner.fetch_lexeme();
+                Ok(())
+            }
             TokenType::StreamEnd => {
                 self.state = State::End;
                 Ok(())
             }
             _ => {
-                // Implicit document start
+                // Bare document
+                self.log_event(Event::DocumentStart, token.0);
                 self.state = State::DocumentContent;
                 Ok(())
             }
         }
     }
 
-    /// Handle document end processing
-    const fn handle_document_end(&mut self) -> Result<(), ScanError> {
+    /// Close the current document at `...`, `---` or the end of the stream
+    fn handle_document_end(&mut self) -> Result<(), ScanError> {
+        let token = self.scanner.peek_lexeme()?;
+        match &token.1 {
+            TokenType::DocumentEnd => {
+                self.scanner.fetch_lexeme();
+            }
+            TokenType::DocumentStart | TokenType::StreamEnd => {}
+            TokenType::VersionDirective(..)
+            | TokenType::TagDirective(..)
+            | TokenType::Reserved(_) => {
+                return Err(ScanError::new(
+                    token.0,
+                    "directives must follow a document end marker '...'",
+                ));
+            }
+            _ => {
+                return Err(ScanError::new(
+                    token.0,
+                    "expected a document end marker '...' or a new document '---'",
+                ));
+            }
+        }
         self.state = State::NextDocument;
         Ok(())
     }
 
     /// Handle next document processing
     fn handle_next_document(&mut self) -> Result<(), ScanError> {
-        let token = self.scanner.peek_token()?;
+        let token = self.scanner.peek_lexeme()?;
         match &token.1 {
             TokenType::StreamEnd => {
                 self.state = State::End;
@@ -935,14 +1946,14 @@ impl<T: Iterator<Item = char>> StateMachine<T> {
         }
 
         // Store for document processing
-        self.yaml_version = Some((major, minor));
+        self.directives.yaml_version = Some((major, minor));
         Ok(())
     }
 
     /// Process TAG directive
     fn process_tag_directive(&mut self, handle: String, prefix: String) -> Result<(), ScanError> {
         // Register tag handle for document scope
-        self.tag_handles.insert(handle, prefix);
+        self.directives.tag_handles.insert(handle, prefix);
         Ok(())
     }
 
@@ -953,6 +1964,87 @@ impl<T: Iterator<Item = char>> StateMachine<T> {
 
     /// Parse next document from stream
     pub fn parse_next_document(&mut self) -> Result<Option<Yaml>, ScanError> {
+        self.parse_next_document_with(|| Ok(()))
+    }
+
+    /// Parse next document, calling `check` before every state transition so
+    /// callers can report progress or abort at token boundaries.
+    pub(crate) fn parse_next_document_with<E, F>(
+        &mut self,
+        mut check: F,
+    ) -> Result<Option<Yaml>, E>
+    where
+        E: From<ScanError>,
+        F: FnMut() -> Result<(), E>,
+    {
+        self.parse_document(|_| check())
+    }
+
+    /// Parse the next document built
+    /// [`with_streamed_nodes`](Self::with_streamed_nodes), passing its events
+    /// to `receiver` as soon as no later token can change them. Returns
+    /// whether there was a document; `check` runs after every pass, so the
+    /// receiver can stop the parse.
+    pub(crate) fn stream_next_document<R, E, F>(
+        &mut self,
+        receiver: &mut R,
+        mut check: F,
+    ) -> Result<bool, E>
+    where
+        R: TaggedEventReceiver,
+        E: From<ScanError>,
+        F: FnMut(&mut R) -> Result<(), E>,
+    {
+        let document = self.parse_document(|machine| {
+            machine.drain_events(receiver, false);
+            check(receiver)
+        });
+        self.drain_events(receiver, true);
+        check(receiver)?;
+        Ok(document?.is_some())
+    }
+
+    /// Pass the logged events that are final to `receiver`, or all of them
+    /// if `all`. A collection's anchor id is set when it closes, and a
+    /// collection in a flow sequence may still become a key, so events are
+    /// held from the start of the first such collection still open.
+    fn drain_events<R: TaggedEventReceiver>(&mut self, receiver: &mut R, all: bool) {
+        let Some(events) = &mut self.events else {
+            return;
+        };
+        let mut end = events.len();
+        if !all {
+            let held = self
+                .builder_props
+                .iter()
+                .filter(|props| props.anchor.is_some() || props.may_become_key)
+                .filter_map(|props| props.start_event);
+            let closed = self
+                .closed_event
+                .filter(|_| self.state == State::FlowSequenceEntryMappingKey);
+            if let Some(first) = held.chain(closed).min() {
+                end = end.min(first.saturating_sub(self.events_drained));
+            }
+        }
+        let base = self.events_drained;
+        self.events_drained += end;
+        let tags = self.collection_tags.partition_point(|&(index, _)| index < base + end);
+        let mut tags = self.collection_tags.drain(..tags).peekable();
+        for (offset, (event, mark)) in events.drain(..end).enumerate() {
+            if let Some((_, tag)) = tags.next_if(|&(index, _)| index == base + offset) {
+                receiver.on_collection_tag(tag);
+            }
+            receiver.on_event(event, mark);
+        }
+    }
+
+    /// Parse one document, calling `step` before every state transition
+    fn parse_document<E, F>(&mut self, mut step: F) -> Result<Option<Yaml>, E>
+    where
+        E: From<ScanError>,
+        F: FnMut(&mut Self) -> Result<(), E>,
+    {
+        trace_span!("yyaml.document");
         // If we're at NextDocument from a previous parse, transition to start next document
         if self.state == State::NextDocument {
             self.handle_next_document()?;
@@ -960,33 +2052,127 @@ impl<T: Iterator<Item = char>> StateMachine<T> {
 
         // Check if already at end (could have transitioned to End in handle_next_document)
         if self.at_stream_end() {
+            self.end_stream();
             return Ok(None);
         }
 
-        // Reset document-level state
-        self.yaml_version = None;
-        self.tag_handles.clear();
+        // Reset document-level state; directives and anchors of one document
+        // never reach the next
+        self.directives = DocumentScope::default();
         self.anchors.clear();
-        self.anchor_id = 1;
+        if self.kept_anchors.is_none() {
+            self.anchor_id = 1;
+        }
+        self.anchored.clear();
+        self.alias_expansion = 0;
+        self.alias_log.clear();
+        self.pending = NodeProperties::default();
         self.ast_stack.clear();
+        self.builder_props.clear();
+        self.block_columns.clear();
+        self.scanner.set_indent_floor(None);
+        self.explicit_document = false;
 
         // Parse until we reach DocumentEnd or stream end
         while self.state != State::End && self.state != State::NextDocument {
+            step(self)?;
             self.execute_state()?;
+            self.raise_violation()?;
+        }
+
+        trace_event!(tokens_scanned = self.scanner.tokens_scanned(), "document parsed");
+
+        // Collections still open at the end of input nest into their parents
+        while self.ast_stack.len() > 1 {
+            self.close_collection();
         }
+        self.raise_violation()?;
 
         // Return constructed document
-        if let Some(builder) = self.ast_stack.pop() {
-            Ok(Some(self.finalize_builder(builder)))
-        } else if self.state == State::End {
-            Ok(None) // End of stream
-        } else {
+        let root = if let Some(yaml) = self.pop_builder() {
+            yaml
+        } else if self.explicit_document || self.state != State::End {
             // Empty document
-            Ok(Some(Yaml::Null))
+            if self.events.is_some() {
+                self.log_node(&Yaml::Null, None, None, None);
+            }
+            Yaml::Null
+        } else {
+            self.end_stream();
+            return Ok(None); // End of stream
+        };
+        self.log_event(Event::DocumentEnd, self.scanner.last_token_end());
+        Ok(Some(root))
+    }
+
+    /// Log `StreamEnd` once the stream has no documents left
+    fn end_stream(&mut self) {
+        if !std::mem::replace(&mut self.stream_ended, true) {
+            self.log_event(Event::StreamEnd, self.scanner.mark());
         }
     }
 }
 
+/// The `%YAML` and `%TAG` directives in force for one document
+#[derive(Debug, Default)]
+struct DocumentScope {
+    yaml_version: Option<(u32, u32)>,
+    /// Prefix of each handle declared with `%TAG`
+    tag_handles: HashMap<String, String>,
+    /// Whether a reserved directive such as `%FUTURE` was seen
+    reserved: bool,
+}
+
+impl DocumentScope {
+    fn is_empty(&self) -> bool {
+        self.yaml_version.is_none() && self.tag_handles.is_empty() && !self.reserved
+    }
+
+    /// The full tag for `handle` and `suffix`. A `%TAG` directive overrides
+    /// even `!` and `!!`, and an empty handle marks a verbatim tag. Named
+    /// handles are checked when read, so an undeclared one never gets here.
+    fn resolve_tag(&self, handle: &str, suffix: String) -> String {
+        match (self.tag_handles.get(handle), handle) {
+            (Some(prefix), _) => format!("{prefix}{suffix}"),
+            (None, "!!") => format!("tag:yaml.org,2002:{suffix}"),
+            (None, "!" | "") => suffix,
+            (None, _) => format!("{handle}{suffix}"),
+        }
+    }
+}
+
+/// Error for a mapping key already present in its mapping
+fn duplicate_key(mark: Marker, key: &Yaml) -> ScanError {
+    let key = crate::pointer::key_text(key).map_or_else(|| format!("{key:?}"), Cow::into_owned);
+    ScanError::new(mark, &format!("duplicate mapping key `{key}`"))
+}
+
+/// Tag standing in for anchor `id` on a node kept by
+/// [`StateMachine::with_kept_anchors`]; scanned tags never contain NUL
+fn anchor_mark(id: usize) -> String {
+    format!("\0&{id}")
+}
+
+/// The anchor id an [`anchor_mark`] tag stands for
+pub(crate) fn marked_anchor(tag: &str) -> Option<usize> {
+    tag.strip_prefix("\0&")?.parse().ok()
+}
+
+/// Number of nodes in `yaml`, counting keys and tag wrappers
+fn node_count(yaml: &Yaml) -> usize {
+    match yaml {
+        Yaml::Array(items) => 1 + items.iter().map(node_count).sum::<usize>(),
+        Yaml::Hash(map) => {
+            1 + map
+                .iter()
+                .map(|(key, value)| node_count(key) + node_count(value))
+                .sum::<usize>()
+        }
+        Yaml::Tagged(_, inner) => 1 + node_count(inner),
+        _ => 1,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1015,10 +2201,78 @@ outer:
         let mut sm = StateMachine::new(yaml.chars());
 
         // Verify ParametricContext is initialized properly
-        assert_eq!(sm.context.current_context, Context::BlockOut);
+        assert_eq!(sm.context.current_context, YamlContext::BlockOut);
         assert_eq!(sm.context.current_indent(), 0);
 
         let result = sm.parse();
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_empty_nodes_and_dedent() {
+        let yaml = "a:\n  b:\n  c: 1\nd:\n- \n- - x\n  - y: 1\n    z:\n";
+        let mut sm = StateMachine::new(yaml.chars()).with_empty_scalar(EmptyScalar::EmptyString);
+        let doc = sm.parse_next_document().unwrap().unwrap();
+        let empty = || Yaml::String(String::new());
+        assert_eq!(doc["a"]["b"], empty());
+        assert_eq!(doc["a"]["c"], Yaml::Integer(1));
+        assert_eq!(doc["d"][0], empty());
+        assert_eq!(doc["d"][1][0], Yaml::String("x".into()));
+        assert_eq!(doc["d"][1][1]["z"], empty());
+    }
+
+    #[test]
+    fn test_explicit_keys() {
+        let yaml = "? - p\n  - q\n: 1\n? a: 1\n: 2\nouter:\n  ? k\n  : v\n  j: 3\n";
+        let doc = StateMachine::new(yaml.chars()).parse().unwrap();
+        let map = doc.as_hash().unwrap();
+        let seq_key = Yaml::Array(vec![Yaml::String("p".into()), Yaml::String("q".into())]);
+        assert_eq!(map.get(&seq_key), Some(&Yaml::Integer(1)));
+        let mut map_key = LinkedHashMap::new();
+        map_key.insert(Yaml::String("a".into()), Yaml::Integer(1));
+        assert_eq!(map.get(&Yaml::Hash(map_key)), Some(&Yaml::Integer(2)));
+        assert_eq!(doc["outer"]["k"], Yaml::String("v".into()));
+        assert_eq!(doc["outer"]["j"], Yaml::Integer(3));
+    }
+
+    #[test]
+    fn test_properties_on_collections() {
+        let yaml = "m: &m !t {a: 1}\ns: &s !u [1, 2]\nb: !v &b\n  - x\nk: &k\n  j: 1\n\
+                    refs:\n  - *m\n  - *s\n  - *b\n  - *k\n";
+        let doc = StateMachine::new(yaml.chars()).parse().unwrap();
+        let mut inner = LinkedHashMap::new();
+        inner.insert(Yaml::String("a".into()), Yaml::Integer(1));
+        let m = Yaml::Tagged("t".into(), Box::new(Yaml::Hash(inner)));
+        let s = Yaml::Tagged(
+            "u".into(),
+            Box::new(Yaml::Array(vec![Yaml::Integer(1), Yaml::Integer(2)])),
+        );
+        let b = Yaml::Tagged("v".into(), Box::new(Yaml::Array(vec![Yaml::String("x".into())])));
+        assert_eq!(doc["m"], m);
+        assert_eq!(doc["s"], s);
+        assert_eq!(doc["b"], b);
+        assert_eq!(doc["refs"][0], m);
+        assert_eq!(doc["refs"][1], s);
+        assert_eq!(doc["refs"][2], b);
+        assert_eq!(doc["refs"][3], doc["k"]);
+        assert_eq!(doc["k"]["j"], Yaml::Integer(1));
+
+        let doc = StateMachine::new("&k key: v\nref: *k\n".chars()).parse().unwrap();
+        assert_eq!(doc["ref"], Yaml::String("key".into()));
+    }
+
+    #[test]
+    fn test_alias_keys_and_flow_aliases() {
+        let yaml = "a: &x k\n*x : v\nm: {*x : 1, n: *x}\ns: [*x, *x: 2]\n";
+        let doc = StateMachine::new(yaml.chars()).parse().unwrap();
+        let k = Yaml::String("k".into());
+        assert_eq!(doc["k"], Yaml::String("v".into()));
+        assert_eq!(doc["m"]["k"], Yaml::Integer(1));
+        assert_eq!(doc["m"]["n"], k);
+        assert_eq!(doc["s"][0], k);
+        assert_eq!(doc["s"][1]["k"], Yaml::Integer(2));
+
+        let err = StateMachine::new("b: [*a]\n".chars()).parse().unwrap_err();
+        assert_eq!((err.info.as_str(), err.mark.col), ("unknown anchor", 4));
+    }
 }
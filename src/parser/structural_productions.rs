@@ -5,7 +5,7 @@
 
 use crate::error::ScanError;
 use crate::parser::character_productions::CharacterProductions;
-use crate::parser::grammar::{ChompingMode, Context as YamlContext, ParametricContext};
+use crate::parser::grammar::{ChompingMode, YamlContext, ParametricContext};
 use crate::scanner::state::ScannerState;
 use crate::scanner::utils::{consume_line_break, skip_whitespace_and_comments};
 
@@ -0,0 +1,373 @@
+//! Filtering and rewriting YAML streams event by event
+//!
+//! A [`Pipeline`] reads a stream, passes every [`Event`] through a chain of
+//! [`Transform`]s and writes what comes out as the parser produces it. No
+//! document is built: memory is bounded by how deeply nodes nest, plus the
+//! events of anchored nodes, which are kept so their aliases can be
+//! written out. Events of an anchored collection reach the transforms once
+//! it closes.
+//!
+//! Events are those of [`YamlLoader::parse_events`](crate::YamlLoader::parse_events): anchored nodes carry
+//! their anchor id and aliases are left unexpanded for transforms to see.
+//! The writer expands aliases, as loading does.
+//!
+//! Output is written as it is produced, so on an error the documents before
+//! it, and part of the one it is in, have already been written.
+
+use std::collections::HashMap;
+use std::{fmt, io};
+
+use smallvec::{SmallVec, smallvec};
+
+use crate::Error;
+use crate::emitter::YamlEmitter;
+use crate::error::{Marker, ScanError};
+use crate::events::{Event, MarkedEventReceiver, TScalarStyle, TaggedEventReceiver};
+use crate::parser::state_machine::{MAX_ALIAS_EXPANSION, StateMachine};
+
+/// What a [`Transform`] passes on for one event; usually just that event
+pub type Events = SmallVec<[Event; 1]>;
+
+/// One stage of a [`Pipeline`].
+///
+/// Returning no events drops the input event, returning several inserts
+/// new ones. The events a transform passes on must still nest properly.
+pub trait Transform {
+    fn on_event(&mut self, event: Event) -> Events;
+}
+
+impl<F: FnMut(Event) -> Events> Transform for F {
+    fn on_event(&mut self, event: Event) -> Events {
+        self(event)
+    }
+}
+
+/// Reader, transforms and writer for one YAML stream.
+///
+/// ```rust
+/// use yyaml::pipeline::{DropKey, Pipeline, RenameKey};
+///
+/// let input = "image: app\nreplicas: 2\nsecret: {token: x}\n---\nimage: db\n";
+/// let mut out = String::new();
+/// let documents = Pipeline::new()
+///     .transform(RenameKey::new("image", "container"))
+///     .transform(DropKey::new("secret"))
+///     .run(input, &mut out)
+///     .unwrap();
+/// assert_eq!(documents, 2);
+/// assert_eq!(out, "---\ncontainer: app\nreplicas: 2\n---\ncontainer: db\n");
+/// ```
+#[derive(Default)]
+pub struct Pipeline {
+    transforms: Vec<Box<dyn Transform>>,
+}
+
+impl Pipeline {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append `transform`, which sees the output of the ones added before it
+    #[must_use]
+    pub fn transform<T: Transform + 'static>(mut self, transform: T) -> Self {
+        self.transforms.push(Box::new(transform));
+        self
+    }
+
+    /// Run `input` through the pipeline into `out`, returning the number of
+    /// documents written
+    pub fn run(&mut self, input: &str, out: &mut dyn fmt::Write) -> Result<usize, Error> {
+        self.drive(input, &mut Writer::new(YamlEmitter::new(out)))
+    }
+
+    /// Like [`run`](Self::run), writing to an [`io::Write`]
+    pub fn run_io(&mut self, input: &str, out: &mut dyn io::Write) -> Result<usize, Error> {
+        self.drive(input, &mut Writer::new(YamlEmitter::from_io(out)))
+    }
+
+    fn drive(&mut self, input: &str, writer: &mut Writer<'_>) -> Result<usize, Error> {
+        let mut state_machine = StateMachine::over_str(input).with_streamed_nodes();
+        let mut forward = Forward {
+            transforms: &mut self.transforms,
+            writer,
+            error: None,
+            failed: false,
+            documents: false,
+        };
+        while state_machine.stream_next_document(&mut forward, Forward::check)? {}
+        Ok(forward.writer.documents)
+    }
+}
+
+/// Pass `event` through every transform in turn and write the result
+fn send(
+    transforms: &mut [Box<dyn Transform>],
+    event: Event,
+    writer: &mut Writer<'_>,
+) -> Result<(), Error> {
+    let mut pending: Events = smallvec![event];
+    for transform in transforms {
+        pending = pending
+            .into_iter()
+            .flat_map(|event| transform.on_event(event))
+            .collect();
+    }
+    pending.into_iter().try_for_each(|event| writer.write(event))
+}
+
+/// A null scalar, as an empty stream's document or an unknown alias
+fn null() -> Event {
+    Event::Scalar("~".to_owned(), TScalarStyle::Plain, 0, None, None)
+}
+
+/// Sends the parser's events on as they are produced, keeping the first
+/// error for [`check`](Self::check); later events are dropped
+struct Forward<'p, 'w, 'a> {
+    transforms: &'p mut [Box<dyn Transform>],
+    writer: &'w mut Writer<'a>,
+    error: Option<Error>,
+    failed: bool,
+    /// Whether a document has started
+    documents: bool,
+}
+
+impl Forward<'_, '_, '_> {
+    fn check(&mut self) -> Result<(), Error> {
+        self.error.take().map_or(Ok(()), Err)
+    }
+
+    fn send(&mut self, event: Event) {
+        if let Err(e) = send(self.transforms, event, self.writer) {
+            self.error = Some(e);
+            self.failed = true;
+        }
+    }
+}
+
+impl MarkedEventReceiver for Forward<'_, '_, '_> {
+    fn on_event(&mut self, ev: Event, mark: Marker) {
+        if self.failed {
+            return;
+        }
+        self.writer.mark = mark;
+        match ev {
+            Event::DocumentStart => self.documents = true,
+            // An empty stream loads as one null document
+            Event::StreamEnd if !self.documents => {
+                self.documents = true;
+                for event in [Event::DocumentStart, null(), Event::DocumentEnd] {
+                    self.on_event(event, mark);
+                }
+            }
+            _ => {}
+        }
+        self.send(ev);
+    }
+}
+
+impl TaggedEventReceiver for Forward<'_, '_, '_> {
+    // Collection tags are not written
+    fn on_collection_tag(&mut self, _tag: String) {}
+}
+
+/// Emits each event as it comes, expanding aliases from the recorded
+/// events of their anchored nodes
+struct Writer<'a> {
+    emitter: YamlEmitter<'a>,
+    /// Events of each anchored node seen in the document, by anchor id
+    anchors: HashMap<usize, Vec<Event>>,
+    /// Anchored nodes being recorded, innermost last, with the collections
+    /// open in each
+    recording: Vec<(usize, Vec<Event>, usize)>,
+    /// Nodes written from expanded aliases so far
+    expanded: usize,
+    /// Where the parser was when the event being written was produced
+    mark: Marker,
+    documents: usize,
+}
+
+impl<'a> Writer<'a> {
+    fn new(emitter: YamlEmitter<'a>) -> Self {
+        Self {
+            emitter,
+            anchors: HashMap::new(),
+            recording: Vec::new(),
+            expanded: 0,
+            mark: Marker::default(),
+            documents: 0,
+        }
+    }
+
+    fn write(&mut self, event: Event) -> Result<(), Error> {
+        match event {
+            Event::Alias(id) => {
+                let events = self.anchors.get(&id).cloned().unwrap_or_else(|| vec![null()]);
+                self.expanded += events.len();
+                if self.expanded > MAX_ALIAS_EXPANSION {
+                    return Err(ScanError::new(self.mark, "repetition limit exceeded").into());
+                }
+                events.into_iter().try_for_each(|event| self.put(event))
+            }
+            Event::DocumentStart => {
+                self.anchors.clear();
+                self.recording.clear();
+                self.put(event)
+            }
+            Event::DocumentEnd => {
+                self.put(event)?;
+                self.documents += 1;
+                Ok(())
+            }
+            _ => self.put(event),
+        }
+    }
+
+    /// Emit an event with no alias in it, recording it for the anchored
+    /// nodes it is part of
+    fn put(&mut self, event: Event) -> Result<(), Error> {
+        let anchor = match event {
+            Event::Scalar(_, _, anchor, ..)
+            | Event::SequenceStart(anchor, _)
+            | Event::MappingStart(anchor, _) => anchor,
+            _ => 0,
+        };
+        if anchor > 0 {
+            self.recording.push((anchor, Vec::new(), 0));
+        }
+        for (_, events, depth) in &mut self.recording {
+            match event {
+                Event::SequenceStart(..) | Event::MappingStart(..) => *depth += 1,
+                Event::SequenceEnd | Event::MappingEnd => *depth -= 1,
+                _ => {}
+            }
+            events.push(event.clone());
+        }
+        while let Some((_, _, 0)) = self.recording.last() {
+            if let Some((anchor, events, _)) = self.recording.pop() {
+                self.anchors.insert(anchor, events);
+            }
+        }
+        self.emitter.emit_event(event)?;
+        Ok(())
+    }
+}
+
+/// Where the next event falls in the collection it belongs to
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Slot {
+    Item,
+    Key,
+    Value,
+}
+
+/// Tracks whether the next node is a mapping key
+#[derive(Default)]
+struct Position {
+    open: Vec<Slot>,
+}
+
+impl Position {
+    fn at_key(&self) -> bool {
+        self.open.last() == Some(&Slot::Key)
+    }
+
+    fn advance(&mut self, event: &Event) {
+        match event {
+            Event::SequenceStart(..) => self.open.push(Slot::Item),
+            Event::MappingStart(..) => self.open.push(Slot::Key),
+            Event::SequenceEnd | Event::MappingEnd => {
+                self.open.pop();
+                self.complete();
+            }
+            Event::Scalar(..) | Event::Alias(_) => self.complete(),
+            _ => {}
+        }
+    }
+
+    /// A whole node was passed: a key is followed by its value and a value
+    /// by the next key
+    fn complete(&mut self) {
+        if let Some(slot) = self.open.last_mut() {
+            *slot = match *slot {
+                Slot::Key => Slot::Value,
+                Slot::Value => Slot::Key,
+                Slot::Item => Slot::Item,
+            };
+        }
+    }
+}
+
+/// Rename every mapping key `from` to `to`, at any depth
+pub struct RenameKey {
+    from: String,
+    to: String,
+    position: Position,
+}
+
+impl RenameKey {
+    #[must_use]
+    pub fn new(from: impl Into<String>, to: impl Into<String>) -> Self {
+        Self {
+            from: from.into(),
+            to: to.into(),
+            position: Position::default(),
+        }
+    }
+}
+
+impl Transform for RenameKey {
+    fn on_event(&mut self, mut event: Event) -> Events {
+        if self.position.at_key()
+            && let Event::Scalar(value, ..) = &mut event
+            && *value == self.from
+        {
+            value.clone_from(&self.to);
+        }
+        self.position.advance(&event);
+        smallvec![event]
+    }
+}
+
+/// Drop every mapping entry whose key is `key`, value and all, at any depth
+pub struct DropKey {
+    key: String,
+    position: Position,
+    /// Collections open inside the value being dropped, if one is
+    dropping: Option<usize>,
+}
+
+impl DropKey {
+    #[must_use]
+    pub fn new(key: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            position: Position::default(),
+            dropping: None,
+        }
+    }
+}
+
+impl Transform for DropKey {
+    fn on_event(&mut self, event: Event) -> Events {
+        if let Some(depth) = self.dropping {
+            self.dropping = match event {
+                Event::SequenceStart(..) | Event::MappingStart(..) => Some(depth + 1),
+                Event::SequenceEnd | Event::MappingEnd if depth > 1 => Some(depth - 1),
+                Event::Scalar(..) | Event::Alias(_) if depth > 0 => Some(depth),
+                _ => None,
+            };
+            return SmallVec::new();
+        }
+        if self.position.at_key()
+            && let Event::Scalar(value, ..) = &event
+            && *value == self.key
+        {
+            // The mapping is back at a key once the value has gone by
+            self.dropping = Some(0);
+            return SmallVec::new();
+        }
+        self.position.advance(&event);
+        smallvec![event]
+    }
+}
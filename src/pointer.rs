@@ -0,0 +1,163 @@
+//! JSON Pointer (RFC 6901) style path handling shared by `Value` and `Yaml`
+//!
+//! Paths look like `/spec/template/containers/0/image`. The empty string
+//! addresses the whole document, `~1` escapes `/` and `~0` escapes `~`.
+
+use crate::value::{Number, Value};
+use crate::{Error, Yaml};
+use std::borrow::Cow;
+
+/// Split a pointer into unescaped reference tokens.
+///
+/// Returns an error for pointers that are neither empty nor start with `/`.
+pub(crate) fn tokens(pointer: &str) -> Result<Vec<Cow<'_, str>>, Error> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+    let Some(rest) = pointer.strip_prefix('/') else {
+        return Err(Error::Custom(format!(
+            "invalid path '{pointer}': must be empty or start with '/'"
+        )));
+    };
+    Ok(rest.split('/').map(unescape).collect())
+}
+
+/// Parse a reference token as a sequence index.
+///
+/// Leading zeros are rejected as in RFC 6901, so `01` never aliases `1`.
+#[must_use]
+pub(crate) fn index(token: &str) -> Option<usize> {
+    if token.is_empty() || (token.len() > 1 && token.starts_with('0')) {
+        return None;
+    }
+    if !token.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    token.parse().ok()
+}
+
+/// Escape a mapping key for use as a reference token.
+#[must_use]
+pub(crate) fn escape(token: &str) -> Cow<'_, str> {
+    if token.contains(['~', '/']) {
+        Cow::Owned(token.replace('~', "~0").replace('/', "~1"))
+    } else {
+        Cow::Borrowed(token)
+    }
+}
+
+/// Token standing for a mapping key that has no spelling in a path, such
+/// as a sequence or mapping used as a key
+pub(crate) const UNNAMED_KEY: &str = "?";
+
+/// The unescaped text a scalar mapping key contributes to a path: strings
+/// and reals as written, integers in decimal, `true`/`false` and `null`,
+/// looking through tags. `None` for any other key.
+#[must_use]
+pub(crate) fn key_text(key: &Yaml) -> Option<Cow<'_, str>> {
+    match key {
+        Yaml::String(s) | Yaml::Real(s) => Some(Cow::Borrowed(s)),
+        Yaml::Integer(i) => Some(Cow::Owned(i.to_string())),
+        Yaml::Boolean(b) => Some(Cow::Owned(b.to_string())),
+        Yaml::Null => Some(Cow::Borrowed("null")),
+        Yaml::Tagged(_, inner) => key_text(inner),
+        _ => None,
+    }
+}
+
+/// [`key_text`] for a [`Value`] key; floats are spelled as the emitter
+/// writes them, so `1.0` stays `1.0`
+#[must_use]
+pub(crate) fn value_key_text(key: &Value) -> Option<Cow<'_, str>> {
+    match key {
+        Value::String(s) => Some(Cow::Borrowed(s)),
+        Value::Number(Number::Integer(i)) => Some(Cow::Owned(i.to_string())),
+        Value::Number(Number::Unsigned(u)) => Some(Cow::Owned(u.to_string())),
+        Value::Number(Number::BigInteger(i)) => Some(Cow::Owned(i.to_string())),
+        Value::Number(Number::BigUnsigned(u)) => Some(Cow::Owned(u.to_string())),
+        Value::Number(Number::Float(f)) => {
+            Some(Cow::Owned(crate::ser::float_text(format!("{f:?}"), *f)))
+        }
+        Value::Bool(b) => Some(Cow::Owned(b.to_string())),
+        Value::Null => Some(Cow::Borrowed("null")),
+        Value::Tagged(tagged) => value_key_text(&tagged.value),
+        _ => None,
+    }
+}
+
+/// [`key_text`] as an owned string, [`UNNAMED_KEY`] when there is none
+#[must_use]
+pub(crate) fn key_name(key: &Yaml) -> String {
+    key_text(key).map_or_else(|| UNNAMED_KEY.to_owned(), Cow::into_owned)
+}
+
+/// The escaped reference token of a mapping key, [`UNNAMED_KEY`] when it
+/// has no [`key_text`]
+#[must_use]
+pub(crate) fn key_token(key: &Yaml) -> String {
+    key_text(key).map_or_else(|| UNNAMED_KEY.to_owned(), |text| escape(&text).into_owned())
+}
+
+/// [`key_token`] for a [`Value`] key
+#[must_use]
+pub(crate) fn value_key_token(key: &Value) -> String {
+    value_key_text(key).map_or_else(|| UNNAMED_KEY.to_owned(), |text| escape(&text).into_owned())
+}
+
+fn unescape(token: &str) -> Cow<'_, str> {
+    if token.contains('~') {
+        Cow::Owned(token.replace("~1", "/").replace("~0", "~"))
+    } else {
+        Cow::Borrowed(token)
+    }
+}
+
+/// A path with wildcards, matched against node paths, e.g. for
+/// [`SerializerConfig::redact_paths`](crate::SerializerConfig::redact_paths).
+///
+/// Written like a pointer: `*` stands for any one reference token and
+/// `**` for any number of them, so `/**/password` matches a `password` key
+/// at any depth.
+///
+/// ```rust
+/// use yyaml::PathPattern;
+///
+/// let pattern = PathPattern::new("/services/*/env/**/token").unwrap();
+/// assert!(pattern.matches("/services/web/env/token"));
+/// assert!(pattern.matches("/services/web/env/aws/prod/token"));
+/// assert!(!pattern.matches("/services/web/token"));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PathPattern {
+    tokens: Vec<String>,
+}
+
+impl PathPattern {
+    /// Parse `pattern`, which must be empty or start with `/`.
+    pub fn new(pattern: &str) -> Result<Self, Error> {
+        let tokens = tokens(pattern)?.into_iter().map(Cow::into_owned).collect();
+        Ok(Self { tokens })
+    }
+
+    /// Whether the node at `pointer` matches.
+    #[must_use]
+    pub fn matches(&self, pointer: &str) -> bool {
+        tokens(pointer).is_ok_and(|path| self.matches_tokens(&path))
+    }
+
+    /// Whether the node reached through the unescaped `path` matches.
+    pub(crate) fn matches_tokens<S: AsRef<str>>(&self, path: &[S]) -> bool {
+        fn step<S: AsRef<str>>(pattern: &[String], path: &[S]) -> bool {
+            match pattern.split_first() {
+                None => path.is_empty(),
+                Some((first, rest)) if first == "**" => {
+                    (0..=path.len()).any(|skip| step(rest, &path[skip..]))
+                }
+                Some((first, rest)) => path.split_first().is_some_and(|(token, tail)| {
+                    (first == "*" || first == token.as_ref()) && step(rest, tail)
+                }),
+            }
+        }
+        step(&self.tokens, path)
+    }
+}
@@ -0,0 +1,24 @@
+//! The supported, semver-stable surface of yyaml.
+//!
+//! Everything re-exported here follows semantic versioning: it is only
+//! removed or changed incompatibly in a major release, and
+//! `tests/test_public_api.rs` pins each signature so an accidental break
+//! fails CI. The `lexer`, `parser`, `scanner` and `semantic` modules are
+//! implementation details that may change in any release, apart from the
+//! semantic telemetry entry points re-exported here.
+//!
+//! ```rust
+//! use yyaml::prelude::*;
+//!
+//! let value: Value = from_str("answer: 42").unwrap();
+//! assert_eq!(to_string(&value).unwrap(), "---\nanswer: 42");
+//! ```
+
+pub use crate::{
+    CancellationToken, DeserializerConfig, EmitError, EmitResult, EmptyScalar, Error, Event,
+    EventReceiver, LoaderOptions, Mapping, MarkedEventReceiver, Marker, Number, ScanError,
+    SchemaMode, Sequence, TScalarStyle, Value, Yaml, YamlEmitter, YamlLoader, from_str,
+    from_str_with_config, from_value, parse_all, parse_first, to_string, to_value,
+};
+#[cfg(feature = "semantic")]
+pub use crate::{ProcessingSummary, analyze_str, analyze_yaml};
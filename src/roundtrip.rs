@@ -0,0 +1,124 @@
+//! Checking that a [`Value`] survives being emitted and parsed again
+//!
+//! [`roundtrip_check`] is the contract the emitter is held to: for any value,
+//! `from_str(to_string(value))` gives back the same value. Numbers must keep
+//! their kind, so an `f64` that comes back as an integer is a divergence even
+//! though the two compare equal with `==`.
+
+use crate::value::{Number, Value};
+use crate::pointer::value_key_token;
+use crate::{Error, from_str, to_string};
+
+/// Why a value did not survive [`roundtrip_check`]
+#[derive(Debug, thiserror::Error)]
+pub enum RoundTripDivergence {
+    /// The value could not be emitted at all
+    #[error("value could not be emitted: {0}")]
+    Emit(Error),
+    /// The emitted text did not parse back
+    #[error("emitted YAML did not parse: {error}\n{emitted}")]
+    Parse { emitted: String, error: Error },
+    /// The reparsed value differs at `path`, a JSON Pointer. `None` on either
+    /// side means the node is missing there.
+    #[error(
+        "round trip diverged at `{path}`: expected {}, found {}\n{emitted}",
+        describe(.expected.as_ref()),
+        describe(.actual.as_ref())
+    )]
+    Mismatch {
+        path: String,
+        expected: Option<Value>,
+        actual: Option<Value>,
+        emitted: String,
+    },
+}
+
+/// Emit `value`, parse the text back and compare, reporting the first node
+/// where the two differ.
+///
+/// ```rust
+/// use yyaml::{Value, roundtrip_check};
+///
+/// let value: Value = yyaml::from_str("name: app\nports: [80, 443]").unwrap();
+/// assert!(roundtrip_check(&value).is_ok());
+/// ```
+pub fn roundtrip_check(value: &Value) -> Result<(), RoundTripDivergence> {
+    let emitted = to_string(value).map_err(RoundTripDivergence::Emit)?;
+    let parsed: Value = match from_str(&emitted) {
+        Ok(parsed) => parsed,
+        Err(error) => return Err(RoundTripDivergence::Parse { emitted, error }),
+    };
+    match first_divergence(value, &parsed, String::new()) {
+        None => Ok(()),
+        Some((path, expected, actual)) => Err(RoundTripDivergence::Mismatch {
+            path,
+            expected,
+            actual,
+            emitted,
+        }),
+    }
+}
+
+type Divergence = (String, Option<Value>, Option<Value>);
+
+fn first_divergence(expected: &Value, actual: &Value, path: String) -> Option<Divergence> {
+    let mismatch = |path| Some((path, Some(expected.clone()), Some(actual.clone())));
+    match (expected, actual) {
+        (Value::Sequence(a), Value::Sequence(b)) => {
+            for i in 0..a.len().max(b.len()) {
+                let child = format!("{path}/{i}");
+                match (a.get(i), b.get(i)) {
+                    (Some(x), Some(y)) => {
+                        if let Some(found) = first_divergence(x, y, child) {
+                            return Some(found);
+                        }
+                    }
+                    (x, y) => return Some((child, x.cloned(), y.cloned())),
+                }
+            }
+            None
+        }
+        (Value::Mapping(a), Value::Mapping(b)) => {
+            for (key, x) in a {
+                let child = format!("{path}/{}", value_key_token(key));
+                match b.get(key) {
+                    Some(y) => {
+                        if let Some(found) = first_divergence(x, y, child) {
+                            return Some(found);
+                        }
+                    }
+                    None => return Some((child, Some(x.clone()), None)),
+                }
+            }
+            b.iter()
+                .find(|(key, _)| !a.contains_key(*key))
+                .map(|(key, y)| (format!("{path}/{}", value_key_token(key)), None, Some(y.clone())))
+        }
+        (Value::Tagged(a), Value::Tagged(b)) => {
+            if a.tag != b.tag {
+                return mismatch(path);
+            }
+            first_divergence(&a.value, &b.value, path)
+        }
+        (Value::Number(a), Value::Number(b)) => {
+            let same = match (a, b) {
+                (Number::Integer(a), Number::Integer(b)) => a == b,
+                (Number::Unsigned(a), Number::Unsigned(b)) => a == b,
+                (Number::BigInteger(a), Number::BigInteger(b)) => a == b,
+                (Number::BigUnsigned(a), Number::BigUnsigned(b)) => a == b,
+                (Number::Float(a), Number::Float(b)) => a == b || (a.is_nan() && b.is_nan()),
+                _ => false,
+            };
+            if same { None } else { mismatch(path) }
+        }
+        _ if expected == actual => None,
+        _ => mismatch(path),
+    }
+}
+
+fn describe(value: Option<&Value>) -> String {
+    match value {
+        None => "nothing".to_string(),
+        Some(value) => format!("{value:?}"),
+    }
+}
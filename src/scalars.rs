@@ -0,0 +1,101 @@
+//! Helpers for writing scalars by hand
+//!
+//! [`refold`] wraps text into the body of a folded (`>`) block scalar, the
+//! same way [`YamlEmitter`](crate::YamlEmitter) does when `fold_width` is
+//! set, so tools that edit folded scalars can re-wrap them before writing
+//! them back.
+
+pub use crate::parser::ChompingMode;
+
+/// The body of a folded block scalar whose value is `text`, with lines
+/// wrapped at `width` characters where the folding rules allow it.
+///
+/// A line is only broken at a single space between two non-space
+/// characters, since that space is what folding restores; words longer than
+/// `width` stay whole. Lines of `text` that start with white space are
+/// more-indented and are written as they are, with no folding around them.
+/// Line breaks between two folded lines are doubled so they survive
+/// folding.
+///
+/// Every line of the result ends with a line break and has no indentation;
+/// indent each non-empty line as the scalar requires. Trailing line breaks
+/// of `text` are only written with [`ChompingMode::Keep`], the others drop
+/// them as a parser would. If the first line of `text` starts with white
+/// space, the header needs an explicit indentation indicator.
+///
+/// ```rust
+/// use yyaml::scalars::{ChompingMode, refold};
+///
+/// let text = "a long line of words\nnext paragraph\n";
+/// assert_eq!(
+///     refold(text, 10, ChompingMode::Clip),
+///     "a long\nline of\nwords\n\nnext\nparagraph\n"
+/// );
+/// assert_eq!(refold("  code\nmore", 80, ChompingMode::Strip), "  code\nmore\n");
+/// ```
+#[must_use]
+pub fn refold(text: &str, width: usize, chomping: ChompingMode) -> String {
+    let content = text.trim_end_matches('\n');
+    let trailing = text.len() - content.len();
+    let mut out = String::with_capacity(text.len() + text.len() / width.max(1) + trailing);
+    let body = content.trim_start_matches('\n');
+    // Leading empty lines are never folded
+    out.extend(std::iter::repeat_n('\n', content.len() - body.len()));
+
+    // Line breaks since the last text line, and whether it was more indented
+    let mut breaks = 0;
+    let mut last_spaced: Option<bool> = None;
+    for line in body.split('\n').filter(|_| !body.is_empty()) {
+        if line.is_empty() {
+            breaks += 1;
+            continue;
+        }
+        let spaced = line.starts_with([' ', '\t']);
+        if let Some(last_spaced) = last_spaced {
+            let folds = !last_spaced && !spaced;
+            out.extend(std::iter::repeat_n('\n', breaks + 1 + usize::from(folds)));
+        }
+        breaks = 0;
+        last_spaced = Some(spaced);
+        if spaced {
+            out.push_str(line);
+        } else {
+            wrap(&mut out, line, width);
+        }
+    }
+
+    if last_spaced.is_some() {
+        out.push('\n');
+    }
+    if chomping == ChompingMode::Keep {
+        let written = usize::from(last_spaced.is_some());
+        out.extend(std::iter::repeat_n('\n', trailing.saturating_sub(written)));
+    }
+    out
+}
+
+/// Write `line`, breaking it at single spaces between words so no line is
+/// longer than `width` where that can be helped
+fn wrap(out: &mut String, line: &str, width: usize) {
+    let mut pieces = line.split(' ');
+    let mut previous = pieces.next().unwrap_or_default();
+    out.push_str(previous);
+    let mut column = previous.chars().count();
+    for piece in pieces {
+        let len = piece.chars().count();
+        // A break next to other white space would not fold back to one space
+        let breakable = !previous.is_empty()
+            && !piece.is_empty()
+            && !previous.ends_with('\t')
+            && !piece.starts_with('\t');
+        if breakable && column + 1 + len > width {
+            out.push('\n');
+            column = len;
+        } else {
+            out.push(' ');
+            column += 1 + len;
+        }
+        out.push_str(piece);
+        previous = piece;
+    }
+}
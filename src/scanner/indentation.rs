@@ -91,7 +91,7 @@ impl IndentationTracker {
         // Validate indentation increase
         if column <= self.current_base {
             return Err(ScanError::new(
-                Marker::at(0, 1, column as usize),
+                Marker::from_parts(0, 0, 1, column as usize),
                 "indentation must increase for nested blocks",
             ));
         }
@@ -99,7 +99,7 @@ impl IndentationTracker {
         // Check maximum nesting depth
         if self.levels.len() >= 128 {
             return Err(ScanError::new(
-                Marker::at(0, 1, column as usize),
+                Marker::from_parts(0, 0, 1, column as usize),
                 "maximum nesting depth exceeded (128 levels)",
             ));
         }
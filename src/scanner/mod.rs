@@ -14,18 +14,60 @@ pub mod token;
 pub mod utils;
 
 pub use state::{QuotedContext, ScannerConfig, ScannerState};
-pub use token::{Token, TokenProducer, TokenStream};
+use state::StrChars;
+pub use token::{ScannedTokens, Token, TokenProducer, TokenStream};
+pub(crate) use token::Lexeme;
+use token::ScalarText;
+
+use std::ops::Range;
 
 use crate::error::{Marker, ScanError};
+use crate::events::{TScalarStyle, TokenType};
+use crate::parser::indentation::{TAB_BEFORE_COMPACT, UNDER_INDENTED_FLOW};
 
 /// High-performance YAML scanner with zero-allocation tokenization
 ///
 /// Provides streaming tokenization of YAML input with complete error handling,
 /// position tracking, and optimized performance for production use.
+#[derive(Clone)]
 pub struct Scanner<T: Iterator<Item = char>> {
     state: ScannerState<T>,
     token_producer: TokenProducer,
     config: ScannerConfig,
+    tokens_scanned: usize,
+    /// Whether the last token was a JSON-like node (quoted scalar or flow
+    /// collection), after which a flow `:` needs no trailing space
+    after_json_node: bool,
+    /// Line of the last `---`, where a plain scalar is root content
+    document_start_line: Option<usize>,
+    /// Start and length of the scalar just produced, checked against
+    /// `max_key_len` if a `:` follows it
+    last_scalar: Option<(Marker, usize)>,
+    /// Line and column of the first token on the most recent line, the
+    /// indentation of the node a block scalar on that line belongs to
+    line_indent: (usize, usize),
+    /// Column of the innermost open block collection, which lines of flow
+    /// nodes must be right of; set by the parser
+    indent_floor: Option<usize>,
+    /// Whether the last token was `-`, `?` or `:` in block context
+    after_block_indicator: bool,
+    /// Line on which a tab followed a block indicator, so a `:` there makes
+    /// a compact mapping
+    tab_after_indicator_line: Option<usize>,
+    /// Where the cached token ends
+    cached_end: Marker,
+    /// Where the last consumed token ends
+    last_end: Marker,
+    /// Source range of the text of the scalar last scanned, when its token
+    /// was left without it
+    scanned_span: Option<Range<usize>>,
+}
+
+impl<'input> Scanner<StrChars<'input>> {
+    /// Scanner over `text` that slices its scalars from it
+    pub(crate) fn over_str(text: impl Into<StrChars<'input>>) -> Self {
+        Self::with_state(ScannerState::over_str(text), ScannerConfig::default())
+    }
 }
 
 impl<T: Iterator<Item = char>> Scanner<T> {
@@ -38,10 +80,25 @@ impl<T: Iterator<Item = char>> Scanner<T> {
     /// Create scanner with custom configuration
     #[inline]
     pub fn with_config(source: T, config: ScannerConfig) -> Self {
+        Self::with_state(ScannerState::new(source), config)
+    }
+
+    fn with_state(state: ScannerState<T>, config: ScannerConfig) -> Self {
         Self {
-            state: ScannerState::new(source),
+            state,
             token_producer: TokenProducer::new(),
             config,
+            tokens_scanned: 0,
+            after_json_node: false,
+            document_start_line: None,
+            last_scalar: None,
+            line_indent: (0, 0),
+            indent_floor: None,
+            after_block_indicator: false,
+            tab_after_indicator_line: None,
+            cached_end: Marker::default(),
+            last_end: Marker::default(),
+            scanned_span: None,
         }
     }
 
@@ -54,39 +111,92 @@ impl<T: Iterator<Item = char>> Scanner<T> {
     /// Peek at next token without consuming
     #[inline]
     pub fn peek_token(&mut self) -> Result<Token, ScanError> {
+        let lexeme = self.peek_lexeme()?;
+        Ok(lexeme.into_token(self.state.source_text()))
+    }
+
+    /// [`peek_token`](Self::peek_token), leaving a scalar sliced from the
+    /// source as its range there
+    pub(crate) fn peek_lexeme(&mut self) -> Result<Lexeme, ScanError> {
         if !self.state.has_cached_token() {
+            self.scanned_span = None;
             let token = self.fetch_next_token()?;
+            self.tokens_scanned += 1;
+            self.cached_end = self.mark();
             self.state.cache_token(token);
         }
-        self.state
+        let token = self
+            .state
             .peek_cached_token()
             .cloned()
-            .ok_or_else(|| ScanError::new(self.mark(), "internal error: no cached token"))
+            .ok_or_else(|| ScanError::new(self.mark(), "internal error: no cached token"))?;
+        Ok(Lexeme {
+            token,
+            span: self.scanned_span.clone(),
+        })
     }
 
     /// Fetch next token, consuming it
     #[inline]
     pub fn fetch_token(&mut self) -> Token {
+        let lexeme = self.fetch_lexeme();
+        lexeme.into_token(self.state.source_text())
+    }
+
+    /// [`fetch_token`](Self::fetch_token), leaving a scalar sliced from the
+    /// source as its range there
+    pub(crate) fn fetch_lexeme(&mut self) -> Lexeme {
         if let Some(token) = self.state.take_cached_token() {
-            token
+            self.last_end = self.cached_end;
+            let span = self.scanned_span.take();
+            Lexeme { token, span }
         } else {
             // If no cached token, fetch one - this follows original API design
             // where peek_token() should be called first in normal usage
-            match self.fetch_next_token() {
-                Ok(token) => token,
+            self.scanned_span = None;
+            let token = match self.fetch_next_token() {
+                Ok(token) => {
+                    self.tokens_scanned += 1;
+                    self.last_end = self.mark();
+                    token
+                }
                 Err(_) => {
                     // Create a no-token as fallback to avoid panic
                     // This maintains API compatibility while being safer than unwrap
                     self.token_producer.no_token(self.mark())
                 }
-            }
+            };
+            let span = self.scanned_span.take();
+            Lexeme { token, span }
+        }
+    }
+
+    /// Text of the scalar `lexeme`, sliced from the source if the scanner
+    /// left it there
+    pub(crate) fn scalar_text<'a>(&'a self, lexeme: &'a Lexeme) -> &'a str {
+        match (&lexeme.span, &lexeme.1) {
+            (Some(span), _) => &self.state.source_text().unwrap_or_default()[span.clone()],
+            (None, TokenType::Scalar(_, text, _)) => text,
+            (None, _) => "",
         }
     }
 
     /// Skip current token without returning it
     #[inline]
     pub fn skip(&mut self) {
+        if self.state.has_cached_token() {
+            self.last_end = self.cached_end;
+        }
         self.state.clear_cached_token();
+        self.scanned_span = None;
+    }
+
+    /// Position just past the last token consumed with
+    /// [`fetch_token`](Self::fetch_token) or [`skip`](Self::skip); a scalar
+    /// may be followed by the white space scanned after it
+    #[inline]
+    pub(crate) const fn last_token_end(&self) -> Marker {
+        self.last_end
     }
 
     /// Check if stream has started
@@ -101,17 +211,80 @@ impl<T: Iterator<Item = char>> Scanner<T> {
         self.state.stream_ended()
     }
 
+    /// Number of flow collections open at the current position
+    #[inline]
+    pub const fn flow_level(&self) -> usize {
+        self.state.flow_level()
+    }
+
+    /// Position and bracket of the innermost flow collection still open
+    #[inline]
+    pub fn flow_opener(&self) -> Option<(Marker, char)> {
+        self.state.flow_opener()
+    }
+
+    pub(crate) fn close_flow_through(&mut self, closer: char) -> Option<Vec<(Marker, char)>> {
+        self.state.close_flow_through(closer)
+    }
+
+    pub(crate) fn take_flow_openers(&mut self) -> Vec<(Marker, char)> {
+        self.state.take_flow_openers()
+    }
+
     /// Get scanner configuration
     #[inline]
     pub const fn config(&self) -> &ScannerConfig {
         &self.config
     }
 
+    #[inline]
+    pub(crate) const fn config_mut(&mut self) -> &mut ScannerConfig {
+        &mut self.config
+    }
+
+    /// Set the column of the innermost open block collection, for
+    /// [`IndentationRules::reject_under_indented_flow`](crate::IndentationRules)
+    #[inline]
+    pub(crate) const fn set_indent_floor(&mut self, floor: Option<usize>) {
+        self.indent_floor = floor;
+    }
+
+    /// The column continuation lines must be right of, if that rule applies
+    fn continuation_floor(&self) -> Option<usize> {
+        self.indent_floor
+            .filter(|_| self.config.indentation.reject_under_indented_flow)
+    }
+
+    /// Whether the blanks before the next token on this line include a tab
+    fn tab_before_content(&mut self) -> bool {
+        let mut tab = false;
+        for i in 0.. {
+            match self.state.peek_char_at(i) {
+                Some(' ') => {}
+                Some('\t') => tab = true,
+                Some(c) => return tab && !matches!(c, '\n' | '\r' | '#'),
+                None => return false,
+            }
+        }
+        false
+    }
+
+    /// Number of tokens produced since construction or the last reset
+    #[inline]
+    pub const fn tokens_scanned(&self) -> usize {
+        self.tokens_scanned
+    }
+
     /// Reset scanner state for reuse
     #[inline]
     pub fn reset(&mut self, source: T) {
         self.state = ScannerState::new(source);
         self.token_producer.reset();
+        self.tokens_scanned = 0;
+        self.after_json_node = false;
+        self.document_start_line = None;
+        self.last_scalar = None;
+        self.scanned_span = None;
     }
 
     // Character-level access methods for state machine separation functions
@@ -166,8 +339,12 @@ impl<T: Iterator<Item = char>> Scanner<T> {
             return Ok(self.token_producer.stream_start_token(self.mark()));
         }
 
+        let tab_after_indicator = self.after_block_indicator
+            && self.config.indentation.reject_tab_before_compact
+            && self.tab_before_content();
+
         // Skip whitespace and comments efficiently
-        utils::skip_whitespace_and_comments(&mut self.state)?;
+        utils::skip_separation(&mut self.state, self.config.allow_separating_tabs)?;
 
         // Handle BOM at document/stream boundaries per YAML 1.2 spec
         self.handle_bom_at_boundary()?;
@@ -183,7 +360,15 @@ impl<T: Iterator<Item = char>> Scanner<T> {
 
         // Peek at next character for dispatching
         let start_mark = self.mark();
-        match self.state.peek_char()? {
+        if self.line_indent.0 != start_mark.line {
+            self.line_indent = (start_mark.line, start_mark.col);
+            if self.state.in_flow_context()
+                && self.continuation_floor().is_some_and(|floor| start_mark.col <= floor)
+            {
+                return Err(ScanError::new(start_mark, UNDER_INDENTED_FLOW));
+            }
+        }
+        let token = match self.state.peek_char()? {
             '-' => self.scan_dash_token(start_mark),
             '.' => self.scan_dot_token(start_mark),
             '[' => self.scan_flow_sequence_start(start_mark),
@@ -191,7 +376,7 @@ impl<T: Iterator<Item = char>> Scanner<T> {
             '{' => self.scan_flow_mapping_start(start_mark),
             '}' => self.scan_flow_mapping_end(start_mark),
             ',' => self.scan_flow_entry(start_mark),
-            ':' => self.scan_value_token(start_mark),
+            ':' if self.is_value_indicator() => self.scan_value_token(start_mark),
             '?' => self.scan_key_token(start_mark),
             '&' => self.scan_anchor_token(start_mark),
             '*' => self.scan_alias_token(start_mark),
@@ -201,8 +386,76 @@ impl<T: Iterator<Item = char>> Scanner<T> {
             '\'' => self.scan_single_quoted_scalar(start_mark),
             '"' => self.scan_double_quoted_scalar(start_mark),
             '%' => self.scan_directive_token(start_mark),
-            '#' => self.scan_comment_and_retry(start_mark),
+            '#' => return self.scan_comment_and_retry(start_mark),
             _ => self.scan_plain_scalar(start_mark),
+        }?;
+        self.after_json_node = matches!(
+            token.1,
+            TokenType::Scalar(TScalarStyle::SingleQuoted | TScalarStyle::DoubleQuoted, ..)
+                | TokenType::FlowSequenceEnd
+                | TokenType::FlowMappingEnd
+        );
+        self.check_tab_before_compact(&token, tab_after_indicator)?;
+        match &token.1 {
+            TokenType::Scalar(_, content, _) => {
+                let len = self.scanned_span.as_ref().map_or(content.len(), Range::len);
+                self.last_scalar = Some((token.0, len));
+            }
+            TokenType::Value => {
+                if let Some((mark, len)) = self.last_scalar.take()
+                    && len > self.config.max_key_len
+                {
+                    let info = format!(
+                        "mapping key is longer than the limit of {} bytes",
+                        self.config.max_key_len
+                    );
+                    return Err(ScanError::new(mark, &info));
+                }
+            }
+            _ => self.last_scalar = None,
+        }
+        Ok(token)
+    }
+
+    /// Enforce [`IndentationRules::reject_tab_before_compact`](crate::IndentationRules)
+    /// on `token`, which a tab separated from a block indicator if
+    /// `tab_after_indicator` is set
+    fn check_tab_before_compact(
+        &mut self,
+        token: &Token,
+        tab_after_indicator: bool,
+    ) -> Result<(), ScanError> {
+        let in_block = !self.state.in_flow_context();
+        let indicator = in_block && matches!(token.1, TokenType::BlockEntry | TokenType::Key);
+        if self.tab_after_indicator_line.is_some_and(|line| line != token.0.line) {
+            self.tab_after_indicator_line = None;
+        }
+        if tab_after_indicator {
+            if matches!(token.1, TokenType::BlockEntry) {
+                return Err(ScanError::new(token.0, TAB_BEFORE_COMPACT));
+            }
+            self.tab_after_indicator_line = Some(token.0.line);
+        } else if in_block
+            && matches!(token.1, TokenType::Value)
+            && self.tab_after_indicator_line == Some(token.0.line)
+        {
+            return Err(ScanError::new(token.0, TAB_BEFORE_COMPACT));
+        }
+        self.after_block_indicator = indicator || (in_block && matches!(token.1, TokenType::Value));
+        Ok(())
+    }
+
+    /// Whether the `:` at the cursor is a value indicator rather than the
+    /// start of a plain scalar such as `:a` or `::1`.
+    ///
+    /// It must be followed by whitespace, or in flow context by a flow
+    /// indicator or directly follow a JSON-like key as in `{"a":1}`.
+    fn is_value_indicator(&mut self) -> bool {
+        let in_flow = self.state.in_flow_context();
+        match self.state.peek_char_at(1) {
+            None | Some(' ' | '\t' | '\n' | '\r') => true,
+            Some(',' | '[' | ']' | '{' | '}') => in_flow,
+            Some(_) => in_flow && self.after_json_node,
         }
     }
 
@@ -212,6 +465,7 @@ impl<T: Iterator<Item = char>> Scanner<T> {
     fn scan_dash_token(&mut self, start_mark: Marker) -> Result<Token, ScanError> {
         if self.state.check_document_start()? {
             self.state.consume_chars(3)?;
+            self.document_start_line = Some(start_mark.line);
             Ok(self.token_producer.document_start_token(start_mark))
         } else if self.state.check_block_entry()? {
             self.state.consume_char()?;
@@ -225,6 +479,17 @@ impl<T: Iterator<Item = char>> Scanner<T> {
     fn scan_dot_token(&mut self, start_mark: Marker) -> Result<Token, ScanError> {
         if self.state.check_document_end()? {
             self.state.consume_chars(3)?;
+            // Only a comment may share the line with `...`
+            self.state.skip_while(|c| matches!(c, ' ' | '\t'));
+            match self.state.peek_char() {
+                Ok('#' | '\n' | '\r') | Err(_) => {}
+                Ok(ch) => {
+                    return Err(ScanError::new(
+                        self.state.mark(),
+                        &format!("unexpected character '{ch}' after document end marker '...'"),
+                    ));
+                }
+            }
             Ok(self.token_producer.document_end_token(start_mark))
         } else {
             self.scan_plain_scalar(start_mark)
@@ -234,28 +499,28 @@ impl<T: Iterator<Item = char>> Scanner<T> {
     #[inline]
     fn scan_flow_sequence_start(&mut self, start_mark: Marker) -> Result<Token, ScanError> {
         self.state.consume_char()?;
-        self.state.enter_flow_context();
+        self.state.enter_flow_context(start_mark, '[');
         Ok(self.token_producer.flow_sequence_start_token(start_mark))
     }
 
     #[inline]
     fn scan_flow_sequence_end(&mut self, start_mark: Marker) -> Result<Token, ScanError> {
         self.state.consume_char()?;
-        self.state.exit_flow_context()?;
+        self.state.exit_flow_context(start_mark, ']')?;
         Ok(self.token_producer.flow_sequence_end_token(start_mark))
     }
 
     #[inline]
     fn scan_flow_mapping_start(&mut self, start_mark: Marker) -> Result<Token, ScanError> {
         self.state.consume_char()?;
-        self.state.enter_flow_context();
+        self.state.enter_flow_context(start_mark, '{');
         Ok(self.token_producer.flow_mapping_start_token(start_mark))
     }
 
     #[inline]
     fn scan_flow_mapping_end(&mut self, start_mark: Marker) -> Result<Token, ScanError> {
         self.state.consume_char()?;
-        self.state.exit_flow_context()?;
+        self.state.exit_flow_context(start_mark, '}')?;
         Ok(self.token_producer.flow_mapping_end_token(start_mark))
     }
 
@@ -298,26 +563,43 @@ impl<T: Iterator<Item = char>> Scanner<T> {
         Ok(self.token_producer.tag_token(start_mark, handle, suffix))
     }
 
+    /// Indentation of the node owning a block scalar that starts at `mark`:
+    /// the first token on its line, unless the scalar is that token or
+    /// follows `---`, where the parent is unknown
+    fn block_parent_indent(&self, mark: Marker) -> Option<usize> {
+        let (line, column) = self.line_indent;
+        (line == mark.line && column < mark.col && self.document_start_line != Some(line))
+            .then_some(column)
+    }
+
     #[inline]
     fn scan_literal_block_scalar(&mut self, start_mark: Marker) -> Result<Token, ScanError> {
         self.state.consume_char()?; // consume '|'
-        let content = scalars::scan_block_scalar(&mut self.state, true)?;
+        let parent = self.block_parent_indent(start_mark);
+        let (content, header) =
+            scalars::scan_block_scalar(&mut self.state, &self.config, start_mark, true, parent)?;
         Ok(self
             .token_producer
-            .literal_scalar_token(start_mark, content))
+            .literal_scalar_token(start_mark, content, header))
     }
 
     #[inline]
     fn scan_folded_block_scalar(&mut self, start_mark: Marker) -> Result<Token, ScanError> {
         self.state.consume_char()?; // consume '>'
-        let content = scalars::scan_block_scalar(&mut self.state, false)?;
-        Ok(self.token_producer.folded_scalar_token(start_mark, content))
+        let parent = self.block_parent_indent(start_mark);
+        let (content, header) =
+            scalars::scan_block_scalar(&mut self.state, &self.config, start_mark, false, parent)?;
+        Ok(self
+            .token_producer
+            .folded_scalar_token(start_mark, content, header))
     }
 
     #[inline]
     fn scan_single_quoted_scalar(&mut self, start_mark: Marker) -> Result<Token, ScanError> {
         self.state.consume_char()?; // consume '\''
-        let content = scalars::scan_single_quoted(&mut self.state)?;
+        let floor = self.continuation_floor();
+        let text = scalars::scan_single_quoted_text(&mut self.state, &self.config, start_mark, floor)?;
+        let content = self.scalar_content(text);
         Ok(self
             .token_producer
             .single_quoted_scalar_token(start_mark, content))
@@ -326,7 +608,9 @@ impl<T: Iterator<Item = char>> Scanner<T> {
     #[inline]
     fn scan_double_quoted_scalar(&mut self, start_mark: Marker) -> Result<Token, ScanError> {
         self.state.consume_char()?; // consume '"'
-        let content = scalars::scan_double_quoted(&mut self.state)?;
+        let floor = self.continuation_floor();
+        let text = scalars::scan_double_quoted_text(&mut self.state, &self.config, start_mark, floor)?;
+        let content = self.scalar_content(text);
         Ok(self
             .token_producer
             .double_quoted_scalar_token(start_mark, content))
@@ -341,10 +625,25 @@ impl<T: Iterator<Item = char>> Scanner<T> {
 
     #[inline]
     fn scan_plain_scalar(&mut self, start_mark: Marker) -> Result<Token, ScanError> {
-        let content = scalars::scan_plain_scalar(&mut self.state, &self.config)?;
+        let root = self.document_start_line == Some(start_mark.line);
+        let floor = self.continuation_floor();
+        let text = scalars::scan_plain_text(&mut self.state, &self.config, root, floor)?;
+        let content = self.scalar_content(text);
         Ok(self.token_producer.plain_scalar_token(start_mark, content))
     }
 
+    /// The text for a scalar's token: empty if it is a range of the source,
+    /// which is kept for the parser to slice instead
+    fn scalar_content(&mut self, text: ScalarText) -> String {
+        match text {
+            ScalarText::Source(range) => {
+                self.scanned_span = Some(range);
+                String::new()
+            }
+            ScalarText::Owned(text) => text,
+        }
+    }
+
     #[inline]
     fn scan_comment_and_retry(&mut self, _start_mark: Marker) -> Result<Token, ScanError> {
         utils::skip_comment_line(&mut self.state)?;
@@ -437,6 +736,10 @@ impl<T: Iterator<Item = char>> Scanner<T> {
         context: &mut crate::parser::grammar::ParametricContext,
         n: i32,
     ) -> Result<(), ScanError> {
+        // A peeked token has already consumed the separation before it
+        if self.state.has_cached_token() {
+            return Ok(());
+        }
         crate::parser::structural_productions::StructuralProductions::process_separation(
             &mut self.state,
             context,
@@ -459,6 +762,9 @@ impl<T: Iterator<Item = char>> Scanner<T> {
 
     /// Skip comments using existing utilities
     pub fn skip_structural_comments(&mut self) -> Result<Vec<String>, ScanError> {
+        if self.state.has_cached_token() {
+            return Ok(Vec::new());
+        }
         crate::parser::structural_productions::StructuralProductions::skip_comment_lines(
             &mut self.state,
         )
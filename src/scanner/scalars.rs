@@ -2,26 +2,56 @@
 //!
 //! This module provides efficient scanning of all YAML scalar types including
 //! plain, quoted, and block scalars with proper escape handling.
+//!
+//! Over a `&str` source, a plain or quoted scalar is kept as a range of the
+//! text while it is one unbroken run of it, rather than built up a character
+//! at a time, and the parser slices it from the text instead of a copy.
+
+use std::ops::Range;
 
-use crate::error::ScanError;
+use crate::error::{Marker, ScanError};
+use crate::events::BlockScalarHeader;
 use crate::parser::character_productions::CharacterProductions;
 use crate::parser::grammar::ChompingMode;
+use crate::parser::indentation::UNDER_INDENTED_FLOW;
 use crate::scanner::ScannerConfig;
 use crate::scanner::state::ScannerState;
+use crate::scanner::token::ScalarText;
+use crate::scanner::utils;
 
-/// Scan plain scalar with efficient character classification
+/// Scan plain scalar with efficient character classification; in flow
+/// context, continuation lines must be right of `floor`
 #[inline]
 pub fn scan_plain_scalar<T: Iterator<Item = char>>(
     state: &mut ScannerState<T>,
-    _config: &ScannerConfig,
+    config: &ScannerConfig,
+    root: bool,
+    floor: Option<usize>,
 ) -> Result<String, ScanError> {
-    let mut result = String::with_capacity(32);
-    let mut spaces = String::new();
+    let text = scan_plain_text(state, config, root, floor)?;
+    Ok(text.into_string(state.source_text()))
+}
+
+/// [`scan_plain_scalar`], leaving the scalar as a range of the source text when
+/// the state has one and the scalar is a single line of it
+pub(crate) fn scan_plain_text<T: Iterator<Item = char>>(
+    state: &mut ScannerState<T>,
+    config: &ScannerConfig,
+    root: bool,
+    floor: Option<usize>,
+) -> Result<ScalarText, ScanError> {
+    let mut result = Text::new(state);
+    // Spaces seen since the last content, written out only if more follows
+    let mut spaces = 0;
+    let start = state.mark();
     let start_col = state.column();
+    let root = root || start_col == 0;
     let in_flow = state.in_flow_context();
     log::debug!("scan_plain_scalar: in_flow={}, flow_level={}, start_col={}", in_flow, state.flow_level(), start_col);
 
     while let Ok(ch) = state.peek_char() {
+        check_scalar_len(result.len() + spaces, config, start)?;
+
         // Flow context indicators
         if in_flow && matches!(ch, ',' | '[' | ']' | '{' | '}') {
             log::debug!("scan_plain_scalar: stopping at flow indicator '{}' in flow context", ch);
@@ -44,14 +74,15 @@ pub fn scan_plain_scalar<T: Iterator<Item = char>>(
                 }
             }
             // '#' preceded by space is comment
-            if ch == '#' && !spaces.is_empty() {
+            if ch == '#' && spaces > 0 {
                 break;
             }
         }
 
         // Whitespace handling
         if ch == ' ' {
-            spaces.push(state.consume_char()?);
+            state.consume_char()?;
+            spaces += 1;
             continue;
         }
         if ch == '\t' {
@@ -63,49 +94,79 @@ pub fn scan_plain_scalar<T: Iterator<Item = char>>(
 
         // Line breaks
         if matches!(ch, '\n' | '\r') {
-            // Check if next line would change indentation in block context
+            // In block context the scalar continues on a more indented line,
+            // or at column 0 when it is the document's root node
             if !in_flow {
-                let _current_mark = state.mark();
-                state.consume_char()?; // consume newline
-
-                // Skip any additional newlines
-                while matches!(state.peek_char(), Ok('\n') | Ok('\r')) {
-                    state.consume_char()?;
-                }
-
-                // Check indentation of next line
+                let mut breaks = 0;
                 let mut next_col = 0;
-                let mut temp_chars = Vec::new();
                 while let Ok(ch) = state.peek_char() {
-                    if ch == ' ' {
-                        temp_chars.push(state.consume_char()?);
-                        next_col += 1;
-                    } else if ch == '\t' {
-                        return Err(ScanError::new(
-                            state.mark(),
-                            "tabs are not allowed in YAML, use spaces for indentation",
-                        ));
-                    } else {
-                        break;
+                    match ch {
+                        '\n' | '\r' => {
+                            utils::consume_line_break(state)?;
+                            breaks += 1;
+                            next_col = 0;
+                        }
+                        ' ' => {
+                            state.consume_char()?;
+                            next_col += 1;
+                        }
+                        '\t' => {
+                            return Err(ScanError::new(
+                                state.mark(),
+                                "tabs are not allowed in YAML, use spaces for indentation",
+                            ));
+                        }
+                        _ => break,
                     }
                 }
 
-                // Put back the indentation chars if we continue
-                if next_col > 0 && next_col >= start_col {
-                    // Continue scalar
-                    if !result.is_empty() && !spaces.is_empty() {
-                        result.push_str(&spaces);
-                    }
-                    result.push(' '); // fold newline to space
-                    spaces.clear();
+                let continues = if next_col > 0 {
+                    root || next_col >= start_col
                 } else {
-                    // End scalar due to dedent
+                    root && !ends_root_scalar(state)?
+                };
+                if !continues || matches!(state.peek_char(), Ok('#') | Err(_)) {
                     break;
                 }
+                spaces = 0;
+                result.fold(state, breaks);
                 continue;
-            } else {
-                break; // newline ends scalar in flow
             }
+
+            // In flow context the scalar continues on the next line unless
+            // that line starts with an indicator (s-flow-line-prefix)
+            let mut breaks = 0;
+            while let Ok(ch) = state.peek_char() {
+                match ch {
+                    '\n' | '\r' => {
+                        utils::consume_line_break(state)?;
+                        breaks += 1;
+                    }
+                    ' ' | '\t' => {
+                        state.consume_char()?;
+                    }
+                    _ => break,
+                }
+            }
+            if breaks > 0
+                && !state.is_done()
+                && floor.is_some_and(|floor| state.mark().col <= floor)
+            {
+                return Err(ScanError::new(state.mark(), UNDER_INDENTED_FLOW));
+            }
+            let ends_scalar = match state.peek_char() {
+                Ok(',' | '[' | ']' | '{' | '}' | '#') | Err(_) => true,
+                Ok(':') => state
+                    .peek_char_at(1)
+                    .is_none_or(|next| matches!(next, ' ' | '\t' | '\n' | '\r' | ',' | '[' | ']' | '{' | '}')),
+                Ok(_) => state.is_done(),
+            };
+            if ends_scalar {
+                break;
+            }
+            spaces = 0;
+            result.fold(state, breaks);
+            continue;
         }
 
         // Document markers
@@ -115,69 +176,206 @@ pub fn scan_plain_scalar<T: Iterator<Item = char>>(
             break;
         }
 
-        // Regular character
-        if !spaces.is_empty() {
-            result.push_str(&spaces);
-            spaces.clear();
-        }
-        result.push(state.consume_char()?);
+        // Regular character, then the run of content after it
+        result.take_spaces(spaces);
+        spaces = 0;
+        result.take_run(state, config.max_scalar_len, |c| {
+            !matches!(
+                c,
+                ' ' | '\t' | '\n' | '\r' | ':' | '#' | ',' | '[' | ']' | '{' | '}' | '-' | '.'
+            )
+        })?;
     }
+    check_scalar_len(result.len(), config, start)?;
 
     // Trim trailing spaces
-    result.truncate(result.trim_end().len());
+    result.trim_end();
 
-    if result.is_empty() {
+    if result.len() == 0 {
         return Err(ScanError::new(state.mark(), "empty plain scalar"));
     }
 
-    Ok(result)
+    Ok(result.finish())
+}
+
+/// A scalar's content while it is scanned. Over a source text it is a byte
+/// range of that text for as long as the content is one unbroken run of
+/// it, and is copied out only once a fold or an escape makes it differ.
+struct Text {
+    source: Option<Range<usize>>,
+    owned: String,
+}
+
+impl Text {
+    fn new<T: Iterator<Item = char>>(state: &ScannerState<T>) -> Self {
+        let at = state.mark().byte_offset;
+        match state.source_text() {
+            Some(_) => Self {
+                source: Some(at..at),
+                owned: String::new(),
+            },
+            None => Self {
+                source: None,
+                owned: String::with_capacity(32),
+            },
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.source.as_ref().map_or(self.owned.len(), Range::len)
+    }
+
+    fn as_str<'s, T: Iterator<Item = char>>(&'s self, state: &'s ScannerState<T>) -> &'s str {
+        match &self.source {
+            Some(range) => &state.source_text().unwrap_or_default()[range.clone()],
+            None => &self.owned,
+        }
+    }
+
+    /// The content to write to, copied out of the source if it still is
+    /// a range of it
+    fn to_mut<T: Iterator<Item = char>>(&mut self, state: &ScannerState<T>) -> &mut String {
+        if let Some(range) = self.source.take() {
+            self.owned = state.source_text().unwrap_or_default()[range].to_owned();
+        }
+        &mut self.owned
+    }
+
+    /// Write `spaces` skipped spaces that turned out to separate content.
+    /// A range takes them in with the run that follows.
+    fn take_spaces(&mut self, spaces: usize) {
+        if self.source.is_none() {
+            self.owned.extend(std::iter::repeat_n(' ', spaces));
+        }
+    }
+
+    /// Consume the next character and the run after it that matches
+    /// `pred`, as content
+    fn take_run<T: Iterator<Item = char>>(
+        &mut self,
+        state: &mut ScannerState<T>,
+        max_len: usize,
+        pred: impl FnMut(char) -> bool,
+    ) -> Result<(), ScanError> {
+        match &mut self.source {
+            Some(range) => {
+                state.consume_char()?;
+                state.skip_while(pred);
+                range.end = state.mark().byte_offset;
+            }
+            None => {
+                self.owned.push(state.consume_char()?);
+                state.consume_while(&mut self.owned, max_len, pred);
+            }
+        }
+        Ok(())
+    }
+
+    /// Fold `breaks` line breaks between plain content lines: one becomes a
+    /// space, more become all but the first
+    fn fold<T: Iterator<Item = char>>(&mut self, state: &ScannerState<T>, breaks: usize) {
+        let out = self.to_mut(state);
+        if breaks == 1 {
+            out.push(' ');
+        } else {
+            out.extend(std::iter::repeat_n('\n', breaks - 1));
+        }
+    }
+
+    fn trim_end(&mut self) {
+        // A range ends with content, never with spaces
+        self.owned.truncate(self.owned.trim_end().len());
+    }
+
+    fn finish(self) -> ScalarText {
+        match self.source {
+            Some(range) => ScalarText::Source(range),
+            None => ScalarText::Owned(self.owned),
+        }
+    }
+}
+
+/// Reject scalar content longer than `config.max_scalar_len`, pointing at
+/// the scalar's start
+#[inline]
+fn check_scalar_len(len: usize, config: &ScannerConfig, start: Marker) -> Result<(), ScanError> {
+    if len > config.max_scalar_len {
+        let info = format!(
+            "scalar is longer than the limit of {} bytes",
+            config.max_scalar_len
+        );
+        return Err(ScanError::new(start, &info));
+    }
+    Ok(())
+}
+
+/// Whether a line starting at column 0 begins a new token rather than
+/// continuing a root plain scalar
+fn ends_root_scalar<T: Iterator<Item = char>>(
+    state: &mut ScannerState<T>,
+) -> Result<bool, ScanError> {
+    if state.check_document_start()? || state.check_document_end()? {
+        return Ok(true);
+    }
+    Ok(match state.peek_char() {
+        Ok('%') => true,
+        Ok('-' | '?' | ':') => state
+            .peek_char_at(1)
+            .is_none_or(|next| matches!(next, ' ' | '\t' | '\n' | '\r')),
+        _ => false,
+    })
 }
 
 /// Scan single-quoted scalar with proper escape handling
 #[inline]
 pub fn scan_single_quoted<T: Iterator<Item = char>>(
     state: &mut ScannerState<T>,
+    config: &ScannerConfig,
+    start: Marker,
+    floor: Option<usize>,
 ) -> Result<String, ScanError> {
-    let mut result = String::with_capacity(32);
-    let _start_mark = state.mark();
+    let text = scan_single_quoted_text(state, config, start, floor)?;
+    Ok(text.into_string(state.source_text()))
+}
+
+/// [`scan_single_quoted`], leaving the scalar as a range of the source text when
+/// the state has one and the scalar has no `''` and no line break
+pub(crate) fn scan_single_quoted_text<T: Iterator<Item = char>>(
+    state: &mut ScannerState<T>,
+    config: &ScannerConfig,
+    start: Marker,
+    floor: Option<usize>,
+) -> Result<ScalarText, ScanError> {
+    let mut result = Text::new(state);
 
     loop {
-        match state.peek_char()? {
+        check_scalar_len(result.len(), config, start)?;
+        let Ok(ch) = state.peek_char() else {
+            let construct = "single-quoted scalar";
+            return Err(ScanError::unclosed(state.mark(), construct, start, '\''));
+        };
+        match ch {
             '\'' => {
                 state.consume_char()?;
                 // Check for escaped quote ''
                 if matches!(state.peek_char(), Ok('\'')) {
                     state.consume_char()?;
-                    result.push('\'');
+                    result.to_mut(state).push('\'');
                 } else {
                     // End of string
-                    return Ok(result);
+                    return Ok(result.finish());
                 }
             }
             '\n' | '\r' => {
                 // Fold newlines to spaces
                 state.consume_char()?;
-                // Skip leading whitespace on next line
-                loop {
-                    match state.peek_char() {
-                        Ok(' ') => {
-                            state.consume_char()?;
-                        }
-                        Ok('\t') => {
-                            return Err(ScanError::new(
-                                state.mark(),
-                                "tabs are not allowed in YAML, use spaces for indentation",
-                            ));
-                        }
-                        _ => break,
-                    }
-                }
-                if !result.is_empty() && !result.ends_with(' ') {
-                    result.push(' ');
-                }
+                skip_continuation_indent(state, floor)?;
+                fold_quoted(&mut result, state);
             }
             _ch => {
-                result.push(state.consume_char()?);
+                result.take_run(state, config.max_scalar_len, |c| {
+                    !matches!(c, '\'' | '\n' | '\r')
+                })?;
             }
         }
     }
@@ -187,54 +385,95 @@ pub fn scan_single_quoted<T: Iterator<Item = char>>(
 #[inline]
 pub fn scan_double_quoted<T: Iterator<Item = char>>(
     state: &mut ScannerState<T>,
+    config: &ScannerConfig,
+    start: Marker,
+    floor: Option<usize>,
 ) -> Result<String, ScanError> {
-    let mut result = String::with_capacity(32);
-    let _start_mark = state.mark();
+    let text = scan_double_quoted_text(state, config, start, floor)?;
+    Ok(text.into_string(state.source_text()))
+}
+
+/// [`scan_double_quoted`], leaving the scalar as a range of the source text when
+/// the state has one and the scalar has no escape and no line break
+pub(crate) fn scan_double_quoted_text<T: Iterator<Item = char>>(
+    state: &mut ScannerState<T>,
+    config: &ScannerConfig,
+    start: Marker,
+    floor: Option<usize>,
+) -> Result<ScalarText, ScanError> {
+    let mut result = Text::new(state);
 
     loop {
-        match state.peek_char()? {
+        check_scalar_len(result.len(), config, start)?;
+        let Ok(ch) = state.peek_char() else {
+            let construct = "double-quoted scalar";
+            return Err(ScanError::unclosed(state.mark(), construct, start, '"'));
+        };
+        match ch {
             '"' => {
                 state.consume_char()?;
-                return Ok(result);
+                return Ok(result.finish());
             }
             '\\' => {
                 state.consume_char()?;
-                let escaped = process_escape_sequence_consolidated(state)?;
-                result.push(escaped);
+                let escaped = process_escape_sequence_consolidated(state, floor)?;
+                result.to_mut(state).push(escaped);
             }
             '\n' | '\r' => {
                 // Fold newlines to spaces
                 state.consume_char()?;
-                // Skip leading whitespace on next line
-                loop {
-                    match state.peek_char() {
-                        Ok(' ') => {
-                            state.consume_char()?;
-                        }
-                        Ok('\t') => {
-                            return Err(ScanError::new(
-                                state.mark(),
-                                "tabs are not allowed in YAML, use spaces for indentation",
-                            ));
-                        }
-                        _ => break,
-                    }
-                }
-                if !result.is_empty() && !result.ends_with(' ') {
-                    result.push(' ');
-                }
+                skip_continuation_indent(state, floor)?;
+                fold_quoted(&mut result, state);
             }
             _ch => {
-                result.push(state.consume_char()?);
+                result.take_run(state, config.max_scalar_len, |c| {
+                    !matches!(c, '"' | '\\' | '\n' | '\r')
+                })?;
             }
         }
     }
 }
 
+/// Fold a quoted scalar's line break into a space, unless one ends the
+/// content already
+fn fold_quoted<T: Iterator<Item = char>>(result: &mut Text, state: &ScannerState<T>) {
+    let text = result.as_str(state);
+    if !text.is_empty() && !text.ends_with(' ') {
+        result.to_mut(state).push(' ');
+    }
+}
+
+/// Skip the indentation of a quoted scalar's continuation line, which must
+/// be right of `floor` unless the line is blank
+fn skip_continuation_indent<T: Iterator<Item = char>>(
+    state: &mut ScannerState<T>,
+    floor: Option<usize>,
+) -> Result<(), ScanError> {
+    loop {
+        match state.peek_char() {
+            Ok(' ') => {
+                state.consume_char()?;
+            }
+            Ok('\t') => {
+                return Err(ScanError::new(
+                    state.mark(),
+                    "tabs are not allowed in YAML, use spaces for indentation",
+                ));
+            }
+            Ok('\n' | '\r') => return Ok(()),
+            _ if floor.is_some_and(|floor| state.mark().col <= floor) => {
+                return Err(ScanError::new(state.mark(), UNDER_INDENTED_FLOW));
+            }
+            _ => return Ok(()),
+        }
+    }
+}
+
 /// Process escape sequence using consolidated character productions API - zero allocation
 #[inline]
 fn process_escape_sequence_consolidated<T: Iterator<Item = char>>(
     state: &mut ScannerState<T>,
+    floor: Option<usize>,
 ) -> Result<char, ScanError> {
     let escape_char = state.consume_char()?;
 
@@ -285,6 +524,26 @@ fn process_escape_sequence_consolidated<T: Iterator<Item = char>>(
                         &format!("invalid unicode escape: {}", escape_error),
                     )
                 })?;
+            // A JSON-style UTF-16 surrogate pair such as `\ud83d\ude00`
+            if (0xD800..0xDC00).contains(&hex_value) && state.check_chars(&['\\', 'u']) {
+                state.consume_chars(2)?;
+                let mut low_chars = ['\0'; 4];
+                for hex_char in &mut low_chars {
+                    *hex_char = state.consume_char()?;
+                }
+                let low = CharacterProductions::parse_hex_chars(&low_chars).map_err(|escape_error| {
+                    ScanError::new(
+                        state.mark(),
+                        &format!("invalid unicode escape: {}", escape_error),
+                    )
+                })?;
+                if (0xDC00..0xE000).contains(&low) {
+                    let combined = 0x10000 + ((hex_value - 0xD800) << 10) + (low - 0xDC00);
+                    if let Some(ch) = char::from_u32(combined) {
+                        return Ok(ch);
+                    }
+                }
+            }
             char::from_u32(hex_value).ok_or_else(|| {
                 ScanError::new(
                     state.mark(),
@@ -314,20 +573,7 @@ fn process_escape_sequence_consolidated<T: Iterator<Item = char>>(
         }
         '\n' | '\r' => {
             // Handle escaped line breaks - skip whitespace and fold to space
-            loop {
-                match state.peek_char() {
-                    Ok(' ') => {
-                        state.consume_char()?;
-                    }
-                    Ok('\t') => {
-                        return Err(ScanError::new(
-                            state.mark(),
-                            "tabs are not allowed in YAML, use spaces for indentation",
-                        ));
-                    }
-                    _ => break,
-                }
-            }
+            skip_continuation_indent(state, floor)?;
             Ok(' ')
         }
         ch => Err(ScanError::new(
@@ -338,31 +584,50 @@ fn process_escape_sequence_consolidated<T: Iterator<Item = char>>(
 }
 
 /// Scan block scalar (literal | or folded >)
+///
+/// `parent_indent` is the indentation of the node that owns the scalar, if
+/// known; content must be indented further, and an explicit indentation
+/// indicator counts from it (spec 8.1.1.1). Folding follows spec 8.1.3:
+/// only a single break between two lines at the base indentation becomes a
+/// space, and breaks next to more-indented lines are kept. Chomping (spec
+/// 8.1.1.2) applies to the final break and trailing blank lines only.
 #[inline]
 pub fn scan_block_scalar<T: Iterator<Item = char>>(
     state: &mut ScannerState<T>,
+    config: &ScannerConfig,
+    start: Marker,
     literal: bool,
-) -> Result<String, ScanError> {
+    parent_indent: Option<usize>,
+) -> Result<(String, BlockScalarHeader), ScanError> {
     let mut result = String::with_capacity(128);
 
     // Parse block scalar header
-    let (chomping, explicit_indent) = parse_block_scalar_header(state)?;
+    let header = parse_block_scalar_header(state)?;
+    let BlockScalarHeader {
+        chomping,
+        indent: explicit_indent,
+    } = header;
 
     // Skip to next line
     skip_to_next_line(state)?;
 
-    // Determine base indentation
-    let base_indent = if let Some(indent) = explicit_indent {
-        indent
-    } else {
-        detect_block_scalar_indent(state)?
+    // Determine base indentation; a first line indented no further than
+    // the parent ends an empty scalar
+    let min_indent = parent_indent.map_or(0, |parent| parent + 1);
+    let base_indent = match explicit_indent {
+        Some(indent) => parent_indent.unwrap_or(0) + indent,
+        None => detect_block_scalar_indent(state)?.max(min_indent),
     };
 
-    // Read block scalar content
-    let mut trailing_breaks = String::new();
-    let mut first_line = true;
+    // Line breaks seen since the last content line, and whether that line
+    // was more indented than the rest, which stops folding around it
+    let mut breaks = 0usize;
+    let mut has_content = false;
+    let mut last_more_indented = false;
 
     loop {
+        check_scalar_len(result.len() + breaks, config, start)?;
+
         // Check for document markers
         if state.at_line_start() && (state.check_document_start()? || state.check_document_end()?) {
             break;
@@ -371,103 +636,69 @@ pub fn scan_block_scalar<T: Iterator<Item = char>>(
         // Read indentation
         let line_indent = count_indentation(state)?;
 
-        if line_indent < base_indent {
-            // Less indented line ends the scalar
-            break;
-        }
-
-        // Check for blank line or EOF
         match state.peek_char() {
-            Ok('\n') | Ok('\r') => {
-                trailing_breaks.push('\n');
+            // Blank lines belong to the scalar whatever their indentation
+            Ok('\n' | '\r') if line_indent <= base_indent => {
+                breaks += 1;
                 consume_line_break(state)?;
                 continue;
             }
-            Err(_) => {
-                // EOF - terminate the scalar
-                break;
-            }
-            Ok(_) => {
-                // Regular character, continue processing
-            }
+            Err(_) => break,
+            Ok(_) if line_indent < base_indent => break,
+            Ok(_) => {}
         }
 
-        // Add any accumulated breaks
-        if !trailing_breaks.is_empty() {
-            if literal || trailing_breaks.len() > 1 {
-                // In literal mode or multiple breaks, preserve them
-                result.push_str(&trailing_breaks);
-            } else if !result.is_empty() {
-                // In folded mode with single break, convert to space
+        let more_indented =
+            line_indent > base_indent || matches!(state.peek_char(), Ok('\t'));
+        if has_content {
+            let folds = !literal && !last_more_indented && !more_indented;
+            if folds && breaks == 1 {
                 result.push(' ');
+            } else {
+                // Folding drops the first break between two normal lines
+                let kept = if folds { breaks - 1 } else { breaks };
+                result.extend(std::iter::repeat_n('\n', kept));
             }
-            trailing_breaks.clear();
-        }
-
-        // Add line content
-        if !first_line && literal {
-            result.push('\n');
-        } else if !first_line && !literal && !result.is_empty() {
-            result.push(' ');
+        } else {
+            result.extend(std::iter::repeat_n('\n', breaks));
         }
-        first_line = false;
+        breaks = 0;
+        has_content = true;
+        last_more_indented = more_indented;
 
-        // Skip extra indentation beyond base
-        for _ in base_indent..line_indent {
-            if matches!(state.peek_char(), Ok(' ')) {
-                state.consume_char()?;
-            }
-        }
+        // Indentation beyond the base is content
+        result.extend(std::iter::repeat_n(' ', line_indent - base_indent));
 
         // Read line content
-        while let Ok(ch) = state.peek_char() {
-            if matches!(ch, '\n' | '\r') {
-                break;
-            }
-            result.push(state.consume_char()?);
-        }
+        state.consume_while(&mut result, config.max_scalar_len, |c| !matches!(c, '\n' | '\r'));
 
         // Consume line break if present
-        if matches!(state.peek_char(), Ok('\n') | Ok('\r')) {
+        if matches!(state.peek_char(), Ok('\n' | '\r')) {
             consume_line_break(state)?;
+            breaks = 1;
         }
     }
 
-    // Apply chomping indicator
+    // Apply chomping indicator to the final line break and trailing blank lines
     match chomping {
-        Chomping::Strip => {
-            // Remove all trailing newlines
-            result.truncate(result.trim_end_matches('\n').len());
-        }
-        Chomping::Keep => {
-            // Keep one trailing newline
-            if !trailing_breaks.is_empty() {
+        ChompingMode::Strip => {}
+        ChompingMode::Clip => {
+            if has_content && breaks > 0 {
                 result.push('\n');
             }
         }
-        Chomping::Clip => {
-            // Keep all trailing newlines
-            result.push_str(&trailing_breaks);
-        }
+        ChompingMode::Keep => result.extend(std::iter::repeat_n('\n', breaks)),
     }
 
-    Ok(result)
-}
-
-/// Block scalar chomping modes
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Chomping {
-    Strip, // - indicator
-    Clip,  // default
-    Keep,  // + indicator
+    Ok((result, header))
 }
 
 /// Parse block scalar header for chomping and indentation
 #[inline]
 fn parse_block_scalar_header<T: Iterator<Item = char>>(
     state: &mut ScannerState<T>,
-) -> Result<(Chomping, Option<usize>), ScanError> {
-    let mut chomping = Chomping::Clip;
+) -> Result<BlockScalarHeader, ScanError> {
+    let mut chomping = ChompingMode::Clip;
     let mut indent = None;
 
     // Skip optional indicators (can be in any order)
@@ -475,11 +706,11 @@ fn parse_block_scalar_header<T: Iterator<Item = char>>(
         match state.peek_char() {
             Ok('+') => {
                 state.consume_char()?;
-                chomping = Chomping::Keep;
+                chomping = ChompingMode::Keep;
             }
             Ok('-') => {
                 state.consume_char()?;
-                chomping = Chomping::Strip;
+                chomping = ChompingMode::Strip;
             }
             Ok(ch @ '1'..='9') => {
                 state.consume_char()?;
@@ -489,7 +720,7 @@ fn parse_block_scalar_header<T: Iterator<Item = char>>(
         }
     }
 
-    Ok((chomping, indent))
+    Ok(BlockScalarHeader { chomping, indent })
 }
 
 /// Skip to next line, consuming any comments
@@ -4,8 +4,18 @@
 //! position tracking, buffer management, and flow context handling.
 
 use crate::error::{Marker, ScanError};
+use crate::parser::IndentationRules;
 use crate::scanner::token::Token;
+use std::cell::Cell;
 use std::collections::VecDeque;
+use std::str::Chars;
+
+/// Characters pulled from the source per refill, so lookahead and runs of
+/// content rarely reach back into the iterator
+const CHUNK: usize = 256;
+
+/// Message of the error for a `]` or `}` with no flow collection open
+pub(crate) const UNMATCHED_FLOW_END: &str = "unexpected flow collection end";
 
 /// Context tracking for BOM filtering - preserves BOMs in quoted strings per YAML 1.2
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -38,6 +48,14 @@ pub struct ScannerConfig {
     pub strict_yaml12: bool,
     /// Allow duplicate anchors (non-standard)
     pub allow_duplicate_anchors: bool,
+    /// Maximum length of a scalar's content in bytes
+    pub max_scalar_len: usize,
+    /// Maximum length in bytes of a scalar used as a mapping key
+    pub max_key_len: usize,
+    /// Allow tabs as separation white space between tokens on a line
+    pub allow_separating_tabs: bool,
+    /// Spec indentation rules to enforce
+    pub indentation: IndentationRules,
 }
 
 impl Default for ScannerConfig {
@@ -49,11 +67,73 @@ impl Default for ScannerConfig {
             max_anchor_length: 1024,
             strict_yaml12: true,
             allow_duplicate_anchors: false,
+            max_scalar_len: usize::MAX,
+            max_key_len: usize::MAX,
+            allow_separating_tabs: true,
+            indentation: IndentationRules::permissive(),
+        }
+    }
+}
+
+/// The characters of a `&str`, for a scanner that slices scalars from the
+/// text instead of building them a character at a time
+#[derive(Clone)]
+pub(crate) struct StrChars<'input> {
+    text: &'input str,
+    chars: Chars<'input>,
+    /// Bytes of `text` read so far, kept for a caller reporting progress
+    read: Option<&'input Cell<usize>>,
+}
+
+impl<'input> StrChars<'input> {
+    pub(crate) fn new(text: &'input str) -> Self {
+        Self {
+            text,
+            chars: text.chars(),
+            read: None,
         }
     }
+
+    /// Keep `read` at the number of bytes read from the text
+    pub(crate) const fn counting(self, read: &'input Cell<usize>) -> Self {
+        Self {
+            read: Some(read),
+            ..self
+        }
+    }
+
+    /// The whole text, however much has been read
+    fn text(&self) -> &str {
+        self.text
+    }
+}
+
+impl<'input> From<&'input str> for StrChars<'input> {
+    fn from(text: &'input str) -> Self {
+        Self::new(text)
+    }
+}
+
+impl Iterator for StrChars<'_> {
+    type Item = char;
+
+    #[inline]
+    fn next(&mut self) -> Option<char> {
+        let ch = self.chars.next()?;
+        if let Some(read) = self.read {
+            read.set(self.text.len() - self.chars.as_str().len());
+        }
+        Some(ch)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chars.size_hint()
+    }
 }
 
 /// Scanner state with efficient buffer and position management
+#[derive(Clone)]
 pub struct ScannerState<T: Iterator<Item = char>> {
     /// Character source iterator
     source: T,
@@ -69,8 +149,9 @@ pub struct ScannerState<T: Iterator<Item = char>> {
     stream_start_produced: bool,
     /// Stream end produced flag
     stream_end_produced: bool,
-    /// Flow nesting level for context tracking
-    flow_level: usize,
+    /// Opening bracket and position of each open flow collection,
+    /// innermost last
+    flow_openers: Vec<(Marker, char)>,
     /// Current indentation level
     indent: i32,
     /// Indentation stack for block context
@@ -79,6 +160,18 @@ pub struct ScannerState<T: Iterator<Item = char>> {
     simple_key_allowed: bool,
     /// BOM filtering context for YAML 1.2 compliance
     quoted_context: QuotedContext,
+    /// The whole text `source` reads, if it has one to slice
+    text: Option<fn(&T) -> &str>,
+}
+
+impl<'input> ScannerState<StrChars<'input>> {
+    /// State over `text`, whose scalars can be sliced from it
+    pub(crate) fn over_str(text: impl Into<StrChars<'input>>) -> Self {
+        Self {
+            text: Some(StrChars::text),
+            ..Self::new(text.into())
+        }
+    }
 }
 
 impl<T: Iterator<Item = char>> ScannerState<T> {
@@ -87,20 +180,28 @@ impl<T: Iterator<Item = char>> ScannerState<T> {
     pub fn new(source: T) -> Self {
         Self {
             source,
-            buffer: VecDeque::with_capacity(64),
+            buffer: VecDeque::with_capacity(CHUNK),
             mark: Marker::new(),
             done: false,
             cached_token: None,
             stream_start_produced: false,
             stream_end_produced: false,
-            flow_level: 0,
+            flow_openers: Vec::new(),
             indent: -1,
             indent_stack: Vec::with_capacity(16),
             simple_key_allowed: true,
             quoted_context: QuotedContext::None,
+            text: None,
         }
     }
 
+    /// The whole source text, if this state reads a `&str`. Marker byte
+    /// offsets index into it.
+    #[inline]
+    pub(crate) fn source_text(&self) -> Option<&str> {
+        self.text.map(|text| text(&self.source))
+    }
+
     /// Get current position marker
     #[inline]
     pub const fn mark(&self) -> Marker {
@@ -140,30 +241,56 @@ impl<T: Iterator<Item = char>> ScannerState<T> {
     /// Check if we're in flow context
     #[inline]
     pub const fn in_flow_context(&self) -> bool {
-        self.flow_level > 0
+        !self.flow_openers.is_empty()
     }
 
-    /// Enter flow context (for [ ] or { })
+    /// Enter flow context for the `[` or `{` at `start`
     #[inline]
-    pub const fn enter_flow_context(&mut self) {
-        self.flow_level += 1;
+    pub fn enter_flow_context(&mut self, start: Marker, opener: char) {
+        self.flow_openers.push((start, opener));
         self.simple_key_allowed = true;
     }
 
-    /// Exit flow context with validation
+    /// Exit flow context at the `]` or `}` at `at`. A closer that does not
+    /// match the innermost opener leaves it open, for the parser to report.
     #[inline]
-    pub fn exit_flow_context(&mut self) -> Result<(), ScanError> {
-        if self.flow_level == 0 {
-            return Err(ScanError::new(self.mark, "unexpected flow collection end"));
+    pub fn exit_flow_context(&mut self, at: Marker, closer: char) -> Result<(), ScanError> {
+        match self.flow_openers.last() {
+            None => Err(ScanError::new(at, UNMATCHED_FLOW_END)),
+            Some((_, opener)) => {
+                if matches!((opener, closer), ('[', ']') | ('{', '}')) {
+                    self.flow_openers.pop();
+                }
+                Ok(())
+            }
         }
-        self.flow_level -= 1;
-        Ok(())
     }
 
     /// Get current flow level
     #[inline]
     pub const fn flow_level(&self) -> usize {
-        self.flow_level
+        self.flow_openers.len()
+    }
+
+    /// Position and bracket of the innermost open flow collection
+    #[inline]
+    pub fn flow_opener(&self) -> Option<(Marker, char)> {
+        self.flow_openers.last().copied()
+    }
+
+    /// Close the innermost flow collection `closer` matches, returning the
+    /// ones inside it that were left open; `None` if nothing matches
+    pub(crate) fn close_flow_through(&mut self, closer: char) -> Option<Vec<(Marker, char)>> {
+        let opener = if closer == ']' { '[' } else { '{' };
+        let at = self.flow_openers.iter().rposition(|(_, open)| *open == opener)?;
+        let mut unclosed = self.flow_openers.split_off(at);
+        unclosed.remove(0);
+        Some(unclosed)
+    }
+
+    /// Forget every open flow collection, returning them outermost first
+    pub(crate) fn take_flow_openers(&mut self) -> Vec<(Marker, char)> {
+        std::mem::take(&mut self.flow_openers)
     }
 
     /// Check if simple key is allowed
@@ -254,11 +381,21 @@ impl<T: Iterator<Item = char>> ScannerState<T> {
     /// Fill buffer to ensure at least n characters
     #[inline]
     pub fn ensure_buffer(&mut self, n: usize) {
-        while self.buffer.len() < n && !self.done {
+        if self.buffer.len() < n && !self.done {
+            self.refill(n);
+        }
+    }
+
+    /// Pull at least a chunk from the source, or `n` characters if more
+    #[inline(never)]
+    fn refill(&mut self, n: usize) {
+        let target = n.max(self.buffer.len() + CHUNK);
+        while self.buffer.len() < target {
             if let Some(ch) = self.source.next() {
                 self.buffer.push_back(ch);
             } else {
                 self.done = true;
+                break;
             }
         }
     }
@@ -266,6 +403,9 @@ impl<T: Iterator<Item = char>> ScannerState<T> {
     /// Peek at next character without consuming (raw - no BOM filtering)
     #[inline]
     pub fn peek_char_raw(&mut self) -> Result<char, ScanError> {
+        if let Some(&ch) = self.buffer.front() {
+            return Ok(ch);
+        }
         self.ensure_buffer(1);
         self.buffer
             .front()
@@ -295,32 +435,81 @@ impl<T: Iterator<Item = char>> ScannerState<T> {
     /// Check if next characters match a pattern (no BOM filtering)
     #[inline]
     pub fn check_chars(&mut self, pattern: &[char]) -> bool {
-        for (i, &expected_char) in pattern.iter().enumerate() {
-            match self.peek_char_at(i) {
-                Some(actual_char) if actual_char == expected_char => continue,
-                _ => return false,
-            }
-        }
-        true
+        self.ensure_buffer(pattern.len());
+        self.buffer.len() >= pattern.len()
+            && self.buffer.iter().zip(pattern).all(|(a, b)| a == b)
     }
 
     /// Consume next character and update position (raw - no BOM filtering)
     #[inline]
     pub fn consume_char_raw(&mut self) -> Result<char, ScanError> {
+        // Escape sequences consume past what was peeked
+        self.ensure_buffer(1);
         if let Some(ch) = self.buffer.pop_front() {
-            self.mark.index += 1;
-            if ch == '\n' {
-                self.mark.line += 1;
-                self.mark.col = 0;
-            } else {
-                self.mark.col += 1;
-            }
+            self.mark.advance_char(ch);
             Ok(ch)
         } else {
             Err(ScanError::new(self.mark, "unexpected end of input"))
         }
     }
 
+    /// Consume the run of characters matching `pred`, appending them to
+    /// `out`, and return how many were taken. Stops early once `out` is
+    /// longer than `max_len` bytes.
+    ///
+    /// Scalar scanners use this to copy plain content a buffer slice at a
+    /// time instead of peeking and consuming every character.
+    #[inline]
+    pub fn consume_while(
+        &mut self,
+        out: &mut String,
+        max_len: usize,
+        mut pred: impl FnMut(char) -> bool,
+    ) -> usize {
+        let mut total = 0;
+        loop {
+            let mut taken = 0;
+            for &ch in &self.buffer {
+                if out.len() > max_len || !pred(ch) {
+                    break;
+                }
+                out.push(ch);
+                self.mark.advance_char(ch);
+                taken += 1;
+            }
+            let exhausted = taken == self.buffer.len();
+            self.buffer.drain(..taken);
+            total += taken;
+            if !exhausted || self.done || out.len() > max_len {
+                return total;
+            }
+            self.refill(1);
+        }
+    }
+
+    /// Skip the run of characters matching `pred`, returning how many were skipped
+    #[inline]
+    pub fn skip_while(&mut self, mut pred: impl FnMut(char) -> bool) -> usize {
+        let mut total = 0;
+        loop {
+            let mut taken = 0;
+            for &ch in &self.buffer {
+                if !pred(ch) {
+                    break;
+                }
+                self.mark.advance_char(ch);
+                taken += 1;
+            }
+            let exhausted = taken == self.buffer.len();
+            self.buffer.drain(..taken);
+            total += taken;
+            if !exhausted || self.done {
+                return total;
+            }
+            self.refill(1);
+        }
+    }
+
     /// Consume next character (no BOM filtering - handled at document level)
     #[inline(always)]
     pub fn consume_char(&mut self) -> Result<char, ScanError> {
@@ -336,16 +525,20 @@ impl<T: Iterator<Item = char>> ScannerState<T> {
         Ok(())
     }
 
-    /// Check for document start marker (---)
+    /// Check for document start marker (---), which only counts at column 0
     #[inline]
     pub fn check_document_start(&mut self) -> Result<bool, ScanError> {
-        Ok(self.check_chars(&['-', '-', '-']) && self.check_boundary_after(3))
+        Ok(self.at_line_start()
+            && self.check_chars(&['-', '-', '-'])
+            && self.check_boundary_after(3))
     }
 
-    /// Check for document end marker (...)
+    /// Check for document end marker (...), which only counts at column 0
     #[inline]
     pub fn check_document_end(&mut self) -> Result<bool, ScanError> {
-        Ok(self.check_chars(&['.', '.', '.']) && self.check_boundary_after(3))
+        Ok(self.at_line_start()
+            && self.check_chars(&['.', '.', '.'])
+            && self.check_boundary_after(3))
     }
 
     /// Check for block entry (- followed by space/newline)
@@ -396,9 +589,10 @@ impl<T: Iterator<Item = char>> ScannerState<T> {
         Self::new(input)
     }
 
-    /// Extract remaining input after block scalar processing
-    pub fn into_remaining_input(self) -> T {
-        self.source
+    /// Extract remaining input after block scalar processing, starting with
+    /// the characters already read ahead into the lookahead buffer
+    pub fn into_remaining_input(self) -> impl Iterator<Item = char> {
+        self.buffer.into_iter().chain(self.source)
     }
 }
 
@@ -408,17 +602,15 @@ impl Marker {
     #[inline]
     #[must_use] 
     pub const fn new() -> Self {
-        Self {
-            index: 0,
-            line: 1,
-            col: 0,
-        }
+        Self::start()
     }
 
-    /// Create marker at specific position
+    /// Create marker at specific position, taking the byte offset to be
+    /// `index`, which only holds when the text before it is ASCII
     #[inline]
-    #[must_use] 
+    #[must_use]
+    #[deprecated(note = "the byte offset is only right for ASCII text; use `Marker::from_parts` or `Marker::locate`")]
     pub const fn at(index: usize, line: usize, col: usize) -> Self {
-        Self { index, line, col }
+        Self::from_parts(index, index, line, col)
     }
 }
@@ -21,10 +21,10 @@ pub fn scan_tag<T: Iterator<Item = char>>(
     }
 
     // Scan tag handle
-    let handle = scan_tag_handle(state)?;
+    let (handle, mut suffix) = scan_tag_handle(state)?;
 
     // Scan tag suffix
-    let suffix = scan_tag_suffix(state)?;
+    suffix.push_str(&scan_tag_suffix(state)?);
 
     // Validate tag components
     validate_tag_handle(&handle, start_mark)?;
@@ -82,36 +82,40 @@ fn scan_verbatim_tag<T: Iterator<Item = char>>(
     // Validate URI
     validate_tag_uri(&uri, start_mark)?;
 
-    Ok(("!".to_string(), uri))
+    // An empty handle marks a tag that needs no resolution
+    Ok((String::new(), uri))
 }
 
-/// Scan tag handle (empty, !, !!, or !word!)
+/// Scan tag handle (empty, !!, or !word!) after the leading `!`
+///
+/// Word characters read while looking for a named handle's closing `!`
+/// belong to the suffix of a primary tag and are returned alongside.
 #[inline]
 fn scan_tag_handle<T: Iterator<Item = char>>(
     state: &mut ScannerState<T>,
-) -> Result<String, ScanError> {
-    let mut handle = String::with_capacity(16);
-
-    // Check for secondary tag indicator
+) -> Result<(String, String), ScanError> {
+    // Secondary tag handle: `!!suffix`
     if matches!(state.peek_char(), Ok('!')) {
-        handle.push(state.consume_char()?);
-
-        // Scan handle name
-        while let Ok(ch) = state.peek_char() {
-            if is_tag_handle_char(ch) {
-                handle.push(state.consume_char()?);
-            } else if ch == '!' {
-                // Closing !
-                handle.push(state.consume_char()?);
-                break;
-            } else {
-                // End of handle
-                break;
-            }
+        state.consume_char()?;
+        return Ok(("!!".to_string(), String::new()));
+    }
+
+    let mut word = String::with_capacity(16);
+    while let Ok(ch) = state.peek_char() {
+        if !is_tag_handle_char(ch) {
+            break;
         }
+        word.push(state.consume_char()?);
     }
 
-    Ok(handle)
+    // Named tag handle: `!word!suffix`
+    if !word.is_empty() && matches!(state.peek_char(), Ok('!')) {
+        state.consume_char()?;
+        return Ok((format!("!{word}!"), String::new()));
+    }
+
+    // Primary tag handle: `!suffix`
+    Ok(("!".to_string(), word))
 }
 
 /// Scan tag suffix
@@ -236,15 +240,15 @@ const fn is_uri_char(ch: char) -> bool {
 #[inline]
 fn validate_tag_handle(handle: &str, position: Marker) -> Result<(), ScanError> {
     if handle.is_empty() {
-        return Ok(()); // Empty handle is valid (primary tag handle)
+        return Ok(()); // Verbatim tag
     }
 
     if handle == "!" {
-        return Ok(()); // Secondary tag handle
+        return Ok(()); // Primary tag handle
     }
 
     if handle == "!!" {
-        return Ok(()); // Global tag handle
+        return Ok(()); // Secondary tag handle
     }
 
     // Named tag handle: !word!
@@ -3,8 +3,13 @@
 //! This module provides efficient token creation and management for the YAML scanner
 //! with specialized builders for different token types.
 
-use crate::error::Marker;
-use crate::events::{TEncoding, TScalarStyle, TokenType};
+use std::collections::VecDeque;
+use std::ops::{Deref, Range};
+
+use crate::error::{Marker, ScanError};
+use crate::events::{BlockScalarHeader, TEncoding, TScalarStyle, TokenType};
+use crate::scanner::Scanner;
+use crate::scanner::state::StrChars;
 
 /// A token with position information
 #[derive(Clone, Debug)]
@@ -40,7 +45,57 @@ impl Token {
     }
 }
 
+/// A scanned scalar's text: a byte range of the `&str` source while it is
+/// one unbroken run of it, or the text itself once a fold or an escape made
+/// it differ
+#[derive(Clone, Debug)]
+pub(crate) enum ScalarText {
+    Source(Range<usize>),
+    Owned(String),
+}
+
+impl ScalarText {
+    /// The text, copied out of `source` if it is a range of it
+    pub(crate) fn into_string(self, source: Option<&str>) -> String {
+        match self {
+            Self::Source(range) => source.unwrap_or_default()[range].to_owned(),
+            Self::Owned(text) => text,
+        }
+    }
+}
+
+/// A token as the scanner hands it to the parser. A scalar sliced from a
+/// `&str` source keeps its byte range there and leaves the token's text
+/// empty, so it is copied only when the parser keeps it as a string.
+#[derive(Clone, Debug)]
+pub(crate) struct Lexeme {
+    pub(crate) token: Token,
+    pub(crate) span: Option<Range<usize>>,
+}
+
+impl Lexeme {
+    /// The token with its scalar text filled in from `source`
+    pub(crate) fn into_token(self, source: Option<&str>) -> Token {
+        match (self.span, self.token) {
+            (Some(span), Token(mark, TokenType::Scalar(style, _, header))) => {
+                let text = ScalarText::Source(span).into_string(source);
+                Token(mark, TokenType::Scalar(style, text, header))
+            }
+            (_, token) => token,
+        }
+    }
+}
+
+impl Deref for Lexeme {
+    type Target = Token;
+
+    fn deref(&self) -> &Token {
+        &self.token
+    }
+}
+
 /// Efficient token producer with optimized constructors
+#[derive(Clone)]
 pub struct TokenProducer {
     // Cache for common tokens to avoid allocations
     encoding: TEncoding,
@@ -179,31 +234,41 @@ impl TokenProducer {
     #[inline]
     #[must_use] 
     pub const fn plain_scalar_token(&self, marker: Marker, value: String) -> Token {
-        Token::new(marker, TokenType::Scalar(TScalarStyle::Plain, value))
+        Token::new(marker, TokenType::Scalar(TScalarStyle::Plain, value, None))
     }
 
     #[inline]
     #[must_use] 
     pub const fn single_quoted_scalar_token(&self, marker: Marker, value: String) -> Token {
-        Token::new(marker, TokenType::Scalar(TScalarStyle::SingleQuoted, value))
+        Token::new(marker, TokenType::Scalar(TScalarStyle::SingleQuoted, value, None))
     }
 
     #[inline]
     #[must_use] 
     pub const fn double_quoted_scalar_token(&self, marker: Marker, value: String) -> Token {
-        Token::new(marker, TokenType::Scalar(TScalarStyle::DoubleQuoted, value))
+        Token::new(marker, TokenType::Scalar(TScalarStyle::DoubleQuoted, value, None))
     }
 
     #[inline]
     #[must_use] 
-    pub const fn literal_scalar_token(&self, marker: Marker, value: String) -> Token {
-        Token::new(marker, TokenType::Scalar(TScalarStyle::Literal, value))
+    pub const fn literal_scalar_token(
+        &self,
+        marker: Marker,
+        value: String,
+        header: BlockScalarHeader,
+    ) -> Token {
+        Token::new(marker, TokenType::Scalar(TScalarStyle::Literal, value, Some(header)))
     }
 
     #[inline]
     #[must_use] 
-    pub const fn folded_scalar_token(&self, marker: Marker, value: String) -> Token {
-        Token::new(marker, TokenType::Scalar(TScalarStyle::Folded, value))
+    pub const fn folded_scalar_token(
+        &self,
+        marker: Marker,
+        value: String,
+        header: BlockScalarHeader,
+    ) -> Token {
+        Token::new(marker, TokenType::Scalar(TScalarStyle::Folded, value, Some(header)))
     }
 
     // Anchor/Alias tokens
@@ -280,7 +345,7 @@ pub struct TokenStream<'a> {
 impl<'a> TokenStream<'a> {
     /// Create new token stream from slice
     #[inline]
-    #[must_use] 
+    #[must_use]
     pub fn new(tokens: &'a [Token]) -> Self {
         Self {
             tokens: tokens.iter(),
@@ -293,16 +358,24 @@ impl<'a> TokenStream<'a> {
         self.tokens.as_slice().first()
     }
 
+    /// Peek `k` tokens ahead without consuming anything; `peek_n(0)` is the
+    /// next token
+    #[inline]
+    #[must_use]
+    pub fn peek_n(&self, k: usize) -> Option<&'a Token> {
+        self.tokens.as_slice().get(k)
+    }
+
     /// Check if stream is empty
     #[inline]
-    #[must_use] 
+    #[must_use]
     pub fn is_empty(&self) -> bool {
         self.tokens.as_slice().is_empty()
     }
 
     /// Get remaining token count
     #[inline]
-    #[must_use] 
+    #[must_use]
     pub fn len(&self) -> usize {
         self.tokens.as_slice().len()
     }
@@ -328,3 +401,109 @@ impl<'a> ExactSizeIterator for TokenStream<'a> {
         self.tokens.len()
     }
 }
+
+/// Tokens scanned lazily from a string, with arbitrary lookahead and
+/// pushback, for building parsers on top of the scanner alone.
+///
+/// The stream ends after [`TokenType::StreamEnd`] or the first scan error.
+/// For tokens already in a slice, use [`TokenStream`].
+///
+/// ```rust
+/// use yyaml::TokenType;
+/// use yyaml::scanner::ScannedTokens;
+///
+/// let mut tokens = ScannedTokens::from_str("name: app");
+/// assert!(matches!(tokens.peek_n(0)?.map(|t| &t.1), Some(TokenType::StreamStart(_))));
+/// // A scalar is a key when the token after it is `:`
+/// assert!(matches!(tokens.peek_n(2)?.map(|t| &t.1), Some(TokenType::Value)));
+///
+/// let start = tokens.next().transpose()?.unwrap();
+/// tokens.push_back(start);
+/// assert_eq!(tokens.count(), 5);
+/// # Ok::<(), yyaml::ScanError>(())
+/// ```
+pub struct ScannedTokens<'a> {
+    scanner: Option<Box<Scanner<StrChars<'a>>>>,
+    lookahead: VecDeque<Token>,
+}
+
+impl<'a> ScannedTokens<'a> {
+    /// Scan `input` lazily, one token at a time
+    #[allow(clippy::should_implement_trait)]
+    #[must_use]
+    pub fn from_str(input: &'a str) -> Self {
+        Self {
+            scanner: Some(Box::new(Scanner::over_str(input))),
+            lookahead: VecDeque::new(),
+        }
+    }
+
+    /// Peek at next token without consuming
+    #[inline]
+    pub fn peek(&mut self) -> Result<Option<&Token>, ScanError> {
+        self.peek_n(0)
+    }
+
+    /// Peek `k` tokens ahead without consuming anything; `peek_n(0)` is the
+    /// next token. `None` once the stream ends before that token.
+    pub fn peek_n(&mut self, k: usize) -> Result<Option<&Token>, ScanError> {
+        while self.lookahead.len() <= k {
+            match self.pull()? {
+                Some(token) => self.lookahead.push_back(token),
+                None => break,
+            }
+        }
+        Ok(self.lookahead.get(k))
+    }
+
+    /// Return `token` to the front of the stream, so the next read yields it
+    /// again. Tokens pushed back in a row come out in reverse order.
+    #[inline]
+    pub fn push_back(&mut self, token: Token) {
+        self.lookahead.push_front(token);
+    }
+
+    /// Whether every token has been read
+    #[inline]
+    pub fn is_empty(&mut self) -> Result<bool, ScanError> {
+        Ok(self.peek()?.is_none())
+    }
+
+    /// The next token from the scanner, skipping the lookahead buffer
+    fn pull(&mut self) -> Result<Option<Token>, ScanError> {
+        let Some(scanner) = self.scanner.as_mut() else {
+            return Ok(None);
+        };
+        let token = match scanner.peek_token() {
+            Ok(token) => token,
+            Err(err) => {
+                self.scanner = None;
+                return Err(err);
+            }
+        };
+        scanner.skip();
+        match token.1 {
+            TokenType::NoToken => {
+                self.scanner = None;
+                Ok(None)
+            }
+            TokenType::StreamEnd => {
+                self.scanner = None;
+                Ok(Some(token))
+            }
+            _ => Ok(Some(token)),
+        }
+    }
+}
+
+impl Iterator for ScannedTokens<'_> {
+    type Item = Result<Token, ScanError>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.lookahead.pop_front() {
+            Some(token) => Some(Ok(token)),
+            None => self.pull().transpose(),
+        }
+    }
+}
@@ -12,11 +12,33 @@ use crate::scanner::state::ScannerState;
 pub fn skip_whitespace_and_comments<T: Iterator<Item = char>>(
     state: &mut ScannerState<T>,
 ) -> Result<(), ScanError> {
+    skip_separation(state, true)
+}
+
+/// Skip whitespace and comments, rejecting every tab unless `allow_tabs`
+/// permits tabs that separate tokens on a line
+#[inline]
+pub fn skip_separation<T: Iterator<Item = char>>(
+    state: &mut ScannerState<T>,
+    allow_tabs: bool,
+) -> Result<(), ScanError> {
+    // Tabs may separate tokens on a line but never indent one
+    let mut indentation = state.at_line_start();
     loop {
         match state.peek_char() {
             Ok(' ') => {
                 state.consume_char()?;
             }
+            // Flow collections have no indentation, so tabs only separate tokens
+            Ok('\t') if allow_tabs && (state.in_flow_context() || !indentation) => {
+                state.consume_char()?;
+            }
+            Ok('\t') if !indentation || state.in_flow_context() => {
+                return Err(ScanError::new(
+                    state.mark(),
+                    "tabs are not allowed as separation white space",
+                ));
+            }
             Ok('\t') => {
                 return Err(ScanError::new(
                     state.mark(),
@@ -25,9 +47,11 @@ pub fn skip_whitespace_and_comments<T: Iterator<Item = char>>(
             }
             Ok('\n') | Ok('\r') => {
                 consume_line_break(state)?;
+                indentation = true;
             }
             Ok('#') => {
                 skip_comment_line(state)?;
+                indentation = true;
             }
             _ => break,
         }
@@ -68,12 +92,9 @@ pub fn skip_comment_line<T: Iterator<Item = char>>(
     }
 
     // Skip to end of line
-    while let Ok(ch) = state.peek_char() {
-        if matches!(ch, '\n' | '\r') {
-            consume_line_break(state)?;
-            break;
-        }
-        state.consume_char()?;
+    state.skip_while(|c| !matches!(c, '\n' | '\r'));
+    if matches!(state.peek_char(), Ok('\n' | '\r')) {
+        consume_line_break(state)?;
     }
 
     Ok(())
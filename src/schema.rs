@@ -0,0 +1,275 @@
+//! Inferring a structural schema from sample documents
+//!
+//! [`infer`] walks a set of example documents, such as every config file of
+//! a fleet, and records which kinds of node appear at each path, which
+//! mapping keys are always present, and which strings only ever take a few
+//! values. The result is a starting point for validation rules, and
+//! serializes as YAML:
+//!
+//! ```rust
+//! use yyaml::Value;
+//!
+//! let samples: Vec<Value> = ["{env: prod, replicas: 3}", "{env: dev}", "{env: prod, replicas: 1}"]
+//!     .iter()
+//!     .map(|s| yyaml::from_str(s).unwrap())
+//!     .collect();
+//! let schema = yyaml::schema::infer(&samples);
+//! assert_eq!(
+//!     yyaml::to_string(&schema).unwrap(),
+//!     "---\nproperties: \n  env: \n    enum: \n      - dev\n      - prod\n    type: string\n  \
+//!      replicas: \n    optional: true\n    type: integer\ntype: mapping"
+//! );
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use serde::{Serialize, Serializer};
+
+use crate::pointer;
+use crate::pointer::value_key_token;
+use crate::value::{Mapping, Number, Value};
+
+/// Strings at one path are reported as an enum when they take at most this
+/// many distinct values and at least one of them repeats
+pub const MAX_ENUM_VALUES: usize = 8;
+
+/// The kind of a node, as recorded by [`infer`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SchemaType {
+    Null,
+    Boolean,
+    Integer,
+    Float,
+    String,
+    Sequence,
+    Mapping,
+}
+
+impl SchemaType {
+    /// The name used in the YAML form of a schema
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Null => "null",
+            Self::Boolean => "boolean",
+            Self::Integer => "integer",
+            Self::Float => "float",
+            Self::String => "string",
+            Self::Sequence => "sequence",
+            Self::Mapping => "mapping",
+        }
+    }
+
+    fn of(value: &Value) -> Self {
+        match value {
+            Value::Null => Self::Null,
+            Value::Bool(_) => Self::Boolean,
+            Value::Number(
+                Number::Integer(_)
+                | Number::Unsigned(_)
+                | Number::BigInteger(_)
+                | Number::BigUnsigned(_),
+            ) => Self::Integer,
+            Value::Number(Number::Float(_)) => Self::Float,
+            Value::String(_) => Self::String,
+            Value::Sequence(_) => Self::Sequence,
+            Value::Mapping(_) => Self::Mapping,
+            Value::Tagged(tagged) => Self::of(&tagged.value),
+        }
+    }
+}
+
+impl fmt::Display for SchemaType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// What the samples showed about the nodes at one path
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Schema {
+    /// Every kind of node seen here
+    pub types: BTreeSet<SchemaType>,
+    /// The values strings here took, if they look like an enum; empty
+    /// otherwise
+    pub enum_values: BTreeSet<String>,
+    /// Keys seen in mappings here
+    pub properties: BTreeMap<String, Property>,
+    /// Items of sequences here
+    pub items: Option<Box<Schema>>,
+}
+
+/// A mapping key in a [`Schema`]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Property {
+    pub schema: Schema,
+    /// Whether every mapping at this path had the key
+    pub required: bool,
+}
+
+impl Schema {
+    /// The schema of the nodes at `path`, a pointer in which `*` stands for
+    /// the items of a sequence
+    #[must_use]
+    pub fn at(&self, path: &str) -> Option<&Self> {
+        pointer::tokens(path).ok()?.iter().try_fold(self, |node, token| match &**token {
+            "*" => node.items.as_deref(),
+            key => node.properties.get(key).map(|property| &property.schema),
+        })
+    }
+
+    /// Every path in the schema with the kinds of node seen there, outermost
+    /// first; sequence items appear under `*`
+    #[must_use]
+    pub fn paths(&self) -> Vec<(String, &BTreeSet<SchemaType>)> {
+        let mut paths = Vec::new();
+        self.collect_paths(String::new(), &mut paths);
+        paths
+    }
+
+    fn collect_paths<'s>(&'s self, path: String, out: &mut Vec<(String, &'s BTreeSet<SchemaType>)>) {
+        out.push((path.clone(), &self.types));
+        for (key, property) in &self.properties {
+            property.schema.collect_paths(format!("{path}/{}", pointer::escape(key)), out);
+        }
+        if let Some(items) = &self.items {
+            items.collect_paths(format!("{path}/*"), out);
+        }
+    }
+
+    /// The schema as a value: `type` is one kind or a list of them, with
+    /// `enum`, `properties` and `items` where they apply, and
+    /// `optional: true` on keys some samples lacked
+    #[must_use]
+    pub fn to_value(&self) -> Value {
+        let mut out = Mapping::new();
+        let mut types = self.types.iter().map(|t| Value::String(t.as_str().to_string()));
+        let kind = match self.types.len() {
+            0 => None,
+            1 => types.next(),
+            _ => Some(Value::Sequence(types.collect())),
+        };
+        if let Some(kind) = kind {
+            out.insert(Value::String("type".into()), kind);
+        }
+        if !self.enum_values.is_empty() {
+            let values = self.enum_values.iter().cloned().map(Value::String).collect();
+            out.insert(Value::String("enum".into()), Value::Sequence(values));
+        }
+        if !self.properties.is_empty() {
+            let properties = self
+                .properties
+                .iter()
+                .map(|(key, property)| {
+                    let mut schema = property.schema.to_value();
+                    if let (false, Value::Mapping(map)) = (property.required, &mut schema) {
+                        map.insert(Value::String("optional".into()), Value::Bool(true));
+                    }
+                    (Value::String(key.clone()), schema)
+                })
+                .collect();
+            out.insert(Value::String("properties".into()), Value::Mapping(properties));
+        }
+        if let Some(items) = &self.items {
+            out.insert(Value::String("items".into()), items.to_value());
+        }
+        Value::Mapping(out)
+    }
+}
+
+impl Serialize for Schema {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_value().serialize(serializer)
+    }
+}
+
+/// Derive a [`Schema`] from `samples`, each one whole document.
+#[must_use]
+pub fn infer(samples: &[Value]) -> Schema {
+    let mut stats = Stats::default();
+    for sample in samples {
+        stats.record(sample);
+    }
+    stats.finish()
+}
+
+/// Counts gathered for one path before they become a [`Schema`]
+#[derive(Default)]
+struct Stats {
+    types: BTreeSet<SchemaType>,
+    /// Distinct strings, until there are too many to be an enum
+    strings: Option<BTreeSet<String>>,
+    string_count: usize,
+    mapping_count: usize,
+    /// Each key with the number of mappings it appeared in
+    properties: BTreeMap<String, (usize, Stats)>,
+    items: Option<Box<Stats>>,
+}
+
+impl Stats {
+    fn record(&mut self, value: &Value) {
+        // Tags are transparent
+        if let Value::Tagged(tagged) = value {
+            return self.record(&tagged.value);
+        }
+        self.types.insert(SchemaType::of(value));
+        match value {
+            Value::String(s) => {
+                if self.string_count == 0 {
+                    self.strings = Some(BTreeSet::new());
+                }
+                self.string_count += 1;
+                if let Some(strings) = &mut self.strings {
+                    strings.insert(s.clone());
+                    if strings.len() > MAX_ENUM_VALUES {
+                        self.strings = None;
+                    }
+                }
+            }
+            Value::Sequence(items) => {
+                let stats = self.items.get_or_insert_with(Box::default);
+                for item in items {
+                    stats.record(item);
+                }
+            }
+            Value::Mapping(map) => {
+                self.mapping_count += 1;
+                for (key, value) in map {
+                    let name = match key {
+                        Value::String(s) => s.clone(),
+                        other => value_key_token(other),
+                    };
+                    let (seen, stats) = self.properties.entry(name).or_default();
+                    *seen += 1;
+                    stats.record(value);
+                }
+            }
+            Value::Null | Value::Bool(_) | Value::Number(_) | Value::Tagged(_) => {}
+        }
+    }
+
+    fn finish(self) -> Schema {
+        let enum_values = match self.strings {
+            Some(strings) if strings.len() < self.string_count => strings,
+            _ => BTreeSet::new(),
+        };
+        let mapping_count = self.mapping_count;
+        Schema {
+            types: self.types,
+            enum_values,
+            properties: self
+                .properties
+                .into_iter()
+                .map(|(key, (seen, stats))| {
+                    let property = Property {
+                        schema: stats.finish(),
+                        required: seen == mapping_count,
+                    };
+                    (key, property)
+                })
+                .collect(),
+            items: self.items.map(|items| Box::new(items.finish())),
+        }
+    }
+}
@@ -4,12 +4,15 @@
 //! tag resolution, document validation, and reference tracking with zero-allocation
 //! design for blazing-fast YAML processing.
 
+use crate::diagnostics::{AnchorFinding, AnchorLint};
+use crate::lexer::Position;
 use crate::parser::ast::{Document, Node, Stream};
 use std::borrow::Cow;
 
 use super::{
     AnalysisContext, AnalysisMetrics, AnchorResolver, DocumentValidator, ProcessingPhase,
-    ReferenceTracker, SemanticConfig, SemanticError, SemanticOptimizations, TagResolver,
+    ReferenceTracker, SemanticConfig, SemanticError, SemanticOptimizations, SemanticWarning,
+    TagResolver,
 };
 
 /// High-performance semantic analyzer for YAML documents
@@ -55,8 +58,11 @@ impl<'input> SemanticAnalyzer<'input> {
         &mut self,
         stream: Stream<'input>,
     ) -> std::result::Result<crate::semantic::types::SemanticResult<'input>, SemanticError> {
+        trace_span!("yyaml.semantic", documents = stream.documents.len());
         let start_time = std::time::Instant::now();
 
+        let warnings = Self::lint_anchors(&stream);
+
         // Pre-analyze for optimization hints
         let _optimization_hints = SemanticOptimizations::estimate_buffer_sizes(&stream);
 
@@ -88,6 +94,12 @@ impl<'input> SemanticAnalyzer<'input> {
 
         let processing_time = start_time.elapsed();
         let doc_count = analyzed_documents.len();
+        trace_event!(
+            documents = doc_count,
+            anchors_resolved = self.anchor_resolver.resolved_count(),
+            tags_resolved = self.tag_resolver.resolved_count(),
+            "semantic analysis finished"
+        );
 
         Ok(crate::semantic::types::SemanticResult {
             documents: analyzed_documents,
@@ -103,15 +115,67 @@ impl<'input> SemanticAnalyzer<'input> {
                     .map(|r| r.cycles.len())
                     .unwrap_or(0),
             },
-            warnings: Vec::new(), // Collect warnings during analysis
+            warnings,
         })
     }
 
+    /// Collect anchor hygiene warnings, scoping anchors to their document
+    fn lint_anchors(stream: &Stream<'input>) -> Vec<SemanticWarning> {
+        fn walk(node: &Node<'_>, lint: &mut AnchorLint<Position>) {
+            match node {
+                Node::Anchor(anchor) => {
+                    lint.define(anchor.name.to_string(), anchor.position);
+                    walk(&anchor.node, lint);
+                }
+                Node::Alias(alias) => {
+                    lint.alias(&alias.name, alias.position);
+                }
+                Node::Tagged(tagged) => walk(&tagged.node, lint),
+                Node::Sequence(seq) => seq.items.iter().for_each(|item| walk(item, lint)),
+                Node::Mapping(map) => {
+                    for pair in &map.pairs {
+                        walk(&pair.key, lint);
+                        walk(&pair.value, lint);
+                    }
+                }
+                Node::Scalar(_) | Node::Null(_) => {}
+            }
+        }
+
+        let mut lint = AnchorLint::default();
+        for document in &stream.documents {
+            if let Some(root) = &document.content {
+                walk(root, &mut lint);
+            }
+            lint.end_document();
+        }
+        lint.into_findings()
+            .into_iter()
+            .map(|finding| match finding {
+                AnchorFinding::Unused { name, at } => SemanticWarning::unused_anchor(name, at),
+                AnchorFinding::Shadowed { name, at, previous } => SemanticWarning::ShadowedAnchor {
+                    anchor_name: name,
+                    position: at,
+                    previous_position: previous,
+                },
+                AnchorFinding::CrossDocument { name, at } => SemanticWarning::CrossDocumentAlias {
+                    alias_name: name,
+                    position: at,
+                },
+                AnchorFinding::Undefined { name, at } => SemanticWarning::UndefinedAlias {
+                    alias_name: name,
+                    position: at,
+                },
+            })
+            .collect()
+    }
+
     /// Perform semantic analysis on single document
     pub fn analyze_document(
         &mut self,
         document: Document<'input>,
     ) -> Result<Document<'input>, SemanticError> {
+        trace_span!("yyaml.semantic.document");
         // Estimate complexity for optimization
         if SemanticOptimizations::requires_complex_analysis(&document) {
             // Enable more thorough tracking for complex documents
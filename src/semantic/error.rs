@@ -149,7 +149,7 @@ impl SemanticError {
             } => {
                 format!(
                     "Duplicate anchor '{}' (first defined at line {}, column {})",
-                    anchor_name, first_position.line, first_position.column
+                    anchor_name, first_position.line, first_position.column()
                 )
             }
             Self::InvalidTagHandle { handle, .. } => {
@@ -14,6 +14,7 @@ pub mod types;
 // Existing semantic analysis modules
 pub mod anchors;
 pub mod references;
+pub mod summary;
 pub mod tags;
 pub mod validation;
 
@@ -33,9 +34,10 @@ pub use types::{
 // Re-export existing semantic analysis functionality with specific exports to avoid conflicts
 pub use anchors::{AnchorDefinition, AnchorRegistry, AnchorResolver};
 pub use references::{ReferenceGraph, ReferenceTracker};
+pub use summary::{analyze_str, analyze_yaml};
 pub use tags::{TagRegistry as TagRegistryType, TagResolver};
 pub use validation::{
-    DocumentValidator, ValidationRule, ValidationRuleSet,
+    DocumentValidator, KeyNamingConvention, KeyOrderingRule, ValidationRule, ValidationRuleSet,
     WarningSeverity as ValidationWarningSeverity,
 };
 
@@ -171,6 +173,92 @@ mod tests {
         assert!(hints.estimated_nodes > 0);
     }
 
+    #[test]
+    fn test_anchor_lint_warnings() {
+        use crate::parser::ast::{AnchorNode, SequenceNode, SequenceStyle};
+
+        let anchored = |value: &'static str, line: usize| {
+            Node::Anchor(AnchorNode::new(
+                "item".into(),
+                Box::new(Node::Scalar(ScalarNode {
+                    value: value.into(),
+                    style: ScalarStyle::Plain,
+                    tag: None,
+                    position: Position::from_parts(0, 0, line, 8),
+                })),
+                Position::from_parts(0, 0, line, 2),
+            ))
+        };
+        let items = vec![anchored("first", 1), anchored("second", 2)];
+        let document = Document {
+            content: Some(Node::Sequence(SequenceNode::new(
+                items,
+                SequenceStyle::Block,
+                Position::from_parts(0, 0, 1, 0),
+            ))),
+            has_explicit_start: false,
+            has_explicit_end: false,
+            position: Position::default(),
+        };
+
+        let warnings = match analyze_stream(Stream {
+            documents: vec![document],
+        }) {
+            Ok(result) => result.warnings,
+            Err(e) => panic!("analysis failed: {e}"),
+        };
+        assert_eq!(
+            warnings,
+            vec![
+                SemanticWarning::unused_anchor("item".to_string(), Position::from_parts(0, 0, 1, 2)),
+                SemanticWarning::ShadowedAnchor {
+                    anchor_name: "item".to_string(),
+                    position: Position::from_parts(0, 0, 2, 2),
+                    previous_position: Position::from_parts(0, 0, 1, 2),
+                },
+                SemanticWarning::unused_anchor("item".to_string(), Position::from_parts(0, 0, 2, 2)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_analyze_str_summary() {
+        let summary = match analyze_str("a: &x !local 1\nb: *x\nc: &unused 2\n---\nd: *x\n") {
+            Ok(summary) => summary,
+            Err(e) => panic!("analysis failed: {e}"),
+        };
+        assert_eq!(summary.documents_count, 2);
+        assert_eq!(summary.anchors_resolved, 2);
+        assert_eq!(summary.aliases_resolved, 1);
+        assert_eq!(summary.tags_resolved, 1);
+        // Unused anchor plus the cross-document alias
+        assert_eq!(summary.warnings_count, 2);
+
+        for (stream, documents) in [
+            ("--- a\n--- b\n", 2),
+            ("%YAML 1.2\n--- a\n...\n%TAG !e! tag:example.com,2000:\n--- b\n", 2),
+        ] {
+            match analyze_str(stream) {
+                Ok(summary) => assert_eq!(summary.documents_count, documents, "{stream:?}"),
+                Err(e) => panic!("analysis failed: {e}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_analyze_yaml_summary() {
+        let yaml = crate::Yaml::Array(vec![
+            crate::Yaml::Tagged("!foo".into(), Box::new(crate::Yaml::Integer(1))),
+            crate::Yaml::Alias(0),
+            crate::Yaml::Alias(0),
+        ]);
+        let summary = analyze_yaml(&yaml);
+        assert_eq!(summary.documents_count, 1);
+        assert_eq!(summary.tags_resolved, 1);
+        assert_eq!(summary.aliases_resolved, 2);
+        assert_eq!(summary.anchors_resolved, 1);
+    }
+
     #[test]
     fn test_analysis_metrics_recording() {
         let mut metrics = AnalysisMetrics::default();
@@ -0,0 +1,114 @@
+//! Semantic telemetry for loaded values and raw YAML text
+//!
+//! The full [`SemanticAnalyzer`](super::SemanticAnalyzer) operates on the
+//! internal AST. These entry points produce the same [`ProcessingSummary`]
+//! from the types applications actually hold.
+
+use super::ProcessingSummary;
+use crate::diagnostics::AnchorLint;
+use crate::events::TokenType;
+use crate::scanner::Scanner;
+use crate::yaml::Yaml;
+use crate::Error;
+use std::collections::HashSet;
+use std::time::Instant;
+
+/// Summarize a loaded document.
+///
+/// Anchors are already resolved in a [`Yaml`] tree, so `anchors_resolved`
+/// counts distinct alias targets still present as [`Yaml::Alias`] nodes.
+#[must_use]
+pub fn analyze_yaml(yaml: &Yaml) -> ProcessingSummary {
+    fn walk(node: &Yaml, summary: &mut ProcessingSummary, targets: &mut HashSet<usize>) {
+        match node {
+            Yaml::Tagged(_, inner) => {
+                summary.tags_resolved += 1;
+                walk(inner, summary, targets);
+            }
+            Yaml::Alias(id) => {
+                summary.aliases_resolved += 1;
+                targets.insert(*id);
+            }
+            Yaml::Array(items) => items.iter().for_each(|item| walk(item, summary, targets)),
+            Yaml::Hash(map) => {
+                for (key, value) in map.iter() {
+                    walk(key, summary, targets);
+                    walk(value, summary, targets);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let start = Instant::now();
+    let mut summary = empty_summary();
+    summary.documents_count = 1;
+    let mut targets = HashSet::new();
+    walk(yaml, &mut summary, &mut targets);
+    summary.anchors_resolved = targets.len();
+    summary.processing_time = start.elapsed();
+    summary
+}
+
+/// Summarize every document in `s` from its token stream.
+///
+/// `warnings_count` includes the anchor hygiene findings reported by
+/// [`crate::diagnostics::lint_anchors`].
+pub fn analyze_str(s: &str) -> Result<ProcessingSummary, Error> {
+    let start = Instant::now();
+    let mut summary = empty_summary();
+    let mut lint = AnchorLint::default();
+    let mut scanner = Scanner::over_str(s);
+    let mut has_content = false;
+    loop {
+        let token = scanner.peek_token()?;
+        scanner.skip();
+        match token.1 {
+            TokenType::StreamStart(_) => continue,
+            TokenType::StreamEnd | TokenType::NoToken => break,
+            TokenType::DocumentStart => {
+                lint.end_document();
+                summary.documents_count += 1;
+                has_content = true;
+                continue;
+            }
+            TokenType::DocumentEnd => {
+                lint.end_document();
+                has_content = false;
+                continue;
+            }
+            // Directives belong to the `---` that must follow them
+            TokenType::VersionDirective(..) | TokenType::TagDirective(..) => continue,
+            TokenType::Anchor(name) => {
+                summary.anchors_resolved += 1;
+                lint.define(name, token.0);
+            }
+            TokenType::Alias(name) => {
+                summary.aliases_resolved += usize::from(lint.alias(&name, token.0));
+            }
+            TokenType::Tag(..) => summary.tags_resolved += 1,
+            _ => {}
+        }
+        // Bare documents have no `---`; count them at their first token
+        if !has_content {
+            has_content = true;
+            summary.documents_count += 1;
+        }
+    }
+    lint.end_document();
+    summary.warnings_count = lint.findings().len();
+    summary.processing_time = start.elapsed();
+    Ok(summary)
+}
+
+const fn empty_summary() -> ProcessingSummary {
+    ProcessingSummary {
+        documents_count: 0,
+        warnings_count: 0,
+        processing_time: std::time::Duration::ZERO,
+        anchors_resolved: 0,
+        aliases_resolved: 0,
+        tags_resolved: 0,
+        cycles_detected: 0,
+    }
+}
@@ -404,7 +404,7 @@ mod tests {
             Some(Cow::Borrowed("!!")),
             Cow::Borrowed("str"),
             YamlType::Str,
-            Position::new(1, 1, 0),
+            Position::from_parts(0, 0, 1, 0),
         );
 
         registry.add_tag(tag);
@@ -431,7 +431,7 @@ mod tests {
             Some(Cow::Borrowed("!!")),
             Cow::Borrowed("int"),
             YamlType::Int,
-            Position::new(1, 1, 0),
+            Position::from_parts(0, 0, 1, 0),
         );
 
         registry.add_tag(tag);
@@ -456,7 +456,7 @@ mod tests {
             Some(Cow::Borrowed("!!")),
             Cow::Borrowed("str"),
             YamlType::Str,
-            Position::new(1, 1, 0),
+            Position::from_parts(0, 0, 1, 0),
         );
 
         let int_tag = ResolvedTag::new(
@@ -465,7 +465,7 @@ mod tests {
             Some(Cow::Borrowed("!!")),
             Cow::Borrowed("int"),
             YamlType::Int,
-            Position::new(1, 1, 0),
+            Position::from_parts(0, 0, 1, 0),
         );
 
         registry.add_tag(str_tag);
@@ -488,7 +488,7 @@ mod tests {
                 Some(Cow::Borrowed("!!")),
                 Cow::Owned(format!("type{i}")),
                 YamlType::Str,
-                Position::new(1, 1, 0),
+                Position::from_parts(0, 0, 1, 0),
             );
             registry.add_tag(tag);
         }
@@ -510,7 +510,7 @@ mod tests {
             Some(Cow::Borrowed("!!")),
             Cow::Borrowed("str"),
             YamlType::Str,
-            Position::new(1, 1, 0),
+            Position::from_parts(0, 0, 1, 0),
         );
 
         assert_eq!(tag.resolved_type, YamlType::Str);
@@ -50,6 +50,25 @@ pub enum SemanticWarning {
         position: crate::lexer::Position,
     },
 
+    /// Anchor name reused within a document, hiding the earlier definition
+    ShadowedAnchor {
+        anchor_name: String,
+        position: crate::lexer::Position,
+        previous_position: crate::lexer::Position,
+    },
+
+    /// Alias naming an anchor that only exists in an earlier document
+    CrossDocumentAlias {
+        alias_name: String,
+        position: crate::lexer::Position,
+    },
+
+    /// Alias naming an anchor that is never defined
+    UndefinedAlias {
+        alias_name: String,
+        position: crate::lexer::Position,
+    },
+
     /// Deprecated tag usage
     DeprecatedTag {
         tag: String,
@@ -128,12 +147,19 @@ impl<'input> SemanticResult<'input> {
 /// Summary of processing results for quick assessment
 #[derive(Debug, Clone, Copy)]
 pub struct ProcessingSummary {
+    /// Documents in the stream
     pub documents_count: usize,
+    /// Warnings raised while analyzing
     pub warnings_count: usize,
+    /// Time spent producing the summary
     pub processing_time: Duration,
+    /// Anchors seen
     pub anchors_resolved: usize,
+    /// Alias nodes seen
     pub aliases_resolved: usize,
+    /// Tagged nodes seen
     pub tags_resolved: usize,
+    /// Alias cycles found
     pub cycles_detected: usize,
 }
 
@@ -200,6 +226,9 @@ impl SemanticWarning {
     pub const fn position(&self) -> crate::lexer::Position {
         match self {
             Self::UnusedAnchor { position, .. } => *position,
+            Self::ShadowedAnchor { position, .. } => *position,
+            Self::CrossDocumentAlias { position, .. } => *position,
+            Self::UndefinedAlias { position, .. } => *position,
             Self::DeprecatedTag { position, .. } => *position,
             Self::InefficiencyWarning { position, .. } => *position,
             Self::CustomValidationWarning { position, .. } => *position,
@@ -213,6 +242,15 @@ impl SemanticWarning {
             Self::UnusedAnchor { anchor_name, .. } => {
                 format!("Unused anchor definition: '{anchor_name}'")
             }
+            Self::ShadowedAnchor { anchor_name, .. } => {
+                format!("Anchor '{anchor_name}' shadows an earlier definition")
+            }
+            Self::CrossDocumentAlias { alias_name, .. } => {
+                format!("Alias '{alias_name}' refers to an anchor in a previous document")
+            }
+            Self::UndefinedAlias { alias_name, .. } => {
+                format!("Alias '{alias_name}' refers to an undefined anchor")
+            }
             Self::DeprecatedTag {
                 tag,
                 suggested_replacement,
@@ -8,6 +8,7 @@ pub mod constraints;
 pub mod context;
 pub mod fixes;
 pub mod metrics;
+pub mod ordering;
 pub mod rules;
 pub mod validator;
 pub mod warnings;
@@ -17,6 +18,7 @@ pub use constraints::{ConstraintChecker, ConstraintRule, StructureConstraints, T
 pub use context::ValidationContext;
 pub use fixes::{FixImpact, FixType, ValidationFix};
 pub use metrics::{ComplexityMetrics, OptimizationDifficulty, OptimizationHint, OptimizationType};
+pub use ordering::{KeyNamingConvention, KeyOrderingRule};
 pub use rules::{ValidationRule, ValidationRuleSet};
 pub use validator::{DocumentValidator, ValidationStatistics};
 pub use warnings::{ValidationWarning, ValidationWarningContext, WarningSeverity, WarningType};
@@ -0,0 +1,418 @@
+//! Key ordering and naming policy rule
+//!
+//! Enforces house-style policies such as "apiVersion, kind, metadata, spec
+//! first" for Kubernetes manifests. Orderings are chosen per document kind,
+//! read from a discriminator key on the root mapping.
+
+use super::context::ValidationContext;
+use super::rules::ValidationRule;
+use super::warnings::{ValidationWarning, ValidationWarningContext, WarningSeverity, WarningType};
+use crate::lexer::Position;
+use crate::parser::ast::{MappingNode, Node};
+use crate::semantic::{AnalysisContext, SemanticError};
+use std::collections::HashMap;
+
+/// Naming convention enforced on mapping keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyNamingConvention {
+    /// `apiVersion`
+    CamelCase,
+    /// `api_version`
+    SnakeCase,
+    /// `api-version`
+    KebabCase,
+}
+
+impl KeyNamingConvention {
+    /// Check whether `key` follows this convention
+    #[must_use]
+    pub fn matches(self, key: &str) -> bool {
+        let mut chars = key.chars();
+        let Some(first) = chars.next() else {
+            return true;
+        };
+        match self {
+            Self::CamelCase => {
+                first.is_ascii_lowercase() && chars.all(|c| c.is_ascii_alphanumeric())
+            }
+            Self::SnakeCase => {
+                first.is_ascii_lowercase()
+                    && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+            }
+            Self::KebabCase => {
+                first.is_ascii_lowercase()
+                    && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for KeyNamingConvention {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::CamelCase => write!(f, "camelCase"),
+            Self::SnakeCase => write!(f, "snake_case"),
+            Self::KebabCase => write!(f, "kebab-case"),
+        }
+    }
+}
+
+/// Validation rule enforcing root key order per document kind and,
+/// optionally, a naming convention for every mapping key
+#[derive(Debug, Clone)]
+pub struct KeyOrderingRule {
+    discriminator: String,
+    orderings: HashMap<String, Vec<String>>,
+    default_ordering: Vec<String>,
+    naming: Option<KeyNamingConvention>,
+    free_form: Vec<String>,
+    severity: WarningSeverity,
+}
+
+impl Default for KeyOrderingRule {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl KeyOrderingRule {
+    /// Create a rule with no orderings that reads the document kind from `kind`
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            discriminator: "kind".to_string(),
+            orderings: HashMap::new(),
+            default_ordering: Vec::new(),
+            naming: None,
+            free_form: Vec::new(),
+            severity: WarningSeverity::Low,
+        }
+    }
+
+    /// Preset for Kubernetes manifests: `apiVersion, kind, metadata` first,
+    /// followed by `spec`/`data` depending on the kind, with camelCase keys
+    /// outside labels, annotations, selectors and `data`
+    #[must_use]
+    pub fn kubernetes() -> Self {
+        Self::new()
+            .with_default_ordering(["apiVersion", "kind", "metadata", "spec"])
+            .with_ordering("ConfigMap", ["apiVersion", "kind", "metadata", "data"])
+            .with_ordering("Secret", ["apiVersion", "kind", "metadata", "type", "data"])
+            .with_naming(KeyNamingConvention::CamelCase)
+            .with_free_form_keys([
+                "labels",
+                "annotations",
+                "matchLabels",
+                "nodeSelector",
+                "data",
+                "stringData",
+                "binaryData",
+            ])
+    }
+
+    /// Read the document kind from `key` on the root mapping
+    #[must_use]
+    pub fn with_discriminator(mut self, key: impl Into<String>) -> Self {
+        self.discriminator = key.into();
+        self
+    }
+
+    /// Keys that must lead, in order, for documents of `kind`
+    #[must_use]
+    pub fn with_ordering<I, S>(mut self, kind: impl Into<String>, keys: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.orderings
+            .insert(kind.into(), keys.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Ordering for documents whose kind has no specific ordering
+    #[must_use]
+    pub fn with_default_ordering<I, S>(mut self, keys: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.default_ordering = keys.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Require every mapping key to follow `convention`
+    #[must_use]
+    pub const fn with_naming(mut self, convention: KeyNamingConvention) -> Self {
+        self.naming = Some(convention);
+        self
+    }
+
+    /// Exempt the mappings under `keys` from the naming convention, for
+    /// maps such as labels whose keys are chosen by the user
+    #[must_use]
+    pub fn with_free_form_keys<I, S>(mut self, keys: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.free_form.extend(keys.into_iter().map(Into::into));
+        self
+    }
+
+    /// Severity attached to produced warnings
+    #[must_use]
+    pub const fn with_severity(mut self, severity: WarningSeverity) -> Self {
+        self.severity = severity;
+        self
+    }
+
+    fn ordering_for(&self, map: &MappingNode<'_>) -> &[String] {
+        map.get(&self.discriminator)
+            .and_then(Node::as_scalar)
+            .and_then(|kind| self.orderings.get(kind.as_str()))
+            .unwrap_or(&self.default_ordering)
+    }
+
+    fn check_order<'input>(
+        &self,
+        map: &MappingNode<'input>,
+        context: &mut ValidationContext,
+        warnings: &mut Vec<ValidationWarning<'input>>,
+    ) {
+        let ordering = self.ordering_for(map);
+        if ordering.is_empty() {
+            return;
+        }
+        let rank = |key: &str| {
+            ordering
+                .iter()
+                .position(|k| k == key)
+                .unwrap_or(ordering.len())
+        };
+
+        // Highest-ranked key seen so far; any later key ranking below it is misplaced
+        let mut latest: Option<(usize, &str, Position)> = None;
+        for (key, position) in scalar_keys(map) {
+            let key_rank = rank(key);
+            match latest {
+                Some((latest_rank, latest_key, latest_position)) if key_rank < latest_rank => {
+                    context.increment_warnings();
+                    warnings.push(self.warning(
+                        context,
+                        "key_ordering",
+                        format!("key '{key}' should appear before '{latest_key}'"),
+                        position,
+                        Some(format!("expected order: {}", ordering.join(", "))),
+                        vec![latest_position],
+                    ));
+                }
+                Some((latest_rank, ..)) if key_rank <= latest_rank => {}
+                _ => latest = Some((key_rank, key, position)),
+            }
+        }
+    }
+
+    fn check_naming<'input>(
+        &self,
+        map: &MappingNode<'input>,
+        context: &mut ValidationContext,
+        warnings: &mut Vec<ValidationWarning<'input>>,
+    ) {
+        let Some(convention) = self.naming else {
+            return;
+        };
+        for (key, position) in scalar_keys(map) {
+            if !convention.matches(key) {
+                context.increment_warnings();
+                warnings.push(self.warning(
+                    context,
+                    "key_naming",
+                    format!("key '{key}' is not {convention}"),
+                    position,
+                    None,
+                    Vec::new(),
+                ));
+            }
+        }
+    }
+
+    fn warning<'input>(
+        &self,
+        context: &ValidationContext,
+        rule_name: &str,
+        message: String,
+        position: Position,
+        suggestion: Option<String>,
+        related_nodes: Vec<Position>,
+    ) -> ValidationWarning<'input> {
+        ValidationWarning {
+            warning_type: WarningType::StyleViolation,
+            message,
+            position,
+            severity: self.severity,
+            rule_name: rule_name.to_string(),
+            suggestion,
+            context: ValidationWarningContext {
+                node_path: context.validation_path.clone(),
+                node_type: None,
+                related_nodes,
+                constraint_violated: Some(rule_name.to_string()),
+                suggested_fix: None,
+            },
+        }
+    }
+}
+
+fn scalar_keys<'a>(map: &'a MappingNode<'_>) -> impl Iterator<Item = (&'a str, Position)> {
+    map.pairs
+        .iter()
+        .filter_map(|pair| pair.key.as_scalar().map(|key| (key.as_str(), key.position)))
+}
+
+impl<'input> ValidationRule<'input> for KeyOrderingRule {
+    fn name(&self) -> &str {
+        "key_ordering"
+    }
+
+    fn description(&self) -> &str {
+        "Enforces per-kind root key ordering and key naming conventions"
+    }
+
+    fn validate(
+        &self,
+        node: &Node<'input>,
+        context: &mut ValidationContext,
+        _analysis_context: &AnalysisContext<'input>,
+    ) -> Result<Vec<ValidationWarning<'input>>, SemanticError> {
+        let mut warnings = Vec::new();
+        if let Node::Mapping(map) = node {
+            // Orderings describe document kinds, so they only apply at the root
+            if context.validation_path.is_empty() {
+                self.check_order(map, context, &mut warnings);
+            }
+            let free_form = context
+                .validation_path
+                .last()
+                .is_some_and(|key| self.free_form.contains(key));
+            if !free_form {
+                self.check_naming(map, context, &mut warnings);
+            }
+        }
+        Ok(warnings)
+    }
+
+    fn is_applicable(&self, node: &Node<'input>) -> bool {
+        node.is_mapping()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::ast::{Document, MappingPair, MappingStyle, ScalarNode};
+    use crate::lexer::ScalarStyle;
+    use crate::semantic::validation::{DocumentValidator, ValidationRuleSet};
+    use std::borrow::Cow;
+
+    fn scalar(value: &'static str, line: usize) -> Node<'static> {
+        Node::Scalar(ScalarNode::new(
+            Cow::Borrowed(value),
+            ScalarStyle::Plain,
+            None,
+            Position::from_parts(0, 0, line, 0),
+        ))
+    }
+
+    fn mapping(pairs: &[(&'static str, Node<'static>)]) -> Node<'static> {
+        let pairs = pairs
+            .iter()
+            .enumerate()
+            .map(|(i, (key, value))| MappingPair::new(scalar(key, i + 1), value.clone()))
+            .collect();
+        Node::Mapping(MappingNode::new(
+            pairs,
+            MappingStyle::Block,
+            Position::from_parts(0, 0, 1, 0),
+        ))
+    }
+
+    fn manifest(pairs: &[(&'static str, Node<'static>)]) -> Document<'static> {
+        let root = mapping(pairs);
+        Document::new(Some(root), false, false, Position::from_parts(0, 0, 1, 0))
+    }
+
+    fn validate(document: &Document<'static>) -> Vec<ValidationWarning<'static>> {
+        let mut rules = ValidationRuleSet::new();
+        rules.add_custom_rule(Box::new(KeyOrderingRule::kubernetes()));
+        let mut validator = DocumentValidator::with_rules(rules);
+        validator
+            .validate_document(document, &AnalysisContext::new())
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|w| w.rule_name != "validation_summary")
+            .collect()
+    }
+
+    #[test]
+    fn test_ordered_manifest_has_no_warnings() {
+        let doc = manifest(&[
+            ("apiVersion", scalar("v1", 1)),
+            ("kind", scalar("Pod", 2)),
+            ("metadata", scalar("x", 3)),
+            ("spec", scalar("y", 4)),
+            ("status", scalar("z", 5)),
+        ]);
+        assert!(validate(&doc).is_empty());
+    }
+
+    #[test]
+    fn test_misplaced_keys_are_positioned() {
+        let doc = manifest(&[
+            ("kind", scalar("Pod", 1)),
+            ("spec", scalar("y", 2)),
+            ("apiVersion", scalar("v1", 3)),
+            ("metadata", scalar("x", 4)),
+        ]);
+        let warnings = validate(&doc);
+        let messages: Vec<_> = warnings.iter().map(|w| w.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            [
+                "key 'apiVersion' should appear before 'spec'",
+                "key 'metadata' should appear before 'spec'",
+            ]
+        );
+        assert_eq!(warnings[0].position.line, 3);
+        assert_eq!(warnings[0].context.related_nodes[0].line, 2);
+    }
+
+    #[test]
+    fn test_ordering_per_kind_and_naming() {
+        let doc = manifest(&[
+            ("apiVersion", scalar("v1", 1)),
+            ("kind", scalar("ConfigMap", 2)),
+            ("data", scalar("x", 3)),
+            ("metadata", scalar("y", 4)),
+            ("extra_field", scalar("z", 5)),
+        ]);
+        let warnings = validate(&doc);
+        let rules: Vec<_> = warnings.iter().map(|w| w.rule_name.as_str()).collect();
+        assert_eq!(rules, ["key_ordering", "key_naming"]);
+        assert_eq!(warnings[0].message, "key 'metadata' should appear before 'data'");
+        assert_eq!(warnings[1].message, "key 'extra_field' is not camelCase");
+    }
+
+    #[test]
+    fn test_free_form_maps_skip_naming() {
+        let labels = mapping(&[("app.kubernetes.io/name", scalar("web", 5))]);
+        let metadata = mapping(&[("labels", labels), ("generate_name", scalar("web-", 6))]);
+        let doc = manifest(&[
+            ("apiVersion", scalar("v1", 1)),
+            ("kind", scalar("ConfigMap", 2)),
+            ("metadata", metadata),
+            ("data", mapping(&[("app.properties", scalar("x", 7))])),
+        ]);
+        let messages: Vec<_> = validate(&doc).into_iter().map(|w| w.message).collect();
+        assert_eq!(messages, ["key 'generate_name' is not camelCase"]);
+    }
+}
@@ -1,6 +1,144 @@
-use crate::{Error, LinkedHashMap, Yaml};
+use crate::pointer::{self, PathPattern};
+use crate::tags::{NULL, SET};
+use crate::{Error, LinkedHashMap, Yaml, YamlEmitter};
 use serde::ser::{self, SerializeMap};
 
+/// Spell a float so it reads back as one: Rust prints `100.0` as `100` and
+/// infinity as `inf`, which YAML would resolve to an integer and a string
+pub(crate) fn float_text(text: String, v: f64) -> String {
+    if v.is_nan() {
+        ".nan".to_string()
+    } else if v.is_infinite() {
+        if v > 0.0 { ".inf" } else { "-.inf" }.to_string()
+    } else if text.contains(['.', 'e', 'E']) {
+        text
+    } else {
+        text + ".0"
+    }
+}
+
+/// Text written in place of a redacted value
+const REDACTED: &str = "***";
+
+/// Options for [`to_string_with_config`](crate::to_string_with_config)
+#[derive(Clone, Debug, Default)]
+pub struct SerializerConfig {
+    redact: Vec<PathPattern>,
+    float_precision: Option<usize>,
+    whole_floats_as_integers: bool,
+    tag_some_null: bool,
+}
+
+impl SerializerConfig {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Write `***` in place of every value whose path matches one of
+    /// `patterns`, collections included, so configs holding secrets can be
+    /// logged. Mapping keys are kept.
+    ///
+    /// ```rust
+    /// use std::collections::BTreeMap;
+    /// use yyaml::{PathPattern, SerializerConfig};
+    ///
+    /// let config = SerializerConfig::new()
+    ///     .redact_paths(vec![PathPattern::new("/**/password").unwrap()]);
+    /// let mut db = BTreeMap::new();
+    /// db.insert("password", "hunter2");
+    /// db.insert("user", "app");
+    /// let mut root = BTreeMap::new();
+    /// root.insert("db", db);
+    /// let text = yyaml::to_string_with_config(&root, &config).unwrap();
+    /// assert_eq!(text, "---\ndb: \n  password: \"***\"\n  user: app");
+    /// ```
+    #[must_use]
+    pub fn redact_paths(mut self, patterns: Vec<PathPattern>) -> Self {
+        self.redact.extend(patterns);
+        self
+    }
+
+    /// Write floats with `digits` digits after the point, as `0.30` for
+    /// `0.1 + 0.2` with two, instead of the shortest text that reads back
+    /// as the same float
+    #[must_use]
+    pub const fn float_precision(mut self, digits: usize) -> Self {
+        self.float_precision = Some(digits);
+        self
+    }
+
+    /// Write floats with a whole value without a point, as `1` for `1.0`,
+    /// the way versions before floats always kept one did. Such text reads
+    /// back as an integer, so typed round-trips of floats break.
+    #[must_use]
+    pub const fn whole_floats_as_integers(mut self, enabled: bool) -> Self {
+        self.whole_floats_as_integers = enabled;
+        self
+    }
+
+    /// Write `Some` of a null, such as `Some(None)` or `Some(())`, as
+    /// `!!null ~` so it reads back apart from `None`; see
+    /// [`ConfiguredSerializer::tag_some_null`]
+    #[must_use]
+    pub const fn tag_some_null(mut self, enabled: bool) -> Self {
+        self.tag_some_null = enabled;
+        self
+    }
+
+    /// The serializer these options call for
+    pub(crate) fn serializer(&self) -> ConfiguredSerializer {
+        let serializer = ConfiguredSerializer::new()
+            .whole_floats_as_integers(self.whole_floats_as_integers)
+            .tag_some_null(self.tag_some_null);
+        match self.float_precision {
+            Some(digits) => serializer.float_precision(digits),
+            None => serializer,
+        }
+    }
+
+    /// `emitter` set up as these options call for
+    pub(crate) const fn emitter<'a>(&self, mut emitter: YamlEmitter<'a>) -> YamlEmitter<'a> {
+        emitter.whole_floats_as_integers = self.whole_floats_as_integers;
+        emitter
+    }
+
+    /// Apply the configured redactions to a serialized tree
+    pub(crate) fn redact(&self, node: &mut Yaml) {
+        if !self.redact.is_empty() {
+            redact_node(&self.redact, node, &mut Vec::new());
+        }
+    }
+}
+
+fn redact_node(patterns: &[PathPattern], node: &mut Yaml, path: &mut Vec<String>) {
+    if patterns.iter().any(|pattern| pattern.matches_tokens(path)) {
+        *node = Yaml::String(REDACTED.to_string());
+        return;
+    }
+    match node {
+        Yaml::Array(items) => {
+            for (i, item) in items.iter_mut().enumerate() {
+                path.push(i.to_string());
+                redact_node(patterns, item, path);
+                path.pop();
+            }
+        }
+        Yaml::Hash(map) => {
+            let mut redacted = LinkedHashMap::new();
+            for (key, mut value) in std::mem::take(map) {
+                path.push(pointer::key_name(&key));
+                redact_node(patterns, &mut value, path);
+                path.pop();
+                redacted.insert(key, value);
+            }
+            *map = redacted;
+        }
+        Yaml::Tagged(_, inner) => redact_node(patterns, inner, path),
+        _ => {}
+    }
+}
+
 pub struct YamlSerializer;
 
 impl Default for YamlSerializer {
@@ -10,10 +148,47 @@ impl Default for YamlSerializer {
 }
 
 impl YamlSerializer {
-    #[must_use] 
+    #[must_use]
     pub const fn new() -> Self {
         Self
     }
+
+    /// A [`ConfiguredSerializer`] with [`human_readable`](ConfiguredSerializer::human_readable) set
+    #[must_use]
+    pub const fn human_readable(self, human_readable: bool) -> ConfiguredSerializer {
+        ConfiguredSerializer::new().human_readable(human_readable)
+    }
+
+    /// A [`ConfiguredSerializer`] with [`float_precision`](ConfiguredSerializer::float_precision) set
+    #[must_use]
+    pub const fn float_precision(self, digits: usize) -> ConfiguredSerializer {
+        ConfiguredSerializer::new().float_precision(digits)
+    }
+
+    /// A [`ConfiguredSerializer`] with
+    /// [`whole_floats_as_integers`](ConfiguredSerializer::whole_floats_as_integers) set
+    #[must_use]
+    pub const fn whole_floats_as_integers(self, enabled: bool) -> ConfiguredSerializer {
+        ConfiguredSerializer::new().whole_floats_as_integers(enabled)
+    }
+
+    /// A [`ConfiguredSerializer`] with [`tag_some_null`](ConfiguredSerializer::tag_some_null) set
+    #[must_use]
+    pub const fn tag_some_null(self, enabled: bool) -> ConfiguredSerializer {
+        ConfiguredSerializer::new().tag_some_null(enabled)
+    }
+}
+
+/// Implement each `serialize_*` method of [`YamlSerializer`] by handing it
+/// to a [`ConfiguredSerializer`] with the default options
+macro_rules! forward_to_configured {
+    ($($method:ident($($arg:ident: $ty:ty),*) -> $ret:ty;)*) => {
+        $(
+            fn $method(self, $($arg: $ty),*) -> Result<$ret, Error> {
+                ConfiguredSerializer::new().$method($($arg),*)
+            }
+        )*
+    };
 }
 
 impl ser::Serializer for YamlSerializer {
@@ -28,6 +203,171 @@ impl ser::Serializer for YamlSerializer {
     type SerializeStruct = MapSerializer;
     type SerializeStructVariant = StructVariantSerializer;
 
+    forward_to_configured! {
+        serialize_bool(v: bool) -> Yaml;
+        serialize_i8(v: i8) -> Yaml;
+        serialize_i16(v: i16) -> Yaml;
+        serialize_i32(v: i32) -> Yaml;
+        serialize_i64(v: i64) -> Yaml;
+        serialize_i128(v: i128) -> Yaml;
+        serialize_u8(v: u8) -> Yaml;
+        serialize_u16(v: u16) -> Yaml;
+        serialize_u32(v: u32) -> Yaml;
+        serialize_u64(v: u64) -> Yaml;
+        serialize_u128(v: u128) -> Yaml;
+        serialize_f32(v: f32) -> Yaml;
+        serialize_f64(v: f64) -> Yaml;
+        serialize_char(v: char) -> Yaml;
+        serialize_str(v: &str) -> Yaml;
+        serialize_bytes(v: &[u8]) -> Yaml;
+        serialize_none() -> Yaml;
+        serialize_unit() -> Yaml;
+        serialize_unit_struct(name: &'static str) -> Yaml;
+        serialize_unit_variant(name: &'static str, variant_index: u32, variant: &'static str) -> Yaml;
+        serialize_seq(len: Option<usize>) -> VecSerializer;
+        serialize_tuple(len: usize) -> VecSerializer;
+        serialize_tuple_struct(name: &'static str, len: usize) -> VecSerializer;
+        serialize_tuple_variant(
+            name: &'static str,
+            variant_index: u32,
+            variant: &'static str,
+            len: usize
+        ) -> TupleVariantSerializer;
+        serialize_map(len: Option<usize>) -> MapSerializer;
+        serialize_struct(name: &'static str, len: usize) -> MapSerializer;
+        serialize_struct_variant(
+            name: &'static str,
+            variant_index: u32,
+            variant: &'static str,
+            len: usize
+        ) -> StructVariantSerializer;
+    }
+
+    fn serialize_some<T: ?Sized + ser::Serialize>(self, value: &T) -> Result<Yaml, Error> {
+        ConfiguredSerializer::new().serialize_some(value)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + ser::Serialize>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Yaml, Error> {
+        ConfiguredSerializer::new().serialize_newtype_struct(name, value)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + ser::Serialize>(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Yaml, Error> {
+        ConfiguredSerializer::new().serialize_newtype_variant(name, variant_index, variant, value)
+    }
+}
+
+/// A [`YamlSerializer`] with options: how floats are written, whether
+/// `Some` of a null is tagged, and what
+/// [`is_human_readable`](ser::Serializer::is_human_readable) reports
+#[derive(Clone, Copy, Debug)]
+pub struct ConfiguredSerializer {
+    human_readable: bool,
+    float_precision: Option<usize>,
+    whole_floats_as_integers: bool,
+    tag_some_null: bool,
+}
+
+impl Default for ConfiguredSerializer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConfiguredSerializer {
+    /// The options of a plain [`YamlSerializer`]
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            human_readable: true,
+            float_precision: None,
+            whole_floats_as_integers: false,
+            tag_some_null: false,
+        }
+    }
+
+    /// Choose what [`is_human_readable`](ser::Serializer::is_human_readable)
+    /// reports; `false` lets types such as addresses or timestamps pick their
+    /// compact machine-oriented form
+    #[must_use]
+    pub const fn human_readable(mut self, human_readable: bool) -> Self {
+        self.human_readable = human_readable;
+        self
+    }
+
+    /// Write finite floats with `digits` digits after the point. By default
+    /// they get the shortest text that reads back as the same float, in
+    /// exponent form below `1e-4` and from `1e16`, e.g. `0.1`, `1e-7` and
+    /// `1.5e300`; neither depends on the locale.
+    #[must_use]
+    pub const fn float_precision(mut self, digits: usize) -> Self {
+        self.float_precision = Some(digits);
+        self
+    }
+
+    /// Write finite floats with a whole value as integers, `1` for `1.0`,
+    /// instead of keeping the point that makes them read back as floats
+    #[must_use]
+    pub const fn whole_floats_as_integers(mut self, enabled: bool) -> Self {
+        self.whole_floats_as_integers = enabled;
+        self
+    }
+
+    /// Write `Some` of a value that serializes as null, such as `Some(None)`
+    /// for an `Option<Option<T>>` or `Some(())`, as `!!null ~` instead of a
+    /// plain `~`. A plain `~` reads back as `None` and the tagged null as
+    /// `Some`, so a field set to null stays apart from one left unset. Only
+    /// the innermost level is kept: `Some(Some(None))` reads back as
+    /// `Some(None)`.
+    ///
+    /// ```rust
+    /// use yyaml::SerializerConfig;
+    ///
+    /// let states: Vec<Option<Option<u8>>> = vec![None, Some(None), Some(Some(1))];
+    /// let config = SerializerConfig::new().tag_some_null(true);
+    /// let text = yyaml::to_string_with_config(&states, &config).unwrap();
+    /// assert_eq!(text, "---\n- ~\n- !!null ~\n- 1");
+    /// assert_eq!(yyaml::from_str::<Vec<Option<Option<u8>>>>(&text).unwrap(), states);
+    /// ```
+    #[must_use]
+    pub const fn tag_some_null(mut self, enabled: bool) -> Self {
+        self.tag_some_null = enabled;
+        self
+    }
+
+    fn float(self, v: f64, shortest: String) -> Yaml {
+        let text = match self.float_precision {
+            Some(digits) if v.is_finite() => format!("{v:.digits$}"),
+            _ if self.whole_floats_as_integers && let Some(whole) = shortest.strip_suffix(".0") => {
+                return Yaml::Real(whole.to_string());
+            }
+            _ => shortest,
+        };
+        Yaml::Real(float_text(text, v))
+    }
+}
+
+impl ser::Serializer for ConfiguredSerializer {
+    type Ok = Yaml;
+    type Error = Error;
+
+    type SerializeSeq = VecSerializer;
+    type SerializeTuple = VecSerializer;
+    type SerializeTupleStruct = VecSerializer;
+    type SerializeTupleVariant = TupleVariantSerializer;
+    type SerializeMap = MapSerializer;
+    type SerializeStruct = MapSerializer;
+    type SerializeStructVariant = StructVariantSerializer;
+
     fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
         Ok(Yaml::Boolean(v))
     }
@@ -70,11 +410,11 @@ impl ser::Serializer for YamlSerializer {
     }
 
     fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
-        Ok(Yaml::Real(v.to_string()))
+        Ok(self.float(f64::from(v), format!("{v:?}")))
     }
 
     fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> {
-        Ok(Yaml::Real(v.to_string()))
+        Ok(self.float(v, format!("{v:?}")))
     }
 
     fn serialize_i128(self, v: i128) -> Result<Self::Ok, Self::Error> {
@@ -102,8 +442,10 @@ impl ser::Serializer for YamlSerializer {
     }
 
     fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
-        let vec = v.iter().map(|&b| Yaml::Integer(b as i64)).collect();
-        Ok(Yaml::Array(vec))
+        Ok(Yaml::Tagged(
+            crate::base64::BINARY_TAG.to_string(),
+            Box::new(Yaml::String(crate::base64::encode(v))),
+        ))
     }
 
     fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
@@ -114,7 +456,12 @@ impl ser::Serializer for YamlSerializer {
         self,
         value: &T,
     ) -> Result<Self::Ok, Self::Error> {
-        value.serialize(self)
+        match value.serialize(self)? {
+            Yaml::Null if self.tag_some_null => {
+                Ok(Yaml::Tagged(NULL.to_string(), Box::new(Yaml::Null)))
+            }
+            yaml => Ok(yaml),
+        }
     }
 
     fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
@@ -136,10 +483,16 @@ impl ser::Serializer for YamlSerializer {
 
     fn serialize_newtype_struct<T: ?Sized + ser::Serialize>(
         self,
-        _name: &'static str,
+        name: &'static str,
         value: &T,
     ) -> Result<Self::Ok, Self::Error> {
-        value.serialize(self)
+        match value.serialize(self)? {
+            Yaml::Array(members) if name == crate::value::SET_STRUCT => {
+                let set = members.into_iter().map(|member| (member, Yaml::Null)).collect();
+                Ok(Yaml::Tagged(SET.to_string(), Box::new(Yaml::Hash(set))))
+            }
+            yaml => Ok(yaml),
+        }
     }
 
     fn serialize_newtype_variant<T: ?Sized + ser::Serialize>(
@@ -156,12 +509,14 @@ impl ser::Serializer for YamlSerializer {
 
     fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
         Ok(VecSerializer {
+            ser: self,
             vec: Vec::with_capacity(len.unwrap_or(0)),
         })
     }
 
     fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
         Ok(VecSerializer {
+            ser: self,
             vec: Vec::with_capacity(len),
         })
     }
@@ -172,6 +527,7 @@ impl ser::Serializer for YamlSerializer {
         len: usize,
     ) -> Result<Self::SerializeTupleStruct, Self::Error> {
         Ok(VecSerializer {
+            ser: self,
             vec: Vec::with_capacity(len),
         })
     }
@@ -184,6 +540,7 @@ impl ser::Serializer for YamlSerializer {
         len: usize,
     ) -> Result<Self::SerializeTupleVariant, Self::Error> {
         Ok(TupleVariantSerializer {
+            ser: self,
             name: variant.to_string(),
             vec: Vec::with_capacity(len),
         })
@@ -191,6 +548,7 @@ impl ser::Serializer for YamlSerializer {
 
     fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
         Ok(MapSerializer {
+            ser: self,
             map: LinkedHashMap::new(),
             key: None,
         })
@@ -202,6 +560,7 @@ impl ser::Serializer for YamlSerializer {
         _len: usize,
     ) -> Result<Self::SerializeStruct, Self::Error> {
         Ok(MapSerializer {
+            ser: self,
             map: LinkedHashMap::new(),
             key: None,
         })
@@ -215,13 +574,19 @@ impl ser::Serializer for YamlSerializer {
         _len: usize,
     ) -> Result<Self::SerializeStructVariant, Self::Error> {
         Ok(StructVariantSerializer {
+            ser: self,
             name: variant.to_string(),
             map: LinkedHashMap::new(),
         })
     }
+
+    fn is_human_readable(&self) -> bool {
+        self.human_readable
+    }
 }
 
 pub struct VecSerializer {
+    ser: ConfiguredSerializer,
     vec: Vec<Yaml>,
 }
 
@@ -233,7 +598,7 @@ impl ser::SerializeSeq for VecSerializer {
     where
         T: ?Sized + ser::Serialize,
     {
-        self.vec.push(value.serialize(YamlSerializer)?);
+        self.vec.push(value.serialize(self.ser)?);
         Ok(())
     }
 
@@ -275,6 +640,7 @@ impl ser::SerializeTupleStruct for VecSerializer {
 }
 
 pub struct TupleVariantSerializer {
+    ser: ConfiguredSerializer,
     name: String,
     vec: Vec<Yaml>,
 }
@@ -287,7 +653,7 @@ impl ser::SerializeTupleVariant for TupleVariantSerializer {
     where
         T: ?Sized + ser::Serialize,
     {
-        self.vec.push(value.serialize(YamlSerializer)?);
+        self.vec.push(value.serialize(self.ser)?);
         Ok(())
     }
 
@@ -299,6 +665,7 @@ impl ser::SerializeTupleVariant for TupleVariantSerializer {
 }
 
 pub struct MapSerializer {
+    ser: ConfiguredSerializer,
     map: LinkedHashMap<Yaml, Yaml>,
     key: Option<Yaml>,
 }
@@ -311,7 +678,7 @@ impl ser::SerializeMap for MapSerializer {
     where
         T: ?Sized + ser::Serialize,
     {
-        self.key = Some(key.serialize(YamlSerializer)?);
+        self.key = Some(key.serialize(self.ser)?);
         Ok(())
     }
 
@@ -323,7 +690,7 @@ impl ser::SerializeMap for MapSerializer {
             .key
             .take()
             .ok_or_else(|| Error::Custom("no key".to_string()))?;
-        let val = value.serialize(YamlSerializer)?;
+        let val = value.serialize(self.ser)?;
         self.map.insert(key, val);
         Ok(())
     }
@@ -351,6 +718,7 @@ impl ser::SerializeStruct for MapSerializer {
 }
 
 pub struct StructVariantSerializer {
+    ser: ConfiguredSerializer,
     name: String,
     map: LinkedHashMap<Yaml, Yaml>,
 }
@@ -365,7 +733,7 @@ impl ser::SerializeStructVariant for StructVariantSerializer {
     {
         self.map.insert(
             Yaml::String(key.to_string()),
-            value.serialize(YamlSerializer)?,
+            value.serialize(self.ser)?,
         );
         Ok(())
     }
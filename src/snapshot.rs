@@ -0,0 +1,213 @@
+//! Golden-file snapshots of serialized values
+//!
+//! [`assert_yaml_snapshot!`](crate::assert_yaml_snapshot) serializes a value
+//! the way [`to_string`](crate::to_string) does and compares it with a file
+//! stored alongside the tests. Comparison is structural: a snapshot that
+//! only differs in formatting still matches, and a mismatch lists every
+//! node that changed by JSON Pointer path.
+//!
+//! A missing snapshot is written on the first run. Set
+//! `YYAML_UPDATE_SNAPSHOTS=1` to rewrite snapshots that no longer match.
+
+use std::fmt::Write as _;
+use std::io::{self, IsTerminal};
+use std::path::Path;
+use std::{env, fs};
+
+use serde::Serialize;
+
+use crate::pointer::value_key_token;
+use crate::value::Value;
+
+/// Environment variable that makes a mismatching snapshot be rewritten
+/// instead of failing
+pub const UPDATE_VAR: &str = "YYAML_UPDATE_SNAPSHOTS";
+
+/// A node that differs between a snapshot and the current value
+#[derive(Clone, Debug, PartialEq)]
+pub struct SnapshotChange {
+    /// JSON Pointer of the node
+    pub path: String,
+    /// The node in the snapshot; `None` if it was added
+    pub expected: Option<Value>,
+    /// The node now; `None` if it was removed
+    pub actual: Option<Value>,
+}
+
+/// Every node where `actual` differs from `expected`, in document order.
+/// Nodes are compared with [`Value::semantic_eq`]; a node whose kind changed
+/// is reported whole rather than child by child.
+///
+/// ```rust
+/// use yyaml::Value;
+/// use yyaml::snapshot::structural_diff;
+///
+/// let old: Value = yyaml::from_str("replicas: 1\nports: [80]").unwrap();
+/// let new: Value = yyaml::from_str("replicas: 2\nports: [80, 443]").unwrap();
+/// let paths: Vec<_> = structural_diff(&old, &new).into_iter().map(|c| c.path).collect();
+/// assert_eq!(paths, ["/ports/1", "/replicas"]);
+/// ```
+#[must_use]
+pub fn structural_diff(expected: &Value, actual: &Value) -> Vec<SnapshotChange> {
+    let mut changes = Vec::new();
+    diff_node(expected, actual, String::new(), &mut changes);
+    changes
+}
+
+fn diff_node(expected: &Value, actual: &Value, path: String, changes: &mut Vec<SnapshotChange>) {
+    let change = |changes: &mut Vec<_>, path, expected: Option<&Value>, actual: Option<&Value>| {
+        changes.push(SnapshotChange {
+            path,
+            expected: expected.cloned(),
+            actual: actual.cloned(),
+        });
+    };
+    match (expected, actual) {
+        (Value::Sequence(a), Value::Sequence(b)) => {
+            for i in 0..a.len().max(b.len()) {
+                let child = format!("{path}/{i}");
+                match (a.get(i), b.get(i)) {
+                    (Some(x), Some(y)) => diff_node(x, y, child, changes),
+                    (x, y) => change(changes, child, x, y),
+                }
+            }
+        }
+        (Value::Mapping(a), Value::Mapping(b)) => {
+            let mut keys: Vec<&Value> = a.keys().chain(b.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let child = format!("{path}/{}", value_key_token(key));
+                match (a.get(key), b.get(key)) {
+                    (Some(x), Some(y)) => diff_node(x, y, child, changes),
+                    (x, y) => change(changes, child, x, y),
+                }
+            }
+        }
+        (Value::Tagged(a), Value::Tagged(b)) if a.tag == b.tag => {
+            diff_node(&a.value, &b.value, path, changes);
+        }
+        _ if expected.semantic_eq(actual) => {}
+        _ => change(changes, path, Some(expected), Some(actual)),
+    }
+}
+
+/// Compare `value` with the snapshot stored at `path`, writing it if there
+/// is none yet; see the [module docs](self). Prefer
+/// [`assert_yaml_snapshot!`](crate::assert_yaml_snapshot), which resolves
+/// relative paths against the calling crate.
+///
+/// # Panics
+///
+/// If `value` cannot be serialized, the snapshot cannot be read or written,
+/// or it does not match.
+#[track_caller]
+pub fn assert_snapshot<T: Serialize + ?Sized>(value: &T, path: &Path) {
+    let shown = path.display();
+    let value = match crate::to_value(value) {
+        Ok(value) => value,
+        Err(e) => panic!("cannot serialize the value for snapshot `{shown}`: {e}"),
+    };
+    let stored = match fs::read_to_string(path) {
+        Ok(stored) => stored,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return write_snapshot(&value, path),
+        Err(e) => panic!("cannot read snapshot `{shown}`: {e}"),
+    };
+    let changes = match stored.parse::<Value>() {
+        Ok(expected) => structural_diff(&expected, &value),
+        Err(e) => panic!("snapshot `{shown}` is not valid YAML: {e}"),
+    };
+    if changes.is_empty() {
+        return;
+    }
+    if env::var_os(UPDATE_VAR).is_some_and(|v| v != "0") {
+        return write_snapshot(&value, path);
+    }
+    let color = env::var_os("NO_COLOR").is_none() && io::stderr().is_terminal();
+    panic!("{}", render_changes(&shown.to_string(), &changes, color));
+}
+
+#[track_caller]
+fn write_snapshot(value: &Value, path: &Path) {
+    let written = crate::to_string(value)
+        .map_err(io::Error::other)
+        .and_then(|text| {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            fs::write(path, text + "\n")
+        });
+    if let Err(e) = written {
+        panic!("cannot write snapshot `{}`: {e}", path.display());
+    }
+}
+
+/// The failure message for `changes` to the snapshot `name`: one `-` line
+/// per removed or old node and one `+` line per added or new node, in red
+/// and green when `color` is set
+#[must_use]
+pub fn render_changes(name: &str, changes: &[SnapshotChange], color: bool) -> String {
+    let (red, green, reset) = if color {
+        ("\x1b[31m", "\x1b[32m", "\x1b[0m")
+    } else {
+        ("", "", "")
+    };
+    let mut out = format!(
+        "snapshot `{name}` does not match ({} changed, set {UPDATE_VAR}=1 to accept):",
+        changes.len()
+    );
+    for change in changes {
+        let path = if change.path.is_empty() { "/" } else { &change.path };
+        if let Some(old) = &change.expected {
+            let _ = write!(out, "\n{red}- {path}: {}{reset}", inline(old));
+        }
+        if let Some(new) = &change.actual {
+            let _ = write!(out, "\n{green}+ {path}: {}{reset}", inline(new));
+        }
+    }
+    out
+}
+
+/// `value` on one line, in flow style
+fn inline(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => format!("{s:?}"),
+        Value::Sequence(items) => {
+            let items: Vec<_> = items.iter().map(inline).collect();
+            format!("[{}]", items.join(", "))
+        }
+        Value::Mapping(map) => {
+            let entries: Vec<_> = map
+                .iter()
+                .map(|(k, v)| format!("{}: {}", inline(k), inline(v)))
+                .collect();
+            format!("{{{}}}", entries.join(", "))
+        }
+        Value::Tagged(tagged) => format!("!<{}> {}", tagged.tag.name, inline(&tagged.value)),
+    }
+}
+
+/// Assert that a serializable value matches the YAML snapshot at a path
+/// relative to the calling crate's manifest directory; see
+/// [`snapshot`](crate::snapshot).
+///
+/// ```rust,no_run
+/// #[derive(serde::Serialize)]
+/// struct Config {
+///     replicas: u32,
+/// }
+///
+/// yyaml::assert_yaml_snapshot!(Config { replicas: 3 }, "tests/snapshots/config.snap");
+/// ```
+#[macro_export]
+macro_rules! assert_yaml_snapshot {
+    ($value:expr, $path:expr $(,)?) => {
+        $crate::snapshot::assert_snapshot(
+            &$value,
+            &::std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join($path),
+        )
+    };
+}
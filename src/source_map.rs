@@ -0,0 +1,151 @@
+//! Byte ranges of the input behind every node of a loaded document
+//!
+//! [`YamlLoader::load_with_source_map`](crate::YamlLoader::load_with_source_map)
+//! returns a [`SourceMap`] next to the documents. Nodes are identified by
+//! their JSON Pointer in the loaded document, so a node copied in by an alias
+//! has a path of its own, and its spans lead back to the text it was copied
+//! from.
+
+use std::collections::{BTreeMap, HashMap};
+use std::ops::{Bound, Range};
+
+/// The input byte ranges that produced each node, by document and path.
+///
+/// The first span of a node is where it appears in the input: the node
+/// itself, or the `*alias` that stands for it. An aliased node then has the
+/// span of the anchored node. Only paths of the loaded documents are mapped,
+/// so the content of a `<<` merge key stays under `<<`, and neither keys nor
+/// values under a sequence or mapping key are mapped.
+///
+/// ```rust
+/// use yyaml::YamlLoader;
+///
+/// let source = "base: &b {port: 80}\nweb: *b\n";
+/// let (docs, map) = YamlLoader::load_with_source_map(source).unwrap();
+/// assert_eq!(docs[0]["web"]["port"].as_i64(), Some(80));
+/// let spans: Vec<&str> = map.spans(0, "/web").iter().map(|r| &source[r.clone()]).collect();
+/// assert_eq!(spans, ["*b", "{port: 80}"]);
+/// assert_eq!(map.spans(0, "/web/port"), [16..18]);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SourceMap {
+    documents: Vec<BTreeMap<String, Vec<Range<usize>>>>,
+}
+
+impl SourceMap {
+    /// Spans of the node at `path` in document `document`; empty for a path
+    /// the map does not know
+    #[must_use]
+    pub fn spans(&self, document: usize, path: &str) -> &[Range<usize>] {
+        self.documents
+            .get(document)
+            .and_then(|paths| paths.get(path))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Every path of document `document` with its spans, in path order
+    pub fn paths(&self, document: usize) -> impl Iterator<Item = (&str, &[Range<usize>])> {
+        self.documents
+            .get(document)
+            .into_iter()
+            .flatten()
+            .map(|(path, spans)| (path.as_str(), spans.as_slice()))
+    }
+
+    /// Map documents whose spans the parser logged, trimming the white
+    /// space scanned after a node from its spans
+    pub(crate) fn new(s: &str, mut documents: Vec<BTreeMap<String, Vec<Range<usize>>>>) -> Self {
+        for spans in documents.iter_mut().flat_map(BTreeMap::values_mut) {
+            for span in spans {
+                let text = s.get(span.clone()).unwrap_or_default();
+                span.end = span.start + text.trim_end().len();
+            }
+        }
+        Self { documents }
+    }
+}
+
+/// Spans of the nodes of the document being parsed, logged by the parser as
+/// each node completes
+#[derive(Debug, Default)]
+pub(crate) struct SpanLog {
+    /// Spans by path in the loaded document
+    paths: BTreeMap<String, Vec<Range<usize>>>,
+    /// Spans of each anchored node and of its children, by path relative to
+    /// the node
+    anchored: HashMap<usize, Vec<(String, Range<usize>)>>,
+    /// Path of the node logged last, if it has one
+    last: Option<String>,
+}
+
+impl SpanLog {
+    /// Log the node at `path`, written at `span`; an alias to anchor `alias`
+    /// also takes the spans of the anchored node and its children. Keys and
+    /// nodes inside them have no path and are not logged.
+    pub(crate) fn record(
+        &mut self,
+        path: Option<String>,
+        span: Range<usize>,
+        alias: Option<usize>,
+    ) {
+        self.last = None;
+        let Some(path) = path else {
+            return;
+        };
+        self.paths.entry(path.clone()).or_default().push(span);
+        if let Some(copied) = alias.and_then(|id| self.anchored.get(&id)) {
+            for (relative, span) in copied {
+                let spans = self.paths.entry(format!("{path}{relative}")).or_default();
+                if !spans.contains(span) {
+                    spans.push(span.clone());
+                }
+            }
+        }
+        self.last = Some(path);
+    }
+
+    /// Drop the spans of `path` and its children, as when a repeated key
+    /// replaces the value they belong to
+    pub(crate) fn forget(&mut self, path: &str) {
+        let stale: Vec<String> = self.subtree(path).map(|(child, _)| child.clone()).collect();
+        for child in stale {
+            self.paths.remove(&child);
+        }
+    }
+
+    /// Keep the spans of the node logged last for aliases to anchor `id`
+    pub(crate) fn anchor(&mut self, id: usize) {
+        let Some(path) = &self.last else {
+            return;
+        };
+        let spans = self
+            .subtree(path)
+            .flat_map(|(child, spans)| {
+                let relative = &child[path.len()..];
+                spans
+                    .iter()
+                    .map(|span| (relative.to_string(), span.clone()))
+            })
+            .collect();
+        self.anchored.insert(id, spans);
+    }
+
+    /// Spans of the document parsed last, leaving the log empty for the next
+    pub(crate) fn take(&mut self) -> BTreeMap<String, Vec<Range<usize>>> {
+        self.anchored.clear();
+        std::mem::take(&mut self.paths)
+    }
+
+    /// Logged paths at or below `path`
+    fn subtree<'a>(
+        &'a self,
+        path: &'a str,
+    ) -> impl Iterator<Item = (&'a String, &'a Vec<Range<usize>>)> + 'a {
+        self.paths
+            .range::<str, _>((Bound::Included(path), Bound::Unbounded))
+            .take_while(move |(child, _)| child.starts_with(path))
+            .filter(move |(child, _)| {
+                child.len() == path.len() || child[path.len()..].starts_with('/')
+            })
+    }
+}
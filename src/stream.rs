@@ -0,0 +1,136 @@
+//! Writing long multi-document streams with checkpoints to resume from
+//!
+//! A [`StreamEmitter`] writes one document per call to an [`io::Write`]
+//! target, such as a file being filled with millions of exported records.
+//! [`checkpoint`](StreamEmitter::checkpoint) flushes the target and returns
+//! a [`StreamState`] saying how much of the stream is complete. After a
+//! crash, cut the output back to that point with
+//! [`StreamState::truncate`] and carry on with [`StreamEmitter::resume`];
+//! documents written after the checkpoint are written again.
+//!
+//! ```rust
+//! use yyaml::{StreamEmitter, StreamState};
+//!
+//! let mut out = Vec::new();
+//! let mut stream = StreamEmitter::begin_stream(&mut out);
+//! stream.emit_document(&vec![1, 2]).unwrap();
+//! let state = stream.checkpoint().unwrap();
+//! // Saved next to the output, e.g. as YAML
+//! let saved = yyaml::to_string(&state).unwrap();
+//! stream.emit_document(&"partial").unwrap();
+//! drop(stream);
+//!
+//! // Later: drop what followed the checkpoint and go on from there
+//! let state: StreamState = yyaml::from_str(&saved).unwrap();
+//! out.truncate(state.bytes() as usize);
+//! let mut stream = StreamEmitter::resume(state, &mut out);
+//! stream.emit_document(&"next").unwrap();
+//! assert_eq!(stream.finish().unwrap().documents(), 2);
+//! assert_eq!(String::from_utf8(out).unwrap(), "---\n- 1\n- 2\n---\nnext\n");
+//! ```
+
+use std::fs::File;
+use std::io::{self, Seek, SeekFrom};
+
+use serde::{Deserialize, Serialize};
+
+use crate::emitter::{EmitError, YamlEmitter};
+use crate::ser::SerializerConfig;
+use crate::Error;
+
+/// How far a stream was written when [`StreamEmitter::checkpoint`] was
+/// called; serializable, so it can be stored next to the output
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StreamState {
+    documents: u64,
+    bytes: u64,
+}
+
+impl StreamState {
+    /// Documents completely written
+    #[must_use]
+    pub const fn documents(&self) -> u64 {
+        self.documents
+    }
+
+    /// Bytes of the output those documents take up; a resumed stream
+    /// continues from this offset
+    #[must_use]
+    pub const fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    /// Cut `file` back to the checkpoint, dropping any document written in
+    /// part after it, and move to its end to resume writing
+    pub fn truncate(&self, file: &mut File) -> io::Result<()> {
+        file.set_len(self.bytes)?;
+        file.seek(SeekFrom::Start(self.bytes))?;
+        Ok(())
+    }
+}
+
+/// A multi-document stream being written; see the [module docs](self)
+pub struct StreamEmitter<W: io::Write> {
+    writer: W,
+    config: SerializerConfig,
+    state: StreamState,
+}
+
+impl<W: io::Write> StreamEmitter<W> {
+    /// Start a new stream at the beginning of `writer`
+    pub fn begin_stream(writer: W) -> Self {
+        Self::resume(StreamState::default(), writer)
+    }
+
+    /// Continue a stream from `state`; `writer` must already be at
+    /// [`state.bytes()`](StreamState::bytes), with nothing after it
+    pub fn resume(state: StreamState, writer: W) -> Self {
+        Self {
+            writer,
+            config: SerializerConfig::default(),
+            state,
+        }
+    }
+
+    /// Serialize documents with `config`
+    #[must_use]
+    pub fn with_config(mut self, config: SerializerConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Serialize `value` and write it as the next document. On failure part
+    /// of it may have been written; resume from the last checkpoint.
+    pub fn emit_document<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        let mut yaml = value.serialize(self.config.serializer())?;
+        self.config.redact(&mut yaml);
+        let mut emitter = self.config.emitter(YamlEmitter::from_io(&mut self.writer));
+        emitter.dump(&yaml)?;
+        emitter.write_line_break()?;
+        self.state.documents += 1;
+        self.state.bytes += emitter.bytes_written() as u64;
+        Ok(())
+    }
+
+    /// Flush the writer and return the state to resume from
+    pub fn checkpoint(&mut self) -> Result<StreamState, Error> {
+        self.writer.flush().map_err(|source| {
+            Error::Emit(EmitError::Io {
+                path: String::new(),
+                written: self.state.bytes as usize,
+                source,
+            })
+        })?;
+        Ok(self.state)
+    }
+
+    /// State as of the last document written, flushed or not
+    pub const fn state(&self) -> StreamState {
+        self.state
+    }
+
+    /// End the stream with a final [`checkpoint`](Self::checkpoint)
+    pub fn finish(mut self) -> Result<StreamState, Error> {
+        self.checkpoint()
+    }
+}
@@ -0,0 +1,20 @@
+//! Full names of the YAML 1.2 standard tags, used by the core modules as
+//! well as the `semantic` analysis
+
+// Most are only read by `semantic`
+#![cfg_attr(not(feature = "semantic"), allow(dead_code))]
+
+pub const NULL: &str = "tag:yaml.org,2002:null";
+pub const BOOL: &str = "tag:yaml.org,2002:bool";
+pub const INT: &str = "tag:yaml.org,2002:int";
+pub const FLOAT: &str = "tag:yaml.org,2002:float";
+pub const STR: &str = "tag:yaml.org,2002:str";
+pub const BINARY: &str = "tag:yaml.org,2002:binary";
+pub const TIMESTAMP: &str = "tag:yaml.org,2002:timestamp";
+pub const SEQ: &str = "tag:yaml.org,2002:seq";
+pub const MAP: &str = "tag:yaml.org,2002:map";
+pub const SET: &str = "tag:yaml.org,2002:set";
+pub const OMAP: &str = "tag:yaml.org,2002:omap";
+pub const PAIRS: &str = "tag:yaml.org,2002:pairs";
+pub const MERGE: &str = "tag:yaml.org,2002:merge";
+pub const VALUE: &str = "tag:yaml.org,2002:value";
@@ -0,0 +1,161 @@
+//! Differential testing of event streams
+//!
+//! [`compare_events`] parses two inputs into the events the parser reports
+//! as it reads them and stops at the first event where they part ways, or
+//! where one of them fails to parse. Events are canonical: scalars carry
+//! their resolved value and full tag, so `0x1f` and `31`, or `!!str a` and
+//! `"a"`, compare equal. Every node that is written gives events, so a
+//! mapping that repeats a key differs from one that does not even though
+//! both load the same. Documents are parsed one at a time, so a difference
+//! early in a long stream is found without loading the rest.
+//!
+//! ```rust
+//! use yyaml::testing::{EventDiff, compare_events};
+//!
+//! assert!(compare_events("a: [1, 2]\n", "{a: [0x1, 2]}").is_same());
+//! let EventDiff::Differ { index, left: Ok(left), right: Ok(right) } =
+//!     compare_events("a: 1\nb: 2\n", "a: 1\nb: 3\n")
+//! else {
+//!     panic!("the streams differ");
+//! };
+//! assert_eq!(index, 6);
+//! assert_eq!((left.mark.line(), right.mark.line()), (2, 2));
+//! ```
+
+use std::collections::VecDeque;
+use std::fmt;
+
+use crate::error::{Marker, ScanError};
+use crate::events::Event;
+use crate::parser::state_machine::StateMachine;
+use crate::scanner::state::StrChars;
+
+/// An event with where its node starts in the input, or for the end of a
+/// collection or document where it ends
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MarkedEvent {
+    pub event: Event,
+    pub mark: Marker,
+}
+
+/// The outcome of [`compare_events`]
+#[derive(Clone, Debug)]
+pub enum EventDiff {
+    /// Both inputs produce the same `events` events
+    Same { events: usize },
+    /// Both inputs fail after the same first `events` events
+    BothFail {
+        events: usize,
+        left: ScanError,
+        right: ScanError,
+    },
+    /// The streams first differ at event `index`; a side that failed to
+    /// parse there holds its error
+    Differ {
+        index: usize,
+        left: Result<MarkedEvent, ScanError>,
+        right: Result<MarkedEvent, ScanError>,
+    },
+}
+
+impl EventDiff {
+    /// Whether both inputs produce the same events
+    #[must_use]
+    pub const fn is_same(&self) -> bool {
+        matches!(self, Self::Same { .. })
+    }
+}
+
+impl fmt::Display for EventDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let side = |f: &mut fmt::Formatter<'_>, name, side: &Result<MarkedEvent, ScanError>| {
+            match side {
+                Ok(marked) => write!(f, "\n  {name}: {:?} at {}", marked.event, marked.mark),
+                Err(error) => write!(f, "\n  {name}: error {error}"),
+            }
+        };
+        match self {
+            Self::Same { events } => write!(f, "both inputs produce the same {events} events"),
+            Self::BothFail { events, left, right } => write!(
+                f,
+                "both inputs fail after {events} events\n  left: {left}\n  right: {right}"
+            ),
+            Self::Differ { index, left, right } => {
+                write!(f, "event {index} differs:")?;
+                side(f, "left", left)?;
+                side(f, "right", right)
+            }
+        }
+    }
+}
+
+/// Parse `a` and `b` and report the first event where their streams differ;
+/// see the [module docs](self).
+#[must_use]
+pub fn compare_events(a: &str, b: &str) -> EventDiff {
+    let mut left = EventStream::new(a);
+    let mut right = EventStream::new(b);
+    let mut index = 0;
+    loop {
+        match (left.next_event(), right.next_event()) {
+            (Ok(x), Ok(y)) if x.event == y.event => {
+                index += 1;
+                if x.event == Event::StreamEnd {
+                    return EventDiff::Same { events: index };
+                }
+            }
+            (Err(left), Err(right)) => {
+                return EventDiff::BothFail {
+                    events: index,
+                    left,
+                    right,
+                };
+            }
+            (left, right) => return EventDiff::Differ { index, left, right },
+        }
+    }
+}
+
+/// The events of one input, parsed a document at a time
+struct EventStream<'a> {
+    state_machine: StateMachine<StrChars<'a>>,
+    pending: VecDeque<MarkedEvent>,
+    /// The error the events in `pending` lead up to
+    error: Option<ScanError>,
+}
+
+impl<'a> EventStream<'a> {
+    fn new(source: &'a str) -> Self {
+        Self {
+            state_machine: StateMachine::over_str(source)
+                .with_kept_anchors()
+                .with_event_log(),
+            pending: VecDeque::new(),
+            error: None,
+        }
+    }
+
+    fn next_event(&mut self) -> Result<MarkedEvent, ScanError> {
+        loop {
+            if let Some(next) = self.pending.pop_front() {
+                return Ok(next);
+            }
+            if let Some(error) = &self.error {
+                return Err(error.clone());
+            }
+            if let Err(error) = self.state_machine.parse_next_document() {
+                self.error = Some(error);
+            }
+            let events = self.state_machine.take_events();
+            if events.is_empty() && self.error.is_none() {
+                // Past the end of the stream
+                return Ok(MarkedEvent {
+                    event: Event::StreamEnd,
+                    mark: self.state_machine.scanner.mark(),
+                });
+            }
+            self.pending
+                .extend(events.into_iter().map(|(event, mark)| MarkedEvent { event, mark }));
+        }
+    }
+}
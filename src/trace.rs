@@ -0,0 +1,20 @@
+//! Internal instrumentation macros.
+//!
+//! With the `tracing` feature enabled these forward to the `tracing` crate;
+//! without it they expand to nothing, so call sites need no `cfg` noise.
+
+/// Enter a debug span that lasts until the end of the enclosing block.
+macro_rules! trace_span {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!($($arg)*).entered();
+    };
+}
+
+/// Record a debug event, typically carrying counters as fields.
+macro_rules! trace_event {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::debug!($($arg)*);
+    };
+}
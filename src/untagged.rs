@@ -0,0 +1,126 @@
+//! Untagged enums that explain why no variant matched
+//!
+//! `#[serde(untagged)]` tries each variant against buffered content and,
+//! when all fail, reports only "data did not match any variant". [`Untagged`]
+//! does the same from a buffered [`Value`], keeping each variant's error and
+//! the path it was raised at, and names the variants that came closest.
+
+use crate::Error;
+use crate::value::{Value, from_value_located};
+use serde::de::{self, Deserialize, DeserializeOwned, Deserializer};
+
+/// How many of the closest variants a failure lists
+const CANDIDATES: usize = 3;
+
+/// Deserialize an enum by trying its variants in order, for use in a
+/// hand-written `Deserialize` impl in place of `#[serde(untagged)]`.
+///
+/// When no variant matches, the error lists the variants that got furthest
+/// into the input, deepest failure first, each with its reason and path.
+///
+/// ```rust
+/// use serde::Deserializer;
+/// use serde_derive::Deserialize;
+/// use yyaml::Untagged;
+///
+/// #[derive(Debug, Deserialize)]
+/// struct File {
+///     path: String,
+/// }
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Inline {
+///     body: String,
+///     mode: u32,
+/// }
+///
+/// #[derive(Debug)]
+/// enum Source {
+///     File(File),
+///     Inline(Inline),
+/// }
+///
+/// impl<'de> serde::Deserialize<'de> for Source {
+///     fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+///         Untagged::new("Source", deserializer)?
+///             .variant("File", Source::File)
+///             .variant("Inline", Source::Inline)
+///             .finish()
+///     }
+/// }
+///
+/// let source: Source = yyaml::from_str("body: echo hi\nmode: 755\n").unwrap();
+/// assert!(matches!(source, Source::Inline(_)));
+///
+/// let err = yyaml::from_str::<Source>("body: echo hi\nmode: rwx\n").unwrap_err();
+/// assert!(err.to_string().contains("closest: `Inline`: "));
+/// assert!(err.to_string().contains("at `/mode`"));
+/// ```
+pub struct Untagged<T, E> {
+    name: &'static str,
+    value: Value,
+    matched: Option<T>,
+    failures: Vec<(&'static str, Error)>,
+    error: std::marker::PhantomData<E>,
+}
+
+impl<T, E: de::Error> Untagged<T, E> {
+    /// Buffer the input of `deserializer` for the enum called `name`
+    pub fn new<'de, D>(name: &'static str, deserializer: D) -> Result<Self, E>
+    where
+        D: Deserializer<'de, Error = E>,
+    {
+        Ok(Self {
+            name,
+            value: Value::deserialize(deserializer)?,
+            matched: None,
+            failures: Vec::new(),
+            error: std::marker::PhantomData,
+        })
+    }
+
+    /// Try the variant called `name`, which holds a `V`, unless an earlier
+    /// variant already matched
+    #[must_use]
+    pub fn variant<V, F>(mut self, name: &'static str, wrap: F) -> Self
+    where
+        V: DeserializeOwned,
+        F: FnOnce(V) -> T,
+    {
+        if self.matched.is_none() {
+            match from_value_located::<V>(self.value.clone()) {
+                Ok(inner) => self.matched = Some(wrap(inner)),
+                Err(e) => self.failures.push((name, e)),
+            }
+        }
+        self
+    }
+
+    /// The first variant that matched, or an error naming the closest ones
+    pub fn finish(self) -> Result<T, E> {
+        if let Some(matched) = self.matched {
+            return Ok(matched);
+        }
+        let mut failures = self.failures;
+        // A failure deeper in the input means the variant matched more of it
+        failures.sort_by_key(|(_, e)| std::cmp::Reverse(depth(e)));
+        let closest: Vec<String> = failures
+            .iter()
+            .take(CANDIDATES)
+            .map(|(variant, e)| format!("`{variant}`: {e}"))
+            .collect();
+        Err(E::custom(format!(
+            "data did not match any variant of untagged enum {}; closest: {}",
+            self.name,
+            closest.join("; ")
+        )))
+    }
+}
+
+/// Number of reference tokens in the path an error was raised at
+fn depth(e: &Error) -> usize {
+    match e {
+        Error::At { path, .. } | Error::Coerce { path, .. } => path.matches('/').count(),
+        _ => 0,
+    }
+}
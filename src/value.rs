@@ -4,12 +4,16 @@
 //! and integrates seamlessly with serde serialization/deserialization.
 
 use crate::Error;
-use crate::yaml::Yaml;
+use crate::parser::{EmptyScalar, IntegerOverflow, LoaderOptions, Profile, SchemaMode};
+use crate::tags::SET;
+use crate::yaml::{Yaml, parse_f64};
 use serde::{Deserialize, Serialize, de, ser};
+use std::borrow::Cow;
 use std::cmp::Ordering;
 use std::collections::BTreeMap;
 use std::fmt;
 use std::ops::Index;
+use std::sync::{Arc, Mutex};
 
 /// A YAML tag (like "!wat" or "tag:yaml.org,2002:str")
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
@@ -23,6 +27,39 @@ impl Tag {
     }
 }
 
+/// Newtype struct name under which a `!!set` [`Value`] serializes its
+/// members, so the YAML serializer can write it back as a set
+pub(crate) const SET_STRUCT: &str = "$yyaml::set";
+
+/// Whether `hash` is the content of a `!!set`: every value is null
+pub(crate) fn is_set_content(hash: &crate::LinkedHashMap<Yaml, Yaml>) -> bool {
+    hash.iter().all(|(_, value)| matches!(value, Yaml::Null))
+}
+
+/// Newtype struct name under which a type asks to see the tag of the value
+/// it deserializes from.
+///
+/// The [`Deserializer`] strips tags for every other request. Under this
+/// name it hands a tagged value to the visitor as an enum whose variant
+/// name is the tag and whose newtype content is the value, while an
+/// untagged value arrives through `visit_newtype_struct` as usual. [`Value`]
+/// and [`TaggedValue`] deserialize this way, so a `struct Wrapper(Value)`
+/// keeps its tags.
+///
+/// ```rust
+/// use yyaml::Value;
+/// use yyaml::value::{Tag, TaggedValue};
+///
+/// #[derive(serde::Deserialize)]
+/// struct Wrapper(Value);
+///
+/// let Wrapper(inner) = yyaml::from_str("!celsius 21").unwrap();
+/// assert_eq!(inner.tag(), Some(&Tag::new("celsius")));
+/// let tagged: TaggedValue = yyaml::from_str("!celsius 21").unwrap();
+/// assert_eq!(tagged.value.as_i64(), Some(21));
+/// ```
+pub const TAGGED_STRUCT: &str = "$yyaml::tagged";
+
 /// A tagged YAML value containing both tag and content
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct TaggedValue {
@@ -79,18 +116,21 @@ pub type Mapping = BTreeMap<Value, Value>;
 pub enum Number {
     /// Integer value
     Integer(i64),
+    /// Integer above `i64::MAX`, kept exact as JSON parsers keep it
+    Unsigned(u64),
+    /// Integer outside both the `i64` and `u64` ranges, kept exact for
+    /// `i128` and `u128` fields
+    BigInteger(i128),
+    /// Integer above `i128::MAX`, kept exact for `u128` fields
+    BigUnsigned(u128),
     /// Float value
     Float(f64),
 }
 
 impl PartialEq for Number {
+    /// Equal exactly when [`Ord`] says so, so numbers behave as map keys
     fn eq(&self, other: &Self) -> bool {
-        match (self, other) {
-            (Self::Integer(a), Self::Integer(b)) => a == b,
-            (Self::Float(a), Self::Float(b)) => a == b,
-            (Self::Integer(a), Self::Float(b)) => *a as f64 == *b,
-            (Self::Float(a), Self::Integer(b)) => *a == *b as f64,
-        }
+        self.cmp(other) == Ordering::Equal
     }
 }
 
@@ -104,7 +144,28 @@ impl PartialOrd for Number {
 
 impl Ord for Number {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.partial_cmp(other).unwrap_or(Ordering::Equal)
+        match (self, other) {
+            (Self::Float(a), b) => float_cmp(*a, b.to_f64()),
+            (a, Self::Float(b)) => float_cmp(a.to_f64(), *b),
+            (a, b) => match (a.to_i128(), b.to_i128()) {
+                (Some(a), Some(b)) => a.cmp(&b),
+                (None, Some(_)) => Ordering::Greater,
+                (Some(_), None) => Ordering::Less,
+                (None, None) => a.to_u128().cmp(&b.to_u128()),
+            },
+        }
+    }
+}
+
+/// Total order on floats that keeps IEEE equality where it is reflexive:
+/// `0.0` equals `-0.0`, and every NaN equals every other NaN and sorts
+/// after all other numbers
+fn float_cmp(a: f64, b: f64) -> Ordering {
+    match (a.is_nan(), b.is_nan()) {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Greater,
+        (false, true) => Ordering::Less,
+        (false, false) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
     }
 }
 
@@ -134,9 +195,12 @@ impl std::str::FromStr for Number {
             _ => {}
         }
 
-        // Try integer first
-        if let Ok(i) = s.parse::<i64>() {
-            return Ok(Self::Integer(i));
+        // Try integer first, in the narrowest variant that holds it
+        if let Ok(i) = s.parse::<i128>() {
+            return Ok(Self::from_i128(i));
+        }
+        if let Ok(u) = s.parse::<u128>() {
+            return Ok(Self::from_u128(u));
         }
 
         // Try float
@@ -152,9 +216,36 @@ impl Number {
     /// Get the number as an f64
     #[must_use] 
     pub const fn as_f64(&self) -> Option<f64> {
+        Some(self.to_f64())
+    }
+
+    const fn to_f64(&self) -> f64 {
+        match self {
+            Self::Float(f) => *f,
+            Self::Integer(i) => *i as f64,
+            Self::Unsigned(u) => *u as f64,
+            Self::BigInteger(i) => *i as f64,
+            Self::BigUnsigned(u) => *u as f64,
+        }
+    }
+
+    /// The exact value of an integer variant, if it fits in an `i128`;
+    /// [`Ord`] compares floats before calling this
+    fn to_i128(&self) -> Option<i128> {
+        match self {
+            Self::Integer(i) => Some(i128::from(*i)),
+            Self::Unsigned(u) => Some(i128::from(*u)),
+            Self::BigInteger(i) => Some(*i),
+            Self::BigUnsigned(u) => i128::try_from(*u).ok(),
+            Self::Float(f) => Some(*f as i128),
+        }
+    }
+
+    /// The exact value of a non-negative integer variant, or 0
+    fn to_u128(&self) -> u128 {
         match self {
-            Self::Float(f) => Some(*f),
-            Self::Integer(i) => Some(*i as f64),
+            Self::BigUnsigned(u) => *u,
+            other => other.to_i128().and_then(|i| u128::try_from(i).ok()).unwrap_or(0),
         }
     }
 
@@ -163,6 +254,7 @@ impl Number {
     pub fn as_i64(&self) -> Option<i64> {
         match self {
             Self::Integer(i) => Some(*i),
+            Self::Unsigned(_) | Self::BigInteger(_) | Self::BigUnsigned(_) => None,
             Self::Float(f) => {
                 if f.fract() == 0.0 && *f >= i64::MIN as f64 && *f <= i64::MAX as f64 {
                     Some(*f as i64)
@@ -173,6 +265,35 @@ impl Number {
         }
     }
 
+    /// `value` as an integer, unsigned only when it does not fit in an `i64`
+    fn from_u64(value: u64) -> Self {
+        i64::try_from(value).map_or(Self::Unsigned(value), Self::Integer)
+    }
+
+    /// `value` in the narrowest integer variant that holds it
+    pub(crate) fn from_i128(value: i128) -> Self {
+        match (i64::try_from(value), u64::try_from(value)) {
+            (Ok(i), _) => Self::Integer(i),
+            (_, Ok(u)) => Self::Unsigned(u),
+            _ => Self::BigInteger(value),
+        }
+    }
+
+    /// `value` in the narrowest integer variant that holds it
+    pub(crate) fn from_u128(value: u128) -> Self {
+        i128::try_from(value).map_or(Self::BigUnsigned(value), Self::from_i128)
+    }
+
+    /// Get the number as a u64 if it is a non-negative integer
+    #[must_use]
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            Self::Integer(i) => u64::try_from(*i).ok(),
+            Self::Unsigned(u) => Some(*u),
+            Self::BigInteger(_) | Self::BigUnsigned(_) | Self::Float(_) => None,
+        }
+    }
+
     /// Check if the number is an integer
     #[must_use] 
     pub const fn is_i64(&self) -> bool {
@@ -229,6 +350,80 @@ impl Value {
         matches!(self, Self::Mapping(_))
     }
 
+    /// Whether `self` and `other` hold the same content, like
+    /// [`Yaml::semantic_eq`]: mapping keys match semantically, and core
+    /// schema tags that only restate how a value resolves are ignored
+    /// (`!!str 1` equals `"1"`).
+    ///
+    /// Values that are `==` are always semantically equal.
+    ///
+    /// ```rust
+    /// use yyaml::Value;
+    ///
+    /// let a: Value = yyaml::from_str("{n: [1, .nan], s: !!str x}").unwrap();
+    /// let b: Value = yyaml::from_str("{s: x, n: [1, .NaN]}").unwrap();
+    /// assert_ne!(a, b);
+    /// assert!(a.semantic_eq(&b));
+    /// ```
+    #[must_use]
+    pub fn semantic_eq(&self, other: &Self) -> bool {
+        match (&*self.core_resolved(), &*other.core_resolved()) {
+            (Self::Number(Number::Float(a)), Self::Number(Number::Float(b))) => {
+                a == b || (a.is_nan() && b.is_nan())
+            }
+            (Self::Sequence(a), Self::Sequence(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(x, y)| x.semantic_eq(y))
+            }
+            (Self::Mapping(a), Self::Mapping(b)) => {
+                a.len() == b.len()
+                    && crate::yaml::entries_semantic_eq(
+                        a.iter(),
+                        b.iter(),
+                        |key| b.get(key),
+                        Self::semantic_eq,
+                    )
+            }
+            (Self::Tagged(a), Self::Tagged(b)) => a.tag == b.tag && a.value.semantic_eq(&b.value),
+            (a, b) => a == b,
+        }
+    }
+
+    /// This value with a core schema tag that restates its resolution
+    /// removed, so `!!str 1` becomes the string `1`
+    fn core_resolved(&self) -> std::borrow::Cow<'_, Self> {
+        use std::borrow::Cow;
+
+        let Self::Tagged(tagged) = self else {
+            return Cow::Borrowed(self);
+        };
+        let Some(kind) = tagged.tag.name.strip_prefix("tag:yaml.org,2002:") else {
+            return Cow::Borrowed(self);
+        };
+        match (kind, &tagged.value) {
+            ("str", Self::String(_))
+            | (
+                "int",
+                Self::Number(
+                    Number::Integer(_)
+                    | Number::Unsigned(_)
+                    | Number::BigInteger(_)
+                    | Number::BigUnsigned(_),
+                ),
+            )
+            | ("float", Self::Number(Number::Float(_)))
+            | ("bool", Self::Bool(_))
+            | ("null", Self::Null)
+            | ("seq", Self::Sequence(_))
+            | ("map", Self::Mapping(_)) => Cow::Borrowed(&tagged.value),
+            ("str", Self::Number(n)) => Cow::Owned(Self::String(n.to_string())),
+            ("str", Self::Bool(b)) => Cow::Owned(Self::String(b.to_string())),
+            ("float", Self::Number(Number::Integer(i))) => {
+                Cow::Owned(Self::Number(Number::Float(*i as f64)))
+            }
+            _ => Cow::Borrowed(self),
+        }
+    }
+
     /// Apply YAML merge keys (<<) to merge referenced mappings into this mapping
     /// This is a zero-allocation, in-place operation for blazing performance
     pub fn apply_merge(&mut self) -> Result<(), Error> {
@@ -278,40 +473,19 @@ impl Value {
     pub const fn into_deserializer(self) -> Deserializer {
         Deserializer::new(self)
     }
-    /// Create Value from a Yaml type
+    /// Create Value from a Yaml type; aliases and invalid nodes become null,
+    /// see [`try_from_yaml`](Self::try_from_yaml)
     pub fn from_yaml(yaml: &Yaml) -> Self {
-        match yaml {
-            Yaml::Real(s) => {
-                if let Ok(f) = s.parse::<f64>() {
-                    Self::Number(Number::Float(f))
-                } else {
-                    Self::String(s.clone())
-                }
-            }
-            Yaml::Integer(i) => Self::Number(Number::Integer(*i)),
-            Yaml::String(s) => Self::String(s.clone()),
-            Yaml::Boolean(b) => Self::Bool(*b),
-            Yaml::Array(arr) => {
-                let seq: Vec<Self> = arr.iter().map(Self::from_yaml).collect();
-                Self::Sequence(seq)
-            }
-            Yaml::Hash(hash) => {
-                let mut map = BTreeMap::new();
-                for (k, v) in hash.iter() {
-                    map.insert(Self::from_yaml(k), Self::from_yaml(v));
-                }
-                Self::Mapping(map)
-            }
-            Yaml::Alias(_) => Self::Null, // Aliases should be resolved before this point
-            Yaml::Tagged(tag_name, boxed_yaml) => {
-                // Preserve tagged content instead of extracting it
-                Self::Tagged(Box::new(TaggedValue {
-                    tag: Tag::new(tag_name.clone()),
-                    value: Self::from_yaml(boxed_yaml),
-                }))
-            }
-            Yaml::Null | Yaml::BadValue => Self::Null,
-        }
+        Self::try_from_yaml(yaml).0
+    }
+
+    /// Parse a single YAML document, resolving untagged plain scalars with
+    /// `schema`; a stream with several documents is an error
+    ///
+    /// Like [`from_str_value`](crate::from_str_value), the value is built
+    /// from the parser's events without loading a [`Yaml`] tree.
+    pub fn from_str_with(s: &str, schema: SchemaMode) -> Result<Self, Error> {
+        crate::single_value(s, crate::YamlLoader::load_values(s, schema)?)
     }
 
     /// Check if the value is null
@@ -338,12 +512,66 @@ impl Value {
         }
     }
 
+    /// Get the value as a usize if it is a non-negative integer that fits;
+    /// floats are not integers here, even whole ones like `3.0`
+    #[must_use]
+    pub fn as_usize(&self) -> Option<usize> {
+        match self {
+            Self::Number(Number::Integer(i)) => usize::try_from(*i).ok(),
+            Self::Number(Number::Unsigned(u)) => usize::try_from(*u).ok(),
+            _ => None,
+        }
+    }
+
+    /// Like [`Value::as_usize`], but only if the integer lies in `bounds`,
+    /// e.g. `1..=64` for a worker count
+    #[must_use]
+    pub fn as_usize_in<R>(&self, bounds: R) -> Option<usize>
+    where
+        R: std::ops::RangeBounds<usize>,
+    {
+        self.as_usize().filter(|n| bounds.contains(n))
+    }
+
+    /// Move the value out, leaving `Value::Null` in its place
+    pub fn take(&mut self) -> Self {
+        std::mem::take(self)
+    }
+
+    /// Move the node at `path` out, leaving `Value::Null` in its place, or
+    /// `None` if there is no node there
+    pub fn pointer_take(&mut self, path: &str) -> Option<Self> {
+        self.pointer_mut(path).map(Self::take)
+    }
+
+    /// Call `f` on every string scalar in the tree, in place
+    ///
+    /// Mapping keys are left alone, since rewriting them could merge entries.
+    pub fn map_over_strings<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&mut String),
+    {
+        self.visit_strings(&mut f);
+    }
+
+    fn visit_strings<F>(&mut self, f: &mut F)
+    where
+        F: FnMut(&mut String),
+    {
+        match self {
+            Self::String(s) => f(s),
+            Self::Sequence(items) => items.iter_mut().for_each(|item| item.visit_strings(f)),
+            Self::Mapping(map) => map.values_mut().for_each(|value| value.visit_strings(f)),
+            Self::Tagged(tagged) => tagged.value.visit_strings(f),
+            _ => {}
+        }
+    }
+
     /// Get the value as an f64 if it is a float
     #[must_use] 
     pub const fn as_f64(&self) -> Option<f64> {
         match self {
-            Self::Number(Number::Float(f)) => Some(*f),
-            Self::Number(Number::Integer(i)) => Some(*i as f64),
+            Self::Number(n) => n.as_f64(),
             _ => None,
         }
     }
@@ -366,6 +594,79 @@ impl Value {
         }
     }
 
+    /// A `!!set` of `members` in the order given, dropping repeats
+    ///
+    /// Loading represents a `!!set` the same way, and serializing writes it
+    /// back in the explicit-key style, `? member` per line.
+    ///
+    /// ```rust
+    /// use yyaml::Value;
+    ///
+    /// let doc: Value = "hosts: !!set\n  ? web\n  ? db\n".parse().unwrap();
+    /// let hosts: Vec<_> = doc["hosts"].as_set().unwrap().iter().filter_map(Value::as_str).collect();
+    /// assert_eq!(hosts, ["web", "db"]);
+    /// assert_eq!(yyaml::to_string(&doc).unwrap(), "---\nhosts: !!set\n  ? web\n  ? db");
+    /// ```
+    pub fn ordered_set<I: IntoIterator<Item = Self>>(members: I) -> Self {
+        let mut unique = Sequence::new();
+        for member in members {
+            if !unique.contains(&member) {
+                unique.push(member);
+            }
+        }
+        Self::Tagged(Box::new(TaggedValue::new(Tag::new(SET), Self::Sequence(unique))))
+    }
+
+    /// The members of a `!!set`, in order, if this is one
+    #[must_use]
+    pub fn as_set(&self) -> Option<&Sequence> {
+        match self {
+            Self::Tagged(tagged) if tagged.tag.name == SET => tagged.value.as_sequence(),
+            _ => None,
+        }
+    }
+
+    /// The tag of a tagged value; the outer one if it is tagged twice
+    ///
+    /// ```rust
+    /// use yyaml::Value;
+    /// use yyaml::value::Tag;
+    ///
+    /// let value: Value = "!celsius 21".parse().unwrap();
+    /// assert_eq!(value.tag().map(|tag| tag.name.as_str()), Some("celsius"));
+    /// let value = value.with_tag(Tag::new("fahrenheit"));
+    /// assert_eq!(value.tag(), Some(&Tag::new("fahrenheit")));
+    /// assert_eq!(value.untag().as_i64(), Some(21));
+    /// ```
+    #[must_use]
+    pub fn tag(&self) -> Option<&Tag> {
+        match self {
+            Self::Tagged(tagged) => Some(&tagged.tag),
+            _ => None,
+        }
+    }
+
+    /// This value tagged `tag`, replacing its outer tag if it has one
+    #[must_use]
+    pub fn with_tag(self, tag: Tag) -> Self {
+        match self {
+            Self::Tagged(mut tagged) => {
+                tagged.tag = tag;
+                Self::Tagged(tagged)
+            }
+            value => Self::Tagged(Box::new(TaggedValue::new(tag, value))),
+        }
+    }
+
+    /// This value without its outer tag; an untagged value is unchanged
+    #[must_use]
+    pub fn untag(self) -> Self {
+        match self {
+            Self::Tagged(tagged) => tagged.value,
+            value => value,
+        }
+    }
+
     /// Get the value as a mapping if it is one
     #[must_use] 
     pub const fn as_mapping(&self) -> Option<&Mapping> {
@@ -374,39 +675,313 @@ impl Value {
             _ => None,
         }
     }
-}
 
-impl fmt::Display for Value {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    /// Entry under the string key `key`, or `None` if this is not a mapping
+    /// or the key is absent
+    ///
+    /// Unlike indexing, a key that is present with a null value gives
+    /// `Some(&Value::Null)`. Tagged nodes are transparent.
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&Self> {
         match self {
-            Self::Null => write!(f, "null"),
-            Self::Bool(b) => write!(f, "{}", b),
-            Self::Number(n) => write!(f, "{}", n),
-            Self::String(s) => write!(f, "{}", s),
-            Self::Sequence(seq) => {
-                write!(f, "[")?;
-                for (i, v) in seq.iter().enumerate() {
-                    if i > 0 {
-                        write!(f, ", ")?;
+            Self::Mapping(map) => mapping_get(map, key),
+            Self::Tagged(tagged) => tagged.value.get(key),
+            _ => None,
+        }
+    }
+
+    /// Mutable variant of [`Value::get`]
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Self> {
+        match self {
+            Self::Mapping(map) => mapping_get_mut(map, key),
+            Self::Tagged(tagged) => tagged.value.get_mut(key),
+            _ => None,
+        }
+    }
+
+    /// Sequence item at `index`, or `None` if this is not a sequence or the
+    /// index is out of bounds
+    #[must_use]
+    pub fn get_index(&self, index: usize) -> Option<&Self> {
+        match self {
+            Self::Sequence(seq) => seq.get(index),
+            Self::Tagged(tagged) => tagged.value.get_index(index),
+            _ => None,
+        }
+    }
+
+    /// Mutable variant of [`Value::get_index`]
+    pub fn get_index_mut(&mut self, index: usize) -> Option<&mut Self> {
+        match self {
+            Self::Sequence(seq) => seq.get_mut(index),
+            Self::Tagged(tagged) => tagged.value.get_index_mut(index),
+            _ => None,
+        }
+    }
+
+    /// Follow `path` one segment at a time, e.g. `&["spec", "ports", "0"]`
+    ///
+    /// Segments select mapping keys, or sequence items by decimal index, the
+    /// same way [`Value::pointer`] does but without `~` escaping.
+    #[must_use]
+    pub fn get_path(&self, path: &[&str]) -> Option<&Self> {
+        path.iter()
+            .try_fold(self, |node, segment| node.pointer_child(segment))
+    }
+
+    /// Mutable variant of [`Value::get_path`]
+    pub fn get_path_mut(&mut self, path: &[&str]) -> Option<&mut Self> {
+        path.iter()
+            .try_fold(self, |node, segment| node.pointer_child_mut(segment))
+    }
+
+    /// Approximate bytes held by this value and everything it owns
+    ///
+    /// Unused capacity is included; mapping storage is estimated from typical
+    /// B-tree occupancy, so treat the result as a planning figure.
+    #[must_use]
+    pub fn estimated_size(&self) -> usize {
+        std::mem::size_of::<Self>() + self.heap_size()
+    }
+
+    fn heap_size(&self) -> usize {
+        match self {
+            Self::String(s) => s.capacity(),
+            Self::Sequence(items) => {
+                items.capacity() * std::mem::size_of::<Self>()
+                    + items.iter().map(Self::heap_size).sum::<usize>()
+            }
+            Self::Mapping(map) => {
+                crate::linked_hash_map::btree_entries_size::<(Self, Self)>(map.len())
+                    + map
+                        .iter()
+                        .map(|(key, value)| key.heap_size() + value.heap_size())
+                        .sum::<usize>()
+            }
+            Self::Tagged(tagged) => {
+                std::mem::size_of::<TaggedValue>()
+                    + tagged.tag.name.capacity()
+                    + tagged.value.heap_size()
+            }
+            _ => 0,
+        }
+    }
+
+    /// Look up a node by JSON Pointer path, e.g. `/spec/replicas` or `/items/0`
+    ///
+    /// Tagged nodes are transparent, so a pointer walks through their content.
+    #[must_use]
+    pub fn pointer(&self, path: &str) -> Option<&Self> {
+        let tokens = crate::pointer::tokens(path).ok()?;
+        tokens
+            .iter()
+            .try_fold(self, |node, token| node.pointer_child(token))
+    }
+
+    /// Mutable variant of [`Value::pointer`]
+    pub fn pointer_mut(&mut self, path: &str) -> Option<&mut Self> {
+        let tokens = crate::pointer::tokens(path).ok()?;
+        tokens
+            .iter()
+            .try_fold(self, |node, token| node.pointer_child_mut(token))
+    }
+
+    /// Serialize `value` and graft it into this document at `path`
+    ///
+    /// Missing intermediate mappings are created, `null` nodes along the way
+    /// become mappings, and `-` (or an index equal to the length) appends to a
+    /// sequence. The empty path replaces the whole document.
+    pub fn set_from<T>(&mut self, path: &str, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        let tokens = crate::pointer::tokens(path)?;
+        let new_value = crate::to_value(value)?;
+        let mut node = self;
+        for token in &tokens {
+            node = node.pointer_child_or_insert(token)?;
+        }
+        *node = new_value;
+        Ok(())
+    }
+
+    /// Every leaf of the tree with its JSON Pointer path, in document order
+    ///
+    /// Leaves are scalars, empty sequences and mappings, and tagged nodes,
+    /// which are yielded whole. [`Value::unflatten`] rebuilds the tree when
+    /// every mapping key is a string other than `0`; paths do not record
+    /// key types, so see there for what comes back otherwise.
+    ///
+    /// ```rust
+    /// use yyaml::Value;
+    ///
+    /// let value: Value = yyaml::from_str("db: {host: x, ports: [1, 2]}\nflags: {}").unwrap();
+    /// let paths: Vec<_> = value.flatten().map(|(path, _)| path).collect();
+    /// assert_eq!(paths, ["/db/host", "/db/ports/0", "/db/ports/1", "/flags"]);
+    /// assert_eq!(Value::unflatten(value.flatten().map(|(p, v)| (p, v.clone()))).unwrap(), value);
+    /// ```
+    pub fn flatten(&self) -> impl Iterator<Item = (String, &Self)> + '_ {
+        let mut stack = vec![(String::new(), self)];
+        std::iter::from_fn(move || {
+            while let Some((path, node)) = stack.pop() {
+                match node {
+                    Self::Sequence(items) if !items.is_empty() => {
+                        let children = items.iter().enumerate().rev();
+                        stack.extend(children.map(|(i, item)| (format!("{path}/{i}"), item)));
+                    }
+                    Self::Mapping(map) if !map.is_empty() => {
+                        let children = map.iter().rev().map(|(key, value)| {
+                            (format!("{path}/{}", crate::pointer::value_key_token(key)), value)
+                        });
+                        stack.extend(children);
                     }
-                    write!(f, "{}", v)?;
+                    _ => return Some((path, node)),
+                }
+            }
+            None
+        })
+    }
+
+    /// Build a tree from `(path, leaf)` pairs such as
+    /// [`Value::flatten`] yields
+    ///
+    /// Missing containers are created on the way: a sequence when the
+    /// first token under it is `0`, otherwise a mapping with string keys.
+    /// Sequence items must arrive in order.
+    ///
+    /// Paths carry no key types, so a flattened mapping does not always
+    /// come back as it was: one whose first key is `0` (string or integer)
+    /// is rebuilt as a sequence, and non-string keys such as `5` or `true`
+    /// come back as the strings `"5"` and `"true"`.
+    ///
+    /// ```rust
+    /// use yyaml::Value;
+    ///
+    /// let value: Value = yyaml::from_str("{0: a, 1: b}").unwrap();
+    /// let rebuilt = Value::unflatten(value.flatten().map(|(p, v)| (p, v.clone()))).unwrap();
+    /// assert_eq!(rebuilt, yyaml::from_str::<Value>("[a, b]").unwrap());
+    ///
+    /// let value: Value = yyaml::from_str("{5: a}").unwrap();
+    /// let rebuilt = Value::unflatten(value.flatten().map(|(p, v)| (p, v.clone()))).unwrap();
+    /// assert_eq!(rebuilt, yyaml::from_str::<Value>("{'5': a}").unwrap());
+    /// ```
+    pub fn unflatten<I, S>(entries: I) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = (S, Self)>,
+        S: AsRef<str>,
+    {
+        let mut root = Self::Null;
+        for (path, leaf) in entries {
+            let mut node = &mut root;
+            for token in crate::pointer::tokens(path.as_ref())? {
+                if node.is_null() && token == "0" {
+                    *node = Self::Sequence(Vec::new());
                 }
-                write!(f, "]")
+                node = node.pointer_child_or_insert(&token)?;
             }
+            *node = leaf;
+        }
+        Ok(root)
+    }
+
+    /// Deserialize the node at `path` into a typed value
+    pub fn get_as<T>(&self, path: &str) -> Result<T, Error>
+    where
+        T: de::DeserializeOwned,
+    {
+        let tokens = crate::pointer::tokens(path)?;
+        let node = tokens
+            .iter()
+            .try_fold(self, |node, token| node.pointer_child(token))
+            .ok_or_else(|| Error::Custom(format!("no value at path '{path}'")))?;
+        T::deserialize(node)
+    }
+
+    pub(crate) fn pointer_child(&self, token: &str) -> Option<&Self> {
+        match self {
+            Self::Sequence(seq) => crate::pointer::index(token).and_then(|i| seq.get(i)),
+            Self::Mapping(map) => map.get(&Self::pointer_key(map, token)),
+            Self::Tagged(tagged) => tagged.value.pointer_child(token),
+            _ => None,
+        }
+    }
+
+    fn pointer_child_mut(&mut self, token: &str) -> Option<&mut Self> {
+        match self {
+            Self::Sequence(seq) => crate::pointer::index(token).and_then(|i| seq.get_mut(i)),
             Self::Mapping(map) => {
-                write!(f, "{{")?;
-                for (i, (k, v)) in map.iter().enumerate() {
-                    if i > 0 {
-                        write!(f, ", ")?;
-                    }
-                    write!(f, "{}: {}", k, v)?;
+                let key = Self::pointer_key(map, token);
+                map.get_mut(&key)
+            }
+            Self::Tagged(tagged) => tagged.value.pointer_child_mut(token),
+            _ => None,
+        }
+    }
+
+    fn pointer_child_or_insert(&mut self, token: &str) -> Result<&mut Self, Error> {
+        if self.is_null() {
+            *self = Self::Mapping(Mapping::new());
+        }
+        match self {
+            Self::Sequence(seq) => {
+                let len = seq.len();
+                let index = if token == "-" {
+                    len
+                } else {
+                    crate::pointer::index(token).ok_or_else(|| {
+                        Error::Custom(format!("invalid sequence index '{token}'"))
+                    })?
+                };
+                if index == len {
+                    seq.push(Self::Null);
                 }
-                write!(f, "}}")
+                seq.get_mut(index).ok_or_else(|| {
+                    Error::Custom(format!(
+                        "sequence index {index} out of bounds (length {len})"
+                    ))
+                })
             }
-            Self::Tagged(tagged) => {
-                write!(f, "{}:{}", tagged.tag.name, tagged.value)
+            Self::Mapping(map) => {
+                let key = Self::pointer_key(map, token);
+                Ok(map.entry(key).or_insert(Self::Null))
+            }
+            Self::Tagged(tagged) => tagged.value.pointer_child_or_insert(token),
+            other => Err(Error::Custom(format!(
+                "cannot descend into scalar {other} at '{token}'"
+            ))),
+        }
+    }
+
+    /// Resolve a pointer token to a mapping key, falling back to an integer
+    /// key when the mapping has no string key of that name.
+    fn pointer_key(map: &Mapping, token: &str) -> Self {
+        let key = Self::String(token.to_owned());
+        if !map.contains_key(&key)
+            && let Ok(i) = token.parse::<i64>()
+        {
+            let int_key = Self::Number(Number::Integer(i));
+            if map.contains_key(&int_key) {
+                return int_key;
             }
         }
+        key
+    }
+}
+
+/// Writes the value as a YAML document, tags included
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (yaml, _) = self.to_yaml();
+        crate::YamlEmitter::new(f).dump(&yaml).map_err(|_| fmt::Error)
+    }
+}
+
+/// Parses a single document with the default [`SchemaMode`]
+impl std::str::FromStr for Value {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        Self::from_str_with(s, SchemaMode::default())
     }
 }
 
@@ -414,6 +989,9 @@ impl fmt::Display for Number {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::Integer(i) => write!(f, "{}", i),
+            Self::Unsigned(u) => write!(f, "{}", u),
+            Self::BigInteger(i) => write!(f, "{}", i),
+            Self::BigUnsigned(u) => write!(f, "{}", u),
             Self::Float(n) => write!(f, "{}", n),
         }
     }
@@ -439,6 +1017,18 @@ impl Serialize for Value {
                 }
                 map_serializer.end()
             }
+            Self::Tagged(tagged)
+                if tagged.tag.name == crate::base64::BINARY_TAG
+                    && let Self::String(encoded) = &tagged.value
+                    && let Some(bytes) = crate::base64::decode(encoded) =>
+            {
+                serializer.serialize_bytes(&bytes)
+            }
+            Self::Tagged(tagged)
+                if tagged.tag.name == SET && let Self::Sequence(members) = &tagged.value =>
+            {
+                serializer.serialize_newtype_struct(SET_STRUCT, members)
+            }
             Self::Tagged(tagged) => {
                 // For serialization, we just serialize the inner value
                 // The tag information might be lost in this process
@@ -455,6 +1045,9 @@ impl Serialize for Number {
     {
         match self {
             Self::Integer(i) => serializer.serialize_i64(*i),
+            Self::Unsigned(u) => serializer.serialize_u64(*u),
+            Self::BigInteger(i) => serializer.serialize_i128(*i),
+            Self::BigUnsigned(u) => serializer.serialize_u128(*u),
             Self::Float(f) => serializer.serialize_f64(*f),
         }
     }
@@ -484,11 +1077,15 @@ impl<'de> Deserialize<'de> for Value {
             }
 
             fn visit_u64<E>(self, value: u64) -> Result<Value, E> {
-                if value <= i64::MAX as u64 {
-                    Ok(Value::Number(Number::Integer(value as i64)))
-                } else {
-                    Ok(Value::Number(Number::Float(value as f64)))
-                }
+                Ok(Value::Number(Number::from_u64(value)))
+            }
+
+            fn visit_i128<E>(self, value: i128) -> Result<Value, E> {
+                Ok(Value::Number(Number::from_i128(value)))
+            }
+
+            fn visit_u128<E>(self, value: u128) -> Result<Value, E> {
+                Ok(Value::Number(Number::from_u128(value)))
             }
 
             fn visit_f64<E>(self, value: f64) -> Result<Value, E> {
@@ -503,6 +1100,13 @@ impl<'de> Deserialize<'de> for Value {
                 Ok(Value::String(value))
             }
 
+            fn visit_bytes<E>(self, value: &[u8]) -> Result<Value, E> {
+                Ok(Value::Tagged(Box::new(TaggedValue::new(
+                    Tag::new(crate::base64::BINARY_TAG),
+                    Value::String(crate::base64::encode(value)),
+                ))))
+            }
+
             fn visit_none<E>(self) -> Result<Value, E> {
                 Ok(Value::Null)
             }
@@ -539,9 +1143,40 @@ impl<'de> Deserialize<'de> for Value {
                 }
                 Ok(Value::Mapping(btree))
             }
+
+            fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Value, D::Error>
+            where
+                D: de::Deserializer<'de>,
+            {
+                deserializer.deserialize_any(self)
+            }
+
+            // A tagged value, as the variant of [`TAGGED_STRUCT`]
+            fn visit_enum<A>(self, data: A) -> Result<Value, A::Error>
+            where
+                A: de::EnumAccess<'de>,
+            {
+                use de::VariantAccess;
+
+                let (tag, variant): (String, _) = data.variant()?;
+                let value = variant.newtype_variant()?;
+                Ok(Value::Tagged(Box::new(TaggedValue::new(Tag::new(tag), value))))
+            }
         }
 
-        deserializer.deserialize_any(ValueVisitor)
+        deserializer.deserialize_newtype_struct(TAGGED_STRUCT, ValueVisitor)
+    }
+}
+
+impl<'de> Deserialize<'de> for TaggedValue {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        match Value::deserialize(deserializer)? {
+            Value::Tagged(tagged) => Ok(*tagged),
+            _ => Err(de::Error::custom("expected a tagged value")),
+        }
     }
 }
 
@@ -564,11 +1199,15 @@ impl<'de> Deserialize<'de> for Number {
             }
 
             fn visit_u64<E>(self, value: u64) -> Result<Number, E> {
-                if value <= i64::MAX as u64 {
-                    Ok(Number::Integer(value as i64))
-                } else {
-                    Ok(Number::Float(value as f64))
-                }
+                Ok(Number::from_u64(value))
+            }
+
+            fn visit_i128<E>(self, value: i128) -> Result<Number, E> {
+                Ok(Number::from_i128(value))
+            }
+
+            fn visit_u128<E>(self, value: u128) -> Result<Number, E> {
+                Ok(Number::from_u128(value))
             }
 
             fn visit_f64<E>(self, value: f64) -> Result<Number, E> {
@@ -583,336 +1222,1800 @@ impl<'de> Deserialize<'de> for Number {
 /// A deserializer for Value
 pub struct Deserializer {
     value: Value,
+    modes: Modes,
+    report: Option<DefaultsReport>,
+    collect: Option<Collect>,
 }
 
-/// High-performance document iterator for multi-document YAML streams
-/// Implements zero-allocation iteration over parsed documents
-/// Matches the expected API pattern from tests
-pub struct DocumentIterator {
-    docs: Vec<crate::yaml::Yaml>,
-    index: usize,
+/// Deserializer settings that apply to every nested value
+#[derive(Clone, Copy)]
+struct Modes {
+    human_readable: bool,
+    lenient: bool,
+    coerce: bool,
+    fields: FieldNameNormalization,
 }
 
-impl DocumentIterator {
-    /// Create new iterator from parsed documents
-    #[must_use] 
-    pub const fn new(docs: Vec<crate::yaml::Yaml>) -> Self {
-        Self { docs, index: 0 }
-    }
+impl Modes {
+    const DEFAULT: Self = Self {
+        human_readable: true,
+        lenient: false,
+        coerce: false,
+        fields: FieldNameNormalization::Exact,
+    };
 }
 
-impl Iterator for DocumentIterator {
-    type Item = Deserializer;
+/// A struct field that was missing from the input, so the struct's
+/// `Deserialize` impl filled it in, e.g. from `#[serde(default)]` or as
+/// `None` for an `Option`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DefaultedField {
+    /// Dotted path of the field from the root, e.g. `server.tls.port` or
+    /// `listeners[1].port`
+    pub path: String,
+}
 
-    /// Get next document deserializer with blazing performance
-    /// Returns Some(deserializer) for valid documents, None when exhausted
-    /// Handles errors internally for ergonomic API matching test expectations
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.index < self.docs.len() {
-            let yaml = &self.docs[self.index];
-            self.index += 1;
-            let value = Value::from_yaml(yaml);
-            Some(Deserializer::new(value))
+/// Where [`from_value_with_defaults`] collects missing fields, and the
+/// path of the value being deserialized
+#[derive(Clone)]
+struct DefaultsReport {
+    path: String,
+    defaulted: Arc<Mutex<Vec<DefaultedField>>>,
+}
+
+impl DefaultsReport {
+    fn child(&self, segment: fmt::Arguments<'_>) -> Self {
+        let path = if self.path.is_empty() {
+            segment.to_string()
         } else {
-            None
+            format!("{}{segment}", self.path)
+        };
+        Self {
+            path,
+            defaulted: Arc::clone(&self.defaulted),
         }
     }
-}
+
+    fn field(&self, key: &Value) -> Self {
+        let name = identifier(key.clone()).unwrap_or_default();
+        if self.path.is_empty() {
+            self.child(format_args!("{name}"))
+        } else {
+            self.child(format_args!(".{name}"))
+        }
+    }
+
+    /// Record each of `fields` that `map` lacks
+    fn missing(&self, fields: &[&str], map: Option<&Mapping>) {
+        let Ok(mut defaulted) = self.defaulted.lock() else {
+            return;
+        };
+        for field in fields {
+            let key = Value::String((*field).to_owned());
+            if !map.is_some_and(|map| map.contains_key(&key)) {
+                defaulted.push(DefaultedField {
+                    path: self.field(&key).path,
+                });
+            }
+        }
+    }
+}
+
+/// State of [`DeserializerConfig::collect_errors`]: the JSON Pointer of
+/// the value being read, and where errors go when reading carries on past
+/// them (`None` to fail on the first one, only locating it)
+#[derive(Clone)]
+struct Collect {
+    path: String,
+    errors: Option<Arc<Mutex<Vec<Error>>>>,
+}
+
+impl Collect {
+    fn child(&self, token: &str) -> Self {
+        Self {
+            path: format!("{}/{token}", self.path),
+            errors: self.errors.clone(),
+        }
+    }
+
+    /// Attach the path of `collect` to an error raised without one
+    fn locate(collect: Option<&Self>, e: Error) -> Error {
+        match (collect, e) {
+            (Some(collect), e @ (Error::Custom(_) | Error::Scan(_))) => Error::At {
+                path: collect.path.clone(),
+                mark: None,
+                source: Box::new(e),
+            },
+            (_, e) => e,
+        }
+    }
+
+    /// Whether errors are recorded and reading carries on past them
+    fn collecting(collect: Option<&Self>) -> bool {
+        collect.is_some_and(|collect| collect.errors.is_some())
+    }
+
+    /// Record `e`, raised for a child of the node at this path, unless it
+    /// is already recorded
+    fn record(&self, e: Error) {
+        let Some(errors) = &self.errors else {
+            return;
+        };
+        if !is_recorded(&e) {
+            let e = match e {
+                Error::Coerce {
+                    path,
+                    mark,
+                    message,
+                } => Error::Coerce {
+                    path: format!("{}{path}", self.path),
+                    mark,
+                    message,
+                },
+                e => e,
+            };
+            if let Ok(mut errors) = errors.lock() {
+                errors.push(e);
+            }
+        }
+    }
+
+    /// Carry on past `e`, raised for a child of the node at this path, by
+    /// recording it and reading a [`Placeholder`] in the child's place
+    fn recover<'de, T: Deserialize<'de>>(collect: Option<&Self>, e: Error) -> Result<T, Error> {
+        match collect.filter(|collect| collect.errors.is_some()) {
+            Some(collect) => {
+                collect.record(e);
+                T::deserialize(Placeholder).map_err(|_| recorded())
+            }
+            None => Err(e),
+        }
+    }
+}
+
+/// Stands for a value whose errors are already recorded: a placeholder
+/// the target type rejects fails the parent in turn, which then stands in
+/// a placeholder of its own
+const fn recorded() -> Error {
+    Error::Multiple(Vec::new())
+}
+
+fn is_recorded(e: &Error) -> bool {
+    matches!(e, Error::Multiple(errors) if errors.is_empty())
+}
+
+/// Deserialize `value` once, reading a [`Placeholder`] in place of each
+/// node that fails so the rest is still checked; every error found is
+/// returned in [`Error::Multiple`]
+pub(crate) fn deserialize_collecting<T>(value: Value, config: &DeserializerConfig) -> Result<T, Error>
+where
+    T: serde::de::DeserializeOwned,
+{
+    let errors = Arc::new(Mutex::new(Vec::new()));
+    let mut deserializer = Deserializer::with_config(value, config);
+    deserializer.collect = Some(Collect {
+        path: String::new(),
+        errors: Some(Arc::clone(&errors)),
+    });
+    let result = T::deserialize(deserializer);
+    let mut errors = errors
+        .lock()
+        .map(|mut errors| std::mem::take(&mut *errors))
+        .map_err(|_| Error::Custom("error list poisoned".to_string()))?;
+    match result {
+        Ok(parsed) if errors.is_empty() => Ok(parsed),
+        Ok(_) => Err(Error::Multiple(errors)),
+        Err(e) => {
+            if !is_recorded(&e) {
+                errors.push(e);
+            }
+            Err(Error::Multiple(errors))
+        }
+    }
+}
+
+/// The most ordinary value of whatever type reads it: zero, empty, `None`
+/// or the first variant. Read in place of a node that failed, so the
+/// fields after it are still checked.
+#[derive(Clone, Copy)]
+struct Placeholder;
+
+impl<'de> de::IntoDeserializer<'de, Error> for Placeholder {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Placeholder {
+    type Error = Error;
+
+    fn deserialize_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_bool<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_bool(false)
+    }
+
+    fn deserialize_i8<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i8(0)
+    }
+
+    fn deserialize_i16<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i16(0)
+    }
+
+    fn deserialize_i32<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i32(0)
+    }
+
+    fn deserialize_i64<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i64(0)
+    }
+
+    fn deserialize_u8<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u8(0)
+    }
+
+    fn deserialize_u16<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u16(0)
+    }
+
+    fn deserialize_u32<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u32(0)
+    }
+
+    fn deserialize_u64<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u64(0)
+    }
+
+    fn deserialize_f32<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_f32(0.0)
+    }
+
+    fn deserialize_f64<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_f64(0.0)
+    }
+
+    fn deserialize_char<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_char('\0')
+    }
+
+    fn deserialize_str<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_str("")
+    }
+
+    fn deserialize_string<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_str("")
+    }
+
+    fn deserialize_bytes<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_bytes(&[])
+    }
+
+    fn deserialize_byte_buf<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_bytes(&[])
+    }
+
+    fn deserialize_option<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_none()
+    }
+
+    fn deserialize_unit<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_seq(de::value::SeqDeserializer::new(std::iter::empty::<Self>()))
+    }
+
+    fn deserialize_tuple<V: de::Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_seq(de::value::SeqDeserializer::new(std::iter::repeat_n(self, len)))
+    }
+
+    fn deserialize_tuple_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_map<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_map(de::value::MapDeserializer::new(std::iter::empty::<(Self, Self)>()))
+    }
+
+    fn deserialize_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_map(de::value::MapDeserializer::new(
+            fields.iter().map(|&field| (field, self)),
+        ))
+    }
+
+    fn deserialize_enum<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let variant = variants
+            .first()
+            .ok_or_else(|| Error::Custom("enum has no variants".to_string()))?;
+        visitor.visit_enum(PlaceholderVariant(variant))
+    }
+
+    fn deserialize_identifier<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_str("")
+    }
+
+    fn deserialize_ignored_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_i128<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i128(0)
+    }
+
+    fn deserialize_u128<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u128(0)
+    }
+}
+
+/// The first variant of an enum, with placeholder content
+struct PlaceholderVariant(&'static str);
+
+impl<'de> de::EnumAccess<'de> for PlaceholderVariant {
+    type Error = Error;
+    type Variant = Placeholder;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Placeholder), Error>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        let variant = seed.deserialize(de::IntoDeserializer::<Error>::into_deserializer(self.0))?;
+        Ok((variant, Placeholder))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for Placeholder {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Error>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: de::Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_tuple(self, len, visitor)
+    }
+
+    fn struct_variant<V: de::Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_struct(self, "", fields, visitor)
+    }
+}
+
+/// How mapping keys are matched to struct field names, see
+/// [`DeserializerConfig::field_name_normalization`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FieldNameNormalization {
+    /// Keys must equal the field name (after any `#[serde(rename)]`)
+    #[default]
+    Exact,
+    /// `MaxConnections` matches `maxconnections`
+    CaseInsensitive,
+    /// Case and `-`/`_` separators are ignored, so `maxConnections`,
+    /// `max-connections` and `max_connections` all match
+    NormalizeSeparators,
+}
+
+impl FieldNameNormalization {
+    fn matches(self, key: &str, field: &str) -> bool {
+        match self {
+            Self::Exact => key == field,
+            Self::CaseInsensitive => key.eq_ignore_ascii_case(field),
+            Self::NormalizeSeparators => {
+                fn letters(s: &str) -> impl Iterator<Item = char> + '_ {
+                    s.chars()
+                        .filter(|c| !matches!(c, '-' | '_'))
+                        .map(|c| c.to_ascii_lowercase())
+                }
+                letters(key).eq(letters(field))
+            }
+        }
+    }
+
+    /// Rename the keys of `map` that loosely match one of `fields` to that
+    /// field. A key that already names a field exactly wins over loose
+    /// matches, which are then left to be reported as unknown; two loose
+    /// matches of one field are an error.
+    fn apply(self, map: Mapping, fields: &[&str]) -> Result<Mapping, Error> {
+        if self == Self::Exact {
+            return Ok(map);
+        }
+        let exact = |field: &str| mapping_get(&map, field).is_some();
+        let taken: Vec<bool> = fields.iter().map(|field| exact(field)).collect();
+        let mut matched: Vec<Option<String>> = vec![None; fields.len()];
+        let mut renamed = Mapping::new();
+        for (key, value) in map {
+            let key = match key {
+                Value::String(k) if !fields.contains(&k.as_str()) => {
+                    let found = (0..fields.len())
+                        .find(|&i| !taken[i] && self.matches(&k, fields[i]));
+                    let Some(i) = found else {
+                        renamed.insert(Value::String(k), value);
+                        continue;
+                    };
+                    if let Some(first) = &matched[i] {
+                        return Err(Error::Custom(format!(
+                            "keys `{first}` and `{k}` both match field `{}`",
+                            fields[i]
+                        )));
+                    }
+                    matched[i] = Some(k);
+                    Value::String(fields[i].to_owned())
+                }
+                key => key,
+            };
+            renamed.insert(key, value);
+        }
+        Ok(renamed)
+    }
+}
+
+/// Options for [`from_str_with_config`](crate::from_str_with_config) and
+/// [`parse_all_with_config`](crate::parse_all_with_config)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DeserializerConfig {
+    pub(crate) empty_scalar: EmptyScalar,
+    pub(crate) schema: SchemaMode,
+    pub(crate) integer_overflow: IntegerOverflow,
+    pub(crate) null_words: Option<&'static [&'static str]>,
+    pub(crate) compact: bool,
+    pub(crate) lenient_collections: bool,
+    pub(crate) coerce_strings: bool,
+    pub(crate) field_names: FieldNameNormalization,
+    pub(crate) collect_errors: bool,
+    pub(crate) profile: Option<Profile>,
+}
+
+impl DeserializerConfig {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Choose whether `key:` deserializes like `key: ~` (the default) or
+    /// like `key: ""`, e.g. `None` versus `Some("")` for `Option<String>`
+    #[must_use]
+    pub const fn empty_scalar_as(mut self, empty: EmptyScalar) -> Self {
+        self.empty_scalar = empty;
+        self
+    }
+
+    /// Choose how untagged plain scalars resolve, e.g. [`SchemaMode::Json`]
+    /// so `yes` and `on` deserialize only as strings
+    #[must_use]
+    pub const fn schema(mut self, schema: SchemaMode) -> Self {
+        self.schema = schema;
+        self
+    }
+
+    /// Choose what integers too large for an `i64` load as, e.g.
+    /// [`IntegerOverflow::Error`] so they fail instead of losing precision
+    /// as floats
+    #[must_use]
+    pub const fn integer_overflow(mut self, policy: IntegerOverflow) -> Self {
+        self.integer_overflow = policy;
+        self
+    }
+
+    /// Resolve exactly `words` as null among plain scalars; see
+    /// [`LoaderOptions::null_words`]
+    #[must_use]
+    pub const fn null_words(mut self, words: &'static [&'static str]) -> Self {
+        self.null_words = Some(words);
+        self
+    }
+
+    /// Report `is_human_readable() == false` to `Deserialize` impls, for
+    /// data written by a serializer in machine-oriented mode
+    #[must_use]
+    pub const fn human_readable(mut self, human_readable: bool) -> Self {
+        self.compact = !human_readable;
+        self
+    }
+
+    /// Read `hosts: foo` as `[foo]` where a sequence is expected, and
+    /// `port: [80]` as `80` where a scalar is expected.
+    ///
+    /// This applies to every field. To accept either form for only some
+    /// fields, leave this off and annotate them with
+    /// `#[serde(with = "yyaml::with::one_or_many")]`.
+    #[must_use]
+    pub const fn lenient_collections(mut self, lenient: bool) -> Self {
+        self.lenient_collections = lenient;
+        self
+    }
+
+    /// Parse quoted scalars such as `port: "8080"` or `debug: "true"` where
+    /// the target type needs a number or boolean. A string that does not
+    /// parse as one fails with [`Error::Coerce`] pointing at it.
+    #[must_use]
+    pub const fn coerce_strings(mut self, coerce: bool) -> Self {
+        self.coerce_strings = coerce;
+        self
+    }
+
+    /// Choose how strictly mapping keys must match struct field names, e.g.
+    /// [`FieldNameNormalization::NormalizeSeparators`] so `maxConnections`
+    /// and `max-connections` both fill `max_connections`
+    #[must_use]
+    pub const fn field_name_normalization(mut self, mode: FieldNameNormalization) -> Self {
+        self.field_names = mode;
+        self
+    }
+
+    /// Keep going past a bad field and fail with [`Error::Multiple`],
+    /// listing every problem with its path and position, so a config can
+    /// be fixed in one pass
+    #[must_use]
+    pub const fn collect_errors(mut self, collect: bool) -> Self {
+        self.collect_errors = collect;
+        self
+    }
+
+    /// Load the input with the rules of `profile`, e.g.
+    /// [`Profile::strict`] so a duplicate key fails deserialization
+    #[must_use]
+    pub const fn profile(mut self, profile: Profile) -> Self {
+        self.profile = Some(profile);
+        self
+    }
+
+    pub(crate) fn loader_options(self) -> LoaderOptions {
+        let options = LoaderOptions::new();
+        let options = match self.profile {
+            Some(profile) => options.profile(profile),
+            None => options,
+        };
+        let options = options
+            .empty_scalar_as(self.empty_scalar)
+            .schema(self.schema)
+            .integer_overflow(self.integer_overflow);
+        match self.null_words {
+            Some(words) => options.null_words(words),
+            None => options,
+        }
+    }
+}
+
+/// High-performance document iterator for multi-document YAML streams
+/// Implements zero-allocation iteration over parsed documents
+/// Matches the expected API pattern from tests
+pub struct DocumentIterator {
+    docs: Vec<crate::yaml::Yaml>,
+    index: usize,
+}
+
+impl DocumentIterator {
+    /// Create new iterator from parsed documents
+    #[must_use] 
+    pub const fn new(docs: Vec<crate::yaml::Yaml>) -> Self {
+        Self { docs, index: 0 }
+    }
+}
+
+impl Iterator for DocumentIterator {
+    type Item = Deserializer;
+
+    /// Get next document deserializer with blazing performance
+    /// Returns Some(deserializer) for valid documents, None when exhausted
+    /// Handles errors internally for ergonomic API matching test expectations
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index < self.docs.len() {
+            let yaml = &self.docs[self.index];
+            self.index += 1;
+            let value = Value::from_yaml(yaml);
+            Some(Deserializer::new(value))
+        } else {
+            None
+        }
+    }
+}
 
 /// Support for iterator interface
 impl IntoIterator for Deserializer {
     type Item = Result<Self, crate::Error>;
     type IntoIter = std::iter::Once<Result<Self, crate::Error>>;
 
-    fn into_iter(self) -> Self::IntoIter {
-        std::iter::once(Ok(self))
+    fn into_iter(self) -> Self::IntoIter {
+        std::iter::once(Ok(self))
+    }
+}
+
+/// Blazing-fast indexing support for Value with zero allocation
+/// Supports both sequence indexing by usize and mapping access by Value key
+impl Index<usize> for Value {
+    type Output = Self;
+
+    /// Index into sequences with bounds checking for safety
+    fn index(&self, index: usize) -> &Self::Output {
+        match self {
+            Self::Sequence(seq) => seq.get(index).unwrap_or(&Self::Null),
+            _ => &Self::Null,
+        }
+    }
+}
+
+impl Index<&Self> for Value {
+    type Output = Self;
+
+    /// Index into mappings by key with zero allocation lookup
+    fn index(&self, key: &Self) -> &Self::Output {
+        match self {
+            Self::Mapping(map) => map.get(key).unwrap_or(&Self::Null),
+            _ => &Self::Null,
+        }
+    }
+}
+
+impl Index<&str> for Value {
+    type Output = Self;
+
+    /// Index into mappings by string key for ergonomic access
+    fn index(&self, key: &str) -> &Self::Output {
+        match self {
+            Self::Mapping(map) => mapping_get(map, key).unwrap_or(&Self::Null),
+            _ => &Self::Null,
+        }
+    }
+}
+
+/// A mapping key as seen by the `BTreeMap` of a [`Mapping`], so a `&str`
+/// can be looked up without building a `Value::String` for it
+trait MappingKey {
+    fn key(&self) -> KeyRef<'_>;
+}
+
+enum KeyRef<'a> {
+    Value(&'a Value),
+    Str(&'a str),
+}
+
+struct StrKey<'a>(&'a str);
+
+impl MappingKey for Value {
+    fn key(&self) -> KeyRef<'_> {
+        KeyRef::Value(self)
+    }
+}
+
+impl MappingKey for StrKey<'_> {
+    fn key(&self) -> KeyRef<'_> {
+        KeyRef::Str(self.0)
+    }
+}
+
+impl<'a> std::borrow::Borrow<dyn MappingKey + 'a> for Value {
+    fn borrow(&self) -> &(dyn MappingKey + 'a) {
+        self
+    }
+}
+
+/// Agrees with the derived order of `Value`, where strings sort after
+/// null, booleans and numbers and before every other variant
+impl Ord for dyn MappingKey + '_ {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let str_order = |value: &Value, s: &str| match value {
+            Value::String(v) => v.as_str().cmp(s),
+            Value::Null | Value::Bool(_) | Value::Number(_) => Ordering::Less,
+            Value::Sequence(_) | Value::Mapping(_) | Value::Tagged(_) => Ordering::Greater,
+        };
+        match (self.key(), other.key()) {
+            (KeyRef::Value(a), KeyRef::Value(b)) => a.cmp(b),
+            (KeyRef::Str(a), KeyRef::Str(b)) => a.cmp(b),
+            (KeyRef::Value(a), KeyRef::Str(b)) => str_order(a, b),
+            (KeyRef::Str(a), KeyRef::Value(b)) => str_order(b, a).reverse(),
+        }
+    }
+}
+
+impl PartialOrd for dyn MappingKey + '_ {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for dyn MappingKey + '_ {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for dyn MappingKey + '_ {}
+
+/// Entry of `map` under the string key `key`, without allocating
+fn mapping_get<'m>(map: &'m Mapping, key: &str) -> Option<&'m Value> {
+    map.get(&StrKey(key) as &dyn MappingKey)
+}
+
+fn mapping_get_mut<'m>(map: &'m mut Mapping, key: &str) -> Option<&'m mut Value> {
+    map.get_mut(&StrKey(key) as &dyn MappingKey)
+}
+
+impl Deserializer {
+    /// Create a new deserializer from a Value
+    #[must_use] 
+    pub const fn new(value: Value) -> Self {
+        Self {
+            value,
+            modes: Modes::DEFAULT,
+            report: None,
+            collect: None,
+        }
+    }
+
+    /// Create a deserializer that honours the serde-level choices in `config`
+    #[must_use]
+    pub const fn with_config(value: Value, config: &DeserializerConfig) -> Self {
+        Self {
+            value,
+            modes: Modes {
+                human_readable: !config.compact,
+                lenient: config.lenient_collections,
+                coerce: config.coerce_strings,
+                fields: config.field_names,
+            },
+            report: None,
+            collect: None,
+        }
+    }
+
+    /// Choose what [`is_human_readable`](de::Deserializer::is_human_readable)
+    /// reports; `true` by default
+    #[must_use]
+    pub const fn human_readable(mut self, human_readable: bool) -> Self {
+        self.modes.human_readable = human_readable;
+        self
+    }
+
+    /// With lenient collections, the item of a one-element sequence found
+    /// where a scalar is expected
+    fn unwrap_singleton(mut self) -> Self {
+        if self.modes.lenient
+            && let Value::Sequence(seq) = &mut self.value
+            && seq.len() == 1
+            && let Some(item) = seq.pop()
+        {
+            self.value = item;
+        }
+        self
+    }
+
+    /// The scalar to read as a `ty`. Under string coercion a quoted `"42"`
+    /// or `"true"` is parsed like a plain scalar, and kept if `accept`
+    /// takes the result.
+    fn scalar(self, ty: &str, accept: impl Fn(&Yaml) -> bool) -> Result<Value, Error> {
+        let coerce = self.modes.coerce;
+        match self.unwrap_singleton().value {
+            Value::String(s) if coerce => {
+                let parsed = Yaml::parse_str(s.trim());
+                if accept(&parsed) {
+                    Ok(Value::from_yaml(&parsed))
+                } else {
+                    Err(Error::coerce(format!("cannot parse {s:?} as {ty}")))
+                }
+            }
+            value => Ok(value),
+        }
+    }
+
+    fn integer<T: TryFrom<i64> + TryFrom<u64> + TryFrom<i128> + TryFrom<u128>>(
+        self,
+        ty: &str,
+    ) -> Result<T, Error> {
+        let fits = |yaml: &Yaml| match Value::from_yaml(yaml) {
+            Value::Number(Number::Integer(i)) => T::try_from(i).is_ok(),
+            Value::Number(Number::Unsigned(u)) => T::try_from(u).is_ok(),
+            Value::Number(Number::BigInteger(i)) => T::try_from(i).is_ok(),
+            Value::Number(Number::BigUnsigned(u)) => T::try_from(u).is_ok(),
+            _ => false,
+        };
+        checked_integer(self.scalar(ty, fits)?, ty)
+    }
+
+    /// Parse a YAML string and return a high-performance document iterator
+    /// This matches the expected test API: deserializer.next() -> Option<Result<Deserializer, Error>>
+    #[must_use] 
+    pub fn parse_str(s: &str) -> DocumentIterator {
+        use crate::parser::YamlLoader;
+        match YamlLoader::load_from_str(s) {
+            Ok(docs) => DocumentIterator::new(docs),
+            Err(_) => DocumentIterator::new(vec![]), // Empty iterator on parse error
+        }
+    }
+
+    /// Add into_deserializer method for serde compatibility
+    #[must_use] 
+    pub const fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+/// Iterator over multiple YAML documents
+pub struct DeserializerIter {
+    docs: Vec<crate::yaml::Yaml>,
+    index: usize,
+}
+
+impl DeserializerIter {
+    #[must_use] 
+    pub const fn new(docs: Vec<crate::yaml::Yaml>) -> Self {
+        Self { docs, index: 0 }
+    }
+}
+
+impl Iterator for DeserializerIter {
+    type Item = Result<Deserializer, crate::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index < self.docs.len() {
+            let yaml = &self.docs[self.index];
+            self.index += 1;
+            let value = Value::from_yaml(yaml);
+            Some(Ok(Deserializer::new(value)))
+        } else {
+            None
+        }
+    }
+}
+
+impl Deserializer {
+    /// Parse a YAML string and return a high-performance document iterator
+    /// Supports the expected API pattern: iterator.next() -> Option<Result<Deserializer, Error>>
+    pub fn parse_str_multi(s: &str) -> Result<DocumentIterator, crate::Error> {
+        use crate::parser::YamlLoader;
+        let docs = YamlLoader::load_from_str(s)?;
+        Ok(DocumentIterator::new(docs))
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Deserializer {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.value {
+            Value::Null => visitor.visit_unit(),
+            Value::Bool(b) => visitor.visit_bool(b),
+            Value::Number(Number::Integer(i)) => visitor.visit_i64(i),
+            Value::Number(Number::Unsigned(u)) => visitor.visit_u64(u),
+            Value::Number(Number::BigInteger(i)) => visitor.visit_i128(i),
+            Value::Number(Number::BigUnsigned(u)) => visitor.visit_u128(u),
+            Value::Number(Number::Float(f)) => visitor.visit_f64(f),
+            Value::String(s) => visitor.visit_string(s),
+            Value::Sequence(seq) => {
+                let seq_deserializer = SeqDeserializer::new(seq.into_iter(), self.modes, self.report, self.collect);
+                visitor.visit_seq(seq_deserializer)
+            }
+            Value::Mapping(map) => {
+                let map_deserializer = MapDeserializer::new(map.into_iter(), self.modes, self.report, self.collect);
+                visitor.visit_map(map_deserializer)
+            }
+            Value::Tagged(tagged) if tagged.tag.name == crate::base64::BINARY_TAG => {
+                visitor.visit_byte_buf(decode_binary(tagged.value)?)
+            }
+            Value::Tagged(tagged) => {
+                // For deserialization, we deserialize the inner value
+                // The tag information is preserved in the Value structure
+                let inner_deserializer = Self {
+                    value: tagged.value,
+                    modes: self.modes,
+                    report: self.report,
+                    collect: self.collect,
+                };
+                inner_deserializer.deserialize_any(visitor)
+            }
+        }
+    }
+
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.scalar("bool", |yaml| matches!(yaml, Yaml::Boolean(_)))? {
+            Value::Bool(b) => visitor.visit_bool(b),
+            _ => Err(Error::Custom("expected bool".to_string())),
+        }
+    }
+
+    fn deserialize_i8<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_i8(self.integer("i8")?)
+    }
+
+    fn deserialize_i16<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_i16(self.integer("i16")?)
+    }
+
+    fn deserialize_i32<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_i32(self.integer("i32")?)
+    }
+
+    fn deserialize_i64<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_i64(self.integer("i64")?)
+    }
+
+    fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_u8(self.integer("u8")?)
+    }
+
+    fn deserialize_u16<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_u16(self.integer("u16")?)
+    }
+
+    fn deserialize_u32<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_u32(self.integer("u32")?)
+    }
+
+    fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_u64(self.integer("u64")?)
+    }
+
+    fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_i128(self.integer("i128")?)
+    }
+
+    fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_u128(self.integer("u128")?)
+    }
+
+    fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.scalar("f32", is_number)? {
+            Value::Number(n) => visitor.visit_f32(n.to_f64() as f32),
+            _ => Err(Error::Custom("expected number".to_string())),
+        }
+    }
+
+    fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.scalar("f64", is_number)? {
+            Value::Number(n) => visitor.visit_f64(n.to_f64()),
+            _ => Err(Error::Custom("expected number".to_string())),
+        }
+    }
+
+    fn deserialize_char<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        // Plain `1` or `y` resolve to other types but are still one character
+        let text = match self.unwrap_singleton().value {
+            Value::Tagged(tagged) => identifier(tagged.value),
+            value => identifier(value),
+        }
+        .ok_or_else(|| Error::Custom("expected string".to_string()))?;
+        let mut chars = text.chars();
+        match (chars.next(), chars.next()) {
+            (Some(ch), None) => visitor.visit_char(ch),
+            (None, _) => Err(Error::Custom("expected char".to_string())),
+            (Some(_), Some(_)) => Err(Error::Custom(format!(
+                "expected single character, found {text:?}"
+            ))),
+        }
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.unwrap_singleton().value {
+            Value::String(s) => visitor.visit_string(s),
+            _ => Err(Error::Custom("expected string".to_string())),
+        }
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.value {
+            Value::String(s) => visitor.visit_byte_buf(s.into_bytes()),
+            Value::Sequence(seq) => visitor.visit_byte_buf(byte_sequence(&seq)?),
+            Value::Tagged(tagged) if tagged.tag.name == crate::base64::BINARY_TAG => {
+                visitor.visit_byte_buf(decode_binary(tagged.value)?)
+            }
+            _ => Err(Error::Custom("expected !!binary, string or sequence of bytes".to_string())),
+        }
+    }
+
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.value {
+            Value::Null => visitor.visit_none(),
+            // `Some` of a null, as the serializer writes `Some(None)`
+            Value::Tagged(ref tagged) if is_some_null(tagged) => visitor.visit_some(Self {
+                value: Value::Null,
+                ..self
+            }),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.value {
+            Value::Null => visitor.visit_unit(),
+            _ => Err(Error::Custom("expected null".to_string())),
+        }
+    }
+
+    fn deserialize_unit_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.value {
+            Value::Tagged(tagged) if name == TAGGED_STRUCT => visitor.visit_enum(TagAccess {
+                tagged: *tagged,
+                modes: self.modes,
+                report: self.report,
+                collect: self.collect,
+            }),
+            value => visitor.visit_newtype_struct(Self {
+                value,
+                modes: self.modes,
+                report: self.report,
+                collect: self.collect,
+            }),
+        }
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.value {
+            Value::Sequence(seq) => {
+                let seq_deserializer = SeqDeserializer::new(seq.into_iter(), self.modes, self.report, self.collect);
+                visitor.visit_seq(seq_deserializer)
+            }
+            // `Vec<u8>` without serde_bytes asks for a sequence
+            Value::Tagged(tagged) if tagged.tag.name == crate::base64::BINARY_TAG => {
+                let bytes = decode_binary(tagged.value)?;
+                let seq = bytes
+                    .into_iter()
+                    .map(|b| Value::Number(Number::Integer(i64::from(b))));
+                visitor.visit_seq(SeqDeserializer::new(seq, self.modes, None, None))
+            }
+            // The members of a `!!set`, in order
+            Value::Tagged(tagged) if tagged.tag.name == SET => {
+                let members = Self {
+                    value: tagged.value,
+                    modes: self.modes,
+                    report: self.report,
+                    collect: self.collect,
+                };
+                members.deserialize_seq(visitor)
+            }
+            // Other tags only matter under the tagged newtype name
+            Value::Tagged(tagged) => Self { value: tagged.value, ..self }.deserialize_seq(visitor),
+            // An empty node such as `key:` is an empty collection
+            Value::Null => visitor.visit_seq(SeqDeserializer::new(std::iter::empty(), self.modes, None, None)),
+            // `hosts: foo` as `hosts: [foo]`
+            value @ (Value::String(_) | Value::Number(_) | Value::Bool(_)) if self.modes.lenient => {
+                let seq = std::iter::once(value);
+                visitor.visit_seq(SeqDeserializer::new(seq, self.modes, self.report, self.collect))
+            }
+            _ => Err(Error::Custom("expected sequence".to_string())),
+        }
+    }
+
+    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
     }
-}
 
-/// Blazing-fast indexing support for Value with zero allocation
-/// Supports both sequence indexing by usize and mapping access by Value key
-impl Index<usize> for Value {
-    type Output = Self;
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
 
-    /// Index into sequences with bounds checking for safety
-    fn index(&self, index: usize) -> &Self::Output {
-        match self {
-            Self::Sequence(seq) => seq.get(index).unwrap_or(&Self::Null),
-            _ => &Self::Null,
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.value {
+            Value::Mapping(map) => {
+                let map_deserializer = MapDeserializer::new(map.into_iter(), self.modes, self.report, self.collect);
+                visitor.visit_map(map_deserializer)
+            }
+            Value::Null => visitor.visit_map(MapDeserializer::new(std::iter::empty(), self.modes, None, None)),
+            // Other tags only matter under the tagged newtype name
+            Value::Tagged(tagged) => Self { value: tagged.value, ..self }.deserialize_map(visitor),
+            _ => Err(Error::Custom("expected mapping".to_string())),
         }
     }
-}
 
-impl Index<&Self> for Value {
-    type Output = Self;
+    fn deserialize_struct<V>(
+        mut self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        if let Value::Mapping(map) = self.value {
+            self.value = Value::Mapping(self.modes.fields.apply(map, fields)?);
+        }
+        if let Some(report) = &self.report {
+            match &self.value {
+                Value::Mapping(map) => report.missing(fields, Some(map)),
+                Value::Null => report.missing(fields, None),
+                _ => {}
+            }
+        }
+        self.deserialize_map(visitor)
+    }
 
-    /// Index into mappings by key with zero allocation lookup
-    fn index(&self, key: &Self) -> &Self::Output {
-        match self {
-            Self::Mapping(map) => map.get(key).unwrap_or(&Self::Null),
-            _ => &Self::Null,
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let value = match self.value {
+            // A variant with content is a mapping from its name to the content
+            Value::Mapping(mut map) if map.len() == 1 => {
+                let entry = map.pop_first();
+                let Some((variant, content)) =
+                    entry.and_then(|(key, content)| Some((identifier(key)?, content)))
+                else {
+                    return Err(Error::Custom("expected string for enum".to_string()));
+                };
+                return visitor.visit_enum(TagAccess {
+                    tagged: TaggedValue::new(Tag::new(variant), content),
+                    modes: self.modes,
+                    report: self.report,
+                    collect: self.collect,
+                });
+            }
+            value => value,
+        };
+        match identifier(value) {
+            Some(s) => visitor.visit_enum(EnumDeserializer {
+                value: s,
+                modes: self.modes,
+            }),
+            None => Err(Error::Custom("expected string for enum".to_string())),
         }
     }
-}
 
-impl Index<&str> for Value {
-    type Output = Self;
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match identifier(self.value) {
+            Some(s) => visitor.visit_string(s),
+            None => Err(Error::Custom("expected string".to_string())),
+        }
+    }
 
-    /// Index into mappings by string key for ergonomic access
-    fn index(&self, key: &str) -> &Self::Output {
-        let key_value = Self::String(key.to_string());
-        self.index(&key_value)
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.modes.human_readable
     }
 }
 
-impl Deserializer {
-    /// Create a new deserializer from a Value
-    #[must_use] 
-    pub const fn new(value: Value) -> Self {
-        Self { value }
+/// Text of a scalar used as a field or variant name.
+///
+/// Plain `1` or `true` resolve to numbers and booleans, but discriminants
+/// such as `#[serde(rename = "1")]` are matched by name.
+fn identifier(value: Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s),
+        Value::Bool(b) => Some(b.to_string()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
     }
+}
 
-    /// Parse a YAML string and return a high-performance document iterator
-    /// This matches the expected test API: deserializer.next() -> Option<Result<Deserializer, Error>>
-    #[must_use] 
-    pub fn parse_str(s: &str) -> DocumentIterator {
-        use crate::parser::YamlLoader;
-        match YamlLoader::load_from_str(s) {
-            Ok(docs) => DocumentIterator::new(docs),
-            Err(_) => DocumentIterator::new(vec![]), // Empty iterator on parse error
-        }
+fn is_number(yaml: &Yaml) -> bool {
+    match yaml {
+        Yaml::Integer(_) => true,
+        Yaml::Real(s) => parse_f64(s).is_some(),
+        _ => false,
     }
+}
 
-    /// Add into_deserializer method for serde compatibility
-    #[must_use] 
-    pub const fn into_deserializer(self) -> Self {
-        self
+/// Integer scalar converted to the target width, rejecting rather than
+/// truncating values that do not fit
+fn checked_integer<T: TryFrom<i64> + TryFrom<u64> + TryFrom<i128> + TryFrom<u128>>(
+    value: Value,
+    ty: &str,
+) -> Result<T, Error> {
+    match value {
+        Value::Number(Number::Integer(i)) => T::try_from(i)
+            .map_err(|_| Error::Custom(format!("integer {i} is out of range for {ty}"))),
+        Value::Number(Number::Unsigned(u)) => T::try_from(u)
+            .map_err(|_| Error::Custom(format!("integer {u} is out of range for {ty}"))),
+        Value::Number(Number::BigInteger(i)) => T::try_from(i)
+            .map_err(|_| Error::Custom(format!("integer {i} is out of range for {ty}"))),
+        Value::Number(Number::BigUnsigned(u)) => T::try_from(u)
+            .map_err(|_| Error::Custom(format!("integer {u} is out of range for {ty}"))),
+        Value::Tagged(tagged) => checked_integer(tagged.value, ty),
+        _ => Err(Error::Custom("expected integer".to_string())),
     }
 }
 
-/// Iterator over multiple YAML documents
-pub struct DeserializerIter {
-    docs: Vec<crate::yaml::Yaml>,
+/// Content of a `!!binary` scalar
+fn decode_binary(value: Value) -> Result<Vec<u8>, Error> {
+    match value {
+        Value::String(s) => crate::base64::decode(&s)
+            .ok_or_else(|| Error::Custom("invalid base64 in !!binary scalar".to_string())),
+        _ => Err(Error::Custom("expected base64 string for !!binary".to_string())),
+    }
+}
+
+fn byte_sequence(seq: &[Value]) -> Result<Vec<u8>, Error> {
+    seq.iter()
+        .map(|item| match *item {
+            Value::Number(Number::Integer(i)) => u8::try_from(i)
+                .map_err(|_| Error::Custom(format!("byte value {i} out of range 0..=255"))),
+            _ => Err(Error::Custom("expected integer byte".to_string())),
+        })
+        .collect()
+}
+
+struct SeqDeserializer<I> {
+    iter: I,
+    modes: Modes,
+    report: Option<DefaultsReport>,
+    collect: Option<Collect>,
     index: usize,
 }
 
-impl DeserializerIter {
-    #[must_use] 
-    pub const fn new(docs: Vec<crate::yaml::Yaml>) -> Self {
-        Self { docs, index: 0 }
+impl<I> SeqDeserializer<I>
+where
+    I: Iterator<Item = Value>,
+{
+    const fn new(
+        iter: I,
+        modes: Modes,
+        report: Option<DefaultsReport>,
+        collect: Option<Collect>,
+    ) -> Self {
+        Self {
+            iter,
+            modes,
+            report,
+            collect,
+            index: 0,
+        }
     }
 }
 
-impl Iterator for DeserializerIter {
-    type Item = Result<Deserializer, crate::Error>;
+impl<'de, I> de::SeqAccess<'de> for SeqDeserializer<I>
+where
+    I: Iterator<Item = Value>,
+{
+    type Error = Error;
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.index < self.docs.len() {
-            let yaml = &self.docs[self.index];
-            self.index += 1;
-            let value = Value::from_yaml(yaml);
-            Some(Ok(Deserializer::new(value)))
-        } else {
-            None
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Error>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        let Some(value) = self.iter.next() else {
+            return Ok(None);
+        };
+        let collect = self
+            .collect
+            .as_ref()
+            .map(|collect| collect.child(&self.index.to_string()));
+        let report = self
+            .report
+            .as_ref()
+            .map(|report| report.child(format_args!("[{}]", self.index)));
+        let index = self.index;
+        self.index += 1;
+        seed.deserialize(Deserializer {
+            value,
+            modes: self.modes,
+            report,
+            collect: collect.clone(),
+        })
+        .map(Some)
+        .map_err(|e| Collect::locate(collect.as_ref(), e.under(&index.to_string())))
+    }
+
+    /// An element that fails under error collection is recorded and read
+    /// as a placeholder, or left out if its type rejects that
+    fn next_element<T: Deserialize<'de>>(&mut self) -> Result<Option<T>, Error> {
+        loop {
+            match self.next_element_seed(std::marker::PhantomData) {
+                Err(e) if Collect::collecting(self.collect.as_ref()) => {
+                    if let Ok(placeholder) = Collect::recover(self.collect.as_ref(), e) {
+                        return Ok(Some(placeholder));
+                    }
+                }
+                result => return result,
+            }
         }
     }
 }
 
-impl Deserializer {
-    /// Parse a YAML string and return a high-performance document iterator
-    /// Supports the expected API pattern: iterator.next() -> Option<Result<Deserializer, Error>>
-    pub fn parse_str_multi(s: &str) -> Result<DocumentIterator, crate::Error> {
-        use crate::parser::YamlLoader;
-        let docs = YamlLoader::load_from_str(s)?;
-        Ok(DocumentIterator::new(docs))
+struct MapDeserializer<I> {
+    iter: I,
+    value: Option<PendingValue>,
+    modes: Modes,
+    report: Option<DefaultsReport>,
+    collect: Option<Collect>,
+}
+
+/// The value of the key a [`MapDeserializer`] just returned
+struct PendingValue {
+    value: Value,
+    report: Option<DefaultsReport>,
+    /// The key as a pointer token, under string coercion or error collection
+    token: Option<String>,
+    collect: Option<Collect>,
+}
+
+impl<I> MapDeserializer<I>
+where
+    I: Iterator<Item = (Value, Value)>,
+{
+    const fn new(
+        iter: I,
+        modes: Modes,
+        report: Option<DefaultsReport>,
+        collect: Option<Collect>,
+    ) -> Self {
+        Self {
+            iter,
+            value: None,
+            modes,
+            report,
+            collect,
+        }
     }
 }
 
-impl<'de> de::Deserializer<'de> for Deserializer {
+impl<'de, I> de::MapAccess<'de> for MapDeserializer<I>
+where
+    I: Iterator<Item = (Value, Value)>,
+{
     type Error = Error;
 
-    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Error>
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Error>
     where
-        V: de::Visitor<'de>,
+        K: de::DeserializeSeed<'de>,
     {
-        match self.value {
-            Value::Null => visitor.visit_unit(),
-            Value::Bool(b) => visitor.visit_bool(b),
-            Value::Number(Number::Integer(i)) => visitor.visit_i64(i),
-            Value::Number(Number::Float(f)) => visitor.visit_f64(f),
-            Value::String(s) => visitor.visit_string(s),
-            Value::Sequence(seq) => {
-                let seq_deserializer = SeqDeserializer::new(seq.into_iter());
-                visitor.visit_seq(seq_deserializer)
-            }
-            Value::Mapping(map) => {
-                let map_deserializer = MapDeserializer::new(map.into_iter());
-                visitor.visit_map(map_deserializer)
-            }
-            Value::Tagged(tagged) => {
-                // For deserialization, we deserialize the inner value
-                // The tag information is preserved in the Value structure
-                let inner_deserializer = Self::new(tagged.value);
-                inner_deserializer.deserialize_any(visitor)
+        let Some((key, value)) = self.iter.next() else {
+            return Ok(None);
+        };
+        let token = (self.modes.coerce || self.collect.is_some())
+            .then(|| crate::pointer::escape(&identifier(key.clone()).unwrap_or_default()).into_owned());
+        let collect = self
+            .collect
+            .as_ref()
+            .zip(token.as_deref())
+            .map(|(collect, token)| collect.child(token));
+        let report = self.report.as_ref().map(|report| report.field(&key));
+        let located = collect.clone();
+        self.value = Some(PendingValue {
+            value,
+            report,
+            token,
+            collect,
+        });
+        seed.deserialize(Deserializer {
+            value: key,
+            modes: self.modes,
+            report: None,
+            collect: None,
+        })
+        .map(Some)
+        .map_err(|e| Collect::locate(located.as_ref(), e))
+    }
+
+    /// A key that fails under error collection is recorded and its entry
+    /// left out, so the fields after it are still read
+    fn next_key<K: Deserialize<'de>>(&mut self) -> Result<Option<K>, Error> {
+        loop {
+            match self.next_key_seed(std::marker::PhantomData) {
+                Err(e) if Collect::collecting(self.collect.as_ref()) => {
+                    self.value = None;
+                    if let Some(collect) = &self.collect {
+                        collect.record(e);
+                    }
+                }
+                result => return result,
             }
         }
     }
 
-    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, Error>
-    where
-        V: de::Visitor<'de>,
-    {
-        match self.value {
-            Value::Bool(b) => visitor.visit_bool(b),
-            _ => Err(Error::Custom("expected bool".to_string())),
+    fn next_value<V: Deserialize<'de>>(&mut self) -> Result<V, Error> {
+        self.next_value_seed(std::marker::PhantomData)
+            .or_else(|e| Collect::recover(self.collect.as_ref(), e))
+    }
+
+    /// An entry whose value fails under error collection is recorded and
+    /// left out, as for a failing key
+    fn next_entry<K: Deserialize<'de>, V: Deserialize<'de>>(&mut self) -> Result<Option<(K, V)>, Error> {
+        while let Some(key) = self.next_key()? {
+            match self.next_value_seed(std::marker::PhantomData) {
+                Err(e) if Collect::collecting(self.collect.as_ref()) => {
+                    if let Some(collect) = &self.collect {
+                        collect.record(e);
+                    }
+                }
+                value => return value.map(|value| Some((key, value))),
+            }
         }
+        Ok(None)
     }
 
-    fn deserialize_i8<V>(self, visitor: V) -> Result<V::Value, Error>
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Error>
     where
-        V: de::Visitor<'de>,
+        V: de::DeserializeSeed<'de>,
     {
-        match self.value {
-            Value::Number(Number::Integer(i)) => visitor.visit_i8(i as i8),
-            _ => Err(Error::Custom("expected integer".to_string())),
+        match self.value.take() {
+            Some(PendingValue {
+                value,
+                report,
+                token,
+                collect,
+            }) => seed
+                .deserialize(Deserializer {
+                    value,
+                    modes: self.modes,
+                    report,
+                    collect: collect.clone(),
+                })
+                .map_err(|e| match token {
+                    Some(token) => Collect::locate(collect.as_ref(), e.under(&token)),
+                    None => e,
+                }),
+            None => Err(Error::Custom("value is missing".to_string())),
         }
     }
+}
 
-    fn deserialize_i16<V>(self, visitor: V) -> Result<V::Value, Error>
+struct EnumDeserializer {
+    value: String,
+    modes: Modes,
+}
+
+impl<'de> de::EnumAccess<'de> for EnumDeserializer {
+    type Error = Error;
+    type Variant = UnitVariantDeserializer;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Error>
     where
-        V: de::Visitor<'de>,
+        V: de::DeserializeSeed<'de>,
     {
-        match self.value {
-            Value::Number(Number::Integer(i)) => visitor.visit_i16(i as i16),
-            _ => Err(Error::Custom("expected integer".to_string())),
+        let variant = seed.deserialize(Deserializer {
+            value: Value::String(self.value),
+            modes: self.modes,
+            report: None,
+            collect: None,
+        })?;
+        Ok((variant, UnitVariantDeserializer))
+    }
+}
+
+/// A tagged value seen as an enum: the tag is the variant and the value
+/// its content
+struct TagAccess {
+    tagged: TaggedValue,
+    modes: Modes,
+    report: Option<DefaultsReport>,
+    collect: Option<Collect>,
+}
+
+impl TagAccess {
+    fn content(self) -> Deserializer {
+        Deserializer {
+            value: self.tagged.value,
+            modes: self.modes,
+            report: self.report,
+            collect: self.collect,
         }
     }
+}
 
-    fn deserialize_i32<V>(self, visitor: V) -> Result<V::Value, Error>
+impl<'de> de::EnumAccess<'de> for TagAccess {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V>(mut self, seed: V) -> Result<(V::Value, Self), Error>
     where
-        V: de::Visitor<'de>,
+        V: de::DeserializeSeed<'de>,
     {
-        match self.value {
-            Value::Number(Number::Integer(i)) => visitor.visit_i32(i as i32),
-            _ => Err(Error::Custom("expected integer".to_string())),
-        }
+        let tag = std::mem::take(&mut self.tagged.tag.name);
+        let variant = seed.deserialize(Deserializer::new(Value::String(tag)))?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for TagAccess {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        de::Deserialize::deserialize(self.content())
     }
 
-    fn deserialize_i64<V>(self, visitor: V) -> Result<V::Value, Error>
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Error>
     where
-        V: de::Visitor<'de>,
+        T: de::DeserializeSeed<'de>,
     {
-        match self.value {
-            Value::Number(Number::Integer(i)) => visitor.visit_i64(i),
-            _ => Err(Error::Custom("expected integer".to_string())),
-        }
+        seed.deserialize(self.content())
     }
 
-    fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value, Error>
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value, Error>
     where
         V: de::Visitor<'de>,
     {
-        match self.value {
-            Value::Number(Number::Integer(i)) => visitor.visit_u8(i as u8),
-            _ => Err(Error::Custom("expected integer".to_string())),
-        }
+        de::Deserializer::deserialize_seq(self.content(), visitor)
     }
 
-    fn deserialize_u16<V>(self, visitor: V) -> Result<V::Value, Error>
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
     where
         V: de::Visitor<'de>,
     {
-        match self.value {
-            Value::Number(Number::Integer(i)) => visitor.visit_u16(i as u16),
-            _ => Err(Error::Custom("expected integer".to_string())),
-        }
+        de::Deserializer::deserialize_map(self.content(), visitor)
     }
+}
 
-    fn deserialize_u32<V>(self, visitor: V) -> Result<V::Value, Error>
-    where
-        V: de::Visitor<'de>,
-    {
-        match self.value {
-            Value::Number(Number::Integer(i)) => visitor.visit_u32(i as u32),
-            _ => Err(Error::Custom("expected integer".to_string())),
-        }
+struct UnitVariantDeserializer;
+
+impl<'de> de::VariantAccess<'de> for UnitVariantDeserializer {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        Ok(())
     }
 
-    fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value, Error>
+    fn newtype_variant_seed<T>(self, _seed: T) -> Result<T::Value, Error>
     where
-        V: de::Visitor<'de>,
+        T: de::DeserializeSeed<'de>,
     {
-        match self.value {
-            Value::Number(Number::Integer(i)) => visitor.visit_u64(i as u64),
-            _ => Err(Error::Custom("expected integer".to_string())),
-        }
+        Err(Error::Custom("newtype variants not supported".to_string()))
     }
 
-    fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value, Error>
+    fn tuple_variant<V>(self, _len: usize, _visitor: V) -> Result<V::Value, Error>
     where
         V: de::Visitor<'de>,
     {
-        match self.value {
-            Value::Number(Number::Float(f)) => visitor.visit_f32(f as f32),
-            Value::Number(Number::Integer(i)) => visitor.visit_f32(i as f32),
-            _ => Err(Error::Custom("expected number".to_string())),
-        }
+        Err(Error::Custom("tuple variants not supported".to_string()))
     }
 
-    fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value, Error>
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Error>
     where
         V: de::Visitor<'de>,
     {
-        match self.value {
-            Value::Number(Number::Float(f)) => visitor.visit_f64(f),
-            Value::Number(Number::Integer(i)) => visitor.visit_f64(i as f64),
-            _ => Err(Error::Custom("expected number".to_string())),
-        }
+        Err(Error::Custom("struct variants not supported".to_string()))
     }
+}
 
-    fn deserialize_char<V>(self, visitor: V) -> Result<V::Value, Error>
-    where
-        V: de::Visitor<'de>,
-    {
-        match self.value {
-            Value::String(s) => {
-                let mut chars = s.chars();
-                let ch = chars
-                    .next()
-                    .ok_or_else(|| Error::Custom("expected char".to_string()))?;
-                if chars.next().is_some() {
-                    return Err(Error::Custom("expected single character".to_string()));
-                }
-                visitor.visit_char(ch)
+/// Convert a YAML value to a serde-compatible Value
+pub fn from_value<T>(value: Value) -> Result<T, Error>
+where
+    T: serde::de::DeserializeOwned,
+{
+    T::deserialize(Deserializer::new(value))
+}
+
+/// Like [`from_value`], reporting a failure as [`Error::At`] the node it
+/// was raised for
+pub(crate) fn from_value_located<T>(value: Value) -> Result<T, Error>
+where
+    T: serde::de::DeserializeOwned,
+{
+    let mut deserializer = Deserializer::new(value);
+    deserializer.collect = Some(Collect {
+        path: String::new(),
+        errors: None,
+    });
+    T::deserialize(deserializer)
+}
+
+/// Like [`from_value`], also listing the struct fields that were absent
+/// from `value` and so took their default, for warning about unset options
+pub fn from_value_with_defaults<T>(value: Value) -> Result<(T, Vec<DefaultedField>), Error>
+where
+    T: serde::de::DeserializeOwned,
+{
+    let defaulted = Arc::new(Mutex::new(Vec::new()));
+    let deserializer = Deserializer {
+        value,
+        modes: Modes::DEFAULT,
+        report: Some(DefaultsReport {
+            path: String::new(),
+            defaulted: Arc::clone(&defaulted),
+        }),
+        collect: None,
+    };
+    let parsed = T::deserialize(deserializer)?;
+    let defaulted = defaulted
+        .lock()
+        .map(|mut fields| std::mem::take(&mut *fields))
+        .map_err(|_| Error::Custom("defaults report poisoned".to_string()))?;
+    Ok((parsed, defaulted))
+}
+
+/// Implement `deserialize_*` methods on `&Value` by cloning into an owned
+/// [`Deserializer`], for methods that expect a scalar
+macro_rules! forward_to_owned {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Error>
+            where
+                V: de::Visitor<'de>,
+            {
+                Deserializer::new(untagged(self).clone()).$method(visitor)
             }
-            _ => Err(Error::Custom("expected string".to_string())),
-        }
-    }
+        )*
+    };
+}
 
-    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, Error>
+// Deserializing by reference walks sequences, mappings and enum variants
+// in place; only scalars are copied into an owned `Deserializer`. Tags
+// other than `!!binary` are transparent.
+impl<'de> de::Deserializer<'de> for &Value {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Error>
     where
         V: de::Visitor<'de>,
     {
-        match self.value {
-            Value::String(s) => visitor.visit_string(s),
-            _ => Err(Error::Custom("expected string".to_string())),
+        match untagged(self) {
+            Value::Sequence(seq) => visitor.visit_seq(SeqRefDeserializer { iter: seq.iter() }),
+            Value::Mapping(map) => visitor.visit_map(MapRefDeserializer::new(map)),
+            value => Deserializer::new(value.clone()).deserialize_any(visitor),
         }
     }
 
-    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, Error>
-    where
-        V: de::Visitor<'de>,
-    {
-        self.deserialize_str(visitor)
+    forward_to_owned! {
+        deserialize_bool deserialize_f32 deserialize_f64
+        deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64 deserialize_i128
+        deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64 deserialize_u128
+        deserialize_char deserialize_str deserialize_string deserialize_unit
+        deserialize_identifier
     }
 
     fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, Error>
     where
         V: de::Visitor<'de>,
     {
-        match self.value {
-            Value::String(s) => visitor.visit_byte_buf(s.into_bytes()),
-            _ => Err(Error::Custom("expected string".to_string())),
+        match untagged(self) {
+            Value::Sequence(seq) => visitor.visit_byte_buf(byte_sequence(seq)?),
+            value => Deserializer::new(value.clone()).deserialize_bytes(visitor),
         }
     }
 
@@ -927,22 +3030,13 @@ impl<'de> de::Deserializer<'de> for Deserializer {
     where
         V: de::Visitor<'de>,
     {
-        match self.value {
-            Value::Null => visitor.visit_none(),
+        match self {
+            Value::Tagged(tagged) if is_some_null(tagged) => visitor.visit_some(&tagged.value),
+            _ if untagged(self).is_null() => visitor.visit_none(),
             _ => visitor.visit_some(self),
         }
     }
 
-    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, Error>
-    where
-        V: de::Visitor<'de>,
-    {
-        match self.value {
-            Value::Null => visitor.visit_unit(),
-            _ => Err(Error::Custom("expected null".to_string())),
-        }
-    }
-
     fn deserialize_unit_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error>
     where
         V: de::Visitor<'de>,
@@ -952,25 +3046,28 @@ impl<'de> de::Deserializer<'de> for Deserializer {
 
     fn deserialize_newtype_struct<V>(
         self,
-        _name: &'static str,
+        name: &'static str,
         visitor: V,
     ) -> Result<V::Value, Error>
     where
         V: de::Visitor<'de>,
     {
-        visitor.visit_newtype_struct(self)
+        match self {
+            Value::Tagged(tagged) if name == TAGGED_STRUCT => visitor.visit_enum(EnumRefDeserializer {
+                variant: Cow::Owned(Value::String(tagged.tag.name.clone())),
+                content: Some(&tagged.value),
+            }),
+            _ => visitor.visit_newtype_struct(self),
+        }
     }
 
     fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Error>
     where
         V: de::Visitor<'de>,
     {
-        match self.value {
-            Value::Sequence(seq) => {
-                let seq_deserializer = SeqDeserializer::new(seq.into_iter());
-                visitor.visit_seq(seq_deserializer)
-            }
-            _ => Err(Error::Custom("expected sequence".to_string())),
+        match untagged(self) {
+            Value::Sequence(seq) => visitor.visit_seq(SeqRefDeserializer { iter: seq.iter() }),
+            value => Deserializer::new(value.clone()).deserialize_seq(visitor),
         }
     }
 
@@ -997,12 +3094,9 @@ impl<'de> de::Deserializer<'de> for Deserializer {
     where
         V: de::Visitor<'de>,
     {
-        match self.value {
-            Value::Mapping(map) => {
-                let map_deserializer = MapDeserializer::new(map.into_iter());
-                visitor.visit_map(map_deserializer)
-            }
-            _ => Err(Error::Custom("expected mapping".to_string())),
+        match untagged(self) {
+            Value::Mapping(map) => visitor.visit_map(MapRefDeserializer::new(map)),
+            value => Deserializer::new(value.clone()).deserialize_map(visitor),
         }
     }
 
@@ -1027,177 +3121,177 @@ impl<'de> de::Deserializer<'de> for Deserializer {
     where
         V: de::Visitor<'de>,
     {
-        match self.value {
-            Value::String(s) => visitor.visit_enum(EnumDeserializer { value: s }),
-            _ => Err(Error::Custom("expected string for enum".to_string())),
-        }
-    }
-
-    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, Error>
-    where
-        V: de::Visitor<'de>,
-    {
-        self.deserialize_str(visitor)
-    }
-
+        let (variant, content) = match untagged(self) {
+            Value::Mapping(map) if map.len() == 1 => match map.first_key_value() {
+                Some((variant, content)) => (variant, Some(content)),
+                None => return Err(Error::Custom("expected string for enum".to_string())),
+            },
+            value @ (Value::String(_) | Value::Bool(_) | Value::Number(_)) => (value, None),
+            _ => return Err(Error::Custom("expected string for enum".to_string())),
+        };
+        visitor.visit_enum(EnumRefDeserializer {
+            variant: Cow::Borrowed(variant),
+            content,
+        })
+    }
+
+    // Skipped without copying the subtree or looking inside it
     fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Error>
     where
         V: de::Visitor<'de>,
     {
         visitor.visit_unit()
     }
-}
-
-struct SeqDeserializer<I> {
-    iter: I,
-}
 
-impl<I> SeqDeserializer<I>
-where
-    I: Iterator<Item = Value>,
-{
-    const fn new(iter: I) -> Self {
-        Self { iter }
+    fn is_human_readable(&self) -> bool {
+        true
     }
 }
 
-impl<'de, I> de::SeqAccess<'de> for SeqDeserializer<I>
-where
-    I: Iterator<Item = Value>,
-{
-    type Error = Error;
+/// Whether `tagged` is `!!null ~`, which an `Option` reads as `Some` of
+/// null rather than `None`
+fn is_some_null(tagged: &TaggedValue) -> bool {
+    tagged.tag.name == crate::tags::NULL && tagged.value.is_null()
+}
 
-    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Error>
-    where
-        T: de::DeserializeSeed<'de>,
-    {
-        match self.iter.next() {
-            Some(value) => seed.deserialize(Deserializer::new(value)).map(Some),
-            None => Ok(None),
+/// `value` under any tags except `!!binary`
+fn untagged(mut value: &Value) -> &Value {
+    while let Value::Tagged(tagged) = value {
+        if tagged.tag.name == crate::base64::BINARY_TAG {
+            break;
         }
+        value = &tagged.value;
     }
+    value
 }
 
-struct MapDeserializer<I> {
-    iter: I,
-    value: Option<Value>,
-}
-
-impl<I> MapDeserializer<I>
-where
-    I: Iterator<Item = (Value, Value)>,
-{
-    const fn new(iter: I) -> Self {
-        Self { iter, value: None }
-    }
+/// [`de::EnumAccess`] over a borrowed enum: the value naming the variant,
+/// and its content unless it is a unit variant written as just its name
+struct EnumRefDeserializer<'a> {
+    variant: Cow<'a, Value>,
+    content: Option<&'a Value>,
 }
 
-impl<'de, I> de::MapAccess<'de> for MapDeserializer<I>
-where
-    I: Iterator<Item = (Value, Value)>,
-{
+impl<'de, 'a> de::EnumAccess<'de> for EnumRefDeserializer<'a> {
     type Error = Error;
+    type Variant = VariantRefDeserializer<'a>;
 
-    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Error>
-    where
-        K: de::DeserializeSeed<'de>,
-    {
-        match self.iter.next() {
-            Some((key, value)) => {
-                self.value = Some(value);
-                seed.deserialize(Deserializer::new(key)).map(Some)
-            }
-            None => Ok(None),
-        }
-    }
-
-    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Error>
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Error>
     where
         V: de::DeserializeSeed<'de>,
     {
-        match self.value.take() {
-            Some(value) => seed.deserialize(Deserializer::new(value)),
-            None => Err(Error::Custom("value is missing".to_string())),
-        }
+        let variant = seed.deserialize(self.variant.as_ref())?;
+        Ok((variant, VariantRefDeserializer { content: self.content }))
     }
 }
 
-struct EnumDeserializer {
-    value: String,
+/// [`de::VariantAccess`] over the borrowed content of a variant
+struct VariantRefDeserializer<'a> {
+    content: Option<&'a Value>,
 }
 
-impl<'de> de::EnumAccess<'de> for EnumDeserializer {
-    type Error = Error;
-    type Variant = UnitVariantDeserializer;
-
-    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Error>
-    where
-        V: de::DeserializeSeed<'de>,
-    {
-        let variant = seed.deserialize(Deserializer::new(Value::String(self.value)))?;
-        Ok((variant, UnitVariantDeserializer))
+impl<'a> VariantRefDeserializer<'a> {
+    fn content(self, expected: &str) -> Result<&'a Value, Error> {
+        self.content
+            .ok_or_else(|| Error::Custom(format!("expected {expected} variant")))
     }
 }
 
-struct UnitVariantDeserializer;
-
-impl<'de> de::VariantAccess<'de> for UnitVariantDeserializer {
+impl<'de> de::VariantAccess<'de> for VariantRefDeserializer<'_> {
     type Error = Error;
 
     fn unit_variant(self) -> Result<(), Error> {
-        Ok(())
+        self.content.map_or(Ok(()), de::Deserialize::deserialize)
     }
 
-    fn newtype_variant_seed<T>(self, _seed: T) -> Result<T::Value, Error>
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Error>
     where
         T: de::DeserializeSeed<'de>,
     {
-        Err(Error::Custom("newtype variants not supported".to_string()))
+        seed.deserialize(self.content("newtype")?)
     }
 
-    fn tuple_variant<V>(self, _len: usize, _visitor: V) -> Result<V::Value, Error>
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value, Error>
     where
         V: de::Visitor<'de>,
     {
-        Err(Error::Custom("tuple variants not supported".to_string()))
+        de::Deserializer::deserialize_seq(self.content("tuple")?, visitor)
     }
 
     fn struct_variant<V>(
         self,
         _fields: &'static [&'static str],
-        _visitor: V,
+        visitor: V,
     ) -> Result<V::Value, Error>
     where
         V: de::Visitor<'de>,
     {
-        Err(Error::Custom("struct variants not supported".to_string()))
+        de::Deserializer::deserialize_map(self.content("struct")?, visitor)
     }
 }
 
-/// Convert a YAML value to a serde-compatible Value
-pub fn from_value<T>(value: Value) -> Result<T, Error>
-where
-    T: serde::de::DeserializeOwned,
-{
-    T::deserialize(Deserializer::new(value))
+/// [`de::SeqAccess`] over the elements of a borrowed sequence
+struct SeqRefDeserializer<'a> {
+    iter: std::slice::Iter<'a, Value>,
 }
 
-// Implement Deserializer for &Value to support direct deserialization
-impl<'de> de::Deserializer<'de> for &Value {
+impl<'de> de::SeqAccess<'de> for SeqRefDeserializer<'_> {
     type Error = Error;
 
-    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Error>
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Error>
     where
-        V: de::Visitor<'de>,
+        T: de::DeserializeSeed<'de>,
     {
-        let deserializer = Deserializer::new(self.clone());
-        deserializer.deserialize_any(visitor)
+        self.iter.next().map(|value| seed.deserialize(value)).transpose()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+/// [`de::MapAccess`] over the entries of a borrowed mapping
+struct MapRefDeserializer<'a> {
+    iter: std::collections::btree_map::Iter<'a, Value, Value>,
+    value: Option<&'a Value>,
+}
+
+impl<'a> MapRefDeserializer<'a> {
+    fn new(map: &'a Mapping) -> Self {
+        Self {
+            iter: map.iter(),
+            value: None,
+        }
+    }
+}
+
+impl<'de> de::MapAccess<'de> for MapRefDeserializer<'_> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Error>
+    where
+        K: de::DeserializeSeed<'de>,
+    {
+        match self.iter.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                seed.deserialize(key).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Error>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        match self.value.take() {
+            Some(value) => seed.deserialize(value),
+            None => Err(Error::Custom("value is missing".to_string())),
+        }
     }
 
-    // Delegate all other methods to the main Deserializer
-    serde::forward_to_deserialize_any! {
-        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
-        bytes byte_buf option unit unit_struct newtype_struct seq tuple
-        tuple_struct map struct enum identifier ignored_any
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
     }
 }
@@ -0,0 +1,100 @@
+//! Byte counts (`u64`) as `512MB`, `2Gi` or `1.5G`
+//!
+//! Decimal suffixes (`k`, `M`, `G`, `T`, `P`, `E`) scale by powers of 1000
+//! and binary ones (`Ki`, `Mi`, `Gi`, …) by powers of 1024; either may end
+//! in `B`, and the prefix letter is case-insensitive. A bare number counts
+//! bytes, and fractions of a byte are truncated. Sizes serialize with
+//! whichever exact suffix gives the smallest number, preferring binary on
+//! ties: `2Gi`, `512MB`, `100B`.
+
+use serde::de::{self, Deserializer, Visitor};
+use serde::ser::Serializer;
+use std::fmt;
+
+const PREFIXES: [char; 6] = ['k', 'm', 'g', 't', 'p', 'e'];
+
+pub fn serialize<S>(value: &u64, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(&format(*value))
+}
+
+pub fn deserialize<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_any(ByteSizeVisitor)
+}
+
+fn format(value: u64) -> String {
+    let largest = |base: u64| {
+        let mut power = 0;
+        let mut n = value;
+        while n != 0 && n.is_multiple_of(base) && power < PREFIXES.len() {
+            n /= base;
+            power += 1;
+        }
+        (n, power)
+    };
+    let (binary, binary_power) = largest(1024);
+    let (decimal, decimal_power) = largest(1000);
+    if binary_power > 0 && binary <= decimal {
+        let prefix = PREFIXES[binary_power - 1].to_ascii_uppercase();
+        format!("{binary}{prefix}i")
+    } else if decimal_power > 0 {
+        let prefix = match PREFIXES[decimal_power - 1] {
+            'k' => 'k',
+            p => p.to_ascii_uppercase(),
+        };
+        format!("{decimal}{prefix}B")
+    } else {
+        format!("{value}B")
+    }
+}
+
+fn parse(s: &str) -> Option<u64> {
+    let s = s.trim();
+    let (number, suffix) = s.split_at(super::number_len(s));
+    let suffix = suffix.trim_start().to_ascii_lowercase();
+    let suffix = suffix.strip_suffix('b').unwrap_or(&suffix);
+    let (prefix, base) = match suffix.strip_suffix('i') {
+        Some(prefix) => (prefix, 1024u128),
+        None => (suffix, 1000),
+    };
+    let power = match prefix {
+        "" if base == 1000 => 0,
+        "" => return None,
+        _ => {
+            let mut chars = prefix.chars();
+            let letter = chars.next()?;
+            if chars.next().is_some() {
+                return None;
+            }
+            PREFIXES.iter().position(|&p| p == letter)? as u32 + 1
+        }
+    };
+    u64::try_from(super::scaled(number, base.pow(power))?).ok()
+}
+
+struct ByteSizeVisitor;
+
+impl Visitor<'_> for ByteSizeVisitor {
+    type Value = u64;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a byte size such as 512MB or 2Gi")
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<u64, E> {
+        Ok(v)
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<u64, E> {
+        u64::try_from(v).map_err(|_| E::invalid_value(de::Unexpected::Signed(v), &self))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<u64, E> {
+        parse(v).ok_or_else(|| E::invalid_value(de::Unexpected::Str(v), &self))
+    }
+}
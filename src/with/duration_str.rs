@@ -0,0 +1,106 @@
+//! [`Duration`] as `30s`, `5m`, `1h30m` or `250ms`
+//!
+//! Accepted units are `d`, `h`, `m`, `s`, `ms`, `us` (or `µs`) and `ns`;
+//! components may be fractional (`1.5h`) and are summed. A bare number,
+//! quoted or not, counts seconds. Durations serialize with the largest unit
+//! that represents them exactly, so `90s` stays `90s` and `1.5s` becomes
+//! `1500ms`.
+
+use serde::de::{self, Deserializer, Visitor};
+use serde::ser::Serializer;
+use std::fmt;
+use std::time::Duration;
+
+const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+const UNITS: [(&str, u128); 7] = [
+    ("d", 86_400 * NANOS_PER_SEC),
+    ("h", 3_600 * NANOS_PER_SEC),
+    ("m", 60 * NANOS_PER_SEC),
+    ("s", NANOS_PER_SEC),
+    ("ms", 1_000_000),
+    ("us", 1_000),
+    ("ns", 1),
+];
+
+pub fn serialize<S>(value: &Duration, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(&format(*value))
+}
+
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_any(DurationVisitor)
+}
+
+fn format(value: Duration) -> String {
+    let nanos = value.as_nanos();
+    for (unit, size) in UNITS {
+        if nanos != 0 && nanos.is_multiple_of(size) {
+            return format!("{}{unit}", nanos / size);
+        }
+    }
+    "0s".to_owned()
+}
+
+fn parse(s: &str) -> Option<Duration> {
+    let s = s.trim();
+    let len = super::number_len(s);
+    if len == s.len() {
+        return from_nanos(super::scaled(s, NANOS_PER_SEC)?);
+    }
+    let mut rest = s;
+    let mut nanos = 0u128;
+    while !rest.is_empty() {
+        let len = super::number_len(rest);
+        let (number, tail) = rest.split_at(len);
+        let unit_len = tail
+            .find(|c: char| c.is_ascii_digit() || c == '.' || c.is_whitespace())
+            .unwrap_or(tail.len());
+        let (unit, tail) = tail.split_at(unit_len);
+        let size = match unit {
+            "µs" => 1_000,
+            _ => UNITS.iter().find(|(name, _)| *name == unit)?.1,
+        };
+        nanos = nanos.checked_add(super::scaled(number, size)?)?;
+        rest = tail.trim_start();
+    }
+    from_nanos(nanos)
+}
+
+fn from_nanos(nanos: u128) -> Option<Duration> {
+    let secs = u64::try_from(nanos / NANOS_PER_SEC).ok()?;
+    Some(Duration::new(secs, (nanos % NANOS_PER_SEC) as u32))
+}
+
+struct DurationVisitor;
+
+impl Visitor<'_> for DurationVisitor {
+    type Value = Duration;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a duration such as 30s, 5m or 1h30m")
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Duration, E> {
+        Ok(Duration::from_secs(v))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Duration, E> {
+        u64::try_from(v)
+            .map(Duration::from_secs)
+            .map_err(|_| E::invalid_value(de::Unexpected::Signed(v), &self))
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Duration, E> {
+        Duration::try_from_secs_f64(v).map_err(|_| E::invalid_value(de::Unexpected::Float(v), &self))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Duration, E> {
+        parse(v).ok_or_else(|| E::invalid_value(de::Unexpected::Str(v), &self))
+    }
+}
@@ -0,0 +1,52 @@
+//! Serde adapters for humanized scalars and lenient fields, for use with
+//! `#[serde(with = ...)]`
+//!
+//! ```rust
+//! use serde::{Deserialize, Serialize};
+//! use std::time::Duration;
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Limits {
+//!     #[serde(with = "yyaml::with::duration_str")]
+//!     timeout: Duration,
+//!     #[serde(with = "yyaml::with::byte_size")]
+//!     memory: u64,
+//! }
+//!
+//! let limits: Limits = yyaml::from_str("timeout: 1m30s\nmemory: 2Gi").unwrap();
+//! assert_eq!(limits.timeout, Duration::from_secs(90));
+//! assert_eq!(limits.memory, 2 << 30);
+//! assert_eq!(yyaml::to_string(&limits).unwrap(), "---\ntimeout: 90s\nmemory: 2Gi");
+//! ```
+
+pub mod byte_size;
+pub mod duration_str;
+pub mod one_or_many;
+
+/// Split a leading decimal number such as `1.5` from the rest of `s` and
+/// scale it by `unit`, truncating fractional digits past 18
+fn scaled(number: &str, unit: u128) -> Option<u128> {
+    let (whole, frac) = number.split_once('.').unwrap_or((number, ""));
+    if whole.is_empty() && frac.is_empty() {
+        return None;
+    }
+    let parse = |digits: &str| -> Option<u128> {
+        if digits.bytes().all(|b| b.is_ascii_digit()) {
+            digits.bytes().try_fold(0u128, |n, b| {
+                n.checked_mul(10)?.checked_add(u128::from(b - b'0'))
+            })
+        } else {
+            None
+        }
+    };
+    let frac = &frac[..frac.len().min(18)];
+    let scale = 10u128.pow(frac.len() as u32);
+    let whole = parse(whole)?.checked_mul(unit)?;
+    whole.checked_add(parse(frac)?.checked_mul(unit)? / scale)
+}
+
+/// Length of the leading `[0-9.]` run of `s`
+fn number_len(s: &str) -> usize {
+    s.find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(s.len())
+}
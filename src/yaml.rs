@@ -1,4 +1,12 @@
+use crate::error::{Marker, ScanError};
+use crate::events::{TScalarStyle, TokenType};
 use crate::linked_hash_map::LinkedHashMap;
+use crate::parser::SchemaMode;
+use crate::parser::state_machine::marked_anchor;
+use crate::scanner::Scanner;
+use crate::value::Number;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 use std::hash::{Hash, Hasher};
 
 /// The YAML node representation, mirroring the original design:
@@ -11,6 +19,7 @@ use std::hash::{Hash, Hasher};
 /// - `Alias` for referencing an anchor.
 /// - `Null` represents explicit YAML null.
 /// - `BadValue` is returned for invalid indexing or out-of-range lookups.
+/// - `Invalid` is a node that could not be built, with the reason.
 #[derive(Clone, PartialEq, PartialOrd, Debug, Eq, Ord)]
 pub enum Yaml {
     Real(String),
@@ -22,7 +31,37 @@ pub enum Yaml {
     Alias(usize),
     Tagged(String, Box<Yaml>),
     Null,
+    /// A bad value with no reason recorded, as a lookup that misses gives
     BadValue,
+    /// A node that could not be built, with why and where
+    Invalid(Box<InvalidNode>),
+}
+
+/// Why a node is a [`Yaml::Invalid`]
+#[derive(Clone, PartialEq, PartialOrd, Debug, Eq, Ord, Hash)]
+pub enum BadValueKind {
+    /// A scalar does not read as the type its core schema tag names, as
+    /// `!!int abc`
+    TagMismatch { tag: String, value: String },
+    /// A document ended without a root node
+    EmptyDocument,
+}
+
+impl fmt::Display for BadValueKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TagMismatch { tag, value } => write!(f, "`{value}` is not a valid !!{tag}"),
+            Self::EmptyDocument => write!(f, "document has no root node"),
+        }
+    }
+}
+
+/// The reason and position of a [`Yaml::Invalid`] node
+#[derive(Clone, PartialEq, PartialOrd, Debug, Eq, Ord, Hash)]
+pub struct InvalidNode {
+    pub kind: BadValueKind,
+    /// Where the node was read, when the builder knew
+    pub mark: Option<Marker>,
 }
 
 impl Hash for Yaml {
@@ -68,6 +107,10 @@ impl Hash for Yaml {
             Self::BadValue => {
                 9.hash(state);
             }
+            Self::Invalid(invalid) => {
+                10.hash(state);
+                invalid.hash(state);
+            }
         }
     }
 }
@@ -140,7 +183,396 @@ impl Yaml {
     #[inline(always)]
     #[must_use] 
     pub const fn is_badvalue(&self) -> bool {
-        matches!(*self, Self::BadValue)
+        matches!(*self, Self::BadValue | Self::Invalid(_))
+    }
+
+    /// Why this node is invalid, if it is a [`Yaml::Invalid`]
+    #[must_use]
+    pub fn invalid_reason(&self) -> Option<&InvalidNode> {
+        match self {
+            Self::Invalid(invalid) => Some(invalid),
+            _ => None,
+        }
+    }
+
+    /// The tag of a [`Yaml::Tagged`] node; the outer one if it is tagged
+    /// twice
+    #[must_use]
+    pub fn tag(&self) -> Option<&str> {
+        match self {
+            Self::Tagged(tag, _) => Some(tag),
+            _ => None,
+        }
+    }
+
+    /// This node tagged `tag`, replacing its outer tag if it has one
+    ///
+    /// ```rust
+    /// use yyaml::Yaml;
+    ///
+    /// let node = Yaml::Integer(21).with_tag("!celsius");
+    /// assert_eq!(node.tag(), Some("!celsius"));
+    /// let node = node.with_tag("!fahrenheit");
+    /// assert_eq!(node.tag(), Some("!fahrenheit"));
+    /// assert_eq!(node.untag(), Yaml::Integer(21));
+    /// ```
+    #[must_use]
+    pub fn with_tag(self, tag: impl Into<String>) -> Self {
+        match self {
+            Self::Tagged(_, inner) => Self::Tagged(tag.into(), inner),
+            node => Self::Tagged(tag.into(), Box::new(node)),
+        }
+    }
+
+    /// This node without its outer tag; an untagged node is unchanged
+    #[must_use]
+    pub fn untag(self) -> Self {
+        match self {
+            Self::Tagged(_, inner) => *inner,
+            node => node,
+        }
+    }
+
+    /// Entry under the string key `key`, or `None` if this is not a mapping
+    /// or the key is absent
+    ///
+    /// Unlike indexing, a key that is present with a null value gives
+    /// `Some(&Yaml::Null)`. Tagged nodes are transparent.
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&Self> {
+        match self {
+            Self::Hash(map) => map.get_str(key),
+            Self::Tagged(_, value) => value.get(key),
+            _ => None,
+        }
+    }
+
+    /// Mutable variant of [`Yaml::get`]
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Self> {
+        match self {
+            Self::Hash(map) => map.get_str_mut(key),
+            Self::Tagged(_, value) => value.get_mut(key),
+            _ => None,
+        }
+    }
+
+    /// Sequence item at `index`, or `None` if this is not a sequence or the
+    /// index is out of bounds
+    #[must_use]
+    pub fn get_index(&self, index: usize) -> Option<&Self> {
+        match self {
+            Self::Array(seq) => seq.get(index),
+            Self::Tagged(_, value) => value.get_index(index),
+            _ => None,
+        }
+    }
+
+    /// Mutable variant of [`Yaml::get_index`]
+    pub fn get_index_mut(&mut self, index: usize) -> Option<&mut Self> {
+        match self {
+            Self::Array(seq) => seq.get_mut(index),
+            Self::Tagged(_, value) => value.get_index_mut(index),
+            _ => None,
+        }
+    }
+
+    /// Follow `path` one segment at a time, e.g. `&["spec", "ports", "0"]`
+    ///
+    /// Segments select mapping keys, or sequence items by decimal index, the
+    /// same way [`Yaml::pointer`] does but without `~` escaping.
+    #[must_use]
+    pub fn get_path(&self, path: &[&str]) -> Option<&Self> {
+        path.iter()
+            .try_fold(self, |node, segment| node.pointer_child(segment))
+    }
+
+    /// Mutable variant of [`Yaml::get_path`]
+    pub fn get_path_mut(&mut self, path: &[&str]) -> Option<&mut Self> {
+        path.iter()
+            .try_fold(self, |node, segment| node.pointer_child_mut(segment))
+    }
+
+    /// Approximate bytes held by this node and everything it owns
+    ///
+    /// Unused capacity is included; map storage is estimated from typical
+    /// B-tree occupancy, so treat the result as a planning figure.
+    #[must_use]
+    pub fn estimated_size(&self) -> usize {
+        std::mem::size_of::<Self>() + self.heap_size()
+    }
+
+    /// Whether `self` and `other` hold the same content, ignoring what `==`
+    /// notices but YAML does not: the order of mapping keys, how a float is
+    /// spelled (`1.0`, `1.00` and `1e0` are equal, as are two `.nan`s), and
+    /// core schema tags that only restate how a node resolves (`!!str a`
+    /// equals `a`, `!!str 1` equals `"1"`).
+    ///
+    /// Nodes that are `==` are always semantically equal; integers and floats
+    /// stay distinct.
+    ///
+    /// ```rust
+    /// use yyaml::YamlLoader;
+    ///
+    /// let a = &YamlLoader::load_from_str("b: 1.50\na: !!str x\nc: [.nan]\n").unwrap()[0];
+    /// let b = &YamlLoader::load_from_str("{a: x, c: [.NaN], b: 1.5}").unwrap()[0];
+    /// assert_ne!(a, b);
+    /// assert!(a.semantic_eq(b));
+    /// ```
+    #[must_use]
+    pub fn semantic_eq(&self, other: &Self) -> bool {
+        match (&*self.core_resolved(), &*other.core_resolved()) {
+            (Self::Real(a), Self::Real(b)) => match (parse_f64(a), parse_f64(b)) {
+                (Some(x), Some(y)) => x == y || (x.is_nan() && y.is_nan()),
+                _ => a == b,
+            },
+            (Self::Array(a), Self::Array(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(x, y)| x.semantic_eq(y))
+            }
+            (Self::Hash(a), Self::Hash(b)) => {
+                a.len() == b.len()
+                    && entries_semantic_eq(a.iter(), b.iter(), |key| b.get(key), Self::semantic_eq)
+            }
+            (Self::Tagged(tag, a), Self::Tagged(other_tag, b)) => {
+                tag == other_tag && a.semantic_eq(b)
+            }
+            (a, b) => a == b,
+        }
+    }
+
+    /// This node with a core schema tag that restates its resolution
+    /// removed, so `!!str 1` becomes the string `1`
+    fn core_resolved(&self) -> std::borrow::Cow<'_, Self> {
+        use std::borrow::Cow;
+
+        let Self::Tagged(tag, inner) = self else {
+            return Cow::Borrowed(self);
+        };
+        let Some(kind) = tag.strip_prefix("tag:yaml.org,2002:") else {
+            return Cow::Borrowed(self);
+        };
+        match (kind, inner.as_ref()) {
+            ("str", Self::String(_))
+            | ("int", Self::Integer(_))
+            | ("float", Self::Real(_))
+            | ("bool", Self::Boolean(_))
+            | ("null", Self::Null)
+            | ("seq", Self::Array(_))
+            | ("map", Self::Hash(_)) => Cow::Borrowed(inner),
+            ("str", Self::Integer(i)) => Cow::Owned(Self::String(i.to_string())),
+            ("str", Self::Real(s)) => Cow::Owned(Self::String(s.clone())),
+            ("str", Self::Boolean(b)) => Cow::Owned(Self::String(b.to_string())),
+            ("float", Self::Integer(i)) => Cow::Owned(Self::Real(i.to_string())),
+            _ => Cow::Borrowed(self),
+        }
+    }
+
+    fn heap_size(&self) -> usize {
+        match self {
+            Self::Real(s) | Self::String(s) => s.capacity(),
+            Self::Array(items) => {
+                items.capacity() * std::mem::size_of::<Self>()
+                    + items.iter().map(Self::heap_size).sum::<usize>()
+            }
+            Self::Hash(map) => {
+                map.table_size()
+                    + map
+                        .iter()
+                        .map(|(key, value)| key.heap_size() + value.heap_size())
+                        .sum::<usize>()
+            }
+            Self::Tagged(tag, inner) => tag.capacity() + inner.estimated_size(),
+            _ => 0,
+        }
+    }
+
+    /// Look up a node by JSON Pointer path, e.g. `/spec/replicas` or `/items/0`
+    ///
+    /// Tagged nodes are transparent, so a pointer walks through their content.
+    #[must_use]
+    pub fn pointer(&self, path: &str) -> Option<&Self> {
+        let tokens = crate::pointer::tokens(path).ok()?;
+        tokens
+            .iter()
+            .try_fold(self, |node, token| node.pointer_child(token))
+    }
+
+    /// A self-contained copy of the node at `path`, a JSON Pointer
+    ///
+    /// Loading expands aliases, so for a loaded document this is a clone of
+    /// what [`pointer`](Self::pointer) finds. In a tree that keeps
+    /// [`Yaml::Alias`] nodes, an alias whose anchor lies outside the subtree
+    /// is replaced by a copy of the anchored node, itself made
+    /// self-contained; the first copy keeps the anchor, so later aliases to
+    /// it still resolve. Aliases to anchors inside the subtree are kept.
+    #[must_use]
+    pub fn extract(&self, path: &str) -> Option<Self> {
+        let node = self.pointer(path)?;
+        let mut anchored = HashMap::new();
+        self.collect_anchored(&mut anchored);
+        Some(node.inline_aliases(&anchored, &mut HashSet::new()))
+    }
+
+    /// Every anchored node in the tree by anchor id, with its anchor
+    fn collect_anchored<'a>(&'a self, anchored: &mut HashMap<usize, &'a Self>) {
+        match self {
+            Self::Tagged(tag, inner) => {
+                if let Some(id) = marked_anchor(tag) {
+                    anchored.insert(id, self);
+                }
+                inner.collect_anchored(anchored);
+            }
+            Self::Array(items) => items.iter().for_each(|item| item.collect_anchored(anchored)),
+            Self::Hash(map) => {
+                for (key, value) in map.iter() {
+                    key.collect_anchored(anchored);
+                    value.collect_anchored(anchored);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Copy of the tree with aliases to anchors not yet `defined` inlined
+    fn inline_aliases(&self, anchored: &HashMap<usize, &Self>, defined: &mut HashSet<usize>) -> Self {
+        match self {
+            Self::Alias(id) if !defined.contains(id) => match anchored.get(id) {
+                Some(target) => target.inline_aliases(anchored, defined),
+                None => self.clone(),
+            },
+            Self::Tagged(tag, inner) => {
+                if let Some(id) = marked_anchor(tag) {
+                    defined.insert(id);
+                }
+                Self::Tagged(tag.clone(), Box::new(inner.inline_aliases(anchored, defined)))
+            }
+            Self::Array(items) => Self::Array(
+                items
+                    .iter()
+                    .map(|item| item.inline_aliases(anchored, defined))
+                    .collect(),
+            ),
+            Self::Hash(map) => {
+                let mut out = LinkedHashMap::new();
+                for (key, value) in map.iter() {
+                    let key = key.inline_aliases(anchored, defined);
+                    out.insert(key, value.inline_aliases(anchored, defined));
+                }
+                Self::Hash(out)
+            }
+            _ => self.clone(),
+        }
+    }
+
+    /// Mutable variant of [`Yaml::pointer`]
+    pub fn pointer_mut(&mut self, path: &str) -> Option<&mut Self> {
+        let tokens = crate::pointer::tokens(path).ok()?;
+        tokens
+            .iter()
+            .try_fold(self, |node, token| node.pointer_child_mut(token))
+    }
+
+    /// Serialize `value` and graft it into this document at `path`
+    ///
+    /// Follows the same rules as [`crate::Value::set_from`]: missing mappings
+    /// are created, `-` appends to a sequence, and `""` replaces the document.
+    pub fn set_from<T>(&mut self, path: &str, value: &T) -> Result<(), crate::Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        let tokens = crate::pointer::tokens(path)?;
+        let new_node = value.serialize(crate::ser::YamlSerializer::new())?;
+        let mut node = self;
+        for token in &tokens {
+            node = node.pointer_child_or_insert(token)?;
+        }
+        *node = new_node;
+        Ok(())
+    }
+
+    /// Deserialize the node at `path` into a typed value
+    pub fn get_as<T>(&self, path: &str) -> Result<T, crate::Error>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let tokens = crate::pointer::tokens(path)?;
+        let node = tokens
+            .iter()
+            .try_fold(self, |node, token| node.pointer_child(token))
+            .ok_or_else(|| crate::Error::Custom(format!("no value at path '{path}'")))?;
+        crate::from_value(crate::Value::from_yaml(node))
+    }
+
+    fn pointer_child(&self, token: &str) -> Option<&Self> {
+        match self {
+            Self::Array(seq) => crate::pointer::index(token).and_then(|i| seq.get(i)),
+            Self::Hash(map) => map.get(&Self::pointer_key(map, token)),
+            Self::Tagged(_, value) => value.pointer_child(token),
+            _ => None,
+        }
+    }
+
+    fn pointer_child_mut(&mut self, token: &str) -> Option<&mut Self> {
+        match self {
+            Self::Array(seq) => crate::pointer::index(token).and_then(|i| seq.get_mut(i)),
+            Self::Hash(map) => {
+                let key = Self::pointer_key(map, token);
+                map.get_mut(&key)
+            }
+            Self::Tagged(_, value) => value.pointer_child_mut(token),
+            _ => None,
+        }
+    }
+
+    fn pointer_child_or_insert(&mut self, token: &str) -> Result<&mut Self, crate::Error> {
+        if self.is_null() {
+            *self = Self::Hash(LinkedHashMap::new());
+        }
+        match self {
+            Self::Array(seq) => {
+                let len = seq.len();
+                let index = if token == "-" {
+                    len
+                } else {
+                    crate::pointer::index(token).ok_or_else(|| {
+                        crate::Error::Custom(format!("invalid sequence index '{token}'"))
+                    })?
+                };
+                if index == len {
+                    seq.push(Self::Null);
+                }
+                seq.get_mut(index).ok_or_else(|| {
+                    crate::Error::Custom(format!(
+                        "sequence index {index} out of bounds (length {len})"
+                    ))
+                })
+            }
+            Self::Hash(map) => {
+                let key = Self::pointer_key(map, token);
+                if !map.contains_key(&key) {
+                    map.insert(key.clone(), Self::Null);
+                }
+                map.get_mut(&key).ok_or_else(|| {
+                    crate::Error::Custom(format!("failed to insert key '{token}'"))
+                })
+            }
+            Self::Tagged(_, value) => value.pointer_child_or_insert(token),
+            other => Err(crate::Error::Custom(format!(
+                "cannot descend into scalar {other:?} at '{token}'"
+            ))),
+        }
+    }
+
+    /// Resolve a pointer token to a mapping key, falling back to an integer
+    /// key when the mapping has no string key of that name.
+    fn pointer_key(map: &LinkedHashMap<Self, Self>, token: &str) -> Self {
+        let key = Self::String(token.to_owned());
+        if !map.contains_key(&key)
+            && let Ok(i) = token.parse::<i64>()
+            && map.contains_key(&Self::Integer(i))
+        {
+            return Self::Integer(i);
+        }
+        key
     }
 
     /// Parse a string into a Yaml value with automatic type detection
@@ -220,10 +652,14 @@ impl Yaml {
         {
             return Self::Integer(i);
         }
+        if let Some(approx) = overflowing_integer(v, SchemaMode::Core) {
+            return Self::promoted_integer(v, approx);
+        }
         match v {
-            "~" | "null" => Self::Null,
-            "true" => Self::Boolean(true),
-            "false" => Self::Boolean(false),
+            // YAML 1.2 core schema spellings
+            "~" | "null" | "Null" | "NULL" => Self::Null,
+            "true" | "True" | "TRUE" => Self::Boolean(true),
+            "false" | "False" | "FALSE" => Self::Boolean(false),
             _ if v.parse::<i64>().is_ok()
                 && !has_invalid_leading_zeros(v)
                 && !has_invalid_sign_prefix(v) =>
@@ -238,6 +674,178 @@ impl Yaml {
             _ => Self::String(v.into()),
         }
     }
+
+    /// Parse `s` as one standalone flow node, such as a value passed on the
+    /// command line.
+    ///
+    /// Unlike [`parse_str`](Self::parse_str), which only resolves a plain
+    /// scalar, this runs the parser, so flow collections, quoted scalars,
+    /// tags and anchors all work. Block collections, block scalars, document
+    /// markers, directives and anything after the node are errors. Empty
+    /// input is null.
+    ///
+    /// ```rust
+    /// use yyaml::Yaml;
+    ///
+    /// let node = Yaml::parse_fragment("[1, {a: 2}]").unwrap();
+    /// assert_eq!(node[1]["a"].as_i64(), Some(2));
+    /// assert!(matches!(Yaml::parse_fragment("!color red").unwrap(), Yaml::Tagged(..)));
+    /// assert!(Yaml::parse_fragment("a: 1").is_err());
+    /// assert!(Yaml::parse_fragment("[1] [2]").is_err());
+    /// ```
+    pub fn parse_fragment(s: &str) -> Result<Self, ScanError> {
+        check_fragment(s)?;
+        let mut docs = crate::YamlLoader::load_from_str(s)?;
+        Ok(docs.pop().unwrap_or(Self::Null))
+    }
+
+    /// Resolve a plain scalar with the YAML 1.2 JSON schema, matching how a
+    /// JSON parser reads the same token.
+    ///
+    /// Only `null`, `true`, `false` and JSON numbers are typed; anything else,
+    /// including `~`, `yes`, `0x1f` and `.inf`, is a string. Integers outside
+    /// the `i64` range load as reals.
+    #[must_use]
+    pub fn parse_json_str(v: &str) -> Self {
+        match v {
+            "null" => Self::Null,
+            "true" => Self::Boolean(true),
+            "false" => Self::Boolean(false),
+            // `-0` keeps its sign as a float, as JSON parsers read it
+            _ if is_json_number(v) => match v.parse::<i64>() {
+                Ok(i) if v != "-0" && !v.contains(['.', 'e', 'E']) => Self::Integer(i),
+                _ => Self::Real(v.into()),
+            },
+            _ => Self::String(v.into()),
+        }
+    }
+
+    /// `v`, an integer too large for an `i64` whose value is about
+    /// `approx`, as a real; decimal spellings keep their text, and others
+    /// are spelled in decimal when they fit in an `i128`
+    pub(crate) fn promoted_integer(v: &str, approx: f64) -> Self {
+        if parse_f64(v).is_some() {
+            Self::Real(v.into())
+        } else if let Some(exact) = radix_integer(v) {
+            Self::Real(exact.to_string())
+        } else {
+            Self::Real(format!("{approx:?}"))
+        }
+    }
+}
+
+/// Whether the entries of two mappings of equal length pair up one to one,
+/// keys and values matching under `eq`. Entries with `==` keys pair through
+/// `get` on the second mapping; the rest are matched by search, and no entry
+/// of `b` is matched twice.
+pub(crate) fn entries_semantic_eq<'a, T: 'a>(
+    a: impl Iterator<Item = (&'a T, &'a T)>,
+    b: impl Iterator<Item = (&'a T, &'a T)>,
+    get: impl Fn(&T) -> Option<&'a T>,
+    eq: impl Fn(&T, &T) -> bool,
+) -> bool {
+    let mut taken = HashSet::new();
+    let mut unpaired = Vec::new();
+    for (key, x) in a {
+        match get(key) {
+            Some(y) if eq(x, y) => {
+                taken.insert(std::ptr::from_ref(y));
+            }
+            _ => unpaired.push((key, x)),
+        }
+    }
+    let mut rest: Vec<_> = b.filter(|(_, y)| !taken.contains(&std::ptr::from_ref(*y))).collect();
+    unpaired.into_iter().all(|(key, x)| {
+        rest.iter()
+            .position(|(k, y)| eq(key, k) && eq(x, y))
+            .map(|i| rest.swap_remove(i))
+            .is_some()
+    })
+}
+
+/// The mismatch if `text` does not read as the core schema type `tag`
+/// names, a full tag such as `tag:yaml.org,2002:int`; tags of other
+/// types, collections and strings included, accept any text
+pub(crate) fn core_tag_mismatch(tag: &str, text: &str) -> Option<BadValueKind> {
+    let kind = tag.strip_prefix("tag:yaml.org,2002:")?;
+    let accepted = match (kind, Yaml::parse_str(text)) {
+        ("int", Yaml::Integer(_)) | ("float", Yaml::Real(_) | Yaml::Integer(_)) => true,
+        ("int", _) => overflowing_integer(text, SchemaMode::Core).is_some(),
+        ("bool", node) => matches!(node, Yaml::Boolean(_)),
+        ("null", node) => text.is_empty() || node.is_null(),
+        ("float", _) => false,
+        _ => true,
+    };
+    (!accepted).then(|| BadValueKind::TagMismatch {
+        tag: kind.to_string(),
+        value: text.to_string(),
+    })
+}
+
+/// The approximate value of `v` if it is an integer under `schema` that
+/// does not fit in an `i64`
+pub(crate) fn overflowing_integer(v: &str, schema: SchemaMode) -> Option<f64> {
+    let core = schema == SchemaMode::Core;
+    let (negative, unsigned) = match v.as_bytes().first()? {
+        b'-' => (true, &v[1..]),
+        b'+' if core => (false, &v[1..]),
+        b'0'..=b'9' => (false, v),
+        _ => return None,
+    };
+    let (radix, digits) = match unsigned.get(..2) {
+        Some("0x") if core => (16, &unsigned[2..]),
+        Some("0o") if core => (8, &unsigned[2..]),
+        Some("0b") if core => (2, &unsigned[2..]),
+        _ => (10, unsigned),
+    };
+    if digits.is_empty()
+        || !digits.chars().all(|c| c.is_digit(radix))
+        || (radix == 10 && digits.len() > 1 && digits.starts_with('0'))
+    {
+        return None;
+    }
+    let limit = if negative { i64::MIN.unsigned_abs() } else { i64::MAX.unsigned_abs() };
+    if u64::from_str_radix(digits, radix).is_ok_and(|magnitude| magnitude <= limit) {
+        return None;
+    }
+    let magnitude = if radix == 10 {
+        digits.parse().ok()?
+    } else {
+        digits
+            .chars()
+            .filter_map(|c| c.to_digit(radix))
+            .fold(0.0, |acc, d| acc * f64::from(radix) + f64::from(d))
+    };
+    Some(if negative { -magnitude } else { magnitude })
+}
+
+/// Whether `v` matches the JSON number grammar `-?(0|[1-9][0-9]*)(\.[0-9]+)?([eE][-+]?[0-9]+)?`
+fn is_json_number(v: &str) -> bool {
+    fn digits(s: &str) -> usize {
+        s.bytes().take_while(u8::is_ascii_digit).count()
+    }
+    let rest = v.strip_prefix('-').unwrap_or(v);
+    let int = digits(rest);
+    if int == 0 || (int > 1 && rest.starts_with('0')) {
+        return false;
+    }
+    let mut rest = &rest[int..];
+    if let Some(frac) = rest.strip_prefix('.') {
+        let n = digits(frac);
+        if n == 0 {
+            return false;
+        }
+        rest = &frac[n..];
+    }
+    if let Some(exp) = rest.strip_prefix(['e', 'E']) {
+        let exp = exp.strip_prefix(['+', '-']).unwrap_or(exp);
+        let n = digits(exp);
+        if n == 0 {
+            return false;
+        }
+        rest = &exp[n..];
+    }
+    rest.is_empty()
 }
 
 /// Check if a string has invalid sign prefixes (++, +-, -+, --)
@@ -270,6 +878,38 @@ fn has_invalid_leading_zeros(v: &str) -> bool {
     false
 }
 
+/// The value of a real spelled as a decimal integer too large for an
+/// `i64` but not for an `i128` or `u128`, such as a JSON id, which a
+/// [`Value`] keeps exact
+///
+/// [`Value`]: crate::Value
+pub(crate) fn integer_real(v: &str) -> Option<Number> {
+    let digits = v.strip_prefix(['+', '-']).unwrap_or(v);
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    match v.parse::<i128>() {
+        Ok(i) => i64::try_from(i).is_err().then(|| Number::from_i128(i)),
+        Err(_) => v.parse::<u128>().ok().map(Number::from_u128),
+    }
+}
+
+/// The exact value of a `0x`, `0o` or `0b` integer that fits in an `i128`
+fn radix_integer(v: &str) -> Option<i128> {
+    let (negative, unsigned) = match v.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, v.strip_prefix('+').unwrap_or(v)),
+    };
+    let radix = match unsigned.get(..2)? {
+        "0x" => 16,
+        "0o" => 8,
+        "0b" => 2,
+        _ => return None,
+    };
+    let magnitude = i128::from_str_radix(&unsigned[2..], radix).ok()?;
+    Some(if negative { -magnitude } else { magnitude })
+}
+
 /// Convert string to float (including .inf, .nan).
 pub fn parse_f64(v: &str) -> Option<f64> {
     match v {
@@ -292,9 +932,8 @@ impl std::ops::Index<&str> for Yaml {
     type Output = Self;
     #[inline]
     fn index(&self, idx: &str) -> &Self {
-        let key = Self::String(idx.to_owned());
         match self.as_hash() {
-            Some(h) => h.get(&key).unwrap_or(&BAD_VALUE),
+            Some(h) => h.get_str(idx).unwrap_or(&BAD_VALUE),
             None => &BAD_VALUE,
         }
     }
@@ -315,3 +954,60 @@ impl std::ops::Index<usize> for Yaml {
         }
     }
 }
+
+/// Reject anything in `s` that is not a single flow node
+fn check_fragment(s: &str) -> Result<(), ScanError> {
+    let mut scanner = Scanner::over_str(s);
+    let mut depth = 0usize;
+    let mut complete = false;
+    loop {
+        let token = scanner.peek_token()?;
+        scanner.skip();
+        let problem = match token.1 {
+            TokenType::StreamStart(_) => None,
+            TokenType::StreamEnd | TokenType::NoToken => return Ok(()),
+            // An implicit key outside flow collections starts a block mapping
+            TokenType::Key | TokenType::Value if depth == 0 => {
+                Some("block collections are not allowed in a fragment, use flow style")
+            }
+            _ if complete => Some("unexpected content after the fragment"),
+            TokenType::DocumentStart
+            | TokenType::DocumentEnd
+            | TokenType::VersionDirective(..)
+            | TokenType::TagDirective(..)
+            | TokenType::Reserved(_) => {
+                Some("document markers and directives are not allowed in a fragment")
+            }
+            TokenType::BlockSequenceStart
+            | TokenType::BlockMappingStart
+            | TokenType::BlockEntry
+            | TokenType::BlockEnd => {
+                Some("block collections are not allowed in a fragment, use flow style")
+            }
+            TokenType::Scalar(TScalarStyle::Literal | TScalarStyle::Folded, ..) => {
+                Some("block scalars are not allowed in a fragment")
+            }
+            TokenType::FlowSequenceStart | TokenType::FlowMappingStart => {
+                depth += 1;
+                None
+            }
+            TokenType::FlowSequenceEnd | TokenType::FlowMappingEnd => {
+                depth = depth.saturating_sub(1);
+                complete = depth == 0;
+                None
+            }
+            TokenType::Scalar(..) | TokenType::Alias(_) => {
+                complete = depth == 0;
+                None
+            }
+            TokenType::Tag(..)
+            | TokenType::Anchor(_)
+            | TokenType::Key
+            | TokenType::Value
+            | TokenType::FlowEntry => None,
+        };
+        if let Some(problem) = problem {
+            return Err(ScanError::new(token.0, problem));
+        }
+    }
+}
@@ -32,9 +32,10 @@ folded: >
 "#;
     let docs = YamlLoader::load_from_str(yaml).unwrap();
     let result = docs[0]["folded"].as_str().unwrap();
-    // Folded style should preserve double line breaks but fold single ones
+    // A single line break folds to a space, and the break before a blank
+    // line is dropped so the blank line alone gives one break
     assert!(result.contains("Line 1 Line 2"));
-    assert!(result.contains("\n\nLine 4"));
+    assert!(result.contains("Line 2\nLine 4"));
 }
 
 /// Test folding in different contexts
@@ -3,7 +3,7 @@
 //! Tests directive handling and processing
 //! References: ../../../docs/ch06-structural-productions/6.8-directives.md
 
-use yyaml::YamlLoader;
+use yyaml::{Yaml, YamlLoader};
 
 /// Test YAML directive processing
 #[test]
@@ -22,8 +22,14 @@ fn test_tag_directive() {
 ---
 !shape Circle"#;
     let docs = YamlLoader::load_from_str(yaml).unwrap();
-    // Tag directive should be processed but content preserved
-    assert_eq!(docs[0].as_str().unwrap(), "Circle");
+    // The primary handle expands to the declared prefix
+    assert_eq!(
+        docs[0],
+        Yaml::Tagged(
+            "tag:example.com,2000:app/shape".to_string(),
+            Box::new(Yaml::String("Circle".to_string()))
+        )
+    );
 }
 
 /// Test multiple directives
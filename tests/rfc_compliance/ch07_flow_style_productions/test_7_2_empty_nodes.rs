@@ -39,4 +39,24 @@ fn test_empty_nodes_in_flow_mappings() {
     assert_eq!(docs[0]["key1"].as_str().unwrap(), "value1");
     assert!(docs[0]["key2"].is_null());
     assert_eq!(docs[0]["key3"].as_str().unwrap(), "value3");
-}
\ No newline at end of file
+}
+
+/// Test empty entries and trailing commas in flow collections
+#[test]
+fn test_empty_flow_entries_and_trailing_commas() {
+    let docs = YamlLoader::load_from_str("[, a, ,]").unwrap();
+    let seq = docs[0].as_vec().unwrap();
+    assert_eq!(seq.len(), 3);
+    assert!(seq[0].is_null());
+    assert_eq!(seq[1].as_str().unwrap(), "a");
+    assert!(seq[2].is_null());
+
+    let docs = YamlLoader::load_from_str("{a: , b: 1,}").unwrap();
+    assert!(docs[0]["a"].is_null());
+    assert_eq!(docs[0]["b"].as_i64().unwrap(), 1);
+    assert_eq!(docs[0].as_hash().unwrap().len(), 2);
+
+    let docs = YamlLoader::load_from_str("[a, [b,],]").unwrap();
+    assert_eq!(docs[0].as_vec().unwrap().len(), 2);
+    assert_eq!(docs[0][1].as_vec().unwrap().len(), 1);
+}
@@ -54,9 +54,6 @@ fn test_plain_flow_scalars() {
     let valid_cases = vec![
         "simple",
         "with spaces",
-        "123",
-        "true",
-        "null",
         "with:colon",
         "with#hash",
     ];
@@ -66,6 +63,11 @@ fn test_plain_flow_scalars() {
         assert_eq!(docs[0].as_str().unwrap(), case);
     }
     
+    // Plain scalars that the core schema resolves to other types
+    assert_eq!(YamlLoader::load_from_str("123").unwrap()[0].as_i64(), Some(123));
+    assert_eq!(YamlLoader::load_from_str("true").unwrap()[0].as_bool(), Some(true));
+    assert!(YamlLoader::load_from_str("null").unwrap()[0].is_null());
+    
     // Invalid in flow context - these should be parsed differently
     let flow_yaml = r#"[key: value]"#; // : followed by space in flow context
     let docs = YamlLoader::load_from_str(flow_yaml).unwrap();
@@ -45,4 +45,80 @@ fn test_flow_mappings() {
     let yaml = r#"{outer: {inner: value}}"#;
     let docs = YamlLoader::load_from_str(yaml).unwrap();
     assert_eq!(docs[0]["outer"]["inner"].as_str().unwrap(), "value");
-}
\ No newline at end of file
+}
+
+/// Test flow collections nested inside block collections
+#[test]
+fn test_flow_collections_in_block() {
+    let yaml = "key: [ {a: 1}, [2, 3] ]\nlist:\n  - [a, [b, {c: [d]}]]\n  - {x: {y: [1]}}\nz: 1\n";
+    let docs = YamlLoader::load_from_str(yaml).unwrap();
+    assert_eq!(docs[0]["key"][0]["a"].as_i64().unwrap(), 1);
+    assert_eq!(docs[0]["key"][1][1].as_i64().unwrap(), 3);
+    assert_eq!(docs[0]["list"][0][1][1]["c"][0].as_str().unwrap(), "d");
+    assert_eq!(docs[0]["list"][1]["x"]["y"][0].as_i64().unwrap(), 1);
+    assert_eq!(docs[0]["z"].as_i64().unwrap(), 1);
+
+    // A closed flow collection leaves later block sequences unaffected
+    for yaml in ["- [0]\n- - 0\n", "- !t [0]\n- - 0\n", "- {a: 0}\n- - 0\n"] {
+        let docs = YamlLoader::load_from_str(yaml).unwrap();
+        assert_eq!(docs[0][1][0].as_i64(), Some(0), "{yaml:?}");
+    }
+}
+
+/// Test example 7.13 flow sequences and example 7.15 flow mappings
+#[test]
+fn test_flow_collection_examples() {
+    let yaml = "- [ one, two, ]\n- [three ,four]\n";
+    let docs = YamlLoader::load_from_str(yaml).unwrap();
+    assert_eq!(docs[0][0].as_vec().unwrap().len(), 2);
+    assert_eq!(docs[0][1][1].as_str().unwrap(), "four");
+
+    let yaml = "- { one : two , three: four , }\n- {five: six,seven : eight}\n";
+    let docs = YamlLoader::load_from_str(yaml).unwrap();
+    assert_eq!(docs[0][0]["three"].as_str().unwrap(), "four");
+    assert_eq!(docs[0][1]["seven"].as_str().unwrap(), "eight");
+}
+
+/// Test example 7.21 single pair implicit entries, including collection keys
+#[test]
+fn test_single_pair_implicit_entries() {
+    let yaml = "- [ YAML : separate ]\n- [ : empty key entry ]\n- [ {JSON: like}:adjacent ]\n";
+    let docs = YamlLoader::load_from_str(yaml).unwrap();
+    assert_eq!(docs[0][0][0]["YAML"].as_str().unwrap(), "separate");
+    let empty_key = docs[0][1][0].as_hash().unwrap().get(&yyaml::Yaml::Null);
+    assert_eq!(empty_key.unwrap().as_str().unwrap(), "empty key entry");
+    let pair = docs[0][2][0].as_hash().unwrap();
+    let (key, value) = pair.iter().next().unwrap();
+    assert_eq!(key["JSON"].as_str().unwrap(), "like");
+    assert_eq!(value.as_str().unwrap(), "adjacent");
+
+    let yaml = "{[a]: b, {c: d}: e}";
+    let docs = YamlLoader::load_from_str(yaml).unwrap();
+    let map = docs[0].as_hash().unwrap();
+    let keys: Vec<_> = map.iter().map(|(key, _)| key).collect();
+    assert_eq!(keys[0][0].as_str().unwrap(), "a");
+    assert_eq!(keys[1]["c"].as_str().unwrap(), "d");
+}
+
+/// Test example 7.14 and flow collections spanning several indented lines
+#[test]
+fn test_multi_line_flow_collections() {
+    let yaml = "[\n\"double\n quoted\", 'single\n  quoted',\nplain\n text, [ nested ],\nsingle: pair,\n]\n";
+    let docs = YamlLoader::load_from_str(yaml).unwrap();
+    assert_eq!(docs[0][0].as_str().unwrap(), "double quoted");
+    assert_eq!(docs[0][1].as_str().unwrap(), "single quoted");
+    assert_eq!(docs[0][2].as_str().unwrap(), "plain text");
+    assert_eq!(docs[0][3][0].as_str().unwrap(), "nested");
+    assert_eq!(docs[0][4]["single"].as_str().unwrap(), "pair");
+
+    let yaml = "{a: multi\n  line\n\n  folded, b: c\n  # comment\n}\n";
+    let docs = YamlLoader::load_from_str(yaml).unwrap();
+    assert_eq!(docs[0]["a"].as_str().unwrap(), "multi line\nfolded");
+    assert_eq!(docs[0]["b"].as_str().unwrap(), "c");
+
+    let yaml = "outer:\n  inner: {\n    a: [1,\n      2],\n    b: x\n  }\n  next: y\n";
+    let docs = YamlLoader::load_from_str(yaml).unwrap();
+    assert_eq!(docs[0]["outer"]["inner"]["a"][1].as_i64().unwrap(), 2);
+    assert_eq!(docs[0]["outer"]["inner"]["b"].as_str().unwrap(), "x");
+    assert_eq!(docs[0]["outer"]["next"].as_str().unwrap(), "y");
+}
@@ -36,8 +36,10 @@ copy: *anchor
     assert_eq!(docs[0]["reference"]["key"].as_str().unwrap(), "value");
     assert_eq!(docs[0]["copy"]["key"].as_str().unwrap(), "value");
     
-    // Flow node with explicit tag
+    // Flow node with explicit tag; a tag does not turn a collection into
+    // a scalar, so the sequence is kept under it
     let yaml = r#"tagged: !!str [1, 2, 3]"#;
     let docs = YamlLoader::load_from_str(yaml).unwrap();
-    assert_eq!(docs[0]["tagged"].as_str().unwrap(), "[1, 2, 3]");
+    assert_eq!(docs[0]["tagged"].tag(), Some("tag:yaml.org,2002:str"));
+    assert_eq!(docs[0]["tagged"].clone().untag().as_vec().map(|items| items.len()), Some(3));
 }
\ No newline at end of file
@@ -41,4 +41,24 @@ explicit: |2
     let docs = YamlLoader::load_from_str(yaml).unwrap();
     let content = docs[0]["explicit"].as_str().unwrap();
     assert_eq!(content, "  Content\n    More content\n");
-}
\ No newline at end of file
+}
+/// Test that an indentation indicator counts from the parent node's indentation
+#[test]
+fn test_indentation_indicator_relative_to_parent() {
+    let yaml = "outer:\n  inner: |2\n      Content\n    Base\n  after: 1\n";
+    let docs = YamlLoader::load_from_str(yaml).unwrap();
+    assert_eq!(docs[0]["outer"]["inner"].as_str().unwrap(), "  Content\nBase\n");
+    assert_eq!(docs[0]["outer"]["after"].as_i64(), Some(1));
+}
+
+/// Test that a first line no more indented than the parent leaves the scalar empty
+#[test]
+fn test_empty_block_scalar_before_sibling() {
+    let yaml = "list:\n  - |\n  - >-\n  - x\n";
+    let docs = YamlLoader::load_from_str(yaml).unwrap();
+    let list = docs[0]["list"].as_vec().unwrap();
+    assert_eq!(list.len(), 3);
+    assert_eq!(list[0].as_str(), Some(""));
+    assert_eq!(list[1].as_str(), Some(""));
+    assert_eq!(list[2].as_str(), Some("x"));
+}
@@ -34,8 +34,8 @@ folded: >
     let docs = YamlLoader::load_from_str(yaml).unwrap();
     let content = docs[0]["folded"].as_str().unwrap();
     
-    // Blank lines should be preserved
-    assert_eq!(content, "Line 1 Line 2\n\nLine 4 after blank\n");
+    // Each blank line keeps one line break (spec example 8.10)
+    assert_eq!(content, "Line 1 Line 2\nLine 4 after blank\n");
 }
 
 /// Test folded style with chomping indicators
@@ -75,4 +75,14 @@ folded: >
     
     // More indented lines should be preserved
     assert!(content.contains("  More indented line"));
-}
\ No newline at end of file
+}
+/// Test folding around blank and more-indented lines (spec example 8.10)
+#[test]
+fn test_folded_lines_example_8_10() {
+    let yaml = "folded: >\n\n  folded\n  line\n\n  next\n  line\n    * bullet\n\n    * list\n    * lines\n\n  last\n  line\n\n# Comment\n";
+    let docs = YamlLoader::load_from_str(yaml).unwrap();
+    assert_eq!(
+        docs[0]["folded"].as_str().unwrap(),
+        "\nfolded line\nnext line\n  * bullet\n\n  * list\n  * lines\n\nlast line\n"
+    );
+}
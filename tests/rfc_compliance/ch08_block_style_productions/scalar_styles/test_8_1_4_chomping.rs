@@ -77,4 +77,15 @@ folded: >+
 "#;
     let docs = YamlLoader::load_from_str(yaml).unwrap();
     assert_eq!(docs[0]["folded"].as_str().unwrap(), "Content line\n\n\n\n");
-}
\ No newline at end of file
+}
+/// Test chomping of trailing blank lines in scalars nested under a sequence
+#[test]
+fn test_chomping_nested_scalars() {
+    let yaml = "- |-\n  strip\n\n- |\n  clip\n\n\n- |+\n  keep\n\n- last\n";
+    let docs = YamlLoader::load_from_str(yaml).unwrap();
+    let items = docs[0].as_vec().unwrap();
+    assert_eq!(items[0].as_str(), Some("strip"));
+    assert_eq!(items[1].as_str(), Some("clip\n"));
+    assert_eq!(items[2].as_str(), Some("keep\n\n"));
+    assert_eq!(items[3].as_str(), Some("last"));
+}
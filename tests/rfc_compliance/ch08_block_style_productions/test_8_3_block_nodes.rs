@@ -46,11 +46,13 @@ copy: *anchor
     assert_eq!(docs[0]["reference"]["key"].as_str().unwrap(), "value");
     assert_eq!(docs[0]["copy"]["key"].as_str().unwrap(), "value");
     
-    // Block node with explicit tag
+    // Block node with explicit tag; a tag does not turn a collection into
+    // a scalar, so the sequence is kept under it
     let yaml = r#"tagged: !!str
 - item1
 - item2
 "#;
     let docs = YamlLoader::load_from_str(yaml).unwrap();
-    assert!(docs[0]["tagged"].as_str().unwrap().contains("item1"));
+    assert_eq!(docs[0]["tagged"].tag(), Some("tag:yaml.org,2002:str"));
+    assert_eq!(docs[0]["tagged"].clone().untag()[0].as_str(), Some("item1"));
 }
\ No newline at end of file
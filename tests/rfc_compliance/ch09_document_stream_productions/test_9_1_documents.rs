@@ -47,4 +47,87 @@ second: document"#;
     assert_eq!(docs.len(), 2);
     assert_eq!(docs[0]["first"].as_str().unwrap(), "document");
     assert_eq!(docs[1]["second"].as_str().unwrap(), "document");
-}
\ No newline at end of file
+}
+
+/// Test example 9.3 bare documents separated by document end markers
+#[test]
+fn test_bare_documents_after_document_end() {
+    let yaml = "Bare\ndocument\n...\n# No document\n...\n|\n%!PS-Adobe-2.0 # Not the first line\n";
+    let docs = YamlLoader::load_from_str(yaml).unwrap();
+    assert_eq!(docs.len(), 2);
+    assert_eq!(docs[0].as_str().unwrap(), "Bare document");
+    assert!(docs[1].as_str().unwrap().starts_with("%!PS-Adobe-2.0 # Not the first line"));
+
+    let yaml = "a\n...\n...\n---\nb\n";
+    let docs = YamlLoader::load_from_str(yaml).unwrap();
+    assert_eq!(docs.len(), 2);
+    assert_eq!(docs[1].as_str().unwrap(), "b");
+}
+
+/// Test example 9.4 explicit documents, including one with no content
+#[test]
+fn test_explicit_documents_without_content() {
+    let yaml = "---\n{ matches\n% : 20 }\n...\n---\n# Empty\n...\n";
+    let docs = YamlLoader::load_from_str(yaml).unwrap();
+    assert_eq!(docs.len(), 2);
+    assert_eq!(docs[0]["matches %"].as_i64().unwrap(), 20);
+    assert!(docs[1].is_null());
+
+    let docs = YamlLoader::load_from_str("---\na\n---\n").unwrap();
+    assert_eq!(docs.len(), 2);
+    assert!(docs[1].is_null());
+}
+
+/// Test example 9.5 directives after a document end marker
+#[test]
+fn test_directives_after_document_end() {
+    let yaml = "%YAML 1.2\n--- |\n%!PS-Adobe-2.0\n...\n%YAML 1.2\n---\n# Empty\n...\n";
+    let docs = YamlLoader::load_from_str(yaml).unwrap();
+    assert_eq!(docs.len(), 2);
+    assert!(docs[0].as_str().unwrap().starts_with("%!PS-Adobe-2.0"));
+    assert!(docs[1].is_null());
+
+    let yaml = "doc1\n...\n%TAG ! tag:example.com,2000:\n--- !foo doc2\n";
+    let docs = YamlLoader::load_from_str(yaml).unwrap();
+    assert_eq!(docs.len(), 2);
+    assert_eq!(docs[0].as_str().unwrap(), "doc1");
+}
+
+/// Test that directives and stray content cannot follow a document without `...`
+#[test]
+fn test_invalid_document_boundaries() {
+    let err = YamlLoader::load_from_str("doc1\n%YAML 1.2\n---\ndoc2\n").unwrap_err();
+    assert!(err.info.contains("document end marker"), "{err}");
+
+    let err = YamlLoader::load_from_str("%YAML 1.2\n...\n").unwrap_err();
+    assert!(err.info.contains("document start marker"), "{err}");
+
+    let err = YamlLoader::load_from_str("%YAML 1.2\n%YAML 1.2\n---\na\n").unwrap_err();
+    assert!(err.info.contains("duplicate"), "{err}");
+
+    let err = YamlLoader::load_from_str("%FUTURE 1.0\na\n").unwrap_err();
+    assert!(err.info.contains("document start marker"), "{err}");
+
+    let err = YamlLoader::load_from_str("a\n%FUTURE\n---\nb\n").unwrap_err();
+    assert!(err.info.contains("document end marker"), "{err}");
+
+    assert!(YamlLoader::load_from_str("[a]\nb\n").is_err());
+    assert!(YamlLoader::load_from_str("a: 1\nb\nc: 2\n").is_err());
+}
+
+/// Test that `---` and `...` are markers only at the start of a line
+#[test]
+fn test_document_markers_at_line_start() {
+    let docs = YamlLoader::load_from_str("- a ...\n- --- b\n- ...\n").unwrap();
+    assert_eq!(docs.len(), 1);
+    assert_eq!(docs[0][0].as_str().unwrap(), "a ...");
+    assert_eq!(docs[0][1].as_str().unwrap(), "--- b");
+    assert_eq!(docs[0][2].as_str().unwrap(), "...");
+
+    let docs = YamlLoader::load_from_str("k: ...\n").unwrap();
+    assert_eq!(docs[0]["k"].as_str().unwrap(), "...");
+
+    let err = YamlLoader::load_from_str("a\n... b\n").unwrap_err();
+    assert!(err.info.contains("after document end marker"), "{err}");
+    assert_eq!(YamlLoader::load_from_str("a\n... # end\n").unwrap().len(), 1);
+}
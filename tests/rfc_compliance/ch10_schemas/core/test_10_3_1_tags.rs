@@ -5,7 +5,7 @@
 
 use yyaml::YamlLoader;
 
-/// Test Core schema extended boolean recognition
+/// Test that YAML 1.1 booleans stay strings in the Core schema
 #[test]
 fn test_core_extended_boolean_tags() {
     let yaml = r#"
@@ -17,13 +17,13 @@ y_value: y
 n_value: n
 "#;
     let docs = YamlLoader::load_from_str(yaml).unwrap();
-    // Core schema extends JSON schema with additional boolean values
-    assert_eq!(docs[0]["yes_value"].as_bool().unwrap(), true);
-    assert_eq!(docs[0]["no_value"].as_bool().unwrap(), false);
-    assert_eq!(docs[0]["on_value"].as_bool().unwrap(), true);
-    assert_eq!(docs[0]["off_value"].as_bool().unwrap(), false);
-    assert_eq!(docs[0]["y_value"].as_bool().unwrap(), true);
-    assert_eq!(docs[0]["n_value"].as_bool().unwrap(), false);
+    // Core schema booleans are only true and false in three casings
+    assert_eq!(docs[0]["yes_value"].as_str(), Some("yes"));
+    assert_eq!(docs[0]["no_value"].as_str(), Some("no"));
+    assert_eq!(docs[0]["on_value"].as_str(), Some("on"));
+    assert_eq!(docs[0]["off_value"].as_str(), Some("off"));
+    assert_eq!(docs[0]["y_value"].as_str(), Some("y"));
+    assert_eq!(docs[0]["n_value"].as_str(), Some("n"));
 }
 
 /// Test Core schema extended integer formats
@@ -39,7 +39,8 @@ with_underscores: 1_000_000
     assert_eq!(docs[0]["octal"].as_i64().unwrap(), 63);
     assert_eq!(docs[0]["hexadecimal"].as_i64().unwrap(), 26);
     assert_eq!(docs[0]["binary"].as_i64().unwrap(), 10);
-    assert_eq!(docs[0]["with_underscores"].as_i64().unwrap(), 1000000);
+    // Digit separators are YAML 1.1 only
+    assert_eq!(docs[0]["with_underscores"].as_str(), Some("1_000_000"));
 }
 
 /// Test Core schema special float values
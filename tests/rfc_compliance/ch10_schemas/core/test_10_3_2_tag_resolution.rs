@@ -29,26 +29,18 @@ fn test_core_null_resolution() {
 /// Test Core schema boolean resolution
 #[test]
 fn test_core_boolean_resolution() {
-    let true_values = vec![
-        "true", "True", "TRUE",
-        "yes", "Yes", "YES", 
-        "on", "On", "ON",
-        "y", "Y"
-    ];
+    let true_values = vec!["true", "True", "TRUE"];
     
-    let false_values = vec![
-        "false", "False", "FALSE",
-        "no", "No", "NO",
-        "off", "Off", "OFF", 
-        "n", "N"
-    ];
+    let false_values = vec!["false", "False", "FALSE"];
+    
+    // YAML 1.1 booleans are plain strings under the 1.2 core schema
+    let strings = vec!["yes", "No", "ON", "off", "y", "N"];
     
     for value in true_values {
         let yaml = format!("key: {}", value);
         let docs = YamlLoader::load_from_str(&yaml).unwrap();
-        assert_eq!(
-            docs[0]["key"].as_bool().unwrap(), 
-            true,
+        assert!(
+            docs[0]["key"].as_bool().unwrap(),
             "Core schema should resolve '{}' to true", 
             value
         );
@@ -57,13 +49,18 @@ fn test_core_boolean_resolution() {
     for value in false_values {
         let yaml = format!("key: {}", value);
         let docs = YamlLoader::load_from_str(&yaml).unwrap();
-        assert_eq!(
-            docs[0]["key"].as_bool().unwrap(), 
-            false,
+        assert!(
+            !docs[0]["key"].as_bool().unwrap(),
             "Core schema should resolve '{}' to false", 
             value
         );
     }
+    
+    for value in strings {
+        let yaml = format!("key: {}", value);
+        let docs = YamlLoader::load_from_str(&yaml).unwrap();
+        assert_eq!(docs[0]["key"].as_str(), Some(value));
+    }
 }/// Test Core schema integer resolution  
 #[test]
 fn test_core_integer_resolution() {
@@ -74,7 +71,6 @@ fn test_core_integer_resolution() {
         ("0x1A", 26i64),        // Hexadecimal
         ("0o77", 63i64),        // Octal  
         ("0b1010", 10i64),      // Binary
-        ("1_000_000", 1000000i64), // With underscores
     ];
     
     for (input, expected) in test_cases {
@@ -88,10 +84,15 @@ fn test_core_integer_resolution() {
             expected
         );
     }
+    
+    // Digit separators are YAML 1.1 only
+    let docs = YamlLoader::load_from_str("key: 1_000_000").unwrap();
+    assert_eq!(docs[0]["key"].as_str(), Some("1_000_000"));
 }
 
 /// Test Core schema float resolution
 #[test]
+#[allow(clippy::approx_constant)]
 fn test_core_float_resolution() {
     // Regular floats
     let yaml = "key: 3.14159";
@@ -110,14 +111,14 @@ fn test_core_float_resolution() {
         (".nan", f64::NAN),
     ];
     
-    for (input, _expected) in special_cases {
+    for (input, expected) in special_cases {
         let yaml = format!("key: {}", input);
         let docs = YamlLoader::load_from_str(&yaml).unwrap();
         // Note: NaN comparison requires special handling
         if input == ".nan" {
-            assert!(docs[0]["key"].as_str().unwrap().contains("NaN"));
+            assert!(docs[0]["key"].as_f64().unwrap().is_nan());
         } else {
-            assert!(docs[0]["key"].as_str().is_some());
+            assert_eq!(docs[0]["key"].as_f64(), Some(expected));
         }
     }
 }
@@ -128,15 +129,18 @@ fn test_explicit_tag_override() {
     // Force string interpretation of number
     let yaml = r#"key: !!str 123"#;
     let docs = YamlLoader::load_from_str(yaml).unwrap();
-    assert_eq!(docs[0]["key"].as_str().unwrap(), "123");
+    assert_eq!(docs[0]["key"].clone().untag().as_str(), Some("123"));
     
     // Force integer interpretation of string  
     let yaml = r#"key: !!int "456""#;
     let docs = YamlLoader::load_from_str(yaml).unwrap();
-    assert_eq!(docs[0]["key"].as_i64().unwrap(), 456);
+    assert_eq!(docs[0]["key"].clone().untag().as_i64(), Some(456));
     
-    // Force boolean interpretation
+    // Force boolean interpretation; `yes` is not a YAML 1.2 boolean
+    let yaml = r#"key: !!bool "true""#;
+    let docs = YamlLoader::load_from_str(yaml).unwrap();
+    assert_eq!(docs[0]["key"].clone().untag().as_bool(), Some(true));
     let yaml = r#"key: !!bool "yes""#;
     let docs = YamlLoader::load_from_str(yaml).unwrap();
-    assert_eq!(docs[0]["key"].as_bool().unwrap(), true);
+    assert!(docs[0]["key"].invalid_reason().is_some());
 }
\ No newline at end of file
@@ -55,7 +55,7 @@ sequence: !!seq [1, 2, 3]
 mapping: !!map { key: value }
 "#;
     let docs = YamlLoader::load_from_str(yaml).unwrap();
-    assert_eq!(docs[0]["string"].as_str().unwrap(), "123");
-    assert!(docs[0]["sequence"].as_vec().is_some());
-    assert!(docs[0]["mapping"].as_hash().is_some());
+    assert_eq!(docs[0]["string"].clone().untag().as_str(), Some("123"));
+    assert!(docs[0]["sequence"].clone().untag().as_vec().is_some());
+    assert!(docs[0]["mapping"].clone().untag().as_hash().is_some());
 }
\ No newline at end of file
@@ -37,9 +37,9 @@ explicit_seq: !!seq [a, b, c]
 explicit_map: !!map { key: value }
 "#;
     let docs = YamlLoader::load_from_str(yaml).unwrap();
-    assert_eq!(docs[0]["explicit_string"].as_str().unwrap(), "123");
-    assert!(docs[0]["explicit_seq"].as_vec().is_some());
-    assert!(docs[0]["explicit_map"].as_hash().is_some());
+    assert_eq!(docs[0]["explicit_string"].clone().untag().as_str(), Some("123"));
+    assert!(docs[0]["explicit_seq"].clone().untag().as_vec().is_some());
+    assert!(docs[0]["explicit_map"].clone().untag().as_hash().is_some());
 }
 
 /// Test Failsafe schema collection resolution
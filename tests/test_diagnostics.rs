@@ -0,0 +1,99 @@
+use yyaml::Severity;
+use yyaml::diagnostics::lint_anchors;
+
+#[test]
+fn test_unused_and_shadowed_anchors() {
+    let input = "base: &base {a: 1}\nother: &dead 2\nuse: *base\nagain: &base 3\n";
+    let diagnostics = lint_anchors(input).unwrap();
+    let found: Vec<_> = diagnostics
+        .iter()
+        .map(|d| (d.code, d.mark.line(), d.severity))
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            ("unused-anchor", 2, Severity::Warning),
+            ("shadowed-anchor", 4, Severity::Warning),
+            ("unused-anchor", 4, Severity::Warning),
+        ]
+    );
+    assert_eq!(diagnostics[1].related.map(|m| m.line()), Some(1));
+    assert_eq!(diagnostics[0].to_string(), "warning[unused-anchor]: anchor 'dead' is never aliased at line 2 col 8");
+}
+
+#[test]
+fn test_alias_across_documents() {
+    let input = "---\nshared: &cfg {x: 1}\nlocal: *cfg\n---\nagain: *cfg\nmissing: *nope\n";
+    let diagnostics = lint_anchors(input).unwrap();
+    let found: Vec<_> = diagnostics
+        .iter()
+        .map(|d| (d.code, d.mark.line(), d.severity))
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            ("cross-document-alias", 5, Severity::Error),
+            ("undefined-alias", 6, Severity::Error),
+        ]
+    );
+}
+
+#[test]
+fn test_clean_anchors() {
+    let input = "a: &x 1\nb: *x\n";
+    assert!(lint_anchors(input).unwrap().is_empty());
+}
+
+#[test]
+fn test_flow_delimiters_recover() {
+    let found = |input| {
+        yyaml::diagnostics::check_flow_delimiters(input)
+            .iter()
+            .map(|d| (d.code, d.mark.line(), d.mark.col()))
+            .collect::<Vec<_>>()
+    };
+    assert!(found("a: [1, {b: 2}]\n").is_empty());
+    assert_eq!(
+        found("a: [1, 2\n---\nb: {c: [3}\n]\n"),
+        vec![
+            ("unclosed-flow", 1, 3),
+            ("unclosed-flow", 3, 7),
+            ("unmatched-flow-end", 4, 0),
+        ]
+    );
+    assert_eq!(
+        found("a: [1, 2]]\nb: {c: [3}}\n"),
+        vec![
+            ("unmatched-flow-end", 1, 9),
+            ("unclosed-flow", 2, 7),
+            ("unmatched-flow-end", 2, 10),
+        ]
+    );
+}
+
+#[test]
+fn test_tag_mismatches() {
+    let found = |input| {
+        yyaml::diagnostics::check_tag_resolution(input)
+            .unwrap()
+            .iter()
+            .map(|d| (d.code, d.message.clone(), d.mark.line(), d.related.map(|m| m.col())))
+            .collect::<Vec<_>>()
+    };
+    assert!(found("a: !!int 0x1f\nb: !!float 3\nc: !!null\nd: !local text\n").is_empty());
+    assert_eq!(
+        found("- !!bool maybe\n- !!null x\n- &n !!int 9999999999999999999999\n"),
+        vec![
+            ("tag-mismatch", "`maybe` is not a valid !!bool".to_string(), 1, Some(2)),
+            ("tag-mismatch", "`x` is not a valid !!null".to_string(), 2, Some(2)),
+        ]
+    );
+    // `!!` no longer names the core schema once a directive moves it
+    assert!(found("%TAG !! tag:example.com,2000:\n---\na: !!int ten\n").is_empty());
+    // ... but only for its own document, even when the next opens with a
+    // bare `---` and no `...` in between
+    assert_eq!(
+        found("%TAG !! tag:example.com,2000:\n---\na: !!int ten\n---\nb: !!int ten\n"),
+        vec![("tag-mismatch", "`ten` is not a valid !!int".to_string(), 5, Some(3))]
+    );
+}
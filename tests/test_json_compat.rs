@@ -0,0 +1,55 @@
+use yyaml::{DeserializerConfig, LoaderOptions, SchemaMode, YamlLoader};
+
+/// Inputs that are valid JSON but easy to get wrong as YAML
+const CORPUS: &[&str] = &[
+    r#"{"a": 1, "b": [true, false, null]}"#,
+    r#"[1e5, 1E+2, -2.5e-3, 0.5, -0, 0, -1, 9223372036854775807]"#,
+    r#"[9223372036854775808, 18446744073709551615, {"id": 12345678901234567890}]"#,
+    r#"["é", "☺", "😀", "\"quoted\"", "back\\slash", "\/"]"#,
+    r#"["\b\f\n\r\t", "", " padded "]"#,
+    r#"["\u00e9", "\u263A", "\ud83d\ude00", "\u0000"]"#,
+    "{\t\"a\":\t1 }",
+    r#"[[], {}, [[]], [{}], {"a": {}}, {"a": []}]"#,
+    r#"{"dup": 1, "dup": 2}"#,
+    r#"{"":"empty key","a":{"b":{"c":[1,[2,[3]]]}}}"#,
+    r#"["yes", "no", "on", "off", "~", "0x1f", ".inf"]"#,
+    r#"{"key:with:colons": "value: with, punctuation # not a comment"}"#,
+    r#""top-level string""#,
+    "42",
+    "-1.5e10",
+    "true",
+    "null",
+];
+
+fn assert_same_as_json(input: &str) {
+    let config = DeserializerConfig::new().schema(SchemaMode::Json);
+    let ours: serde_json::Value = yyaml::from_str_with_config(input, &config)
+        .unwrap_or_else(|e| panic!("{input}: {e}"));
+    let theirs: serde_json::Value = serde_json::from_str(input).unwrap();
+    assert_eq!(ours, theirs, "{input}");
+}
+
+#[test]
+fn test_json_corpus_matches_serde_json() {
+    for input in CORPUS {
+        assert_same_as_json(input);
+    }
+}
+
+#[test]
+fn test_json_schema_plain_scalars() {
+    let options = LoaderOptions::new().schema(SchemaMode::Json);
+    let docs = YamlLoader::load_with_options("[yes, on, ~, Null, 0x1f, .inf, 1e3, 07]", &options)
+        .unwrap();
+    let items = docs[0].as_vec().unwrap();
+    for (item, text) in items.iter().zip(["yes", "on", "~", "Null", "0x1f", ".inf"]) {
+        assert_eq!(item.as_str(), Some(text));
+    }
+    assert_eq!(items[6].as_f64(), Some(1000.0));
+    assert_eq!(items[7].as_str(), Some("07"));
+
+    // The default core schema still types the YAML spellings
+    let docs = YamlLoader::load_from_str("[~, 0x1f]").unwrap();
+    assert!(docs[0][0].is_null());
+    assert_eq!(docs[0][1].as_i64(), Some(31));
+}
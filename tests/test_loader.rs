@@ -0,0 +1,1199 @@
+use std::sync::{Arc, Mutex};
+use yyaml::{BadValueKind, CancellationToken, Error, Event, EventReceiver, LoaderOptions, PushParser, YamlLoader};
+use yyaml::parser::ScalarHistogram;
+
+const STREAM: &str = "---\nname: first\n---\nname: second\n";
+
+#[test]
+fn test_progress_reports_bytes_and_documents() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&seen);
+    let options = LoaderOptions::new().progress(move |bytes, docs| {
+        sink.lock().unwrap().push((bytes, docs));
+    });
+
+    let docs = YamlLoader::load_with_options(STREAM, &options).unwrap();
+    assert_eq!(docs.len(), 2);
+    assert_eq!(docs[1]["name"].as_str(), Some("second"));
+
+    let seen = seen.lock().unwrap();
+    assert!(!seen.is_empty());
+    assert!(seen.windows(2).all(|w| w[0].0 <= w[1].0 && w[0].1 <= w[1].1));
+    let &(bytes, docs_emitted) = seen.last().unwrap();
+    assert_eq!(docs_emitted, 2);
+    assert!(bytes <= STREAM.len());
+}
+
+#[test]
+fn test_progress_on_simple_input() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&seen);
+    let options = LoaderOptions::new().progress(move |bytes, docs| {
+        sink.lock().unwrap().push((bytes, docs));
+    });
+
+    // Input the fast path would take is still reported token by token
+    YamlLoader::load_with_options("key: value", &options).unwrap();
+    let seen = seen.lock().unwrap();
+    assert!(seen.len() > 1, "{seen:?}");
+    assert_eq!(seen.last(), Some(&(10, 1)));
+}
+
+#[test]
+fn test_cancel_simple_input() {
+    let token = CancellationToken::new();
+    let trigger = token.clone();
+    let options = LoaderOptions::new()
+        .cancellation(token)
+        .progress(move |bytes, _| {
+            if bytes > 0 {
+                trigger.cancel();
+            }
+        });
+
+    let err = YamlLoader::load_with_options("key: value", &options).unwrap_err();
+    assert!(matches!(err, Error::Cancelled));
+}
+
+#[test]
+fn test_cancelled_before_start() {
+    let token = CancellationToken::new();
+    token.cancel();
+    let options = LoaderOptions::new().cancellation(token);
+    let err = YamlLoader::load_with_options("key: value", &options).unwrap_err();
+    assert!(matches!(err, Error::Cancelled));
+}
+
+#[test]
+fn test_cancel_from_progress_callback() {
+    let token = CancellationToken::new();
+    let trigger = token.clone();
+    let options = LoaderOptions::new()
+        .cancellation(token)
+        .progress(move |_, docs| {
+            if docs >= 1 {
+                trigger.cancel();
+            }
+        });
+
+    let err = YamlLoader::load_with_options(STREAM, &options).unwrap_err();
+    assert!(matches!(err, Error::Cancelled));
+}
+
+#[test]
+fn test_strict_tags() {
+    let input = "port: !!int eighty\n";
+    let docs = YamlLoader::load_from_str(input).unwrap();
+    let invalid = docs[0]["port"].invalid_reason().expect("an invalid node");
+    assert_eq!(
+        invalid.kind,
+        BadValueKind::TagMismatch { tag: "int".into(), value: "eighty".into() }
+    );
+    let mark = invalid.mark.expect("the scalar's position");
+    assert_eq!((mark.line(), mark.col()), (1, 12));
+    let options = LoaderOptions::new().strict_tags(true);
+    match YamlLoader::load_with_options(input, &options) {
+        Err(Error::Scan(e)) => {
+            assert_eq!((e.mark.line(), e.mark.col()), (1, 12));
+            assert_eq!(e.info, "`eighty` is not a valid !!int");
+        }
+        other => panic!("expected a tag mismatch error, got {other:?}"),
+    }
+    let docs = YamlLoader::load_with_options("port: !!int 80\n", &options).unwrap();
+    assert!(matches!(&docs[0]["port"], yyaml::Yaml::Tagged(_, v) if v.as_i64() == Some(80)));
+}
+
+#[test]
+fn test_invalid_nodes_carry_their_marks() {
+    use yyaml::{InvalidNode, Marker};
+
+    let input = "---\nport: !!int eighty\n---\n- !!bool maybe\n- !!int 8\n";
+    let docs = YamlLoader::load_from_str(input).unwrap();
+    let invalid = [&docs[0]["port"], &docs[1][0], &docs[1][1]].map(|node| node.invalid_reason());
+    let mismatch = |tag: &str, value: &str, at: usize| InvalidNode {
+        kind: BadValueKind::TagMismatch { tag: tag.into(), value: value.into() },
+        mark: Some(Marker::locate(input, at)),
+    };
+    let eighty = input.find("eighty").unwrap();
+    let maybe = input.find("maybe").unwrap();
+    assert_eq!(
+        invalid,
+        [Some(&mismatch("int", "eighty", eighty)), Some(&mismatch("bool", "maybe", maybe)), None]
+    );
+    assert!(docs[1][0].is_badvalue());
+}
+
+#[test]
+fn test_core_tags_type_their_scalar() {
+    use yyaml::Yaml;
+
+    let input = "s: !!str 123\ni: !!int \"456\"\nf: !!float 1\nn: !!null ''\n";
+    let docs = YamlLoader::load_from_str(input).unwrap();
+    let untagged = |key: &str| docs[0][key].clone().untag();
+    assert_eq!(untagged("s"), Yaml::String("123".into()));
+    assert_eq!(untagged("i"), Yaml::Integer(456));
+    assert_eq!(untagged("f"), Yaml::Real("1".into()));
+    assert_eq!(untagged("n"), Yaml::Null);
+}
+
+#[test]
+fn test_load_instrumented_metrics() {
+    let (docs, metrics) = YamlLoader::load_from_str_instrumented(STREAM).unwrap();
+    assert_eq!(docs.len(), 2);
+    assert!(!metrics.fast_path);
+    assert_eq!(metrics.bytes, STREAM.len());
+    assert_eq!(metrics.documents.len(), 2);
+    // each document: start/end, mapping start/end, one key and one value
+    assert!(metrics.documents.iter().all(|d| d.events_emitted == 6));
+    assert_eq!(metrics.events_emitted, 12);
+    assert!(metrics.documents.iter().all(|d| d.tokens_scanned > 0));
+    assert!(metrics.tokens_scanned >= metrics.documents.iter().map(|d| d.tokens_scanned).sum());
+    assert!(metrics.estimated_allocations > 0);
+}
+
+#[test]
+fn test_load_instrumented_document_stats() {
+    let input = "# header\na: 1\nb: [x, 2.5, ~, true]\n---\n- &n 7\n- *n\n";
+    let (_, metrics) = YamlLoader::load_from_str_instrumented(input).unwrap();
+    let [first, second] = &metrics.documents[..] else {
+        panic!("{metrics:?}");
+    };
+    assert_eq!(first.span, 0..input.find("---").unwrap());
+    assert_eq!(second.span, first.span.end..input.len());
+    assert_eq!((first.nodes, first.mappings, first.sequences), (9, 1, 1));
+    assert_eq!(
+        first.scalars,
+        ScalarHistogram { nulls: 1, booleans: 1, integers: 1, floats: 1, strings: 3 }
+    );
+    // the alias is counted as the node it expands to
+    assert_eq!((second.nodes, second.scalars.integers), (3, 2));
+    assert_eq!(metrics.nodes, 12);
+    assert_eq!(metrics.scalars.total(), 9);
+    assert_eq!(metrics.size_histogram(), [(16, 1), (64, 1)]);
+}
+
+#[test]
+fn test_load_instrumented_fast_path() {
+    let (docs, metrics) = YamlLoader::load_from_str_instrumented("key: value").unwrap();
+    assert_eq!(docs[0]["key"].as_str(), Some("value"));
+    assert!(metrics.fast_path);
+    assert_eq!(metrics.tokens_scanned, 0);
+    assert_eq!(metrics.documents.len(), 1);
+}
+
+#[test]
+fn test_push_parser_byte_at_a_time() {
+    let input = "# leading comment\nname: first\n---\nname: \"sec\u{f6}nd\"\n...\n---\n- 1\n- 2\n";
+    let mut parser = PushParser::new();
+    let mut docs = Vec::new();
+    for byte in input.as_bytes() {
+        docs.extend(parser.feed(std::slice::from_ref(byte)).unwrap());
+    }
+    // the last document can only be closed by finish()
+    assert_eq!(docs.len(), 2);
+    docs.extend(parser.finish().unwrap());
+
+    assert_eq!(docs.len(), 3);
+    assert_eq!(docs[0]["name"].as_str(), Some("first"));
+    assert_eq!(docs[1]["name"].as_str(), Some("sec\u{f6}nd"));
+    assert_eq!(docs[2][1].as_i64(), Some(2));
+    assert_eq!(parser.buffered(), 0);
+}
+
+#[test]
+fn test_push_parser_events() {
+    #[derive(Default)]
+    struct Collect(Vec<Event>);
+    impl EventReceiver for Collect {
+        fn on_event(&mut self, ev: Event) {
+            self.0.push(ev);
+        }
+    }
+
+    let mut parser = PushParser::new();
+    let mut events = Collect::default();
+    assert_eq!(parser.feed_events(b"a: 1\n--", &mut events).unwrap(), 0);
+    assert_eq!(parser.feed_events(b"-\nb: x\n", &mut events).unwrap(), 1);
+    assert_eq!(parser.finish_events(&mut events).unwrap(), 1);
+
+    assert_eq!(events.0.first(), Some(&Event::StreamStart));
+    assert_eq!(events.0.last(), Some(&Event::StreamEnd));
+    let scalars = events.0.iter().filter(|e| matches!(e, Event::Scalar(..))).count();
+    assert_eq!(scalars, 4);
+    let docs = events.0.iter().filter(|e| **e == Event::DocumentStart).count();
+    assert_eq!(docs, 2);
+}
+
+#[test]
+fn test_load_events_keeps_anchor_names() {
+    #[derive(Default)]
+    struct Collect(Vec<Event>);
+    impl EventReceiver for Collect {
+        fn on_event(&mut self, ev: Event) {
+            self.0.push(ev);
+        }
+    }
+
+    let yaml = "defaults: &defaults {retries: 3}\nlist: &items [&first a, b]\nuse: *defaults\n\
+                --- \n- &defaults !t x\n- *defaults\n";
+    let mut events = Collect::default();
+    let anchors = YamlLoader::load_events(yaml, &mut events).unwrap();
+    let named: Vec<_> = anchors.iter().collect();
+    assert_eq!(named, [(1, "defaults"), (2, "first"), (3, "items"), (4, "defaults")]);
+    assert_eq!(anchors.id("defaults"), Some(4));
+    assert_eq!(anchors.name(3), Some("items"));
+    assert_eq!(anchors.name(0), None);
+
+    let ev = &events.0;
+    assert!(ev.contains(&Event::MappingStart(1, Some("defaults".into()))));
+    assert!(ev.contains(&Event::SequenceStart(3, Some("items".into()))));
+    assert!(ev.contains(&Event::MappingStart(0, None)));
+    let aliases: Vec<_> = ev.iter().filter(|e| matches!(e, Event::Alias(_))).collect();
+    assert_eq!(aliases, [&Event::Alias(1), &Event::Alias(4)]);
+    assert!(ev.iter().any(|e| matches!(
+        e,
+        Event::Scalar(v, _, 2, None, _) if v == "a"
+    )));
+    assert!(ev.iter().any(|e| matches!(
+        e,
+        Event::Scalar(v, _, 4, Some(yyaml::TokenType::Tag(_, t)), _) if v == "x" && t == "t"
+    )));
+
+    let docs = ev.iter().filter(|e| **e == Event::DocumentStart).count();
+    assert_eq!(docs, 2);
+    assert!(YamlLoader::load_events("*missing", &mut Collect::default()).is_err());
+}
+
+#[test]
+fn test_push_parser_error_position() {
+    let mut parser = PushParser::new();
+    parser.feed(b"ok: 1\n---\n").unwrap();
+    match parser.feed(b"bad: \xff\n---\n") {
+        Err(Error::Scan(e)) => assert_eq!((e.mark.line(), e.mark.col()), (3, 5)),
+        other => panic!("expected scan error, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_verify_fast_path() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&seen);
+    let options = LoaderOptions::new().verify_fast_path(move |diagnostic| {
+        sink.lock().unwrap().push(diagnostic.clone());
+    });
+
+    let inputs = [
+        "key: value",
+        "- 1\n- two",
+        "[1, 2, 3]",
+        "enabled: yes",
+        "n: 0x1f",
+        r#"quoted: "a\tb""#,
+        "single: 'it''s'",
+        "[http://example.com, a:b, :c]",
+        "[a: b, c]",
+        "{a:b}",
+        "{url: http://x:80}",
+        "a:1",
+        "url: http://x",
+        "a: 1\nurl: http://x:8/p\nc:d: e",
+    ];
+    for input in inputs {
+        YamlLoader::load_with_options(input, &options).unwrap();
+    }
+    let diagnostics = seen.lock().unwrap();
+    assert!(diagnostics.is_empty(), "{diagnostics:?}");
+}
+
+#[test]
+fn test_colon_in_flow_context() {
+    let docs = YamlLoader::load_from_str("[http://example.com, a:b, :c]").unwrap();
+    let items = docs[0].as_vec().unwrap();
+    assert_eq!(items[0].as_str(), Some("http://example.com"));
+    assert_eq!(items[1].as_str(), Some("a:b"));
+    assert_eq!(items[2].as_str(), Some(":c"));
+
+    let docs = YamlLoader::load_from_str(r#"{"a":1, b: [x], url: http://x:80}"#).unwrap();
+    assert_eq!(docs[0]["a"].as_i64(), Some(1));
+    assert_eq!(docs[0]["b"][0].as_str(), Some("x"));
+    assert_eq!(docs[0]["url"].as_str(), Some("http://x:80"));
+
+    let docs = YamlLoader::load_from_str("[a: b, c]").unwrap();
+    assert_eq!(docs[0][0]["a"].as_str(), Some("b"));
+    assert_eq!(docs[0][1].as_str(), Some("c"));
+}
+
+#[test]
+fn test_colon_in_block_context() {
+    let docs = YamlLoader::load_from_str("a:1").unwrap();
+    assert_eq!(docs[0].as_str(), Some("a:1"));
+
+    let docs = YamlLoader::load_from_str("url: http://x:8/p\nk:v: 1\nkey::").unwrap();
+    assert_eq!(docs[0]["url"].as_str(), Some("http://x:8/p"));
+    assert_eq!(docs[0]["k:v"].as_i64(), Some(1));
+    assert!(docs[0]["key:"].is_null());
+
+    let docs = YamlLoader::load_from_str("- a:1\n- b: c:d").unwrap();
+    assert_eq!(docs[0][0].as_str(), Some("a:1"));
+    assert_eq!(docs[0][1]["b"].as_str(), Some("c:d"));
+}
+
+#[test]
+fn test_strict_flow() {
+    let options = LoaderOptions::new().strict_flow(true);
+    let docs = YamlLoader::load_with_options(r#"{"a": [1, 2], "b": {}}"#, &options).unwrap();
+    assert_eq!(docs[0]["a"][1].as_i64(), Some(2));
+
+    for input in ["[a, , b]", "[, a]", "[a,]", "{a: 1,}", "{a: }", "{a, b: 1}"] {
+        match YamlLoader::load_with_options(input, &options) {
+            Err(Error::Scan(e)) => assert!(e.info.ends_with("not allowed in strict mode"), "{e}"),
+            other => panic!("expected strict flow error for {input:?}, got {other:?}"),
+        }
+    }
+}
+
+#[test]
+fn test_scalars_spanning_buffer_refills() {
+    let plain = "word ".repeat(300);
+    let plain = plain.trim_end();
+    let quoted = "x".repeat(1000);
+    let yaml = format!(
+        "plain: {plain}\nsingle: '{quoted}'\ndouble: \"{quoted}\"\nblock: |\n  {quoted}\n# {quoted}\nlast: 1\n"
+    );
+    let docs = YamlLoader::load_from_str(&yaml).unwrap();
+    assert_eq!(docs[0]["plain"].as_str().unwrap(), plain);
+    assert_eq!(docs[0]["single"].as_str().unwrap(), quoted);
+    assert_eq!(docs[0]["double"].as_str().unwrap(), quoted);
+    assert!(docs[0]["block"].as_str().unwrap().starts_with(&quoted));
+    assert_eq!(docs[0]["last"].as_i64().unwrap(), 1);
+
+    let mut scanner = yyaml::scanner::Scanner::new(yaml.chars());
+    let mut last_key = None;
+    while let Ok(token) = scanner.peek_token() {
+        scanner.skip();
+        match token.1 {
+            yyaml::TokenType::Scalar(_, ref value, _) if value == "last" => last_key = Some(token.0),
+            yyaml::TokenType::StreamEnd => break,
+            _ => {}
+        }
+    }
+    let mark = last_key.unwrap();
+    assert_eq!((mark.line(), mark.col()), (7, 0));
+    assert_eq!(mark.index(), yaml.chars().count() - "last: 1\n".len());
+}
+
+#[test]
+fn test_remaining_input_keeps_lookahead() {
+    let mut state = yyaml::scanner::ScannerState::new("|\n  text\nrest: 1\n".chars());
+    assert_eq!(state.consume_char().unwrap(), '|');
+    // Fills the lookahead buffer well past the cursor
+    assert_eq!(state.peek_char_at(3), Some('t'));
+    let rest: String = state.into_remaining_input().collect();
+    assert_eq!(rest, "\n  text\nrest: 1\n");
+}
+
+#[test]
+fn test_emitter_dedup_round_trips() {
+    let labels = "{app: web, tier: frontend, team: platform}";
+    let mut input = String::from("services:\n");
+    for i in 0..4 {
+        input.push_str(&format!("  - name: svc{i}\n    labels: {labels}\n"));
+    }
+    input.push_str(&format!("default: {labels}\nsmall: [1]\nagain: [1]\n"));
+    input.push_str("pairs:\n  - {a: 1, b: 2, c: 3}\n  - {a: 1, b: 2, c: 3}\n");
+    let doc = YamlLoader::load_from_str(&input).unwrap().remove(0);
+
+    let mut plain = String::new();
+    yyaml::YamlEmitter::new(&mut plain).dump(&doc).unwrap();
+
+    let mut compressed = String::new();
+    let mut emitter = yyaml::YamlEmitter::new(&mut compressed);
+    emitter.dedup_threshold = Some(4);
+    emitter.dump(&doc).unwrap();
+
+    assert!(compressed.len() < plain.len());
+    assert_eq!(compressed.matches("&id001").count(), 1);
+    assert_eq!(compressed.matches("*id001").count(), 4);
+    assert!(compressed.contains("- &id002\n"));
+    assert!(compressed.contains("- *id002"));
+    assert!(!compressed.contains("id003"), "[1] is under the threshold");
+    assert_eq!(YamlLoader::load_from_str(&compressed).unwrap()[0], doc);
+}
+
+#[test]
+fn test_emitter_anchor_plan() {
+    use yyaml::{AnchorPlan, EmitError, YamlEmitter};
+
+    let input = "base: {image: app, port: 80}\nstages:\n  - {image: app, port: 80}\n  - name: x\n\
+                 ports: [80, 443]\nother: 80\n";
+    let doc = YamlLoader::load_from_str(input).unwrap().remove(0);
+    let emit = |plan: AnchorPlan| {
+        let mut out = String::new();
+        let mut emitter = YamlEmitter::new(&mut out);
+        emitter.anchor_plan = Some(plan);
+        emitter.dump(&doc).map(|()| out)
+    };
+
+    let plan = AnchorPlan::new()
+        .anchor("/base", "base")
+        .alias("/stages/0", "base")
+        .anchor("/ports/0", "http")
+        .alias_node(&doc["other"], "http");
+    let out = emit(plan).unwrap();
+    assert_eq!(
+        out,
+        "---\nbase: &base\n  image: app\n  port: 80\nstages: \n  - *base\n  - name: x\n\
+         ports: \n  - &http 80\n  - 443\nother: *http"
+    );
+    assert_eq!(YamlLoader::load_from_str(&out).unwrap()[0], doc);
+
+    let rejected = |plan: AnchorPlan| match emit(plan) {
+        Err(EmitError::InvalidNode { path, reason, .. }) => (path, reason),
+        other => panic!("expected InvalidNode, got {other:?}"),
+    };
+    let (path, reason) = rejected(AnchorPlan::new().alias("/base", "b").anchor("/stages/0", "b"));
+    assert_eq!((path.as_str(), reason.as_str()), ("base", "alias `*b` is not preceded by its anchor"));
+    let (path, _) = rejected(AnchorPlan::new().anchor("/base", "b").alias("/other", "b"));
+    assert_eq!(path, "other");
+    let (path, _) = rejected(AnchorPlan::new().anchor("/missing", "m"));
+    assert_eq!(path, "missing");
+    let (path, _) = rejected(AnchorPlan::new().anchor("/stages/1/a~1b", "m"));
+    assert_eq!(path, "stages[1].a/b");
+    rejected(AnchorPlan::new().anchor("/base", "bad name"));
+    rejected(AnchorPlan::new().anchor("", "root"));
+}
+
+#[test]
+fn test_nested_alias_chain_is_rejected_early() {
+    let mut yaml = String::from("a: &a [lol]\n");
+    let names = ["a", "b", "c", "d", "e", "f", "g", "h", "i"];
+    for (prev, name) in names.iter().zip(&names[1..]) {
+        let aliases = vec![format!("*{prev}"); 9].join(", ");
+        yaml.push_str(&format!("{name}: &{name} [{aliases}]\n"));
+    }
+    let err = YamlLoader::load_from_str(&yaml).unwrap_err();
+    assert_eq!(
+        err.info,
+        "alias expansion limit exceeded for anchor chain e -> d -> c -> b -> a"
+    );
+    assert_eq!(err.mark.line(), 6);
+
+    // The same aliases without the nesting stay within budget
+    let flat = format!("a: &a [lol]\nb: [{}]\n", vec!["*a"; 5_000].join(", "));
+    assert!(YamlLoader::load_from_str(&flat).is_ok());
+}
+
+#[test]
+fn test_scalar_and_key_length_limits() {
+    let options = LoaderOptions::new().max_scalar_len(8).max_key_len(4);
+    let cases = [
+        ("a: 123456789\n", "scalar is longer than the limit of 8 bytes", 1),
+        ("a: ok\nb: 'quoted text'\n", "scalar is longer than the limit of 8 bytes", 2),
+        ("a: \"escaped\\ttext\"\n", "scalar is longer than the limit of 8 bytes", 1),
+        ("a: |\n  one\n  two\n  three\n", "scalar is longer than the limit of 8 bytes", 1),
+        ("a: 1\nlonger: 2\n", "mapping key is longer than the limit of 4 bytes", 2),
+        ("{a: 1, 'quoted': 2}", "mapping key is longer than the limit of 4 bytes", 1),
+    ];
+    for (input, info, line) in cases {
+        let Err(Error::Scan(err)) = YamlLoader::load_with_options(input, &options) else {
+            panic!("{input:?} should exceed a limit");
+        };
+        assert_eq!(err.info, info, "{input:?}");
+        assert_eq!(err.mark.line(), line, "{input:?}");
+    }
+
+    // Long values are fine under a key limit alone, and the defaults impose none
+    let input = "key: a value well past four bytes\n";
+    let keyed = LoaderOptions::new().max_key_len(4);
+    assert!(YamlLoader::load_with_options(input, &keyed).is_ok());
+    let long = format!("{}: {}\n", "k".repeat(10_000), "v".repeat(100_000));
+    assert!(YamlLoader::load_with_options(&long, &LoaderOptions::new()).is_ok());
+}
+
+#[test]
+fn test_emit_errors_carry_node_path() {
+    use yyaml::{EmitError, Yaml, YamlEmitter};
+
+    let mut doc = YamlLoader::load_from_str("servers:\n  - port: 1\n  - port: 2\n    ratio: 0.5\n")
+        .unwrap()
+        .remove(0);
+    let mut out = String::new();
+    assert!(YamlEmitter::new(&mut out).check(&doc).is_empty());
+
+    let servers = doc.get_mut("servers").unwrap();
+    *servers.get_index_mut(0).unwrap() = Yaml::Real("1.2.3".into());
+    *servers.get_index_mut(1).unwrap() = Yaml::Alias(0);
+
+    let problems = YamlEmitter::new(&mut out).check(&doc);
+    let paths: Vec<_> = problems
+        .iter()
+        .map(|p| match p {
+            EmitError::InvalidNode { path, kind, .. } => format!("{path} {kind}"),
+            other => panic!("unexpected {other:?}"),
+        })
+        .collect();
+    assert_eq!(paths, ["servers[0] real", "servers[1] alias"]);
+    assert_eq!(
+        YamlEmitter::new(&mut out).dump(&doc).unwrap_err().to_string(),
+        "cannot emit real at `servers[0]`: `1.2.3` is not a float"
+    );
+    assert!(out.is_empty(), "nothing is written for an invalid document");
+
+    struct Limited(usize);
+    impl std::fmt::Write for Limited {
+        fn write_str(&mut self, s: &str) -> std::fmt::Result {
+            self.0 = self.0.checked_sub(s.len()).ok_or(std::fmt::Error)?;
+            Ok(())
+        }
+    }
+    let doc = YamlLoader::load_from_str("a:\n  b: [1, 2]\n").unwrap().remove(0);
+    let err = YamlEmitter::new(&mut Limited(28)).dump(&doc).unwrap_err();
+    assert!(matches!(err, EmitError::Write { ref path, kind: "integer", .. } if path == "a.b[1]"));
+}
+
+#[test]
+fn test_emit_to_io_writer() {
+    use yyaml::{EmitError, YamlEmitter};
+
+    let doc = YamlLoader::load_from_str("a:\n  b: [1, 2]\nc: text\n").unwrap().remove(0);
+    let mut text = String::new();
+    YamlEmitter::new(&mut text).dump(&doc).unwrap();
+
+    let mut bytes = Vec::new();
+    let mut emitter = YamlEmitter::from_io(&mut bytes);
+    emitter.dump(&doc).unwrap();
+    assert_eq!(emitter.bytes_written(), text.len());
+    assert_eq!(String::from_utf8(bytes).unwrap(), text);
+
+    let mut serialized = Vec::new();
+    yyaml::to_writer(&mut serialized, &vec![1, 2]).unwrap();
+    assert_eq!(serialized, yyaml::to_string(&vec![1, 2]).unwrap().into_bytes());
+
+    let mut full = [0u8; 20];
+    let err = YamlEmitter::from_io(&mut full.as_mut_slice()).dump(&doc).unwrap_err();
+    let EmitError::Io { path, written, source } = &err else {
+        panic!("expected an io error, got {err:?}");
+    };
+    assert_eq!((path.as_str(), *written), ("a.b[0]", 20));
+    assert_eq!(source.kind(), std::io::ErrorKind::WriteZero);
+}
+
+#[test]
+fn test_yaml_cache() {
+    use yyaml::{ContentHash, Eviction, YamlCache};
+
+    let evicted = Arc::new(Mutex::new(Vec::new()));
+    let log = Arc::clone(&evicted);
+    let cache = YamlCache::new(20)
+        .on_evict(move |hash, why| log.lock().unwrap().push((hash, why)));
+    let a = b"name: a\n".as_slice();
+    let b = b"name: b\n".as_slice();
+    let c = b"name: c\n".as_slice();
+
+    let first = cache.get_or_parse(a).unwrap();
+    assert!(Arc::ptr_eq(&first, &cache.get_or_parse(a).unwrap()));
+    cache.get_or_parse(b).unwrap();
+    // `a` was used more recently than `b`, so `b` makes room for `c`
+    cache.get_or_parse(a).unwrap();
+    cache.get_or_parse(c).unwrap();
+    assert_eq!((cache.len(), cache.bytes()), (2, 16));
+    assert_eq!(*evicted.lock().unwrap(), [(ContentHash::of(b), Eviction::Size)]);
+    assert!(Arc::ptr_eq(&first, &cache.get_or_parse(a).unwrap()));
+
+    // Too large to keep, but still parsed
+    let big = "items: [".to_string() + &"1, ".repeat(20) + "1]";
+    assert_eq!(cache.get_or_parse(big.as_bytes()).unwrap()[0]["items"][20].as_i64(), Some(1));
+    assert_eq!(cache.len(), 2);
+
+    assert!(cache.get_or_parse(b"a: [1").is_err());
+    let err = cache.get_or_parse(b"a: \xff").unwrap_err();
+    assert!(err.to_string().contains("invalid UTF-8"), "{err}");
+    assert_eq!(cache.len(), 2);
+
+    evicted.lock().unwrap().clear();
+    cache.retain(|_, docs| docs[0]["name"].as_str() == Some("a"));
+    assert!(!cache.invalidate_hash(ContentHash::of(c)));
+    assert!(!cache.invalidate(b));
+    cache.clear();
+    assert!(cache.is_empty() && cache.bytes() == 0);
+    assert_eq!(
+        *evicted.lock().unwrap(),
+        [(ContentHash::of(c), Eviction::Invalidated), (ContentHash::of(a), Eviction::Cleared)]
+    );
+    assert_eq!(ContentHash::of(a).to_string().len(), 16);
+}
+
+#[test]
+fn test_stream_checkpoint_and_resume() {
+    use std::collections::BTreeMap;
+    use std::fs::OpenOptions;
+    use std::io::Write;
+    use yyaml::{StreamEmitter, StreamState};
+
+    let record = |id: u32| BTreeMap::from([("id", id.to_string()), ("text", "a\nb".to_string())]);
+    let path = std::env::temp_dir().join(format!("yyaml-stream-{}.yaml", std::process::id()));
+    let mut file = std::fs::File::create(&path).unwrap();
+    let mut stream = StreamEmitter::begin_stream(&mut file);
+    for id in 0..3 {
+        stream.emit_document(&record(id)).unwrap();
+    }
+    let saved = yyaml::to_string(&stream.checkpoint().unwrap()).unwrap();
+    stream.emit_document(&record(3)).unwrap();
+    drop(stream);
+    // The crash leaves half of a document behind
+    file.write_all(b"---\nid: '4'\ntex").unwrap();
+    drop(file);
+
+    let state: StreamState = yyaml::from_str(&saved).unwrap();
+    assert_eq!(state.documents(), 3);
+    let mut file = OpenOptions::new().write(true).open(&path).unwrap();
+    state.truncate(&mut file).unwrap();
+    let mut stream = StreamEmitter::resume(state, &mut file);
+    for id in 3..6 {
+        stream.emit_document(&record(id)).unwrap();
+    }
+    let end = stream.finish().unwrap();
+    drop(file);
+
+    let text = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(end.documents(), 6);
+    assert_eq!(end.bytes(), text.len() as u64);
+    let docs = YamlLoader::load_from_str(&text).unwrap();
+    let ids: Vec<_> = docs.iter().map(|doc| doc["id"].as_str().unwrap()).collect();
+    assert_eq!(ids, ["0", "1", "2", "3", "4", "5"]);
+    assert!(docs.iter().all(|doc| doc["text"].as_str() == Some("a\nb")));
+}
+
+#[test]
+fn test_merge_streams() {
+    use yyaml::{MergePolicy, merge_streams};
+
+    let deployment = "%YAML 1.2\n---\nkind: Deployment\n---\nkind: Service\n";
+    let config = "%TAG !k! tag:k8s.io,2024:\n--- {kind: ConfigMap}\n";
+    let bundle = merge_streams(&[deployment, config], MergePolicy::Concatenate).unwrap();
+    assert_eq!(
+        bundle,
+        "%YAML 1.2\n---\nkind: Deployment\n---\nkind: Service\n...\n\
+         %TAG !k! tag:k8s.io,2024:\n---\nkind: ConfigMap\n"
+    );
+    let kinds: Vec<_> = YamlLoader::load_from_str(&bundle)
+        .unwrap()
+        .iter()
+        .map(|doc| doc["kind"].as_str().unwrap().to_owned())
+        .collect();
+    assert_eq!(kinds, ["Deployment", "Service", "ConfigMap"]);
+
+    let base = "%YAML 1.2\n---\nspec: {replicas: 1, ports: [80]}\nname: app\n";
+    let overlay = "spec: {replicas: 3, ports: [443]}\n";
+    let merged = merge_streams(&[base, overlay], MergePolicy::DeepMerge).unwrap();
+    let doc = &YamlLoader::load_from_str(&merged).unwrap()[0];
+    assert!(merged.starts_with("%YAML 1.2\n---\n"));
+    assert_eq!(doc["spec"]["replicas"].as_i64(), Some(3));
+    assert_eq!(doc["spec"]["ports"][0].as_i64(), Some(443));
+    assert_eq!(doc["name"].as_str(), Some("app"));
+
+    let other = "%TAG !k! tag:other.io,2024:\n--- {}\n";
+    assert!(merge_streams(&[config, other], MergePolicy::DeepMerge).is_err());
+    assert_eq!(merge_streams(&[], MergePolicy::Concatenate).unwrap(), "");
+}
+
+#[test]
+fn test_format_and_json() {
+    use yyaml::{format_str, to_json};
+
+    let formatted = format_str("%YAML 1.2\n--- {name: app, ports: [80]}\n--- x\n").unwrap();
+    assert_eq!(formatted, "%YAML 1.2\n---\nname: app\nports: \n  - 80\n---\nx\n");
+    assert_eq!(format_str(&formatted).unwrap(), formatted);
+
+    let docs = YamlLoader::load_from_str(
+        "a: [1, .5, -2.0e3, true, ~]\n\"q\": \"say \\\"hi\\\"\\n\"\n3: !t tagged\n",
+    )
+    .unwrap();
+    assert_eq!(
+        to_json(&docs[0]).unwrap(),
+        r#"{"a":[1,0.5,-2.0e3,true,null],"q":"say \"hi\"\n","3":"tagged"}"#
+    );
+    for bad in ["x: .nan", "x: .inf", "? [a, b]\n: 1\n", "{1: a, '1': b}"] {
+        let docs = YamlLoader::load_from_str(bad).unwrap();
+        assert!(to_json(&docs[0]).is_err(), "{bad}");
+    }
+}
+
+#[test]
+fn test_block_scalar_headers_in_tokens() {
+    use yyaml::parser::ChompingMode;
+    use yyaml::scanner::Scanner;
+    use yyaml::{BlockScalarHeader, TScalarStyle, TokenType};
+
+    let mut scanner = Scanner::new("a: |2-\n   x\nb: >+\n y\n\nc: |\n z\n".chars());
+    let mut headers = Vec::new();
+    loop {
+        let token = scanner.peek_token().unwrap();
+        scanner.skip();
+        match token.1 {
+            TokenType::Scalar(style, _, header) => headers.push((style, header)),
+            TokenType::StreamEnd => break,
+            _ => {}
+        }
+    }
+    let header = |chomping, indent| Some(BlockScalarHeader { chomping, indent });
+    assert_eq!(
+        headers,
+        [
+            (TScalarStyle::Plain, None),
+            (TScalarStyle::Literal, header(ChompingMode::Strip, Some(2))),
+            (TScalarStyle::Plain, None),
+            (TScalarStyle::Folded, header(ChompingMode::Keep, None)),
+            (TScalarStyle::Plain, None),
+            (TScalarStyle::Literal, header(ChompingMode::Clip, None)),
+        ]
+    );
+}
+
+#[test]
+fn test_profiles() {
+    use yyaml::{DeserializerConfig, Profile, Value};
+
+    let strict = YamlLoader::with_profile(Profile::strict());
+    let err = YamlLoader::load_with_options("a: 1\nb:\n  c: 2\n  c: 3\n", &strict).unwrap_err();
+    assert!(err.to_string().contains("duplicate mapping key `c` at line 4 col 3"), "{err}");
+    let err = YamlLoader::load_with_options("{x: 1, x: [2,\n 3]}", &strict).unwrap_err();
+    assert!(err.to_string().ends_with("at line 1 col 8"), "{err}");
+    let err = YamlLoader::load_with_options("a:\tb\n", &strict).unwrap_err();
+    assert!(err.to_string().contains("tabs"), "{err}");
+    let deep = format!("{}1{}", "[".repeat(65), "]".repeat(65));
+    assert!(YamlLoader::load_with_options(&deep, &strict).is_err());
+    let shallow = format!("{}1{}", "[".repeat(64), "]".repeat(64));
+    assert!(YamlLoader::load_with_options(&shallow, &strict).is_ok());
+    // Tabs inside scalars are content
+    let docs = YamlLoader::load_with_options("a: \"x\ty\"\n", &strict).unwrap();
+    assert_eq!(docs[0]["a"].as_str(), Some("x\ty"));
+
+    let permissive = YamlLoader::with_profile(Profile::permissive());
+    let docs = YamlLoader::load_with_options("a: 1\na: 2\nb:\tc\n", &permissive).unwrap();
+    assert_eq!(docs[0]["a"].as_i64(), Some(2));
+    assert_eq!(docs[0]["b"].as_str(), Some("c"));
+    assert!(YamlLoader::load_with_options(&deep, &permissive).is_ok());
+
+    let k8s = YamlLoader::with_profile(Profile::strict_k8s());
+    let long_key = format!("{}: 1\n", "k".repeat(318));
+    assert!(YamlLoader::load_with_options(&long_key, &k8s).is_err());
+    let ci = YamlLoader::with_profile(Profile::strict_ci());
+    assert!(YamlLoader::load_with_options("a: [1, 2,]\n", &ci).is_err());
+
+    assert_eq!(Profile::by_name("strict-k8s"), Some(Profile::strict_k8s()));
+    assert_eq!(Profile::by_name("strict-ci").map(|p| p.name()), Some("strict-ci"));
+    assert_eq!(Profile::by_name("lax"), None);
+    assert_eq!(Profile::default(), Profile::permissive());
+
+    let config = DeserializerConfig::new().profile(Profile::strict());
+    assert!(yyaml::from_str_with_config::<Value>("a: 1\na: 2\n", &config).is_err());
+    assert!(yyaml::from_str_with_config::<Value>("a: 1\nb: 2\n", &config).is_ok());
+}
+
+#[test]
+fn test_parse_fragment() {
+    use yyaml::Yaml;
+
+    let err = Yaml::parse_fragment("[1, *b]").unwrap_err();
+    assert!(err.to_string().contains("unknown anchor"), "{err}");
+    assert_eq!(Yaml::parse_fragment("[&b x, *b]").unwrap()[1].as_str(), Some("x"));
+    let node = Yaml::parse_fragment(" [1, {a: 2}, 'x y'] ").unwrap();
+    assert_eq!(node[0].as_i64(), Some(1));
+    assert_eq!(node[1]["a"].as_i64(), Some(2));
+    assert_eq!(node[2].as_str(), Some("x y"));
+    let tagged = Yaml::Tagged("tag".into(), Box::new(Yaml::String("foo".into())));
+    assert_eq!(Yaml::parse_fragment("!tag foo").unwrap(), tagged);
+    assert_eq!(Yaml::parse_fragment("0x1f").unwrap(), Yaml::Integer(31));
+    assert_eq!(Yaml::parse_fragment("").unwrap(), Yaml::Null);
+    assert_eq!(Yaml::parse_fragment("{a: [1,\n  2]}").unwrap()["a"][1].as_i64(), Some(2));
+
+    for (input, problem) in [
+        ("a: 1", "block collections"),
+        ("- 1", "block collections"),
+        ("|\n  text\n", "block scalars"),
+        ("--- 1", "document markers"),
+        ("[1] [2]", "after the fragment"),
+        ("foo bar: 1", "block collections"),
+    ] {
+        let err = Yaml::parse_fragment(input).unwrap_err();
+        assert!(err.to_string().contains(problem), "{input:?}: {err}");
+    }
+    let err = Yaml::parse_fragment("[1, 2] x").unwrap_err();
+    assert_eq!(err.mark.col(), 7);
+    assert!(Yaml::parse_fragment("[1, 2").is_err());
+}
+
+#[test]
+fn test_node_middleware() {
+    use yyaml::Yaml;
+
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&seen);
+    let options = LoaderOptions::new().node_middleware(move |info, node| {
+        sink.lock().unwrap().push((info.path.to_string(), info.is_key));
+        match node {
+            Yaml::String(s) if !info.is_key && info.path.contains("/secrets/") => {
+                Yaml::String("*".repeat(s.len()))
+            }
+            Yaml::String(s) if info.is_key => Yaml::String(s.to_uppercase()),
+            node => node,
+        }
+    });
+    let input = "name: app\nsecrets:\n  token: abc\n  a/b: [x, &k y, *k]\nlist:\n- {k: v}\n";
+    let docs = YamlLoader::load_with_options(input, &options).unwrap();
+    assert_eq!(docs[0]["NAME"].as_str(), Some("app"));
+    assert_eq!(docs[0]["SECRETS"]["TOKEN"].as_str(), Some("***"));
+    assert_eq!(docs[0]["SECRETS"]["A/B"][2].as_str(), Some("*"));
+    assert_eq!(docs[0]["LIST"][0]["K"].as_str(), Some("v"));
+
+    let seen = seen.lock().unwrap();
+    let paths: Vec<&str> = seen.iter().map(|(path, _)| path.as_str()).collect();
+    assert_eq!(
+        paths,
+        [
+            "/name", "/name", "/secrets", "/secrets/token", "/secrets/token", "/secrets/a~1b",
+            "/secrets/a~1b/0", "/secrets/a~1b/1", "/secrets/a~1b/2", "/secrets/a~1b",
+            "/secrets", "/list", "/list/0/k", "/list/0/k", "/list/0", "/list", "",
+        ]
+    );
+    assert_eq!(seen.iter().filter(|(_, is_key)| *is_key).count(), 6);
+    drop(seen);
+
+    // Aliases copy the node the middleware left, wherever they are
+    let mask = LoaderOptions::new().node_middleware(|info, node| {
+        if !info.is_key && info.path.ends_with("/token") {
+            Yaml::String("***".into())
+        } else {
+            node
+        }
+    });
+    let input = "secrets:\n  token: &t abc\n  copy: *t\npublic: *t\nlist: [&u {token: x}, *u]\n";
+    let docs = YamlLoader::load_with_options(input, &mask).unwrap();
+    assert_eq!(docs[0]["secrets"]["token"].as_str(), Some("***"));
+    assert_eq!(docs[0]["secrets"]["copy"].as_str(), Some("***"));
+    assert_eq!(docs[0]["public"].as_str(), Some("***"));
+    assert_eq!(docs[0]["list"][1]["token"].as_str(), Some("***"));
+
+    // The fast path would skip the middleware
+    let options = LoaderOptions::new().node_middleware(|_, _| Yaml::Integer(1));
+    assert_eq!(YamlLoader::load_with_options("plain", &options).unwrap()[0], Yaml::Integer(1));
+}
+
+#[test]
+fn test_directives_scoped_per_document() {
+    use yyaml::Yaml;
+
+    let tagged = |tag: &str, value: Yaml| Yaml::Tagged(tag.to_string(), Box::new(value));
+    let stream = "%TAG !e! tag:one.io,2024:\n%TAG ! tag:local.io:\n--- [!e!x 1, !y 2, !<tag:v> 3]\n\
+                  ...\n%YAML 1.1\n%TAG !e! tag:two.io,2024:\n--- !e!x 4\n...\n--- [!y 6]\n";
+    let docs = YamlLoader::load_from_str(stream).unwrap();
+    assert_eq!(
+        docs,
+        vec![
+            Yaml::Array(vec![
+                tagged("tag:one.io,2024:x", Yaml::Integer(1)),
+                tagged("tag:local.io:y", Yaml::Integer(2)),
+                tagged("tag:v", Yaml::Integer(3)),
+            ]),
+            tagged("tag:two.io,2024:x", Yaml::Integer(4)),
+            Yaml::Array(vec![tagged("y", Yaml::Integer(6))]),
+        ]
+    );
+
+    // A named handle is undefined once its `%TAG` goes out of scope
+    let stream = "%TAG !e! tag:one.io,2024:\n--- !e!x 4\n...\n--- [5, !e!x 6]\n";
+    let error = YamlLoader::load_from_str(stream).unwrap_err();
+    assert_eq!(error.to_string(), "undefined tag handle !e! at line 4 col 9");
+
+    // Anchors and directives end with their document
+    assert!(YamlLoader::load_from_str("--- &a 1\n--- *a\n").is_err());
+    assert!(YamlLoader::load_from_str("%YAML 1.2\n--- a\n...\n%YAML 1.2\n--- b\n").is_ok());
+    let error = YamlLoader::load_from_str("%TAG !e! a:\n%TAG !e! b:\n--- 1\n").unwrap_err();
+    assert!(error.to_string().contains("duplicate %TAG directive"), "{error}");
+}
+
+#[test]
+fn test_max_documents() {
+    let stream = "a: 1\n---\nb: 2\n...\n---\nc: 3\n";
+    let options = LoaderOptions::new().max_documents(2);
+    let error = YamlLoader::load_with_options(stream, &options).unwrap_err();
+    assert!(error.to_string().contains("more than 2 documents"), "{error}");
+    assert!(error.to_string().contains("line 5"), "{error}");
+    let single = LoaderOptions::new().max_documents(1);
+    let docs = YamlLoader::load_with_options("a: 1\n...\n# end\n", &single).unwrap();
+    assert_eq!(docs.len(), 1);
+
+    let options = options.truncate_documents(true);
+    let (docs, truncated) = YamlLoader::load_truncated(stream, &options).unwrap();
+    assert!(truncated);
+    assert_eq!(docs.len(), 2);
+    assert_eq!(docs[1]["b"].as_i64(), Some(2));
+    let options = LoaderOptions::new().max_documents(0).truncate_documents(true);
+    assert_eq!(YamlLoader::load_truncated("a", &options).unwrap(), (Vec::new(), true));
+    assert_eq!(YamlLoader::load_truncated("", &options).unwrap(), (Vec::new(), false));
+    let none = LoaderOptions::new().max_documents(0);
+    assert_eq!(YamlLoader::load_with_options("# only a comment
+", &none).unwrap(), Vec::new());
+}
+
+#[test]
+fn test_integer_overflow_policy() {
+    use yyaml::{DeserializerConfig, IntegerOverflow, Value, Yaml};
+
+    let source = "\
+big: 9223372036854775808
+small: -9223372036854775809
+hex: 0x10000000000000000
+max: 9223372036854775807
+";
+    let load = |policy| {
+        YamlLoader::load_with_options(source, &LoaderOptions::new().integer_overflow(policy))
+    };
+
+    let docs = load(IntegerOverflow::PromoteToFloat).unwrap();
+    assert_eq!(docs[0]["big"], Yaml::Real("9223372036854775808".into()));
+    assert_eq!(docs[0]["hex"].as_f64(), Some(18446744073709551616.0));
+    assert_eq!(docs[0]["max"].as_i64(), Some(i64::MAX));
+    // The fast path and the plain loader agree with the default policy
+    assert_eq!(YamlLoader::load_from_str(source).unwrap(), docs);
+    assert_eq!(YamlLoader::load_from_str("0x10000000000000000").unwrap()[0], docs[0]["hex"]);
+    // `Value` and `i128` fields read integers that fit in an `i128` exactly
+    let wide: std::collections::BTreeMap<String, i128> = yyaml::from_str(source).unwrap();
+    assert_eq!(wide["small"], -9_223_372_036_854_775_809);
+    assert_eq!(wide["hex"], 1 << 64);
+    let value: Value = yyaml::from_str(source).unwrap();
+    assert_eq!(value["hex"], yyaml::to_value(&(1_i128 << 64)).unwrap());
+
+    let docs = load(IntegerOverflow::Saturate).unwrap();
+    assert_eq!(docs[0]["big"].as_i64(), Some(i64::MAX));
+    assert_eq!(docs[0]["small"].as_i64(), Some(i64::MIN));
+    assert_eq!(docs[0]["hex"].as_i64(), Some(i64::MAX));
+
+    let docs = load(IntegerOverflow::KeepString).unwrap();
+    assert_eq!(docs[0]["small"].as_str(), Some("-9223372036854775809"));
+
+    let error = load(IntegerOverflow::Error).unwrap_err().to_string();
+    assert!(error.contains("integer 9223372036854775808 does not fit"), "{error}");
+    assert!(error.contains("line 1"), "{error}");
+    let quoted = "big: '9223372036854775808'";
+    let options = LoaderOptions::new().integer_overflow(IntegerOverflow::Error);
+    assert!(YamlLoader::load_with_options(quoted, &options).is_ok());
+
+    let config = DeserializerConfig::new().integer_overflow(IntegerOverflow::KeepString);
+    let value: Value = yyaml::from_str_with_config(source, &config).unwrap();
+    assert_eq!(value["hex"].as_str(), Some("0x10000000000000000"));
+    let config = DeserializerConfig::new().integer_overflow(IntegerOverflow::Error);
+    assert!(yyaml::from_str_with_config::<Value>(source, &config).is_err());
+
+    let found = yyaml::diagnostics::check_integer_overflow(source).unwrap();
+    let lines: Vec<_> = found.iter().map(|d| d.mark.line()).collect();
+    assert_eq!(lines, [1, 2, 3]);
+}
+
+#[test]
+fn test_null_words() {
+    use yyaml::{DeserializerConfig, Value, Yaml, YamlEmitter};
+
+    let options = LoaderOptions::new().null_words(&["NULL", "Null", "~"]);
+    let source = "a: null\nb: Null\nc: ~\nd:\ne: 'NULL'\nNULL: key\n";
+    let doc = &YamlLoader::load_with_options(source, &options).unwrap()[0];
+    assert_eq!(doc["a"].as_str(), Some("null"));
+    assert!(doc["b"].is_null() && doc["c"].is_null() && doc["d"].is_null());
+    assert_eq!(doc["e"].as_str(), Some("NULL"));
+    assert!(doc.as_hash().unwrap().contains_key(&Yaml::Null));
+
+    let options = LoaderOptions::new()
+        .schema(yyaml::SchemaMode::Json)
+        .null_words(&["none"]);
+    let doc = &YamlLoader::load_with_options("[none, null]", &options).unwrap()[0];
+    assert!(doc[0].is_null());
+    assert_eq!(doc[1].as_str(), Some("null"));
+
+    let config = DeserializerConfig::new().null_words(&["nil"]);
+    let value: Value = yyaml::from_str_with_config("[nil, ~]", &config).unwrap();
+    assert_eq!(value, Value::Sequence(vec![Value::Null, Value::String("~".into())]));
+
+    let mut out = String::new();
+    let mut emitter = YamlEmitter::new(&mut out);
+    emitter.null_word = "nil";
+    let doc = Yaml::Array(vec![Yaml::Null, Yaml::String("nil".into())]);
+    emitter.dump(&doc).unwrap();
+    assert_eq!(out, "---\n- nil\n- \"nil\"");
+    let options = LoaderOptions::new().null_words(&["nil"]);
+    assert_eq!(YamlLoader::load_with_options(&out, &options).unwrap()[0], doc);
+}
+
+#[test]
+fn test_indentation_rules() {
+    use yyaml::{IndentationRules, Profile};
+
+    let load = |s: &str, options: &LoaderOptions| YamlLoader::load_with_options(s, options);
+    let spec = LoaderOptions::new().indentation_rules(IndentationRules::strict());
+    for bad in ["-\t- x\n", "-\tkey: v\n", "?\t- a\n: b\n"] {
+        let err = load(bad, &spec).unwrap_err();
+        assert!(err.to_string().contains("a tab may not separate"), "{bad:?}: {err}");
+    }
+    for bad in [
+        "key: [a,\nb]\n",
+        "- k: {a: 1,\n  b: 2}\n",
+        "k: 'a\nb'\n",
+        "k: \"a\\\nb\"\n",
+        "key: [a\nb]\n",
+        "key: {a: b\nc: d}\n",
+    ] {
+        let err = load(bad, &spec).unwrap_err();
+        assert!(err.to_string().contains("must be indented more"), "{bad:?}: {err}");
+    }
+    // Tabs before scalars, and lines right of their block collection, are fine
+    for good in [
+        "-\tx\n",
+        "key:\tvalue\n",
+        "-\t# note\n  - x\n",
+        "key: [a,\n b]\n",
+        "'a\nb'\n",
+        "key: [a\n b]\n",
+    ] {
+        assert!(load(good, &spec).is_ok(), "{good:?}");
+    }
+
+    // Each rule toggles on its own
+    let flow_only = LoaderOptions::new().indentation_rules(IndentationRules {
+        reject_under_indented_flow: true,
+        ..IndentationRules::permissive()
+    });
+    let docs = load("-\tkey: v\n", &flow_only).unwrap();
+    assert_eq!(docs[0][0]["key"].as_str(), Some("v"));
+    assert!(load("key: [a,\nb]\n", &flow_only).is_err());
+
+    let strict = YamlLoader::with_profile(Profile::strict());
+    assert!(load("k: 'a\nb'\n", &strict).is_err());
+    let permissive = YamlLoader::with_profile(Profile::permissive());
+    let docs = load("key: [a,\nb]\n", &permissive).unwrap();
+    assert_eq!(docs[0]["key"][1].as_str(), Some("b"));
+}
+
+#[test]
+fn test_compare_events() {
+    use yyaml::testing::{EventDiff, compare_events};
+
+    assert!(compare_events("- &a x\n- *a\n", "[&b x, *b]").is_same());
+    let diff = compare_events("a:\n  b: 1\n  c: 2\n", "a:\n  b: 1\n  d: 2\n");
+    let EventDiff::Differ { index: 7, left: Ok(left), right: Ok(right) } = &diff else {
+        panic!("expected the key to differ, got {diff}");
+    };
+    assert_eq!((left.mark.line(), left.mark.col(), right.mark.col()), (3, 2, 2));
+    assert!(matches!(&right.event, Event::Scalar(key, ..) if key == "d"));
+
+    // A longer stream differs at the end of the shorter one
+    let diff = compare_events("--- 1\n", "--- 1\n--- 2\n");
+    let EventDiff::Differ { index: 4, left: Ok(left), right: Ok(right) } = &diff else {
+        panic!("expected the second document to differ, got {diff}");
+    };
+    assert_eq!((&left.event, &right.event), (&Event::StreamEnd, &Event::DocumentStart));
+    assert_eq!(right.mark.line(), 2);
+
+    // Failures show up after the events read before them
+    let diff = compare_events("a: [1\n", "a: [1]\n");
+    assert!(matches!(diff, EventDiff::Differ { index: 6, left: Err(_), right: Ok(_) }), "{diff}");
+    let diff = compare_events("a\n--- [b, c\n", "a\n--- [b, c]\n");
+    let EventDiff::Differ { index: 8, left: Err(_), right: Ok(right) } = &diff else {
+        panic!("expected the second document to fail at its end, got {diff}");
+    };
+    assert_eq!(right.event, Event::SequenceEnd);
+    assert!(matches!(compare_events("a: [1\n", "a: [1\n"), EventDiff::BothFail { events: 6, .. }));
+
+    // A repeated key gives events even though the last value wins
+    let diff = compare_events("{a: 1, a: 2}", "{a: 2}");
+    let EventDiff::Differ { index: 4, left: Ok(left), .. } = &diff else {
+        panic!("expected the first value to differ, got {diff}");
+    };
+    assert_eq!((left.mark.line(), left.mark.col()), (1, 4));
+}
+
+#[test]
+fn test_anchor_library() {
+    let defaults: yyaml::Value = yyaml::from_str("{retries: 3, timeout: 30}").unwrap();
+    let port = yyaml::Value::String("8080".into());
+    let library: yyaml::AnchorLibrary = [("defaults", defaults), ("port", port)].into_iter().collect();
+    let options = LoaderOptions::new().anchor_library(library);
+
+    let input = "a: *defaults\nb:\n  <<: *defaults\n  timeout: 5\nport: &port 80\nc: *port\n";
+    let docs = YamlLoader::load_with_options(input, &options).unwrap();
+    assert_eq!(docs[0]["a"]["retries"].as_i64(), Some(3));
+    assert_eq!(docs[0]["b"]["<<"]["retries"].as_i64(), Some(3));
+    assert_eq!(docs[0]["b"]["timeout"].as_i64(), Some(5));
+    // An anchor of the document shadows the library
+    assert_eq!(docs[0]["c"].as_i64(), Some(80));
+
+    match YamlLoader::load_with_options("- *nope\n", &options) {
+        Err(Error::Scan(e)) => {
+            assert_eq!(e.info, "unknown anchor 'nope' in the document or the anchor library");
+            assert_eq!((e.mark.line(), e.mark.col()), (1, 2));
+        }
+        other => panic!("expected an unknown anchor error, got {other:?}"),
+    }
+    assert!(YamlLoader::load_from_str("a: *defaults\n").is_err());
+}
+
+#[test]
+fn test_extract_self_contained_subtree() {
+    use yyaml::Yaml;
+
+    let yaml = "base: &b {x: 1}\nuse: {ref: *b, again: *b, own: &o [1], dup: *o}\n";
+    let doc = YamlLoader::load_from_str(yaml).unwrap().remove(0);
+    // Loading expands aliases, so the copy is what the pointer finds
+    let extracted = doc.extract("/use").unwrap();
+    assert_eq!(Some(&extracted), doc.pointer("/use"));
+    assert_eq!(extracted["ref"]["x"], Yaml::Integer(1));
+    assert_eq!(extracted["dup"], extracted["own"]);
+    assert_eq!(doc.extract("/base/x"), Some(Yaml::Integer(1)));
+    assert_eq!(doc.extract("/missing"), None);
+
+    // An alias with no anchor anywhere in the tree is kept as it is
+    let dangling = Yaml::Array(vec![Yaml::Alias(3), Yaml::Integer(1)]);
+    assert_eq!(dangling.extract("/0"), Some(Yaml::Alias(3)));
+    assert_eq!(dangling.extract(""), Some(dangling.clone()));
+}
+
+#[test]
+fn test_scalars_sliced_from_str_match_char_scanning() {
+    let input = concat!(
+        "---\n",
+        "plain: two  spaced   words # comment\n",
+        "unicode: héllo wörld ✓\n",
+        "single: 'it''s quoted'\n",
+        "single_folded: 'one\n  two'\n",
+        "double: \"tab\\there \\u00e9\"\n",
+        "double_plain: \"no escapes ✓\"\n",
+        "flow: [a b, 'c d', \"e f\", g\n  h]\n",
+        "typed: [42, -1.5, true, ~, '7']\n",
+        "---\n",
+        "first line\nsecond line\n\nafter blank\n",
+    );
+    let mut state_machine = yyaml::parser::StateMachine::new(input.chars());
+    let mut from_chars = Vec::new();
+    while let Some(doc) = state_machine.parse_next_document().unwrap() {
+        from_chars.push(doc);
+    }
+    let from_str = YamlLoader::load_from_str(input).unwrap();
+    assert_eq!(from_str, from_chars);
+    assert_eq!(from_str[0]["plain"].as_str(), Some("two  spaced   words"));
+    assert_eq!(from_str[1].as_str(), Some("first line second line\nafter blank"));
+    assert_eq!(from_str[0]["single"].as_str(), Some("it's quoted"));
+    assert_eq!(from_str[0]["double"].as_str(), Some("tab\there é"));
+    assert_eq!(from_str[0]["flow"][3].as_str(), Some("g h"));
+    assert_eq!(from_str[0]["typed"][0].as_i64(), Some(42));
+    assert!(from_str[0]["typed"][3].is_null());
+    assert_eq!(from_str[0]["typed"][4].as_str(), Some("7"));
+
+    // Tokens handed out of the scanner carry their text
+    let texts: Vec<String> = yyaml::scanner::ScannedTokens::from_str("k: 'v w'\n")
+        .filter_map(|token| match token.unwrap().1 {
+            yyaml::TokenType::Scalar(_, text, _) => Some(text),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(texts, ["k", "v w"]);
+}
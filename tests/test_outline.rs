@@ -0,0 +1,140 @@
+use yyaml::{OutlineKind, YamlLoader, outline};
+
+#[test]
+fn test_outline_nested_structure() {
+    let input = "server:\n  host: localhost\n  ports:\n    - 80\n    - 443\nplugins: [auth, {name: cache, ttl: 60}]\nempty: {}\n";
+    let entries = outline(input).unwrap();
+    let summary: Vec<_> = entries
+        .iter()
+        .map(|e| (e.path.as_str(), e.kind, e.len))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("", OutlineKind::Mapping, Some(3)),
+            ("/server", OutlineKind::Mapping, Some(2)),
+            ("/server/host", OutlineKind::Scalar, None),
+            ("/server/ports", OutlineKind::Sequence, Some(2)),
+            ("/server/ports/0", OutlineKind::Scalar, None),
+            ("/server/ports/1", OutlineKind::Scalar, None),
+            ("/plugins", OutlineKind::Sequence, Some(2)),
+            ("/plugins/0", OutlineKind::Scalar, None),
+            ("/plugins/1", OutlineKind::Mapping, Some(2)),
+            ("/plugins/1/name", OutlineKind::Scalar, None),
+            ("/plugins/1/ttl", OutlineKind::Scalar, None),
+            ("/empty", OutlineKind::Mapping, Some(0)),
+        ]
+    );
+
+    let ports = &entries[3];
+    assert_eq!((ports.span.0.line(), ports.span.0.col()), (4, 4));
+    assert_eq!(ports.span.1.line(), 5);
+    let plugins = &entries[6];
+    assert_eq!(plugins.span.0.line(), 6);
+    assert_eq!(plugins.span.1.line(), 6);
+}
+
+#[test]
+fn test_outline_sequence_under_key_and_documents() {
+    let input = "---\nitems:\n- a\n-\n- c/d\nafter: 1\n---\nk~/x: *ref\n";
+    let entries = outline(input).unwrap();
+    let summary: Vec<_> = entries
+        .iter()
+        .map(|e| (e.document, e.path.as_str(), e.kind, e.len))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (0, "", OutlineKind::Mapping, Some(2)),
+            (0, "/items", OutlineKind::Sequence, Some(3)),
+            (0, "/items/0", OutlineKind::Scalar, None),
+            (0, "/items/2", OutlineKind::Scalar, None),
+            (0, "/after", OutlineKind::Scalar, None),
+            (1, "", OutlineKind::Mapping, Some(1)),
+            (1, "/k~0~1x", OutlineKind::Alias, None),
+        ]
+    );
+}
+
+#[test]
+fn test_doc_comments() {
+    let source = "\
+# File header
+
+# The service
+service:
+  name: api # trailing, not a doc
+  # Exposed ports
+  #
+  #   one per listener
+  ports: [80]
+  hosts:
+    # Primary
+    - name: a
+      # Options
+      opts: {x: 1, y: 2}
+";
+    let docs = yyaml::doc_comments(source).unwrap();
+    let found: Vec<(&str, &str)> = docs.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+    assert_eq!(
+        found,
+        [
+            ("/service", "The service"),
+            ("/service/hosts/0/name", "Primary"),
+            ("/service/hosts/0/opts", "Options"),
+            ("/service/ports", "Exposed ports\n\n  one per listener"),
+        ]
+    );
+    assert!(yyaml::doc_comments("a: \"open").is_err());
+
+    // `#` lines inside scalars are content, not comments
+    let docs = yyaml::doc_comments("script: |\n  # not a comment\nnext: 1\n").unwrap();
+    assert!(docs.is_empty(), "{docs:?}");
+    let docs = yyaml::doc_comments("a: \"x\n  # quoted\"\nb: 1\n").unwrap();
+    assert!(docs.is_empty(), "{docs:?}");
+    let docs = yyaml::doc_comments("script: >\n  text\n# next key\nnext: 1\n").unwrap();
+    assert_eq!(docs["/next"], "next key");
+}
+
+#[test]
+fn test_source_map_aliases_and_merges() {
+    let source = "defaults: &d\n  retries: 3\n  timeout: 5\nservice:\n  <<: *d\n  timeout: 9\n  \
+                  name: 'api'\n---\n- &x [1]\n- *x\n";
+    let (docs, map) = YamlLoader::load_with_source_map(source).unwrap();
+    assert_eq!(docs.len(), 2);
+    let text = |document: usize, path: &str| -> Vec<&str> {
+        map.spans(document, path).iter().map(|r| &source[r.clone()]).collect()
+    };
+    // Merged content stays under `<<`, as it is in the loaded document
+    assert_eq!(text(0, "/service"), ["<<: *d\n  timeout: 9\n  name: 'api'"]);
+    assert_eq!(text(0, "/service/<<"), ["*d", "retries: 3\n  timeout: 5"]);
+    assert_eq!(text(0, "/service/<</retries"), ["3"]);
+    assert!(map.spans(0, "/service/retries").is_empty());
+    assert_eq!(text(0, "/service/timeout"), ["9"]);
+    assert_eq!(text(0, "/service/name"), ["'api'"]);
+    assert_eq!(text(1, "/1"), ["*x", "[1]"]);
+    assert_eq!(text(1, "/1/0"), ["1"]);
+    assert!(map.spans(0, "/missing").is_empty());
+    assert!(map.paths(1).any(|(path, _)| path == "/0/0"));
+}
+
+#[test]
+fn test_source_map_paths_match_documents() {
+    let source = "0x10: a\n1.0: b\n~: c\ntrue: d\ndup: [1]\ndup: [2, 3]\n? [k]\n: v\n";
+    let (docs, map) = YamlLoader::load_with_source_map(source).unwrap();
+    let text = |path: &str| -> Vec<&str> {
+        map.spans(0, path).iter().map(|r| &source[r.clone()]).collect()
+    };
+    // Keys are spelled as they load, not as they are written
+    assert_eq!(text("/16"), ["a"]);
+    assert_eq!(text("/1.0"), ["b"]);
+    assert_eq!(text("/null"), ["c"]);
+    assert_eq!(text("/true"), ["d"]);
+    // The last of a repeated key wins, as in the document
+    assert_eq!(text("/dup"), ["[2, 3]"]);
+    assert_eq!(docs[0]["dup"][1].as_i64(), Some(3));
+    assert_eq!(text("/dup/1"), ["3"]);
+    // The value under a collection key has no path
+    let paths: Vec<&str> = map.paths(0).map(|(path, _)| path).collect();
+    assert_eq!(paths, ["", "/1.0", "/16", "/dup", "/dup/0", "/dup/1", "/null", "/true"]);
+}
@@ -0,0 +1,126 @@
+use indoc::indoc;
+use smallvec::smallvec;
+use yyaml::pipeline::{DropKey, Events, Pipeline, RenameKey};
+use yyaml::parser::loader::YamlReceiver;
+use yyaml::{BadValueKind, Event, EventReceiver, InvalidNode, Marker, TScalarStyle, TokenType, Yaml};
+
+#[test]
+fn test_identity_matches_format() {
+    let input = indoc! {"
+        a: [1, 2]
+        b: {c: x, d: ~}
+        ---
+        - plain
+        - 'quoted'
+        - &a {e: [], f: {}}
+        - *a
+        ---
+        ? [1, 2]
+        : [x, [y]]
+        ---
+    "};
+    let mut out = String::new();
+    let documents = Pipeline::new().run(input, &mut out).unwrap();
+    assert_eq!(documents, 4);
+    assert_eq!(out, yyaml::format_str(input).unwrap());
+
+    let mut bytes = Vec::new();
+    Pipeline::new().run_io(input, &mut bytes).unwrap();
+    assert_eq!(String::from_utf8(bytes).unwrap(), out);
+}
+
+#[test]
+fn test_documents_are_written_as_read() {
+    // The first document is out before the error in the second is read
+    let mut out = String::new();
+    let err = Pipeline::new().run("a: 1\n---\nb: [2\n", &mut out).unwrap_err();
+    assert!(err.to_string().contains("did not find expected"), "{err}");
+    assert!(out.starts_with("---\na: 1\n---\n"), "{out:?}");
+}
+
+#[test]
+fn test_key_transforms_at_depth() {
+    let input = indoc! {"
+        name: app
+        spec:
+          name: inner
+          password: {nested: [1, {name: 2}]}
+          list:
+            - password: x
+              keep: z
+        password: top
+        values: [name, password]
+    "};
+    let mut out = String::new();
+    Pipeline::new()
+        .transform(DropKey::new("password"))
+        .transform(RenameKey::new("name", "id"))
+        .run(input, &mut out)
+        .unwrap();
+    let expected = indoc! {"
+        ---
+        id: app
+        spec: 
+          id: inner
+          list: 
+            - keep: z
+        values: 
+          - name
+          - password
+    "};
+    assert_eq!(out, expected);
+}
+
+#[test]
+fn test_closure_sees_aliases() {
+    let mut aliases = 0;
+    let mut out = String::new();
+    Pipeline::new()
+        .transform(move |event: Event| -> Events {
+            match event {
+                // Count aliases, replacing each with a marker scalar
+                Event::Alias(_) => {
+                    aliases += 1;
+                    let marker = format!("alias {aliases}");
+                    smallvec![Event::Scalar(marker, TScalarStyle::DoubleQuoted, 0, None, None)]
+                }
+                Event::Scalar(ref s, ..) if s == "drop" => smallvec![],
+                event => smallvec![event],
+            }
+        })
+        .run("base: &b {x: 1}\ncopy: *b\nmore: *b\nlist: [drop, kept]\n", &mut out)
+        .unwrap();
+    assert_eq!(out, "---\nbase: \n  x: 1\ncopy: alias 1\nmore: alias 2\nlist: \n  - kept\n");
+}
+
+#[test]
+fn test_bad_tagged_scalar_reports_reason() {
+    // Retagging `port` as an integer leaves a value that does not read as one
+    let retag = |event: Event| -> Events {
+        match event {
+            Event::Scalar(value, _, aid, None, header) if value == "eighty" => {
+                let tag = TokenType::Tag("!!".into(), "int".into());
+                smallvec![Event::Scalar(value, TScalarStyle::Plain, aid, Some(tag), header)]
+            }
+            other => smallvec![other],
+        }
+    };
+    let mut out = String::new();
+    let err = Pipeline::new()
+        .transform(retag)
+        .run("port: eighty\n", &mut out)
+        .unwrap_err();
+    assert!(err.to_string().contains("`eighty` is not a valid !!int"), "{err}");
+
+    // A receiver builds invalid nodes with why, and where if it is told
+    let mut receiver = YamlReceiver::new();
+    receiver.on_event(Event::DocumentStart);
+    receiver.on_event(Event::DocumentEnd);
+    let end = Marker::from_parts(4, 4, 2, 0);
+    receiver.on_event_at(Event::DocumentStart, Marker::default());
+    receiver.on_event_at(Event::DocumentEnd, end);
+    let invalid = |mark| Yaml::Invalid(Box::new(InvalidNode { kind: BadValueKind::EmptyDocument, mark }));
+    assert_eq!(receiver.docs, [invalid(None), invalid(Some(end))]);
+    assert!(receiver.docs.iter().all(Yaml::is_badvalue));
+    assert_eq!(Yaml::BadValue.invalid_reason(), None);
+}
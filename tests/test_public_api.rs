@@ -0,0 +1,94 @@
+//! Pins the signatures re-exported from `yyaml::prelude`.
+//!
+//! Every item in the prelude is covered by semver; if a change here is
+//! needed to make this file compile, the change is a breaking one.
+
+use yyaml::prelude::*;
+
+#[test]
+fn test_prelude_functions() {
+    let _: fn(&str) -> Result<Value, Error> = from_str::<Value>;
+    let _: fn(&str, &DeserializerConfig) -> Result<Value, Error> = from_str_with_config::<Value>;
+    let _: fn(&str) -> Result<Value, Error> = parse_first::<Value>;
+    let _: fn(&str) -> Result<Vec<Value>, Error> = parse_all::<Value>;
+    let _: fn(Value) -> Result<Value, Error> = from_value::<Value>;
+    let _: fn(&Value) -> Result<String, Error> = to_string::<Value>;
+    let _: fn(&Value) -> Result<Value, Error> = to_value::<Value>;
+}
+
+#[test]
+fn test_prelude_loader() {
+    let _: fn(&str) -> Result<Vec<Yaml>, ScanError> = YamlLoader::load_from_str;
+    let _: fn(&str, &LoaderOptions) -> Result<Vec<Yaml>, Error> = YamlLoader::load_with_options;
+
+    let token = CancellationToken::new();
+    let options = LoaderOptions::new()
+        .empty_scalar_as(EmptyScalar::Null)
+        .schema(SchemaMode::Core)
+        .strict_flow(false)
+        .cancellation(token.clone())
+        .progress(|_bytes, _docs| {});
+    let docs = YamlLoader::load_with_options("a: 1", &options).unwrap();
+    assert_eq!(docs[0]["a"].as_i64(), Some(1));
+    assert!(!token.is_cancelled());
+
+    let config = DeserializerConfig::new()
+        .empty_scalar_as(EmptyScalar::EmptyString)
+        .schema(SchemaMode::Json);
+    let value: Value = from_str_with_config("a: yes", &config).unwrap();
+    assert_eq!(value, from_str::<Value>("a: 'yes'").unwrap());
+}
+
+#[test]
+fn test_prelude_values_and_emitter() {
+    let mut mapping = Mapping::new();
+    mapping.insert(Value::String("n".into()), Value::Number(Number::Integer(1)));
+    let sequence: Sequence = vec![Value::Mapping(mapping)];
+    let yaml = to_string(&sequence).unwrap();
+
+    let docs = YamlLoader::load_from_str(&yaml).unwrap();
+    let mut out = String::new();
+    let result: EmitResult = YamlEmitter::new(&mut out).dump(&docs[0]);
+    result.unwrap();
+    assert_eq!(out, yaml);
+    let _: Option<EmitError> = None;
+}
+
+#[test]
+fn test_prelude_errors_and_events() {
+    let err: ScanError = YamlLoader::load_from_str("[a").unwrap_err();
+    let _: Marker = err.mark;
+    let _: String = err.info;
+
+    struct Scalars(Vec<String>);
+    impl EventReceiver for Scalars {
+        fn on_event(&mut self, ev: Event) {
+            if let Event::Scalar(value, style, ..) = ev {
+                let _: TScalarStyle = style;
+                self.0.push(value);
+            }
+        }
+    }
+    fn parse_marked<R: MarkedEventReceiver>(s: &str, receiver: &mut R) {
+        YamlLoader::parse_events(s, receiver).unwrap();
+    }
+    let mut scalars = Scalars(Vec::new());
+    parse_marked("a: 'b'\nc: [d]\n", &mut scalars);
+    assert_eq!(scalars.0, ["a", "b", "c", "d"]);
+}
+
+#[cfg(feature = "semantic")]
+#[test]
+fn test_prelude_semantic_summary() {
+    let _: fn(&str) -> Result<ProcessingSummary, Error> = analyze_str;
+    let _: fn(&Yaml) -> ProcessingSummary = analyze_yaml;
+
+    let summary = analyze_str("a: &x 1\nb: *x\n---\nc: !t 2\n").unwrap();
+    let _: std::time::Duration = summary.processing_time;
+    assert_eq!(summary.documents_count, 2);
+    assert_eq!(summary.anchors_resolved, 1);
+    assert_eq!(summary.aliases_resolved, 1);
+    assert_eq!(summary.tags_resolved, 1);
+    assert_eq!(summary.warnings_count, 0);
+    assert_eq!(summary.cycles_detected, 0);
+}
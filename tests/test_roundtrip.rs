@@ -0,0 +1,219 @@
+//! Property tests for `parse(emit(x)) == x` over generated values.
+//!
+//! Values come from a seeded xorshift generator so failures reproduce; the
+//! seed and the emitted text are part of every failure message.
+
+use yyaml::{
+    Mapping, Number, RoundTripDivergence, Value, Yaml, YamlEmitter, YamlLoader, roundtrip_check,
+};
+use yyaml::value::{Tag, TaggedValue};
+
+const CASES: u64 = 500;
+
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+
+    fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[self.below(items.len() as u64) as usize]
+    }
+}
+
+/// Strings chosen to stress quoting: reserved words, number look-alikes,
+/// indicators and whitespace
+const STRINGS: &[&str] = &[
+    "plain", "two words", "", " ", "true", "False", "null", "~", "yes", "no", "123", "-7",
+    "1.5", "1e3", ".inf", ".nan", "0x1F", "0o17", "- item", "key: value", "#comment",
+    "a # b", "&anchor", "*alias", "!tag", "|", ">", "'single'", "\"double\"", "%directive",
+    "@reserved", "`tick`", "[flow]", "{map}", "a, b", "---", "...", "trailing ", " leading",
+    "tab\there", "line\nbreak", "unicode é ✓", "back\\slash", "colon:", "?", "-",
+    "bell\u{7}", "nel\u{85}", "line\u{2028}separator", "\u{FEFF}bom", "two\nlines\n",
+    "kept\n\n\n", "\nlead", " indented\nblock", "tab\tin\nblock", "a\n\n  b",
+];
+
+fn scalar(rng: &mut Rng) -> Value {
+    match rng.below(6) {
+        0 => Value::Null,
+        1 => Value::Bool(rng.below(2) == 0),
+        2 => Value::Number(Number::Integer(rng.next() as i64 >> rng.below(60))),
+        3 => {
+            let floats = [0.5, -2.25, 1e-7, 3.0e12, 100.0, -0.0];
+            Value::Number(Number::Float(floats[rng.below(floats.len() as u64) as usize]))
+        }
+        _ => Value::String(rng.pick(STRINGS).to_string()),
+    }
+}
+
+fn value(rng: &mut Rng, depth: u32) -> Value {
+    if depth == 0 {
+        return scalar(rng);
+    }
+    match rng.below(4) {
+        0 => Value::Sequence((0..1 + rng.below(4)).map(|_| value(rng, depth - 1)).collect()),
+        1 => {
+            let mut mapping = Mapping::new();
+            for _ in 0..1 + rng.below(4) {
+                let key = Value::String(rng.pick(STRINGS).to_string());
+                mapping.insert(key, value(rng, depth - 1));
+            }
+            Value::Mapping(mapping)
+        }
+        _ => scalar(rng),
+    }
+}
+
+#[test]
+fn test_generated_values_round_trip() {
+    for seed in 1..=CASES {
+        let mut rng = Rng(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+        let root = value(&mut rng, 3);
+        if let Err(divergence) = roundtrip_check(&root) {
+            panic!("seed {seed}: {divergence}");
+        }
+    }
+}
+
+#[test]
+fn test_scalar_strings_round_trip() {
+    for s in STRINGS {
+        let root = Value::Sequence(vec![Value::String((*s).to_string())]);
+        if let Err(divergence) = roundtrip_check(&root) {
+            panic!("{s:?}: {divergence}");
+        }
+    }
+}
+
+#[test]
+fn test_fragments_splice_back() {
+    for seed in 1..=CASES {
+        let mut rng = Rng(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+        let root = value(&mut rng, 3);
+        let fragment = YamlEmitter::emit_fragment(&root, 2).unwrap();
+        assert!(fragment.ends_with('\n') && !fragment.starts_with("---"), "seed {seed}");
+        let spliced = format!("outer:\n  inner:\n{fragment}  after: 1\n");
+        let parsed: Value = yyaml::from_str(&spliced)
+            .unwrap_or_else(|e| panic!("seed {seed}: {e}\n{spliced}"));
+        assert_eq!(parsed["outer"]["inner"], root, "seed {seed}\n{spliced}");
+    }
+}
+
+#[test]
+fn test_fragment_block_scalars() {
+    let value: Value =
+        yyaml::from_str("run:\n  - \"a\\n\\n  b\"\n  - \"kept\\n\\n\"\n  - \" lead\\nx\"\n").unwrap();
+    let fragment = YamlEmitter::emit_fragment(&value, 1).unwrap();
+    assert_eq!(
+        fragment,
+        "  run: \n    - |-\n      a\n\n        b\n    - |+\n      kept\n\n    - \" lead\\nx\"\n"
+    );
+    assert_eq!(YamlEmitter::emit_fragment(&Value::String("x".into()), 3).unwrap(), "      x\n");
+}
+
+#[test]
+fn test_folded_strings_round_trip() {
+    for width in [1, 8, 40] {
+        for s in STRINGS {
+            let long = format!("{s} {s} and then a few more words to wrap\n\n{s}");
+            for text in [(*s).to_string(), long] {
+                let doc = Yaml::Array(vec![Yaml::String(text.clone())]);
+                let mut out = String::new();
+                let mut emitter = YamlEmitter::new(&mut out);
+                emitter.fold_width = Some(width);
+                emitter.dump(&doc).unwrap();
+                let parsed = YamlLoader::load_from_str(&out)
+                    .unwrap_or_else(|e| panic!("width {width}: {e}\n{out}"));
+                assert_eq!(parsed[0][0].as_str(), Some(text.as_str()), "width {width}\n{out}");
+            }
+        }
+    }
+}
+
+type Configure = fn(&mut YamlEmitter);
+
+/// Emitter settings the idempotency harness runs under
+const CONFIGS: &[(&str, Configure)] = &[
+    ("default", |_| {}),
+    ("fold 8", |e| e.fold_width = Some(8)),
+    ("fold 40", |e| e.fold_width = Some(40)),
+    ("indent 4", |e| e.best_indent = 4),
+    ("indent 3, fold 8", |e| {
+        e.best_indent = 3;
+        e.fold_width = Some(8);
+    }),
+    ("not compact", |e| e.compact = false),
+    ("null word", |e| e.null_word = "null"),
+    ("dedup", |e| e.dedup_threshold = Some(2)),
+];
+
+fn emit_with(doc: &Yaml, configure: Configure) -> String {
+    let mut out = String::new();
+    let mut emitter = YamlEmitter::new(&mut out);
+    configure(&mut emitter);
+    emitter.dump(doc).unwrap();
+    out
+}
+
+#[test]
+fn test_emit_is_idempotent() {
+    // Once written, a document reads back to text the emitter writes again
+    // byte for byte
+    for (name, configure) in CONFIGS {
+        for seed in 1..=CASES {
+            let mut rng = Rng(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+            let root = value(&mut rng, 3);
+            let doc = &YamlLoader::load_from_str(&yyaml::to_string(&root).unwrap()).unwrap()[0];
+            let once = emit_with(doc, *configure);
+            let reparsed = YamlLoader::load_from_str(&once)
+                .unwrap_or_else(|e| panic!("{name}, seed {seed}: {e}\n{once}"));
+            assert_eq!(emit_with(&reparsed[0], *configure), once, "{name}, seed {seed}");
+        }
+    }
+}
+
+#[test]
+fn test_emit_keeps_final_block_break() {
+    let doc = Yaml::Array(vec![Yaml::String("two\nlines\n".into())]);
+    let mut out = String::new();
+    let mut emitter = YamlEmitter::new(&mut out);
+    emitter.fold_width = Some(40);
+    emitter.best_indent = 4;
+    emitter.dump(&doc).unwrap();
+    assert_eq!(out, "---\n-   >\n    two\n\n    lines\n");
+    assert_eq!(YamlLoader::load_from_str(&out).unwrap()[0], doc);
+}
+
+#[test]
+fn test_divergence_reports_path() {
+    // NaN is emitted as `.nan` and parses back, but as a float it never
+    // equals itself; the checker treats it as the same value
+    let mut mapping = Mapping::new();
+    mapping.insert(Value::String("x".into()), Value::Number(Number::Float(f64::NAN)));
+    assert!(roundtrip_check(&Value::Mapping(mapping)).is_ok());
+
+    let nested: Value = yyaml::from_str("a: {b: [1, 2]}").unwrap();
+    assert!(roundtrip_check(&nested).is_ok());
+
+    // A node carries one tag, so a tag on a tagged value has no spelling
+    let mut value: Value = yyaml::from_str("a: {b: [1, 2]}").unwrap();
+    let inner = TaggedValue::new(Tag::new("!inner"), Value::Bool(true));
+    let outer = TaggedValue::new(Tag::new("!outer"), Value::Tagged(Box::new(inner)));
+    *value.pointer_mut("/a/b/1").unwrap() = Value::Tagged(Box::new(outer));
+    let divergence = roundtrip_check(&value).unwrap_err();
+    let RoundTripDivergence::Mismatch { path, expected, .. } = &divergence else {
+        panic!("expected a mismatch, got {divergence}");
+    };
+    assert_eq!(path, "/a/b/1");
+    assert_eq!(expected.as_ref(), Some(&value["a"]["b"][1]));
+    assert!(divergence.to_string().starts_with("round trip diverged at `/a/b/1`"));
+}
+
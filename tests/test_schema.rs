@@ -0,0 +1,56 @@
+use yyaml::Value;
+use yyaml::schema::{SchemaType, infer};
+
+fn samples(docs: &[&str]) -> Vec<Value> {
+    docs.iter().map(|doc| yyaml::from_str(doc).unwrap()).collect()
+}
+
+#[test]
+fn test_infer_types_and_optionality() {
+    let schema = infer(&samples(&[
+        "name: web\nports: [80, 443]\nlimits: {cpu: 0.5}\ntier: frontend\n",
+        "name: api\nports: [8080]\nlimits: ~\ntier: backend\n",
+        "name: db\nports: []\ntier: backend\nreplicas: !count 3\n",
+    ]));
+
+    let paths: Vec<_> = schema
+        .paths()
+        .into_iter()
+        .map(|(path, types)| (path, types.iter().map(|t| t.as_str()).collect::<Vec<_>>()))
+        .collect();
+    assert_eq!(
+        paths,
+        vec![
+            (String::new(), vec!["mapping"]),
+            ("/limits".into(), vec!["null", "mapping"]),
+            ("/limits/cpu".into(), vec!["float"]),
+            ("/name".into(), vec!["string"]),
+            ("/ports".into(), vec!["sequence"]),
+            ("/ports/*".into(), vec!["integer"]),
+            ("/replicas".into(), vec!["integer"]),
+            ("/tier".into(), vec!["string"]),
+        ]
+    );
+    assert!(schema.properties["name"].required);
+    assert!(!schema.properties["limits"].required);
+    assert!(!schema.properties["replicas"].required);
+    // Three distinct names are not an enum; two tiers with a repeat are
+    assert!(schema.properties["name"].schema.enum_values.is_empty());
+    let tiers: Vec<_> = schema.properties["tier"].schema.enum_values.iter().collect();
+    assert_eq!(tiers, ["backend", "frontend"]);
+    assert!(schema.at("/limits/cpu").unwrap().types.contains(&SchemaType::Float));
+    assert!(schema.at("/ports/0").is_none());
+}
+
+#[test]
+fn test_schema_as_yaml() {
+    let schema = infer(&samples(&["[{id: 1}, {id: x}]", "- {id: 2, extra: true}\n"]));
+    let value: Value = yyaml::from_str(&yyaml::to_string(&schema).unwrap()).unwrap();
+    let expected: Value = yyaml::from_str(
+        "type: sequence\nitems:\n  type: mapping\n  properties:\n    \
+         id: {type: [integer, string]}\n    extra: {type: boolean, optional: true}\n",
+    )
+    .unwrap();
+    assert_eq!(value, expected);
+    assert_eq!(infer(&[]).to_value(), Value::Mapping(Default::default()));
+}
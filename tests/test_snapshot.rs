@@ -0,0 +1,61 @@
+use serde::Serialize;
+use yyaml::Value;
+use yyaml::snapshot::{SnapshotChange, render_changes, structural_diff};
+
+#[derive(Serialize)]
+struct Deployment {
+    name: &'static str,
+    replicas: u32,
+    ports: Vec<u16>,
+}
+
+#[test]
+fn test_snapshot_written_then_compared() {
+    let dir = std::env::temp_dir().join(format!("yyaml-snapshot-{}", std::process::id()));
+    let path = dir.join("nested/deployment.snap");
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let deployment = Deployment { name: "web", replicas: 2, ports: vec![80, 443] };
+    yyaml::assert_yaml_snapshot!(deployment, &path);
+    let stored = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(stored, "---\nname: web\nports: \n  - 80\n  - 443\nreplicas: 2\n");
+
+    // Formatting is free to differ
+    std::fs::write(&path, "{replicas: 2, ports: [80, 443], name: 'web'}\n").unwrap();
+    yyaml::assert_yaml_snapshot!(deployment, &path);
+
+    let changed = Deployment { name: "web", replicas: 3, ports: vec![80] };
+    let failure = std::panic::catch_unwind(|| yyaml::assert_yaml_snapshot!(changed, &path));
+    let message = failure.unwrap_err();
+    let message = message.downcast_ref::<String>().unwrap();
+    assert!(message.contains("2 changed"), "{message}");
+    assert!(message.contains("- /ports/1: 443"), "{message}");
+    assert!(message.contains("+ /replicas: 3"), "{message}");
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_structural_diff_rendering() {
+    let old: Value = yyaml::from_str("a: {b: [1, x]}\nold: true\n").unwrap();
+    let new: Value = yyaml::from_str("a: {b: [1, y]}\nnew: ~\n").unwrap();
+    let changes = structural_diff(&old, &new);
+    assert_eq!(
+        changes,
+        vec![
+            SnapshotChange {
+                path: "/a/b/1".into(),
+                expected: Some(Value::String("x".into())),
+                actual: Some(Value::String("y".into())),
+            },
+            SnapshotChange { path: "/new".into(), expected: None, actual: Some(Value::Null) },
+            SnapshotChange { path: "/old".into(), expected: Some(Value::Bool(true)), actual: None },
+        ]
+    );
+    assert_eq!(
+        render_changes("s.snap", &changes[..2], false),
+        "snapshot `s.snap` does not match (2 changed, set YYAML_UPDATE_SNAPSHOTS=1 to accept):\n\
+         - /a/b/1: \"x\"\n+ /a/b/1: \"y\"\n+ /new: null"
+    );
+    assert!(render_changes("s", &changes[2..], true).ends_with("\x1b[31m- /old: true\x1b[0m"));
+    assert!(structural_diff(&new, &new).is_empty());
+}
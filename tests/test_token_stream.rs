@@ -0,0 +1,43 @@
+use yyaml::TokenType;
+use yyaml::scanner::{ScannedTokens, TokenStream};
+
+fn kinds(tokens: ScannedTokens<'_>) -> Vec<TokenType> {
+    tokens.map(|token| token.unwrap().1).collect()
+}
+
+#[test]
+fn test_lookahead_and_pushback() {
+    let mut tokens = ScannedTokens::from_str("- a\n- b\n");
+    assert!(matches!(tokens.peek_n(3).unwrap().map(|t| &t.1), Some(TokenType::BlockEntry)));
+    assert!(tokens.peek_n(100).unwrap().is_none());
+
+    let first = tokens.next().unwrap().unwrap();
+    let second = tokens.next().unwrap().unwrap();
+    tokens.push_back(second);
+    tokens.push_back(first);
+    assert!(matches!(tokens.peek().unwrap().map(|t| &t.1), Some(TokenType::StreamStart(_))));
+
+    let all = kinds(tokens);
+    assert_eq!(all.len(), 6);
+    assert_eq!(all.last(), Some(&TokenType::StreamEnd));
+}
+
+#[test]
+fn test_stream_ends_after_an_error() {
+    let mut tokens = ScannedTokens::from_str("a: \"open\n");
+    let results: Vec<_> = tokens.by_ref().collect();
+    assert!(results.last().unwrap().is_err());
+    assert!(tokens.is_empty().unwrap());
+    assert!(tokens.next().is_none());
+}
+
+#[test]
+fn test_stream_over_a_slice() {
+    let scanned: Vec<_> = ScannedTokens::from_str("[1]").map(Result::unwrap).collect();
+    let mut tokens = TokenStream::new(&scanned);
+    assert_eq!(tokens.len(), scanned.len());
+    assert!(matches!(tokens.peek_n(2).map(|t| &t.1), Some(TokenType::Scalar(..))));
+    assert!(matches!(tokens.peek().map(|t| &t.1), Some(TokenType::StreamStart(_))));
+    let kinds: Vec<_> = tokens.map(|t| t.1.clone()).collect();
+    assert_eq!(kinds, scanned.iter().map(|t| t.1.clone()).collect::<Vec<_>>());
+}